@@ -1188,6 +1188,10 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FlashIo
             #[cfg(feature = "embedded-storage")]
             StorageIoError::EmbeddedStorage(_) => unreachable_storage_format_error(),
             StorageIoError::FileBacking(_) => unreachable_storage_format_error(),
+            StorageIoError::ReadOnly(_) => unreachable_storage_format_error(),
+            StorageIoError::BadRegion(region_index) => {
+                StorageFormatError::from(MockFormatError::from(MockError::BadRegion(region_index)))
+            }
         })?;
 
         for region_index in 0..region_count {
@@ -1199,6 +1203,10 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FlashIo
                     #[cfg(feature = "embedded-storage")]
                     StorageIoError::EmbeddedStorage(_) => unreachable_storage_format_error(),
                     StorageIoError::FileBacking(_) => unreachable_storage_format_error(),
+                    StorageIoError::ReadOnly(_) => unreachable_storage_format_error(),
+                    StorageIoError::BadRegion(region_index) => StorageFormatError::from(
+                        MockFormatError::from(MockError::BadRegion(region_index)),
+                    ),
                 })?;
         }
 
@@ -1236,6 +1244,10 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FlashIo
                 #[cfg(feature = "embedded-storage")]
                 StorageIoError::EmbeddedStorage(_) => unreachable_storage_format_error(),
                 StorageIoError::FileBacking(_) => unreachable_storage_format_error(),
+                StorageIoError::ReadOnly(_) => unreachable_storage_format_error(),
+                StorageIoError::BadRegion(region_index) => StorageFormatError::from(
+                    MockFormatError::from(MockError::BadRegion(region_index)),
+                ),
             })?;
 
         let mut free_space_region = [self.erased_byte; REGION_SIZE];
@@ -1281,6 +1293,10 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FlashIo
                     #[cfg(feature = "embedded-storage")]
                     StorageIoError::EmbeddedStorage(_) => unreachable_storage_format_error(),
                     StorageIoError::FileBacking(_) => unreachable_storage_format_error(),
+                    StorageIoError::ReadOnly(_) => unreachable_storage_format_error(),
+                    StorageIoError::BadRegion(region_index) => StorageFormatError::from(
+                        MockFormatError::from(MockError::BadRegion(region_index)),
+                    ),
                 })?;
         }
 
@@ -1289,6 +1305,10 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FlashIo
             #[cfg(feature = "embedded-storage")]
             StorageIoError::EmbeddedStorage(_) => unreachable_storage_format_error(),
             StorageIoError::FileBacking(_) => unreachable_storage_format_error(),
+            StorageIoError::ReadOnly(_) => unreachable_storage_format_error(),
+            StorageIoError::BadRegion(region_index) => {
+                StorageFormatError::from(MockFormatError::from(MockError::BadRegion(region_index)))
+            }
         })?;
         Ok(metadata)
     }
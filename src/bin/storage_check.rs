@@ -0,0 +1,287 @@
+//! Read-only consistency check for a `FileBacking` database file.
+//!
+//! Opening a store can run recovery that appends WAL records or erases
+//! regions. The check maps the file with `FileBacking::open_read_only` and
+//! opens it through `ReadOnlyFlash`, which keeps every such write in a RAM
+//! overlay, so the file on disk is never modified and need not be writable.
+//! A store that fails to open is still checked region by region.
+
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use borromean::{
+    select_metadata_copy, CheckFinding, CheckSummary, FileBacking, FileBackingOptions,
+    FileBackingScratch, ReadOnlyFlash, Storage, StorageMemory, StorageMetadata,
+};
+
+const MAX_COLLECTIONS: usize = 64;
+const OVERLAY_REGIONS: usize = 8;
+const CHECK_THREAD_STACK_BYTES: usize = 64 * 1024 * 1024;
+const USAGE: &str = "usage: storage_check [--erased-byte <hex>] <path> [<region-size>]";
+
+/// Region sizes and counts `check_file` instantiates the check for.
+const REGION_SIZES: [usize; 4] = [4096, 8192, 16_384, 65_536];
+const REGION_COUNTS: [u32; 4] = [64, 256, 1024, 4096];
+
+const EXIT_CLEAN: u8 = 0;
+const EXIT_FINDINGS: u8 = 1;
+const EXIT_ERROR: u8 = 2;
+
+type CheckResult<T> = Result<T, String>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CheckArgs {
+    path: PathBuf,
+    region_size: Option<usize>,
+    erased_byte: u8,
+}
+
+/// Store geometry the check runs with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Geometry {
+    region_size: usize,
+    region_count: u32,
+    erased_byte: u8,
+}
+
+/// Outcome of one check.
+#[derive(Debug)]
+struct CheckReport {
+    summary: CheckSummary,
+    /// Why the store failed to open, when only its regions were checked.
+    open_error: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let status = std::thread::Builder::new()
+        .name("storage-check".to_owned())
+        .stack_size(CHECK_THREAD_STACK_BYTES)
+        .spawn(move || run(&args, &mut std::io::stdout(), &mut std::io::stderr()))
+        .ok()
+        .and_then(|thread| thread.join().ok())
+        .unwrap_or(EXIT_ERROR);
+    ExitCode::from(status)
+}
+
+/// Parses `args`, checks the named file, and returns the exit status.
+fn run(args: &[String], out: &mut dyn Write, err: &mut dyn Write) -> u8 {
+    let args = match parse_args(args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            let _ = writeln!(out, "{USAGE}");
+            return EXIT_CLEAN;
+        }
+        Err(error) => {
+            let _ = writeln!(err, "[storage-check] {error}");
+            return EXIT_ERROR;
+        }
+    };
+    match check_file(&args, out) {
+        Ok(report) => {
+            let summary = report.summary;
+            let _ = writeln!(
+                out,
+                "regions_checked={} wal_records={} findings={}",
+                summary.regions_checked, summary.wal_records, summary.findings
+            );
+            if let Some(error) = &report.open_error {
+                let _ = writeln!(err, "[storage-check] failed to open store: {error}");
+            }
+            if !summary.is_clean() {
+                EXIT_FINDINGS
+            } else if report.open_error.is_some() {
+                EXIT_ERROR
+            } else {
+                EXIT_CLEAN
+            }
+        }
+        Err(error) => {
+            let _ = writeln!(err, "[storage-check] {error}");
+            EXIT_ERROR
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> CheckResult<Option<CheckArgs>> {
+    let mut erased_byte = 0xff;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--erased-byte" => {
+                let value = args.next().ok_or_else(|| USAGE.to_owned())?;
+                let digits = value.trim_start_matches("0x");
+                erased_byte = u8::from_str_radix(digits, 16)
+                    .map_err(|_| format!("invalid erased byte {value:?}"))?;
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let (path, region_size) = match positional.as_slice() {
+        [path] => (path, None),
+        [path, region_size] => (
+            path,
+            Some(
+                region_size
+                    .parse()
+                    .map_err(|_| format!("invalid region size {region_size:?}"))?,
+            ),
+        ),
+        _ => return Err(USAGE.to_owned()),
+    };
+    Ok(Some(CheckArgs {
+        path: PathBuf::from(path),
+        region_size,
+        erased_byte,
+    }))
+}
+
+/// Reads the store geometry and runs the check instantiated for it.
+fn check_file(args: &CheckArgs, out: &mut dyn Write) -> CheckResult<CheckReport> {
+    let geometry = read_geometry(args)?;
+
+    macro_rules! dispatch_count {
+        ($region_size:literal) => {
+            match geometry.region_count {
+                64 => check_geometry::<$region_size, 64>(args, geometry, out),
+                256 => check_geometry::<$region_size, 256>(args, geometry, out),
+                1024 => check_geometry::<$region_size, 1024>(args, geometry, out),
+                4096 => check_geometry::<$region_size, 4096>(args, geometry, out),
+                _ => Err(unsupported_geometry(geometry)),
+            }
+        };
+    }
+
+    match geometry.region_size {
+        4096 => dispatch_count!(4096),
+        8192 => dispatch_count!(8192),
+        16_384 => dispatch_count!(16_384),
+        65_536 => dispatch_count!(65_536),
+        _ => Err(unsupported_geometry(geometry)),
+    }
+}
+
+fn unsupported_geometry(geometry: Geometry) -> String {
+    format!(
+        "unsupported geometry of {} regions of {} bytes; storage_check is built for region \
+         sizes {REGION_SIZES:?} and region counts {REGION_COUNTS:?}",
+        geometry.region_count, geometry.region_size
+    )
+}
+
+/// Takes the geometry and erased byte from the stored metadata. When no
+/// metadata copy decodes, the region size must come from the command line
+/// and the region count from the file length.
+fn read_geometry(args: &CheckArgs) -> CheckResult<Geometry> {
+    let file_len = std::fs::metadata(&args.path)
+        .map_err(|error| format!("failed to stat {}: {error}", args.path.display()))?
+        .len();
+    for region_size in args.region_size.into_iter().chain(REGION_SIZES) {
+        let Some(metadata) = read_metadata(args, region_size, file_len)? else {
+            continue;
+        };
+        let stored_len = u64::from(metadata.region_count)
+            .checked_add(1)
+            .and_then(|regions| regions.checked_mul(u64::from(metadata.region_size)));
+        if stored_len != Some(file_len) {
+            return Err(format!(
+                "file length {file_len} does not hold the stored geometry of {} regions of \
+                 {region_size} bytes",
+                metadata.region_count
+            ));
+        }
+        if let Some(expected) = args.region_size.filter(|expected| *expected != region_size) {
+            return Err(format!(
+                "region size {expected} does not match the stored region size {region_size}"
+            ));
+        }
+        return Ok(Geometry {
+            region_size,
+            region_count: metadata.region_count,
+            erased_byte: metadata.erased_byte,
+        });
+    }
+
+    let region_size = args
+        .region_size
+        .ok_or("storage metadata is unreadable; pass the region size")?;
+    let region_len = u64::try_from(region_size).map_err(|_| "region size overflowed")?;
+    if region_len == 0 || file_len % region_len != 0 || file_len < region_len {
+        return Err(format!(
+            "file length {file_len} is not a whole number of {region_size}-byte regions"
+        ));
+    }
+    let region_count = u32::try_from(file_len / region_len - 1)
+        .map_err(|_| format!("file length {file_len} holds too many regions"))?;
+    Ok(Geometry {
+        region_size,
+        region_count,
+        erased_byte: args.erased_byte,
+    })
+}
+
+/// Decodes the metadata region as if regions were `region_size` bytes.
+fn read_metadata(
+    args: &CheckArgs,
+    region_size: usize,
+    file_len: u64,
+) -> CheckResult<Option<StorageMetadata>> {
+    if u64::try_from(region_size).map_or(true, |region_len| region_len > file_len) {
+        return Ok(None);
+    }
+    let mut region = vec![0u8; region_size];
+    File::open(&args.path)
+        .and_then(|mut file| file.read_exact(&mut region))
+        .map_err(|error| format!("failed to read {}: {error}", args.path.display()))?;
+    Ok(select_metadata_copy(&region)
+        .ok()
+        .map(|selection| selection.copy.metadata)
+        .filter(|metadata| usize::try_from(metadata.region_size) == Ok(region_size)))
+}
+
+fn check_geometry<const REGION_SIZE: usize, const REGION_COUNT: usize>(
+    args: &CheckArgs,
+    geometry: Geometry,
+    out: &mut dyn Write,
+) -> CheckResult<CheckReport> {
+    let mut backing = FileBacking::<REGION_SIZE, REGION_COUNT>::open_read_only(
+        &args.path,
+        FileBackingOptions::new(geometry.erased_byte),
+        &mut FileBackingScratch::new(),
+    )
+    .map_err(|error| format!("failed to open {}: {error:?}", args.path.display()))?;
+    let mut flash = ReadOnlyFlash::<_, REGION_SIZE, OVERLAY_REGIONS>::new(&mut backing);
+    let mut memory = StorageMemory::<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>::new();
+    let mut write_error = None;
+    let mut sink = |finding: CheckFinding| {
+        if let Err(error) = writeln!(out, "{finding:?}") {
+            write_error.get_or_insert(error);
+        }
+    };
+    let opened = Storage::open_read_only(&mut flash, &mut memory)
+        .map(|mut storage| storage.check(&mut sink));
+    let report = match opened {
+        Ok(summary) => CheckReport {
+            summary: summary.map_err(|error| format!("check failed: {error:?}"))?,
+            open_error: None,
+        },
+        Err(open_error) => CheckReport {
+            summary: Storage::check_regions(&mut flash, &mut memory, &mut sink)
+                .map_err(|error| format!("region check failed: {error:?}"))?,
+            open_error: Some(format!("{open_error:?}")),
+        },
+    };
+    match write_error {
+        Some(error) => Err(format!("failed to print findings: {error}")),
+        None => Ok(report),
+    }
+}
+
+#[cfg(test)]
+#[path = "storage_check/tests.rs"]
+mod tests;
@@ -0,0 +1,216 @@
+use super::*;
+
+use borromean::{Cell, CollectionId, LsmMap, LsmMapMemory, MapFrontierMemory, StorageFormatConfig};
+
+const REGION_SIZE: usize = 4096;
+const REGION_COUNT: usize = 64;
+
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        let mut path = env::temp_dir();
+        path.push(format!(
+            "borromean-storage-check-{name}-{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        Self { path }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Formats a store with a cell and a flushed map, optionally leaving one
+/// committed region no collection reaches, and returns the WAL head region.
+fn write_store(path: &std::path::Path, leak_region: bool) -> u32 {
+    write_store_with::<REGION_COUNT>(path, leak_region)
+}
+
+fn write_store_with<const REGION_COUNT: usize>(path: &std::path::Path, leak_region: bool) -> u32 {
+    let mut backing = FileBacking::<REGION_SIZE, REGION_COUNT>::create_new(
+        path,
+        FileBackingOptions::new(0xff),
+        &mut FileBackingScratch::new(),
+    )
+    .unwrap();
+    let mut memory = StorageMemory::<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>::new();
+    let mut storage = Storage::format(
+        &mut backing,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        &mut memory,
+    )
+    .unwrap();
+    let mut cell = Cell::new(&mut storage, 1u32).unwrap();
+    cell.set(&mut storage, 2u32).unwrap();
+    let mut map_memory = LsmMapMemory::<u16, u16, 8>::new();
+    let mut map = LsmMap::<u16, u16, 8>::new(&mut storage, &mut map_memory).unwrap();
+    for key in 0..32u16 {
+        map.set(&mut storage, key, key).unwrap();
+    }
+    let mut buffer = vec![0u8; REGION_SIZE];
+    let mut frontier_memory = MapFrontierMemory::<u16, 8>::new();
+    let mut frontier = storage
+        .open_map::<u16, u16, 8>(map.collection_id(), &mut buffer, &mut frontier_memory)
+        .unwrap();
+    storage.flush_map(&mut frontier).unwrap();
+    if leak_region {
+        let region = storage.reserve_next_region().unwrap();
+        storage
+            .write_committed_region(region, CollectionId::new(0x7777), 1, &[0u8; 16])
+            .unwrap();
+    }
+    storage.wal_head()
+}
+
+fn run_check(path: &std::path::Path) -> (u8, String) {
+    let (status, out, _) = run_args(&[path.display().to_string(), REGION_SIZE.to_string()]);
+    (status, out)
+}
+
+fn run_args(args: &[String]) -> (u8, String, String) {
+    let mut out = Vec::new();
+    let mut err = Vec::new();
+    let status = run(args, &mut out, &mut err);
+    (
+        status,
+        String::from_utf8(out).unwrap(),
+        String::from_utf8(err).unwrap(),
+    )
+}
+
+/// Flips one byte of the region header at `region_index`.
+fn corrupt_header(path: &std::path::Path, region_index: u32) {
+    let mut bytes = std::fs::read(path).unwrap();
+    bytes[(region_index as usize + 1) * REGION_SIZE + 4] ^= 0x01;
+    std::fs::write(path, bytes).unwrap();
+}
+
+//= spec/check.md#command-line-check
+//= type=test
+//# `RING-CHECK-008` `storage_check` MUST leave the database file byte for
+//# byte unchanged, print each finding, and exit with status 0 when the store
+//# is clean, 1 when it reports findings, and 2 when the file cannot be
+//# opened.
+#[test]
+fn requirement_storage_check_cli_reports_without_writing() {
+    let clean = TempFile::new("clean");
+    write_store(&clean.path, false);
+    let before = std::fs::read(&clean.path).unwrap();
+    let (status, output) = run_check(&clean.path);
+    assert_eq!(status, EXIT_CLEAN, "{output}");
+    assert!(output.ends_with("findings=0\n"), "{output}");
+    assert_eq!(std::fs::read(&clean.path).unwrap(), before);
+
+    let leaked = TempFile::new("leaked");
+    write_store(&leaked.path, true);
+    let before = std::fs::read(&leaked.path).unwrap();
+    let (status, output) = run_check(&leaked.path);
+    assert_eq!(status, EXIT_FINDINGS, "{output}");
+    assert!(output.starts_with("LeakedRegion"), "{output}");
+    assert!(output.ends_with("findings=1\n"), "{output}");
+    assert_eq!(std::fs::read(&leaked.path).unwrap(), before);
+
+    let missing = TempFile::new("missing");
+    assert_eq!(run_check(&missing.path).0, EXIT_ERROR);
+    assert_eq!(run(&[], &mut Vec::new(), &mut Vec::new()), EXIT_ERROR);
+}
+
+//= spec/check.md#command-line-check
+//= type=test
+//# `RING-CHECK-010` When the store fails to open, `storage_check` MUST print
+//# the findings of `Storage::check_regions` and exit with status 1 if there
+//# are any, or 2 if there are none.
+#[test]
+fn requirement_storage_check_cli_checks_regions_when_open_fails() {
+    let file = TempFile::new("unopenable");
+    let wal_head = write_store(&file.path, false);
+    corrupt_header(&file.path, wal_head);
+    let before = std::fs::read(&file.path).unwrap();
+
+    let (status, output, errors) = run_args(&[file.path.display().to_string()]);
+    assert_eq!(status, EXIT_FINDINGS, "{output}{errors}");
+    assert_eq!(
+        output.lines().next(),
+        Some(format!("InvalidHeader {{ region_index: {wal_head} }}").as_str()),
+        "{output}"
+    );
+    assert!(output.ends_with("findings=1\n"), "{output}");
+    assert!(errors.contains("failed to open store"), "{errors}");
+    assert_eq!(std::fs::read(&file.path).unwrap(), before);
+
+    let mut before = before;
+    before[..REGION_SIZE].fill(0x5a);
+    std::fs::write(&file.path, &before).unwrap();
+    let (status, output) = run_check(&file.path);
+    assert_eq!(status, EXIT_FINDINGS, "{output}");
+    assert!(output.starts_with("MetadataMismatch\n"), "{output}");
+    assert!(output.ends_with("findings=2\n"), "{output}");
+    assert_eq!(std::fs::read(&file.path).unwrap(), before);
+}
+
+//= spec/check.md#command-line-check
+//= type=test
+//# `RING-CHECK-011` `storage_check` MUST take the region size, region count,
+//# and erased byte from the stored metadata when a copy decodes, and MUST
+//# reject a geometry it is not built for with an error naming that geometry
+//# and the supported sizes and counts.
+#[test]
+fn requirement_storage_check_cli_reads_geometry_from_metadata() {
+    let file = TempFile::new("derived");
+    write_store(&file.path, false);
+    let (status, output, _) = run_args(&[
+        "--erased-byte".to_owned(),
+        "00".to_owned(),
+        file.path.display().to_string(),
+    ]);
+    assert_eq!(status, EXIT_CLEAN, "{output}");
+    assert!(
+        output.starts_with(&format!("regions_checked={REGION_COUNT} ")),
+        "{output}"
+    );
+
+    let (status, _, errors) = run_args(&[file.path.display().to_string(), "8192".to_owned()]);
+    assert_eq!(status, EXIT_ERROR);
+    assert!(
+        errors.contains("region size 8192 does not match the stored region size 4096"),
+        "{errors}"
+    );
+
+    let unsupported = TempFile::new("unsupported");
+    write_store_with::<32>(&unsupported.path, false);
+    let (status, _, errors) = run_args(&[unsupported.path.display().to_string()]);
+    assert_eq!(status, EXIT_ERROR);
+    assert!(
+        errors.contains("unsupported geometry of 32 regions of 4096 bytes"),
+        "{errors}"
+    );
+    assert!(errors.contains("[4096, 8192, 16384, 65536]"), "{errors}");
+}
+
+//= spec/check.md#command-line-check
+//= type=test
+//# `RING-CHECK-012` `storage_check` MUST check a database file it has no
+//# write permission for.
+#[test]
+fn requirement_storage_check_cli_checks_read_only_files() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let file = TempFile::new("read-only");
+    write_store(&file.path, true);
+    std::fs::set_permissions(&file.path, std::fs::Permissions::from_mode(0o444)).unwrap();
+    let before = std::fs::read(&file.path).unwrap();
+
+    let (status, output) = run_check(&file.path);
+    assert_eq!(status, EXIT_FINDINGS, "{output}");
+    assert!(output.starts_with("LeakedRegion"), "{output}");
+    assert_eq!(std::fs::read(&file.path).unwrap(), before);
+    let mode = std::fs::metadata(&file.path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o444);
+}
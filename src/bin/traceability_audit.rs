@@ -215,7 +215,79 @@ fn spec_format_policy(relative_spec_path: &str) -> Option<SpecFormatPolicy> {
             prefixes: &["RING-OBJECT-"],
             allow_empty: false,
         },
-        "spec/channel.md" | "spec/mock.md" => SpecFormatPolicy {
+        "spec/collection-types.md" => SpecFormatPolicy {
+            prefixes: &["RING-PLUGIN-"],
+            allow_empty: false,
+        },
+        "spec/catalog.md" => SpecFormatPolicy {
+            prefixes: &["RING-CATALOG-"],
+            allow_empty: false,
+        },
+        "spec/cell.md" => SpecFormatPolicy {
+            prefixes: &["RING-CELL-"],
+            allow_empty: false,
+        },
+        "spec/channel.md" => SpecFormatPolicy {
+            prefixes: &["RING-IMPL-REGRESSION-", "RING-CHANNEL-"],
+            allow_empty: false,
+        },
+        "spec/queue.md" => SpecFormatPolicy {
+            prefixes: &["RING-QUEUE-"],
+            allow_empty: false,
+        },
+        "spec/ring-log.md" => SpecFormatPolicy {
+            prefixes: &["RING-LOG-"],
+            allow_empty: false,
+        },
+        "spec/sequence.md" => SpecFormatPolicy {
+            prefixes: &["RING-SEQ-"],
+            allow_empty: false,
+        },
+        "spec/set.md" => SpecFormatPolicy {
+            prefixes: &["RING-SET-"],
+            allow_empty: false,
+        },
+        "spec/settings.md" => SpecFormatPolicy {
+            prefixes: &["RING-SETTINGS-"],
+            allow_empty: false,
+        },
+        "spec/time-series.md" => SpecFormatPolicy {
+            prefixes: &["RING-TS-"],
+            allow_empty: false,
+        },
+        "spec/usage.md" => SpecFormatPolicy {
+            prefixes: &["RING-USAGE-"],
+            allow_empty: false,
+        },
+        "spec/wear.md" => SpecFormatPolicy {
+            prefixes: &["RING-WEAR-"],
+            allow_empty: false,
+        },
+        "spec/migration.md" => SpecFormatPolicy {
+            prefixes: &["RING-MIGRATE-"],
+            allow_empty: false,
+        },
+        "spec/check.md" => SpecFormatPolicy {
+            prefixes: &["RING-CHECK-"],
+            allow_empty: false,
+        },
+        "spec/scrub.md" => SpecFormatPolicy {
+            prefixes: &["RING-SCRUB-"],
+            allow_empty: false,
+        },
+        "spec/salvage.md" => SpecFormatPolicy {
+            prefixes: &["RING-SALVAGE-"],
+            allow_empty: false,
+        },
+        "spec/read-only.md" => SpecFormatPolicy {
+            prefixes: &["RING-READONLY-"],
+            allow_empty: false,
+        },
+        "spec/recovery-report.md" => SpecFormatPolicy {
+            prefixes: &["RING-RECOVERY-"],
+            allow_empty: false,
+        },
+        "spec/mock.md" => SpecFormatPolicy {
             prefixes: &["RING-IMPL-REGRESSION-"],
             allow_empty: false,
         },
@@ -0,0 +1,582 @@
+//! Named collection catalog kept by storage.
+//!
+//! The catalog is a storage-private collection of type
+//! [`CollectionType::CATALOG_CODE`] that maps short names to collection ids
+//! and types. It is created with the first named collection, and every change
+//! records the whole catalog as one WAL snapshot in the same inline
+//! transaction as the `new_collection` or `drop_collection` record it
+//! describes, so a name never outlives or precedes its collection.
+
+use core::mem::size_of;
+
+use heapless::{String, Vec};
+
+use crate::flash_io::FlashIo;
+use crate::mode::{
+    CollectionCreateMode, CollectionDropMode, CollectionUpdateMode, ReadMode, StorageMode,
+};
+use crate::startup::StartupCollectionBasis;
+use crate::storage::{StorageRuntimeError, StorageVisitError};
+use crate::wal_record::WalRecord;
+use crate::{CollectionId, CollectionType, Storage};
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum length, in bytes, of a catalog name.
+pub const MAX_COLLECTION_NAME_LEN: usize = 16;
+
+/// Name a collection is registered under in the catalog.
+pub type CollectionName = String<MAX_COLLECTION_NAME_LEN>;
+
+const STATE_MAGIC: [u8; 4] = *b"CTLG";
+const STATE_VERSION: u16 = 1;
+const HEADER_LEN: usize = STATE_MAGIC.len() + size_of::<u16>() + size_of::<u16>();
+const EMPTY_SNAPSHOT: [u8; HEADER_LEN] = [b'C', b'T', b'L', b'G', 1, 0, 0, 0];
+const ENTRY_FIXED_LEN: usize = size_of::<u8>() + size_of::<u64>() + size_of::<u16>();
+
+/// Errors returned by the named collection catalog.
+#[derive(Debug)]
+pub enum CatalogError {
+    /// Shared storage failed.
+    Storage(StorageRuntimeError),
+    /// A name was empty or longer than [`MAX_COLLECTION_NAME_LEN`] bytes.
+    InvalidName,
+    /// Another live collection already uses the name.
+    NameInUse,
+    /// No live collection uses the name.
+    UnknownName,
+    /// The named collection has a different type than the caller expected.
+    CollectionTypeMismatch {
+        collection_id: CollectionId,
+        expected: u16,
+        actual: u16,
+    },
+    /// The collection type keeps state its constructor must write, so it
+    /// cannot be created empty; create it with its constructor and register
+    /// it with [`Storage::bind_name`].
+    RequiresConstructor(u16),
+    /// The collection is already registered under another name.
+    CollectionAlreadyNamed(CollectionId),
+    /// The encoded catalog does not fit the snapshot buffer.
+    CatalogTooLarge,
+    /// Encoded catalog data was malformed.
+    InvalidEncoding,
+}
+
+impl From<StorageRuntimeError> for CatalogError {
+    fn from(error: StorageRuntimeError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+/// One live entry of the named collection catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedCollection {
+    name: CollectionName,
+    collection_id: CollectionId,
+    collection_type: u16,
+}
+
+impl NamedCollection {
+    /// Returns the name the collection is registered under.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the stable collection id.
+    pub fn collection_id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    /// Returns the stable collection type code.
+    pub fn collection_type(&self) -> u16 {
+        self.collection_type
+    }
+}
+
+/// Catalog contents rebuilt from the WAL.
+struct Catalog<const MAX_COLLECTIONS: usize> {
+    collection_id: Option<CollectionId>,
+    entries: Vec<NamedCollection, MAX_COLLECTIONS>,
+}
+
+impl<const MAX_COLLECTIONS: usize> Catalog<MAX_COLLECTIONS> {
+    fn find(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.name() == name)
+    }
+}
+
+impl<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    > Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>
+{
+    /// Creates an empty collection of `collection_type` registered under
+    /// `name` and returns its id.
+    ///
+    /// The `new_collection` record and the updated catalog are written as one
+    /// inline transaction. Open the collection afterwards with its type's
+    /// `open` constructor. A ring log records its region budget when it is
+    /// created, so it is rejected here; create it with `RingLog::new` and
+    /// name it with [`Storage::bind_name`].
+    pub fn create_named(
+        &mut self,
+        name: &str,
+        collection_type: u16,
+    ) -> Result<CollectionId, CatalogError> {
+        let name = validate_name(name)?;
+        let mut catalog = self.load_catalog()?;
+        if catalog.find(&name).is_some() {
+            return Err(CatalogError::NameInUse);
+        }
+
+        let catalog_id = match catalog.collection_id {
+            Some(catalog_id) => catalog_id,
+            None => self.allocate_collection_id()?,
+        };
+        let collection_id = match catalog.collection_id {
+            Some(_) => self.allocate_collection_id()?,
+            None => catalog_id
+                .increment()
+                .ok_or(StorageRuntimeError::TooManyTrackedCollections)?,
+        };
+        self.memory
+            .state
+            .validate_supported_user_collection_type(collection_id, collection_type)?;
+        if collection_type == CollectionType::RING_LOG_CODE {
+            return Err(CatalogError::RequiresConstructor(collection_type));
+        }
+        let new_collections = if catalog.collection_id.is_some() {
+            1
+        } else {
+            2
+        };
+        if self.collections().len() + new_collections > MAX_COLLECTIONS {
+            return Err(CatalogError::Storage(
+                StorageRuntimeError::TooManyTrackedCollections,
+            ));
+        }
+        catalog
+            .entries
+            .push(NamedCollection {
+                name,
+                collection_id,
+                collection_type,
+            })
+            .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)?;
+
+        self.enter_mode(StorageMode::CreatingCollection(
+            CollectionCreateMode::Running,
+        ))?;
+        let result = self.write_catalog_with(&catalog.entries, catalog_id, |catalog_record| {
+            let create_record = WalRecord::NewCollection {
+                collection_id,
+                collection_type,
+            };
+            match catalog.collection_id {
+                Some(_) => CatalogRecords::Two([create_record, catalog_record]),
+                None => CatalogRecords::Three([
+                    WalRecord::NewCollection {
+                        collection_id: catalog_id,
+                        collection_type: CollectionType::CATALOG_CODE,
+                    },
+                    create_record,
+                    catalog_record,
+                ]),
+            }
+        });
+        self.finish_mode();
+        result?;
+        Ok(collection_id)
+    }
+
+    /// Registers the live collection `collection_id` under `name`.
+    ///
+    /// Use this to name a collection created by its type's constructor. The
+    /// updated catalog is written as one inline transaction, which also
+    /// creates the catalog collection the first time a name is registered.
+    pub fn bind_name(
+        &mut self,
+        name: &str,
+        collection_id: CollectionId,
+    ) -> Result<(), CatalogError> {
+        let name = validate_name(name)?;
+        let mut catalog = self.load_catalog()?;
+        if catalog.find(&name).is_some() {
+            return Err(CatalogError::NameInUse);
+        }
+        if catalog
+            .entries
+            .iter()
+            .any(|entry| entry.collection_id == collection_id)
+        {
+            return Err(CatalogError::CollectionAlreadyNamed(collection_id));
+        }
+        let collection = self
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == collection_id)
+            .copied()
+            .ok_or(StorageRuntimeError::UnknownCollection(collection_id))?;
+        if collection.basis() == StartupCollectionBasis::Dropped {
+            return Err(CatalogError::Storage(
+                StorageRuntimeError::DroppedCollection(collection_id),
+            ));
+        }
+        let collection_type = collection
+            .collection_type()
+            .ok_or(StorageRuntimeError::UnknownCollection(collection_id))?;
+        self.memory
+            .state
+            .validate_supported_user_collection_type(collection_id, collection_type)?;
+
+        let catalog_id = match catalog.collection_id {
+            Some(catalog_id) => catalog_id,
+            None => {
+                if self.collections().len() + 1 > MAX_COLLECTIONS {
+                    return Err(CatalogError::Storage(
+                        StorageRuntimeError::TooManyTrackedCollections,
+                    ));
+                }
+                self.allocate_collection_id()?
+            }
+        };
+        catalog
+            .entries
+            .push(NamedCollection {
+                name,
+                collection_id,
+                collection_type,
+            })
+            .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)?;
+
+        self.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result =
+            self.write_catalog_with(&catalog.entries, catalog_id, |catalog_record| match catalog
+                .collection_id
+            {
+                Some(_) => CatalogRecords::One([catalog_record]),
+                None => CatalogRecords::Two([
+                    WalRecord::NewCollection {
+                        collection_id: catalog_id,
+                        collection_type: CollectionType::CATALOG_CODE,
+                    },
+                    catalog_record,
+                ]),
+            });
+        self.finish_mode();
+        result
+    }
+
+    /// Returns the id of the collection registered under `name`, checking
+    /// that it has `collection_type`.
+    pub fn open_named(
+        &mut self,
+        name: &str,
+        collection_type: u16,
+    ) -> Result<CollectionId, CatalogError> {
+        let catalog = self.load_catalog()?;
+        let entry = catalog
+            .find(name)
+            .map(|index| &catalog.entries[index])
+            .ok_or(CatalogError::UnknownName)?;
+        if entry.collection_type != collection_type {
+            return Err(CatalogError::CollectionTypeMismatch {
+                collection_id: entry.collection_id,
+                expected: collection_type,
+                actual: entry.collection_type,
+            });
+        }
+        Ok(entry.collection_id)
+    }
+
+    /// Registers the collection named `from` under `to` instead.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<(), CatalogError> {
+        let to = validate_name(to)?;
+        let mut catalog = self.load_catalog()?;
+        let index = catalog.find(from).ok_or(CatalogError::UnknownName)?;
+        if from == to.as_str() {
+            return Ok(());
+        }
+        if catalog.find(&to).is_some() {
+            return Err(CatalogError::NameInUse);
+        }
+        let catalog_id = catalog.collection_id.ok_or(CatalogError::InvalidEncoding)?;
+        catalog.entries[index].name = to;
+
+        self.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.write_catalog_with(&catalog.entries, catalog_id, |catalog_record| {
+            CatalogRecords::One([catalog_record])
+        });
+        self.finish_mode();
+        result
+    }
+
+    /// Drops the collection registered under `name` and removes the name,
+    /// beginning reclaim for its last region basis like
+    /// [`Storage::drop_map`].
+    ///
+    /// The `drop_collection` record and the updated catalog are written as
+    /// one inline transaction. A plugin collection instead frees the regions
+    /// its head references with the drop in a collection transaction, and
+    /// the catalog follows in its own inline transaction.
+    pub fn drop_named(&mut self, name: &str) -> Result<Option<u32>, CatalogError> {
+        let mut catalog = self.load_catalog()?;
+        let index = catalog.find(name).ok_or(CatalogError::UnknownName)?;
+        let catalog_id = catalog.collection_id.ok_or(CatalogError::InvalidEncoding)?;
+        let collection_id = catalog.entries.remove(index).collection_id;
+        if self.memory.state.transaction_open_for(collection_id) {
+            return Err(CatalogError::Storage(
+                StorageRuntimeError::TransactionAlreadyOpen(collection_id),
+            ));
+        }
+
+        self.enter_mode(StorageMode::DroppingCollection(CollectionDropMode::Running))?;
+        self.invalidate_map_frontier_buffer(collection_id);
+        let payload_len = encode_catalog(&catalog.entries, &mut self.memory.collection_scratch);
+        let result = match payload_len {
+            Ok(payload_len) => self
+                .memory
+                .state
+                .drop_collection_with_record_and_free_regions::<REGION_SIZE, REGION_COUNT, IO>(
+                    self.backing,
+                    &mut self.memory.workspace,
+                    collection_id,
+                    Some(WalRecord::Snapshot {
+                        collection_id: catalog_id,
+                        collection_type: CollectionType::CATALOG_CODE,
+                        payload: &self.memory.collection_scratch[..payload_len],
+                    }),
+                    &mut self.memory.wal_chain_scratch,
+                )
+                .map_err(CatalogError::from),
+            Err(error) => Err(error),
+        };
+        if result.is_ok() {
+            self.clear_dirty_frontier(collection_id);
+        }
+        self.finish_mode();
+        result
+    }
+
+    /// Returns every live named collection in registration order.
+    pub fn list_collections(
+        &mut self,
+    ) -> Result<Vec<NamedCollection, MAX_COLLECTIONS>, CatalogError> {
+        Ok(self.load_catalog()?.entries)
+    }
+
+    /// Encodes `entries` and appends them as the catalog snapshot inside the
+    /// inline transaction `records` builds around it.
+    fn write_catalog_with<F>(
+        &mut self,
+        entries: &[NamedCollection],
+        catalog_id: CollectionId,
+        records: F,
+    ) -> Result<(), CatalogError>
+    where
+        F: for<'payload> FnOnce(WalRecord<'payload>) -> CatalogRecords<'payload>,
+    {
+        let payload_len = encode_catalog(entries, &mut self.memory.collection_scratch)?;
+        let records = records(WalRecord::Snapshot {
+            collection_id: catalog_id,
+            collection_type: CollectionType::CATALOG_CODE,
+            payload: &self.memory.collection_scratch[..payload_len],
+        });
+        self.memory
+            .state
+            .append_inline_transaction_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                self.backing,
+                &mut self.memory.workspace,
+                records.as_slice(),
+            )?;
+        Ok(())
+    }
+
+    /// Rebuilds the catalog from its last committed snapshot, keeping only
+    /// entries whose collection is still live.
+    fn load_catalog(&mut self) -> Result<Catalog<MAX_COLLECTIONS>, CatalogError> {
+        let mut catalog = Catalog {
+            collection_id: None,
+            entries: Vec::new(),
+        };
+        for collection in self.collections() {
+            if collection.collection_type() == Some(CollectionType::CATALOG_CODE)
+                && collection.basis() != StartupCollectionBasis::Dropped
+            {
+                if catalog.collection_id.is_some() {
+                    return Err(CatalogError::InvalidEncoding);
+                }
+                catalog.collection_id = Some(collection.collection_id());
+            }
+        }
+        let Some(catalog_id) = catalog.collection_id else {
+            return Ok(catalog);
+        };
+
+        self.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let mut inline_open = false;
+        let mut staged = None;
+        let entries = &mut catalog.entries;
+        let result = self
+            .memory
+            .state
+            .visit_wal_records::<REGION_SIZE, IO, CatalogError, _>(
+                self.backing,
+                &mut self.memory.workspace,
+                |_flash, record| {
+                    match record {
+                        WalRecord::BeginInlineTransaction { .. } => {
+                            inline_open = true;
+                            staged = None;
+                        }
+                        WalRecord::CommitInlineTransaction { .. } => {
+                            inline_open = false;
+                            if let Some(committed) = staged.take() {
+                                *entries = committed;
+                            }
+                        }
+                        WalRecord::RollbackInlineTransaction { .. } => {
+                            inline_open = false;
+                            staged = None;
+                        }
+                        WalRecord::Snapshot {
+                            collection_id,
+                            collection_type: CollectionType::CATALOG_CODE,
+                            payload,
+                        } if collection_id == catalog_id => {
+                            let decoded = decode_catalog(payload)?;
+                            if inline_open {
+                                staged = Some(decoded);
+                            } else {
+                                *entries = decoded;
+                            }
+                        }
+                        _ => {}
+                    }
+                    Ok(())
+                },
+            );
+        self.finish_mode();
+        match result {
+            Ok(()) => {}
+            Err(StorageVisitError::Storage(error)) => return Err(CatalogError::Storage(error)),
+            Err(StorageVisitError::Visitor(error)) => return Err(error),
+        }
+
+        let collections = self.collections();
+        catalog.entries.retain(|entry| {
+            collections.iter().any(|collection| {
+                collection.collection_id() == entry.collection_id
+                    && collection.basis() != StartupCollectionBasis::Dropped
+            })
+        });
+        Ok(catalog)
+    }
+}
+
+/// WAL records written together with one catalog snapshot.
+enum CatalogRecords<'payload> {
+    One([WalRecord<'payload>; 1]),
+    Two([WalRecord<'payload>; 2]),
+    Three([WalRecord<'payload>; 3]),
+}
+
+impl<'payload> CatalogRecords<'payload> {
+    fn as_slice(&self) -> &[WalRecord<'payload>] {
+        match self {
+            Self::One(records) => records,
+            Self::Two(records) => records,
+            Self::Three(records) => records,
+        }
+    }
+}
+
+pub(crate) fn empty_snapshot() -> &'static [u8] {
+    &EMPTY_SNAPSHOT
+}
+
+fn validate_name(name: &str) -> Result<CollectionName, CatalogError> {
+    if name.is_empty() {
+        return Err(CatalogError::InvalidName);
+    }
+    CollectionName::try_from(name).map_err(|_| CatalogError::InvalidName)
+}
+
+fn encode_catalog(entries: &[NamedCollection], out: &mut [u8]) -> Result<usize, CatalogError> {
+    let count = u16::try_from(entries.len()).map_err(|_| CatalogError::InvalidEncoding)?;
+    let header = out
+        .get_mut(..HEADER_LEN)
+        .ok_or(CatalogError::CatalogTooLarge)?;
+    header[..STATE_MAGIC.len()].copy_from_slice(&STATE_MAGIC);
+    header[STATE_MAGIC.len()..STATE_MAGIC.len() + size_of::<u16>()]
+        .copy_from_slice(&STATE_VERSION.to_le_bytes());
+    header[STATE_MAGIC.len() + size_of::<u16>()..].copy_from_slice(&count.to_le_bytes());
+
+    let mut offset = HEADER_LEN;
+    for entry in entries {
+        let name = entry.name.as_bytes();
+        let end = offset + ENTRY_FIXED_LEN + name.len();
+        let bytes = out
+            .get_mut(offset..end)
+            .ok_or(CatalogError::CatalogTooLarge)?;
+        // Names are at most MAX_COLLECTION_NAME_LEN bytes, so the length fits.
+        bytes[0] = name.len() as u8;
+        let (name_bytes, rest) = bytes[1..].split_at_mut(name.len());
+        name_bytes.copy_from_slice(name);
+        rest[..size_of::<u64>()].copy_from_slice(&entry.collection_id.to_le_bytes());
+        rest[size_of::<u64>()..].copy_from_slice(&entry.collection_type.to_le_bytes());
+        offset = end;
+    }
+    Ok(offset)
+}
+
+fn decode_catalog<const MAX_COLLECTIONS: usize>(
+    payload: &[u8],
+) -> Result<Vec<NamedCollection, MAX_COLLECTIONS>, CatalogError> {
+    if payload.len() < HEADER_LEN
+        || payload[..STATE_MAGIC.len()] != STATE_MAGIC
+        || payload[STATE_MAGIC.len()..STATE_MAGIC.len() + size_of::<u16>()]
+            != STATE_VERSION.to_le_bytes()
+    {
+        return Err(CatalogError::InvalidEncoding);
+    }
+    let count = u16::from_le_bytes([payload[HEADER_LEN - 2], payload[HEADER_LEN - 1]]);
+
+    let mut entries = Vec::new();
+    let mut rest = &payload[HEADER_LEN..];
+    for _ in 0..count {
+        let (&name_len, tail) = rest.split_first().ok_or(CatalogError::InvalidEncoding)?;
+        let name_len = usize::from(name_len);
+        if tail.len() < name_len + size_of::<u64>() + size_of::<u16>() {
+            return Err(CatalogError::InvalidEncoding);
+        }
+        let (name, tail) = tail.split_at(name_len);
+        let (id, tail) = tail.split_at(size_of::<u64>());
+        let (collection_type, tail) = tail.split_at(size_of::<u16>());
+        let name = core::str::from_utf8(name).map_err(|_| CatalogError::InvalidEncoding)?;
+        let mut id_bytes = [0u8; size_of::<u64>()];
+        id_bytes.copy_from_slice(id);
+        entries
+            .push(NamedCollection {
+                name: validate_name(name).map_err(|_| CatalogError::InvalidEncoding)?,
+                collection_id: CollectionId::new(u64::from_le_bytes(id_bytes)),
+                collection_type: u16::from_le_bytes([collection_type[0], collection_type[1]]),
+            })
+            .map_err(|_| CatalogError::InvalidEncoding)?;
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        return Err(CatalogError::InvalidEncoding);
+    }
+    Ok(entries)
+}
@@ -0,0 +1,459 @@
+use super::*;
+
+use crate::collections::cell::Cell;
+use crate::MockFlash;
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+
+fn names(
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+) -> std::vec::Vec<std::string::String> {
+    storage
+        .list_collections()
+        .unwrap()
+        .iter()
+        .map(|entry| std::string::String::from(entry.name()))
+        .collect()
+}
+
+/// Returns the record kinds appended after the first `skip` WAL records.
+fn record_types_after<const MAX_COLLECTIONS: usize>(
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    skip: usize,
+) -> std::vec::Vec<crate::WalRecordType> {
+    let mut types = std::vec::Vec::new();
+    storage
+        .visit_wal_records::<(), _>(|record| {
+            types.push(record.record_type());
+            Ok(())
+        })
+        .unwrap();
+    types.split_off(skip)
+}
+
+fn record_count<const MAX_COLLECTIONS: usize>(
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+) -> usize {
+    record_types_after(storage, 0).len()
+}
+
+//= spec/catalog.md#names
+//= type=test
+//# `RING-CATALOG-001` `Storage::create_named` MUST create an empty
+//# collection of the requested type under an unused valid name, and MUST
+//# reject empty, overlong, or already used names without writing to the WAL.
+#[test]
+fn requirement_catalog_create_named_validates_names() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let id = storage
+        .create_named("config", CollectionType::CELL_CODE)
+        .unwrap();
+    assert_eq!(*Cell::<u32>::open(id, &mut storage).unwrap().get(), 0);
+
+    let before = record_count(&mut storage);
+    assert!(matches!(
+        storage.create_named("", CollectionType::CELL_CODE),
+        Err(CatalogError::InvalidName)
+    ));
+    assert!(matches!(
+        storage.create_named("seventeen-bytes!!", CollectionType::CELL_CODE),
+        Err(CatalogError::InvalidName)
+    ));
+    assert!(matches!(
+        storage.create_named("config", CollectionType::SEQUENCE_CODE),
+        Err(CatalogError::NameInUse)
+    ));
+    assert_eq!(record_count(&mut storage), before);
+
+    let longest = "sixteen-bytes-ok";
+    assert_eq!(longest.len(), MAX_COLLECTION_NAME_LEN);
+    storage
+        .create_named(longest, CollectionType::SEQUENCE_CODE)
+        .unwrap();
+    assert_eq!(names(&mut storage), ["config", longest]);
+}
+
+//= spec/catalog.md#names
+//= type=test
+//# `RING-CATALOG-002` `Storage::open_named` MUST return the id registered
+//# under a name and MUST reject unknown names and names registered with a
+//# different collection type.
+#[test]
+fn requirement_catalog_open_named_checks_name_and_type() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let id = storage
+        .create_named("ids", CollectionType::SEQUENCE_CODE)
+        .unwrap();
+
+    assert_eq!(
+        storage
+            .open_named("ids", CollectionType::SEQUENCE_CODE)
+            .unwrap(),
+        id
+    );
+    assert!(matches!(
+        storage.open_named("missing", CollectionType::SEQUENCE_CODE),
+        Err(CatalogError::UnknownName)
+    ));
+    assert!(matches!(
+        storage.open_named("ids", CollectionType::CELL_CODE),
+        Err(CatalogError::CollectionTypeMismatch {
+            collection_id,
+            expected: CollectionType::CELL_CODE,
+            actual: CollectionType::SEQUENCE_CODE,
+        }) if collection_id == id
+    ));
+}
+
+//= spec/catalog.md#names
+//= type=test
+//# `RING-CATALOG-003` `Storage::rename` MUST move an entry to an unused
+//# name, and `Storage::list_collections` MUST return the live entries in
+//# registration order, both before and after storage reopens.
+#[test]
+fn requirement_catalog_rename_and_list_survive_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let (first, second) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let first = storage
+            .create_named("alpha", CollectionType::CELL_CODE)
+            .unwrap();
+        let second = storage
+            .create_named("beta", CollectionType::CELL_CODE)
+            .unwrap();
+        assert!(matches!(
+            storage.rename("alpha", "beta"),
+            Err(CatalogError::NameInUse)
+        ));
+        assert!(matches!(
+            storage.rename("gamma", "delta"),
+            Err(CatalogError::UnknownName)
+        ));
+        storage.rename("alpha", "gamma").unwrap();
+        assert_eq!(names(&mut storage), ["gamma", "beta"]);
+        (first, second)
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let entries = storage.list_collections().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name(), "gamma");
+    assert_eq!(entries[0].collection_id(), first);
+    assert_eq!(entries[0].collection_type(), CollectionType::CELL_CODE);
+    assert_eq!(entries[1].name(), "beta");
+    assert_eq!(entries[1].collection_id(), second);
+    assert!(matches!(
+        storage.open_named("alpha", CollectionType::CELL_CODE),
+        Err(CatalogError::UnknownName)
+    ));
+}
+
+//= spec/catalog.md#atomicity
+//= type=test
+//# `RING-CATALOG-004` `Storage::create_named` MUST append its
+//# `new_collection` record and the updated catalog snapshot in one inline
+//# transaction, which also creates the catalog collection the first time a
+//# name is registered.
+#[test]
+fn requirement_catalog_create_named_is_one_inline_transaction() {
+    use crate::WalRecordType::{
+        BeginInlineTransaction, CommitInlineTransaction, NewCollection, Snapshot,
+    };
+
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let before = record_count(&mut storage);
+    storage
+        .create_named("first", CollectionType::CELL_CODE)
+        .unwrap();
+    assert_eq!(
+        record_types_after(&mut storage, before),
+        [
+            BeginInlineTransaction,
+            NewCollection,
+            NewCollection,
+            Snapshot,
+            CommitInlineTransaction
+        ]
+    );
+
+    let before = record_count(&mut storage);
+    storage
+        .create_named("second", CollectionType::CELL_CODE)
+        .unwrap();
+    assert_eq!(
+        record_types_after(&mut storage, before),
+        [
+            BeginInlineTransaction,
+            NewCollection,
+            Snapshot,
+            CommitInlineTransaction
+        ]
+    );
+    let catalogs = storage
+        .collections()
+        .iter()
+        .filter(|collection| collection.collection_type() == Some(CollectionType::CATALOG_CODE))
+        .count();
+    assert_eq!(catalogs, 1);
+}
+
+//= spec/catalog.md#atomicity
+//= type=test
+//# `RING-CATALOG-005` `Storage::drop_named` MUST append its
+//# `drop_collection` record and the updated catalog snapshot in one inline
+//# transaction, and the catalog MUST omit entries whose collection is no
+//# longer live, unless `RING-CATALOG-007` applies.
+#[test]
+fn requirement_catalog_drop_named_is_one_inline_transaction() {
+    use crate::WalRecordType::{
+        BeginInlineTransaction, CommitInlineTransaction, DropCollection, Snapshot,
+    };
+
+    let mut flash = TestFlash::new(0xff);
+    {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let dropped = storage
+            .create_named("dropped", CollectionType::CELL_CODE)
+            .unwrap();
+        let raw = storage
+            .create_named("raw", CollectionType::CELL_CODE)
+            .unwrap();
+        storage
+            .create_named("kept", CollectionType::CELL_CODE)
+            .unwrap();
+
+        let before = record_count(&mut storage);
+        assert_eq!(storage.drop_named("dropped").unwrap(), None);
+        assert_eq!(
+            record_types_after(&mut storage, before),
+            [
+                BeginInlineTransaction,
+                DropCollection,
+                Snapshot,
+                CommitInlineTransaction
+            ]
+        );
+        assert!(storage
+            .collections()
+            .iter()
+            .any(|collection| collection.collection_id() == dropped
+                && collection.basis() == StartupCollectionBasis::Dropped));
+
+        storage
+            .memory
+            .state
+            .drop_collection_and_begin_reclaim::<REGION_SIZE, REGION_COUNT, _>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                raw,
+            )
+            .unwrap();
+        assert_eq!(names(&mut storage), ["kept"]);
+        assert!(matches!(
+            storage.drop_named("dropped"),
+            Err(CatalogError::UnknownName)
+        ));
+    }
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(names(&mut storage), ["kept"]);
+}
+
+//= spec/catalog.md#atomicity
+//= type=test
+//# `RING-CATALOG-006` User collection APIs MUST reject the catalog
+//# collection type, so the catalog is only written through the named
+//# collection APIs.
+#[test]
+fn requirement_catalog_type_is_storage_private() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let id = storage.allocate_collection_id().unwrap();
+    assert!(matches!(
+        storage.append_new_collection(id, CollectionType::CATALOG_CODE),
+        Err(StorageRuntimeError::UnsupportedCollectionType(
+            CollectionType::CATALOG_CODE
+        ))
+    ));
+    assert!(matches!(
+        storage.create_named("catalog", CollectionType::CATALOG_CODE),
+        Err(CatalogError::Storage(
+            StorageRuntimeError::UnsupportedCollectionType(CollectionType::CATALOG_CODE)
+        ))
+    ));
+    assert!(storage.list_collections().unwrap().is_empty());
+    assert!(storage.collections().is_empty());
+}
+
+//= spec/catalog.md#names
+//= type=test
+//# `RING-CATALOG-008` `Storage::create_named` MUST reject a type whose
+//# constructor records state the empty collection lacks, and
+//# `Storage::bind_name` MUST register a live collection of any user type
+//# under an unused valid name so that its type's `open` constructor accepts
+//# the id `Storage::open_named` returns.
+#[test]
+fn requirement_catalog_bind_name_opens_every_collection_type() {
+    use crate::collections::channel::{AddCommand, Channel, MemberId, MemberSequence};
+    use crate::collections::object_log::{ObjectLog, ObjectLogMemory};
+    use crate::collections::queue::{DurableQueue, DurableQueueMemory};
+    use crate::collections::ring_log::{RingLog, RingLogMemory};
+    use crate::collections::sequence::Sequence;
+    use crate::collections::set::{LsmSet, LsmSetMemory};
+    use crate::collections::time_series::{TimeSeries, TimeSeriesMemory};
+    use crate::vec_like::VecLikeSlice;
+    use crate::{Collection, LsmMap, LsmMapMemory, StorageFormatConfig};
+
+    type NamedStorage<'db> = Storage<'db, 'static, TestFlash, REGION_SIZE, REGION_COUNT, 16>;
+    type TestChannel<'a> = Channel<
+        'a,
+        'a,
+        'a,
+        u32,
+        VecLikeSlice<'a, MemberSequence, 2>,
+        VecLikeSlice<'a, MemberId, 2>,
+        VecLikeSlice<'a, AddCommand<u32, 8>, 2>,
+        8,
+        2,
+    >;
+
+    let mut flash = TestFlash::new(0xff);
+    {
+        let mut storage = NamedStorage::format(
+            &mut flash,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let before = record_count(&mut storage);
+        assert!(matches!(
+            storage.create_named("ring", CollectionType::RING_LOG_CODE),
+            Err(CatalogError::RequiresConstructor(
+                CollectionType::RING_LOG_CODE
+            ))
+        ));
+        assert_eq!(record_count(&mut storage), before);
+
+        let mut ring_memory = RingLogMemory::<REGION_SIZE, 3>::new();
+        let ring = RingLog::new(&mut storage, &mut ring_memory).unwrap().id();
+        let mut map_memory = LsmMapMemory::<u16, u16, 4>::new();
+        let map = LsmMap::new(&mut storage, &mut map_memory)
+            .unwrap()
+            .collection_id();
+        let mut set_memory = LsmSetMemory::<u16, 4>::new();
+        let set = LsmSet::new(&mut storage, &mut set_memory)
+            .unwrap()
+            .collection_id();
+        let mut log_memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
+        let log = ObjectLog::new(&mut storage, &mut log_memory, b"meta")
+            .unwrap()
+            .id();
+        let mut queue_memory = DurableQueueMemory::<REGION_SIZE>::new();
+        let queue = DurableQueue::<u32, REGION_SIZE>::new(&mut storage, &mut queue_memory)
+            .unwrap()
+            .id();
+        let mut series_memory = TimeSeriesMemory::<REGION_SIZE, 2>::new();
+        let series = TimeSeries::new(&mut storage, &mut series_memory)
+            .unwrap()
+            .id();
+        let cell = Cell::new(&mut storage, 7u32).unwrap().id();
+        let sequence = Sequence::new(&mut storage, 100).unwrap().id();
+        let mut pending_data = core::array::from_fn::<_, 2, _>(|_| AddCommand::default());
+        let mut members_data = [MemberSequence::default(); 2];
+        let mut updates_data = [MemberId::default(); 2];
+        let channel = TestChannel::new(
+            &mut storage,
+            MemberId::new(1),
+            &mut VecLikeSlice::new(&mut pending_data),
+            &mut VecLikeSlice::new(&mut members_data),
+            &mut VecLikeSlice::new(&mut updates_data),
+        )
+        .unwrap()
+        .id();
+
+        for (name, id) in [
+            ("ring", ring),
+            ("map", map),
+            ("set", set),
+            ("log", log),
+            ("queue", queue),
+            ("series", series),
+            ("cell", cell),
+            ("sequence", sequence),
+            ("channel", channel),
+        ] {
+            storage
+                .bind_name(name, id)
+                .unwrap_or_else(|e| panic!("{name}: {e:?}"));
+        }
+        assert!(matches!(
+            storage.bind_name("again", ring),
+            Err(CatalogError::CollectionAlreadyNamed(id)) if id == ring
+        ));
+        assert!(matches!(
+            storage.bind_name("ring", cell),
+            Err(CatalogError::NameInUse)
+        ));
+    }
+
+    let mut storage = NamedStorage::open(&mut flash, crate::test_storage_memory()).unwrap();
+    let id = storage
+        .open_named("ring", CollectionType::RING_LOG_CODE)
+        .unwrap();
+    let mut ring_memory = RingLogMemory::<REGION_SIZE, 3>::new();
+    assert_eq!(
+        RingLog::open(id, &mut storage, &mut ring_memory)
+            .unwrap()
+            .region_budget(),
+        3
+    );
+    let id = storage.open_named("map", CollectionType::MAP_CODE).unwrap();
+    let mut map_memory = LsmMapMemory::<u16, u16, 4>::new();
+    LsmMap::open(id, &mut storage, &mut map_memory).unwrap();
+    let id = storage.open_named("set", CollectionType::SET_CODE).unwrap();
+    let mut set_memory = LsmSetMemory::<u16, 4>::new();
+    LsmSet::open(id, &mut storage, &mut set_memory).unwrap();
+    let id = storage
+        .open_named("log", CollectionType::OBJECT_LOG_CODE)
+        .unwrap();
+    let mut log_memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
+    ObjectLog::open(id, &mut storage, &mut log_memory).unwrap();
+    let id = storage
+        .open_named("queue", CollectionType::QUEUE_CODE)
+        .unwrap();
+    let mut queue_memory = DurableQueueMemory::<REGION_SIZE>::new();
+    DurableQueue::<u32, REGION_SIZE>::open(id, &mut storage, &mut queue_memory).unwrap();
+    let id = storage
+        .open_named("series", CollectionType::TIME_SERIES_CODE)
+        .unwrap();
+    let mut series_memory = TimeSeriesMemory::<REGION_SIZE, 2>::new();
+    TimeSeries::open(id, &mut storage, &mut series_memory).unwrap();
+    let id = storage
+        .open_named("cell", CollectionType::CELL_CODE)
+        .unwrap();
+    assert_eq!(*Cell::<u32>::open(id, &mut storage).unwrap().get(), 7);
+    let id = storage
+        .open_named("sequence", CollectionType::SEQUENCE_CODE)
+        .unwrap();
+    assert_eq!(Sequence::open(id, &mut storage).unwrap().peek(), 100);
+    let id = storage
+        .open_named("channel", CollectionType::CHANNEL_CODE)
+        .unwrap();
+    let mut pending_data = core::array::from_fn::<_, 2, _>(|_| AddCommand::default());
+    let mut members_data = [MemberSequence::default(); 2];
+    let mut updates_data = [MemberId::default(); 2];
+    TestChannel::open(
+        id,
+        &mut storage,
+        &mut VecLikeSlice::new(&mut pending_data),
+        &mut VecLikeSlice::new(&mut members_data),
+        &mut VecLikeSlice::new(&mut updates_data),
+    )
+    .unwrap();
+}
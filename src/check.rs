@@ -0,0 +1,825 @@
+//! Whole-store consistency checking.
+//!
+//! [`Storage::check`](crate::Storage::check) walks the stored metadata, the
+//! retained WAL chain, the free-space queue, and every live collection's
+//! committed regions without writing to flash. Each inconsistency goes to a
+//! [`CheckReportSink`] as a [`CheckFinding`], so one pass reports every
+//! problem instead of stopping at the first [`StartupError`].
+//! [`Storage::check_regions`](crate::Storage::check_regions) checks a store
+//! that fails to open, one region at a time.
+//!
+//! [`StartupError`]: crate::StartupError
+
+use heapless::Vec;
+use serde::Serialize;
+
+use crate::collections::map::{self, MapHeadRegion, MapStorageError, MAP_REGION_V2_FORMAT};
+use crate::disk::{
+    FREE_SPACE_V2_FORMAT, FREE_SPACE_WEAR_V1_FORMAT, MAIN_WAL_V2_FORMAT, TRANSACTION_LOG_V2_FORMAT,
+};
+use crate::flash_io::FlashIo;
+use crate::startup::{StartupCollection, StartupCollectionBasis, StartupError};
+use crate::storage::{StorageRuntime, StorageRuntimeError};
+use crate::wal_record::{decode_record, WalRecord};
+use crate::workspace::StorageWorkspace;
+use crate::{CollectionId, CollectionType, Header, StorageMetadata};
+
+#[cfg(test)]
+mod tests;
+
+/// One inconsistency found by [`Storage::check`](crate::Storage::check).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CheckFinding {
+    /// The stored metadata is unreadable or differs from the metadata the
+    /// store opened with.
+    MetadataMismatch,
+    /// A region header failed its checksum or could not be decoded.
+    InvalidHeader {
+        /// Region whose header was rejected.
+        region_index: u32,
+    },
+    /// A region header names a format its owner never writes.
+    UnexpectedFormat {
+        /// Region whose header was rejected.
+        region_index: u32,
+        /// Collection id recorded in the header.
+        collection_id: CollectionId,
+        /// Format code recorded in the header.
+        collection_format: u16,
+    },
+    /// A retained WAL record failed to decode and no `wal_recovery` record
+    /// closes the gap.
+    CorruptWalRecord {
+        /// WAL region holding the damaged bytes.
+        region_index: u32,
+        /// Byte offset of the first damaged granule.
+        offset: usize,
+    },
+    /// The WAL chain ends, loops, or links away before reaching the tail.
+    BrokenWalChain {
+        /// Last WAL region reached.
+        region_index: u32,
+    },
+    /// The free-space queue lists a region more than once.
+    DuplicateFreeRegion {
+        /// Region queued twice.
+        region_index: u32,
+    },
+    /// A ready free region holds programmed bytes.
+    ReadyRegionNotErased {
+        /// Ready region that is not erased.
+        region_index: u32,
+    },
+    /// A region is queued as free while an owner still holds it.
+    FreeAndOwned {
+        /// Region both free and owned.
+        region_index: u32,
+        /// Owning collection, or `CollectionId(0)` for storage-owned
+        /// regions such as the WAL.
+        collection_id: CollectionId,
+    },
+    /// Two owners claim the same region.
+    RegionOwnedTwice {
+        /// Region claimed twice.
+        region_index: u32,
+    },
+    /// A live collection's head region belongs to another collection.
+    HeadMismatch {
+        /// Collection whose head was checked.
+        collection_id: CollectionId,
+        /// Head region recorded by replay.
+        region_index: u32,
+    },
+    /// A map manifest or one of its run segments failed validation.
+    InvalidMapManifest {
+        /// Map collection being checked.
+        collection_id: CollectionId,
+        /// Manifest or run region that was rejected.
+        region_index: u32,
+    },
+    /// A map manifest does not list its runs newest generation first.
+    UnorderedMapRuns {
+        /// Map collection being checked.
+        collection_id: CollectionId,
+        /// First run region out of order.
+        region_index: u32,
+    },
+    /// A live collection's committed regions could not be enumerated.
+    InvalidCommittedRegions {
+        /// Collection being checked.
+        collection_id: CollectionId,
+        /// Head region recorded by replay.
+        region_index: u32,
+    },
+    /// A region is neither free, retired, nor reachable from live state.
+    LeakedRegion {
+        /// Unreachable region.
+        region_index: u32,
+    },
+}
+
+/// Receives findings from [`Storage::check`](crate::Storage::check).
+pub trait CheckReportSink {
+    /// Records one finding.
+    fn report(&mut self, finding: CheckFinding);
+}
+
+impl<F: FnMut(CheckFinding)> CheckReportSink for F {
+    fn report(&mut self, finding: CheckFinding) {
+        self(finding);
+    }
+}
+
+/// Keeps the first `N` findings and drops the rest; the summary still counts
+/// them.
+impl<const N: usize> CheckReportSink for Vec<CheckFinding, N> {
+    fn report(&mut self, finding: CheckFinding) {
+        let _ = self.push(finding);
+    }
+}
+
+/// Totals for one [`Storage::check`](crate::Storage::check) pass.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CheckSummary {
+    /// Regions the pass accounted for.
+    pub regions_checked: u32,
+    /// Retained WAL records that decoded.
+    pub wal_records: u32,
+    /// Findings sent to the sink.
+    pub findings: u32,
+}
+
+impl CheckSummary {
+    /// Returns whether the pass found no inconsistencies.
+    pub fn is_clean(&self) -> bool {
+        self.findings == 0
+    }
+}
+
+struct Reporter<'a, S: ?Sized> {
+    sink: &'a mut S,
+    findings: u32,
+}
+
+impl<S: CheckReportSink + ?Sized> Reporter<'_, S> {
+    fn report(&mut self, finding: CheckFinding) {
+        self.findings = self.findings.saturating_add(1);
+        self.sink.report(finding);
+    }
+}
+
+/// Runs every check against `storage` and the backing `flash`.
+///
+/// `wal_regions` and `claimed` are scratch for the WAL chain and for every
+/// region some owner has claimed so far.
+pub(crate) fn check_storage<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+    S: CheckReportSink + ?Sized,
+>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    wal_regions: &mut Vec<u32, REGION_COUNT>,
+    claimed: &mut Vec<u32, REGION_COUNT>,
+    sink: &mut S,
+) -> Result<CheckSummary, StorageRuntimeError> {
+    let mut reporter = Reporter { sink, findings: 0 };
+    let metadata = storage.metadata();
+    if !matches!(flash.read_metadata(), Ok(Some(stored)) if stored == metadata) {
+        reporter.report(CheckFinding::MetadataMismatch);
+    }
+
+    let wal_records = check_wal_chain::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS, S>(
+        storage,
+        flash,
+        workspace,
+        wal_regions,
+        &mut reporter,
+    )?;
+    claimed.clear();
+    check_free_space::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS, S>(
+        storage,
+        flash,
+        claimed,
+        &mut reporter,
+    )?;
+    for region_index in wal_regions.iter().copied() {
+        claim(
+            storage,
+            claimed,
+            region_index,
+            CollectionId(0),
+            &mut reporter,
+        )?;
+    }
+    if let Some(region_index) = storage.ready_region() {
+        claim(
+            storage,
+            claimed,
+            region_index,
+            CollectionId(0),
+            &mut reporter,
+        )?;
+    }
+
+    for collection in storage.collections().iter().copied() {
+        let StartupCollectionBasis::Region(head_region) = collection.basis() else {
+            continue;
+        };
+        let collection_id = collection.collection_id();
+        let Some(header) = read_header::<IO>(flash, head_region)? else {
+            reporter.report(CheckFinding::InvalidHeader {
+                region_index: head_region,
+            });
+            continue;
+        };
+        if header.collection_id != collection_id {
+            reporter.report(CheckFinding::HeadMismatch {
+                collection_id,
+                region_index: head_region,
+            });
+            continue;
+        }
+
+        let owned = &mut *wal_regions;
+        owned.clear();
+        let mut previous_generation = None;
+        let mut unordered_region = None;
+        let result = collect_owned_regions::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            workspace,
+            collection,
+            head_region,
+            header.collection_format,
+            owned,
+            |region| {
+                if let Some((_, 0)) = region.run {
+                    if unordered_region.is_none()
+                        && previous_generation.is_some_and(|previous| region.generation >= previous)
+                    {
+                        unordered_region = Some(region.region_index);
+                    }
+                    previous_generation = Some(region.generation);
+                }
+            },
+        );
+        match result {
+            Ok(()) => {}
+            Err(OwnedRegionsError::InvalidMap(error)) => {
+                reporter.report(CheckFinding::InvalidMapManifest {
+                    collection_id,
+                    region_index: map_error_region(error).unwrap_or(head_region),
+                })
+            }
+            Err(OwnedRegionsError::InvalidCommittedRegions) => {
+                reporter.report(CheckFinding::InvalidCommittedRegions {
+                    collection_id,
+                    region_index: head_region,
+                })
+            }
+            Err(OwnedRegionsError::Storage(error)) => return Err(error),
+        }
+        if let Some(region_index) = unordered_region {
+            reporter.report(CheckFinding::UnorderedMapRuns {
+                collection_id,
+                region_index,
+            });
+        }
+
+        for region_index in owned.iter().copied() {
+            if region_index != head_region {
+                match read_header::<IO>(flash, region_index)? {
+                    None => reporter.report(CheckFinding::InvalidHeader { region_index }),
+                    Some(header)
+                        if header.collection_id != collection_id
+                            || is_storage_format(header.collection_format) =>
+                    {
+                        reporter.report(CheckFinding::UnexpectedFormat {
+                            region_index,
+                            collection_id: header.collection_id,
+                            collection_format: header.collection_format,
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            claim(storage, claimed, region_index, collection_id, &mut reporter)?;
+        }
+    }
+
+    check_unclaimed_regions::<IO, REGION_COUNT, MAX_COLLECTIONS, S>(
+        storage,
+        flash,
+        claimed,
+        &mut reporter,
+    )?;
+
+    Ok(CheckSummary {
+        regions_checked: metadata.region_count,
+        wal_records,
+        findings: reporter.findings,
+    })
+}
+
+/// Checks each region of a store that did not open, on its own.
+///
+/// Without replay there is no WAL chain, free-space queue, or live
+/// collection state, so only stored metadata, region headers, and the
+/// records inside each main-WAL region are checked. A damaged span still
+/// open at the end of a WAL region may be the torn tail and is not reported.
+pub(crate) fn check_regions<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    S: CheckReportSink + ?Sized,
+>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    sink: &mut S,
+) -> Result<CheckSummary, StorageRuntimeError> {
+    let mut reporter = Reporter { sink, findings: 0 };
+    let metadata = match flash.read_metadata() {
+        Ok(Some(metadata))
+            if usize::try_from(metadata.region_size) == Ok(REGION_SIZE)
+                && usize::try_from(metadata.region_count) == Ok(REGION_COUNT) =>
+        {
+            Some(metadata)
+        }
+        _ => {
+            reporter.report(CheckFinding::MetadataMismatch);
+            None
+        }
+    };
+    let region_count = u32::try_from(REGION_COUNT)
+        .map_err(|_| StorageRuntimeError::Startup(StartupError::LengthOverflow))?;
+
+    let mut wal_records = 0u32;
+    for region_index in 0..region_count {
+        let (region_bytes, logical_scratch) = workspace.scan_buffers();
+        flash.read_region(region_index, 0, REGION_SIZE, |bytes| {
+            region_bytes.copy_from_slice(bytes);
+        })?;
+        let header_bytes = &region_bytes[..Header::ENCODED_LEN];
+        let header = match Header::decode(header_bytes) {
+            Ok(header) => header,
+            Err(_) => {
+                // Without metadata an erased header still reads as one
+                // repeated byte.
+                let erased_byte = metadata.map_or(header_bytes[0], |metadata| metadata.erased_byte);
+                if header_bytes.iter().any(|byte| *byte != erased_byte) {
+                    reporter.report(CheckFinding::InvalidHeader { region_index });
+                }
+                continue;
+            }
+        };
+        let Some(metadata) = metadata else {
+            continue;
+        };
+        if header.collection_id != CollectionId(0) || header.collection_format != MAIN_WAL_V2_FORMAT
+        {
+            continue;
+        }
+        let scan = scan_wal_region(
+            metadata,
+            &region_bytes[..],
+            REGION_SIZE,
+            logical_scratch,
+            |offset| {
+                reporter.report(CheckFinding::CorruptWalRecord {
+                    region_index,
+                    offset,
+                })
+            },
+        )?;
+        wal_records = wal_records.saturating_add(scan.records);
+    }
+
+    Ok(CheckSummary {
+        regions_checked: region_count,
+        wal_records,
+        findings: reporter.findings,
+    })
+}
+
+/// Walks the WAL from head to tail, leaving the chain in `wal_regions`, and
+/// returns the number of records that decoded.
+fn check_wal_chain<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+    S: CheckReportSink + ?Sized,
+>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    wal_regions: &mut Vec<u32, REGION_COUNT>,
+    reporter: &mut Reporter<'_, S>,
+) -> Result<u32, StorageRuntimeError> {
+    let metadata = storage.metadata();
+    let region_size = usize::try_from(metadata.region_size)
+        .ok()
+        .filter(|region_size| *region_size <= REGION_SIZE)
+        .ok_or(StorageRuntimeError::Startup(StartupError::LengthOverflow))?;
+    let mut records = 0u32;
+    wal_regions.clear();
+    let mut current_region = storage.wal_head();
+
+    for _ in 0..metadata.region_count {
+        if current_region >= metadata.region_count || wal_regions.contains(&current_region) {
+            break;
+        }
+        push_unique(wal_regions, current_region)?;
+        let is_tail = current_region == storage.wal_tail();
+        let limit = if is_tail {
+            storage.wal_append_offset()
+        } else {
+            region_size
+        };
+        let (region_bytes, logical_scratch) = workspace.scan_buffers();
+        flash.read_region(current_region, 0, region_bytes.len(), |bytes| {
+            region_bytes.copy_from_slice(bytes);
+        })?;
+        match Header::decode(&region_bytes[..Header::ENCODED_LEN]) {
+            Err(_) => {
+                reporter.report(CheckFinding::InvalidHeader {
+                    region_index: current_region,
+                });
+                return Ok(records);
+            }
+            Ok(header)
+                if header.collection_id != CollectionId(0)
+                    || header.collection_format != MAIN_WAL_V2_FORMAT =>
+            {
+                reporter.report(CheckFinding::UnexpectedFormat {
+                    region_index: current_region,
+                    collection_id: header.collection_id,
+                    collection_format: header.collection_format,
+                });
+                return Ok(records);
+            }
+            Ok(_) => {}
+        }
+
+        let scan = scan_wal_region(
+            metadata,
+            &region_bytes[..region_size],
+            limit,
+            logical_scratch,
+            |offset| {
+                reporter.report(CheckFinding::CorruptWalRecord {
+                    region_index: current_region,
+                    offset,
+                })
+            },
+        )?;
+        records = records.saturating_add(scan.records);
+        let corrupt_offset = scan.open_corrupt_offset;
+        let next_region = scan.next_region;
+
+        if let Some(offset) = corrupt_offset {
+            // Replay leaves a torn tail open until the next append closes it.
+            if !(is_tail && storage.pending_wal_recovery_boundary()) {
+                reporter.report(CheckFinding::CorruptWalRecord {
+                    region_index: current_region,
+                    offset,
+                });
+            }
+        }
+        if is_tail {
+            return Ok(records);
+        }
+        let Some(next_region) = next_region else {
+            break;
+        };
+        current_region = next_region;
+    }
+
+    reporter.report(CheckFinding::BrokenWalChain {
+        region_index: current_region,
+    });
+    Ok(records)
+}
+
+/// Records decoded from one WAL region image by [`scan_wal_region`].
+pub(crate) struct WalRegionScan {
+    /// Records that decoded.
+    pub(crate) records: u32,
+    /// First damaged offset not followed by any decoded record.
+    pub(crate) open_corrupt_offset: Option<usize>,
+    /// Region named by the `link` record that ends the region, if any.
+    pub(crate) next_region: Option<u32>,
+}
+
+/// Decodes the records of a main-WAL region image up to `limit`.
+///
+/// A damaged span followed by a decoded record other than `wal_recovery` is
+/// passed to `on_corrupt`; a span still open at the end is returned.
+pub(crate) fn scan_wal_region(
+    metadata: StorageMetadata,
+    region_bytes: &[u8],
+    limit: usize,
+    logical_scratch: &mut [u8],
+    mut on_corrupt: impl FnMut(usize),
+) -> Result<WalRegionScan, StorageRuntimeError> {
+    let granule = usize::try_from(metadata.wal_write_granule)
+        .map_err(|_| StorageRuntimeError::Startup(StartupError::LengthOverflow))?;
+    let mut offset = metadata
+        .wal_record_area_offset()
+        .map_err(|error| StorageRuntimeError::Startup(error.into()))?;
+    let limit = limit.min(region_bytes.len());
+    let mut scan = WalRegionScan {
+        records: 0,
+        open_corrupt_offset: None,
+        next_region: None,
+    };
+    while offset < limit && scan.next_region.is_none() {
+        if region_bytes[offset] == metadata.erased_byte {
+            break;
+        }
+        let decoded = if region_bytes[offset] == metadata.wal_record_magic {
+            decode_record(&region_bytes[offset..limit], metadata, logical_scratch).ok()
+        } else {
+            None
+        };
+        let Some(decoded) = decoded else {
+            scan.open_corrupt_offset.get_or_insert(offset);
+            offset = offset
+                .checked_add(granule)
+                .ok_or(StorageRuntimeError::Startup(StartupError::LengthOverflow))?;
+            continue;
+        };
+        scan.records = scan.records.saturating_add(1);
+        match decoded.record {
+            WalRecord::WalRecovery => scan.open_corrupt_offset = None,
+            record => {
+                if let Some(offset) = scan.open_corrupt_offset.take() {
+                    on_corrupt(offset);
+                }
+                if let WalRecord::Link {
+                    next_region_index, ..
+                } = record
+                {
+                    scan.next_region = Some(next_region_index);
+                }
+            }
+        }
+        offset = offset
+            .checked_add(decoded.encoded_len)
+            .ok_or(StorageRuntimeError::Startup(StartupError::LengthOverflow))?;
+    }
+    Ok(scan)
+}
+
+/// Why [`collect_owned_regions`] could not enumerate a collection.
+pub(crate) enum OwnedRegionsError {
+    /// The map manifest or one of its runs failed validation.
+    InvalidMap(MapStorageError),
+    /// The collection's committed regions could not be enumerated.
+    InvalidCommittedRegions,
+    /// Flash I/O failed or scratch overflowed.
+    Storage(StorageRuntimeError),
+}
+
+/// Collects every region a live collection's committed state names into
+/// `owned`, head included, passing each map manifest and run region to
+/// `visit_map_region`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn collect_owned_regions<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    collection: StartupCollection,
+    head_region: u32,
+    head_format: u16,
+    owned: &mut Vec<u32, REGION_COUNT>,
+    mut visit_map_region: impl FnMut(&MapHeadRegion),
+) -> Result<(), OwnedRegionsError> {
+    let collection_id = collection.collection_id();
+    match collection.collection_type() {
+        Some(CollectionType::MAP_CODE) if head_format == MAP_REGION_V2_FORMAT => {
+            // Awaiting migration; the single v2 region holds the whole map.
+            push_unique(owned, head_region).map_err(OwnedRegionsError::Storage)
+        }
+        Some(collection_type) if map::is_lsm_collection_type(collection_type) => {
+            map::visit_map_head_regions::<REGION_SIZE, IO, _>(
+                flash,
+                workspace,
+                storage.metadata(),
+                collection_id,
+                head_region,
+                |region| {
+                    visit_map_region(&region);
+                    if !owned.contains(&region.region_index) {
+                        owned.push(region.region_index).map_err(|_| {
+                            MapStorageError::InvalidManifest {
+                                collection_id,
+                                region_index: head_region,
+                            }
+                        })?;
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(OwnedRegionsError::InvalidMap)
+        }
+        _ => {
+            let result = crate::startup::collect_collection_committed_regions::<
+                REGION_SIZE,
+                REGION_COUNT,
+                IO,
+                MAX_COLLECTIONS,
+            >(
+                flash,
+                workspace,
+                storage.metadata(),
+                storage.collection_types(),
+                Some(collection),
+                owned,
+            );
+            push_unique(owned, head_region).map_err(OwnedRegionsError::Storage)?;
+            result.map_err(|_| OwnedRegionsError::InvalidCommittedRegions)
+        }
+    }
+}
+
+/// Claims the free-space metadata chain and every queued free region, and
+/// verifies that ready regions are erased.
+fn check_free_space<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+    S: CheckReportSink + ?Sized,
+>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    claimed: &mut Vec<u32, REGION_COUNT>,
+    reporter: &mut Reporter<'_, S>,
+) -> Result<(), StorageRuntimeError> {
+    let metadata = storage.metadata();
+    let free_space = storage.free_space();
+    let start = usize::try_from(free_space.allocation_head()).unwrap_or(usize::MAX);
+    let ready_end = usize::try_from(free_space.ready_boundary()).unwrap_or(usize::MAX);
+    let end = usize::try_from(free_space.append_tail()).unwrap_or(usize::MAX);
+    let entries = free_space.entries().get(start..end).unwrap_or(&[]);
+    for (position, region_index) in entries.iter().copied().enumerate() {
+        if region_index >= metadata.region_count || claimed.contains(&region_index) {
+            reporter.report(CheckFinding::DuplicateFreeRegion { region_index });
+            continue;
+        }
+        push_unique(claimed, region_index)?;
+        if start.saturating_add(position) >= ready_end || free_space.is_retired(region_index) {
+            continue;
+        }
+        let erased = flash.read_region(region_index, 0, REGION_SIZE, |bytes| {
+            bytes.iter().all(|byte| *byte == metadata.erased_byte)
+        })?;
+        if !erased {
+            reporter.report(CheckFinding::ReadyRegionNotErased { region_index });
+        }
+    }
+
+    for (regions, format) in [
+        (free_space.metadata_regions(), FREE_SPACE_V2_FORMAT),
+        (free_space.wear_regions(), FREE_SPACE_WEAR_V1_FORMAT),
+    ] {
+        for region_index in regions.iter().copied() {
+            match read_header::<IO>(flash, region_index)? {
+                None => reporter.report(CheckFinding::InvalidHeader { region_index }),
+                Some(header)
+                    if header.collection_id != CollectionId(0)
+                        || header.collection_format != format =>
+                {
+                    reporter.report(CheckFinding::UnexpectedFormat {
+                        region_index,
+                        collection_id: header.collection_id,
+                        collection_format: header.collection_format,
+                    })
+                }
+                Some(_) => {}
+            }
+            claim(storage, claimed, region_index, CollectionId(0), reporter)?;
+        }
+    }
+    Ok(())
+}
+
+/// Classifies every region no owner claimed.
+///
+/// Transaction logs and segments of collections whose committed state does
+/// not name every region they hold are identified by header alone.
+fn check_unclaimed_regions<
+    IO: FlashIo,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+    S: CheckReportSink + ?Sized,
+>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    claimed: &Vec<u32, REGION_COUNT>,
+    reporter: &mut Reporter<'_, S>,
+) -> Result<(), StorageRuntimeError> {
+    let metadata = storage.metadata();
+    for region_index in 0..metadata.region_count {
+        if claimed.contains(&region_index) || storage.is_region_retired(region_index) {
+            continue;
+        }
+        let erased = flash.read_region(region_index, 0, Header::ENCODED_LEN, |bytes| {
+            bytes.iter().all(|byte| *byte == metadata.erased_byte)
+        })?;
+        if erased {
+            continue;
+        }
+        let Some(header) = read_header::<IO>(flash, region_index)? else {
+            reporter.report(CheckFinding::InvalidHeader { region_index });
+            continue;
+        };
+        let attributed = if header.collection_id == CollectionId(0) {
+            header.collection_format == TRANSACTION_LOG_V2_FORMAT
+        } else {
+            storage
+                .collections()
+                .iter()
+                .find(|collection| collection.collection_id() == header.collection_id)
+                .filter(|collection| collection.basis() != StartupCollectionBasis::Dropped)
+                .and_then(|collection| collection.collection_type())
+                .is_some_and(|collection_type| !map::is_lsm_collection_type(collection_type))
+        };
+        if !attributed {
+            reporter.report(CheckFinding::LeakedRegion { region_index });
+        }
+    }
+    Ok(())
+}
+
+fn claim<const REGION_COUNT: usize, const MAX_COLLECTIONS: usize, S: CheckReportSink + ?Sized>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    claimed: &mut Vec<u32, REGION_COUNT>,
+    region_index: u32,
+    collection_id: CollectionId,
+    reporter: &mut Reporter<'_, S>,
+) -> Result<(), StorageRuntimeError> {
+    if storage.free_space().contains_free_region(region_index) {
+        reporter.report(CheckFinding::FreeAndOwned {
+            region_index,
+            collection_id,
+        });
+    } else if claimed.contains(&region_index) {
+        reporter.report(CheckFinding::RegionOwnedTwice { region_index });
+    } else {
+        push_unique(claimed, region_index)?;
+    }
+    Ok(())
+}
+
+fn push_unique<const REGION_COUNT: usize>(
+    regions: &mut Vec<u32, REGION_COUNT>,
+    region_index: u32,
+) -> Result<(), StorageRuntimeError> {
+    if !regions.contains(&region_index) {
+        regions
+            .push(region_index)
+            .map_err(|_| StorageRuntimeError::Startup(StartupError::LengthOverflow))?;
+    }
+    Ok(())
+}
+
+fn read_header<IO: FlashIo>(
+    flash: &mut IO,
+    region_index: u32,
+) -> Result<Option<Header>, StorageRuntimeError> {
+    Ok(
+        flash.read_region(region_index, 0, Header::ENCODED_LEN, |bytes| {
+            Header::decode(bytes).ok()
+        })?,
+    )
+}
+
+fn is_storage_format(collection_format: u16) -> bool {
+    matches!(
+        collection_format,
+        MAIN_WAL_V2_FORMAT
+            | TRANSACTION_LOG_V2_FORMAT
+            | FREE_SPACE_V2_FORMAT
+            | FREE_SPACE_WEAR_V1_FORMAT
+    )
+}
+
+fn map_error_region(error: MapStorageError) -> Option<u32> {
+    match error {
+        MapStorageError::UnsupportedRegionFormat { region_index, .. }
+        | MapStorageError::InvalidManifest { region_index, .. }
+        | MapStorageError::InvalidRun { region_index, .. } => Some(region_index),
+        _ => None,
+    }
+}
@@ -0,0 +1,410 @@
+use super::*;
+
+use crate::collections::cell::Cell;
+use crate::collections::object_log::{ObjectLog, ObjectLogMemory};
+use crate::{MockFlash, MockOperation, Storage, StorageMetadata, MAP_RUN_V2_FORMAT};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestStorage<'a> = Storage<'a, 'static, TestFlash, REGION_SIZE, REGION_COUNT>;
+type Findings = Vec<CheckFinding, 32>;
+
+fn check(storage: &mut TestStorage<'_>) -> (CheckSummary, Findings) {
+    let mut findings = Findings::new();
+    let summary = storage.check(&mut findings).unwrap();
+    assert_eq!(summary.findings as usize, findings.len());
+    (summary, findings)
+}
+
+fn region_copy(storage: &mut TestStorage<'_>, region_index: u32) -> [u8; REGION_SIZE] {
+    storage.with_io_workspace(|flash, _| *flash.region_bytes(region_index).unwrap())
+}
+
+fn overwrite_region(storage: &mut TestStorage<'_>, region_index: u32, bytes: &[u8]) {
+    storage
+        .with_io_workspace(|flash, _| flash.write_region(region_index, 0, bytes))
+        .unwrap();
+}
+
+fn read_u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Returns the byte range of each run entry in a manifest payload.
+fn manifest_run_entries(payload: &[u8]) -> std::vec::Vec<core::ops::Range<usize>> {
+    let run_count = read_u32_at(payload, 0) as usize;
+    let mut offset = 4;
+    let mut entries = std::vec::Vec::new();
+    for _ in 0..run_count {
+        let start = offset;
+        let lower_key_len = read_u32_at(payload, start + 20) as usize;
+        let upper_key_len = read_u32_at(payload, start + 24) as usize;
+        offset = start + 28 + lower_key_len + upper_key_len;
+        entries.push(start..offset);
+    }
+    entries
+}
+
+//= spec/check.md#pass
+//= type=test
+//# `RING-CHECK-001` `Storage::check` MUST NOT write, erase, or sync flash,
+//# MUST send every finding to the sink instead of stopping at the first, and
+//# MUST report no findings for a store written only through the public API.
+#[test]
+fn requirement_check_is_read_only_and_clean_for_public_writes() {
+    let mut flash = TestFlash::new(0xff);
+    {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut cell = Cell::new(&mut storage, std::vec![0u8; 4]).unwrap();
+        cell.set(&mut storage, std::vec![1u8; 300]).unwrap();
+        let mut memory = ObjectLogMemory::<REGION_SIZE, 16, 16>::new();
+        let mut log = ObjectLog::new(&mut storage, &mut memory, b"log").unwrap();
+        let mut scratch = [0u8; REGION_SIZE];
+        log.append(&mut storage, b"object", &mut scratch).unwrap();
+        log.flush(&mut storage).unwrap();
+        crate::test_flushed_map(&mut storage);
+
+        let (summary, findings) = check(&mut storage);
+        assert!(summary.is_clean(), "{findings:?}");
+        assert_eq!(summary.regions_checked, REGION_COUNT as u32);
+        assert!(summary.wal_records > 0);
+    }
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    storage.with_io_workspace(|flash, _| flash.clear_operations());
+    let mut findings = std::vec::Vec::new();
+    let summary = storage
+        .check(&mut |finding| findings.push(finding))
+        .unwrap();
+    assert!(summary.is_clean(), "{findings:?}");
+    let operations = storage.with_io_workspace(|flash, _| flash.operations().to_vec());
+    assert!(!operations.is_empty());
+    assert!(operations.iter().all(|operation| matches!(
+        operation,
+        MockOperation::ReadMetadata | MockOperation::ReadRegion { .. }
+    )));
+
+    // Two independent faults are both reported.
+    let wal_head = storage.wal_head();
+    let mut bytes = region_copy(&mut storage, wal_head);
+    bytes[1] ^= 0x01;
+    overwrite_region(&mut storage, wal_head, &bytes);
+    let metadata = storage.metadata();
+    storage
+        .with_io_workspace(|flash, _| {
+            flash.write_metadata(StorageMetadata {
+                min_free_regions: metadata.min_free_regions + 1,
+                ..metadata
+            })
+        })
+        .unwrap();
+    let (summary, findings) = check(&mut storage);
+    assert!(findings.contains(&CheckFinding::MetadataMismatch));
+    assert!(findings.contains(&CheckFinding::InvalidHeader {
+        region_index: wal_head
+    }));
+    assert!(summary.findings >= 2);
+}
+
+//= spec/check.md#pass
+//= type=test
+//# `RING-CHECK-002` Every region the pass reaches MUST have a header whose
+//# CRC verifies, or the pass MUST report `InvalidHeader`; a header whose
+//# collection id or format code its owner never writes MUST be reported as
+//# `UnexpectedFormat`.
+#[test]
+fn requirement_check_verifies_header_crc_and_format() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let map_id = crate::test_flushed_map(&mut storage);
+    let manifest = crate::test_head_region(&storage, map_id);
+
+    let mut bytes = region_copy(&mut storage, manifest);
+    bytes[Header::ENCODED_LEN - 1] ^= 0x01;
+    overwrite_region(&mut storage, manifest, &bytes);
+
+    let wal_head = storage.wal_head();
+    let mut bytes = region_copy(&mut storage, wal_head);
+    let header = Header::decode(&bytes[..Header::ENCODED_LEN]).unwrap();
+    Header {
+        collection_format: TRANSACTION_LOG_V2_FORMAT,
+        ..header
+    }
+    .encode_into(&mut bytes[..Header::ENCODED_LEN])
+    .unwrap();
+    overwrite_region(&mut storage, wal_head, &bytes);
+
+    let (_, findings) = check(&mut storage);
+    assert!(findings.contains(&CheckFinding::InvalidHeader {
+        region_index: manifest
+    }));
+    assert!(findings.contains(&CheckFinding::UnexpectedFormat {
+        region_index: wal_head,
+        collection_id: CollectionId(0),
+        collection_format: TRANSACTION_LOG_V2_FORMAT,
+    }));
+}
+
+//= spec/check.md#pass
+//= type=test
+//# `RING-CHECK-003` The pass MUST follow the WAL from head to tail through
+//# `link` records, and MUST report a record that fails to decode as
+//# `CorruptWalRecord` unless a later `wal_recovery` record closes the gap or
+//# it is the open torn tail replay left for the next append.
+#[test]
+fn requirement_check_reports_corrupt_wal_records() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut cell = Cell::new(&mut storage, 0u32).unwrap();
+    let mut value = 0u32;
+    while storage.wal_head() == storage.wal_tail() {
+        value += 1;
+        cell.set(&mut storage, value).unwrap();
+    }
+    let (summary, findings) = check(&mut storage);
+    assert!(summary.is_clean(), "{findings:?}");
+
+    let wal_head = storage.wal_head();
+    let record_offset = storage.metadata().wal_record_area_offset().unwrap();
+    let mut bytes = region_copy(&mut storage, wal_head);
+    bytes[record_offset + 1] ^= 0x01;
+    overwrite_region(&mut storage, wal_head, &bytes);
+
+    let (summary, findings) = check(&mut storage);
+    assert_eq!(
+        findings.as_slice(),
+        &[CheckFinding::CorruptWalRecord {
+            region_index: wal_head,
+            offset: record_offset,
+        }]
+    );
+    assert!(summary.wal_records > 0);
+}
+
+//= spec/check.md#pass
+//= type=test
+//# `RING-CHECK-004` The free-space queue between the allocation head and the
+//# append tail MUST list each region once, every ready entry not retired MUST
+//# read as erased, and a region both queued and claimed by the WAL,
+//# free-space metadata, or a live collection MUST be reported as
+//# `FreeAndOwned`.
+#[test]
+fn requirement_check_validates_free_space_queue() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let map_id = crate::test_flushed_map(&mut storage);
+    let manifest = crate::test_head_region(&storage, map_id);
+
+    let (allocation_head, ready_boundary, ..) = storage.free_space_cursors();
+    assert!(allocation_head + 1 < ready_boundary);
+    let queued = storage.free_space_entries()[ready_boundary as usize - 1];
+    assert_ne!(Some(queued), storage.ready_region());
+    let bytes = region_copy(&mut storage, manifest);
+    overwrite_region(&mut storage, queued, &bytes);
+    storage
+        .append_head(map_id, CollectionType::MAP_CODE, queued)
+        .unwrap();
+
+    let (_, findings) = check(&mut storage);
+    assert!(findings.contains(&CheckFinding::ReadyRegionNotErased {
+        region_index: queued
+    }));
+    assert!(findings.contains(&CheckFinding::FreeAndOwned {
+        region_index: queued,
+        collection_id: map_id,
+    }));
+}
+
+//= spec/check.md#reachability
+//= type=test
+//# `RING-CHECK-005` A programmed region that is neither queued, retired,
+//# storage-owned, reachable from a live collection, nor attributed to a
+//# live non-map collection or a transaction log by its header MUST be
+//# reported as `LeakedRegion`.
+#[test]
+fn requirement_check_reports_leaked_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let map_id = crate::test_flushed_map(&mut storage);
+
+    // An erased reservation is not a leak.
+    let reserved = storage.reserve_next_region().unwrap();
+    let (summary, findings) = check(&mut storage);
+    assert!(summary.is_clean(), "{findings:?}");
+
+    let leaked = storage
+        .write_committed_region(reserved, map_id, MAP_RUN_V2_FORMAT, &[0u8; 16])
+        .unwrap();
+    let unknown = storage.reserve_next_region().unwrap();
+    let unknown = storage
+        .write_committed_region(unknown, CollectionId(0x7777), 1, &[0u8; 16])
+        .unwrap();
+
+    let (_, findings) = check(&mut storage);
+    assert_eq!(
+        findings.as_slice(),
+        &[
+            CheckFinding::LeakedRegion {
+                region_index: leaked.min(unknown)
+            },
+            CheckFinding::LeakedRegion {
+                region_index: leaked.max(unknown)
+            },
+        ]
+    );
+}
+
+//= spec/check.md#reachability
+//= type=test
+//# `RING-CHECK-006` Each live map's manifest MUST decode with every run
+//# chain matching its recorded generation, or the pass MUST report
+//# `InvalidMapManifest`; runs MUST be listed newest generation first, or the
+//# pass MUST report `UnorderedMapRuns`.
+#[test]
+fn requirement_check_validates_map_manifests_and_run_order() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let map_id = crate::test_flushed_map(&mut storage);
+    let manifest = crate::test_head_region(&storage, map_id);
+    let original = region_copy(&mut storage, manifest);
+    let entries = manifest_run_entries(&original[Header::ENCODED_LEN..]);
+    assert_eq!(entries.len(), 2);
+
+    // List the older run first.
+    let mut swapped = original;
+    let payload = &mut swapped[Header::ENCODED_LEN..];
+    let (newer, older) = (entries[0].clone(), entries[1].clone());
+    let mut reordered = payload[older.clone()].to_vec();
+    reordered.extend_from_slice(&original[Header::ENCODED_LEN..][newer.clone()]);
+    payload[newer.start..older.end].copy_from_slice(&reordered);
+    overwrite_region(&mut storage, manifest, &swapped);
+    let newer_first_region = read_u32_at(&original[Header::ENCODED_LEN..], newer.start + 8);
+    let (_, findings) = check(&mut storage);
+    assert_eq!(
+        findings.as_slice(),
+        &[CheckFinding::UnorderedMapRuns {
+            collection_id: map_id,
+            region_index: newer_first_region,
+        }]
+    );
+
+    // A run with no segments cannot be walked.
+    let mut empty_run = original;
+    let region_count_offset = Header::ENCODED_LEN + entries[0].start + 12;
+    empty_run[region_count_offset..region_count_offset + 4].copy_from_slice(&0u32.to_le_bytes());
+    overwrite_region(&mut storage, manifest, &empty_run);
+    let (_, findings) = check(&mut storage);
+    assert_eq!(
+        findings.first(),
+        Some(&CheckFinding::InvalidMapManifest {
+            collection_id: map_id,
+            region_index: manifest,
+        })
+    );
+    assert!(findings
+        .iter()
+        .skip(1)
+        .all(|finding| matches!(finding, CheckFinding::LeakedRegion { .. })));
+}
+
+//= spec/check.md#reachability
+//= type=test
+//# `RING-CHECK-007` The metadata read back from flash MUST equal the
+//# metadata the store opened with, or the pass MUST report
+//# `MetadataMismatch`.
+#[test]
+fn requirement_check_compares_stored_metadata() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let metadata = storage.metadata();
+    storage
+        .with_io_workspace(|flash, _| {
+            flash.write_metadata(StorageMetadata {
+                wal_record_magic: metadata.wal_record_magic ^ 0xff,
+                ..metadata
+            })
+        })
+        .unwrap();
+    let (_, findings) = check(&mut storage);
+    assert_eq!(findings.as_slice(), &[CheckFinding::MetadataMismatch]);
+}
+
+//= spec/check.md#unopened-stores
+//= type=test
+//# `RING-CHECK-009` `Storage::check_regions` MUST NOT write to flash, MUST
+//# report `MetadataMismatch` when the stored metadata is unreadable or names
+//# another geometry, `InvalidHeader` for each region whose header is neither
+//# valid nor erased, and `CorruptWalRecord` for each damaged span a later
+//# record follows inside a main-WAL region.
+#[test]
+fn requirement_check_regions_reports_without_opening() {
+    let mut flash = TestFlash::new(0xff);
+    let (manifest, wal_head, metadata) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let map_id = crate::test_flushed_map(&mut storage);
+        let mut cell = Cell::new(&mut storage, 0u32).unwrap();
+        let mut value = 0u32;
+        while storage.wal_head() == storage.wal_tail() {
+            value += 1;
+            cell.set(&mut storage, value).unwrap();
+        }
+        (
+            crate::test_head_region(&storage, map_id),
+            storage.wal_head(),
+            storage.metadata(),
+        )
+    };
+    let check_regions = |flash: &mut TestFlash| {
+        let mut findings = Findings::new();
+        flash.clear_operations();
+        let summary =
+            TestStorage::check_regions(flash, crate::test_storage_memory(), &mut findings).unwrap();
+        assert!(flash.operations().iter().all(|operation| matches!(
+            operation,
+            MockOperation::ReadMetadata | MockOperation::ReadRegion { .. }
+        )));
+        assert_eq!(summary.findings as usize, findings.len());
+        assert_eq!(summary.regions_checked, REGION_COUNT as u32);
+        (summary, findings)
+    };
+    let (summary, findings) = check_regions(&mut flash);
+    assert!(summary.is_clean(), "{findings:?}");
+    assert!(summary.wal_records > 0);
+
+    let record_offset = metadata.wal_record_area_offset().unwrap();
+    let mut bytes = *flash.region_bytes(wal_head).unwrap();
+    bytes[record_offset + 1] ^= 0x01;
+    flash.write_region(wal_head, 0, &bytes).unwrap();
+    let mut bytes = *flash.region_bytes(manifest).unwrap();
+    bytes[Header::ENCODED_LEN - 1] ^= 0x01;
+    flash.write_region(manifest, 0, &bytes).unwrap();
+    let (_, findings) = check_regions(&mut flash);
+    assert_eq!(findings.len(), 2, "{findings:?}");
+    assert!(findings.contains(&CheckFinding::CorruptWalRecord {
+        region_index: wal_head,
+        offset: record_offset,
+    }));
+    assert!(findings.contains(&CheckFinding::InvalidHeader {
+        region_index: manifest
+    }));
+
+    flash
+        .write_metadata(StorageMetadata {
+            region_count: REGION_COUNT as u32 / 2,
+            ..metadata
+        })
+        .unwrap();
+    let (summary, findings) = check_regions(&mut flash);
+    assert_eq!(
+        findings.as_slice(),
+        &[
+            CheckFinding::MetadataMismatch,
+            CheckFinding::InvalidHeader {
+                region_index: manifest
+            },
+        ]
+    );
+    assert_eq!(summary.wal_records, 0);
+}
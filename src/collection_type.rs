@@ -0,0 +1,167 @@
+//! Extension points for collection types defined outside Borromean.
+//!
+//! Built-in collections are wired into the storage engine directly. Downstream
+//! crates instead implement [`CollectionTypePlugin`] for a code in
+//! [`EXTERNAL_COLLECTION_TYPE_CODES`] and register it on the
+//! [`crate::StorageMemory`] they open storage with. The engine then accepts the
+//! code in its type gates and calls the plugin hooks wherever it would
+//! otherwise special-case a built-in collection.
+
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use crate::{CollectionId, WalRecord};
+
+#[cfg(test)]
+mod tests;
+
+/// Collection type codes reserved for Borromean's own collections.
+pub const BUILTIN_COLLECTION_TYPE_CODES: RangeInclusive<u16> = 0x0000..=0x7fff;
+
+/// Collection type codes available to downstream collection types.
+///
+/// `0xffff` is excluded because replay uses it to report a live collection
+/// without a known type.
+pub const EXTERNAL_COLLECTION_TYPE_CODES: RangeInclusive<u16> = 0x8000..=0xfffe;
+
+/// Error returned by a [`CollectionTypePlugin`] hook that rejects durable
+/// state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectionPluginError;
+
+/// Errors returned while registering collection type plugins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectionTypeRegistryError {
+    /// The plugin code lies outside [`EXTERNAL_COLLECTION_TYPE_CODES`].
+    ReservedCode(u16),
+    /// More than one plugin claimed the same code.
+    DuplicateCode(u16),
+}
+
+/// Storage-engine hooks for a downstream collection type.
+///
+/// Handles for the collection write through the public `Storage` facade
+/// (`append_new_collection`, `append_update`, `append_snapshot`,
+/// `write_committed_region`, `append_head`) and rebuild their state with
+/// `Storage::visit_wal_records`. The hooks below cover the points where the
+/// engine itself must understand the collection.
+pub trait CollectionTypePlugin: Sync {
+    /// Returns the stable on-disk collection type code.
+    fn code(&self) -> u16;
+
+    /// Returns the snapshot payload selected as the basis of a collection
+    /// that has none when WAL reclaim retires its `new_collection` record.
+    fn empty_snapshot(&self) -> &'static [u8];
+
+    /// Checks one retained WAL record addressed to a live collection of this
+    /// type while storage opens.
+    ///
+    /// Records arrive in WAL order, including records of transactions that
+    /// later roll back.
+    fn replay(
+        &self,
+        collection_id: CollectionId,
+        record: WalRecord<'_>,
+    ) -> Result<(), CollectionPluginError> {
+        let _ = (collection_id, record);
+        Ok(())
+    }
+
+    /// Checks the committed region a live collection's `head` selects as its
+    /// basis while storage opens.
+    fn validate_head(
+        &self,
+        collection_id: CollectionId,
+        region_index: u32,
+        collection_format: u16,
+    ) -> Result<(), CollectionPluginError> {
+        let _ = (collection_id, region_index, collection_format);
+        Ok(())
+    }
+
+    /// Reports every region other than `head_region` that a committed head
+    /// keeps live, such as segments listed in a manifest payload.
+    ///
+    /// Transaction recovery walks the bases on both sides of an interrupted
+    /// transaction through this hook and rejects the store when it fails.
+    /// [`crate::Storage::drop_collection`] and
+    /// [`crate::Storage::drop_named`] free every reported region with the
+    /// head when the collection is dropped.
+    fn referenced_regions(
+        &self,
+        collection_id: CollectionId,
+        head_region: u32,
+        head_payload: &[u8],
+        referenced: &mut dyn FnMut(u32) -> Result<(), CollectionPluginError>,
+    ) -> Result<(), CollectionPluginError> {
+        let _ = (collection_id, head_region, head_payload, referenced);
+        Ok(())
+    }
+}
+
+/// Set of collection type plugins registered on storage memory.
+#[derive(Clone, Copy, Default)]
+pub struct CollectionTypeRegistry {
+    plugins: &'static [&'static dyn CollectionTypePlugin],
+}
+
+impl CollectionTypeRegistry {
+    /// Builds a registry after checking every code is external and unique.
+    pub fn new(
+        plugins: &'static [&'static dyn CollectionTypePlugin],
+    ) -> Result<Self, CollectionTypeRegistryError> {
+        for (index, plugin) in plugins.iter().enumerate() {
+            let code = plugin.code();
+            if !EXTERNAL_COLLECTION_TYPE_CODES.contains(&code) {
+                return Err(CollectionTypeRegistryError::ReservedCode(code));
+            }
+            if plugins[..index].iter().any(|other| other.code() == code) {
+                return Err(CollectionTypeRegistryError::DuplicateCode(code));
+            }
+        }
+        Ok(Self { plugins })
+    }
+
+    /// Returns the registered plugins.
+    pub fn plugins(&self) -> &'static [&'static dyn CollectionTypePlugin] {
+        self.plugins
+    }
+
+    /// Returns the plugin registered for `code`, if any.
+    pub fn get(&self, code: u16) -> Option<&'static dyn CollectionTypePlugin> {
+        self.plugins
+            .iter()
+            .copied()
+            .find(|plugin| plugin.code() == code)
+    }
+
+    /// Returns whether a plugin is registered for `code`.
+    pub fn contains(&self, code: u16) -> bool {
+        self.get(code).is_some()
+    }
+
+    /// Returns whether no plugins are registered.
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+}
+
+impl fmt::Debug for CollectionTypeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.plugins.iter().map(|plugin| plugin.code()))
+            .finish()
+    }
+}
+
+/// Returns the collection a record addresses, for records a plugin replays.
+pub(crate) fn replayed_collection_id(record: WalRecord<'_>) -> Option<CollectionId> {
+    match record {
+        WalRecord::NewCollection { collection_id, .. }
+        | WalRecord::Update { collection_id, .. }
+        | WalRecord::Snapshot { collection_id, .. }
+        | WalRecord::Head { collection_id, .. }
+        | WalRecord::DropCollection { collection_id } => Some(collection_id),
+        _ => None,
+    }
+}
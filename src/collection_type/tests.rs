@@ -0,0 +1,449 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::*;
+
+use crate::{
+    MockFlash, StartupCollectionBasis, StartupError, Storage, StorageOpenError, StorageRuntimeError,
+};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+const COUNTER_CODE: u16 = 0x8001;
+const COUNTER_HEAD_FORMAT: u16 = 0x8001;
+const COUNTER_SEGMENT_FORMAT: u16 = 0x8002;
+const EMPTY_COUNTER: [u8; 8] = [0; 8];
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+
+/// Counter collection whose updates and snapshots are little-endian `u64`s
+/// and whose head payload lists referenced segment regions.
+struct CounterPlugin {
+    head_format: u16,
+    referenced: AtomicUsize,
+}
+
+impl CounterPlugin {
+    const fn new(head_format: u16) -> Self {
+        Self {
+            head_format,
+            referenced: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl CollectionTypePlugin for CounterPlugin {
+    fn code(&self) -> u16 {
+        COUNTER_CODE
+    }
+
+    fn empty_snapshot(&self) -> &'static [u8] {
+        &EMPTY_COUNTER
+    }
+
+    fn replay(
+        &self,
+        _collection_id: CollectionId,
+        record: WalRecord<'_>,
+    ) -> Result<(), CollectionPluginError> {
+        match record {
+            WalRecord::Update { payload, .. } | WalRecord::Snapshot { payload, .. }
+                if payload.len() != EMPTY_COUNTER.len() =>
+            {
+                Err(CollectionPluginError)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_head(
+        &self,
+        _collection_id: CollectionId,
+        _region_index: u32,
+        collection_format: u16,
+    ) -> Result<(), CollectionPluginError> {
+        if collection_format == self.head_format {
+            Ok(())
+        } else {
+            Err(CollectionPluginError)
+        }
+    }
+
+    fn referenced_regions(
+        &self,
+        _collection_id: CollectionId,
+        _head_region: u32,
+        head_payload: &[u8],
+        referenced: &mut dyn FnMut(u32) -> Result<(), CollectionPluginError>,
+    ) -> Result<(), CollectionPluginError> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(head_payload.get(..4).ok_or(CollectionPluginError)?);
+        self.referenced.fetch_add(1, Ordering::Relaxed);
+        referenced(u32::from_le_bytes(bytes))
+    }
+}
+
+static COUNTER: CounterPlugin = CounterPlugin::new(COUNTER_HEAD_FORMAT);
+static STRICT_COUNTER: CounterPlugin = CounterPlugin::new(0x8fff);
+static RECOVERY_COUNTER: CounterPlugin = CounterPlugin::new(COUNTER_HEAD_FORMAT);
+static COUNTER_TYPES: [&dyn CollectionTypePlugin; 1] = [&COUNTER];
+static STRICT_COUNTER_TYPES: [&dyn CollectionTypePlugin; 1] = [&STRICT_COUNTER];
+static RECOVERY_COUNTER_TYPES: [&dyn CollectionTypePlugin; 1] = [&RECOVERY_COUNTER];
+
+fn open_storage<'db>(
+    flash: &'db mut TestFlash,
+    plugins: &'static [&'static dyn CollectionTypePlugin],
+) -> Result<Storage<'db, 'static, TestFlash, REGION_SIZE, REGION_COUNT>, StorageOpenError> {
+    Storage::<_, REGION_SIZE, REGION_COUNT>::open(flash, crate::test_plugin_storage_memory(plugins))
+}
+
+fn create_counter(
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+) -> CollectionId {
+    let collection_id = storage.allocate_collection_id().unwrap();
+    storage
+        .append_new_collection(collection_id, COUNTER_CODE)
+        .unwrap();
+    collection_id
+}
+
+/// Rebuilds a counter the way a downstream handle would.
+fn counter_value(
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    collection_id: CollectionId,
+) -> u64 {
+    let mut value = 0u64;
+    storage
+        .visit_wal_records::<(), _>(|record| {
+            match record {
+                WalRecord::Snapshot {
+                    collection_id: seen,
+                    payload,
+                    ..
+                } if seen == collection_id => {
+                    value = u64::from_le_bytes(payload.try_into().unwrap());
+                }
+                WalRecord::Update {
+                    collection_id: seen,
+                    payload,
+                } if seen == collection_id => {
+                    value += u64::from_le_bytes(payload.try_into().unwrap());
+                }
+                _ => {}
+            }
+            Ok(())
+        })
+        .unwrap();
+    value
+}
+
+fn write_counter_head(
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    collection_id: CollectionId,
+    segment: u32,
+) -> u32 {
+    let head = storage.reserve_next_region().unwrap();
+    storage
+        .write_committed_region(
+            head,
+            collection_id,
+            COUNTER_HEAD_FORMAT,
+            &segment.to_le_bytes(),
+        )
+        .unwrap()
+}
+
+//= spec/collection-types.md#type-codes
+//= type=test
+//# `RING-PLUGIN-001` `CollectionTypeRegistry::new` MUST reject a plugin code
+//# outside `0x8000..=0xfffe` and a code claimed by more than one plugin.
+#[test]
+fn requirement_plugin_registry_rejects_reserved_and_duplicate_codes() {
+    struct Fixed(u16);
+    impl CollectionTypePlugin for Fixed {
+        fn code(&self) -> u16 {
+            self.0
+        }
+        fn empty_snapshot(&self) -> &'static [u8] {
+            &[]
+        }
+    }
+    static BUILTIN: Fixed = Fixed(crate::CollectionType::MAP_CODE);
+    static UNKNOWN: Fixed = Fixed(0xffff);
+    static EXTERNAL: Fixed = Fixed(0x8000);
+    static LAST: Fixed = Fixed(0xfffe);
+    static RESERVED: [&dyn CollectionTypePlugin; 2] = [&EXTERNAL, &BUILTIN];
+    static SENTINEL: [&dyn CollectionTypePlugin; 1] = [&UNKNOWN];
+    static DUPLICATE: [&dyn CollectionTypePlugin; 3] = [&EXTERNAL, &LAST, &EXTERNAL];
+    static VALID: [&dyn CollectionTypePlugin; 2] = [&EXTERNAL, &LAST];
+
+    assert_eq!(
+        CollectionTypeRegistry::new(&RESERVED).unwrap_err(),
+        CollectionTypeRegistryError::ReservedCode(crate::CollectionType::MAP_CODE)
+    );
+    assert_eq!(
+        CollectionTypeRegistry::new(&SENTINEL).unwrap_err(),
+        CollectionTypeRegistryError::ReservedCode(0xffff)
+    );
+    assert_eq!(
+        CollectionTypeRegistry::new(&DUPLICATE).unwrap_err(),
+        CollectionTypeRegistryError::DuplicateCode(0x8000)
+    );
+
+    let registry = CollectionTypeRegistry::new(&VALID).unwrap();
+    assert_eq!(registry.plugins().len(), 2);
+    assert!(registry.contains(0xfffe));
+    assert!(!registry.contains(0x8001));
+    assert!(CollectionTypeRegistry::default().is_empty());
+}
+
+//= spec/collection-types.md#type-codes
+//= type=test
+//# `RING-PLUGIN-002` Storage MUST accept a registered plugin code wherever it
+//# accepts a built-in collection type, and MUST reject an unregistered
+//# external code when a collection is created or storage is opened.
+#[test]
+fn requirement_plugin_codes_pass_storage_type_gates() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_plugin_storage(&mut flash, &[]);
+        let id = storage.allocate_collection_id().unwrap();
+        assert!(matches!(
+            storage.append_new_collection(id, COUNTER_CODE),
+            Err(StorageRuntimeError::UnsupportedCollectionType(COUNTER_CODE))
+        ));
+        id
+    };
+
+    {
+        let mut storage = open_storage(&mut flash, &COUNTER_TYPES).unwrap();
+        storage.append_new_collection(id, COUNTER_CODE).unwrap();
+        storage.append_update(id, &5u64.to_le_bytes()).unwrap();
+        storage
+            .append_snapshot(id, COUNTER_CODE, &7u64.to_le_bytes())
+            .unwrap();
+        storage.append_update(id, &3u64.to_le_bytes()).unwrap();
+        assert_eq!(counter_value(&mut storage, id), 10);
+    }
+
+    assert!(matches!(
+        open_storage(&mut flash, &[]),
+        Err(StorageOpenError::Runtime(StorageRuntimeError::Startup(
+            StartupError::UnsupportedLiveCollectionType(COUNTER_CODE)
+        )))
+    ));
+    let mut storage = open_storage(&mut flash, &COUNTER_TYPES).unwrap();
+    assert_eq!(counter_value(&mut storage, id), 10);
+}
+
+//= spec/collection-types.md#engine-hooks
+//= type=test
+//# `RING-PLUGIN-003` WAL reclaim that retires the `new_collection` record of a
+//# plugin collection with an empty basis MUST retain the plugin's empty
+//# snapshot as that collection's basis.
+#[test]
+fn requirement_plugin_empty_basis_survives_wal_reclaim() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_plugin_storage(&mut flash, &COUNTER_TYPES);
+        let id = create_counter(&mut storage);
+        storage.append_update(id, &4u64.to_le_bytes()).unwrap();
+        let filler = create_counter(&mut storage);
+        while storage.wal_head() == storage.wal_tail() {
+            storage.append_update(filler, &1u64.to_le_bytes()).unwrap();
+        }
+        storage.reclaim_wal_head().unwrap();
+
+        let mut empty_snapshots = 0usize;
+        storage
+            .visit_wal_records::<(), _>(|record| {
+                if let WalRecord::Snapshot {
+                    collection_id,
+                    collection_type,
+                    payload,
+                } = record
+                {
+                    if collection_id == id {
+                        assert_eq!(collection_type, COUNTER_CODE);
+                        assert_eq!(payload, EMPTY_COUNTER.as_slice());
+                        empty_snapshots += 1;
+                    }
+                }
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(empty_snapshots, 1);
+        id
+    };
+
+    let mut storage = open_storage(&mut flash, &COUNTER_TYPES).unwrap();
+    let collection = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == id)
+        .copied()
+        .unwrap();
+    assert_eq!(collection.basis(), StartupCollectionBasis::WalSnapshot);
+    assert_eq!(counter_value(&mut storage, id), 4);
+}
+
+//= spec/collection-types.md#engine-hooks
+//= type=test
+//# `RING-PLUGIN-004` Open MUST pass each retained WAL record addressed to a
+//# live plugin collection to the plugin's replay hook in WAL order, and MUST
+//# fail when the hook rejects a record.
+#[test]
+fn requirement_plugin_replay_hook_checks_retained_records() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_plugin_storage(&mut flash, &COUNTER_TYPES);
+        let id = create_counter(&mut storage);
+        storage.append_update(id, &2u64.to_le_bytes()).unwrap();
+        id
+    };
+    assert!(open_storage(&mut flash, &COUNTER_TYPES).is_ok());
+
+    {
+        let mut storage = open_storage(&mut flash, &COUNTER_TYPES).unwrap();
+        storage.append_update(id, b"bad").unwrap();
+    }
+    assert!(matches!(
+        open_storage(&mut flash, &COUNTER_TYPES),
+        Err(StorageOpenError::CollectionReplayRejected {
+            collection_id,
+            collection_type: COUNTER_CODE,
+        }) if collection_id == id
+    ));
+}
+
+//= spec/collection-types.md#engine-hooks
+//= type=test
+//# `RING-PLUGIN-005` Open MUST reject a live plugin head that the plugin's
+//# head hook rejects, and transaction recovery MUST collect the regions a
+//# plugin head references through the plugin's region hook.
+#[test]
+fn requirement_plugin_head_and_region_hooks() {
+    let mut flash = TestFlash::new(0xff);
+    {
+        let mut storage = crate::test_format_plugin_storage(&mut flash, &RECOVERY_COUNTER_TYPES);
+        let id = create_counter(&mut storage);
+        let segment = storage.reserve_next_region().unwrap();
+        let segment = storage
+            .write_committed_region(segment, id, COUNTER_SEGMENT_FORMAT, &[1])
+            .unwrap();
+        let head = write_counter_head(&mut storage, id, segment);
+        storage.append_head(id, COUNTER_CODE, head).unwrap();
+
+        // Replace the head in a transaction that commits but never finishes.
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, _>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                id,
+            )
+            .unwrap();
+        let head = write_counter_head(&mut storage, id, segment);
+        storage
+            .memory
+            .state
+            .append_head::<REGION_SIZE, REGION_COUNT, _>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                id,
+                COUNTER_CODE,
+                head,
+            )
+            .unwrap();
+        storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, _>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                id,
+            )
+            .unwrap();
+    }
+
+    assert_eq!(RECOVERY_COUNTER.referenced.load(Ordering::Relaxed), 0);
+    assert!(open_storage(&mut flash, &RECOVERY_COUNTER_TYPES).is_ok());
+    assert_eq!(RECOVERY_COUNTER.referenced.load(Ordering::Relaxed), 2);
+
+    assert!(open_storage(&mut flash, &COUNTER_TYPES).is_ok());
+    assert!(matches!(
+        open_storage(&mut flash, &STRICT_COUNTER_TYPES),
+        Err(StorageOpenError::Runtime(StorageRuntimeError::Startup(
+            StartupError::InvalidCommittedRegionHead { .. }
+        )))
+    ));
+}
+
+//= spec/collection-types.md#engine-hooks
+//= type=test
+//# `RING-PLUGIN-006` Dropping a plugin collection whose basis is a committed
+//# region MUST free that region and every region the plugin's region hook
+//# reports for it, and the drop and the frees MUST commit in one collection
+//# transaction.
+#[test]
+fn requirement_plugin_drop_frees_referenced_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let (segment, head) = {
+        let mut storage = crate::test_format_plugin_storage(&mut flash, &COUNTER_TYPES);
+        let id = create_counter(&mut storage);
+        let segment = storage.reserve_next_region().unwrap();
+        let segment = storage
+            .write_committed_region(segment, id, COUNTER_SEGMENT_FORMAT, &[1])
+            .unwrap();
+        let head = write_counter_head(&mut storage, id, segment);
+        storage.append_head(id, COUNTER_CODE, head).unwrap();
+        assert!(!storage.runtime().free_space().contains_free_region(segment));
+
+        assert_eq!(storage.drop_collection(id).unwrap(), Some(head));
+        let free_space = storage.runtime().free_space();
+        assert!(free_space.contains_free_region(segment));
+        assert!(free_space.contains_free_region(head));
+        assert!(!storage.runtime().transaction_open_for(id));
+        (segment, head)
+    };
+
+    let storage = open_storage(&mut flash, &COUNTER_TYPES).unwrap();
+    let free_space = storage.runtime().free_space();
+    assert!(free_space.contains_free_region(segment));
+    assert!(free_space.contains_free_region(head));
+    assert!(storage
+        .runtime()
+        .collections()
+        .iter()
+        .all(
+            |collection| collection.basis() == StartupCollectionBasis::Dropped
+                || collection.collection_type() != Some(COUNTER_CODE)
+        ));
+}
+
+//= spec/catalog.md#atomicity
+//= type=test
+//# `RING-CATALOG-007` `Storage::drop_named` of a plugin collection with a
+//# committed region basis MUST commit the drop as `RING-PLUGIN-006` requires
+//# and then append the updated catalog snapshot in its own inline
+//# transaction.
+#[test]
+fn requirement_plugin_drop_named_frees_referenced_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_plugin_storage(&mut flash, &COUNTER_TYPES);
+    let named = storage.create_named("counter", COUNTER_CODE).unwrap();
+    let named_segment = storage.reserve_next_region().unwrap();
+    let named_segment = storage
+        .write_committed_region(named_segment, named, COUNTER_SEGMENT_FORMAT, &[1])
+        .unwrap();
+    let named_head = write_counter_head(&mut storage, named, named_segment);
+    storage
+        .append_head(named, COUNTER_CODE, named_head)
+        .unwrap();
+    assert_eq!(storage.drop_named("counter").unwrap(), Some(named_head));
+    let free_space = storage.runtime().free_space();
+    assert!(free_space.contains_free_region(named_segment));
+    assert!(free_space.contains_free_region(named_head));
+    assert!(storage.list_collections().unwrap().is_empty());
+}
@@ -1,4 +1,8 @@
-/// Experimental channel collection APIs.
+/// Single-value cell collection APIs.
+pub mod cell;
+pub use cell::*;
+
+/// Durable channel collection APIs.
 pub mod channel;
 pub use channel::*;
 
@@ -9,3 +13,27 @@ pub use map::*;
 /// Durable opaque object log collection APIs.
 pub mod object_log;
 pub use object_log::*;
+
+/// Durable FIFO queue collection APIs.
+pub mod queue;
+pub use queue::*;
+
+/// Fixed-capacity ring log collection APIs.
+pub mod ring_log;
+pub use ring_log::*;
+
+/// Monotonic ID sequence collection APIs.
+pub mod sequence;
+pub use sequence::*;
+
+/// Durable ordered set collection APIs.
+pub mod set;
+pub use set::*;
+
+/// Typed hierarchical settings built on the map collection.
+pub mod settings;
+pub use settings::*;
+
+/// Compressed time-series collection APIs.
+pub mod time_series;
+pub use time_series::*;
@@ -0,0 +1,857 @@
+//! Single-value cell collection APIs.
+
+use core::mem::size_of;
+
+use crc::{Crc, CRC_32_ISCSI};
+use postcard::{from_bytes, to_slice};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::disk::Header;
+use crate::flash_io::FlashIo;
+use crate::mode::{CollectionUpdateMode, StorageMode};
+use crate::startup::StartupCollectionBasis;
+use crate::storage::{StorageRuntimeError, StorageVisitError};
+use crate::wal_record::WalRecord;
+use crate::{Collection, CollectionId, CollectionType, Storage, StorageMetadata};
+
+#[cfg(test)]
+mod tests;
+
+/// Committed-region format code for cell values stored outside the WAL.
+pub const CELL_VALUE_V1_FORMAT: u16 = 13;
+
+/// Encoded value size above which a [`Cell`] moves its value into a
+/// committed region instead of the WAL snapshot.
+pub const DEFAULT_CELL_INLINE_LIMIT: usize = 128;
+
+const VALUE_MAGIC: [u8; 4] = *b"CLVL";
+const VALUE_VERSION: u16 = 1;
+const VALUE_START: usize = VALUE_MAGIC.len() + size_of::<u16>() + size_of::<u32>();
+const VALUE_CHECKSUM_LEN: usize = size_of::<u32>();
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+const STATE_MAGIC: [u8; 4] = *b"CLSN";
+const STATE_VERSION: u16 = 1;
+const STATE_VALUE_START: usize = STATE_MAGIC.len() + size_of::<u16>() + size_of::<u8>();
+const EMPTY_SNAPSHOT: [u8; STATE_VALUE_START] = [b'C', b'L', b'S', b'N', 1, 0, 0];
+
+const STATE_EMPTY: u8 = 0;
+const STATE_INLINE: u8 = 1;
+const STATE_REGION: u8 = 2;
+
+/// Errors returned by [`Cell`].
+#[derive(Debug)]
+pub enum CellError {
+    /// Shared storage failed.
+    Storage(StorageRuntimeError),
+    /// The collection does not exist.
+    UnknownCollection(CollectionId),
+    /// The collection type did not match cell.
+    CollectionTypeMismatch {
+        collection_id: CollectionId,
+        actual: Option<u16>,
+    },
+    /// The collection was dropped.
+    DroppedCollection(CollectionId),
+    /// Encoded cell data was malformed.
+    InvalidEncoding,
+    /// Postcard failed to encode or decode the value.
+    SerializationError,
+    /// The encoded value does not fit in one committed region.
+    ValueTooLarge { capacity: usize },
+    /// Checked arithmetic overflowed.
+    LengthOverflow,
+}
+
+impl From<StorageRuntimeError> for CellError {
+    fn from(error: StorageRuntimeError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+impl From<crate::StartupError> for CellError {
+    fn from(error: crate::StartupError) -> Self {
+        Self::Storage(error.into())
+    }
+}
+
+impl From<postcard::Error> for CellError {
+    fn from(_: postcard::Error) -> Self {
+        Self::SerializationError
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CellRegion {
+    region_index: u32,
+    len: u32,
+}
+
+/// Decoded durable cell state.
+enum CellState<T> {
+    Empty,
+    Inline(T),
+    Region(CellRegion),
+}
+
+/// Value encoding waiting in the storage payload scratch to be committed.
+#[derive(Clone, Copy)]
+enum CellWrite {
+    /// An inline state record of `used` bytes.
+    Inline { used: usize },
+    /// A committed-region payload holding a value of `len` bytes.
+    Region { len: u32 },
+}
+
+struct CellReplayTransaction<T> {
+    transaction_log_id: u32,
+    joined: bool,
+    state: Option<CellState<T>>,
+}
+
+/// Durable single value of a postcard-encoded `T`.
+///
+/// The handle owns the decoded value and needs no caller memory, so many
+/// cells are cheap to keep open. Each write records the full state as one WAL
+/// snapshot. Values whose encoding exceeds the inline limit are written to a
+/// committed region instead, and the snapshot only references that region.
+pub struct Cell<T> {
+    collection_id: CollectionId,
+    value: T,
+    region: Option<CellRegion>,
+    inline_limit: usize,
+}
+
+impl<T> Collection for Cell<T> {
+    fn id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    fn collection_type(&self) -> CollectionType {
+        CollectionType::Cell
+    }
+}
+
+impl<T> Cell<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new cell collection holding `value`.
+    pub fn new<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        value: T,
+    ) -> Result<Self, CellError> {
+        Self::new_with_inline_limit(storage, value, DEFAULT_CELL_INLINE_LIMIT)
+    }
+
+    /// Creates a new cell collection that keeps values of at most
+    /// `inline_limit` encoded bytes in its WAL snapshot.
+    pub fn new_with_inline_limit<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        value: T,
+        inline_limit: usize,
+    ) -> Result<Self, CellError> {
+        let collection_id = storage.allocate_collection_id()?;
+        storage.append_new_collection(collection_id, CollectionType::CELL_CODE)?;
+        let mut cell = Self {
+            collection_id,
+            value,
+            region: None,
+            inline_limit,
+        };
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = Self::write_state(storage, collection_id, inline_limit, None, &cell.value);
+        storage.finish_mode();
+        cell.region = result?;
+        Ok(cell)
+    }
+
+    /// Opens an existing cell collection.
+    ///
+    /// A cell whose first value never became durable opens with
+    /// `T::default()`.
+    pub fn open<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Self, CellError>
+    where
+        T: Default,
+    {
+        validate_collection::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage,
+            collection_id,
+        )?;
+        let state = Self::replay(storage, collection_id)?;
+        let (value, region) = match state {
+            CellState::Empty => (T::default(), None),
+            CellState::Inline(value) => (value, None),
+            CellState::Region(region) => (
+                read_region_value::<IO, T, REGION_SIZE>(storage.backing, collection_id, region)?,
+                Some(region),
+            ),
+        };
+        Ok(Self {
+            collection_id,
+            value,
+            region,
+            inline_limit: DEFAULT_CELL_INLINE_LIMIT,
+        })
+    }
+
+    /// Overrides the encoded size above which values leave the WAL snapshot.
+    ///
+    /// The limit applies from the next write.
+    pub fn with_inline_limit(mut self, inline_limit: usize) -> Self {
+        self.inline_limit = inline_limit;
+        self
+    }
+
+    /// Returns the configured inline limit in encoded bytes.
+    pub fn inline_limit(&self) -> usize {
+        self.inline_limit
+    }
+
+    /// Returns the stable collection id.
+    pub fn collection_id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    /// Returns the current value.
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the committed region holding the value, or `None` when the
+    /// value lives in the WAL snapshot.
+    pub fn region_index(&self) -> Option<u32> {
+        self.region.map(|region| region.region_index)
+    }
+
+    /// Durably replaces the value.
+    ///
+    /// The handle keeps its previous value when the write fails.
+    pub fn set<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        value: T,
+    ) -> Result<(), CellError> {
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = Self::write_state(
+            storage,
+            self.collection_id,
+            self.inline_limit,
+            self.region,
+            &value,
+        );
+        storage.finish_mode();
+        self.region = result?;
+        self.value = value;
+        Ok(())
+    }
+
+    /// Applies `update` to a copy of the value and durably stores the result.
+    pub fn update<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        update: F,
+    ) -> Result<(), CellError>
+    where
+        T: Clone,
+        F: FnOnce(&mut T),
+    {
+        let mut value = self.value.clone();
+        update(&mut value);
+        self.set(storage, value)
+    }
+
+    /// Encodes `value` and records it as the cell state, returning the region
+    /// that now holds the value, if any.
+    ///
+    /// Inline values replacing inline values are a single snapshot. Any write
+    /// that adds or releases a value region goes through a transaction so the
+    /// previous region is only freed once the replacement state is committed.
+    fn write_state<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        collection_id: CollectionId,
+        inline_limit: usize,
+        previous: Option<CellRegion>,
+        value: &T,
+    ) -> Result<Option<CellRegion>, CellError> {
+        let value_capacity = REGION_SIZE - STATE_VALUE_START;
+        let encoded_len = match to_slice(
+            value,
+            &mut storage.memory.payload_scratch[STATE_VALUE_START..],
+        ) {
+            Ok(encoded) => encoded.len(),
+            Err(postcard::Error::SerializeBufferFull) => {
+                return Err(CellError::ValueTooLarge {
+                    capacity: value_capacity,
+                });
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let write = if encoded_len <= inline_limit {
+            let used = write_state_header(&mut storage.memory.payload_scratch, STATE_INLINE)?;
+            CellWrite::Inline {
+                used: used + encoded_len,
+            }
+        } else {
+            let capacity = region_value_capacity::<REGION_SIZE>(storage.metadata())?;
+            if encoded_len > capacity {
+                return Err(CellError::ValueTooLarge { capacity });
+            }
+            let len = u32::try_from(encoded_len).map_err(|_| CellError::LengthOverflow)?;
+            storage.memory.payload_scratch.copy_within(
+                STATE_VALUE_START..STATE_VALUE_START + encoded_len,
+                VALUE_START,
+            );
+            let mut used = write_bytes(&mut storage.memory.payload_scratch, 0, &VALUE_MAGIC)?;
+            used = write_u16(&mut storage.memory.payload_scratch, used, VALUE_VERSION)?;
+            write_u32(&mut storage.memory.payload_scratch, used, len)?;
+            let value_end = VALUE_START + encoded_len;
+            let checksum = crc32(&storage.memory.payload_scratch[..value_end]);
+            write_u32(&mut storage.memory.payload_scratch, value_end, checksum)?;
+            CellWrite::Region { len }
+        };
+
+        if let (CellWrite::Inline { used }, None) = (write, previous) {
+            storage
+                .memory
+                .state
+                .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    collection_id,
+                    CollectionType::CELL_CODE,
+                    &storage.memory.payload_scratch[..used],
+                )?;
+            return Ok(None);
+        }
+
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+            )?;
+        let mut allocated = None;
+        let (region, used) = match Self::write_transactional(
+            storage,
+            collection_id,
+            write,
+            previous,
+            &mut allocated,
+        ) {
+            Ok(committed) => committed,
+            Err(error) => {
+                return match rollback_write(storage, collection_id, allocated) {
+                    Ok(()) => Err(error),
+                    Err(cleanup_error) => Err(cleanup_error),
+                };
+            }
+        };
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+            )?;
+
+        // The committed update already holds the full state; the snapshot
+        // lets WAL reclaim drop the transaction records.
+        storage
+            .memory
+            .state
+            .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+                CollectionType::CELL_CODE,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        Ok(region)
+    }
+
+    /// Writes the value region when needed, commits the new state as an
+    /// update, and frees the previous value region after the commit.
+    ///
+    /// Returns the new value region and the length of the state encoding left
+    /// in the payload scratch.
+    fn write_transactional<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        collection_id: CollectionId,
+        write: CellWrite,
+        previous: Option<CellRegion>,
+        allocated: &mut Option<u32>,
+    ) -> Result<(Option<CellRegion>, usize), CellError> {
+        let (region, used) = match write {
+            CellWrite::Inline { used } => (None, used),
+            CellWrite::Region { len } => {
+                let region_index = storage
+                    .memory
+                    .state
+                    .reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+                        storage.backing,
+                        &mut storage.memory.workspace,
+                        collection_id,
+                        &mut storage.memory.reclaim_source_regions,
+                        &mut storage.memory.active_collections,
+                        &mut storage.memory.reclaim_plan,
+                        &mut storage.memory.open_plan,
+                    )?;
+                *allocated = Some(region_index);
+                let payload_len = (VALUE_START + VALUE_CHECKSUM_LEN)
+                    .checked_add(usize::try_from(len).map_err(|_| CellError::LengthOverflow)?)
+                    .ok_or(CellError::LengthOverflow)?;
+                let region_index = storage
+                    .memory
+                    .state
+                    .write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
+                        storage.backing,
+                        &mut storage.memory.workspace,
+                        region_index,
+                        collection_id,
+                        CELL_VALUE_V1_FORMAT,
+                        &storage.memory.payload_scratch[..payload_len],
+                    )?;
+                *allocated = Some(region_index);
+                let region = CellRegion { region_index, len };
+                let used = encode_region_state(&mut storage.memory.payload_scratch, region)?;
+                (Some(region), used)
+            }
+        };
+
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+            )?;
+        if let Some(previous) = previous {
+            storage
+                .memory
+                .state
+                .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    collection_id,
+                    previous.region_index,
+                )?;
+        }
+        Ok((region, used))
+    }
+
+    fn replay<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        collection_id: CollectionId,
+    ) -> Result<CellState<T>, CellError> {
+        let mut state = CellState::Empty;
+        let mut transaction = None::<CellReplayTransaction<T>>;
+        let result =
+            storage
+                .memory
+                .state
+                .visit_wal_records::<REGION_SIZE, IO, CellError, _>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    |_flash, record| {
+                        match record {
+                            WalRecord::NewCollection {
+                                collection_id: seen,
+                                collection_type,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::CELL_CODE =>
+                            {
+                                state = CellState::Empty;
+                            }
+                            WalRecord::BeginTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                transaction = Some(CellReplayTransaction {
+                                    transaction_log_id,
+                                    joined: transaction_log_id == 0,
+                                    state: None,
+                                });
+                            }
+                            WalRecord::AddTransactionCollection {
+                                collection_id: seen,
+                                ..
+                            } if seen == collection_id => {
+                                if let Some(open) = transaction.as_mut() {
+                                    open.joined = true;
+                                }
+                            }
+                            WalRecord::Snapshot {
+                                collection_id: seen,
+                                collection_type,
+                                payload,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::CELL_CODE =>
+                            {
+                                state = decode_state(payload)?;
+                            }
+                            // Reclaim can copy a committed update out of its
+                            // transaction, so it may also appear as a plain update.
+                            WalRecord::Update {
+                                collection_id: seen,
+                                payload,
+                            } if seen == collection_id => match transaction.as_mut() {
+                                Some(open) if open.joined => {
+                                    open.state = Some(decode_state(payload)?);
+                                }
+                                _ => state = decode_state(payload)?,
+                            },
+                            WalRecord::CommitTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    if let Some(committed) =
+                                        transaction.take().and_then(|open| open.state)
+                                    {
+                                        state = committed;
+                                    }
+                                }
+                            }
+                            WalRecord::RollbackTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    transaction = None;
+                                }
+                            }
+                            WalRecord::DropCollection {
+                                collection_id: seen,
+                            } if seen == collection_id => {
+                                state = CellState::Empty;
+                            }
+                            _ => {}
+                        }
+                        Ok(())
+                    },
+                );
+        match result {
+            Ok(()) => Ok(state),
+            Err(StorageVisitError::Storage(error)) => Err(CellError::Storage(error)),
+            Err(StorageVisitError::Visitor(error)) => Err(error),
+        }
+    }
+}
+
+fn rollback_write<
+    'db,
+    'storage_mem,
+    IO: FlashIo,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+    allocated: Option<u32>,
+) -> Result<(), CellError> {
+    let mut first_error = None::<CellError>;
+    if let Err(error) = storage
+        .memory
+        .state
+        .rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+            storage.backing,
+            &mut storage.memory.workspace,
+            collection_id,
+        )
+    {
+        first_error = Some(error.into());
+    }
+    if let Some(region_index) = allocated {
+        if let Err(error) = storage
+            .memory
+            .state
+            .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                CollectionId(0),
+                region_index,
+            )
+        {
+            if first_error.is_none() {
+                first_error = Some(error.into());
+            }
+        }
+    }
+    match first_error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn validate_collection<
+    IO: FlashIo,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+) -> Result<(), CellError> {
+    let collection = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .ok_or(CellError::UnknownCollection(collection_id))?;
+    if collection.basis() == StartupCollectionBasis::Dropped {
+        return Err(CellError::DroppedCollection(collection_id));
+    }
+    storage.runtime().ensure_not_quarantined(collection_id)?;
+    if collection.collection_type() != Some(CollectionType::CELL_CODE) {
+        return Err(CellError::CollectionTypeMismatch {
+            collection_id,
+            actual: collection.collection_type(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn empty_snapshot() -> &'static [u8] {
+    &EMPTY_SNAPSHOT
+}
+
+/// Returns the encoded value bytes one committed value region can hold.
+fn region_value_capacity<const REGION_SIZE: usize>(
+    metadata: StorageMetadata,
+) -> Result<usize, CellError> {
+    let granule =
+        usize::try_from(metadata.wal_write_granule).map_err(|_| CellError::LengthOverflow)?;
+    if granule == 0 {
+        return Err(CellError::InvalidEncoding);
+    }
+    (REGION_SIZE - REGION_SIZE % granule)
+        .checked_sub(Header::ENCODED_LEN + VALUE_START + VALUE_CHECKSUM_LEN)
+        .ok_or(CellError::LengthOverflow)
+}
+
+/// Returns whether a `cell_value_v1` payload matches the CRC-32C that follows
+/// its value.
+pub(crate) fn value_checksum_matches(payload: &[u8]) -> bool {
+    decode_value_checksum(payload)
+        .is_ok_and(|(value_end, checksum)| crc32(&payload[..value_end]) == checksum)
+}
+
+/// Returns where a value payload's encoded value ends and the checksum stored
+/// after it.
+fn decode_value_checksum(payload: &[u8]) -> Result<(usize, u32), CellError> {
+    let mut offset = 0usize;
+    if read_bytes(payload, &mut offset, VALUE_MAGIC.len())? != VALUE_MAGIC
+        || read_u16(payload, &mut offset)? != VALUE_VERSION
+    {
+        return Err(CellError::InvalidEncoding);
+    }
+    let len =
+        usize::try_from(read_u32(payload, &mut offset)?).map_err(|_| CellError::LengthOverflow)?;
+    read_bytes(payload, &mut offset, len)?;
+    let value_end = offset;
+    Ok((value_end, read_u32(payload, &mut offset)?))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    CRC32C.checksum(bytes)
+}
+
+fn read_region_value<IO: FlashIo, T: DeserializeOwned, const REGION_SIZE: usize>(
+    flash: &mut IO,
+    collection_id: CollectionId,
+    region: CellRegion,
+) -> Result<T, CellError> {
+    let header = flash
+        .read_region(region.region_index, 0, Header::ENCODED_LEN, Header::decode)
+        .map_err(StorageRuntimeError::from)?
+        .map_err(|_| CellError::InvalidEncoding)?;
+    if header.collection_id != collection_id || header.collection_format != CELL_VALUE_V1_FORMAT {
+        return Err(CellError::InvalidEncoding);
+    }
+    let len = usize::try_from(region.len).map_err(|_| CellError::LengthOverflow)?;
+    let payload_len = VALUE_START
+        .checked_add(len)
+        .filter(|payload_len| Header::ENCODED_LEN + *payload_len <= REGION_SIZE)
+        .ok_or(CellError::InvalidEncoding)?;
+    flash
+        .read_region(
+            region.region_index,
+            Header::ENCODED_LEN,
+            payload_len,
+            |bytes| {
+                let mut offset = 0usize;
+                if read_bytes(bytes, &mut offset, VALUE_MAGIC.len())? != VALUE_MAGIC
+                    || read_u16(bytes, &mut offset)? != VALUE_VERSION
+                    || read_u32(bytes, &mut offset)? != region.len
+                {
+                    return Err(CellError::InvalidEncoding);
+                }
+                Ok(from_bytes(read_bytes(bytes, &mut offset, len)?)?)
+            },
+        )
+        .map_err(StorageRuntimeError::from)?
+}
+
+fn write_state_header(output: &mut [u8], kind: u8) -> Result<usize, CellError> {
+    let used = write_bytes(output, 0, &STATE_MAGIC)?;
+    let used = write_u16(output, used, STATE_VERSION)?;
+    write_u8(output, used, kind)
+}
+
+fn encode_region_state(output: &mut [u8], region: CellRegion) -> Result<usize, CellError> {
+    let used = write_state_header(output, STATE_REGION)?;
+    let used = write_u32(output, used, region.region_index)?;
+    write_u32(output, used, region.len)
+}
+
+fn decode_state<T: DeserializeOwned>(payload: &[u8]) -> Result<CellState<T>, CellError> {
+    let mut offset = 0usize;
+    if read_bytes(payload, &mut offset, STATE_MAGIC.len())? != STATE_MAGIC
+        || read_u16(payload, &mut offset)? != STATE_VERSION
+    {
+        return Err(CellError::InvalidEncoding);
+    }
+    let state = match read_u8(payload, &mut offset)? {
+        STATE_EMPTY => CellState::Empty,
+        STATE_INLINE => {
+            let value = payload.get(offset..).ok_or(CellError::InvalidEncoding)?;
+            return Ok(CellState::Inline(from_bytes(value)?));
+        }
+        STATE_REGION => CellState::Region(CellRegion {
+            region_index: read_u32(payload, &mut offset)?,
+            len: read_u32(payload, &mut offset)?,
+        }),
+        _ => return Err(CellError::InvalidEncoding),
+    };
+    if offset != payload.len() {
+        return Err(CellError::InvalidEncoding);
+    }
+    Ok(state)
+}
+
+fn write_u8(output: &mut [u8], offset: usize, value: u8) -> Result<usize, CellError> {
+    write_bytes(output, offset, &[value])
+}
+
+fn write_u16(output: &mut [u8], offset: usize, value: u16) -> Result<usize, CellError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u32(output: &mut [u8], offset: usize, value: u32) -> Result<usize, CellError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_bytes(output: &mut [u8], offset: usize, bytes: &[u8]) -> Result<usize, CellError> {
+    let end = offset
+        .checked_add(bytes.len())
+        .ok_or(CellError::LengthOverflow)?;
+    let target = output
+        .get_mut(offset..end)
+        .ok_or(CellError::LengthOverflow)?;
+    target.copy_from_slice(bytes);
+    Ok(end)
+}
+
+fn read_u8(input: &[u8], offset: &mut usize) -> Result<u8, CellError> {
+    Ok(read_bytes(input, offset, size_of::<u8>())?[0])
+}
+
+fn read_u16(input: &[u8], offset: &mut usize) -> Result<u16, CellError> {
+    let mut bytes = [0u8; size_of::<u16>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u16>())?);
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(input: &[u8], offset: &mut usize) -> Result<u32, CellError> {
+    let mut bytes = [0u8; size_of::<u32>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u32>())?);
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_bytes<'a>(input: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8], CellError> {
+    let end = offset.checked_add(len).ok_or(CellError::LengthOverflow)?;
+    let bytes = input.get(*offset..end).ok_or(CellError::InvalidEncoding)?;
+    *offset = end;
+    Ok(bytes)
+}
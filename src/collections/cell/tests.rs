@@ -0,0 +1,247 @@
+use super::*;
+
+use serde::Deserialize;
+
+use crate::MockFlash;
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: std::string::String,
+    retries: u32,
+    blob: std::vec::Vec<u8>,
+}
+
+fn config(name: &str, retries: u32, blob_len: usize) -> Config {
+    Config {
+        name: name.into(),
+        retries,
+        blob: (0..blob_len).map(|byte| byte as u8).collect(),
+    }
+}
+
+fn value_region_count(flash: &mut TestFlash) -> usize {
+    (0..REGION_COUNT as u32)
+        .filter(|region_index| {
+            flash
+                .read_region(*region_index, 0, Header::ENCODED_LEN, Header::decode)
+                .unwrap()
+                .is_ok_and(|header| header.collection_format == CELL_VALUE_V1_FORMAT)
+        })
+        .count()
+}
+
+//= spec/cell.md#cell-state
+//= type=test
+//# `RING-CELL-001` `Cell::new` MUST create a collection with
+//# `collection_type = 0x0007` and record its initial value before returning.
+#[test]
+fn requirement_cell_new_records_initial_value() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let cell = Cell::new(&mut storage, 41u32).unwrap();
+        assert_eq!(cell.collection_type(), CollectionType::Cell);
+        assert_eq!(
+            storage
+                .collections()
+                .iter()
+                .find(|collection| collection.collection_id() == cell.collection_id())
+                .and_then(|collection| collection.collection_type()),
+            Some(CollectionType::CELL_CODE)
+        );
+        assert_eq!(*cell.get(), 41);
+        cell.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(*Cell::<u32>::open(id, &mut storage).unwrap().get(), 41);
+}
+
+//= spec/cell.md#cell-state
+//= type=test
+//# `RING-CELL-002` A value whose encoding fits the inline limit MUST be
+//# written as a single WAL snapshot without reserving a committed region.
+#[test]
+fn requirement_cell_small_values_stay_in_wal() {
+    let mut flash = TestFlash::new(0xff);
+    {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut cells = std::vec::Vec::new();
+        for index in 0..8u32 {
+            cells.push(Cell::new(&mut storage, config("cell", index, 8)).unwrap());
+        }
+        for _ in 0..4 {
+            for cell in cells.iter_mut() {
+                cell.update(&mut storage, |value| value.retries += 100)
+                    .unwrap();
+                assert_eq!(cell.region_index(), None);
+            }
+        }
+    }
+
+    assert_eq!(value_region_count(&mut flash), 0);
+}
+
+//= spec/cell.md#cell-state
+//= type=test
+//# `RING-CELL-003` `set` and `update` MUST replace the whole value, and a
+//# reopened cell MUST observe the newest durable value.
+#[test]
+fn requirement_cell_set_and_update_survive_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let (id, other) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut cell = Cell::new(&mut storage, config("boot", 1, 4)).unwrap();
+        cell.set(&mut storage, config("wifi", 3, 12)).unwrap();
+        cell.update(&mut storage, |value| {
+            value.retries *= 2;
+            value.name.push_str("-ap");
+        })
+        .unwrap();
+        assert_eq!(*cell.get(), config("wifi-ap", 6, 12));
+        let other = Cell::new(&mut storage, 7u8).unwrap();
+        (cell.collection_id(), other.collection_id())
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let cell = Cell::<Config>::open(id, &mut storage).unwrap();
+    assert_eq!(*cell.get(), config("wifi-ap", 6, 12));
+    assert_eq!(*Cell::<u8>::open(other, &mut storage).unwrap().get(), 7);
+}
+
+//= spec/cell.md#durable-storage
+//= type=test
+//# `RING-CELL-004` A value whose encoding exceeds the inline limit MUST be
+//# written to a committed region in the same transaction as the update that
+//# references it, and a replaced value region MUST be freed after that commit.
+#[test]
+fn requirement_cell_large_values_use_committed_region() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut cell = Cell::new(&mut storage, config("small", 0, 4)).unwrap();
+        assert_eq!(cell.region_index(), None);
+
+        cell.set(&mut storage, config("large", 1, 300)).unwrap();
+        let first = cell.region_index().unwrap();
+        cell.set(&mut storage, config("larger", 2, 320)).unwrap();
+        let second = cell.region_index().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(storage.free_space_tail_region(), Some(first));
+        cell.collection_id()
+    };
+    assert_eq!(value_region_count(&mut flash), 2);
+
+    {
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        let mut cell = Cell::<Config>::open(id, &mut storage).unwrap();
+        assert_eq!(*cell.get(), config("larger", 2, 320));
+        let region = cell.region_index().unwrap();
+
+        cell.set(&mut storage, config("small", 3, 4)).unwrap();
+        assert_eq!(cell.region_index(), None);
+        assert_eq!(storage.free_space_tail_region(), Some(region));
+    }
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let cell = Cell::<Config>::open(id, &mut storage).unwrap();
+    assert_eq!(*cell.get(), config("small", 3, 4));
+    assert_eq!(cell.region_index(), None);
+}
+
+//= spec/cell.md#durable-storage
+//= type=test
+//# `RING-CELL-005` The inline limit MUST be configurable per handle, and a
+//# value that does not fit one committed region MUST be rejected without
+//# changing the durable or in-memory value.
+#[test]
+fn requirement_cell_inline_limit_and_oversized_values() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let cell = Cell::new_with_inline_limit(&mut storage, config("tiny", 0, 0), 4).unwrap();
+        assert_eq!(cell.inline_limit(), 4);
+        assert!(cell.region_index().is_some());
+
+        let mut cell = cell.with_inline_limit(DEFAULT_CELL_INLINE_LIMIT);
+        cell.set(&mut storage, config("tiny", 1, 0)).unwrap();
+        assert_eq!(cell.region_index(), None);
+
+        assert!(matches!(
+            cell.set(&mut storage, config("huge", 2, 600)),
+            Err(CellError::ValueTooLarge { .. })
+        ));
+        assert_eq!(*cell.get(), config("tiny", 1, 0));
+        cell.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(
+        *Cell::<Config>::open(id, &mut storage).unwrap().get(),
+        config("tiny", 1, 0)
+    );
+}
+
+//= spec/cell.md#durable-storage
+//= type=test
+//# `RING-CELL-006` A cell whose basis is the empty snapshot MUST open with the
+//# default value, and `Cell::open` MUST reject collections that are not cells.
+#[test]
+fn requirement_cell_empty_basis_and_type_checks() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let empty = storage.allocate_collection_id().unwrap();
+    storage
+        .append_new_collection(empty, CollectionType::CELL_CODE)
+        .unwrap();
+    let other = storage.allocate_collection_id().unwrap();
+    storage
+        .append_new_collection(other, CollectionType::RING_LOG_CODE)
+        .unwrap();
+
+    assert_eq!(*Cell::<u64>::open(empty, &mut storage).unwrap().get(), 0);
+    assert!(matches!(
+        decode_state::<u64>(empty_snapshot()),
+        Ok(CellState::Empty)
+    ));
+    assert!(matches!(
+        Cell::<u64>::open(other, &mut storage),
+        Err(CellError::CollectionTypeMismatch { .. })
+    ));
+    assert!(matches!(
+        Cell::<u64>::open(CollectionId(999), &mut storage),
+        Err(CellError::UnknownCollection(_))
+    ));
+}
+
+//= spec/cell.md#durable-storage
+//= type=test
+//# `RING-CELL-007` WAL-head reclaim MUST NOT copy a snapshot out of the
+//# reclaimed region when a later WAL region holds a newer one, so a reopened
+//# cell MUST read the last value set.
+#[test]
+fn requirement_cell_survives_wal_head_reclaim() {
+    let mut flash = TestFlash::new(0xff);
+    let (id, last) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut cell = Cell::new(&mut storage, 0u32).unwrap();
+        let mut value = 0;
+        while storage.wal_head() == storage.wal_tail() {
+            value += 1;
+            cell.set(&mut storage, value).unwrap();
+        }
+        value += 1;
+        cell.set(&mut storage, value).unwrap();
+        storage.reclaim_wal_head().unwrap();
+        assert_eq!(*cell.get(), value);
+        (cell.collection_id(), value)
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(*Cell::<u32>::open(id, &mut storage).unwrap().get(), last);
+}
@@ -1,20 +1,63 @@
-//! Experimental in-memory channel collection APIs.
+//! Durable channel collection APIs.
 
 use core::marker::PhantomData;
+use core::mem::size_of;
 
+use crc::{Crc, CRC_32_ISCSI};
 use heapless::Vec;
 
+use crate::disk::Header;
+use crate::flash_io::FlashIo;
+use crate::mode::{CollectionFlushMode, CollectionUpdateMode, ReadMode, StorageMode};
+use crate::startup::StartupCollectionBasis;
+use crate::storage::{StorageRuntimeError, StorageVisitError};
 use crate::vec_like::VecLike;
-use crate::CollectionId;
+use crate::wal_record::WalRecord;
+use crate::{Collection, CollectionId, CollectionType, Storage, StorageMetadata};
+mod sync;
 #[cfg(test)]
 mod tests;
 
+pub use sync::{ChannelSyncBatch, ChannelSyncMerge, CHANNEL_SYNC_MAX_COMMANDS};
+
+/// Committed-region format code for channel command segments.
+pub const CHANNEL_SEGMENT_V1_FORMAT: u16 = 9;
+
+const SEGMENT_MAGIC: [u8; 4] = *b"CHSG";
+const SEGMENT_VERSION: u16 = 2;
+/// Segment version written before segments carried a trailing checksum.
+const SEGMENT_VERSION_UNCHECKED: u16 = 1;
+const SEGMENT_CHECKSUM_LEN: usize = size_of::<u32>();
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+const OPT_REGION_ENCODED_LEN: usize = size_of::<u8>() + size_of::<u32>();
+const SEGMENT_PROLOGUE_LEN: usize =
+    SEGMENT_MAGIC.len() + size_of::<u16>() + OPT_REGION_ENCODED_LEN + size_of::<u32>();
+const SEGMENT_START: usize = Header::ENCODED_LEN + SEGMENT_PROLOGUE_LEN;
+const COMMAND_FIXED_LEN: usize =
+    2 * size_of::<u32>() + 2 * size_of::<u64>() + 2 * size_of::<u128>() + size_of::<u32>();
+const MEMBER_SEQUENCE_ENCODED_LEN: usize = size_of::<u128>() + size_of::<u64>();
+
+const SNAPSHOT_MAGIC: [u8; 4] = *b"CHNS";
+const SNAPSHOT_VERSION: u16 = 1;
+const EMPTY_SNAPSHOT_LEN: usize = SNAPSHOT_MAGIC.len()
+    + size_of::<u16>()
+    + 2 * size_of::<u64>()
+    + 2 * size_of::<u32>()
+    + 3 * OPT_REGION_ENCODED_LEN
+    + 2 * size_of::<u32>();
+const EMPTY_SNAPSHOT: [u8; EMPTY_SNAPSHOT_LEN] = encode_empty_snapshot();
+
+const UPDATE_ADD_MEMBER: u8 = 1;
+const UPDATE_ADD_COMMAND: u8 = 2;
+const UPDATE_CHECKPOINT: u8 = 3;
+const UPDATE_RESERVE_SEGMENT: u8 = 4;
+
 /// Address type used to name channel regions or command locations.
 pub trait ChannelAddress: Copy + Eq + PartialEq + Default {}
 
 impl<T> ChannelAddress for T where T: Copy + Eq + PartialEq + Default {}
 
-/// Errors returned by the experimental channel API.
+/// Errors returned by the channel API.
 #[derive(Debug)]
 pub enum ChannelError {
     /// The member registry is full.
@@ -25,25 +68,116 @@ pub enum ChannelError {
     PendingLimitReached,
     /// The update tracker is full and a checkpoint is required.
     NeedsCheckpoint,
+    /// Shared storage failed.
+    Storage(StorageRuntimeError),
+    /// The collection does not exist.
+    UnknownCollection(CollectionId),
+    /// The collection type did not match channel.
+    CollectionTypeMismatch {
+        collection_id: CollectionId,
+        actual: Option<u16>,
+    },
+    /// The collection was dropped.
+    DroppedCollection(CollectionId),
+    /// Encoded channel data was malformed.
+    InvalidEncoding,
+    /// A command did not continue its author's sequence chain.
+    SequenceMismatch {
+        member: MemberId,
+        expected: ChannelSequence,
+        actual: ChannelSequence,
+    },
+    /// A command sequence did not advance past its author's last sequence.
+    InvalidSequence {
+        member: MemberId,
+        sequence: ChannelSequence,
+    },
+    /// A checkpoint did not match the channel state it summarizes.
+    CheckpointMismatch,
+    /// The members changed since the last checkpoint exceed `CHECKPOINT_MAX`.
+    CheckpointLimitReached,
+    /// The channel has no reserved segment to place commands in.
+    MissingFrontier,
+    /// A command does not fit in an empty segment or in `PAYLOAD_MAX`.
+    CommandTooLarge { len: usize, capacity: usize },
+    /// A command address does not name a stored command.
+    InvalidAddress,
+    /// Checked arithmetic overflowed.
+    LengthOverflow,
+    /// A sync frame does not fit in the caller's buffer.
+    SyncBufferTooSmall { len: usize, capacity: usize },
+    /// A synced command reused a `MessageId` the channel already holds.
+    DuplicateMessageId(MessageId),
+}
+
+impl From<StorageRuntimeError> for ChannelError {
+    fn from(error: StorageRuntimeError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+impl From<crate::StartupError> for ChannelError {
+    fn from(error: crate::StartupError) -> Self {
+        Self::Storage(error.into())
+    }
 }
 
 ///////////// basic types /////////////
 /// Monotonic sequence number used by channel commands.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Sequence 0 is never assigned to a command; it is the last sequence of a
+/// member that has not authored anything yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct ChannelSequence(u64);
 
+impl ChannelSequence {
+    /// Wraps a raw sequence number.
+    pub const fn new(sequence: u64) -> Self {
+        Self(sequence)
+    }
+
+    /// Returns the raw sequence number.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
 /// Stable member identifier used by the channel API.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct MemberId {
     id: u128,
 }
 
+impl MemberId {
+    /// Wraps a raw member identifier.
+    pub const fn new(id: u128) -> Self {
+        Self { id }
+    }
+
+    /// Returns the raw member identifier.
+    pub const fn id(self) -> u128 {
+        self.id
+    }
+}
+
 /// Stable message identifier used by the channel API.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct MessageId {
     id: u128,
 }
 
+impl MessageId {
+    /// Wraps a raw message identifier.
+    pub const fn new(id: u128) -> Self {
+        Self { id }
+    }
+
+    /// Returns the raw message identifier.
+    pub const fn id(self) -> u128 {
+        self.id
+    }
+}
+
 /// Address of a command within a channel log.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommandAddress<A: ChannelAddress> {
@@ -64,15 +198,27 @@ impl<A: ChannelAddress> CommandAddress<A> {
 }
 
 /// Per-member sequence tracking stored by checkpoints.
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Default)]
 pub struct MemberSequence {
     member: MemberId,
     last_sequence: ChannelSequence,
 }
 
+impl MemberSequence {
+    /// Returns the tracked member.
+    pub fn member(&self) -> MemberId {
+        self.member
+    }
+
+    /// Returns the last sequence used by the member.
+    pub fn last_sequence(&self) -> ChannelSequence {
+        self.last_sequence
+    }
+}
+
 ///////////// Protocol /////////////
 
-/// Top-level experimental channel commands.
+/// Top-level channel commands.
 pub enum ChannelCommand<A: ChannelAddress, const PAYLOAD_MAX: usize, const CHECKPOINT_MAX: usize> {
     /// Application payload command.
     AddCommand(AddCommand<A, PAYLOAD_MAX>),
@@ -170,10 +316,29 @@ impl<A: ChannelAddress, const PAYLOAD_MAX: usize> AddCommand<A, PAYLOAD_MAX> {
     pub fn payload(&self) -> &[u8] {
         self.payload.as_slice()
     }
+
+    fn encoded_len(&self) -> Result<usize, ChannelError> {
+        COMMAND_FIXED_LEN
+            .checked_add(self.payload.len())
+            .ok_or(ChannelError::LengthOverflow)
+    }
+}
+
+impl<A: ChannelAddress, const PAYLOAD_MAX: usize> Default for AddCommand<A, PAYLOAD_MAX> {
+    fn default() -> Self {
+        Self {
+            prior: CommandAddress::zero(),
+            sender_last: ChannelSequence::default(),
+            sequence: ChannelSequence::default(),
+            author: MemberId::default(),
+            message_id: MessageId::default(),
+            payload: Vec::new(),
+        }
+    }
 }
 
 ///////////// Add Member Command /////////////
-/// Membership update command for the experimental channel API.
+/// Membership update command for the channel API.
 pub struct AddMemberCommand<A: ChannelAddress> {
     member: MemberId,
     phantom: PhantomData<A>,
@@ -201,8 +366,6 @@ impl<A: ChannelAddress> AddMemberCommand<A> {
 /// A check point is used when a one needs to talk about which devices
 /// have sent which commands in a way that allow only describing recent
 /// changes.
-///
-/// This API is experimental and not yet wired into the durable storage engine.
 pub struct CheckPointCommand<A: ChannelAddress, const USER_LIMIT: usize> {
     /// The checkpoint that this builds on
     previous_checkpoint: CommandAddress<A>,
@@ -248,18 +411,14 @@ impl<A: ChannelAddress, const MEMBER_LIMIT: usize> CheckPointCommand<A, MEMBER_L
 
 ///////////// Channel State /////////////
 
-/// The channel is represented by an ordered set of regions. Each region has a pointer
-/// the next and previous region in the channel. The header of the next region is not
-/// written until if is full at which point it becomes the head region of the channel.
-/// Because the header of then next channel is not written prior to becoming the head
-/// It will have a lower region sequence number than the current head region and so what ever
-/// stale information it contains will be ignored. To track how much of the next region is
-/// used the current head also references a WAL to track updates to the next region.
-///
-/// We wright commands in to the next segment instead of the WAL so that they have a
-/// stable address.
+/// The channel is represented by an ordered set of segment regions. Each
+/// flushed segment names the segment after it, so commands can be walked from
+/// the oldest segment to the newest. The frontier segment is reserved before
+/// any command is placed in it, but it is not written until it is flushed;
+/// until then the WAL carries the commands that belong to it.
 ///
-/// This type is experimental and currently models only the in-memory command protocol.
+/// We write commands in to the frontier segment instead of the WAL so that
+/// they have a stable address.
 pub struct Channel<
     'a,
     'b,
@@ -272,15 +431,40 @@ pub struct Channel<
     const CHECKPOINT_MAX: usize,
 > {
     id: CollectionId,
-    //next_region: A,
-    //previous_region: Option<A>,
-    //wal: A,
-    //next_wal: Option<A>,
     next_sequence: ChannelSequence,
     members: &'a mut M,
     checkpoint: CommandAddress<A>,
     updates: &'b mut U,
     pending: &'c mut P,
+    command_count: u64,
+    first_segment: Option<A>,
+    frontier: Option<CommandAddress<A>>,
+    spare_segment: Option<A>,
+    relocation_epoch: u64,
+}
+
+struct ChannelReplayTransaction {
+    transaction_log_id: u32,
+    joined: bool,
+    reserved: Option<u32>,
+}
+
+impl<
+        A: ChannelAddress,
+        M: VecLike<MemberSequence>,
+        U: VecLike<MemberId>,
+        P: VecLike<AddCommand<A, PAYLOAD_MAX>>,
+        const PAYLOAD_MAX: usize,
+        const CHECKPOINT_MAX: usize,
+    > Collection for Channel<'_, '_, '_, A, M, U, P, PAYLOAD_MAX, CHECKPOINT_MAX>
+{
+    fn id(&self) -> CollectionId {
+        self.id
+    }
+
+    fn collection_type(&self) -> CollectionType {
+        CollectionType::Channel
+    }
 }
 
 impl<
@@ -295,64 +479,113 @@ impl<
         const CHECKPOINT_MAX: usize,
     > Channel<'a, 'b, 'c, A, M, U, P, PAYLOAD_MAX, CHECKPOINT_MAX>
 {
-    /// Creates a new channel with a single initial member.
-    pub fn new(
-        id: CollectionId,
-        initial_member: MemberId,
-        pending: &'c mut P,
-        members: &'a mut M,
-        updates: &'b mut U,
-    ) -> Result<Self, ChannelError> {
-        let member_sequence = MemberSequence {
-            member: initial_member,
-            last_sequence: ChannelSequence(0),
-        };
-        let Ok(_) = members.push(member_sequence) else {
-            return Err(ChannelError::UserLimitReached);
-        };
-
-        Ok(Self {
+    fn empty(id: CollectionId, pending: &'c mut P, members: &'a mut M, updates: &'b mut U) -> Self {
+        let mut channel = Self {
             id,
-            next_sequence: ChannelSequence(0),
+            next_sequence: ChannelSequence(1),
             members,
             checkpoint: CommandAddress::zero(),
             updates,
             pending,
-        })
+            command_count: 0,
+            first_segment: None,
+            frontier: None,
+            spare_segment: None,
+            relocation_epoch: 0,
+        };
+        channel.reset();
+        channel
     }
 
-    /// Adds a member and returns the corresponding command.
-    pub fn add_member(
-        &mut self,
-        member: MemberId,
-    ) -> Result<ChannelCommand<A, PAYLOAD_MAX, CHECKPOINT_MAX>, ChannelError> {
-        let command = AddMemberCommand::into_command(member);
-        self.apply_command(&command)?;
-
-        Ok(command)
+    fn reset(&mut self) {
+        self.next_sequence = ChannelSequence(1);
+        self.members.clear();
+        self.checkpoint = CommandAddress::zero();
+        self.updates.clear();
+        self.pending.clear();
+        self.command_count = 0;
+        self.first_segment = None;
+        self.frontier = None;
+        self.spare_segment = None;
     }
 
-    /// Adds an application command and returns the resulting protocol item.
-    pub fn add_command(
-        &mut self,
-        prior: CommandAddress<A>,
-        author: MemberId,
-        message_id: MessageId,
-        payload: Vec<u8, PAYLOAD_MAX>,
-    ) -> Result<ChannelCommand<A, PAYLOAD_MAX, CHECKPOINT_MAX>, ChannelError> {
-        let sender_last = self.get_last_sequence(&author)?;
-        let sequence = self.get_next_sequence();
-        let command =
-            AddCommand::into_command(prior, sender_last, sequence, author, message_id, payload);
-        self.apply_command(&command)?;
-
-        Ok(command)
+    /// Checks that `command` can be applied to the current state without
+    /// changing anything.
+    ///
+    /// Every command is validated before its WAL update is written and again
+    /// when it is replayed, so a channel never persists a command that breaks
+    /// an author's sequence chain.
+    fn validate_command(
+        &self,
+        command: &ChannelCommand<A, PAYLOAD_MAX, CHECKPOINT_MAX>,
+    ) -> Result<(), ChannelError> {
+        match command {
+            ChannelCommand::AddMemberCommand(command) => {
+                if !self.members.iter().any(|m| m.member == command.member)
+                    && self.members.len() >= self.members.capacity()
+                {
+                    return Err(ChannelError::UserLimitReached);
+                }
+                Ok(())
+            }
+            ChannelCommand::AddCommand(command) => {
+                let last = self.get_last_sequence(&command.author)?;
+                if command.sender_last != last {
+                    return Err(ChannelError::SequenceMismatch {
+                        member: command.author,
+                        expected: last,
+                        actual: command.sender_last,
+                    });
+                }
+                if command.sequence <= command.sender_last || command.sequence.0 == u64::MAX {
+                    return Err(ChannelError::InvalidSequence {
+                        member: command.author,
+                        sequence: command.sequence,
+                    });
+                }
+                if !self.updates.iter().any(|m| *m == command.author)
+                    && self.updates.len() >= self.updates.capacity()
+                {
+                    return Err(ChannelError::NeedsCheckpoint);
+                }
+                if self.pending.len() >= self.pending.capacity() {
+                    return Err(ChannelError::PendingLimitReached);
+                }
+                if self.frontier.is_none() {
+                    return Err(ChannelError::MissingFrontier);
+                }
+                Ok(())
+            }
+            ChannelCommand::CheckPointCommand(command) => {
+                if command.previous_checkpoint != self.checkpoint
+                    || command.command_count != self.command_count
+                {
+                    return Err(ChannelError::CheckpointMismatch);
+                }
+                for sequence in command.sequences.iter() {
+                    if self.get_last_sequence(&sequence.member)? != sequence.last_sequence {
+                        return Err(ChannelError::CheckpointMismatch);
+                    }
+                }
+                let covers_updates = self.updates.iter().all(|member| {
+                    command
+                        .sequences
+                        .iter()
+                        .any(|sequence| sequence.member == *member)
+                });
+                if !covers_updates {
+                    return Err(ChannelError::CheckpointMismatch);
+                }
+                Ok(())
+            }
+        }
     }
 
     pub(crate) fn apply_command(
         &mut self,
         command: &ChannelCommand<A, PAYLOAD_MAX, CHECKPOINT_MAX>,
     ) -> Result<(), ChannelError> {
+        self.validate_command(command)?;
         match command {
             ChannelCommand::AddMemberCommand(command) => {
                 if !self.members.iter().any(|m| m.member == command.member) {
@@ -368,19 +601,30 @@ impl<
                 Ok(())
             }
             ChannelCommand::AddCommand(command) => {
-                // TODO: check that all the sequences and such are valid.
+                let mut frontier = self.frontier.clone().ok_or(ChannelError::MissingFrontier)?;
+                frontier.offset = frontier
+                    .offset
+                    .checked_add(command.encoded_len()?)
+                    .ok_or(ChannelError::LengthOverflow)?;
                 self.use_sequence(&command.author(), command.sequence())?;
 
                 let pending_command = command.clone();
                 let Ok(_) = self.pending.push(pending_command) else {
                     return Err(ChannelError::PendingLimitReached);
                 };
+                self.frontier = Some(frontier);
+                self.command_count = self
+                    .command_count
+                    .checked_add(1)
+                    .ok_or(ChannelError::LengthOverflow)?;
+                self.next_sequence = self
+                    .next_sequence
+                    .max(ChannelSequence(command.sequence.0 + 1));
 
                 Ok(())
             }
-            ChannelCommand::CheckPointCommand(command) => {
-                self.checkpoint = command.previous_checkpoint().clone();
-                self.next_sequence = ChannelSequence(command.command_count());
+            ChannelCommand::CheckPointCommand(_) => {
+                self.checkpoint = self.frontier.clone().unwrap_or_else(CommandAddress::zero);
                 self.updates.clear();
                 Ok(())
             }
@@ -424,6 +668,20 @@ impl<
         sequence
     }
 
+    fn apply_reserved_segment(&mut self, region: A) -> Result<(), ChannelError> {
+        if self.frontier.is_none() {
+            self.frontier = Some(CommandAddress {
+                region,
+                offset: SEGMENT_START,
+            });
+        } else if self.spare_segment.is_none() {
+            self.spare_segment = Some(region);
+        } else {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        Ok(())
+    }
+
     /// Returns the collection id associated with this channel.
     pub fn id(&self) -> CollectionId {
         self.id
@@ -438,4 +696,1768 @@ impl<
     pub fn update_count(&self) -> usize {
         self.updates.len()
     }
+
+    /// Returns the total number of commands stored in the channel.
+    pub fn command_count(&self) -> u64 {
+        self.command_count
+    }
+
+    /// Returns every member with the last sequence it used.
+    pub fn members(&self) -> &[MemberSequence] {
+        self.members.as_slice()
+    }
+
+    /// Returns the sequence the next locally authored command will use.
+    pub fn next_sequence(&self) -> ChannelSequence {
+        self.next_sequence
+    }
+}
+
+impl<
+        'a,
+        'b,
+        'c,
+        M: VecLike<MemberSequence>,
+        U: VecLike<MemberId>,
+        P: VecLike<AddCommand<u32, PAYLOAD_MAX>>,
+        const PAYLOAD_MAX: usize,
+        const CHECKPOINT_MAX: usize,
+    > Channel<'a, 'b, 'c, u32, M, U, P, PAYLOAD_MAX, CHECKPOINT_MAX>
+{
+    /// Creates a new channel collection with a single initial member.
+    ///
+    /// The first command segment is reserved before this returns, so the
+    /// channel can accept commands immediately.
+    pub fn new<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        initial_member: MemberId,
+        pending: &'c mut P,
+        members: &'a mut M,
+        updates: &'b mut U,
+    ) -> Result<Self, ChannelError> {
+        let collection_id = storage.allocate_collection_id()?;
+        let mut channel = Self::empty(collection_id, pending, members, updates);
+        channel.relocation_epoch = storage.runtime().relocation_epoch();
+        channel.validate_command(&AddMemberCommand::into_command(initial_member))?;
+
+        storage.append_new_collection(collection_id, CollectionType::CHANNEL_CODE)?;
+        channel.add_member(storage, initial_member)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = channel.ensure_frontier(storage);
+        storage.finish_mode();
+        result?;
+        Ok(channel)
+    }
+
+    /// Opens an existing channel collection.
+    ///
+    /// Replay re-validates every command against its author's sequence chain
+    /// and every checkpoint against the member sequences it summarizes.
+    pub fn open<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        pending: &'c mut P,
+        members: &'a mut M,
+        updates: &'b mut U,
+    ) -> Result<Self, ChannelError> {
+        validate_collection::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage,
+            collection_id,
+        )?;
+        let mut channel = Self::empty(collection_id, pending, members, updates);
+        channel.relocation_epoch = storage.runtime().relocation_epoch();
+        channel.replay(storage)?;
+        Ok(channel)
+    }
+
+    /// Adds a member.
+    ///
+    /// Adding an existing member is a no-op and writes nothing.
+    pub fn add_member<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        member: MemberId,
+    ) -> Result<(), ChannelError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.add_member_inner(storage, member);
+        storage.finish_mode();
+        result
+    }
+
+    /// Adds an application command authored by `author` and returns its
+    /// stable address.
+    ///
+    /// The command takes the channel's next sequence and continues the
+    /// author's sequence chain. A full frontier segment or pending buffer is
+    /// flushed first.
+    pub fn add_command<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        prior: CommandAddress<u32>,
+        author: MemberId,
+        message_id: MessageId,
+        payload: &[u8],
+    ) -> Result<CommandAddress<u32>, ChannelError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.add_command_inner(storage, prior, author, message_id, payload);
+        storage.finish_mode();
+        result
+    }
+
+    /// Records a checkpoint of the member sequences changed since the last
+    /// checkpoint and returns the new checkpoint address.
+    pub fn add_checkpoint<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<CommandAddress<u32>, ChannelError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.add_checkpoint_inner(storage);
+        storage.finish_mode();
+        result
+    }
+
+    /// Writes pending commands into their frontier segment and records the
+    /// channel state in a WAL snapshot.
+    pub fn flush<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), ChannelError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::FlushingCollection(
+            CollectionFlushMode::CommitRegion,
+        ))?;
+        let result = self.flush_inner(storage);
+        storage.finish_mode();
+        result
+    }
+
+    /// Reads the command stored at `address`.
+    pub fn read_command<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        address: &CommandAddress<u32>,
+    ) -> Result<AddCommand<u32, PAYLOAD_MAX>, ChannelError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self.read_command_inner(storage, address);
+        storage.finish_mode();
+        result
+    }
+
+    /// Visits every command from oldest to newest with its address.
+    pub fn visit_commands<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        visitor: F,
+    ) -> Result<(), ChannelError>
+    where
+        F: FnMut(&CommandAddress<u32>, &AddCommand<u32, PAYLOAD_MAX>) -> Result<(), ChannelError>,
+    {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self.visit_commands_inner(storage, visitor);
+        storage.finish_mode();
+        result
+    }
+
+    /// Replays the channel again when a wear-leveling pass moved its segments
+    /// since this handle last replayed.
+    fn refresh<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), ChannelError> {
+        let epoch = storage.runtime().relocation_epoch();
+        if self.relocation_epoch != epoch {
+            self.reset();
+            self.replay(storage)?;
+            self.relocation_epoch = epoch;
+        }
+        Ok(())
+    }
+
+    fn add_member_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        member: MemberId,
+    ) -> Result<(), ChannelError> {
+        if self.members.iter().any(|m| m.member == member) {
+            return Ok(());
+        }
+        let command = AddMemberCommand::into_command(member);
+        self.validate_command(&command)?;
+        let mut offset = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_ADD_MEMBER)?;
+        offset = write_u128(&mut storage.memory.payload_scratch, offset, member.id)?;
+        self.append_update(storage, offset)?;
+        self.apply_command(&command)
+    }
+
+    fn add_command_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        prior: CommandAddress<u32>,
+        author: MemberId,
+        message_id: MessageId,
+        payload: &[u8],
+    ) -> Result<CommandAddress<u32>, ChannelError> {
+        let payload = Vec::<u8, PAYLOAD_MAX>::from_slice(payload).map_err(|_| {
+            ChannelError::CommandTooLarge {
+                len: payload.len(),
+                capacity: PAYLOAD_MAX,
+            }
+        })?;
+        let sender_last = self.get_last_sequence(&author)?;
+        let sequence = self.get_next_sequence();
+        let command = AddCommand {
+            prior,
+            sender_last,
+            sequence,
+            author,
+            message_id,
+            payload,
+        };
+        self.append_command(storage, command)
+    }
+
+    fn append_command<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        command: AddCommand<u32, PAYLOAD_MAX>,
+    ) -> Result<CommandAddress<u32>, ChannelError> {
+        let record_len = command.encoded_len()?;
+        let segment_end = segment_end::<REGION_SIZE>(storage.metadata())?;
+        let capacity = segment_end.saturating_sub(SEGMENT_START);
+        if record_len > capacity {
+            return Err(ChannelError::CommandTooLarge {
+                len: record_len,
+                capacity,
+            });
+        }
+        let command = ChannelCommand::AddCommand(command);
+        match self.validate_command(&command) {
+            Ok(())
+            | Err(ChannelError::PendingLimitReached)
+            | Err(ChannelError::MissingFrontier) => {}
+            Err(error) => return Err(error),
+        }
+
+        self.ensure_frontier(storage)?;
+        let frontier = self.frontier.clone().ok_or(ChannelError::MissingFrontier)?;
+        let fits = frontier
+            .offset
+            .checked_add(record_len)
+            .is_some_and(|end| end <= segment_end);
+        if !fits || self.pending.len() >= self.pending.capacity() {
+            self.flush_inner(storage)?;
+        }
+        self.validate_command(&command)?;
+
+        let ChannelCommand::AddCommand(add) = &command else {
+            return Err(ChannelError::InvalidEncoding);
+        };
+        let address = self.frontier.clone().ok_or(ChannelError::MissingFrontier)?;
+        let mut offset = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_ADD_COMMAND)?;
+        offset = write_address(&mut storage.memory.payload_scratch, offset, &address)?;
+        offset = encode_command(&mut storage.memory.payload_scratch, offset, add)?;
+        self.append_update(storage, offset)?;
+        self.apply_command(&command)?;
+        Ok(address)
+    }
+
+    fn add_checkpoint_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<CommandAddress<u32>, ChannelError> {
+        let mut sequences = Vec::<MemberSequence, CHECKPOINT_MAX>::new();
+        for member in self.updates.iter() {
+            let last_sequence = self.get_last_sequence(member)?;
+            sequences
+                .push(MemberSequence {
+                    member: *member,
+                    last_sequence,
+                })
+                .map_err(|_| ChannelError::CheckpointLimitReached)?;
+        }
+        let command = CheckPointCommand::into_command::<PAYLOAD_MAX>(
+            self.checkpoint.clone(),
+            self.command_count,
+            &sequences,
+        );
+        self.validate_command(&command)?;
+        let ChannelCommand::CheckPointCommand(checkpoint) = &command else {
+            return Err(ChannelError::InvalidEncoding);
+        };
+        let mut offset = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_CHECKPOINT)?;
+        offset = encode_checkpoint(&mut storage.memory.payload_scratch, offset, checkpoint)?;
+        self.append_update(storage, offset)?;
+        self.apply_command(&command)?;
+        Ok(self.checkpoint.clone())
+    }
+
+    fn append_update<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        used: usize,
+    ) -> Result<(), ChannelError> {
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        Ok(())
+    }
+
+    fn ensure_frontier<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), ChannelError> {
+        if self.frontier.is_none() {
+            self.reserve_segment(storage)?;
+        }
+        Ok(())
+    }
+
+    /// Reserves one segment region in a collection transaction so the
+    /// allocation and the WAL update naming it commit together.
+    fn reserve_segment<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), ChannelError> {
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+            )?;
+        let mut allocated = None;
+        let region_index = match self.reserve_segment_transactional(storage, &mut allocated) {
+            Ok(region_index) => region_index,
+            Err(error) => {
+                return match self.rollback_reservation(storage, allocated) {
+                    Ok(()) => Err(error),
+                    Err(cleanup_error) => Err(cleanup_error),
+                };
+            }
+        };
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+            )?;
+        self.apply_reserved_segment(region_index)
+    }
+
+    fn reserve_segment_transactional<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        allocated: &mut Option<u32>,
+    ) -> Result<u32, ChannelError> {
+        let region_index = storage
+            .memory
+            .state
+            .reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+                &mut storage.memory.reclaim_source_regions,
+                &mut storage.memory.active_collections,
+                &mut storage.memory.reclaim_plan,
+                &mut storage.memory.open_plan,
+            )?;
+        *allocated = Some(region_index);
+        let mut offset = write_u8(
+            &mut storage.memory.payload_scratch,
+            0,
+            UPDATE_RESERVE_SEGMENT,
+        )?;
+        offset = write_u32(&mut storage.memory.payload_scratch, offset, region_index)?;
+        self.append_update(storage, offset)?;
+        storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+            )?;
+        Ok(region_index)
+    }
+
+    fn rollback_reservation<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        allocated: Option<u32>,
+    ) -> Result<(), ChannelError> {
+        let mut first_error = None::<ChannelError>;
+        if let Err(error) = storage
+            .memory
+            .state
+            .rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+            )
+        {
+            first_error = Some(error.into());
+        }
+        if let Some(region_index) = allocated {
+            if let Err(error) = storage
+                .memory
+                .state
+                .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    CollectionId(0),
+                    region_index,
+                )
+            {
+                if first_error.is_none() {
+                    first_error = Some(error.into());
+                }
+            }
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn flush_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), ChannelError> {
+        let Some(frontier) = self.frontier.clone() else {
+            return Ok(());
+        };
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        if self.spare_segment.is_none() {
+            self.reserve_segment(storage)?;
+        }
+        let next_segment = self.spare_segment.ok_or(ChannelError::MissingFrontier)?;
+
+        let payload_len = self.encode_segment(next_segment, &mut storage.memory.payload_scratch)?;
+        match storage
+            .memory
+            .state
+            .write_committed_region_in_place::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                frontier.region,
+                self.id,
+                CHANNEL_SEGMENT_V1_FORMAT,
+                &storage.memory.payload_scratch[..payload_len],
+            ) {
+            Ok(()) => {}
+            Err(StorageRuntimeError::BadRegion(failed)) if failed == frontier.region => {
+                return self.flush_relocated(storage, frontier.region, next_segment);
+            }
+            Err(error) => return Err(error.into()),
+        }
+        self.finish_flush(storage, frontier.region, next_segment)
+    }
+
+    /// Advances the frontier past the segment just written to `written` and
+    /// records the channel state in a WAL snapshot.
+    fn finish_flush<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        written: u32,
+        next_segment: u32,
+    ) -> Result<(), ChannelError> {
+        if self.first_segment.is_none() {
+            self.first_segment = Some(written);
+        }
+        self.frontier = Some(CommandAddress {
+            region: next_segment,
+            offset: SEGMENT_START,
+        });
+        self.spare_segment = None;
+        self.pending.clear();
+
+        let snapshot_len = self.encode_snapshot(&mut storage.memory.payload_scratch)?;
+        storage
+            .memory
+            .state
+            .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+                CollectionType::CHANNEL_CODE,
+                &storage.memory.payload_scratch[..snapshot_len],
+            )?;
+        Ok(())
+    }
+
+    /// Flushes the pending commands after the backend reported their frontier
+    /// segment `bad` and storage retired it.
+    ///
+    /// Earlier segments name their successor and commands name earlier
+    /// commands by address, so every flushed segment is copied along with the
+    /// pending commands into freshly reserved segments, with each link
+    /// rewritten to the copies, and the replaced segments are freed. This all
+    /// happens in one collection transaction. Commands in the moved segments
+    /// get new addresses.
+    fn flush_relocated<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        bad: u32,
+        next_segment: u32,
+    ) -> Result<(), ChannelError> {
+        let mut chain = segment_chain::<IO, REGION_COUNT>(
+            storage.backing,
+            self.id,
+            storage.metadata().region_count,
+            self.first_segment,
+            Some(bad),
+        )?
+        .ok_or(ChannelError::Storage(StorageRuntimeError::BadRegion(bad)))?;
+        chain
+            .push(bad)
+            .map_err(|_| ChannelError::Storage(StorageRuntimeError::BadRegion(bad)))?;
+        storage
+            .memory
+            .state
+            .ensure_foreground_allocation_headroom_for::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                &mut storage.memory.reclaim_source_regions,
+                &mut storage.memory.active_collections,
+                &mut storage.memory.reclaim_plan,
+                &mut storage.memory.open_plan,
+                u32::try_from(chain.len()).map_err(|_| ChannelError::LengthOverflow)?,
+            )?;
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+            )?;
+        let relocated: Result<(), ChannelError> = (|| {
+            let copies = self.copy_segments_in_transaction(storage, &chain, next_segment)?;
+            let copy_of = |region: u32| {
+                chain
+                    .iter()
+                    .position(|old| *old == region)
+                    .map_or(region, |index| copies[index])
+            };
+            self.first_segment = self.first_segment.map(copy_of);
+            if self.checkpoint.offset != 0 {
+                self.checkpoint.region = copy_of(self.checkpoint.region);
+            }
+            self.finish_flush(storage, copy_of(bad), next_segment)?;
+            for old_region in chain.iter().copied() {
+                storage
+                    .memory
+                    .state
+                    .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                        storage.backing,
+                        &mut storage.memory.workspace,
+                        self.id,
+                        old_region,
+                    )?;
+            }
+            storage
+                .memory
+                .state
+                .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    self.id,
+                )?;
+            Ok(())
+        })();
+        if let Err(error) = relocated {
+            // The in-memory chain may already name the copies, so it is
+            // rebuilt from the WAL once the transaction is rolled back.
+            let cleanup = storage
+                .memory
+                .state
+                .rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    self.id,
+                )
+                .map_err(ChannelError::from)
+                .and_then(|()| {
+                    self.reset();
+                    self.replay(storage)
+                });
+            return match cleanup {
+                Ok(()) => Err(error),
+                Err(cleanup_error) => Err(cleanup_error),
+            };
+        }
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.id,
+            )?;
+        storage.memory.state.note_region_relocation();
+        self.relocation_epoch = storage.runtime().relocation_epoch();
+        Ok(())
+    }
+
+    /// Copies the segments in `chain` into freshly reserved regions and
+    /// returns the copies in the same order.
+    ///
+    /// Every segment but the last is copied from flash; the last one is the
+    /// retired frontier and is written from the pending commands. Each copy's
+    /// links name the other copies.
+    fn copy_segments_in_transaction<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        chain: &[u32],
+        next_segment: u32,
+    ) -> Result<Vec<u32, REGION_COUNT>, ChannelError> {
+        let mut copies = Vec::<u32, REGION_COUNT>::new();
+        for old_region in chain.iter().copied() {
+            let copy = storage
+                .memory
+                .state
+                .reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    self.id,
+                    &mut storage.memory.reclaim_source_regions,
+                    &mut storage.memory.active_collections,
+                    &mut storage.memory.reclaim_plan,
+                    &mut storage.memory.open_plan,
+                )?;
+            copies
+                .push(copy)
+                .map_err(|_| ChannelError::Storage(StorageRuntimeError::BadRegion(old_region)))?;
+        }
+        let copy_of = |region: u32| {
+            chain
+                .iter()
+                .position(|old| *old == region)
+                .map_or(region, |index| copies[index])
+        };
+        let capacity = storage
+            .memory
+            .state
+            .committed_payload_capacity::<REGION_SIZE>()?;
+        let last = chain.len().saturating_sub(1);
+        for (index, (old_region, copy)) in chain
+            .iter()
+            .copied()
+            .zip(copies.iter().copied())
+            .enumerate()
+        {
+            let payload_len = if index == last {
+                self.encode_segment(next_segment, &mut storage.memory.payload_scratch)?
+            } else {
+                let payload = storage
+                    .memory
+                    .payload_scratch
+                    .get_mut(..capacity)
+                    .ok_or(ChannelError::LengthOverflow)?;
+                storage
+                    .backing
+                    .read_region(old_region, Header::ENCODED_LEN, capacity, |bytes| {
+                        payload.copy_from_slice(bytes);
+                    })
+                    .map_err(StorageRuntimeError::from)?;
+                capacity
+            };
+            retarget_segment_payload(
+                storage
+                    .memory
+                    .payload_scratch
+                    .get_mut(..payload_len)
+                    .ok_or(ChannelError::LengthOverflow)?,
+                copy_of,
+            )?;
+            let written = storage
+                .memory
+                .state
+                .write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    copy,
+                    self.id,
+                    CHANNEL_SEGMENT_V1_FORMAT,
+                    &storage.memory.payload_scratch[..payload_len],
+                )?;
+            // Copies already written name the reserved region, so a copy that
+            // had to move past another bad region fails the whole flush.
+            if written != copy {
+                return Err(ChannelError::Storage(StorageRuntimeError::BadRegion(copy)));
+            }
+        }
+        Ok(copies)
+    }
+
+    fn read_command_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        address: &CommandAddress<u32>,
+    ) -> Result<AddCommand<u32, PAYLOAD_MAX>, ChannelError> {
+        if let Some(frontier) = self.frontier.as_ref() {
+            if frontier.region == address.region {
+                let mut offset = SEGMENT_START;
+                for command in self.pending.iter() {
+                    if offset == address.offset {
+                        return Ok(command.clone());
+                    }
+                    offset = offset
+                        .checked_add(command.encoded_len()?)
+                        .ok_or(ChannelError::LengthOverflow)?;
+                }
+                return Err(ChannelError::InvalidAddress);
+            }
+        }
+
+        let mut found = None;
+        self.visit_flushed_commands(storage, |candidate, command| {
+            if candidate == address {
+                found = Some(command.clone());
+            }
+            Ok(())
+        })?;
+        found.ok_or(ChannelError::InvalidAddress)
+    }
+
+    fn visit_commands_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        mut visitor: F,
+    ) -> Result<(), ChannelError>
+    where
+        F: FnMut(&CommandAddress<u32>, &AddCommand<u32, PAYLOAD_MAX>) -> Result<(), ChannelError>,
+    {
+        self.visit_flushed_commands(storage, &mut visitor)?;
+        let Some(frontier) = self.frontier.as_ref() else {
+            return Ok(());
+        };
+        let mut address = CommandAddress {
+            region: frontier.region,
+            offset: SEGMENT_START,
+        };
+        for command in self.pending.iter() {
+            visitor(&address, command)?;
+            address.offset = address
+                .offset
+                .checked_add(command.encoded_len()?)
+                .ok_or(ChannelError::LengthOverflow)?;
+        }
+        Ok(())
+    }
+
+    fn visit_flushed_commands<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        mut visitor: F,
+    ) -> Result<(), ChannelError>
+    where
+        F: FnMut(&CommandAddress<u32>, &AddCommand<u32, PAYLOAD_MAX>) -> Result<(), ChannelError>,
+    {
+        let frontier_region = self.frontier.as_ref().map(|frontier| frontier.region);
+        let mut segment = self.first_segment;
+        let mut remaining = storage.metadata().region_count;
+        while let Some(region_index) = segment {
+            if Some(region_index) == frontier_region {
+                return Ok(());
+            }
+            remaining = remaining
+                .checked_sub(1)
+                .ok_or(ChannelError::InvalidEncoding)?;
+            let (next, count) = read_segment_prologue(storage.backing, self.id, region_index)?;
+            let mut address = CommandAddress {
+                region: region_index,
+                offset: SEGMENT_START,
+            };
+            for _ in 0..count {
+                let command = read_segment_command::<IO, PAYLOAD_MAX>(storage.backing, &address)?;
+                visitor(&address, &command)?;
+                address.offset = address
+                    .offset
+                    .checked_add(command.encoded_len()?)
+                    .ok_or(ChannelError::LengthOverflow)?;
+            }
+            segment = next;
+        }
+        if frontier_region.is_some() && self.first_segment.is_some() {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        Ok(())
+    }
+
+    fn replay<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), ChannelError> {
+        let collection_id = self.id;
+        let mut transaction = None::<ChannelReplayTransaction>;
+        // WAL head reclaim drops a superseded snapshot but keeps the updates
+        // that followed it, so updates before the first retained basis are
+        // skipped.
+        let mut based = !storage
+            .memory
+            .state
+            .wal_has_collection_basis::<REGION_SIZE, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+                CollectionType::CHANNEL_CODE,
+            )?;
+        let result =
+            storage
+                .memory
+                .state
+                .visit_wal_records::<REGION_SIZE, IO, ChannelError, _>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    |_flash, record| {
+                        match record {
+                            WalRecord::NewCollection {
+                                collection_id: seen,
+                                collection_type,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::CHANNEL_CODE =>
+                            {
+                                based = true;
+                                self.reset();
+                            }
+                            WalRecord::BeginTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                transaction = Some(ChannelReplayTransaction {
+                                    transaction_log_id,
+                                    joined: transaction_log_id == 0,
+                                    reserved: None,
+                                });
+                            }
+                            WalRecord::AddTransactionCollection {
+                                collection_id: seen,
+                                ..
+                            } if seen == collection_id => {
+                                if let Some(open) = transaction.as_mut() {
+                                    open.joined = true;
+                                }
+                            }
+                            WalRecord::Snapshot {
+                                collection_id: seen,
+                                collection_type,
+                                payload,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::CHANNEL_CODE =>
+                            {
+                                based = true;
+                                self.apply_snapshot(payload)?;
+                            }
+                            WalRecord::Update {
+                                collection_id: seen,
+                                payload,
+                            } if seen == collection_id && based => match transaction.as_mut() {
+                                Some(open) if open.joined => {
+                                    // Channel transactions only reserve segments.
+                                    if open.reserved.is_some() {
+                                        return Err(ChannelError::InvalidEncoding);
+                                    }
+                                    open.reserved = Some(decode_reserve_segment(payload)?);
+                                }
+                                _ => self.apply_update_payload(payload)?,
+                            },
+                            WalRecord::CommitTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    if let Some(region_index) =
+                                        transaction.take().and_then(|open| open.reserved)
+                                    {
+                                        self.apply_reserved_segment(region_index)?;
+                                    }
+                                }
+                            }
+                            WalRecord::RollbackTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    transaction = None;
+                                }
+                            }
+                            WalRecord::DropCollection {
+                                collection_id: seen,
+                            } if seen == collection_id => {
+                                self.reset();
+                            }
+                            _ => {}
+                        }
+                        Ok(())
+                    },
+                );
+        match result {
+            Ok(()) => Ok(()),
+            Err(StorageVisitError::Storage(error)) => Err(ChannelError::Storage(error)),
+            Err(StorageVisitError::Visitor(error)) => Err(error),
+        }
+    }
+
+    fn apply_update_payload(&mut self, payload: &[u8]) -> Result<(), ChannelError> {
+        let mut offset = 0usize;
+        match read_u8(payload, &mut offset)? {
+            UPDATE_ADD_MEMBER => {
+                let member = MemberId::new(read_u128(payload, &mut offset)?);
+                self.apply_command(&AddMemberCommand::into_command(member))?;
+            }
+            UPDATE_ADD_COMMAND => {
+                let address = read_address(payload, &mut offset)?;
+                let command = decode_command::<PAYLOAD_MAX>(payload, &mut offset)?;
+                if self.frontier.as_ref() != Some(&address) {
+                    return Err(ChannelError::InvalidEncoding);
+                }
+                self.apply_command(&ChannelCommand::AddCommand(command))?;
+            }
+            UPDATE_CHECKPOINT => {
+                let command = decode_checkpoint::<CHECKPOINT_MAX>(payload, &mut offset)?;
+                self.apply_command(&ChannelCommand::CheckPointCommand(command))?;
+            }
+            UPDATE_RESERVE_SEGMENT => {
+                // Reclaim can copy a committed reservation out of its
+                // transaction, so it may also appear as a plain update.
+                self.apply_reserved_segment(read_u32(payload, &mut offset)?)?;
+            }
+            _ => return Err(ChannelError::InvalidEncoding),
+        }
+        if offset != payload.len() {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        Ok(())
+    }
+
+    fn encode_segment(&self, next_segment: u32, output: &mut [u8]) -> Result<usize, ChannelError> {
+        let count = u32::try_from(self.pending.len()).map_err(|_| ChannelError::LengthOverflow)?;
+        let mut offset = write_bytes(output, 0, &SEGMENT_MAGIC)?;
+        offset = write_u16(output, offset, SEGMENT_VERSION)?;
+        offset = write_opt_region(output, offset, Some(next_segment))?;
+        offset = write_u32(output, offset, count)?;
+        for command in self.pending.iter() {
+            offset = encode_command(output, offset, command)?;
+        }
+        let checksum = crc32(output.get(..offset).ok_or(ChannelError::LengthOverflow)?);
+        write_u32(output, offset, checksum)
+    }
+
+    fn encode_snapshot(&self, output: &mut [u8]) -> Result<usize, ChannelError> {
+        let frontier = self.frontier.as_ref();
+        let mut offset = write_bytes(output, 0, &SNAPSHOT_MAGIC)?;
+        offset = write_u16(output, offset, SNAPSHOT_VERSION)?;
+        offset = write_u64(output, offset, self.next_sequence.0)?;
+        offset = write_u64(output, offset, self.command_count)?;
+        offset = write_address(output, offset, &self.checkpoint)?;
+        offset = write_opt_region(output, offset, self.first_segment)?;
+        offset = write_opt_region(output, offset, frontier.map(|frontier| frontier.region))?;
+        offset = write_opt_region(output, offset, self.spare_segment)?;
+        let members =
+            u32::try_from(self.members.len()).map_err(|_| ChannelError::LengthOverflow)?;
+        offset = write_u32(output, offset, members)?;
+        for member in self.members.iter() {
+            offset = write_u128(output, offset, member.member.id)?;
+            offset = write_u64(output, offset, member.last_sequence.0)?;
+        }
+        let updates =
+            u32::try_from(self.updates.len()).map_err(|_| ChannelError::LengthOverflow)?;
+        offset = write_u32(output, offset, updates)?;
+        for member in self.updates.iter() {
+            offset = write_u128(output, offset, member.id)?;
+        }
+        Ok(offset)
+    }
+
+    /// Replaces the channel state with a snapshot.
+    ///
+    /// Snapshots are taken right after a flush, so they never carry pending
+    /// commands and the frontier always starts at the segment's first slot.
+    fn apply_snapshot(&mut self, payload: &[u8]) -> Result<(), ChannelError> {
+        let mut offset = 0usize;
+        if read_bytes(payload, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+            || read_u16(payload, &mut offset)? != SNAPSHOT_VERSION
+        {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        self.reset();
+        self.next_sequence = ChannelSequence(read_u64(payload, &mut offset)?);
+        self.command_count = read_u64(payload, &mut offset)?;
+        self.checkpoint = read_address(payload, &mut offset)?;
+        self.first_segment = read_opt_region(payload, &mut offset)?;
+        self.frontier = read_opt_region(payload, &mut offset)?.map(|region| CommandAddress {
+            region,
+            offset: SEGMENT_START,
+        });
+        self.spare_segment = read_opt_region(payload, &mut offset)?;
+        if self.next_sequence.0 == 0 || (self.frontier.is_none() && self.spare_segment.is_some()) {
+            return Err(ChannelError::InvalidEncoding);
+        }
+
+        let members = read_u32(payload, &mut offset)?;
+        for _ in 0..members {
+            let member = MemberId::new(read_u128(payload, &mut offset)?);
+            let last_sequence = ChannelSequence(read_u64(payload, &mut offset)?);
+            if last_sequence >= self.next_sequence {
+                return Err(ChannelError::InvalidSequence {
+                    member,
+                    sequence: last_sequence,
+                });
+            }
+            if self.members.iter().any(|m| m.member == member) {
+                return Err(ChannelError::InvalidEncoding);
+            }
+            self.members
+                .push(MemberSequence {
+                    member,
+                    last_sequence,
+                })
+                .map_err(|_| ChannelError::UserLimitReached)?;
+        }
+        let updates = read_u32(payload, &mut offset)?;
+        for _ in 0..updates {
+            let member = MemberId::new(read_u128(payload, &mut offset)?);
+            let _ = self.get_last_sequence(&member)?;
+            if self.updates.iter().any(|m| *m == member) {
+                return Err(ChannelError::InvalidEncoding);
+            }
+            self.updates
+                .push(member)
+                .map_err(|_| ChannelError::NeedsCheckpoint)?;
+        }
+        if offset != payload.len() {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        Ok(())
+    }
+}
+
+fn validate_collection<
+    IO: FlashIo,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+) -> Result<(), ChannelError> {
+    let collection = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .ok_or(ChannelError::UnknownCollection(collection_id))?;
+    if collection.basis() == StartupCollectionBasis::Dropped {
+        return Err(ChannelError::DroppedCollection(collection_id));
+    }
+    storage.runtime().ensure_not_quarantined(collection_id)?;
+    if collection.collection_type() != Some(CollectionType::CHANNEL_CODE) {
+        return Err(ChannelError::CollectionTypeMismatch {
+            collection_id,
+            actual: collection.collection_type(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn empty_snapshot() -> &'static [u8] {
+    &EMPTY_SNAPSHOT
+}
+
+/// Returns the flushed segments an encoded snapshot chains from its first
+/// segment up to its frontier, oldest first, or `None` when the chain holds
+/// more than `MAX_CHAIN` segments.
+pub(crate) fn snapshot_segment_chain<IO: FlashIo, const MAX_CHAIN: usize>(
+    flash: &mut IO,
+    collection_id: CollectionId,
+    region_count: u32,
+    snapshot: &[u8],
+) -> Result<Option<Vec<u32, MAX_CHAIN>>, ChannelError> {
+    let mut offset = 0usize;
+    if read_bytes(snapshot, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+        || read_u16(snapshot, &mut offset)? != SNAPSHOT_VERSION
+    {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    let _next_sequence = read_u64(snapshot, &mut offset)?;
+    let _command_count = read_u64(snapshot, &mut offset)?;
+    let _checkpoint = read_address(snapshot, &mut offset)?;
+    let first = read_opt_region(snapshot, &mut offset)?;
+    let frontier = read_opt_region(snapshot, &mut offset)?;
+    segment_chain(flash, collection_id, region_count, first, frontier)
+}
+
+/// Returns the flushed segments chained from `first` up to `frontier`, oldest
+/// first, or `None` when the chain holds more than `MAX_CHAIN` segments.
+fn segment_chain<IO: FlashIo, const MAX_CHAIN: usize>(
+    flash: &mut IO,
+    collection_id: CollectionId,
+    region_count: u32,
+    first: Option<u32>,
+    frontier: Option<u32>,
+) -> Result<Option<Vec<u32, MAX_CHAIN>>, ChannelError> {
+    let mut segment = first;
+    let mut chain = Vec::new();
+    while let Some(region_index) = segment {
+        if Some(region_index) == frontier {
+            return Ok(Some(chain));
+        }
+        if chain.len() >= usize::try_from(region_count).unwrap_or(usize::MAX) {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        if chain.push(region_index).is_err() {
+            return Ok(None);
+        }
+        segment = read_segment_prologue(flash, collection_id, region_index)?.0;
+    }
+    if frontier.is_some() && !chain.is_empty() {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    Ok(Some(chain))
+}
+
+/// Rewrites the next segment and every command `prior` region a segment
+/// payload names through `retarget`, then reseals its checksum.
+pub(crate) fn retarget_segment_payload(
+    payload: &mut [u8],
+    retarget: impl Fn(u32) -> u32,
+) -> Result<(), ChannelError> {
+    let mut offset = 0usize;
+    if read_bytes(payload, &mut offset, SEGMENT_MAGIC.len())? != SEGMENT_MAGIC {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    let version = read_u16(payload, &mut offset)?;
+    if version != SEGMENT_VERSION && version != SEGMENT_VERSION_UNCHECKED {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    let next_field = offset;
+    if let Some(next) = read_opt_region(payload, &mut offset)? {
+        write_opt_region(payload, next_field, Some(retarget(next)))?;
+    }
+    for _ in 0..read_u32(payload, &mut offset)? {
+        let prior_field = offset;
+        let prior = read_address(payload, &mut offset)?;
+        // The zero address names no command and is left as written.
+        if prior.offset != 0 {
+            write_u32(payload, prior_field, retarget(prior.region))?;
+        }
+        read_bytes(
+            payload,
+            &mut offset,
+            COMMAND_FIXED_LEN - 2 * size_of::<u32>() - size_of::<u32>(),
+        )?;
+        let payload_len = usize::try_from(read_u32(payload, &mut offset)?)
+            .map_err(|_| ChannelError::LengthOverflow)?;
+        read_bytes(payload, &mut offset, payload_len)?;
+    }
+    if version == SEGMENT_VERSION {
+        let checksum = crc32(payload.get(..offset).ok_or(ChannelError::LengthOverflow)?);
+        write_u32(payload, offset, checksum)?;
+    }
+    Ok(())
+}
+
+/// Rewrites the checkpoint address and first segment an encoded snapshot
+/// names through `retarget`.
+pub(crate) fn retarget_snapshot(
+    snapshot: &mut [u8],
+    retarget: impl Fn(u32) -> u32,
+) -> Result<(), ChannelError> {
+    let mut offset = 0usize;
+    if read_bytes(snapshot, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+        || read_u16(snapshot, &mut offset)? != SNAPSHOT_VERSION
+    {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    let _next_sequence = read_u64(snapshot, &mut offset)?;
+    let _command_count = read_u64(snapshot, &mut offset)?;
+    let checkpoint_field = offset;
+    let checkpoint = read_address(snapshot, &mut offset)?;
+    if checkpoint.offset != 0 {
+        write_u32(snapshot, checkpoint_field, retarget(checkpoint.region))?;
+    }
+    let first_field = offset;
+    if let Some(first) = read_opt_region(snapshot, &mut offset)? {
+        write_opt_region(snapshot, first_field, Some(retarget(first)))?;
+    }
+    Ok(())
+}
+
+const fn encode_empty_snapshot() -> [u8; EMPTY_SNAPSHOT_LEN] {
+    let mut bytes = [0u8; EMPTY_SNAPSHOT_LEN];
+    let mut index = 0;
+    while index < SNAPSHOT_MAGIC.len() {
+        bytes[index] = SNAPSHOT_MAGIC[index];
+        index += 1;
+    }
+    let version = SNAPSHOT_VERSION.to_le_bytes();
+    bytes[index] = version[0];
+    bytes[index + 1] = version[1];
+    // An empty channel starts at sequence 1; every other field is zero or an
+    // absent region.
+    bytes[index + 2] = 1;
+    bytes
+}
+
+/// Returns the region offset one past the last byte a segment's commands may
+/// use, leaving room for the trailing checksum.
+fn segment_end<const REGION_SIZE: usize>(metadata: StorageMetadata) -> Result<usize, ChannelError> {
+    let granule =
+        usize::try_from(metadata.wal_write_granule).map_err(|_| ChannelError::LengthOverflow)?;
+    if granule == 0 {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    (REGION_SIZE - REGION_SIZE % granule)
+        .checked_sub(SEGMENT_CHECKSUM_LEN)
+        .ok_or(ChannelError::LengthOverflow)
+}
+
+/// Returns whether a `channel_segment_v1` payload frames its commands and
+/// matches the CRC-32C that follows them.
+///
+/// Version 1 segments predate the checksum and are accepted on their framing
+/// alone.
+pub(crate) fn segment_checksum_matches(payload: &[u8]) -> bool {
+    match decode_segment_checksum(payload) {
+        Ok((commands_end, Some(checksum))) => crc32(&payload[..commands_end]) == checksum,
+        Ok((_, None)) => true,
+        Err(_) => false,
+    }
+}
+
+/// Returns where a segment payload's commands end and, for version 2
+/// segments, the checksum stored after them.
+fn decode_segment_checksum(payload: &[u8]) -> Result<(usize, Option<u32>), ChannelError> {
+    let mut offset = 0usize;
+    if read_bytes(payload, &mut offset, SEGMENT_MAGIC.len())? != SEGMENT_MAGIC {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    let version = read_u16(payload, &mut offset)?;
+    if version != SEGMENT_VERSION && version != SEGMENT_VERSION_UNCHECKED {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    read_opt_region(payload, &mut offset)?;
+    for _ in 0..read_u32(payload, &mut offset)? {
+        read_bytes(payload, &mut offset, COMMAND_FIXED_LEN - size_of::<u32>())?;
+        let payload_len = usize::try_from(read_u32(payload, &mut offset)?)
+            .map_err(|_| ChannelError::LengthOverflow)?;
+        read_bytes(payload, &mut offset, payload_len)?;
+    }
+    let commands_end = offset;
+    if version == SEGMENT_VERSION_UNCHECKED {
+        return Ok((commands_end, None));
+    }
+    Ok((commands_end, Some(read_u32(payload, &mut offset)?)))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    CRC32C.checksum(bytes)
+}
+
+fn read_segment_prologue<IO: FlashIo>(
+    flash: &mut IO,
+    collection_id: CollectionId,
+    region_index: u32,
+) -> Result<(Option<u32>, u32), ChannelError> {
+    let header = flash
+        .read_region(region_index, 0, Header::ENCODED_LEN, Header::decode)
+        .map_err(StorageRuntimeError::from)?
+        .map_err(|_| ChannelError::InvalidEncoding)?;
+    if header.collection_id != collection_id
+        || header.collection_format != CHANNEL_SEGMENT_V1_FORMAT
+    {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    flash
+        .read_region(
+            region_index,
+            Header::ENCODED_LEN,
+            SEGMENT_PROLOGUE_LEN,
+            |bytes| {
+                let mut offset = 0usize;
+                if read_bytes(bytes, &mut offset, SEGMENT_MAGIC.len())? != SEGMENT_MAGIC {
+                    return Err(ChannelError::InvalidEncoding);
+                }
+                let version = read_u16(bytes, &mut offset)?;
+                if version != SEGMENT_VERSION && version != SEGMENT_VERSION_UNCHECKED {
+                    return Err(ChannelError::InvalidEncoding);
+                }
+                let next = read_opt_region(bytes, &mut offset)?;
+                let count = read_u32(bytes, &mut offset)?;
+                Ok((next, count))
+            },
+        )
+        .map_err(StorageRuntimeError::from)?
+}
+
+fn read_segment_command<IO: FlashIo, const PAYLOAD_MAX: usize>(
+    flash: &mut IO,
+    address: &CommandAddress<u32>,
+) -> Result<AddCommand<u32, PAYLOAD_MAX>, ChannelError> {
+    let mut command = flash
+        .read_region(address.region, address.offset, COMMAND_FIXED_LEN, |bytes| {
+            let mut offset = 0usize;
+            decode_command_fixed::<PAYLOAD_MAX>(bytes, &mut offset)
+        })
+        .map_err(StorageRuntimeError::from)??;
+    let (command, payload_len) = (&mut command.0, command.1);
+    let payload_offset = address
+        .offset
+        .checked_add(COMMAND_FIXED_LEN)
+        .ok_or(ChannelError::LengthOverflow)?;
+    flash
+        .read_region(address.region, payload_offset, payload_len, |bytes| {
+            command.payload.extend_from_slice(bytes)
+        })
+        .map_err(StorageRuntimeError::from)?
+        .map_err(|_| ChannelError::InvalidEncoding)?;
+    Ok(core::mem::take(command))
+}
+
+fn decode_reserve_segment(payload: &[u8]) -> Result<u32, ChannelError> {
+    let mut offset = 0usize;
+    if read_u8(payload, &mut offset)? != UPDATE_RESERVE_SEGMENT {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    let region_index = read_u32(payload, &mut offset)?;
+    if offset != payload.len() {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    Ok(region_index)
+}
+
+fn encode_command<const PAYLOAD_MAX: usize>(
+    output: &mut [u8],
+    mut offset: usize,
+    command: &AddCommand<u32, PAYLOAD_MAX>,
+) -> Result<usize, ChannelError> {
+    let payload_len =
+        u32::try_from(command.payload.len()).map_err(|_| ChannelError::LengthOverflow)?;
+    offset = write_address(output, offset, &command.prior)?;
+    offset = write_u64(output, offset, command.sender_last.0)?;
+    offset = write_u64(output, offset, command.sequence.0)?;
+    offset = write_u128(output, offset, command.author.id)?;
+    offset = write_u128(output, offset, command.message_id.id)?;
+    offset = write_u32(output, offset, payload_len)?;
+    write_bytes(output, offset, &command.payload)
+}
+
+fn decode_command_fixed<const PAYLOAD_MAX: usize>(
+    input: &[u8],
+    offset: &mut usize,
+) -> Result<(AddCommand<u32, PAYLOAD_MAX>, usize), ChannelError> {
+    let prior = read_address(input, offset)?;
+    let sender_last = ChannelSequence(read_u64(input, offset)?);
+    let sequence = ChannelSequence(read_u64(input, offset)?);
+    let author = MemberId::new(read_u128(input, offset)?);
+    let message_id = MessageId::new(read_u128(input, offset)?);
+    let payload_len =
+        usize::try_from(read_u32(input, offset)?).map_err(|_| ChannelError::LengthOverflow)?;
+    if payload_len > PAYLOAD_MAX {
+        return Err(ChannelError::CommandTooLarge {
+            len: payload_len,
+            capacity: PAYLOAD_MAX,
+        });
+    }
+    let command = AddCommand {
+        prior,
+        sender_last,
+        sequence,
+        author,
+        message_id,
+        payload: Vec::new(),
+    };
+    Ok((command, payload_len))
+}
+
+fn decode_command<const PAYLOAD_MAX: usize>(
+    input: &[u8],
+    offset: &mut usize,
+) -> Result<AddCommand<u32, PAYLOAD_MAX>, ChannelError> {
+    let (mut command, payload_len) = decode_command_fixed::<PAYLOAD_MAX>(input, offset)?;
+    command
+        .payload
+        .extend_from_slice(read_bytes(input, offset, payload_len)?)
+        .map_err(|_| ChannelError::InvalidEncoding)?;
+    Ok(command)
+}
+
+fn encode_checkpoint<const CHECKPOINT_MAX: usize>(
+    output: &mut [u8],
+    mut offset: usize,
+    command: &CheckPointCommand<u32, CHECKPOINT_MAX>,
+) -> Result<usize, ChannelError> {
+    let count = u32::try_from(command.sequences.len()).map_err(|_| ChannelError::LengthOverflow)?;
+    offset = write_address(output, offset, &command.previous_checkpoint)?;
+    offset = write_u64(output, offset, command.command_count)?;
+    offset = write_u32(output, offset, count)?;
+    for sequence in command.sequences.iter() {
+        offset = write_u128(output, offset, sequence.member.id)?;
+        offset = write_u64(output, offset, sequence.last_sequence.0)?;
+    }
+    Ok(offset)
+}
+
+fn decode_checkpoint<const CHECKPOINT_MAX: usize>(
+    input: &[u8],
+    offset: &mut usize,
+) -> Result<CheckPointCommand<u32, CHECKPOINT_MAX>, ChannelError> {
+    let previous_checkpoint = read_address(input, offset)?;
+    let command_count = read_u64(input, offset)?;
+    let count =
+        usize::try_from(read_u32(input, offset)?).map_err(|_| ChannelError::LengthOverflow)?;
+    let encoded_len = count
+        .checked_mul(MEMBER_SEQUENCE_ENCODED_LEN)
+        .ok_or(ChannelError::LengthOverflow)?;
+    if input.len().saturating_sub(*offset) < encoded_len {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    let mut sequences = Vec::new();
+    for _ in 0..count {
+        let member = MemberId::new(read_u128(input, offset)?);
+        let last_sequence = ChannelSequence(read_u64(input, offset)?);
+        sequences
+            .push(MemberSequence {
+                member,
+                last_sequence,
+            })
+            .map_err(|_| ChannelError::CheckpointLimitReached)?;
+    }
+    Ok(CheckPointCommand {
+        previous_checkpoint,
+        command_count,
+        sequences,
+    })
+}
+
+fn write_address(
+    output: &mut [u8],
+    offset: usize,
+    address: &CommandAddress<u32>,
+) -> Result<usize, ChannelError> {
+    let address_offset = u32::try_from(address.offset).map_err(|_| ChannelError::LengthOverflow)?;
+    let offset = write_u32(output, offset, address.region)?;
+    write_u32(output, offset, address_offset)
+}
+
+fn read_address(input: &[u8], offset: &mut usize) -> Result<CommandAddress<u32>, ChannelError> {
+    let region = read_u32(input, offset)?;
+    let address_offset =
+        usize::try_from(read_u32(input, offset)?).map_err(|_| ChannelError::LengthOverflow)?;
+    Ok(CommandAddress {
+        region,
+        offset: address_offset,
+    })
+}
+
+fn write_opt_region(
+    output: &mut [u8],
+    offset: usize,
+    region: Option<u32>,
+) -> Result<usize, ChannelError> {
+    match region {
+        Some(region) => {
+            let offset = write_u8(output, offset, 1)?;
+            write_u32(output, offset, region)
+        }
+        None => {
+            let offset = write_u8(output, offset, 0)?;
+            write_u32(output, offset, 0)
+        }
+    }
+}
+
+fn read_opt_region(input: &[u8], offset: &mut usize) -> Result<Option<u32>, ChannelError> {
+    let tag = read_u8(input, offset)?;
+    let region = read_u32(input, offset)?;
+    match tag {
+        0 if region == 0 => Ok(None),
+        1 => Ok(Some(region)),
+        _ => Err(ChannelError::InvalidEncoding),
+    }
+}
+
+fn write_u8(output: &mut [u8], offset: usize, value: u8) -> Result<usize, ChannelError> {
+    write_bytes(output, offset, &[value])
+}
+
+fn write_u16(output: &mut [u8], offset: usize, value: u16) -> Result<usize, ChannelError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u32(output: &mut [u8], offset: usize, value: u32) -> Result<usize, ChannelError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u64(output: &mut [u8], offset: usize, value: u64) -> Result<usize, ChannelError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u128(output: &mut [u8], offset: usize, value: u128) -> Result<usize, ChannelError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_bytes(output: &mut [u8], offset: usize, bytes: &[u8]) -> Result<usize, ChannelError> {
+    let end = offset
+        .checked_add(bytes.len())
+        .ok_or(ChannelError::LengthOverflow)?;
+    let target = output
+        .get_mut(offset..end)
+        .ok_or(ChannelError::LengthOverflow)?;
+    target.copy_from_slice(bytes);
+    Ok(end)
+}
+
+fn read_u8(input: &[u8], offset: &mut usize) -> Result<u8, ChannelError> {
+    let bytes = read_bytes(input, offset, size_of::<u8>())?;
+    Ok(bytes[0])
+}
+
+fn read_u16(input: &[u8], offset: &mut usize) -> Result<u16, ChannelError> {
+    let mut value = [0u8; size_of::<u16>()];
+    value.copy_from_slice(read_bytes(input, offset, size_of::<u16>())?);
+    Ok(u16::from_le_bytes(value))
+}
+
+fn read_u32(input: &[u8], offset: &mut usize) -> Result<u32, ChannelError> {
+    let mut value = [0u8; size_of::<u32>()];
+    value.copy_from_slice(read_bytes(input, offset, size_of::<u32>())?);
+    Ok(u32::from_le_bytes(value))
+}
+
+fn read_u64(input: &[u8], offset: &mut usize) -> Result<u64, ChannelError> {
+    let mut value = [0u8; size_of::<u64>()];
+    value.copy_from_slice(read_bytes(input, offset, size_of::<u64>())?);
+    Ok(u64::from_le_bytes(value))
+}
+
+fn read_u128(input: &[u8], offset: &mut usize) -> Result<u128, ChannelError> {
+    let mut value = [0u8; size_of::<u128>()];
+    value.copy_from_slice(read_bytes(input, offset, size_of::<u128>())?);
+    Ok(u128::from_le_bytes(value))
+}
+
+fn read_bytes<'a>(
+    input: &'a [u8],
+    offset: &mut usize,
+    len: usize,
+) -> Result<&'a [u8], ChannelError> {
+    let end = offset
+        .checked_add(len)
+        .ok_or(ChannelError::LengthOverflow)?;
+    let bytes = input
+        .get(*offset..end)
+        .ok_or(ChannelError::InvalidEncoding)?;
+    *offset = end;
+    Ok(bytes)
 }
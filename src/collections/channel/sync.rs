@@ -0,0 +1,565 @@
+//! Transport-agnostic channel replica synchronization.
+//!
+//! Synchronization is pull based. The receiving replica encodes a request
+//! carrying its per-member sequence vector, the sending replica answers with
+//! the members and commands that vector does not cover, and the receiver
+//! merges that response. A response that did not fit in its buffer sets the
+//! `more` flag, and the receiver repeats the exchange until it is clear.
+
+use core::mem::size_of;
+
+use heapless::Vec;
+
+use super::{
+    read_bytes, read_u128, read_u16, read_u32, read_u64, read_u8, write_bytes, write_u128,
+    write_u16, write_u32, write_u64, write_u8, AddCommand, Channel, ChannelError, ChannelSequence,
+    CommandAddress, MemberId, MemberSequence, MessageId,
+};
+use crate::flash_io::FlashIo;
+use crate::mode::{CollectionUpdateMode, ReadMode, StorageMode};
+use crate::vec_like::VecLike;
+use crate::Storage;
+
+const SYNC_REQUEST_MAGIC: [u8; 4] = *b"CHRQ";
+const SYNC_RESPONSE_MAGIC: [u8; 4] = *b"CHRS";
+const SYNC_VERSION: u16 = 1;
+const SYNC_MORE: u8 = 0x01;
+const SYNC_HEADER_LEN: usize = SYNC_REQUEST_MAGIC.len() + size_of::<u16>();
+const SYNC_REQUEST_ENTRY_LEN: usize = size_of::<u128>() + size_of::<u64>();
+const SYNC_RESPONSE_MEMBER_LEN: usize = size_of::<u128>();
+const SYNC_COMMAND_FIXED_LEN: usize =
+    2 * size_of::<u64>() + 2 * size_of::<u128>() + size_of::<u32>();
+
+/// Most commands one sync response carries.
+///
+/// Merging indexes a response in fixed arrays of this size, so the
+/// duplicate `MessageId` check and the predecessor lookup stay bounded.
+pub const CHANNEL_SYNC_MAX_COMMANDS: usize = 32;
+
+/// Summary of one encoded sync response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelSyncBatch {
+    /// Encoded response length in bytes.
+    pub len: usize,
+    /// Members the requester did not know about.
+    pub members: u32,
+    /// Commands the requester was missing that fit in this response.
+    pub commands: u32,
+    /// More missing commands remain for a later exchange.
+    pub more: bool,
+}
+
+/// Summary of one merged sync response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelSyncMerge {
+    /// Members added to the local channel.
+    pub members_added: u32,
+    /// Commands appended to the local channel.
+    pub merged: u32,
+    /// Commands skipped because the local channel already had them.
+    pub skipped: u32,
+    /// The sender had more missing commands than fit in the response.
+    pub more: bool,
+}
+
+/// One command as carried on the wire.
+///
+/// `prior` names a location in the author's replica, so it is not sent; the
+/// merging replica points it at its own predecessor instead.
+struct SyncCommand<'a> {
+    sender_last: ChannelSequence,
+    sequence: ChannelSequence,
+    author: MemberId,
+    message_id: MessageId,
+    payload: &'a [u8],
+}
+
+/// Predecessor search state for one new command in a merged response.
+struct SyncPrior {
+    sequence: ChannelSequence,
+    best: Option<(ChannelSequence, CommandAddress<u32>)>,
+}
+
+/// A decoded and structurally validated sync response.
+struct SyncResponse<'a> {
+    more: bool,
+    member_count: u32,
+    members: &'a [u8],
+    command_count: u32,
+    commands: &'a [u8],
+}
+
+impl<'a> SyncResponse<'a> {
+    fn decode<const PAYLOAD_MAX: usize>(input: &'a [u8]) -> Result<Self, ChannelError> {
+        let mut offset = 0usize;
+        read_sync_header(input, &mut offset, SYNC_RESPONSE_MAGIC)?;
+        let flags = read_u8(input, &mut offset)?;
+        if flags & !SYNC_MORE != 0 {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        let member_count = read_u32(input, &mut offset)?;
+        let members_len = usize::try_from(member_count)
+            .ok()
+            .and_then(|count| count.checked_mul(SYNC_RESPONSE_MEMBER_LEN))
+            .ok_or(ChannelError::InvalidEncoding)?;
+        let members = read_bytes(input, &mut offset, members_len)?;
+        let command_count = read_u32(input, &mut offset)?;
+        if usize::try_from(command_count).map_or(true, |count| count > CHANNEL_SYNC_MAX_COMMANDS) {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        let commands = input.get(offset..).ok_or(ChannelError::InvalidEncoding)?;
+
+        let response = Self {
+            more: flags & SYNC_MORE != 0,
+            member_count,
+            members,
+            command_count,
+            commands,
+        };
+        // Walk every command once so later passes can decode without
+        // re-checking lengths, and so trailing bytes are rejected.
+        let mut cursor = 0usize;
+        for _ in 0..command_count {
+            let command = read_sync_command(commands, &mut cursor)?;
+            if command.payload.len() > PAYLOAD_MAX {
+                return Err(ChannelError::CommandTooLarge {
+                    len: command.payload.len(),
+                    capacity: PAYLOAD_MAX,
+                });
+            }
+        }
+        if cursor != commands.len() {
+            return Err(ChannelError::InvalidEncoding);
+        }
+        Ok(response)
+    }
+
+    fn member(&self, index: u32) -> Result<MemberId, ChannelError> {
+        let mut offset = usize::try_from(index)
+            .ok()
+            .and_then(|index| index.checked_mul(SYNC_RESPONSE_MEMBER_LEN))
+            .ok_or(ChannelError::LengthOverflow)?;
+        Ok(MemberId::new(read_u128(self.members, &mut offset)?))
+    }
+
+    fn visit_commands<F>(&self, mut visitor: F) -> Result<(), ChannelError>
+    where
+        F: FnMut(&SyncCommand<'a>) -> Result<(), ChannelError>,
+    {
+        let mut offset = 0usize;
+        for _ in 0..self.command_count {
+            let command = read_sync_command(self.commands, &mut offset)?;
+            visitor(&command)?;
+        }
+        Ok(())
+    }
+}
+
+impl<
+        'a,
+        'b,
+        'c,
+        M: VecLike<MemberSequence>,
+        U: VecLike<MemberId>,
+        P: VecLike<AddCommand<u32, PAYLOAD_MAX>>,
+        const PAYLOAD_MAX: usize,
+        const CHECKPOINT_MAX: usize,
+    > Channel<'a, 'b, 'c, u32, M, U, P, PAYLOAD_MAX, CHECKPOINT_MAX>
+{
+    /// Encodes a sync request carrying this replica's per-member sequence
+    /// vector and returns its length.
+    pub fn encode_sync_request(&self, output: &mut [u8]) -> Result<usize, ChannelError> {
+        let count = u32::try_from(self.members.len()).map_err(|_| ChannelError::LengthOverflow)?;
+        let len = self
+            .members
+            .len()
+            .checked_mul(SYNC_REQUEST_ENTRY_LEN)
+            .and_then(|len| len.checked_add(SYNC_HEADER_LEN + size_of::<u32>()))
+            .ok_or(ChannelError::LengthOverflow)?;
+        if len > output.len() {
+            return Err(ChannelError::SyncBufferTooSmall {
+                len,
+                capacity: output.len(),
+            });
+        }
+        let mut offset = write_sync_header(output, SYNC_REQUEST_MAGIC)?;
+        offset = write_u32(output, offset, count)?;
+        for member in self.members.iter() {
+            offset = write_u128(output, offset, member.member.id())?;
+            offset = write_u64(output, offset, member.last_sequence.get())?;
+        }
+        Ok(offset)
+    }
+
+    /// Encodes the members and commands missing from a peer's sync request
+    /// into `output`.
+    ///
+    /// A command is missing when its sequence is newer than the peer's last
+    /// sequence for its author. Commands are encoded oldest to newest and the
+    /// response never grows past `output` or [`CHANNEL_SYNC_MAX_COMMANDS`];
+    /// when a missing command does not fit, it and every later command are
+    /// left for the next exchange.
+    pub fn encode_sync_response<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        request: &[u8],
+        output: &mut [u8],
+    ) -> Result<ChannelSyncBatch, ChannelError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self.encode_sync_response_inner(storage, request, output);
+        storage.finish_mode();
+        result
+    }
+
+    /// Merges a sync response from a peer replica.
+    ///
+    /// The whole response is validated before anything is written: a
+    /// malformed frame, a `MessageId` repeated within the response, or a new
+    /// command reusing a `MessageId` the local channel already holds is
+    /// rejected. Commands the local channel already has are skipped. A
+    /// command whose `sender_last` is not its author's local last sequence
+    /// means the response skipped commands and fails with
+    /// [`ChannelError::SequenceMismatch`]; commands merged before it stay
+    /// merged, so the next exchange resumes after them.
+    ///
+    /// Each merged command's `prior` is the local address of the command
+    /// holding the largest sequence below its own, the command its author
+    /// had seen last when the replicas agree. The local log is read once per
+    /// merge, and only when the response carries new commands.
+    pub fn merge_sync_response<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        response: &[u8],
+    ) -> Result<ChannelSyncMerge, ChannelError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.merge_sync_response_inner(storage, response);
+        storage.finish_mode();
+        result
+    }
+
+    fn encode_sync_response_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        request: &[u8],
+        output: &mut [u8],
+    ) -> Result<ChannelSyncBatch, ChannelError> {
+        let peer = decode_sync_request(request)?;
+
+        let mut members = 0u32;
+        for member in self.members.iter() {
+            if peer_last_sequence(peer, member.member)?.is_none() {
+                members = members.checked_add(1).ok_or(ChannelError::LengthOverflow)?;
+            }
+        }
+        let prefix_len = usize::try_from(members)
+            .ok()
+            .and_then(|count| count.checked_mul(SYNC_RESPONSE_MEMBER_LEN))
+            .and_then(|len| len.checked_add(SYNC_HEADER_LEN + size_of::<u8>()))
+            .and_then(|len| len.checked_add(2 * size_of::<u32>()))
+            .ok_or(ChannelError::LengthOverflow)?;
+        if prefix_len > output.len() {
+            return Err(ChannelError::SyncBufferTooSmall {
+                len: prefix_len,
+                capacity: output.len(),
+            });
+        }
+
+        let mut offset = write_sync_header(output, SYNC_RESPONSE_MAGIC)?;
+        let flags_offset = offset;
+        offset = write_u8(output, offset, 0)?;
+        offset = write_u32(output, offset, members)?;
+        for member in self.members.iter() {
+            if peer_last_sequence(peer, member.member)?.is_none() {
+                offset = write_u128(output, offset, member.member.id())?;
+            }
+        }
+        let count_offset = offset;
+        offset = write_u32(output, offset, 0)?;
+
+        let mut commands = 0u32;
+        let mut more = false;
+        self.visit_commands_inner(storage, |_, command| {
+            let peer_last = peer_last_sequence(peer, command.author)?.unwrap_or_default();
+            if command.sequence <= peer_last {
+                return Ok(());
+            }
+            if more {
+                return Ok(());
+            }
+            if commands as usize == CHANNEL_SYNC_MAX_COMMANDS {
+                more = true;
+                return Ok(());
+            }
+            let len = SYNC_COMMAND_FIXED_LEN
+                .checked_add(command.payload.len())
+                .ok_or(ChannelError::LengthOverflow)?;
+            if offset.checked_add(len).is_none_or(|end| end > output.len()) {
+                if commands == 0 {
+                    return Err(ChannelError::SyncBufferTooSmall {
+                        len: offset.saturating_add(len),
+                        capacity: output.len(),
+                    });
+                }
+                more = true;
+                return Ok(());
+            }
+            offset = write_sync_command(output, offset, command)?;
+            commands = commands
+                .checked_add(1)
+                .ok_or(ChannelError::LengthOverflow)?;
+            Ok(())
+        })?;
+
+        write_u8(output, flags_offset, if more { SYNC_MORE } else { 0 })?;
+        write_u32(output, count_offset, commands)?;
+        Ok(ChannelSyncBatch {
+            len: offset,
+            members,
+            commands,
+            more,
+        })
+    }
+
+    fn merge_sync_response_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        response: &[u8],
+    ) -> Result<ChannelSyncMerge, ChannelError> {
+        let response = SyncResponse::decode::<PAYLOAD_MAX>(response)?;
+
+        // Duplicate detection runs against the state before the merge, so a
+        // command counts as new when it is newer than its author's current
+        // last sequence. Sorted ids find repeats without comparing every
+        // pair, and one pass over the local log checks held ids and collects
+        // each new command's predecessor.
+        let mut ids = Vec::<u128, CHANNEL_SYNC_MAX_COMMANDS>::new();
+        let mut new_ids = Vec::<u128, CHANNEL_SYNC_MAX_COMMANDS>::new();
+        let mut priors = Vec::<SyncPrior, CHANNEL_SYNC_MAX_COMMANDS>::new();
+        response.visit_commands(|command| {
+            ids.push(command.message_id.id())
+                .map_err(|_| ChannelError::InvalidEncoding)?;
+            if self.is_new_sync_command(command) {
+                new_ids
+                    .push(command.message_id.id())
+                    .map_err(|_| ChannelError::InvalidEncoding)?;
+                priors
+                    .push(SyncPrior {
+                        sequence: command.sequence,
+                        best: None,
+                    })
+                    .map_err(|_| ChannelError::InvalidEncoding)?;
+            }
+            Ok(())
+        })?;
+        ids.sort_unstable();
+        if let Some(pair) = ids.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(ChannelError::DuplicateMessageId(MessageId::new(pair[0])));
+        }
+        if !new_ids.is_empty() {
+            new_ids.sort_unstable();
+            priors.sort_unstable_by_key(|prior| prior.sequence);
+            self.visit_commands_inner(storage, |address, local| {
+                if new_ids.binary_search(&local.message_id.id()).is_ok() {
+                    return Err(ChannelError::DuplicateMessageId(local.message_id));
+                }
+                // A local command precedes every new command with a larger
+                // sequence; note it on the first one and carry it forward.
+                let slot = priors.partition_point(|prior| prior.sequence <= local.sequence);
+                if let Some(prior) = priors.get_mut(slot) {
+                    prior.best =
+                        newer_prior(prior.best.take(), Some((local.sequence, address.clone())));
+                }
+                Ok(())
+            })?;
+            let mut best = None;
+            for prior in priors.iter_mut() {
+                best = newer_prior(best, prior.best.take());
+                prior.best.clone_from(&best);
+            }
+        }
+
+        let mut summary = ChannelSyncMerge {
+            members_added: 0,
+            merged: 0,
+            skipped: 0,
+            more: response.more,
+        };
+        for index in 0..response.member_count {
+            let member = response.member(index)?;
+            if self.get_last_sequence(&member).is_err() {
+                self.add_member_inner(storage, member)?;
+                summary.members_added += 1;
+            }
+        }
+
+        let mut merged =
+            Vec::<(ChannelSequence, CommandAddress<u32>), CHANNEL_SYNC_MAX_COMMANDS>::new();
+        let mut offset = 0usize;
+        for _ in 0..response.command_count {
+            let command = read_sync_command(response.commands, &mut offset)?;
+            if !self.is_new_sync_command(&command) {
+                summary.skipped += 1;
+                continue;
+            }
+            let slot = priors.partition_point(|prior| prior.sequence < command.sequence);
+            let mut prior = priors.get(slot).and_then(|prior| prior.best.clone());
+            for (sequence, address) in merged.iter() {
+                if *sequence < command.sequence {
+                    prior = newer_prior(prior, Some((*sequence, address.clone())));
+                }
+            }
+            let payload = Vec::<u8, PAYLOAD_MAX>::from_slice(command.payload).map_err(|_| {
+                ChannelError::CommandTooLarge {
+                    len: command.payload.len(),
+                    capacity: PAYLOAD_MAX,
+                }
+            })?;
+            let address = self.append_command(
+                storage,
+                AddCommand {
+                    prior: prior.map_or_else(CommandAddress::zero, |(_, address)| address),
+                    sender_last: command.sender_last,
+                    sequence: command.sequence,
+                    author: command.author,
+                    message_id: command.message_id,
+                    payload,
+                },
+            )?;
+            merged
+                .push((command.sequence, address))
+                .map_err(|_| ChannelError::InvalidEncoding)?;
+            summary.merged += 1;
+        }
+        Ok(summary)
+    }
+
+    fn is_new_sync_command(&self, command: &SyncCommand<'_>) -> bool {
+        self.get_last_sequence(&command.author)
+            .map_or(true, |last| command.sequence > last)
+    }
+}
+
+/// Returns whichever predecessor candidate holds the larger sequence.
+fn newer_prior(
+    current: Option<(ChannelSequence, CommandAddress<u32>)>,
+    candidate: Option<(ChannelSequence, CommandAddress<u32>)>,
+) -> Option<(ChannelSequence, CommandAddress<u32>)> {
+    match (current, candidate) {
+        (Some(current), Some(candidate)) if candidate.0 > current.0 => Some(candidate),
+        (Some(current), _) => Some(current),
+        (None, candidate) => candidate,
+    }
+}
+
+fn write_sync_header(output: &mut [u8], magic: [u8; 4]) -> Result<usize, ChannelError> {
+    let offset = write_bytes(output, 0, &magic)?;
+    write_u16(output, offset, SYNC_VERSION)
+}
+
+fn read_sync_header(input: &[u8], offset: &mut usize, magic: [u8; 4]) -> Result<(), ChannelError> {
+    if read_bytes(input, offset, magic.len())? != magic || read_u16(input, offset)? != SYNC_VERSION
+    {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    Ok(())
+}
+
+/// Validates a sync request and returns its sequence-vector entries.
+fn decode_sync_request(input: &[u8]) -> Result<&[u8], ChannelError> {
+    let mut offset = 0usize;
+    read_sync_header(input, &mut offset, SYNC_REQUEST_MAGIC)?;
+    let count = read_u32(input, &mut offset)?;
+    let len = usize::try_from(count)
+        .ok()
+        .and_then(|count| count.checked_mul(SYNC_REQUEST_ENTRY_LEN))
+        .ok_or(ChannelError::InvalidEncoding)?;
+    let entries = read_bytes(input, &mut offset, len)?;
+    if offset != input.len() {
+        return Err(ChannelError::InvalidEncoding);
+    }
+    Ok(entries)
+}
+
+/// Returns the peer's last sequence for `member`, or `None` when the peer
+/// does not know the member.
+fn peer_last_sequence(
+    entries: &[u8],
+    member: MemberId,
+) -> Result<Option<ChannelSequence>, ChannelError> {
+    let mut offset = 0usize;
+    while offset < entries.len() {
+        let candidate = MemberId::new(read_u128(entries, &mut offset)?);
+        let last_sequence = ChannelSequence::new(read_u64(entries, &mut offset)?);
+        if candidate == member {
+            return Ok(Some(last_sequence));
+        }
+    }
+    Ok(None)
+}
+
+fn write_sync_command<const PAYLOAD_MAX: usize>(
+    output: &mut [u8],
+    offset: usize,
+    command: &AddCommand<u32, PAYLOAD_MAX>,
+) -> Result<usize, ChannelError> {
+    let payload_len =
+        u32::try_from(command.payload.len()).map_err(|_| ChannelError::LengthOverflow)?;
+    let mut offset = write_u64(output, offset, command.sender_last.get())?;
+    offset = write_u64(output, offset, command.sequence.get())?;
+    offset = write_u128(output, offset, command.author.id())?;
+    offset = write_u128(output, offset, command.message_id.id())?;
+    offset = write_u32(output, offset, payload_len)?;
+    write_bytes(output, offset, command.payload.as_slice())
+}
+
+fn read_sync_command<'a>(
+    input: &'a [u8],
+    offset: &mut usize,
+) -> Result<SyncCommand<'a>, ChannelError> {
+    let sender_last = ChannelSequence::new(read_u64(input, offset)?);
+    let sequence = ChannelSequence::new(read_u64(input, offset)?);
+    let author = MemberId::new(read_u128(input, offset)?);
+    let message_id = MessageId::new(read_u128(input, offset)?);
+    let payload_len =
+        usize::try_from(read_u32(input, offset)?).map_err(|_| ChannelError::LengthOverflow)?;
+    let payload = read_bytes(input, offset, payload_len)?;
+    Ok(SyncCommand {
+        sender_last,
+        sequence,
+        author,
+        message_id,
+        payload,
+    })
+}
@@ -1,42 +1,65 @@
 use super::*;
 
 use crate::vec_like::VecLikeSlice;
+use crate::MockFlash;
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 64;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+
+fn pending_data<const N: usize>() -> [AddCommand<u32, 8>; N] {
+    core::array::from_fn(|_| AddCommand::default())
+}
+
+fn member(id: u128) -> MemberId {
+    MemberId::new(id)
+}
+
+fn message(id: u128) -> MessageId {
+    MessageId::new(id)
+}
 
 //= spec/channel.md#channel-state-and-member-sequences
 //= type=test
 //# `RING-IMPL-REGRESSION-001` Channel construction MUST initialize a channel with the requested
-//# collection id, first member, next sequence 0, and first member last sequence 0.
+//# collection id, first member, next sequence 1, and first member last sequence 0.
 #[test]
 fn requirement_test_new_channel() {
-    let id = CollectionId(1);
-    let member = MemberId { id: 1 };
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let member = member(1);
 
-    let mut members_data = [MemberSequence {
-        member: MemberId { id: 0 },
-        last_sequence: ChannelSequence(0),
-    }; 1024];
+    let mut members_data = [MemberSequence::default(); 1024];
     let mut members = VecLikeSlice::new(&mut members_data);
 
-    let mut updates_data = [MemberId { id: 0 }; 1024];
+    let mut updates_data = [MemberId::default(); 1024];
     let mut updates = VecLikeSlice::new(&mut updates_data);
 
-    let mut pending_data: [_; 1024] = core::array::from_fn(|_| AddCommand::<u32, 8> {
-        prior: CommandAddress::zero(),
-        sender_last: ChannelSequence(0),
-        sequence: ChannelSequence(0),
-        author: MemberId { id: 0 },
-        message_id: MessageId { id: 0 },
-        payload: Vec::new(),
-    });
+    let mut pending_data = pending_data::<1024>();
     let mut pending = VecLikeSlice::new(&mut pending_data);
 
-    let channel =
-        Channel::<_, _, _, _, 8, 1>::new(id, member, &mut pending, &mut members, &mut updates);
+    let channel = Channel::<_, _, _, _, 8, 1>::new(
+        &mut storage,
+        member,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    );
     assert!(channel.is_ok());
 
     let channel = channel.unwrap();
+    let id = channel.id();
     assert_eq!(channel.id, id);
-    assert_eq!(channel.next_sequence, ChannelSequence(0));
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == id)
+            .and_then(|collection| collection.collection_type()),
+        Some(CollectionType::CHANNEL_CODE)
+    );
+    assert_eq!(channel.next_sequence, ChannelSequence(1));
     assert_eq!(channel.members.len(), 1);
     assert_eq!(channel.members.get(0).unwrap().member, member);
     assert_eq!(
@@ -51,31 +74,22 @@ fn requirement_test_new_channel() {
 //# capacity and MUST retain both existing and added members.
 #[test]
 fn requirement_test_add_member() {
-    let id = CollectionId(1);
-    let initial_member = MemberId { id: 1 };
-    let new_member = MemberId { id: 2 };
-
-    let mut members_data = [MemberSequence {
-        member: MemberId { id: 0 },
-        last_sequence: ChannelSequence(0),
-    }; 2];
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let initial_member = member(1);
+    let new_member = member(2);
+
+    let mut members_data = [MemberSequence::default(); 2];
     let mut members = VecLikeSlice::new(&mut members_data);
 
-    let mut updates_data = [MemberId { id: 0 }; 1];
+    let mut updates_data = [MemberId::default(); 1];
     let mut updates = VecLikeSlice::new(&mut updates_data);
 
-    let mut pending_data = [AddCommand::<u32, 8> {
-        prior: CommandAddress::zero(),
-        sender_last: ChannelSequence(0),
-        sequence: ChannelSequence(0),
-        author: MemberId { id: 0 },
-        message_id: MessageId { id: 0 },
-        payload: Vec::new(),
-    }; 1];
+    let mut pending_data = pending_data::<1>();
     let mut pending = VecLikeSlice::new(&mut pending_data);
 
     let mut channel = Channel::<_, _, _, _, 8, 2>::new(
-        id,
+        &mut storage,
         initial_member,
         &mut pending,
         &mut members,
@@ -83,12 +97,11 @@ fn requirement_test_add_member() {
     )
     .unwrap();
 
-    let result = channel.add_member(new_member);
+    let result = channel.add_member(&mut storage, new_member);
     assert!(result.is_ok());
-
     assert_eq!(channel.members.len(), 2);
-    assert!(channel.members.iter().any(|m| m.member == initial_member));
-    assert!(channel.members.iter().any(|m| m.member == new_member));
+    assert_eq!(channel.members.get(0).unwrap().member, initial_member);
+    assert_eq!(channel.members.get(1).unwrap().member, new_member);
 }
 
 //= spec/channel.md#channel-state-and-member-sequences
@@ -97,44 +110,34 @@ fn requirement_test_add_member() {
 //# with UserLimitReached after filling available slots.
 #[test]
 fn requirement_test_add_member_limit() {
-    let id = CollectionId(1);
-    let initial_member = MemberId { id: 1 };
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
 
-    let mut members_data = [MemberSequence {
-        member: MemberId { id: 0 },
-        last_sequence: ChannelSequence(0),
-    }; 2];
+    let mut members_data = [MemberSequence::default(); 2];
     let mut members = VecLikeSlice::new(&mut members_data);
 
-    let mut updates_data = [MemberId { id: 0 }; 1];
+    let mut updates_data = [MemberId::default(); 1];
     let mut updates = VecLikeSlice::new(&mut updates_data);
 
-    let mut pending_data = [AddCommand::<u32, 8> {
-        prior: CommandAddress::zero(),
-        sender_last: ChannelSequence(0),
-        sequence: ChannelSequence(0),
-        author: MemberId { id: 0 },
-        message_id: MessageId { id: 0 },
-        payload: Vec::new(),
-    }; 1];
+    let mut pending_data = pending_data::<1>();
     let mut pending = VecLikeSlice::new(&mut pending_data);
 
     let mut channel = Channel::<_, _, _, _, 8, 2>::new(
-        id,
-        initial_member,
+        &mut storage,
+        member(1),
         &mut pending,
         &mut members,
         &mut updates,
     )
     .unwrap();
 
-    // Add one member should succeed
-    let result = channel.add_member(MemberId { id: 2 });
-    assert!(result.is_ok());
+    // Fill up to capacity
+    assert!(channel.add_member(&mut storage, member(2)).is_ok());
 
-    // Adding third member should fail with UserLimitReached
-    let result = channel.add_member(MemberId { id: 3 });
+    // Should fail when exceeding capacity
+    let result = channel.add_member(&mut storage, member(3));
     assert!(matches!(result, Err(ChannelError::UserLimitReached)));
+    assert_eq!(channel.members.len(), 2);
 }
 
 //= spec/channel.md#channel-state-and-member-sequences
@@ -143,30 +146,22 @@ fn requirement_test_add_member_limit() {
 //# existing member and MemberNotFound for an unknown member.
 #[test]
 fn requirement_test_get_last_sequence() {
-    let id = CollectionId(1);
-    let initial_member = MemberId { id: 1 };
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let initial_member = member(1);
+    let unknown_member = member(2);
 
-    let mut members_data = [MemberSequence {
-        member: MemberId { id: 0 },
-        last_sequence: ChannelSequence(0),
-    }; 1];
+    let mut members_data = [MemberSequence::default(); 1];
     let mut members = VecLikeSlice::new(&mut members_data);
 
-    let mut updates_data = [MemberId { id: 0 }; 1];
+    let mut updates_data = [MemberId::default(); 1];
     let mut updates = VecLikeSlice::new(&mut updates_data);
 
-    let mut pending_data = [AddCommand::<u32, 8> {
-        prior: CommandAddress::zero(),
-        sender_last: ChannelSequence(0),
-        sequence: ChannelSequence(0),
-        author: MemberId { id: 0 },
-        message_id: MessageId { id: 0 },
-        payload: Vec::new(),
-    }; 1];
+    let mut pending_data = pending_data::<1>();
     let mut pending = VecLikeSlice::new(&mut pending_data);
 
     let channel = Channel::<_, _, _, _, 8, 1>::new(
-        id,
+        &mut storage,
         initial_member,
         &mut pending,
         &mut members,
@@ -174,14 +169,13 @@ fn requirement_test_get_last_sequence() {
     )
     .unwrap();
 
-    // Initial sequence should be 0
+    // Should return sequence for existing member
     let seq = channel.get_last_sequence(&initial_member);
     assert!(seq.is_ok());
     assert_eq!(seq.unwrap(), ChannelSequence(0));
 
-    // Non-existent member should return error
-    let bad_member = MemberId { id: 999 };
-    let seq = channel.get_last_sequence(&bad_member);
+    // Should return error for unknown member
+    let seq = channel.get_last_sequence(&unknown_member);
     assert!(matches!(seq, Err(ChannelError::MemberNotFound(_))));
 }
 
@@ -191,44 +185,61 @@ fn requirement_test_get_last_sequence() {
 //# increment subsequent next_sequence monotonically.
 #[test]
 fn requirement_test_get_next_sequence() {
-    let id = CollectionId(1);
-    let initial_member = MemberId { id: 1 };
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let author = member(1);
 
-    let mut members_data = [MemberSequence {
-        member: MemberId { id: 0 },
-        last_sequence: ChannelSequence(0),
-    }; 1];
+    let mut members_data = [MemberSequence::default(); 1];
     let mut members = VecLikeSlice::new(&mut members_data);
 
-    let mut updates_data = [MemberId { id: 0 }; 1];
+    let mut updates_data = [MemberId::default(); 1];
     let mut updates = VecLikeSlice::new(&mut updates_data);
 
-    let mut pending_data = [AddCommand::<u32, 8> {
-        prior: CommandAddress::zero(),
-        sender_last: ChannelSequence(0),
-        sequence: ChannelSequence(0),
-        author: MemberId { id: 0 },
-        message_id: MessageId { id: 0 },
-        payload: Vec::new(),
-    }; 1];
+    let mut pending_data = pending_data::<4>();
     let mut pending = VecLikeSlice::new(&mut pending_data);
 
     let mut channel = Channel::<_, _, _, _, 8, 1>::new(
-        id,
-        initial_member,
+        &mut storage,
+        author,
         &mut pending,
         &mut members,
         &mut updates,
     )
     .unwrap();
 
-    // First call should return 0 and increment internal counter
-    assert_eq!(channel.get_next_sequence(), ChannelSequence(0));
-    assert_eq!(channel.next_sequence, ChannelSequence(1));
-
-    // Second call should return 1 and increment internal counter
-    assert_eq!(channel.get_next_sequence(), ChannelSequence(1));
+    // First command should take sequence 1 and increment the counter
+    let first = channel
+        .add_command(
+            &mut storage,
+            CommandAddress::zero(),
+            author,
+            message(1),
+            b"a",
+        )
+        .unwrap();
+    assert_eq!(
+        channel
+            .read_command(&mut storage, &first)
+            .unwrap()
+            .sequence(),
+        ChannelSequence(1)
+    );
     assert_eq!(channel.next_sequence, ChannelSequence(2));
+
+    // Second command should take sequence 2 and increment the counter
+    let second = channel
+        .add_command(&mut storage, first, author, message(2), b"b")
+        .unwrap();
+    assert_eq!(
+        channel
+            .read_command(&mut storage, &second)
+            .unwrap()
+            .sequence(),
+        ChannelSequence(2)
+    );
+    assert_eq!(channel.next_sequence, ChannelSequence(3));
+    assert_eq!(channel.get_next_sequence(), ChannelSequence(3));
+    assert_eq!(channel.next_sequence, ChannelSequence(4));
 }
 
 //= spec/channel.md#channel-state-and-member-sequences
@@ -237,34 +248,30 @@ fn requirement_test_get_next_sequence() {
 //# NOT create duplicate member entries.
 #[test]
 fn requirement_test_duplicate_member_add() {
-    let id = CollectionId(1);
-    let member = MemberId { id: 1 };
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let member = member(1);
 
-    let mut members_data = [MemberSequence {
-        member: MemberId { id: 0 },
-        last_sequence: ChannelSequence(0),
-    }; 1];
+    let mut members_data = [MemberSequence::default(); 1];
     let mut members = VecLikeSlice::new(&mut members_data);
 
-    let mut updates_data = [MemberId { id: 0 }; 1];
+    let mut updates_data = [MemberId::default(); 1];
     let mut updates = VecLikeSlice::new(&mut updates_data);
 
-    let mut pending_data = [AddCommand::<u32, 8> {
-        prior: CommandAddress::zero(),
-        sender_last: ChannelSequence(0),
-        sequence: ChannelSequence(0),
-        author: MemberId { id: 0 },
-        message_id: MessageId { id: 0 },
-        payload: Vec::new(),
-    }; 1];
+    let mut pending_data = pending_data::<1>();
     let mut pending = VecLikeSlice::new(&mut pending_data);
 
-    let mut channel =
-        Channel::<_, _, _, _, 8, 1>::new(id, member, &mut pending, &mut members, &mut updates)
-            .unwrap();
+    let mut channel = Channel::<_, _, _, _, 8, 1>::new(
+        &mut storage,
+        member,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
 
     // Adding same member again should succeed but not create duplicate
-    let result = channel.add_member(member);
+    let result = channel.add_member(&mut storage, member);
     assert!(result.is_ok());
     assert_eq!(channel.members.len(), 1);
     assert_eq!(channel.members.get(0).unwrap().member, member);
@@ -304,32 +311,28 @@ fn requirement_checkpoint_command_reports_exact_command_count() {
 //# sequence, track that member only once for checkpoint pressure, and reject unknown members.
 #[test]
 fn requirement_use_sequence_updates_member_once_and_tracks_checkpoint_pressure() {
-    let id = CollectionId(1);
-    let member = MemberId { id: 1 };
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let member = member(1);
     let other = MemberId { id: 2 };
 
-    let mut members_data = [MemberSequence {
-        member: MemberId { id: 0 },
-        last_sequence: ChannelSequence(0),
-    }; 1];
+    let mut members_data = [MemberSequence::default(); 1];
     let mut members = VecLikeSlice::new(&mut members_data);
 
-    let mut updates_data = [MemberId { id: 0 }; 1];
+    let mut updates_data = [MemberId::default(); 1];
     let mut updates = VecLikeSlice::new(&mut updates_data);
 
-    let mut pending_data = [AddCommand::<u32, 8> {
-        prior: CommandAddress::zero(),
-        sender_last: ChannelSequence(0),
-        sequence: ChannelSequence(0),
-        author: MemberId { id: 0 },
-        message_id: MessageId { id: 0 },
-        payload: Vec::new(),
-    }; 1];
+    let mut pending_data = pending_data::<1>();
     let mut pending = VecLikeSlice::new(&mut pending_data);
 
-    let mut channel =
-        Channel::<_, _, _, _, 8, 1>::new(id, member, &mut pending, &mut members, &mut updates)
-            .unwrap();
+    let mut channel = Channel::<_, _, _, _, 8, 1>::new(
+        &mut storage,
+        member,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
 
     assert_eq!(channel.update_count(), 0);
     channel.use_sequence(&member, ChannelSequence(5)).unwrap();
@@ -353,3 +356,1125 @@ fn requirement_use_sequence_updates_member_once_and_tracks_checkpoint_pressure()
         Err(ChannelError::MemberNotFound(found)) if found == other
     ));
 }
+
+//= spec/channel.md#durable-storage
+//= type=test
+//# `RING-CHANNEL-001` `Channel::new` MUST create a `channel` collection,
+//# persist its initial member through a WAL update, and reserve a frontier
+//# segment region before it returns.
+#[test]
+fn requirement_channel_new_persists_member_and_reserves_frontier() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut members_data = [MemberSequence::default(); 2];
+        let mut members = VecLikeSlice::new(&mut members_data);
+        let mut updates_data = [MemberId::default(); 2];
+        let mut updates = VecLikeSlice::new(&mut updates_data);
+        let mut pending_data = pending_data::<4>();
+        let mut pending = VecLikeSlice::new(&mut pending_data);
+        let channel = Channel::<_, _, _, _, 8, 2>::new(
+            &mut storage,
+            member(7),
+            &mut pending,
+            &mut members,
+            &mut updates,
+        )
+        .unwrap();
+        let frontier = channel.frontier.clone().unwrap();
+        assert_eq!(frontier.offset, SEGMENT_START);
+        assert!(channel.first_segment.is_none());
+        channel.id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut members_data = [MemberSequence::default(); 2];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 2];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data = pending_data::<4>();
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    let channel = Channel::<_, _, _, _, 8, 2>::open(
+        id,
+        &mut storage,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    assert_eq!(channel.members().len(), 1);
+    assert_eq!(channel.members()[0].member(), member(7));
+    assert!(channel.frontier.is_some());
+}
+
+//= spec/channel.md#durable-storage
+//= type=test
+//# `RING-CHANNEL-002` Member, command, and checkpoint changes MUST each be
+//# persisted through a WAL update so that reopening the channel restores the
+//# same members, sequences, checkpoint, and commands.
+#[test]
+fn requirement_channel_commands_survive_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let (id, first, second, checkpoint) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut members_data = [MemberSequence::default(); 2];
+        let mut members = VecLikeSlice::new(&mut members_data);
+        let mut updates_data = [MemberId::default(); 2];
+        let mut updates = VecLikeSlice::new(&mut updates_data);
+        let mut pending_data = pending_data::<4>();
+        let mut pending = VecLikeSlice::new(&mut pending_data);
+        let mut channel = Channel::<_, _, _, _, 8, 2>::new(
+            &mut storage,
+            member(1),
+            &mut pending,
+            &mut members,
+            &mut updates,
+        )
+        .unwrap();
+        channel.add_member(&mut storage, member(2)).unwrap();
+        let first = channel
+            .add_command(
+                &mut storage,
+                CommandAddress::zero(),
+                member(1),
+                message(10),
+                b"one",
+            )
+            .unwrap();
+        let second = channel
+            .add_command(&mut storage, first.clone(), member(2), message(11), b"two")
+            .unwrap();
+        let checkpoint = channel.add_checkpoint(&mut storage).unwrap();
+        assert_eq!(channel.update_count(), 0);
+        (channel.id(), first, second, checkpoint)
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut members_data = [MemberSequence::default(); 2];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 2];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data = pending_data::<4>();
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    let mut channel = Channel::<_, _, _, _, 8, 2>::open(
+        id,
+        &mut storage,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    assert_eq!(channel.command_count(), 2);
+    assert_eq!(channel.next_sequence(), ChannelSequence(3));
+    assert_eq!(channel.checkpoint(), &checkpoint);
+    assert_eq!(channel.update_count(), 0);
+    assert_eq!(
+        channel.get_last_sequence(&member(1)).unwrap(),
+        ChannelSequence(1)
+    );
+    assert_eq!(
+        channel.get_last_sequence(&member(2)).unwrap(),
+        ChannelSequence(2)
+    );
+
+    let command = channel.read_command(&mut storage, &second).unwrap();
+    assert_eq!(command.prior(), &first);
+    assert_eq!(command.author(), member(2));
+    assert_eq!(command.message_id(), message(11));
+    assert_eq!(command.sender_last(), ChannelSequence(0));
+    assert_eq!(command.payload(), b"two");
+
+    let third = channel
+        .add_command(&mut storage, second, member(1), message(12), b"three")
+        .unwrap();
+    let command = channel.read_command(&mut storage, &third).unwrap();
+    assert_eq!(command.sender_last(), ChannelSequence(1));
+    assert_eq!(command.sequence(), ChannelSequence(3));
+}
+
+//= spec/channel.md#durable-storage
+//= type=test
+//# `RING-CHANNEL-003` Flushing MUST write pending commands into their
+//# frontier segment at the addresses already returned for them, link the
+//# segment to the next reserved segment, and record the channel state in a WAL
+//# snapshot.
+#[test]
+fn requirement_channel_flush_materializes_segments_at_stable_addresses() {
+    let mut flash = TestFlash::new(0xff);
+    let mut addresses = std::vec::Vec::new();
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut members_data = [MemberSequence::default(); 2];
+        let mut members = VecLikeSlice::new(&mut members_data);
+        let mut updates_data = [MemberId::default(); 2];
+        let mut updates = VecLikeSlice::new(&mut updates_data);
+        let mut pending_data = pending_data::<3>();
+        let mut pending = VecLikeSlice::new(&mut pending_data);
+        let mut channel = Channel::<_, _, _, _, 8, 2>::new(
+            &mut storage,
+            member(1),
+            &mut pending,
+            &mut members,
+            &mut updates,
+        )
+        .unwrap();
+        let mut prior = CommandAddress::zero();
+        for index in 0..8u8 {
+            let address = channel
+                .add_command(
+                    &mut storage,
+                    prior,
+                    member(1),
+                    message(index.into()),
+                    &[index; 8],
+                )
+                .unwrap();
+            addresses.push(address.clone());
+            prior = address;
+        }
+        // A three-command pending buffer forces flushes along the way.
+        let first_segment = channel.first_segment.unwrap();
+        assert_eq!(addresses[0].region, first_segment);
+        assert_ne!(addresses[7].region, first_segment);
+        channel.flush(&mut storage).unwrap();
+        assert!(channel.pending.is_empty());
+        for (index, address) in addresses.iter().enumerate() {
+            let command = channel.read_command(&mut storage, address).unwrap();
+            assert_eq!(command.payload(), &[index as u8; 8]);
+        }
+        channel.id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut members_data = [MemberSequence::default(); 2];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 2];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data = pending_data::<3>();
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    let mut channel = Channel::<_, _, _, _, 8, 2>::open(
+        id,
+        &mut storage,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    assert_eq!(channel.command_count(), 8);
+    assert!(channel.pending.is_empty());
+    for (index, address) in addresses.iter().enumerate() {
+        let command = channel.read_command(&mut storage, address).unwrap();
+        assert_eq!(command.sequence(), ChannelSequence(index as u64 + 1));
+        assert_eq!(command.payload(), &[index as u8; 8]);
+    }
+}
+
+//= spec/channel.md#durable-storage
+//= type=test
+//# `RING-CHANNEL-004` Command traversal MUST visit flushed segments from oldest
+//# to newest and then the pending frontier commands, each with its address.
+#[test]
+fn requirement_channel_visit_commands_walks_oldest_to_newest() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut members_data = [MemberSequence::default(); 2];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 2];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data = pending_data::<2>();
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    let mut channel = Channel::<_, _, _, _, 8, 2>::new(
+        &mut storage,
+        member(1),
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    let mut expected = std::vec::Vec::new();
+    let mut prior = CommandAddress::zero();
+    for index in 0..5u8 {
+        prior = channel
+            .add_command(
+                &mut storage,
+                prior,
+                member(1),
+                message(index.into()),
+                &[index],
+            )
+            .unwrap();
+        expected.push(prior.clone());
+    }
+    assert_eq!(channel.pending.len(), 1);
+
+    let mut visited = std::vec::Vec::new();
+    channel
+        .visit_commands(&mut storage, |address, command| {
+            assert_eq!(command.payload(), &[visited.len() as u8]);
+            visited.push(address.clone());
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(visited, expected);
+}
+
+//= spec/channel.md#durable-storage
+//= type=test
+//# `RING-CHANNEL-005` Adding a command MUST fail before anything is written
+//# when its author is not a member, and adding a checkpoint MUST fail when the
+//# members changed since the previous checkpoint exceed `CHECKPOINT_MAX`.
+#[test]
+fn requirement_channel_rejects_invalid_commands_before_writing() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut members_data = [MemberSequence::default(); 2];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 2];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data = pending_data::<2>();
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    let mut channel = Channel::<_, _, _, _, 8, 1>::new(
+        &mut storage,
+        member(1),
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    channel.add_member(&mut storage, member(2)).unwrap();
+
+    assert!(matches!(
+        channel.add_command(&mut storage, CommandAddress::zero(), member(3), message(1), b"x"),
+        Err(ChannelError::MemberNotFound(found)) if found == member(3)
+    ));
+    assert!(matches!(
+        channel.add_command(
+            &mut storage,
+            CommandAddress::zero(),
+            member(1),
+            message(1),
+            &[0; 9]
+        ),
+        Err(ChannelError::CommandTooLarge {
+            len: 9,
+            capacity: 8
+        })
+    ));
+    assert_eq!(channel.command_count(), 0);
+    assert_eq!(channel.next_sequence(), ChannelSequence(1));
+
+    let first = channel
+        .add_command(
+            &mut storage,
+            CommandAddress::zero(),
+            member(1),
+            message(1),
+            b"a",
+        )
+        .unwrap();
+    channel
+        .add_command(&mut storage, first, member(2), message(2), b"b")
+        .unwrap();
+    assert!(matches!(
+        channel.add_checkpoint(&mut storage),
+        Err(ChannelError::CheckpointLimitReached)
+    ));
+    assert_eq!(channel.update_count(), 2);
+}
+
+//= spec/channel.md#durable-storage
+//= type=test
+//# `RING-CHANNEL-006` Opening a channel MUST validate membership sequences on
+//# replay and MUST reject a command whose author is not a member, whose
+//# `sender_last` is not the author's last sequence, or whose sequence does not
+//# advance past it.
+#[test]
+fn requirement_channel_open_validates_membership_sequences() {
+    fn encoded_command(
+        frontier: &CommandAddress<u32>,
+        command: &AddCommand<u32, 8>,
+    ) -> std::vec::Vec<u8> {
+        let mut update = [0u8; 128];
+        let mut offset = write_u8(&mut update, 0, UPDATE_ADD_COMMAND).unwrap();
+        offset = write_address(&mut update, offset, frontier).unwrap();
+        offset = encode_command(&mut update, offset, command).unwrap();
+        update[..offset].to_vec()
+    }
+
+    let cases = [
+        (member(9), ChannelSequence(0), ChannelSequence(1)),
+        (member(1), ChannelSequence(4), ChannelSequence(5)),
+        (member(1), ChannelSequence(0), ChannelSequence(0)),
+    ];
+    for (author, sender_last, sequence) in cases {
+        let mut flash = TestFlash::new(0xff);
+        let id = {
+            let mut storage = crate::test_format_storage(&mut flash);
+            let mut members_data = [MemberSequence::default(); 2];
+            let mut members = VecLikeSlice::new(&mut members_data);
+            let mut updates_data = [MemberId::default(); 2];
+            let mut updates = VecLikeSlice::new(&mut updates_data);
+            let mut pending_data = pending_data::<2>();
+            let mut pending = VecLikeSlice::new(&mut pending_data);
+            let channel = Channel::<_, _, _, _, 8, 2>::new(
+                &mut storage,
+                member(1),
+                &mut pending,
+                &mut members,
+                &mut updates,
+            )
+            .unwrap();
+            let command = AddCommand {
+                prior: CommandAddress::zero(),
+                sender_last,
+                sequence,
+                author,
+                message_id: message(1),
+                payload: Vec::new(),
+            };
+            let update = encoded_command(channel.frontier.as_ref().unwrap(), &command);
+            storage.append_update(channel.id(), &update).unwrap();
+            channel.id()
+        };
+
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        let mut members_data = [MemberSequence::default(); 2];
+        let mut members = VecLikeSlice::new(&mut members_data);
+        let mut updates_data = [MemberId::default(); 2];
+        let mut updates = VecLikeSlice::new(&mut updates_data);
+        let mut pending_data = pending_data::<2>();
+        let mut pending = VecLikeSlice::new(&mut pending_data);
+        let result = Channel::<_, _, _, _, 8, 2>::open(
+            id,
+            &mut storage,
+            &mut pending,
+            &mut members,
+            &mut updates,
+        );
+        match result {
+            Err(ChannelError::MemberNotFound(found)) => assert_eq!(found, member(9)),
+            Err(ChannelError::SequenceMismatch {
+                member: found,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(found, member(1));
+                assert_eq!(expected, ChannelSequence(0));
+                assert_eq!(actual, ChannelSequence(4));
+            }
+            Err(ChannelError::InvalidSequence {
+                member: found,
+                sequence,
+            }) => {
+                assert_eq!(found, member(1));
+                assert_eq!(sequence, ChannelSequence(0));
+            }
+            Err(error) => panic!("unexpected error {error:?}"),
+            Ok(_) => panic!("replay accepted an invalid command"),
+        }
+    }
+}
+
+//= spec/channel.md#durable-storage
+//= type=test
+//# `RING-CHANNEL-007` A replayed checkpoint MUST match the previous checkpoint
+//# address, the channel command count, and the last sequence of every member it
+//# names.
+#[test]
+fn requirement_channel_open_rejects_mismatched_checkpoint() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut members_data = [MemberSequence::default(); 2];
+        let mut members = VecLikeSlice::new(&mut members_data);
+        let mut updates_data = [MemberId::default(); 2];
+        let mut updates = VecLikeSlice::new(&mut updates_data);
+        let mut pending_data = pending_data::<2>();
+        let mut pending = VecLikeSlice::new(&mut pending_data);
+        let mut channel = Channel::<_, _, _, _, 8, 2>::new(
+            &mut storage,
+            member(1),
+            &mut pending,
+            &mut members,
+            &mut updates,
+        )
+        .unwrap();
+        channel
+            .add_command(
+                &mut storage,
+                CommandAddress::zero(),
+                member(1),
+                message(1),
+                b"a",
+            )
+            .unwrap();
+
+        let mut sequences = Vec::<MemberSequence, 2>::new();
+        sequences
+            .push(MemberSequence {
+                member: member(1),
+                last_sequence: ChannelSequence(7),
+            })
+            .unwrap();
+        let ChannelCommand::CheckPointCommand(checkpoint) =
+            CheckPointCommand::<u32, 2>::into_command::<8>(CommandAddress::zero(), 1, &sequences)
+        else {
+            panic!("expected checkpoint command");
+        };
+        let mut update = [0u8; 128];
+        let mut offset = write_u8(&mut update, 0, UPDATE_CHECKPOINT).unwrap();
+        offset = encode_checkpoint(&mut update, offset, &checkpoint).unwrap();
+        storage
+            .append_update(channel.id(), &update[..offset])
+            .unwrap();
+        channel.id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut members_data = [MemberSequence::default(); 2];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 2];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data = pending_data::<2>();
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    assert!(matches!(
+        Channel::<_, _, _, _, 8, 2>::open(
+            id,
+            &mut storage,
+            &mut pending,
+            &mut members,
+            &mut updates
+        ),
+        Err(ChannelError::CheckpointMismatch)
+    ));
+}
+
+//= spec/channel.md#durable-storage
+//= type=test
+//# `RING-CHANNEL-008` The empty channel snapshot used by WAL head reclaim MUST
+//# replay to the same state as a newly created channel collection with no
+//# updates.
+#[test]
+fn requirement_channel_empty_snapshot_replays_initial_state() {
+    let mut members_data = [MemberSequence::default(); 2];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 2];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data = pending_data::<2>();
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    let mut channel = Channel::<u32, _, _, _, 8, 2>::empty(
+        CollectionId(5),
+        &mut pending,
+        &mut members,
+        &mut updates,
+    );
+    channel.apply_reserved_segment(3).unwrap();
+    channel
+        .apply_command(&AddMemberCommand::into_command(member(1)))
+        .unwrap();
+
+    channel.apply_snapshot(empty_snapshot()).unwrap();
+    assert_eq!(channel.next_sequence(), ChannelSequence(1));
+    assert_eq!(channel.command_count(), 0);
+    assert!(channel.members().is_empty());
+    assert_eq!(channel.update_count(), 0);
+    assert_eq!(channel.checkpoint(), &CommandAddress::zero());
+    assert!(channel.first_segment.is_none());
+    assert!(channel.frontier.is_none());
+    assert!(channel.spare_segment.is_none());
+}
+
+//= spec/ring/03-collection-lifecycle.md#collection-head-state-machine
+//= type=test
+//# `RING-INVARIANT-006` Collection replay MUST ignore `update` records for a
+//# collection that precede its earliest retained basis record.
+#[test]
+fn requirement_channel_replay_skips_updates_before_retained_basis() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let author = member(1);
+    let mut members_data = [MemberSequence::default(); 1];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 1];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut written_data = pending_data::<4>();
+    let mut pending = VecLikeSlice::new(&mut written_data);
+    let mut channel = Channel::<_, _, _, _, 8, 1>::new(
+        &mut storage,
+        author,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    // Reclaiming after every command drops each flush snapshot once a newer
+    // one exists, leaving the commands logged after it ahead of the newest
+    // snapshot in the retained WAL.
+    let mut prior = CommandAddress::zero();
+    for id in 0..16u8 {
+        prior = channel
+            .add_command(&mut storage, prior, author, message(id.into()), &[id; 8])
+            .unwrap();
+        if id % 4 == 3 {
+            channel.flush(&mut storage).unwrap();
+        }
+        if storage.wal_head() != storage.wal_tail() {
+            storage.reclaim_wal_head().unwrap();
+        }
+    }
+    let id = channel.id();
+
+    let mut members_data = [MemberSequence::default(); 1];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 1];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut reopened_data = pending_data::<4>();
+    let mut pending = VecLikeSlice::new(&mut reopened_data);
+    let mut reopened = Channel::<_, _, _, _, 8, 1>::open(
+        id,
+        &mut storage,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    assert_eq!(reopened.command_count(), 16);
+    assert_eq!(
+        reopened
+            .read_command(&mut storage, &prior)
+            .unwrap()
+            .payload(),
+        &[15u8; 8]
+    );
+}
+
+//= spec/wear.md#bad-regions
+//= type=test
+//# `RING-WEAR-014` When writing a channel's frontier segment fails with
+//# `BadRegion`, the flush MUST copy every flushed segment of the channel and
+//# the pending commands into freshly reserved regions whose links name the
+//# copies, append a snapshot naming them, and free every replaced region, all
+//# within one collection transaction.
+#[test]
+fn requirement_channel_flush_relocates_chain_past_bad_segment() {
+    let mut flash = TestFlash::new(0xff);
+    let author = member(1);
+    let (id, flushed, bad) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut members_data = [MemberSequence::default(); 1];
+        let mut members = VecLikeSlice::new(&mut members_data);
+        let mut updates_data = [MemberId::default(); 1];
+        let mut updates = VecLikeSlice::new(&mut updates_data);
+        let mut written_data = pending_data::<4>();
+        let mut pending = VecLikeSlice::new(&mut written_data);
+        let mut channel = Channel::<_, _, _, _, 8, 1>::new(
+            &mut storage,
+            author,
+            &mut pending,
+            &mut members,
+            &mut updates,
+        )
+        .unwrap();
+        let mut prior = CommandAddress::zero();
+        let mut flushed = std::vec::Vec::new();
+        for id in 0..6u8 {
+            prior = channel
+                .add_command(&mut storage, prior, author, message(id.into()), &[id; 8])
+                .unwrap();
+            if id % 2 == 1 && id < 5 {
+                flushed.push(prior.region);
+                channel.flush(&mut storage).unwrap();
+            }
+        }
+        let bad = prior.region;
+        storage
+            .with_io_workspace(|flash, _| flash.fail_region(bad))
+            .unwrap();
+        channel.flush(&mut storage).unwrap();
+
+        assert!(storage.is_region_retired(bad));
+        assert!(storage.runtime().free_space().contains_free_region(bad));
+        for region in flushed.iter() {
+            assert!(storage.runtime().free_space().contains_free_region(*region));
+        }
+        (channel.id(), flushed, bad)
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut members_data = [MemberSequence::default(); 1];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 1];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut reopened_data = pending_data::<4>();
+    let mut pending = VecLikeSlice::new(&mut reopened_data);
+    let mut reopened = Channel::<_, _, _, _, 8, 1>::open(
+        id,
+        &mut storage,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    let mut previous = CommandAddress::zero();
+    let mut count = 0u8;
+    reopened
+        .visit_commands(&mut storage, |address, command| {
+            assert!(address.region != bad && !flushed.contains(&address.region));
+            assert_eq!(command.payload(), &[count; 8]);
+            assert_eq!(*command.prior(), previous);
+            previous = address.clone();
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(count, 6);
+    assert!(storage.is_region_retired(bad));
+}
+
+type SyncMembers = VecLikeSlice<'static, MemberSequence, 4>;
+type SyncUpdates = VecLikeSlice<'static, MemberId, 4>;
+type SyncPending = VecLikeSlice<'static, AddCommand<u32, 8>, 4>;
+type SyncChannel =
+    Channel<'static, 'static, 'static, u32, SyncMembers, SyncUpdates, SyncPending, 8, 4>;
+
+/// One replica of a channel in its own `MockFlash`-backed store.
+struct Replica<'f> {
+    storage: Storage<'f, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    channel: SyncChannel,
+}
+
+fn sync_flash() -> std::boxed::Box<TestFlash> {
+    std::boxed::Box::new(TestFlash::new(0xff))
+}
+
+fn leak<T>(value: T) -> &'static mut T {
+    std::boxed::Box::leak(std::boxed::Box::new(value))
+}
+
+fn sync_buffers() -> (
+    &'static mut SyncPending,
+    &'static mut SyncMembers,
+    &'static mut SyncUpdates,
+) {
+    (
+        leak(VecLikeSlice::new(leak(pending_data::<4>()))),
+        leak(VecLikeSlice::new(leak([MemberSequence::default(); 4]))),
+        leak(VecLikeSlice::new(leak([MemberId::default(); 4]))),
+    )
+}
+
+fn replica(flash: &mut TestFlash, first: MemberId) -> Replica<'_> {
+    let mut storage = crate::test_format_storage(flash);
+    let (pending, members, updates) = sync_buffers();
+    let channel = Channel::new(&mut storage, first, pending, members, updates).unwrap();
+    Replica { storage, channel }
+}
+
+fn reopen_replica(flash: &mut TestFlash, id: CollectionId) -> Replica<'_> {
+    let mut storage = crate::test_reopen_storage(flash);
+    let (pending, members, updates) = sync_buffers();
+    let channel = Channel::open(id, &mut storage, pending, members, updates).unwrap();
+    Replica { storage, channel }
+}
+
+impl Replica<'_> {
+    fn author(&mut self, author: u128, message_id: u128, payload: &[u8]) {
+        self.channel
+            .add_command(
+                &mut self.storage,
+                CommandAddress::zero(),
+                member(author),
+                message(message_id),
+                payload,
+            )
+            .unwrap();
+    }
+
+    fn commands(&mut self) -> std::vec::Vec<(MemberId, u64, MessageId, std::vec::Vec<u8>)> {
+        let mut commands = std::vec::Vec::new();
+        self.channel
+            .visit_commands(&mut self.storage, |_, command| {
+                commands.push((
+                    command.author(),
+                    command.sequence().get(),
+                    command.message_id(),
+                    command.payload().to_vec(),
+                ));
+                Ok(())
+            })
+            .unwrap();
+        commands
+    }
+
+    fn priors(&mut self) -> std::vec::Vec<(u64, CommandAddress<u32>, CommandAddress<u32>)> {
+        let mut priors = std::vec::Vec::new();
+        self.channel
+            .visit_commands(&mut self.storage, |address, command| {
+                priors.push((
+                    command.sequence().get(),
+                    address.clone(),
+                    command.prior().clone(),
+                ));
+                Ok(())
+            })
+            .unwrap();
+        priors
+    }
+}
+
+/// An in-process byte pipe standing in for the transport between replicas.
+struct BytePipe {
+    bytes: [u8; 512],
+    limit: usize,
+    len: usize,
+}
+
+impl BytePipe {
+    fn new(limit: usize) -> Self {
+        Self {
+            bytes: [0; 512],
+            limit,
+            len: 0,
+        }
+    }
+
+    fn send(
+        &mut self,
+        encode: impl FnOnce(&mut [u8]) -> Result<usize, ChannelError>,
+    ) -> Result<(), ChannelError> {
+        self.len = encode(&mut self.bytes[..self.limit])?;
+        assert!(self.len <= self.limit);
+        Ok(())
+    }
+
+    fn received(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Runs one pull exchange that brings `to` up to date with `from`.
+fn pull(
+    from: &mut Replica<'_>,
+    to: &mut Replica<'_>,
+    pipe: &mut BytePipe,
+) -> Result<ChannelSyncMerge, ChannelError> {
+    let mut request = BytePipe::new(512);
+    request.send(|output| to.channel.encode_sync_request(output))?;
+    pipe.send(|output| {
+        from.channel
+            .encode_sync_response(&mut from.storage, request.received(), output)
+            .map(|batch| batch.len)
+    })?;
+    to.channel
+        .merge_sync_response(&mut to.storage, pipe.received())
+}
+
+//= spec/channel.md#replica-synchronization
+//= type=test
+//# `RING-CHANNEL-009` A sync response MUST carry the members missing from the
+//# requester's sequence vector and, oldest to newest, every command whose
+//# sequence is newer than the requester's last sequence for its author.
+#[test]
+fn requirement_channel_sync_sends_missing_commands_oldest_first() {
+    let mut flash_a = sync_flash();
+    let mut flash_b = sync_flash();
+    let mut a = replica(&mut flash_a, member(1));
+    let mut b = replica(&mut flash_b, member(1));
+    a.channel.add_member(&mut a.storage, member(2)).unwrap();
+    a.author(1, 10, b"a");
+    a.author(2, 11, b"b");
+    a.author(1, 12, b"c");
+
+    let mut request = BytePipe::new(512);
+    request
+        .send(|output| b.channel.encode_sync_request(output))
+        .unwrap();
+    let mut response = [0u8; 512];
+    let batch = a
+        .channel
+        .encode_sync_response(&mut a.storage, request.received(), &mut response)
+        .unwrap();
+    assert_eq!(batch.members, 1);
+    assert_eq!(batch.commands, 3);
+    assert!(!batch.more);
+
+    let merge = b
+        .channel
+        .merge_sync_response(&mut b.storage, &response[..batch.len])
+        .unwrap();
+    assert_eq!(merge.members_added, 1);
+    assert_eq!(merge.merged, 3);
+    assert_eq!(merge.skipped, 0);
+    assert!(!merge.more);
+    assert_eq!(b.commands(), a.commands());
+    assert_eq!(b.channel.members(), a.channel.members());
+
+    let mut pipe = BytePipe::new(512);
+    let merge = pull(&mut a, &mut b, &mut pipe).unwrap();
+    assert_eq!((merge.members_added, merge.merged), (0, 0));
+
+    // A command authored on the replica jumps past every synced sequence,
+    // and only that command flows back.
+    b.author(2, 13, b"d");
+    assert_eq!(b.commands()[3].1, 4);
+    let merge = pull(&mut b, &mut a, &mut pipe).unwrap();
+    assert_eq!(merge.merged, 1);
+    assert_eq!(a.commands(), b.commands());
+}
+
+//= spec/channel.md#replica-synchronization
+//= type=test
+//# `RING-CHANNEL-010` Sync frames MUST never exceed the caller's buffer: a
+//# response MUST stop before the first missing command that does not fit and
+//# set its `more` flag, and MUST fail with `SyncBufferTooSmall` when not even
+//# one missing command fits.
+#[test]
+fn requirement_channel_sync_response_is_bounded() {
+    let mut flash_a = sync_flash();
+    let mut flash_b = sync_flash();
+    let mut a = replica(&mut flash_a, member(1));
+    let mut b = replica(&mut flash_b, member(1));
+    for index in 0..9u8 {
+        a.author(1, index.into(), &[index; 8]);
+    }
+
+    let mut tiny = BytePipe::new(40);
+    assert!(matches!(
+        pull(&mut a, &mut b, &mut tiny),
+        Err(ChannelError::SyncBufferTooSmall { capacity: 40, .. })
+    ));
+    assert_eq!(b.channel.command_count(), 0);
+
+    let mut pipe = BytePipe::new(160);
+    let mut rounds = 0;
+    loop {
+        rounds += 1;
+        let merge = pull(&mut a, &mut b, &mut pipe).unwrap();
+        assert!(merge.merged > 0);
+        if !merge.more {
+            break;
+        }
+    }
+    assert!(rounds > 1);
+    assert_eq!(b.commands(), a.commands());
+}
+
+//= spec/channel.md#replica-synchronization
+//= type=test
+//# `RING-CHANNEL-011` Merging a sync response MUST skip commands the replica
+//# already has and MUST reject, without writing it, a command whose
+//# `sender_last` is not its author's last sequence on the replica.
+#[test]
+fn requirement_channel_sync_merge_skips_known_commands_and_detects_gaps() {
+    let mut flash_a = sync_flash();
+    let mut flash_b = sync_flash();
+    let mut flash_c = sync_flash();
+    let mut a = replica(&mut flash_a, member(1));
+    let mut b = replica(&mut flash_b, member(1));
+    let mut c = replica(&mut flash_c, member(1));
+    a.author(1, 1, b"one");
+    a.author(1, 2, b"two");
+
+    // A response sized for one command leaves `c` one command behind.
+    let mut pipe = BytePipe::new(SYNC_ONE_COMMAND_RESPONSE);
+    let merge = pull(&mut a, &mut c, &mut pipe).unwrap();
+    assert_eq!((merge.merged, merge.more), (1, true));
+
+    // The response for `c` starts after sequence 1, which `b` never saw.
+    let mut request = BytePipe::new(512);
+    request
+        .send(|output| c.channel.encode_sync_request(output))
+        .unwrap();
+    let mut response = BytePipe::new(512);
+    response
+        .send(|output| {
+            a.channel
+                .encode_sync_response(&mut a.storage, request.received(), output)
+                .map(|batch| batch.len)
+        })
+        .unwrap();
+    assert!(matches!(
+        b.channel.merge_sync_response(&mut b.storage, response.received()),
+        Err(ChannelError::SequenceMismatch { member: author, expected, actual })
+            if author == member(1)
+                && expected == ChannelSequence(0)
+                && actual == ChannelSequence(1)
+    ));
+    assert_eq!(b.channel.command_count(), 0);
+
+    // The same response merges into `c`, and merging it again skips it.
+    let merge = c
+        .channel
+        .merge_sync_response(&mut c.storage, response.received())
+        .unwrap();
+    assert_eq!((merge.merged, merge.skipped), (1, 0));
+    let merge = c
+        .channel
+        .merge_sync_response(&mut c.storage, response.received())
+        .unwrap();
+    assert_eq!((merge.merged, merge.skipped), (0, 1));
+    assert_eq!(c.commands(), a.commands());
+}
+
+/// Response length for one command with a three-byte payload and no members.
+const SYNC_ONE_COMMAND_RESPONSE: usize = 4 + 2 + 1 + 4 + 4 + 8 + 8 + 16 + 16 + 4 + 3;
+
+//= spec/channel.md#replica-synchronization
+//= type=test
+//# `RING-CHANNEL-012` Merging a sync response MUST fail with
+//# `DuplicateMessageId` before writing anything when a new command reuses a
+//# `MessageId` held by the replica or by another command in the response.
+#[test]
+fn requirement_channel_sync_merge_rejects_duplicate_message_ids() {
+    let mut flash_a = sync_flash();
+    let mut flash_b = sync_flash();
+    let mut flash_c = sync_flash();
+    let mut a = replica(&mut flash_a, member(1));
+    let mut b = replica(&mut flash_b, member(1));
+    let mut c = replica(&mut flash_c, member(1));
+    a.channel.add_member(&mut a.storage, member(2)).unwrap();
+    a.author(2, 6, b"six");
+    a.author(1, 7, b"seven");
+    b.channel.add_member(&mut b.storage, member(2)).unwrap();
+    b.author(2, 7, b"other");
+
+    let mut pipe = BytePipe::new(512);
+    assert!(matches!(
+        pull(&mut a, &mut b, &mut pipe),
+        Err(ChannelError::DuplicateMessageId(id)) if id == message(7)
+    ));
+    assert_eq!(b.channel.command_count(), 1);
+    assert_eq!(
+        b.channel.get_last_sequence(&member(1)).unwrap(),
+        ChannelSequence(0)
+    );
+
+    a.author(1, 6, b"again");
+    assert!(matches!(
+        pull(&mut a, &mut c, &mut pipe),
+        Err(ChannelError::DuplicateMessageId(id)) if id == message(6)
+    ));
+    assert_eq!(c.channel.command_count(), 0);
+    assert_eq!(c.channel.members().len(), 1);
+}
+
+//= spec/channel.md#replica-synchronization
+//= type=test
+//# `RING-CHANNEL-013` Merged members and commands MUST be persisted through
+//# the same WAL updates as locally added ones, keeping their author, sequence,
+//# and `MessageId`.
+#[test]
+fn requirement_channel_sync_merge_survives_reopen() {
+    let mut flash_a = sync_flash();
+    let mut flash_b = sync_flash();
+    let mut a = replica(&mut flash_a, member(1));
+    a.channel.add_member(&mut a.storage, member(3)).unwrap();
+    a.author(3, 30, b"x");
+    a.author(1, 31, b"y");
+    let expected = a.commands();
+    let id = {
+        let mut b = replica(&mut flash_b, member(1));
+        let mut pipe = BytePipe::new(512);
+        pull(&mut a, &mut b, &mut pipe).unwrap();
+        b.channel.id()
+    };
+
+    let mut b = reopen_replica(&mut flash_b, id);
+    assert_eq!(b.commands(), expected);
+    assert_eq!(
+        b.channel.get_last_sequence(&member(3)).unwrap(),
+        ChannelSequence(1)
+    );
+    assert_eq!(b.channel.next_sequence(), ChannelSequence(3));
+}
+
+//= spec/channel.md#replica-synchronization
+//= type=test
+//# `RING-CHANNEL-014` A merged command's `prior` MUST be the local address of
+//# the command with the largest sequence below its own, or the zero address
+//# when the replica holds no lower sequence.
+#[test]
+fn requirement_channel_sync_merge_records_local_predecessor() {
+    let mut flash_a = sync_flash();
+    let mut flash_b = sync_flash();
+    let mut a = replica(&mut flash_a, member(1));
+    let mut b = replica(&mut flash_b, member(1));
+    b.channel.add_member(&mut b.storage, member(2)).unwrap();
+    b.author(2, 50, b"local");
+
+    let mut pipe = BytePipe::new(512);
+    pull(&mut b, &mut a, &mut pipe).unwrap();
+    assert_eq!(a.priors()[0].2, CommandAddress::zero());
+
+    // The first merged command follows `b`'s own command, and the second
+    // follows the first one merged from the same response.
+    a.author(1, 60, b"two");
+    a.author(1, 61, b"three");
+    let merge = pull(&mut a, &mut b, &mut pipe).unwrap();
+    assert_eq!(merge.merged, 2);
+    let priors = b.priors();
+    assert_eq!(
+        priors
+            .iter()
+            .map(|entry| entry.0)
+            .collect::<std::vec::Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert_eq!(priors[1].2, priors[0].1);
+    assert_eq!(priors[2].2, priors[1].1);
+}
+
+//= spec/channel.md#replica-synchronization
+//= type=test
+//# `RING-CHANNEL-015` A sync response MUST carry at most
+//# `CHANNEL_SYNC_MAX_COMMANDS` commands, setting its `more` flag when further
+//# missing commands remain, and merging MUST reject a response that carries
+//# more.
+#[test]
+fn requirement_channel_sync_response_command_count_is_bounded() {
+    let mut flash_a = sync_flash();
+    let mut flash_b = sync_flash();
+    let mut a = replica(&mut flash_a, member(1));
+    let mut b = replica(&mut flash_b, member(1));
+    for index in 0..=CHANNEL_SYNC_MAX_COMMANDS as u128 {
+        a.author(1, index, &[index as u8]);
+    }
+
+    let mut request = BytePipe::new(512);
+    request
+        .send(|output| b.channel.encode_sync_request(output))
+        .unwrap();
+    let mut response = [0u8; 4096];
+    let batch = a
+        .channel
+        .encode_sync_response(&mut a.storage, request.received(), &mut response)
+        .unwrap();
+    assert_eq!(batch.commands as usize, CHANNEL_SYNC_MAX_COMMANDS);
+    assert!(batch.more);
+
+    // One command too many: repeat the last command and bump the count.
+    let command_len = SYNC_ONE_COMMAND_RESPONSE - (4 + 2 + 1 + 4 + 4) - 2;
+    let mut oversized = response[..batch.len].to_vec();
+    oversized.extend_from_within(batch.len - command_len..);
+    let count_offset = 4 + 2 + 1 + 4;
+    oversized[count_offset..count_offset + 4]
+        .copy_from_slice(&(CHANNEL_SYNC_MAX_COMMANDS as u32 + 1).to_le_bytes());
+    assert!(matches!(
+        b.channel.merge_sync_response(&mut b.storage, &oversized),
+        Err(ChannelError::InvalidEncoding)
+    ));
+    assert_eq!(b.channel.command_count(), 0);
+
+    let merge = b
+        .channel
+        .merge_sync_response(&mut b.storage, &response[..batch.len])
+        .unwrap();
+    assert_eq!(
+        (merge.merged as usize, merge.more),
+        (CHANNEL_SYNC_MAX_COMMANDS, true)
+    );
+    let mut pipe = BytePipe::new(512);
+    let merge = pull(&mut a, &mut b, &mut pipe).unwrap();
+    assert_eq!((merge.merged, merge.more), (1, false));
+    assert_eq!(b.commands(), a.commands());
+}
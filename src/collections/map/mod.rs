@@ -12,6 +12,7 @@ use core::cmp::Ordering;
 use core::fmt::Debug;
 use core::marker::PhantomData;
 use core::mem::size_of;
+use core::ops::{Bound, RangeBounds};
 use heapless::Vec;
 use postcard::{from_bytes, to_slice};
 use serde::{Deserialize, Serialize};
@@ -262,6 +263,30 @@ impl LsmKey for bool {
     const COMPARES_ENCODED_KEY_WITHOUT_DECODE: bool = true;
 }
 
+/// String keys encode as their raw UTF-8 bytes, so stored byte order matches
+/// `str` ordering and every key sharing a prefix forms one contiguous range.
+impl<const N: usize> LsmKey for heapless::String<N> {
+    fn encode_key(&self, out: &mut [u8]) -> Result<usize, LsmKeyError> {
+        let bytes = self.as_bytes();
+        let target = out
+            .get_mut(..bytes.len())
+            .ok_or(LsmKeyError::BufferTooSmall)?;
+        target.copy_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn decode_key(bytes: &[u8]) -> Result<Self, LsmKeyError> {
+        let key = core::str::from_utf8(bytes).map_err(|_| LsmKeyError::SerializationError)?;
+        heapless::String::try_from(key).map_err(|_| LsmKeyError::SerializationError)
+    }
+
+    fn compare_encoded_key(encoded: &[u8], key: &Self) -> Result<Ordering, LsmKeyError> {
+        Ok(encoded.cmp(key.as_bytes()))
+    }
+
+    const COMPARES_ENCODED_KEY_WITHOUT_DECODE: bool = true;
+}
+
 /// Public value boundary for durable LSM maps.
 ///
 /// The default implementation preserves the current postcard-encoded value
@@ -321,8 +346,22 @@ const ENTRY_KIND_SIZE: usize = size_of::<u8>();
 const ENTRY_KEY_LEN_SIZE: usize = size_of::<u32>();
 const ENTRY_VALUE_LEN_SIZE: usize = size_of::<u32>();
 const ENTRY_HEADER_SIZE: usize = ENTRY_KIND_SIZE + ENTRY_KEY_LEN_SIZE + ENTRY_VALUE_LEN_SIZE;
+const KEY_ONLY_ENTRY_HEADER_SIZE: usize = ENTRY_KIND_SIZE + ENTRY_KEY_LEN_SIZE;
 const ENTRY_KIND_SET: u8 = 1;
 const ENTRY_KIND_DELETE: u8 = 2;
+/// Returns whether `collection_type` is stored with the LSM map layout,
+/// which covers maps and the key-only sets built on them.
+pub(crate) const fn is_lsm_collection_type(collection_type: u16) -> bool {
+    matches!(
+        collection_type,
+        CollectionType::MAP_CODE | CollectionType::SET_CODE
+    )
+}
+
+/// Set member entry: kind, key length, and key, with no value field.
+const ENTRY_KIND_MEMBER: u8 = 3;
+/// Set removal entry: kind, key length, and key, with no value field.
+const ENTRY_KIND_REMOVED: u8 = 4;
 
 /// Stable committed-region format identifier for map regions.
 pub const MAP_REGION_V2_FORMAT: u16 = 4;
@@ -473,6 +512,12 @@ impl From<StorageIoError> for MapStorageError {
             StorageIoError::FileBacking(error) => Self::Storage(StorageRuntimeError::from(
                 crate::flash_io::StorageIoError::FileBacking(error),
             )),
+            StorageIoError::BadRegion(region_index) => {
+                Self::Storage(StorageRuntimeError::BadRegion(region_index))
+            }
+            StorageIoError::ReadOnly(error) => {
+                Self::Storage(StorageRuntimeError::from(StorageIoError::ReadOnly(error)))
+            }
         }
     }
 }
@@ -557,6 +602,36 @@ where
         }
         true
     }
+
+    fn may_overlap<R: RangeBounds<K>>(&self, range: &R) -> bool {
+        if let Some(upper_key) = self.upper_key.as_ref() {
+            if key_before_range(range, upper_key) {
+                return false;
+            }
+        }
+        if let Some(lower_key) = self.lower_key.as_ref() {
+            if key_after_range(range, lower_key) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn key_before_range<K: Ord, R: RangeBounds<K>>(range: &R, key: &K) -> bool {
+    match range.start_bound() {
+        Bound::Included(start) => key < start,
+        Bound::Excluded(start) => key <= start,
+        Bound::Unbounded => false,
+    }
+}
+
+fn key_after_range<K: Ord, R: RangeBounds<K>>(range: &R, key: &K) -> bool {
+    match range.end_bound() {
+        Bound::Included(end) => key > end,
+        Bound::Excluded(end) => key >= end,
+        Bound::Unbounded => false,
+    }
 }
 
 fn read_u32(buffer: &[u8], offset: &mut usize) -> Result<u32, MapError> {
@@ -830,7 +905,7 @@ fn ref_to_usize(value: RefType) -> Result<usize, MapError> {
 }
 
 fn parse_encoded_entry(entry: &[u8]) -> Result<EncodedEntry<'_>, MapError> {
-    if entry.len() < ENTRY_HEADER_SIZE {
+    if entry.len() < KEY_ONLY_ENTRY_HEADER_SIZE {
         return Err(MapError::SerializationError);
     }
 
@@ -840,6 +915,30 @@ fn parse_encoded_entry(entry: &[u8]) -> Result<EncodedEntry<'_>, MapError> {
     let key_len = usize::try_from(u32::from_le_bytes(key_len_bytes))
         .map_err(|_| MapError::SerializationError)?;
 
+    if matches!(kind, ENTRY_KIND_MEMBER | ENTRY_KIND_REMOVED) {
+        let key_end = checked_add_usize(KEY_ONLY_ENTRY_HEADER_SIZE, key_len)?;
+        if key_end != entry.len() {
+            return Err(MapError::SerializationError);
+        }
+        let key = &entry[KEY_ONLY_ENTRY_HEADER_SIZE..key_end];
+        return Ok(if kind == ENTRY_KIND_MEMBER {
+            EncodedEntry {
+                kind: ENTRY_KIND_SET,
+                key,
+                value: Some(&[]),
+            }
+        } else {
+            EncodedEntry {
+                kind: ENTRY_KIND_DELETE,
+                key,
+                value: None,
+            }
+        });
+    }
+
+    if entry.len() < ENTRY_HEADER_SIZE {
+        return Err(MapError::SerializationError);
+    }
     let value_len_offset = ENTRY_KIND_SIZE + ENTRY_KEY_LEN_SIZE;
     let mut value_len_bytes = [0u8; ENTRY_VALUE_LEN_SIZE];
     value_len_bytes
@@ -874,11 +973,40 @@ fn parse_encoded_entry(entry: &[u8]) -> Result<EncodedEntry<'_>, MapError> {
     }
 }
 
-fn encode_entry_into<K, V>(key: &K, value: Option<&V>, out: &mut [u8]) -> Result<usize, MapError>
+/// Encodes one entry. Key-only entries, written by sets, drop the value
+/// length and reject any value that does not encode to nothing.
+fn encode_entry_into<K, V>(
+    key: &K,
+    value: Option<&V>,
+    key_only: bool,
+    out: &mut [u8],
+) -> Result<usize, MapError>
 where
     K: LsmKey,
     V: LsmValue,
 {
+    if key_only {
+        if out.len() < KEY_ONLY_ENTRY_HEADER_SIZE {
+            return Err(MapError::BufferTooSmall);
+        }
+        if let Some(value) = value {
+            if !matches!(value.encode_value(&mut []), Ok(0)) {
+                return Err(MapError::SerializationError);
+            }
+        }
+        let key_len = key.encode_key(&mut out[KEY_ONLY_ENTRY_HEADER_SIZE..])?;
+        let end = checked_add_usize(KEY_ONLY_ENTRY_HEADER_SIZE, key_len)?;
+        out[0] = if value.is_some() {
+            ENTRY_KIND_MEMBER
+        } else {
+            ENTRY_KIND_REMOVED
+        };
+        let key_len_u32 = u32::try_from(key_len).map_err(|_| MapError::SerializationError)?;
+        out[ENTRY_KIND_SIZE..KEY_ONLY_ENTRY_HEADER_SIZE]
+            .copy_from_slice(&key_len_u32.to_le_bytes());
+        return Ok(end);
+    }
+
     if out.len() < ENTRY_HEADER_SIZE {
         return Err(MapError::BufferTooSmall);
     }
@@ -1134,6 +1262,7 @@ where
         let used = encode_entry_into(
             &entry.key,
             entry.value.as_ref(),
+            false,
             &mut snapshot[write_offset..temp_refs_start],
         )?;
         let next_write_offset = write_offset
@@ -1816,6 +1945,7 @@ where
 /// Caller-owned bounded map frontier used by advanced storage helpers.
 pub struct MapFrontier<'a, K, V, const MAX_RUNS: usize = DEFAULT_MAX_RUNS> {
     id: CollectionId,
+    collection_type: u16,
     record_count: EntryCount,
     next_record_offset: RecordOffset,
     next_record_index: RecordIndex,
@@ -1826,6 +1956,7 @@ pub struct MapFrontier<'a, K, V, const MAX_RUNS: usize = DEFAULT_MAX_RUNS> {
 
 pub(crate) struct MapFrontierState {
     id: CollectionId,
+    collection_type: u16,
     record_count: EntryCount,
     next_record_offset: RecordOffset,
     next_record_index: RecordIndex,
@@ -2298,14 +2429,15 @@ where
                 |snapshot| self.segment.encode_snapshot_into(snapshot),
             )?
         };
-        storage.write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
-            flash,
-            workspace,
-            region_index,
-            collection_id,
-            MAP_RUN_V2_FORMAT,
-            used,
-        )?;
+        let region_index = storage
+            .write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                region_index,
+                collection_id,
+                MAP_RUN_V2_FORMAT,
+                used,
+            )?;
         if self.lowest_region.is_none() {
             self.lowest_region = Some(region_index);
         }
@@ -2450,6 +2582,7 @@ where
 
         Ok(Self {
             id,
+            collection_type: CollectionType::MAP_CODE,
             record_count,
             next_record_index,
             next_record_offset,
@@ -2459,6 +2592,22 @@ where
         })
     }
 
+    /// Sets the durable collection type this frontier writes as; sets use
+    /// [`CollectionType::SET_CODE`] and key-only entries.
+    pub(crate) fn with_collection_type(mut self, collection_type: u16) -> Self {
+        self.collection_type = collection_type;
+        self
+    }
+
+    /// Returns the durable collection type this frontier writes as.
+    pub(crate) fn collection_type(&self) -> u16 {
+        self.collection_type
+    }
+
+    fn key_only(&self) -> bool {
+        self.collection_type == CollectionType::SET_CODE
+    }
+
     pub(crate) fn from_state(
         state: MapFrontierState,
         buffer: &'a mut [u8],
@@ -2466,6 +2615,7 @@ where
     ) -> Self {
         Self {
             id: state.id,
+            collection_type: state.collection_type,
             record_count: state.record_count,
             next_record_index: state.next_record_index,
             next_record_offset: state.next_record_offset,
@@ -2478,6 +2628,7 @@ where
     pub(crate) fn into_state(self) -> MapFrontierState {
         MapFrontierState {
             id: self.id,
+            collection_type: self.collection_type,
             record_count: self.record_count,
             next_record_offset: self.next_record_offset,
             next_record_index: self.next_record_index,
@@ -2643,7 +2794,7 @@ where
         V: LsmValue,
     {
         let search_result = self.find_index(key)?;
-        let entry_len = encode_entry_into(key, value, scratch)?;
+        let entry_len = encode_entry_into(key, value, self.key_only(), scratch)?;
         let start = self.next_record_offset;
         let index_offset = self
             .next_record_index
@@ -2864,6 +3015,143 @@ where
         Ok(None)
     }
 
+    /// Visits visible entries whose keys fall in `range` in ascending key order.
+    ///
+    /// The frontier and every overlapping run are merged with one cursor per
+    /// run; the newest layer holding a key decides whether it is visible.
+    /// Scanning stops early when `visitor` returns `false`.
+    pub(crate) fn visit_range<const REGION_SIZE: usize, IO: FlashIo, R, F>(
+        &self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        range: &R,
+        cursors: &mut Vec<RunEntryCursor<K, V>, MAX_RUNS>,
+        visitor: &mut F,
+    ) -> Result<(), MapStorageError>
+    where
+        R: RangeBounds<K>,
+        F: FnMut(K, V) -> Result<bool, MapStorageError>,
+    {
+        cursors.clear();
+        for run in self.runs.iter() {
+            if !run.may_overlap(range) {
+                continue;
+            }
+            let mut cursor = RunEntryCursor::new(run)?;
+            cursor.advance::<REGION_SIZE, IO>(self.id, flash, workspace)?;
+            while cursor
+                .current
+                .as_ref()
+                .is_some_and(|entry| key_before_range(range, &entry.key))
+            {
+                cursor.advance::<REGION_SIZE, IO>(self.id, flash, workspace)?;
+            }
+            cursors
+                .push(cursor)
+                .map_err(|_| MapStorageError::TooManyRuns {
+                    collection_id: self.id,
+                    max_runs: MAX_RUNS,
+                })?;
+        }
+
+        let frontier_len = self.frontier_entry_count();
+        let mut frontier_index = match range.start_bound() {
+            Bound::Unbounded => 0,
+            Bound::Included(key) => match self.find_index(key)? {
+                SearchResult::Found(index) | SearchResult::NotFound(index) => index.0,
+            },
+            Bound::Excluded(key) => match self.find_index(key)? {
+                SearchResult::Found(index) => index.next().0,
+                SearchResult::NotFound(index) => index.0,
+            },
+        };
+        let mut frontier_entry = self.frontier_entry_at(frontier_index, frontier_len)?;
+
+        loop {
+            let mut min_index: Option<usize> = None;
+            for index in 0..cursors.len() {
+                let Some(entry) = cursors[index].current.as_ref() else {
+                    continue;
+                };
+                let should_replace = match min_index {
+                    Some(current_min) => {
+                        let min_entry = cursors[current_min]
+                            .current
+                            .as_ref()
+                            .ok_or(MapError::SerializationError)?;
+                        entry.key < min_entry.key
+                    }
+                    None => true,
+                };
+                if should_replace {
+                    min_index = Some(index);
+                }
+            }
+
+            let take_frontier = match (frontier_entry.as_ref(), min_index) {
+                (None, None) => break,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some(entry), Some(index)) => {
+                    let run_entry = cursors[index]
+                        .current
+                        .as_ref()
+                        .ok_or(MapError::SerializationError)?;
+                    entry.key <= run_entry.key
+                }
+            };
+            let entry = if take_frontier {
+                let entry = frontier_entry.take().ok_or(MapError::SerializationError)?;
+                frontier_index = checked_add_usize(frontier_index, 1)?;
+                frontier_entry = self.frontier_entry_at(frontier_index, frontier_len)?;
+                entry
+            } else {
+                let index = min_index.ok_or(MapError::SerializationError)?;
+                let entry = cursors[index]
+                    .current
+                    .take()
+                    .ok_or(MapError::SerializationError)?;
+                cursors[index].advance::<REGION_SIZE, IO>(self.id, flash, workspace)?;
+                entry
+            };
+            if key_after_range(range, &entry.key) {
+                break;
+            }
+            for cursor in cursors.iter_mut() {
+                if cursor
+                    .current
+                    .as_ref()
+                    .is_some_and(|shadowed| shadowed.key == entry.key)
+                {
+                    cursor.advance::<REGION_SIZE, IO>(self.id, flash, workspace)?;
+                }
+            }
+            if let Some(value) = entry.value {
+                if !visitor(entry.key, value)? {
+                    break;
+                }
+            }
+        }
+
+        cursors.clear();
+        Ok(())
+    }
+
+    fn frontier_entry_at(
+        &self,
+        index: usize,
+        frontier_len: usize,
+    ) -> Result<Option<Entry<K, V>>, MapError> {
+        if index >= frontier_len {
+            return Ok(None);
+        }
+        let entry_ref = EntryRef::read(self.map, RecordIndex::new(index))?;
+        let start = ref_to_usize(entry_ref.start)?;
+        let end = ref_to_usize(entry_ref.end)?;
+        let bytes = self.map.get(start..end).ok_or(MapError::IndexOutOfBounds)?;
+        encoded_entry_to_entry(bytes).map(Some)
+    }
+
     fn clear_frontier(&mut self) {
         self.record_count = EntryCount(0);
         self.next_record_offset = RecordOffset(ENTRY_COUNT_SIZE);
@@ -3200,7 +3488,8 @@ where
         }
 
         let segment =
-            MapFrontier::<K, V, MAX_RUNS>::new_with_runs(self.id, segment_buffer, segment_runs)?;
+            MapFrontier::<K, V, MAX_RUNS>::new_with_runs(self.id, segment_buffer, segment_runs)?
+                .with_collection_type(self.collection_type);
         let mut writer =
             CompactionRunWriter::<K, V, MAX_RUNS>::new(self.next_run_generation(), segment);
         loop {
@@ -3396,8 +3685,9 @@ where
         if start.0 >= index_offset {
             return Err(MapError::BufferTooSmall);
         }
+        let key_only = self.key_only();
         let buf = &mut self.map[start.0..index_offset];
-        let used = encode_entry_into(key, value, buf)?;
+        let used = encode_entry_into(key, value, key_only, buf)?;
 
         let mut end = start;
 
@@ -4077,7 +4367,7 @@ where
             flash,
             workspace,
             self.id,
-            CollectionType::MAP_CODE,
+            self.collection_type,
             &snapshot_scratch[..used],
         )?;
         Ok(())
@@ -4275,7 +4565,7 @@ where
                     plan.entry_count,
                 )?
             };
-            storage
+            let region_index = storage
                 .write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
                     flash,
                     workspace,
@@ -4379,7 +4669,7 @@ where
                     plan.entry_count,
                 )?
             };
-            storage
+            let region_index = storage
                 .write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
                     flash,
                     workspace,
@@ -4470,19 +4760,20 @@ where
             let payload = committed_payload_buffer::<REGION_SIZE>(payload)?;
             self.encode_manifest_into(payload, extra_newest.as_ref(), None)?
         };
-        storage.write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
-            flash,
-            workspace,
-            manifest_region,
-            self.id,
-            MAP_MANIFEST_V2_FORMAT,
-            used,
-        )?;
+        let manifest_region = storage
+            .write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                manifest_region,
+                self.id,
+                MAP_MANIFEST_V2_FORMAT,
+                used,
+            )?;
         storage.append_head_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
             flash,
             workspace,
             self.id,
-            CollectionType::MAP_CODE,
+            self.collection_type,
             manifest_region,
         )?;
         if let Some(previous_region) = previous_region {
@@ -4593,19 +4884,20 @@ where
             let payload = committed_payload_buffer::<REGION_SIZE>(payload)?;
             self.encode_manifest_into(payload, frontier_run.as_ref(), None)?
         };
-        storage.write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
-            flash,
-            workspace,
-            manifest_region,
-            self.id,
-            MAP_MANIFEST_V2_FORMAT,
-            used,
-        )?;
+        let manifest_region = storage
+            .write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                manifest_region,
+                self.id,
+                MAP_MANIFEST_V2_FORMAT,
+                used,
+            )?;
         storage.append_head_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
             flash,
             workspace,
             self.id,
-            CollectionType::MAP_CODE,
+            self.collection_type,
             manifest_region,
         )?;
         if let Some(previous_region) = previous_region {
@@ -4660,6 +4952,37 @@ where
             collection_id,
             buffer,
             memory,
+            false,
+            #[cfg(feature = "perf-counters")]
+            None,
+        )
+    }
+
+    /// Opens a live map collection whose head may still be a retired
+    /// `map_region_v2` snapshot region, so a migration can rewrite it.
+    pub(crate) fn open_legacy_from_storage<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &StorageRuntime<MAX_COLLECTIONS>,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        basis_scratch: &mut [u8],
+        collection_id: CollectionId,
+        buffer: &'a mut [u8],
+        memory: &'a mut MapFrontierMemory<K, MAX_RUNS>,
+    ) -> Result<Self, MapStorageError> {
+        Self::open_from_storage_inner::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            workspace,
+            basis_scratch,
+            collection_id,
+            buffer,
+            memory,
+            true,
             #[cfg(feature = "perf-counters")]
             None,
         )
@@ -4689,6 +5012,7 @@ where
             collection_id,
             buffer,
             memory,
+            false,
             Some(metrics),
         )
     }
@@ -4706,6 +5030,7 @@ where
         collection_id: CollectionId,
         buffer: &'a mut [u8],
         memory: &'a mut MapFrontierMemory<K, MAX_RUNS>,
+        accept_region_v2: bool,
         #[cfg(feature = "perf-counters")] mut metrics: Option<&mut StoragePerfMetrics>,
     ) -> Result<Self, MapStorageError> {
         let Some(collection) = storage
@@ -4718,15 +5043,20 @@ where
         if collection.basis() == crate::StartupCollectionBasis::Dropped {
             return Err(MapStorageError::DroppedCollection(collection_id));
         }
-        if collection.collection_type() != Some(CollectionType::MAP_CODE) {
-            return Err(MapStorageError::CollectionTypeMismatch {
-                collection_id,
-                expected: CollectionType::MAP_CODE,
-                actual: collection.collection_type(),
-            });
-        }
+        storage.ensure_not_quarantined(collection_id)?;
+        let collection_type = match collection.collection_type() {
+            Some(collection_type) if is_lsm_collection_type(collection_type) => collection_type,
+            actual => {
+                return Err(MapStorageError::CollectionTypeMismatch {
+                    collection_id,
+                    expected: CollectionType::MAP_CODE,
+                    actual,
+                });
+            }
+        };
 
-        let mut map = Self::new(collection_id, buffer, memory)?;
+        let mut map =
+            Self::new(collection_id, buffer, memory)?.with_collection_type(collection_type);
         let target_basis = collection.basis();
         let mut basis_loaded = matches!(target_basis, crate::StartupCollectionBasis::Empty);
         #[cfg(feature = "perf-counters")]
@@ -4760,10 +5090,10 @@ where
                                 if record_collection_id != collection_id {
                                     return Ok(());
                                 }
-                                if collection_type != CollectionType::MAP_CODE {
+                                if collection_type != map.collection_type {
                                     return Err(MapStorageError::CollectionTypeMismatch {
                                         collection_id,
-                                        expected: CollectionType::MAP_CODE,
+                                        expected: map.collection_type,
                                         actual: Some(collection_type),
                                     });
                                 }
@@ -4782,10 +5112,10 @@ where
                                 if record_collection_id != collection_id {
                                     return Ok(());
                                 }
-                                if collection_type != CollectionType::MAP_CODE {
+                                if collection_type != map.collection_type {
                                     return Err(MapStorageError::CollectionTypeMismatch {
                                         collection_id,
-                                        expected: CollectionType::MAP_CODE,
+                                        expected: map.collection_type,
                                         actual: Some(collection_type),
                                     });
                                 }
@@ -4799,6 +5129,7 @@ where
                                         region_index,
                                         basis_scratch,
                                         &mut map,
+                                        accept_region_v2,
                                     )?;
                                     basis_loaded = true;
                                 } else {
@@ -4838,10 +5169,10 @@ where
                                 if record_collection_id != collection_id {
                                     return Ok(());
                                 }
-                                if collection_type != CollectionType::MAP_CODE {
+                                if collection_type != map.collection_type {
                                     return Err(MapStorageError::CollectionTypeMismatch {
                                         collection_id,
-                                        expected: CollectionType::MAP_CODE,
+                                        expected: map.collection_type,
                                         actual: Some(collection_type),
                                     });
                                 }
@@ -4860,10 +5191,10 @@ where
                                 if record_collection_id != collection_id {
                                     return Ok(());
                                 }
-                                if collection_type != CollectionType::MAP_CODE {
+                                if collection_type != map.collection_type {
                                     return Err(MapStorageError::CollectionTypeMismatch {
                                         collection_id,
-                                        expected: CollectionType::MAP_CODE,
+                                        expected: map.collection_type,
                                         actual: Some(collection_type),
                                     });
                                 }
@@ -4877,6 +5208,7 @@ where
                                         region_index,
                                         basis_scratch,
                                         &mut map,
+                                        accept_region_v2,
                                     )?;
                                     basis_loaded = true;
                                 } else {
@@ -4915,6 +5247,7 @@ fn load_map_basis_from_flash<const REGION_SIZE: usize, IO: FlashIo, K, V, const
     region_index: u32,
     basis_scratch: &mut [u8],
     map: &mut MapFrontier<'_, K, V, MAX_RUNS>,
+    accept_region_v2: bool,
 ) -> Result<(), MapStorageError>
 where
     K: LsmKey,
@@ -4933,6 +5266,9 @@ where
     }
 
     match header.collection_format {
+        MAP_REGION_V2_FORMAT if accept_region_v2 => {
+            map.load_region(payload)?;
+        }
         MAP_REGION_V2_FORMAT => {
             return Err(MapStorageError::UnsupportedRegionFormat {
                 collection_id,
@@ -4979,6 +5315,50 @@ pub(crate) fn collect_map_head_regions<const REGION_SIZE: usize, IO: FlashIo, co
     head_region: u32,
     regions: &mut Vec<u32, CAP>,
 ) -> Result<(), MapStorageError> {
+    // A retired `map_region_v2` head owns only itself. Startup still has to
+    // account for it when a migration transaction replaces it.
+    let header = flash.read_region(head_region, 0, Header::ENCODED_LEN, Header::decode)??;
+    if header.collection_id == collection_id && header.collection_format == MAP_REGION_V2_FORMAT {
+        return push_unique_collected_region(regions, collection_id, head_region, head_region);
+    }
+    visit_map_head_regions::<REGION_SIZE, IO, _>(
+        flash,
+        workspace,
+        metadata,
+        collection_id,
+        head_region,
+        |region| {
+            push_unique_collected_region(regions, collection_id, head_region, region.region_index)
+        },
+    )
+}
+
+/// One committed region reachable from a map manifest head.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MapHeadRegion {
+    pub(crate) region_index: u32,
+    pub(crate) sequence: u64,
+    /// Manifest run index and depth within that run's chain, or `None` for
+    /// the manifest region itself.
+    pub(crate) run: Option<(usize, u32)>,
+    /// Generation the manifest records for the run, or zero for the manifest
+    /// region itself.
+    pub(crate) generation: u64,
+}
+
+/// Visits the manifest named by `head_region` and then every run-chain region
+/// it references, in manifest order and chain order.
+pub(crate) fn visit_map_head_regions<const REGION_SIZE: usize, IO: FlashIo, F>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    metadata: StorageMetadata,
+    collection_id: CollectionId,
+    head_region: u32,
+    mut visit: F,
+) -> Result<(), MapStorageError>
+where
+    F: FnMut(MapHeadRegion) -> Result<(), MapStorageError>,
+{
     let (manifest_region, run_region) = workspace.scan_buffers();
     let (header, payload) =
         read_committed_region::<REGION_SIZE, IO>(flash, metadata, head_region, manifest_region)?;
@@ -4999,7 +5379,12 @@ pub(crate) fn collect_map_head_regions<const REGION_SIZE: usize, IO: FlashIo, co
             actual: header.collection_format,
         });
     }
-    push_unique_collected_region(regions, collection_id, head_region, head_region)?;
+    visit(MapHeadRegion {
+        region_index: head_region,
+        sequence: header.sequence,
+        run: None,
+        generation: 0,
+    })?;
 
     let mut offset = 0usize;
     let run_count = usize::try_from(read_u32(payload, &mut offset)?).map_err(|_| {
@@ -5008,8 +5393,8 @@ pub(crate) fn collect_map_head_regions<const REGION_SIZE: usize, IO: FlashIo, co
             region_index: head_region,
         }
     })?;
-    for _ in 0..run_count {
-        let _generation = read_u64(payload, &mut offset)?;
+    for run in 0..run_count {
+        let generation = read_u64(payload, &mut offset)?;
         let first_region = read_u32(payload, &mut offset)?;
         let region_count = read_u32(payload, &mut offset)?;
         let _approx_state_count = read_u32(payload, &mut offset)?;
@@ -5047,12 +5432,11 @@ pub(crate) fn collect_map_head_regions<const REGION_SIZE: usize, IO: FlashIo, co
         offset = bounds_end;
 
         let mut current_region = Some(first_region);
-        for _ in 0..region_count {
+        for depth in 0..region_count {
             let region_index = current_region.ok_or(MapStorageError::InvalidRun {
                 collection_id,
                 region_index: first_region,
             })?;
-            push_unique_collected_region(regions, collection_id, head_region, region_index)?;
 
             let (run_header, run_payload) = read_committed_region::<REGION_SIZE, IO>(
                 flash,
@@ -5078,6 +5462,18 @@ pub(crate) fn collect_map_head_regions<const REGION_SIZE: usize, IO: FlashIo, co
                     region_index,
                 }
             })?;
+            if view.generation != generation {
+                return Err(MapStorageError::InvalidRun {
+                    collection_id,
+                    region_index,
+                });
+            }
+            visit(MapHeadRegion {
+                region_index,
+                sequence: run_header.sequence,
+                run: Some((run, depth)),
+                generation,
+            })?;
             current_region = view.next_region;
         }
     }
@@ -5085,6 +5481,287 @@ pub(crate) fn collect_map_head_regions<const REGION_SIZE: usize, IO: FlashIo, co
     Ok(())
 }
 
+/// Returns whether a `map_run_v2` payload frames correctly and every entry
+/// reference stays inside the snapshot. Run segments carry no checksum past
+/// the region header, so framing is all a scrub can verify.
+pub(crate) fn run_segment_frames(payload: &[u8]) -> bool {
+    let Ok(view) = parse_run_segment_payload(payload) else {
+        return false;
+    };
+    let Ok((entry_count, ..)) = snapshot_parts(view.snapshot) else {
+        return false;
+    };
+    (0..entry_count).all(|index| snapshot_entry_bytes(view.snapshot, index).is_ok())
+}
+
+/// Copies `target` and every run-chain region above it into fresh regions,
+/// retargets the map head at a rewritten manifest, and frees the old copies.
+///
+/// Returns the number of regions copied, including the manifest.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn relocate_map_head_region<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+    const MAX_CHAIN: usize,
+>(
+    storage: &mut StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    reclaim_source_regions: &mut Vec<u32, REGION_COUNT>,
+    active_collections: &mut Vec<CollectionId, MAX_COLLECTIONS>,
+    reclaim_plan: &mut WalHeadReclaimPlan<MAX_COLLECTIONS>,
+    open_plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+    head_region: u32,
+    target: MapHeadRegion,
+) -> Result<usize, MapStorageError> {
+    let mut chain = Vec::<u32, MAX_CHAIN>::new();
+    if let Some((target_run, target_depth)) = target.run {
+        visit_map_head_regions::<REGION_SIZE, IO, _>(
+            flash,
+            workspace,
+            storage.metadata(),
+            collection_id,
+            head_region,
+            |region| match region.run {
+                Some((run, depth)) if run == target_run && depth <= target_depth => chain
+                    .push(region.region_index)
+                    .map_err(|_| MapStorageError::InvalidRun {
+                        collection_id,
+                        region_index: region.region_index,
+                    }),
+                _ => Ok(()),
+            },
+        )?;
+        if chain.last() != Some(&target.region_index) {
+            return Err(MapStorageError::InvalidRun {
+                collection_id,
+                region_index: target.region_index,
+            });
+        }
+    }
+    let copied = chain
+        .len()
+        .checked_add(1)
+        .ok_or(MapError::SerializationError)?;
+    storage.ensure_foreground_allocation_headroom_for::<REGION_SIZE, REGION_COUNT, IO>(
+        flash,
+        workspace,
+        reclaim_source_regions,
+        active_collections,
+        reclaim_plan,
+        open_plan,
+        u32::try_from(copied).map_err(|_| MapError::SerializationError)?,
+    )?;
+
+    storage.begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+        flash,
+        workspace,
+        collection_id,
+    )?;
+    let result = relocate_map_chain_in_transaction::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+        storage,
+        flash,
+        workspace,
+        reclaim_source_regions,
+        active_collections,
+        reclaim_plan,
+        open_plan,
+        collection_id,
+        head_region,
+        target.run.map(|(run, _)| run),
+        &chain,
+    );
+    match result {
+        Ok(()) => {
+            storage.commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                collection_id,
+            )?;
+            storage.finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                collection_id,
+            )?;
+            Ok(copied)
+        }
+        Err(error) => {
+            let _ = storage.rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                collection_id,
+            );
+            Err(error)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn relocate_map_chain_in_transaction<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &mut StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    reclaim_source_regions: &mut Vec<u32, REGION_COUNT>,
+    active_collections: &mut Vec<CollectionId, MAX_COLLECTIONS>,
+    reclaim_plan: &mut WalHeadReclaimPlan<MAX_COLLECTIONS>,
+    open_plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+    head_region: u32,
+    run: Option<usize>,
+    chain: &[u32],
+) -> Result<(), MapStorageError> {
+    let collection_type = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .and_then(|collection| collection.collection_type())
+        .ok_or(MapStorageError::UnknownCollection(collection_id))?;
+    let payload_len = storage.committed_payload_capacity::<REGION_SIZE>()?;
+
+    // The deepest region keeps its own successor, so the chain is copied
+    // bottom-up and each copy links to the copy written just before it.
+    let mut copied_next = None;
+    for old_region in chain.iter().rev().copied() {
+        let new_region = storage.reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+            reclaim_source_regions,
+            active_collections,
+            reclaim_plan,
+            open_plan,
+        )?;
+        copy_committed_payload_to_workspace::<REGION_SIZE, IO>(
+            flash,
+            workspace,
+            old_region,
+            payload_len,
+        )?;
+        if let Some(next_region) = copied_next {
+            let (payload, _) = workspace.encode_buffers();
+            let mut offset = RUN_GENERATION_SIZE;
+            write_u32(payload, &mut offset, next_region)?;
+        }
+        let new_region = storage
+            .write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                new_region,
+                collection_id,
+                MAP_RUN_V2_FORMAT,
+                payload_len,
+            )?;
+        copied_next = Some(new_region);
+    }
+
+    let manifest_region = storage.reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+        flash,
+        workspace,
+        collection_id,
+        reclaim_source_regions,
+        active_collections,
+        reclaim_plan,
+        open_plan,
+    )?;
+    copy_committed_payload_to_workspace::<REGION_SIZE, IO>(
+        flash,
+        workspace,
+        head_region,
+        payload_len,
+    )?;
+    if let (Some(run), Some(first_region)) = (run, copied_next) {
+        let (payload, _) = workspace.encode_buffers();
+        let mut offset =
+            manifest_run_first_region_offset(payload, collection_id, head_region, run)?;
+        write_u32(payload, &mut offset, first_region)?;
+    }
+    let manifest_region = storage
+        .write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            manifest_region,
+            collection_id,
+            MAP_MANIFEST_V2_FORMAT,
+            payload_len,
+        )?;
+    storage.append_head_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+        flash,
+        workspace,
+        collection_id,
+        collection_type,
+        manifest_region,
+    )?;
+    for old_region in core::iter::once(head_region).chain(chain.iter().copied()) {
+        storage.append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+            old_region,
+        )?;
+    }
+    Ok(())
+}
+
+fn copy_committed_payload_to_workspace<const REGION_SIZE: usize, IO: FlashIo>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    region_index: u32,
+    payload_len: usize,
+) -> Result<(), MapStorageError> {
+    let (payload, _) = workspace.encode_buffers();
+    let payload = payload
+        .get_mut(..payload_len)
+        .ok_or(MapError::BufferTooSmall)?;
+    flash.read_region(region_index, Header::ENCODED_LEN, payload_len, |bytes| {
+        payload.copy_from_slice(bytes);
+    })?;
+    Ok(())
+}
+
+fn manifest_run_first_region_offset(
+    manifest_payload: &[u8],
+    collection_id: CollectionId,
+    manifest_region: u32,
+    run: usize,
+) -> Result<usize, MapStorageError> {
+    let invalid = || MapStorageError::InvalidManifest {
+        collection_id,
+        region_index: manifest_region,
+    };
+    let mut offset = 0usize;
+    let run_count =
+        usize::try_from(read_u32(manifest_payload, &mut offset)?).map_err(|_| invalid())?;
+    if run >= run_count {
+        return Err(invalid());
+    }
+    for _ in 0..run {
+        let _generation = read_u64(manifest_payload, &mut offset)?;
+        let _first_region = read_u32(manifest_payload, &mut offset)?;
+        let _region_count = read_u32(manifest_payload, &mut offset)?;
+        let _approx_state_count = read_u32(manifest_payload, &mut offset)?;
+        let lower_key_len =
+            usize::try_from(read_u32(manifest_payload, &mut offset)?).map_err(|_| invalid())?;
+        let upper_key_len =
+            usize::try_from(read_u32(manifest_payload, &mut offset)?).map_err(|_| invalid())?;
+        offset = offset
+            .checked_add(lower_key_len)
+            .and_then(|end| end.checked_add(upper_key_len))
+            .ok_or_else(invalid)?;
+    }
+    offset
+        .checked_add(size_of::<u64>())
+        .filter(|first_region_offset| *first_region_offset < manifest_payload.len())
+        .ok_or_else(invalid)
+}
+
 #[cfg(test)]
 pub(crate) fn map_head_region_references_region<const REGION_SIZE: usize, IO: FlashIo>(
     flash: &mut IO,
@@ -701,7 +701,7 @@ fn requirement_perf_metrics_custom_key_fallback_records_key_decodes() {
 #[test]
 fn requirement_v2_entry_layout_validates_headers_and_lengths() {
     let mut encoded = [0u8; 32];
-    let used = encode_entry_into(&5u16, Some(&70u16), &mut encoded).unwrap();
+    let used = encode_entry_into(&5u16, Some(&70u16), false, &mut encoded).unwrap();
     assert_eq!(encoded[0], ENTRY_KIND_SET);
     let parsed = parse_encoded_entry(&encoded[..used]).unwrap();
     assert_eq!(u16::decode_key(parsed.key).unwrap(), 5);
@@ -711,7 +711,7 @@ fn requirement_v2_entry_layout_validates_headers_and_lengths() {
         LookupResult::Set(70)
     );
 
-    let delete_used = encode_entry_into::<u16, u16>(&5, None, &mut encoded).unwrap();
+    let delete_used = encode_entry_into::<u16, u16>(&5, None, false, &mut encoded).unwrap();
     assert_eq!(encoded[0], ENTRY_KIND_DELETE);
     assert!(matches!(
         encoded_entry_lookup_value::<u16>(&encoded[..delete_used]).unwrap(),
@@ -752,7 +752,7 @@ fn requirement_v2_entry_layout_validates_headers_and_lengths() {
     ));
 
     let mut exact_header = [0u8; ENTRY_HEADER_SIZE];
-    let used = encode_entry_into::<(), u16>(&(), None, &mut exact_header).unwrap();
+    let used = encode_entry_into::<(), u16>(&(), None, false, &mut exact_header).unwrap();
     assert_eq!(used, ENTRY_HEADER_SIZE);
     assert_eq!(
         parse_encoded_entry(&exact_header).unwrap().key,
@@ -761,10 +761,30 @@ fn requirement_v2_entry_layout_validates_headers_and_lengths() {
 
     let mut too_small_for_key = [0u8; ENTRY_HEADER_SIZE];
     assert!(matches!(
-        encode_entry_into::<u16, u16>(&5, None, &mut too_small_for_key),
+        encode_entry_into::<u16, u16>(&5, None, false, &mut too_small_for_key),
         Err(MapError::BufferTooSmall)
     ));
 
+    let used = encode_entry_into(&5u16, Some(&()), true, &mut encoded).unwrap();
+    assert_eq!(&encoded[..used], &[ENTRY_KIND_MEMBER, 2, 0, 0, 0, 0, 5]);
+    let parsed = parse_encoded_entry(&encoded[..used]).unwrap();
+    assert_eq!(parsed.kind, ENTRY_KIND_SET);
+    assert_eq!(parsed.value, Some(&[] as &[u8]));
+    let used = encode_entry_into::<u16, ()>(&5, None, true, &mut encoded).unwrap();
+    assert_eq!(&encoded[..used], &[ENTRY_KIND_REMOVED, 2, 0, 0, 0, 0, 5]);
+    assert_eq!(
+        parse_encoded_entry(&encoded[..used]).unwrap().kind,
+        ENTRY_KIND_DELETE
+    );
+    assert!(matches!(
+        encode_entry_into(&5u16, Some(&70u16), true, &mut encoded),
+        Err(MapError::SerializationError)
+    ));
+    assert!(matches!(
+        parse_encoded_entry(&[ENTRY_KIND_MEMBER, 2, 0, 0, 0, 0]),
+        Err(MapError::SerializationError)
+    ));
+
     let mut frontier_buffer = [0u8; 512];
     let mut frontier = MapFrontier::<i32, i32, 8>::new(
         CollectionId(91),
@@ -1023,7 +1043,8 @@ fn requirement_bool_key_encoding_is_single_byte_and_ordered() {
 
     let original_key = OwnedKey(vec![3, 1, 4]);
     let mut encoded_entry = [0u8; 64];
-    let encoded_len = encode_entry_into(&original_key, Some(&159u16), &mut encoded_entry).unwrap();
+    let encoded_len =
+        encode_entry_into(&original_key, Some(&159u16), false, &mut encoded_entry).unwrap();
     let decoded_entry: Entry<OwnedKey, u16> =
         encoded_entry_to_entry(&encoded_entry[..encoded_len]).unwrap();
     assert_eq!(decoded_entry.key, original_key);
@@ -1143,7 +1164,7 @@ fn requirement_encoded_key_comparison_reports_equal_less_greater_and_invalid_byt
 #[test]
 fn requirement_metered_entry_helpers_preserve_results_and_count_work() {
     let mut encoded = [0u8; 32];
-    let used = encode_entry_into(&5u16, Some(&70u16), &mut encoded).unwrap();
+    let used = encode_entry_into(&5u16, Some(&70u16), false, &mut encoded).unwrap();
     let mut metrics = StoragePerfMetrics::default();
     assert_eq!(
         encoded_entry_lookup_value_metered::<u16>(&encoded[..used], Some(&mut metrics)).unwrap(),
@@ -1151,7 +1172,7 @@ fn requirement_metered_entry_helpers_preserve_results_and_count_work() {
     );
     assert_eq!(metrics.value_decodes, 1);
 
-    let delete_used = encode_entry_into::<u16, u16>(&5, None, &mut encoded).unwrap();
+    let delete_used = encode_entry_into::<u16, u16>(&5, None, false, &mut encoded).unwrap();
     assert_eq!(
         encoded_entry_lookup_value_metered::<u16>(&encoded[..delete_used], Some(&mut metrics))
             .unwrap(),
@@ -1977,9 +1998,9 @@ fn requirement_search_helpers_cover_even_windows_and_insert_positions() {
 #[test]
 fn requirement_load_snapshot_accepts_reversed_adjacent_entry_storage() {
     let mut first_bytes = [0u8; 32];
-    let first_len = encode_entry_into(&1i32, Some(&10i32), &mut first_bytes).unwrap();
+    let first_len = encode_entry_into(&1i32, Some(&10i32), false, &mut first_bytes).unwrap();
     let mut second_bytes = [0u8; 32];
-    let second_len = encode_entry_into(&2i32, Some(&20i32), &mut second_bytes).unwrap();
+    let second_len = encode_entry_into(&2i32, Some(&20i32), false, &mut second_bytes).unwrap();
 
     let entry_bytes_len = first_len + second_len;
     let snapshot_len = SNAPSHOT_HEADER_SIZE + entry_bytes_len + 2 * ENTRY_REF_SIZE;
@@ -2106,12 +2127,12 @@ fn assert_snapshot_entry_bytes_reject_refs_beyond_entry_bytes() {
 fn assert_snapshot_decode_rejects_overlapping_nested_entry_refs() {
     const NESTED_ENTRY_LEN: usize = ENTRY_HEADER_SIZE + size_of::<i32>() + size_of::<u8>();
     let mut nested_entry = [0u8; NESTED_ENTRY_LEN];
-    let nested_len = encode_entry_into(&2i32, Some(&7u8), &mut nested_entry).unwrap();
+    let nested_len = encode_entry_into(&2i32, Some(&7u8), false, &mut nested_entry).unwrap();
     assert_eq!(nested_len, NESTED_ENTRY_LEN);
 
     let outer_value = nested_entry;
     let mut outer_entry = [0u8; 64];
-    let outer_len = encode_entry_into(&1i32, Some(&outer_value), &mut outer_entry).unwrap();
+    let outer_len = encode_entry_into(&1i32, Some(&outer_value), false, &mut outer_entry).unwrap();
     assert_eq!(
         outer_len,
         ENTRY_HEADER_SIZE + size_of::<i32>() + NESTED_ENTRY_LEN
@@ -3940,7 +3961,8 @@ fn requirement_update_payload_undo_restores_found_update_and_delete() {
     ));
 
     let mut encoded_entry = [0u8; 64];
-    let exact_update_entry_len = encode_entry_into(&1, Some(&99), &mut encoded_entry).unwrap();
+    let exact_update_entry_len =
+        encode_entry_into(&1, Some(&99), false, &mut encoded_entry).unwrap();
     let mut exact_update_scratch = vec![0u8; exact_update_entry_len + ENTRY_REF_SIZE];
     let undo = map
         .set_worker_with_undo(&1, Some(&99), exact_update_scratch.as_mut_slice())
@@ -5211,6 +5233,75 @@ fn assert_storage_region_flush_restores_map_basis() {
     );
 }
 
+//= spec/map.md#merge-and-frontier-rules
+//= type=test
+//# `MAP-MERGE-004` `LsmMap::range` MUST visit every visible key in the
+//# requested bounds exactly once, in ascending key order, applying the same
+//# newest-wins visibility as `get` across the frontier and retained runs so
+//# that keys whose newest entry is a delete are skipped.
+#[test]
+fn requirement_lsm_map_range_merges_frontier_and_runs() {
+    let mut flash = MockFlash::<512, 32, 16384>::new(0xff);
+    let mut storage = Storage::<_, 512, 32>::format(
+        &mut flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let mut map = LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+
+    for key in 0..80u16 {
+        if map.set(&mut storage, key, key * 10).unwrap() {
+            map.compact(&mut storage).unwrap();
+        }
+    }
+    for key in (0..80u16).step_by(7) {
+        if map.delete(&mut storage, key).unwrap() {
+            map.compact(&mut storage).unwrap();
+        }
+    }
+    map.set(&mut storage, 14, 1_400).unwrap();
+    map.set(&mut storage, 33, 3_300).unwrap();
+    assert!(!map.memory.frontier.runs.is_empty());
+
+    let mut visited = Vec::new();
+    map.range(&mut storage, 10..=40, |key, value| {
+        visited.push((key, value));
+        Ok(())
+    })
+    .unwrap();
+    let expected: Vec<(u16, u16)> = (10..=40u16)
+        .filter(|key| key % 7 != 0 || *key == 14)
+        .map(|key| match key {
+            14 => (14, 1_400),
+            33 => (33, 3_300),
+            key => (key, key * 10),
+        })
+        .collect();
+    assert_eq!(visited, expected);
+
+    let mut all = Vec::new();
+    map.range(&mut storage, .., |key, _| {
+        all.push(key);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(all.len(), 80 - 12 + 1);
+    assert!(all.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let mut tail = Vec::new();
+    map.range(
+        &mut storage,
+        (core::ops::Bound::Excluded(75), core::ops::Bound::Unbounded),
+        |key, _| {
+            tail.push(key);
+            Ok(())
+        },
+    )
+    .unwrap();
+    assert_eq!(tail, [76, 78, 79]);
+}
+
 //= spec/ring/03-collection-lifecycle.md#collection-head-state-machine
 //= type=test
 //# `RING-FORMAT-005` Every user collection MUST remain log-structured:
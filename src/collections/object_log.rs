@@ -2,6 +2,7 @@ use core::{fmt, mem::size_of};
 
 use crc::{Crc, CRC_32_ISCSI};
 use heapless::Vec;
+use sha2::{Digest, Sha256};
 
 use crate::disk::Header;
 use crate::flash_io::FlashIo;
@@ -37,6 +38,8 @@ const RECORD_HEADER_LEN: usize = size_of::<u8>() + size_of::<u32>() + size_of::<
 const RECORD_INLINE_OBJECT: u8 = 0x01;
 const RECORD_OBJECT_CHUNK: u8 = 0x02;
 const RECORD_LARGE_RECORD_ENTRY: u8 = 0x03;
+const RECORD_CHAIN_SEAL: u8 = 0x04;
+const RECORD_TIMESTAMP: u8 = 0x05;
 const AUX_POINTER_ENCODED_LEN: usize = size_of::<u32>();
 const OBJECT_CHUNK_FIXED_BODY_LEN: usize = size_of::<u64>() + size_of::<u32>() + size_of::<u32>();
 const AUX_CHUNK_FIXED_LEN: usize = size_of::<u8>() + OBJECT_CHUNK_FIXED_BODY_LEN;
@@ -52,9 +55,24 @@ const UPDATE_APPEND: u8 = 1;
 const UPDATE_TRUNCATE_HEAD: u8 = 2;
 const UPDATE_SET_LOG_METADATA: u8 = 3;
 const UPDATE_MATERIALIZED_REGION: u8 = 4;
+const UPDATE_APPEND_BATCH: u8 = 5;
+const UPDATE_ENABLE_HASH_CHAIN: u8 = 6;
+const UPDATE_SET_RETENTION: u8 = 7;
+
+const SNAPSHOT_FLAG_HASH_CHAIN: u16 = 0x0001;
+const SNAPSHOT_FLAG_RETENTION: u16 = 0x0002;
+/// Length of one [`ObjectLog`] hash-chain digest.
+pub const OBJECT_LOG_CHAIN_DIGEST_LEN: usize = 32;
+const CHAIN_SEAL_RECORD_LEN: usize = RECORD_HEADER_LEN + OBJECT_LOG_CHAIN_DIGEST_LEN;
+const CHAIN_GENESIS_DIGEST: [u8; OBJECT_LOG_CHAIN_DIGEST_LEN] = [0; OBJECT_LOG_CHAIN_DIGEST_LEN];
+
+const TIMESTAMP_RECORD_LEN: usize = RECORD_HEADER_LEN + size_of::<u64>();
 
 /// Stable object address returned by [`ObjectLog::append`].
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// Handles compare by the sequence of their data region and their offset in
+/// it; the region index is where that data lived when the handle was made.
+#[derive(Clone, Copy)]
 pub struct ObjectLogHandle {
     region_index: u32,
     sequence: u64,
@@ -67,6 +85,26 @@ impl fmt::Debug for ObjectLogHandle {
     }
 }
 
+impl PartialEq for ObjectLogHandle {
+    fn eq(&self, other: &Self) -> bool {
+        (self.sequence, self.offset) == (other.sequence, other.offset)
+    }
+}
+
+impl Eq for ObjectLogHandle {}
+
+impl PartialOrd for ObjectLogHandle {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ObjectLogHandle {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.sequence, self.offset).cmp(&(other.sequence, other.offset))
+    }
+}
+
 impl ObjectLogHandle {
     /// Creates a handle from its checked fields.
     pub(crate) const fn new(region_index: u32, sequence: u64, offset: u32) -> Self {
@@ -145,6 +183,7 @@ struct LargeTailAppendPlan {
     tail_start: usize,
     tail_logical_len: u32,
     first_aux: AuxRegionPointer,
+    seal: Option<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
 }
 
 #[derive(Clone, Copy)]
@@ -189,6 +228,50 @@ enum AppendVisibility {
     Committed,
 }
 
+/// Automatic retention bound for an [`ObjectLog`].
+///
+/// Retention always keeps the newest committed object and discards only a
+/// prefix of the live log through [`ObjectLog::truncate_before`] semantics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectLogRetention {
+    /// Keep every object until the caller truncates explicitly.
+    Unbounded,
+    /// Keep at most this many live objects.
+    MaxObjects(u64),
+    /// Keep at most this many live object payload bytes.
+    MaxBytes(u64),
+    /// Keep at most this many live object-log data regions.
+    MaxRegions(usize),
+    /// Discard objects before the first object whose record-header timestamp,
+    /// written by [`ObjectLog::append_timestamped`], is at least this value.
+    MinTimestamp(u64),
+}
+
+/// When an [`ObjectLog`] applies its configured [`ObjectLogRetention`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectLogRetentionEnforcement {
+    /// Only [`ObjectLog::enforce_retention`] applies the policy.
+    Explicit,
+    /// Every standalone append applies the policy after the appended object
+    /// is durable. A failure leaves the object committed and is reported by
+    /// [`ObjectLog::take_retention_error`].
+    OnAppend,
+}
+
+/// Prefix that a retention pass discards, ending before `boundary`.
+struct RetentionCut {
+    boundary: ObjectLogHandle,
+    objects: u64,
+    bytes: u64,
+}
+
+/// Live object count and payload bytes tracked for retention.
+#[derive(Clone, Copy)]
+struct ObjectLogLiveTotals {
+    objects: u64,
+    bytes: u64,
+}
+
 #[derive(Clone, Copy)]
 struct ObjectLogReplayTransaction {
     transaction_log_id: u32,
@@ -209,6 +292,16 @@ pub struct ObjectLogMemory<
     log_metadata: [u8; LOG_METADATA_MAX],
     log_metadata_len: usize,
     next_sequence: u64,
+    retention: ObjectLogRetention,
+    retention_enforcement: ObjectLogRetentionEnforcement,
+    retention_error: Option<ObjectLogError>,
+    live_totals: Option<ObjectLogLiveTotals>,
+    append_timestamp: Option<u64>,
+    hash_chain: bool,
+    chain_anchor: [u8; OBJECT_LOG_CHAIN_DIGEST_LEN],
+    chain_committed_head: [u8; OBJECT_LOG_CHAIN_DIGEST_LEN],
+    chain_planned_head: [u8; OBJECT_LOG_CHAIN_DIGEST_LEN],
+    relocation_epoch: u64,
 }
 
 impl<const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADATA_MAX: usize>
@@ -224,6 +317,16 @@ impl<const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADATA_MAX:
             log_metadata: [0; LOG_METADATA_MAX],
             log_metadata_len: 0,
             next_sequence: 0,
+            retention: ObjectLogRetention::Unbounded,
+            retention_enforcement: ObjectLogRetentionEnforcement::Explicit,
+            retention_error: None,
+            live_totals: None,
+            append_timestamp: None,
+            hash_chain: false,
+            chain_anchor: CHAIN_GENESIS_DIGEST,
+            chain_committed_head: CHAIN_GENESIS_DIGEST,
+            chain_planned_head: CHAIN_GENESIS_DIGEST,
+            relocation_epoch: 0,
         }
     }
 
@@ -235,6 +338,15 @@ impl<const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADATA_MAX:
         self.log_metadata.fill(0);
         self.log_metadata_len = 0;
         self.next_sequence = 0;
+        self.retention = ObjectLogRetention::Unbounded;
+        self.retention_enforcement = ObjectLogRetentionEnforcement::Explicit;
+        self.retention_error = None;
+        self.live_totals = None;
+        self.append_timestamp = None;
+        self.hash_chain = false;
+        self.chain_anchor = CHAIN_GENESIS_DIGEST;
+        self.chain_committed_head = CHAIN_GENESIS_DIGEST;
+        self.chain_planned_head = CHAIN_GENESIS_DIGEST;
     }
 }
 
@@ -286,6 +398,28 @@ impl<
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         bytes: &[u8],
         large_scratch: &mut [u8],
+    ) -> Result<ObjectLogHandle, ObjectLogError> {
+        self.append_with_timestamp(storage, None, bytes, large_scratch)
+    }
+
+    /// Appends an object whose record header carries `timestamp` to this
+    /// transaction and returns its planned stable handle.
+    pub fn append_timestamped<'db, 'storage_mem, IO: FlashIo, const MAX_COLLECTIONS: usize>(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        timestamp: u64,
+        bytes: &[u8],
+        large_scratch: &mut [u8],
+    ) -> Result<ObjectLogHandle, ObjectLogError> {
+        self.append_with_timestamp(storage, Some(timestamp), bytes, large_scratch)
+    }
+
+    fn append_with_timestamp<'db, 'storage_mem, IO: FlashIo, const MAX_COLLECTIONS: usize>(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        timestamp: Option<u64>,
+        bytes: &[u8],
+        large_scratch: &mut [u8],
     ) -> Result<ObjectLogHandle, ObjectLogError> {
         self.writer
             .require_collection(self.log.collection_id)
@@ -293,12 +427,14 @@ impl<
         storage.enter_mode(StorageMode::UpdatingCollection(
             CollectionUpdateMode::Running,
         ))?;
+        self.log.memory.append_timestamp = timestamp;
         let result = self.log.append_transactional(
             storage,
             bytes,
             large_scratch,
             &mut self.writer.memory.object_log_allocated_regions,
         );
+        self.log.memory.append_timestamp = None;
         storage.finish_mode();
         if result.is_err() {
             let _ = self.rollback_open(storage);
@@ -339,6 +475,7 @@ impl<
         let result: Result<(), StorageRuntimeError> = (|| {
             storage.commit_transaction_marker(self.log.collection_id)?;
             self.log.commit_staged_appends();
+            self.log.memory.live_totals = None;
             self.log.clear_append_checkpoint();
             storage.finish_transaction_marker(self.log.collection_id)?;
             Ok(())
@@ -386,20 +523,63 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         memory: &'mem mut ObjectLogMemory<REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
         log_metadata: &[u8],
+    ) -> Result<Self, ObjectLogError> {
+        Self::create(storage, memory, log_metadata, false)
+    }
+
+    /// Creates a new object-log collection whose objects form a SHA-256
+    /// hash chain.
+    ///
+    /// Every appended object is sealed with the digest of the previous seal,
+    /// its length, and its bytes, so [`ObjectLog::verify_chain`] detects a
+    /// changed or removed object anywhere in the live log.
+    pub fn new_hash_chained<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut ObjectLogMemory<REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
+        log_metadata: &[u8],
+    ) -> Result<Self, ObjectLogError> {
+        Self::create(storage, memory, log_metadata, true)
+    }
+
+    fn create<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut ObjectLogMemory<REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
+        log_metadata: &[u8],
+        hash_chain: bool,
     ) -> Result<Self, ObjectLogError> {
         validate_log_metadata_len::<LOG_METADATA_MAX>(log_metadata.len())?;
         let collection_id = storage.allocate_collection_id()?;
         memory.clear();
+        memory.relocation_epoch = storage.runtime().relocation_epoch();
 
         storage.append_new_collection(collection_id, CollectionType::OBJECT_LOG_CODE)?;
         let mut update = [0u8; REGION_SIZE];
         let used = encode_set_log_metadata_update(log_metadata, &mut update)?;
         storage.append_update(collection_id, &update[..used])?;
+        if hash_chain {
+            let used = write_u8(&mut update, 0, UPDATE_ENABLE_HASH_CHAIN)?;
+            storage.append_update(collection_id, &update[..used])?;
+        }
         let mut log = Self {
             collection_id,
             memory,
         };
         log.apply_log_metadata(log_metadata)?;
+        if hash_chain {
+            log.apply_enable_hash_chain()?;
+        }
         log.validate_open_state(storage)?;
         Ok(log)
     }
@@ -421,6 +601,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             collection_id,
         )?;
         memory.clear();
+        memory.relocation_epoch = storage.runtime().relocation_epoch();
         replay_object_log::<
             IO,
             REGION_SIZE,
@@ -443,6 +624,10 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
     }
 
     /// Appends an object and returns its stable handle.
+    ///
+    /// Under [`ObjectLogRetentionEnforcement::OnAppend`] a retention failure
+    /// does not fail the append; it is kept for
+    /// [`ObjectLog::take_retention_error`].
     pub fn append<
         'db,
         'storage_mem,
@@ -455,10 +640,90 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         bytes: &[u8],
         large_scratch: &mut [u8],
     ) -> Result<ObjectLogHandle, ObjectLogError> {
+        self.refresh(storage)?;
         storage.enter_mode(StorageMode::UpdatingCollection(
             CollectionUpdateMode::Running,
         ))?;
-        let result = self.append_inner(storage, bytes, large_scratch);
+        let result = self.append_with_retention(storage, None, bytes, large_scratch);
+        storage.finish_mode();
+        result
+    }
+
+    /// Appends an object whose record header carries `timestamp` and returns
+    /// its stable handle.
+    ///
+    /// [`ObjectLogRetention::MinTimestamp`] reads this header timestamp; the
+    /// object bytes are stored unchanged.
+    pub fn append_timestamped<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        timestamp: u64,
+        bytes: &[u8],
+        large_scratch: &mut [u8],
+    ) -> Result<ObjectLogHandle, ObjectLogError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.append_with_retention(storage, Some(timestamp), bytes, large_scratch);
+        storage.finish_mode();
+        result
+    }
+
+    /// Appends small objects with one WAL update and returns their handles in
+    /// `handles`, in object order.
+    ///
+    /// The batch is all-or-nothing: every object must be small enough for an
+    /// inline record and the whole batch must fit in one ordinary data region.
+    pub fn append_batch<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+        const MAX_HANDLES: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        objects: &[&[u8]],
+        handles: &mut Vec<ObjectLogHandle, MAX_HANDLES>,
+    ) -> Result<(), ObjectLogError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.append_batch_with_retention(storage, None, objects, handles);
+        storage.finish_mode();
+        result
+    }
+
+    /// Appends a batch like [`ObjectLog::append_batch`] with `timestamp` in
+    /// the record header of every batched object.
+    pub fn append_batch_timestamped<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+        const MAX_HANDLES: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        timestamp: u64,
+        objects: &[&[u8]],
+        handles: &mut Vec<ObjectLogHandle, MAX_HANDLES>,
+    ) -> Result<(), ObjectLogError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.append_batch_with_retention(storage, Some(timestamp), objects, handles);
         storage.finish_mode();
         result
     }
@@ -474,6 +739,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         &mut self,
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
     ) -> Result<(), ObjectLogError> {
+        self.refresh(storage)?;
         storage.enter_mode(StorageMode::FlushingCollection(
             crate::mode::CollectionFlushMode::CommitRegion,
         ))?;
@@ -492,7 +758,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         const REGION_COUNT: usize,
         const MAX_COLLECTIONS: usize,
     >(
-        &self,
+        &mut self,
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         handle: ObjectLogHandle,
         scratch: &mut [u8],
@@ -501,8 +767,9 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
     where
         F: FnOnce(&[u8]) -> R,
     {
+        self.refresh(storage)?;
         storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
-        let result = self.get_inner(storage, handle, scratch, read);
+        let result = self.get_inner(storage, self.resolve_handle(handle), scratch, read);
         storage.finish_mode();
         result
     }
@@ -515,12 +782,13 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         const REGION_COUNT: usize,
         const MAX_COLLECTIONS: usize,
     >(
-        &self,
+        &mut self,
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         handle: ObjectLogHandle,
     ) -> Result<u64, ObjectLogError> {
+        self.refresh(storage)?;
         storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
-        let result = self.get_object_len_inner(storage, handle);
+        let result = self.get_object_len_inner(storage, self.resolve_handle(handle));
         storage.finish_mode();
         result
     }
@@ -535,7 +803,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         const REGION_COUNT: usize,
         const MAX_COLLECTIONS: usize,
     >(
-        &self,
+        &mut self,
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         handle: ObjectLogHandle,
         offset: u64,
@@ -546,7 +814,9 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
     where
         F: FnOnce(&[u8]) -> R,
     {
+        self.refresh(storage)?;
         storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let handle = self.resolve_handle(handle);
         let result = self.get_range_inner(storage, handle, offset, len, scratch, read);
         storage.finish_mode();
         result
@@ -564,11 +834,13 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         handle: ObjectLogHandle,
     ) -> Result<(), ObjectLogError> {
+        self.refresh(storage)?;
         storage.enter_mode(StorageMode::UpdatingCollection(
             CollectionUpdateMode::Running,
         ))?;
-        let result = self.truncate_before_inner(storage, handle);
+        let result = self.truncate_before_inner(storage, self.resolve_handle(handle));
         storage.finish_mode();
+        self.memory.live_totals = None;
         result
     }
 
@@ -630,17 +902,40 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         const REGION_COUNT: usize,
         const MAX_COLLECTIONS: usize,
     >(
-        &self,
+        &mut self,
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         handle: ObjectLogHandle,
     ) -> Result<Option<ObjectLogHandle>, ObjectLogError> {
+        self.refresh(storage)?;
         storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
-        let result = self.next_handle_inner(storage, handle);
+        let result = self.next_handle_inner(storage, self.resolve_handle(handle));
         storage.finish_mode();
         result
     }
 
-    fn append_inner<
+    /// Returns whether this log was created with
+    /// [`ObjectLog::new_hash_chained`].
+    pub fn is_hash_chained(&self) -> bool {
+        self.memory.hash_chain
+    }
+
+    /// Returns the chain digest sealed by the newest committed object.
+    ///
+    /// An empty hash-chained log reports the all-zero genesis digest. Logs
+    /// without a hash chain return `None`.
+    pub fn head_digest(&self) -> Option<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]> {
+        self.memory
+            .hash_chain
+            .then_some(self.memory.chain_committed_head)
+    }
+
+    /// Recomputes the hash chain over the live objects from `from` through
+    /// `to` inclusive.
+    ///
+    /// Object bytes are streamed through `scratch`, which may be smaller than
+    /// the objects. When `to` is the newest committed object, its seal must
+    /// also match [`ObjectLog::head_digest`].
+    pub fn verify_chain<
         'db,
         'storage_mem,
         IO: FlashIo,
@@ -649,34 +944,759 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
     >(
         &mut self,
         storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        from: ObjectLogHandle,
+        to: ObjectLogHandle,
+        scratch: &mut [u8],
+    ) -> Result<(), ObjectLogError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let (from, to) = (self.resolve_handle(from), self.resolve_handle(to));
+        let result = self.verify_chain_inner(storage, from, to, scratch);
+        storage.finish_mode();
+        result
+    }
+
+    /// Configures automatic retention for this log.
+    ///
+    /// The policy is recorded by a durable update and carried in snapshots,
+    /// so opening the collection again restores it.
+    pub fn set_retention<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        retention: ObjectLogRetention,
+        enforcement: ObjectLogRetentionEnforcement,
+    ) -> Result<(), ObjectLogError> {
+        self.refresh(storage)?;
+        validate_retention(retention)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result: Result<(), ObjectLogError> = (|| {
+            let used = encode_set_retention_update(
+                retention,
+                enforcement,
+                &mut storage.memory.payload_scratch,
+            )?;
+            storage
+                .memory
+                .state
+                .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    self.collection_id,
+                    &storage.memory.payload_scratch[..used],
+                )?;
+            Ok(())
+        })();
+        storage.finish_mode();
+        result?;
+        self.memory.retention = retention;
+        self.memory.retention_enforcement = enforcement;
+        Ok(())
+    }
+
+    /// Returns the configured retention policy and enforcement mode.
+    pub fn retention(&self) -> (ObjectLogRetention, ObjectLogRetentionEnforcement) {
+        (self.memory.retention, self.memory.retention_enforcement)
+    }
+
+    /// Takes the error from the last append-time retention pass that failed.
+    ///
+    /// The appended object stays committed; the prefix that retention would
+    /// have discarded stays live until a later pass succeeds.
+    pub fn take_retention_error(&mut self) -> Option<ObjectLogError> {
+        self.memory.retention_error.take()
+    }
+
+    /// Truncates the committed log prefix that falls outside the configured
+    /// retention policy and returns whether anything was discarded.
+    pub fn enforce_retention<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<bool, ObjectLogError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.enforce_retention_inner(storage);
+        storage.finish_mode();
+        result
+    }
+
+    /// Replays the log again when a wear-leveling pass moved one of its data
+    /// regions since this handle last replayed.
+    ///
+    /// A pending append-time retention error survives the replay.
+    fn refresh<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), ObjectLogError> {
+        let epoch = storage.runtime().relocation_epoch();
+        if self.memory.relocation_epoch == epoch {
+            return Ok(());
+        }
+        let retention_error = self.memory.retention_error.take();
+        self.memory.clear();
+        replay_object_log::<
+            IO,
+            REGION_SIZE,
+            REGION_COUNT,
+            MAX_COLLECTIONS,
+            MAX_REGIONS,
+            LOG_METADATA_MAX,
+        >(storage, self.collection_id, self.memory)?;
+        self.validate_open_state(storage)?;
+        self.memory.retention_error = retention_error;
+        self.memory.relocation_epoch = epoch;
+        Ok(())
+    }
+
+    /// Rewrites `handle` to name the region that now holds its sequence.
+    ///
+    /// A data region keeps its sequence when wear leveling moves it, so a
+    /// handle returned before the move still finds its object.
+    fn resolve_handle(&self, handle: ObjectLogHandle) -> ObjectLogHandle {
+        self.memory
+            .regions
+            .iter()
+            .find(|region| region.sequence == handle.sequence)
+            .map_or(handle, |region| {
+                ObjectLogHandle::new(region.region_index, handle.sequence, handle.offset)
+            })
+    }
+
+    fn append_with_retention<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        timestamp: Option<u64>,
         bytes: &[u8],
         large_scratch: &mut [u8],
     ) -> Result<ObjectLogHandle, ObjectLogError> {
-        if self.object_requires_large_record(storage.metadata(), bytes.len())? {
-            return self.append_in_transaction(storage, bytes, large_scratch);
+        if timestamp.is_none()
+            && matches!(self.memory.retention, ObjectLogRetention::MinTimestamp(_))
+        {
+            return Err(ObjectLogError::MissingRetentionTimestamp);
         }
+        self.memory.append_timestamp = timestamp;
+        let appended = self.append_inner(storage, bytes, large_scratch);
+        self.memory.append_timestamp = None;
+        let handle = appended?;
+        self.note_live_append(1, bytes.len());
+        self.enforce_retention_after_append(storage);
+        Ok(handle)
+    }
 
-        let record_len = inline_record_len(bytes.len())?;
-        let payload_capacity = committed_payload_capacity::<REGION_SIZE>(storage.metadata())?;
-        if record_len
-            > empty_region_record_capacity(payload_capacity, self.memory.log_metadata_len)?
+    fn append_batch_with_retention<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+        const MAX_HANDLES: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        timestamp: Option<u64>,
+        objects: &[&[u8]],
+        handles: &mut Vec<ObjectLogHandle, MAX_HANDLES>,
+    ) -> Result<(), ObjectLogError> {
+        handles.clear();
+        if timestamp.is_none()
+            && matches!(self.memory.retention, ObjectLogRetention::MinTimestamp(_))
         {
-            return self.append_in_transaction(storage, bytes, large_scratch);
+            return Err(ObjectLogError::MissingRetentionTimestamp);
+        }
+        self.memory.append_timestamp = timestamp;
+        let appended = self.append_batch_inner(storage, objects, handles);
+        self.memory.append_timestamp = None;
+        appended?;
+        if handles.is_empty() {
+            return Ok(());
         }
+        self.note_live_append(objects.len(), objects.iter().map(|bytes| bytes.len()).sum());
+        self.enforce_retention_after_append(storage);
+        Ok(())
+    }
 
-        if self.needs_new_region(record_len, payload_capacity)? {
-            return self.append_in_transaction(storage, bytes, large_scratch);
+    fn enforce_retention_after_append<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) {
+        if self.memory.retention_enforcement != ObjectLogRetentionEnforcement::OnAppend {
+            return;
+        }
+        if let Err(error) = self.enforce_retention_inner(storage) {
+            self.memory.retention_error = Some(error);
         }
+    }
+
+    /// Adds committed appends to the tracked live totals, if tracked.
+    fn note_live_append(&mut self, objects: usize, bytes: usize) {
+        let Some(totals) = self.memory.live_totals else {
+            return;
+        };
+        self.memory.live_totals = u64::try_from(objects)
+            .ok()
+            .and_then(|objects| totals.objects.checked_add(objects))
+            .zip(
+                u64::try_from(bytes)
+                    .ok()
+                    .and_then(|bytes| totals.bytes.checked_add(bytes)),
+            )
+            .map(|(objects, bytes)| ObjectLogLiveTotals { objects, bytes });
+    }
 
+    /// Returns the live object count and payload bytes, scanning the log only
+    /// when they are not already tracked.
+    fn live_totals<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        head: ObjectLogHandle,
+    ) -> Result<ObjectLogLiveTotals, ObjectLogError> {
+        if let Some(totals) = self.memory.live_totals {
+            return Ok(totals);
+        }
+        let mut totals = ObjectLogLiveTotals {
+            objects: 0,
+            bytes: 0,
+        };
+        let mut current = Some(head);
+        while let Some(handle) = current {
+            totals.objects = totals
+                .objects
+                .checked_add(1)
+                .ok_or(ObjectLogError::LengthOverflow)?;
+            totals.bytes = totals
+                .bytes
+                .checked_add(self.get_object_len_inner(storage, handle)?)
+                .ok_or(ObjectLogError::LengthOverflow)?;
+            current = self.next_handle_inner(storage, handle)?;
+        }
+        self.memory.live_totals = Some(totals);
+        Ok(totals)
+    }
+
+    fn enforce_retention_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<bool, ObjectLogError> {
+        let Some(head) = self.first_handle() else {
+            return Ok(false);
+        };
+        let mut cut = RetentionCut {
+            boundary: head,
+            objects: 0,
+            bytes: 0,
+        };
+        match self.memory.retention {
+            ObjectLogRetention::Unbounded => return Ok(false),
+            ObjectLogRetention::MaxObjects(limit) => {
+                let totals = self.live_totals(storage, head)?;
+                let mut objects = totals.objects;
+                while objects > limit && self.advance_retention_cut(storage, &mut cut)? {
+                    objects -= 1;
+                }
+            }
+            ObjectLogRetention::MaxBytes(limit) => {
+                let totals = self.live_totals(storage, head)?;
+                while totals.bytes.saturating_sub(cut.bytes) > limit
+                    && self.advance_retention_cut(storage, &mut cut)?
+                {}
+            }
+            ObjectLogRetention::MaxRegions(limit) => {
+                let Some(first_retained) = self.memory.regions.len().checked_sub(limit) else {
+                    return Ok(false);
+                };
+                while self
+                    .find_region(cut.boundary.region_index, cut.boundary.sequence)
+                    .ok_or(ObjectLogError::InvalidHandle)?
+                    < first_retained
+                    && self.advance_retention_cut(storage, &mut cut)?
+                {}
+            }
+            ObjectLogRetention::MinTimestamp(cutoff) => {
+                while self
+                    .read_record_timestamp(storage, cut.boundary)?
+                    .ok_or(ObjectLogError::MissingRetentionTimestamp)?
+                    < cutoff
+                    && self.advance_retention_cut(storage, &mut cut)?
+                {}
+            }
+        }
+        if cut.boundary == head {
+            return Ok(false);
+        }
+        self.truncate_before_inner(storage, cut.boundary)?;
+        self.memory.live_totals = self.memory.live_totals.and_then(|totals| {
+            Some(ObjectLogLiveTotals {
+                objects: totals.objects.checked_sub(cut.objects)?,
+                bytes: totals.bytes.checked_sub(cut.bytes)?,
+            })
+        });
+        Ok(true)
+    }
+
+    /// Moves `cut` past its boundary object unless that object is the newest,
+    /// returning whether it moved.
+    fn advance_retention_cut<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        cut: &mut RetentionCut,
+    ) -> Result<bool, ObjectLogError> {
+        let Some(next) = self.next_handle_inner(storage, cut.boundary)? else {
+            return Ok(false);
+        };
+        cut.objects = cut
+            .objects
+            .checked_add(1)
+            .ok_or(ObjectLogError::LengthOverflow)?;
+        cut.bytes = cut
+            .bytes
+            .checked_add(self.get_object_len_inner(storage, cut.boundary)?)
+            .ok_or(ObjectLogError::LengthOverflow)?;
+        cut.boundary = next;
+        Ok(true)
+    }
+
+    /// Reads the timestamp record that follows the public record at `handle`.
+    fn read_record_timestamp<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        handle: ObjectLogHandle,
+    ) -> Result<Option<u64>, ObjectLogError> {
+        let (region, record) = self.read_public_record_info(storage, handle)?;
+        let offset = record
+            .record_end
+            .checked_add(
+                u32::try_from(self.chain_seal_len()).map_err(|_| ObjectLogError::LengthOverflow)?,
+            )
+            .ok_or(ObjectLogError::LengthOverflow)?;
+        if offset >= region.committed_end_offset {
+            return Ok(None);
+        }
+        let timestamp_handle = ObjectLogHandle::new(handle.region_index, handle.sequence, offset);
+        let timestamp = self.read_record_info(storage, region, timestamp_handle)?;
+        if timestamp.record_type != RECORD_TIMESTAMP {
+            return Ok(None);
+        }
+        self.read_record_body_into_storage_scratch(
+            storage,
+            region,
+            timestamp_handle,
+            timestamp,
+            true,
+        )?;
+        decode_timestamp_body(&storage.memory.payload_scratch[..timestamp.body_len]).map(Some)
+    }
+
+    /// Returns the length of the private records written after each public
+    /// record: the chain seal and, for timestamped appends, the timestamp.
+    fn record_trailer_len(&self) -> usize {
+        let timestamp_len = if self.memory.append_timestamp.is_some() {
+            TIMESTAMP_RECORD_LEN
+        } else {
+            0
+        };
+        self.chain_seal_len() + timestamp_len
+    }
+
+    fn chain_seal_len(&self) -> usize {
+        if self.memory.hash_chain {
+            CHAIN_SEAL_RECORD_LEN
+        } else {
+            0
+        }
+    }
+
+    fn next_chain_seal(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Option<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>, ObjectLogError> {
+        if !self.memory.hash_chain {
+            return Ok(None);
+        }
+        chain_digest(&self.memory.chain_planned_head, bytes).map(Some)
+    }
+
+    fn verify_chain_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        from: ObjectLogHandle,
+        to: ObjectLogHandle,
+        scratch: &mut [u8],
+    ) -> Result<(), ObjectLogError> {
+        if !self.memory.hash_chain {
+            return Err(ObjectLogError::HashChainDisabled);
+        }
+        if scratch.is_empty() {
+            return Err(ObjectLogError::BufferTooSmall {
+                needed: 1,
+                available: 0,
+            });
+        }
+        let _ = self.validate_live_handle(storage, to)?;
+        let mut previous = self.chain_predecessor_digest(storage, from)?;
+        let mut current = from;
+        loop {
+            let expected = self.object_chain_digest(storage, &previous, current, scratch)?;
+            let sealed = self.read_chain_seal(storage, current)?;
+            if sealed != expected {
+                return Err(ObjectLogError::HashChainMismatch { handle: current });
+            }
+            previous = sealed;
+            if current == to {
+                break;
+            }
+            current = self
+                .next_handle_inner(storage, current)?
+                .ok_or(ObjectLogError::InvalidHandle)?;
+        }
+        if self.next_handle_inner(storage, to)?.is_none()
+            && previous != self.memory.chain_committed_head
+        {
+            return Err(ObjectLogError::HashChainMismatch { handle: to });
+        }
+        Ok(())
+    }
+
+    fn chain_predecessor_digest<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        handle: ObjectLogHandle,
+    ) -> Result<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN], ObjectLogError> {
+        let mut current = self.first_handle().ok_or(ObjectLogError::InvalidHandle)?;
+        if current == handle {
+            return Ok(self.memory.chain_anchor);
+        }
+        loop {
+            let next = self
+                .next_handle_inner(storage, current)?
+                .ok_or(ObjectLogError::InvalidHandle)?;
+            if next == handle {
+                return self.read_chain_seal(storage, current);
+            }
+            current = next;
+        }
+    }
+
+    fn object_chain_digest<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        previous: &[u8; OBJECT_LOG_CHAIN_DIGEST_LEN],
+        handle: ObjectLogHandle,
+        scratch: &mut [u8],
+    ) -> Result<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN], ObjectLogError> {
+        let object_len = self.get_object_len_inner(storage, handle)?;
+        let step = u64::try_from(scratch.len()).map_err(|_| ObjectLogError::LengthOverflow)?;
+        let mut hasher = chain_hasher(previous, object_len);
+        let mut offset = 0u64;
+        while offset < object_len {
+            let len = (object_len - offset).min(step);
+            self.get_range_inner(storage, handle, offset, len, scratch, |bytes| {
+                hasher.update(bytes)
+            })?;
+            offset = offset
+                .checked_add(len)
+                .ok_or(ObjectLogError::LengthOverflow)?;
+        }
+        Ok(hasher.finalize().into())
+    }
+
+    fn read_chain_seal<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        handle: ObjectLogHandle,
+    ) -> Result<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN], ObjectLogError> {
+        let (region, record) = self.read_public_record_info(storage, handle)?;
+        let seal_handle =
+            ObjectLogHandle::new(handle.region_index, handle.sequence, record.record_end);
+        let seal = self.read_record_info(storage, region, seal_handle)?;
+        if seal.record_type != RECORD_CHAIN_SEAL {
+            return Err(ObjectLogError::InvalidFrame);
+        }
+        self.read_record_body_into_storage_scratch(storage, region, seal_handle, seal, true)?;
+        decode_chain_seal_body(&storage.memory.payload_scratch[..seal.body_len])
+    }
+
+    fn append_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        bytes: &[u8],
+        large_scratch: &mut [u8],
+    ) -> Result<ObjectLogHandle, ObjectLogError> {
+        if self.object_requires_large_record(storage.metadata(), bytes.len())? {
+            return self.append_in_transaction(storage, bytes, large_scratch);
+        }
+
+        let record_len = inline_record_len(bytes.len())?
+            .checked_add(self.record_trailer_len())
+            .ok_or(ObjectLogError::LengthOverflow)?;
+        let payload_capacity = committed_payload_capacity::<REGION_SIZE>(storage.metadata())?;
+        if record_len
+            > empty_region_record_capacity(payload_capacity, self.memory.log_metadata_len)?
+        {
+            return self.append_in_transaction(storage, bytes, large_scratch);
+        }
+
+        if self.needs_new_region(record_len, payload_capacity)? {
+            return self.append_in_transaction(storage, bytes, large_scratch);
+        }
+
+        let region = self
+            .memory
+            .regions
+            .last()
+            .copied()
+            .ok_or(ObjectLogError::MissingFrontier)?;
+        let handle = ObjectLogHandle::new(region.region_index, region.sequence, region.end_offset);
+        let seal = self.next_chain_seal(bytes)?;
+        let encoded = encode_inline_append_update(
+            handle,
+            bytes,
+            seal.as_ref(),
+            self.memory.append_timestamp,
+            &mut storage.memory.payload_scratch,
+        )?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..encoded.used],
+            )?;
+        self.apply_append_records(
+            handle,
+            &storage.memory.payload_scratch[encoded.record_start..encoded.used],
+            AppendVisibility::Committed,
+        )?;
+        if usize::try_from(handle.offset).map_err(|_| ObjectLogError::LengthOverflow)?
+            < Header::ENCODED_LEN + self.object_payload_start()?
+        {
+            return Err(ObjectLogError::InvalidHandle);
+        }
+        Ok(handle)
+    }
+
+    fn append_batch_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+        const MAX_HANDLES: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        objects: &[&[u8]],
+        handles: &mut Vec<ObjectLogHandle, MAX_HANDLES>,
+    ) -> Result<(), ObjectLogError> {
+        if objects.is_empty() {
+            return Ok(());
+        }
+        if objects.len() > MAX_HANDLES {
+            return Err(ObjectLogError::BufferTooSmall {
+                needed: objects.len(),
+                available: MAX_HANDLES,
+            });
+        }
+        let mut records_len = 0usize;
+        for (index, bytes) in objects.iter().enumerate() {
+            if self.object_requires_large_record(storage.metadata(), bytes.len())? {
+                return Err(ObjectLogError::BatchObjectNotInline { index });
+            }
+            records_len = records_len
+                .checked_add(inline_record_len(bytes.len())?)
+                .and_then(|len| len.checked_add(self.record_trailer_len()))
+                .ok_or(ObjectLogError::LengthOverflow)?;
+        }
+        let payload_capacity = committed_payload_capacity::<REGION_SIZE>(storage.metadata())?;
+        let region_capacity =
+            empty_region_record_capacity(payload_capacity, self.memory.log_metadata_len)?;
+        if records_len > region_capacity {
+            return Err(ObjectLogError::ObjectTooLarge {
+                len: records_len,
+                capacity: region_capacity,
+            });
+        }
+
+        if !self.needs_new_region(records_len, payload_capacity)? {
+            return self.append_batch_update(
+                storage,
+                objects,
+                handles,
+                AppendVisibility::Committed,
+            );
+        }
+
+        self.checkpoint_append_state()?;
+        let mut allocated_regions = Vec::<u32, REGION_COUNT>::new();
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        let staged: Result<(), ObjectLogError> = (|| {
+            self.materialize_current_frontier_in_transaction(storage)?;
+            let reserved = self.reserve_region(storage, &mut allocated_regions)?;
+            self.install_reserved_frontier(reserved)?;
+            self.append_batch_update(storage, objects, handles, AppendVisibility::Planned)
+        })();
+        if let Err(error) = staged {
+            handles.clear();
+            return match self.rollback_transaction(storage, allocated_regions) {
+                Ok(()) => Err(error),
+                Err(cleanup_error) => Err(cleanup_error),
+            };
+        }
+        if let Err(error) = storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )
+        {
+            handles.clear();
+            return match self.rollback_transaction(storage, allocated_regions) {
+                Ok(()) => Err(error.into()),
+                Err(cleanup_error) => Err(cleanup_error),
+            };
+        }
+        self.commit_staged_appends();
+        self.clear_append_checkpoint();
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        Ok(())
+    }
+
+    fn append_batch_update<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+        const MAX_HANDLES: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        objects: &[&[u8]],
+        handles: &mut Vec<ObjectLogHandle, MAX_HANDLES>,
+        visibility: AppendVisibility,
+    ) -> Result<(), ObjectLogError> {
         let region = self
             .memory
             .regions
             .last()
             .copied()
             .ok_or(ObjectLogError::MissingFrontier)?;
-        let handle = ObjectLogHandle::new(region.region_index, region.sequence, region.end_offset);
-        let encoded =
-            encode_inline_append_update(handle, bytes, &mut storage.memory.payload_scratch)?;
+        let first = ObjectLogHandle::new(region.region_index, region.sequence, region.end_offset);
+        let chain = self
+            .memory
+            .hash_chain
+            .then_some(self.memory.chain_planned_head);
+        let encoded = encode_batch_append_update(
+            first,
+            objects,
+            chain,
+            self.memory.append_timestamp,
+            &mut storage.memory.payload_scratch,
+        )?;
         storage
             .memory
             .state
@@ -686,17 +1706,32 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                 self.collection_id,
                 &storage.memory.payload_scratch[..encoded.used],
             )?;
-        self.apply_append_record(
-            handle,
+        self.apply_append_batch(
+            first,
+            objects.len(),
             &storage.memory.payload_scratch[encoded.record_start..encoded.used],
-            AppendVisibility::Committed,
+            visibility,
         )?;
-        if usize::try_from(handle.offset).map_err(|_| ObjectLogError::LengthOverflow)?
-            < Header::ENCODED_LEN + self.object_payload_start()?
-        {
-            return Err(ObjectLogError::InvalidHandle);
+        let mut offset = first.offset;
+        for bytes in objects {
+            handles
+                .push(ObjectLogHandle::new(
+                    first.region_index,
+                    first.sequence,
+                    offset,
+                ))
+                .map_err(|_| ObjectLogError::BufferTooSmall {
+                    needed: objects.len(),
+                    available: MAX_HANDLES,
+                })?;
+            let record_len = inline_record_len(bytes.len())?
+                .checked_add(self.record_trailer_len())
+                .ok_or(ObjectLogError::LengthOverflow)?;
+            offset = offset
+                .checked_add(u32::try_from(record_len).map_err(|_| ObjectLogError::LengthOverflow)?)
+                .ok_or(ObjectLogError::LengthOverflow)?;
         }
-        Ok(handle)
+        Ok(())
     }
 
     fn append_in_transaction<
@@ -784,7 +1819,9 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             );
         }
 
-        let record_len = inline_record_len(bytes.len())?;
+        let record_len = inline_record_len(bytes.len())?
+            .checked_add(self.record_trailer_len())
+            .ok_or(ObjectLogError::LengthOverflow)?;
         let payload_capacity = committed_payload_capacity::<REGION_SIZE>(storage.metadata())?;
         if record_len
             > empty_region_record_capacity(payload_capacity, self.memory.log_metadata_len)?
@@ -822,8 +1859,14 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             .copied()
             .ok_or(ObjectLogError::MissingFrontier)?;
         let handle = ObjectLogHandle::new(region.region_index, region.sequence, region.end_offset);
-        let encoded =
-            encode_inline_append_update(handle, bytes, &mut storage.memory.payload_scratch)?;
+        let seal = self.next_chain_seal(bytes)?;
+        let encoded = encode_inline_append_update(
+            handle,
+            bytes,
+            seal.as_ref(),
+            self.memory.append_timestamp,
+            &mut storage.memory.payload_scratch,
+        )?;
         storage
             .memory
             .state
@@ -833,7 +1876,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                 self.collection_id,
                 &storage.memory.payload_scratch[..encoded.used],
             )?;
-        self.apply_append_record(
+        self.apply_append_records(
             handle,
             &storage.memory.payload_scratch[encoded.record_start..encoded.used],
             AppendVisibility::Planned,
@@ -863,7 +1906,9 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             );
         }
 
-        let record_len = inline_record_len(bytes.len())?;
+        let record_len = inline_record_len(bytes.len())?
+            .checked_add(self.record_trailer_len())
+            .ok_or(ObjectLogError::LengthOverflow)?;
         let payload_capacity = committed_payload_capacity::<REGION_SIZE>(storage.metadata())?;
         if record_len
             > empty_region_record_capacity(payload_capacity, self.memory.log_metadata_len)?
@@ -885,8 +1930,14 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             .copied()
             .ok_or(ObjectLogError::MissingFrontier)?;
         let handle = ObjectLogHandle::new(region.region_index, region.sequence, region.end_offset);
-        let encoded =
-            encode_inline_append_update(handle, bytes, &mut storage.memory.payload_scratch)?;
+        let seal = self.next_chain_seal(bytes)?;
+        let encoded = encode_inline_append_update(
+            handle,
+            bytes,
+            seal.as_ref(),
+            self.memory.append_timestamp,
+            &mut storage.memory.payload_scratch,
+        )?;
         storage
             .memory
             .state
@@ -896,7 +1947,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                 self.collection_id,
                 &storage.memory.payload_scratch[..encoded.used],
             )?;
-        self.apply_append_record(
+        self.apply_append_records(
             handle,
             &storage.memory.payload_scratch[encoded.record_start..encoded.used],
             AppendVisibility::Planned,
@@ -931,6 +1982,16 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             }
         }
         for region_index in allocated_regions {
+            // Regions the storage transaction allocated were already freed by
+            // its rollback.
+            if storage
+                .memory
+                .state
+                .free_space()
+                .contains_free_region(region_index)
+            {
+                continue;
+            }
             if let Err(error) = storage
                 .memory
                 .state
@@ -1069,17 +2130,9 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             return Ok(());
         }
         let payload_len = payload_offset(region.end_offset)?;
-        storage
-            .memory
-            .state
-            .write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
-                storage.backing,
-                &mut storage.memory.workspace,
-                region.region_index,
-                self.collection_id,
-                OBJECT_LOG_DATA_V1_FORMAT,
-                &self.memory.frontier_payload[..payload_len],
-            )?;
+        if !self.write_frontier_in_place(storage, region.region_index, payload_len)? {
+            self.relocate_frontier(storage, index, payload_len)?;
+        }
         self.memory
             .regions
             .get_mut(index)
@@ -1192,6 +2245,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             .len()
             .checked_sub(scratch_logical_len)
             .ok_or(ObjectLogError::LengthOverflow)?;
+        let seal = self.next_chain_seal(bytes)?;
         self.append_large_entry_and_tail(
             storage,
             LargeTailAppendPlan {
@@ -1200,6 +2254,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                 tail_start,
                 tail_logical_len,
                 first_aux: first_aux.unwrap_or(AuxRegionPointer { region_index: 0 }),
+                seal,
             },
             &bytes[tail_start..],
             allocated_regions,
@@ -1212,7 +2267,8 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         len: usize,
     ) -> Result<bool, ObjectLogError> {
         let payload_capacity = committed_payload_capacity::<REGION_SIZE>(metadata)?;
-        let inline_capacity = inline_body_capacity(payload_capacity, self.memory.log_metadata_len)?;
+        let inline_capacity = inline_body_capacity(payload_capacity, self.memory.log_metadata_len)?
+            .saturating_sub(self.record_trailer_len());
         if len > inline_capacity {
             return Ok(true);
         }
@@ -1239,9 +2295,12 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         tail_bytes: &[u8],
         allocated_regions: &mut Vec<u32, REGION_COUNT>,
     ) -> Result<ObjectLogHandle, ObjectLogError> {
+        let timestamp = self.memory.append_timestamp;
         let handle = self.append_generated_record_transactional(
             storage,
-            large_entry_record_len()?,
+            large_entry_record_len()?
+                .checked_add(self.record_trailer_len())
+                .ok_or(ObjectLogError::LengthOverflow)?,
             allocated_regions,
             |handle, output| {
                 encode_large_entry_append_update(
@@ -1249,6 +2308,8 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                     plan.total_object_len,
                     plan.tail_logical_len,
                     plan.first_aux,
+                    plan.seal.as_ref(),
+                    timestamp,
                     output,
                 )
             },
@@ -1334,7 +2395,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                         self.collection_id,
                         &storage.memory.payload_scratch[..encoded.used],
                     )?;
-                self.apply_append_record(
+                self.apply_append_records(
                     handle,
                     &storage.memory.payload_scratch[encoded.record_start..encoded.used],
                     AppendVisibility::Planned,
@@ -1400,7 +2461,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         allocated_regions: &mut Vec<u32, REGION_COUNT>,
     ) -> Result<AuxRegionPointer, ObjectLogError> {
         let reserved = self.reserve_region(storage, allocated_regions)?;
-        storage
+        let region_index = storage
             .memory
             .state
             .write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
@@ -1411,9 +2472,12 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                 OBJECT_LOG_AUX_V1_FORMAT,
                 &scratch[..geometry.payload_capacity],
             )?;
-        Ok(AuxRegionPointer {
-            region_index: reserved.region_index,
-        })
+        if region_index != reserved.region_index {
+            allocated_regions
+                .push(region_index)
+                .map_err(|_| ObjectLogError::TooManyRegions)?;
+        }
+        Ok(AuxRegionPointer { region_index })
     }
 
     fn write_aux_next_link<
@@ -1468,6 +2532,135 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         Ok(end > Header::ENCODED_LEN + payload_capacity)
     }
 
+    fn apply_append_batch(
+        &mut self,
+        first: ObjectLogHandle,
+        count: usize,
+        records: &[u8],
+        visibility: AppendVisibility,
+    ) -> Result<(), ObjectLogError> {
+        if count == 0 {
+            return Err(ObjectLogError::InvalidEncoding);
+        }
+        let mut handle = first;
+        let mut position = 0usize;
+        for _ in 0..count {
+            let remaining = records
+                .get(position..)
+                .ok_or(ObjectLogError::InvalidEncoding)?;
+            let record_info = decode_record_info_at(handle.offset, remaining)?;
+            if record_info.record_type != RECORD_INLINE_OBJECT {
+                return Err(ObjectLogError::InvalidEncoding);
+            }
+            let mut sealed_len = record_len(record_info.body_len)?
+                .checked_add(self.chain_seal_len())
+                .ok_or(ObjectLogError::LengthOverflow)?;
+            let trailer_start = position
+                .checked_add(sealed_len)
+                .ok_or(ObjectLogError::LengthOverflow)?;
+            if let Some(trailer) = records.get(trailer_start..).filter(|rest| !rest.is_empty()) {
+                let trailer_offset = handle
+                    .offset
+                    .checked_add(
+                        u32::try_from(sealed_len).map_err(|_| ObjectLogError::LengthOverflow)?,
+                    )
+                    .ok_or(ObjectLogError::LengthOverflow)?;
+                if decode_record_info_at(trailer_offset, trailer)?.record_type == RECORD_TIMESTAMP {
+                    sealed_len = sealed_len
+                        .checked_add(TIMESTAMP_RECORD_LEN)
+                        .ok_or(ObjectLogError::LengthOverflow)?;
+                }
+            }
+            let end = position
+                .checked_add(sealed_len)
+                .ok_or(ObjectLogError::LengthOverflow)?;
+            let record = records
+                .get(position..end)
+                .ok_or(ObjectLogError::InvalidEncoding)?;
+            self.apply_append_records(handle, record, visibility)?;
+            handle = ObjectLogHandle::new(
+                handle.region_index,
+                handle.sequence,
+                handle
+                    .offset
+                    .checked_add(
+                        u32::try_from(sealed_len).map_err(|_| ObjectLogError::LengthOverflow)?,
+                    )
+                    .ok_or(ObjectLogError::LengthOverflow)?,
+            );
+            position = end;
+        }
+        if position != records.len() {
+            return Err(ObjectLogError::InvalidEncoding);
+        }
+        Ok(())
+    }
+
+    fn apply_append_records(
+        &mut self,
+        handle: ObjectLogHandle,
+        records: &[u8],
+        visibility: AppendVisibility,
+    ) -> Result<(), ObjectLogError> {
+        let record_info = decode_record_info_at(handle.offset, records)?;
+        if matches!(
+            record_info.record_type,
+            RECORD_CHAIN_SEAL | RECORD_TIMESTAMP
+        ) {
+            return Err(ObjectLogError::InvalidEncoding);
+        }
+        let record_end = record_len(record_info.body_len)?;
+        let record = records
+            .get(..record_end)
+            .ok_or(ObjectLogError::InvalidFrame)?;
+        let trailer = records
+            .get(record_end..)
+            .ok_or(ObjectLogError::InvalidFrame)?;
+        let public = record_type_is_public(record_info.record_type);
+        let seal_len = if self.memory.hash_chain && public {
+            CHAIN_SEAL_RECORD_LEN
+        } else {
+            0
+        };
+        let (seal, timestamp) = if trailer.len() == seal_len {
+            (&trailer[..seal_len], None)
+        } else if public && trailer.len() == seal_len + TIMESTAMP_RECORD_LEN {
+            (&trailer[..seal_len], Some(&trailer[seal_len..]))
+        } else {
+            return Err(ObjectLogError::InvalidEncoding);
+        };
+        let seal_handle =
+            ObjectLogHandle::new(handle.region_index, handle.sequence, record_info.record_end);
+        let timestamp_handle = ObjectLogHandle::new(
+            handle.region_index,
+            handle.sequence,
+            record_info
+                .record_end
+                .checked_add(u32::try_from(seal_len).map_err(|_| ObjectLogError::LengthOverflow)?)
+                .ok_or(ObjectLogError::LengthOverflow)?,
+        );
+        if !seal.is_empty()
+            && decode_record_info_at(seal_handle.offset, seal)?.record_type != RECORD_CHAIN_SEAL
+        {
+            return Err(ObjectLogError::InvalidEncoding);
+        }
+        if let Some(timestamp) = timestamp {
+            if decode_record_info_at(timestamp_handle.offset, timestamp)?.record_type
+                != RECORD_TIMESTAMP
+            {
+                return Err(ObjectLogError::InvalidEncoding);
+            }
+        }
+        self.apply_append_record(handle, record, visibility)?;
+        if !seal.is_empty() {
+            self.apply_append_record(seal_handle, seal, visibility)?;
+        }
+        if let Some(timestamp) = timestamp {
+            self.apply_append_record(timestamp_handle, timestamp, visibility)?;
+        }
+        Ok(())
+    }
+
     fn apply_append_record(
         &mut self,
         handle: ObjectLogHandle,
@@ -1556,6 +2749,13 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             region.committed_end_offset = region.end_offset;
         }
         self.memory.next_sequence = self.memory.next_sequence.max(next_sequence);
+        if record_info.record_type == RECORD_CHAIN_SEAL {
+            let digest = decode_chain_seal_body(body)?;
+            self.memory.chain_planned_head = digest;
+            if matches!(visibility, AppendVisibility::Committed) {
+                self.memory.chain_committed_head = digest;
+            }
+        }
         Ok(())
     }
 
@@ -1571,7 +2771,14 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         if region.committed_end_offset > region.end_offset {
             return Err(ObjectLogError::InvalidEncoding);
         }
-        match self.find_region(region.region_index, region.sequence) {
+        // A region that moved past a bad region is materialized under its
+        // replacement index, so the reserved region is found by sequence.
+        match self
+            .memory
+            .regions
+            .iter()
+            .position(|existing| existing.sequence == region.sequence)
+        {
             Some(index) => {
                 let existing = self
                     .memory
@@ -1581,6 +2788,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                 if existing.start_offset != region.start_offset {
                     return Err(ObjectLogError::InvalidEncoding);
                 }
+                existing.region_index = region.region_index;
                 existing.end_offset = region.end_offset;
                 existing.flushed = true;
                 existing.first_planned_public_offset = region.first_planned_public_offset;
@@ -1628,6 +2836,17 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         Ok(())
     }
 
+    fn apply_enable_hash_chain(&mut self) -> Result<(), ObjectLogError> {
+        if self.memory.hash_chain || !self.memory.regions.is_empty() {
+            return Err(ObjectLogError::InvalidEncoding);
+        }
+        self.memory.hash_chain = true;
+        self.memory.chain_anchor = CHAIN_GENESIS_DIGEST;
+        self.memory.chain_committed_head = CHAIN_GENESIS_DIGEST;
+        self.memory.chain_planned_head = CHAIN_GENESIS_DIGEST;
+        Ok(())
+    }
+
     fn checkpoint_append_state(&mut self) -> Result<(), ObjectLogError> {
         self.memory.rollback_regions.clear();
         for region in self.memory.regions.iter().copied() {
@@ -1650,6 +2869,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         self.memory
             .frontier_payload
             .copy_from_slice(&self.memory.rollback_frontier_payload);
+        self.memory.chain_planned_head = self.memory.chain_committed_head;
     }
 
     fn clear_append_checkpoint(&mut self) {
@@ -1664,15 +2884,20 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             }
             region.first_planned_public_offset = None;
         }
+        self.memory.chain_committed_head = self.memory.chain_planned_head;
     }
 
     fn apply_truncate_before<const FREED_CAP: usize>(
         &mut self,
         handle: ObjectLogHandle,
         retained_start: ObjectLogHandle,
+        chain_anchor: Option<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
         freed_regions: &mut Vec<u32, FREED_CAP>,
     ) -> Result<(), ObjectLogError> {
         freed_regions.clear();
+        if self.memory.hash_chain != chain_anchor.is_some() {
+            return Err(ObjectLogError::InvalidEncoding);
+        }
         let retained_index = self
             .find_region(retained_start.region_index, retained_start.sequence)
             .ok_or(ObjectLogError::InvalidHandle)?;
@@ -1722,6 +2947,9 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             .get_mut(public_index)
             .ok_or(ObjectLogError::InvalidHandle)?
             .first_committed_public_offset = Some(handle.offset);
+        if let Some(anchor) = chain_anchor {
+            self.memory.chain_anchor = anchor;
+        }
         Ok(())
     }
 
@@ -1740,9 +2968,18 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
         let mut freed_aux_regions = Vec::<u32, REGION_COUNT>::new();
         self.validate_live_handle(storage, handle)?;
         let retained_start = self.retained_start_for_truncate(storage, handle)?;
+        let chain_anchor = if self.memory.hash_chain {
+            Some(self.chain_predecessor_digest(storage, handle)?)
+        } else {
+            None
+        };
         self.collect_aux_regions_truncated_before(storage, retained_start, &mut freed_aux_regions)?;
-        let used =
-            encode_truncate_update(handle, retained_start, &mut storage.memory.payload_scratch)?;
+        let used = encode_truncate_update(
+            handle,
+            retained_start,
+            chain_anchor.as_ref(),
+            &mut storage.memory.payload_scratch,
+        )?;
         storage
             .memory
             .state
@@ -1768,7 +3005,7 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
                 &mut storage.memory.workspace,
                 self.collection_id,
             )?;
-        self.apply_truncate_before(handle, retained_start, &mut freed_regions)?;
+        self.apply_truncate_before(handle, retained_start, chain_anchor, &mut freed_regions)?;
         for region_index in freed_aux_regions {
             push_unique_region_index(&mut freed_regions, region_index)?;
         }
@@ -1918,37 +3155,217 @@ impl<'mem, const REGION_SIZE: usize, const MAX_REGIONS: usize, const LOG_METADAT
             return Ok(());
         }
         let payload_len = payload_offset(region.end_offset)?;
+        if !self.write_frontier_in_place(storage, region.region_index, payload_len)? {
+            return self.flush_relocated(storage, index, payload_len);
+        }
+        self.finish_flush(storage, index)
+    }
+
+    /// Marks data region `index` flushed and records the log in a WAL
+    /// snapshot.
+    fn finish_flush<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        index: usize,
+    ) -> Result<(), ObjectLogError> {
+        self.memory
+            .regions
+            .get_mut(index)
+            .ok_or(ObjectLogError::InvalidHandle)?
+            .flushed = true;
+        let chain = self
+            .memory
+            .hash_chain
+            .then_some((&self.memory.chain_anchor, &self.memory.chain_committed_head));
+        let retention = (self.memory.retention != ObjectLogRetention::Unbounded
+            || self.memory.retention_enforcement != ObjectLogRetentionEnforcement::Explicit)
+            .then_some((self.memory.retention, self.memory.retention_enforcement));
+        let snapshot_len = encode_snapshot::<MAX_REGIONS, LOG_METADATA_MAX>(
+            &self.memory.regions,
+            &self.memory.log_metadata[..self.memory.log_metadata_len],
+            chain,
+            retention,
+            &mut storage.memory.payload_scratch,
+        )?;
+        storage
+            .memory
+            .state
+            .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                CollectionType::OBJECT_LOG_CODE,
+                &storage.memory.payload_scratch[..snapshot_len],
+            )?;
+        Ok(())
+    }
+
+    /// Flushes data region `index` after the backend reported its reserved
+    /// region bad, moving the payload to a fresh region in one collection
+    /// transaction.
+    fn flush_relocated<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        index: usize,
+        payload_len: usize,
+    ) -> Result<(), ObjectLogError> {
+        let saved = self
+            .memory
+            .regions
+            .get(index)
+            .copied()
+            .ok_or(ObjectLogError::InvalidHandle)?;
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        let relocated: Result<(), ObjectLogError> = (|| {
+            self.relocate_frontier(storage, index, payload_len)?;
+            self.finish_flush(storage, index)?;
+            storage
+                .memory
+                .state
+                .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    self.collection_id,
+                )?;
+            Ok(())
+        })();
+        if let Err(error) = relocated {
+            if let Some(region) = self.memory.regions.get_mut(index) {
+                *region = saved;
+            }
+            return match storage
+                .memory
+                .state
+                .rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    self.collection_id,
+                ) {
+                Ok(()) => Err(error),
+                Err(cleanup_error) => Err(cleanup_error.into()),
+            };
+        }
         storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        Ok(())
+    }
+
+    /// Writes the frontier payload into its reserved data region and returns
+    /// `false` when the backend reported that region bad and storage retired
+    /// it.
+    fn write_frontier_in_place<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        region_index: u32,
+        payload_len: usize,
+    ) -> Result<bool, ObjectLogError> {
+        match storage
+            .memory
+            .state
+            .write_committed_region_in_place::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                region_index,
+                self.collection_id,
+                OBJECT_LOG_DATA_V1_FORMAT,
+                &self.memory.frontier_payload[..payload_len],
+            ) {
+            Ok(()) => Ok(true),
+            Err(StorageRuntimeError::BadRegion(failed)) if failed == region_index => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Writes the frontier payload of data region `index` to a region
+    /// reserved in the open collection transaction, points the region list at
+    /// it, and frees the retired region it replaces.
+    ///
+    /// Handles name data regions by sequence, which the move keeps, and
+    /// auxiliary regions never name their data region, so nothing else is
+    /// rewritten.
+    fn relocate_frontier<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        index: usize,
+        payload_len: usize,
+    ) -> Result<(), ObjectLogError> {
+        let region = self
+            .memory
+            .regions
+            .get_mut(index)
+            .ok_or(ObjectLogError::InvalidHandle)?;
+        let bad = region.region_index;
+        let replacement = storage
+            .memory
+            .state
+            .reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &mut storage.memory.reclaim_source_regions,
+                &mut storage.memory.active_collections,
+                &mut storage.memory.reclaim_plan,
+                &mut storage.memory.open_plan,
+            )?;
+        region.region_index = storage
             .memory
             .state
             .write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
                 storage.backing,
                 &mut storage.memory.workspace,
-                region.region_index,
+                replacement,
                 self.collection_id,
                 OBJECT_LOG_DATA_V1_FORMAT,
                 &self.memory.frontier_payload[..payload_len],
             )?;
-        self.memory
-            .regions
-            .get_mut(index)
-            .ok_or(ObjectLogError::InvalidHandle)?
-            .flushed = true;
-        let snapshot_len = encode_snapshot::<MAX_REGIONS, LOG_METADATA_MAX>(
-            &self.memory.regions,
-            &self.memory.log_metadata[..self.memory.log_metadata_len],
-            &mut storage.memory.payload_scratch,
-        )?;
         storage
             .memory
             .state
-            .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+            .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
                 storage.backing,
                 &mut storage.memory.workspace,
                 self.collection_id,
-                CollectionType::OBJECT_LOG_CODE,
-                &storage.memory.payload_scratch[..snapshot_len],
+                bad,
             )?;
+        storage.memory.state.note_region_relocation();
+        self.memory.relocation_epoch = storage.runtime().relocation_epoch();
         Ok(())
     }
 
@@ -2871,6 +4288,16 @@ pub enum ObjectLogError {
     InvalidFrame,
     /// Checked arithmetic overflowed.
     LengthOverflow,
+    /// A batch object at `index` is too large for an inline record.
+    BatchObjectNotInline { index: usize },
+    /// A retention limit of zero would discard the newest object.
+    InvalidRetention,
+    /// Timestamp retention found an object shorter than its `u64` timestamp.
+    MissingRetentionTimestamp,
+    /// The log was not created with [`ObjectLog::new_hash_chained`].
+    HashChainDisabled,
+    /// The object at `handle` does not match its hash-chain seal.
+    HashChainMismatch { handle: ObjectLogHandle },
 }
 
 impl From<StorageRuntimeError> for ObjectLogError {
@@ -2902,6 +4329,7 @@ fn validate_collection<
     if collection.basis() == StartupCollectionBasis::Dropped {
         return Err(ObjectLogError::DroppedCollection(collection_id));
     }
+    storage.runtime().ensure_not_quarantined(collection_id)?;
     if collection.collection_type() != Some(CollectionType::OBJECT_LOG_CODE) {
         return Err(ObjectLogError::CollectionTypeMismatch {
             collection_id,
@@ -2924,6 +4352,18 @@ fn replay_object_log<
     memory: &mut ObjectLogMemory<REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
 ) -> Result<(), ObjectLogError> {
     let mut transaction = None::<ObjectLogReplayTransaction>;
+    // WAL head reclaim drops a superseded snapshot but keeps the updates
+    // that followed it, so updates before the first retained basis are
+    // skipped.
+    let mut based = !storage
+        .memory
+        .state
+        .wal_has_collection_basis::<REGION_SIZE, IO>(
+            storage.backing,
+            &mut storage.memory.workspace,
+            collection_id,
+            CollectionType::OBJECT_LOG_CODE,
+        )?;
     let result = storage
         .memory
         .state
@@ -2938,6 +4378,7 @@ fn replay_object_log<
                     } if seen == collection_id
                         && collection_type == CollectionType::OBJECT_LOG_CODE =>
                     {
+                        based = true;
                         memory.clear();
                     }
                     WalRecord::BeginTransaction {
@@ -2973,12 +4414,13 @@ fn replay_object_log<
                     } if seen == collection_id
                         && collection_type == CollectionType::OBJECT_LOG_CODE =>
                     {
+                        based = true;
                         decode_snapshot(payload, memory).map_err(|_| ())?;
                     }
                     WalRecord::Update {
                         collection_id: seen,
                         payload,
-                    } if seen == collection_id => {
+                    } if seen == collection_id && based => {
                         let visibility =
                             if transaction.as_ref().is_some_and(|open| open.checkpointed) {
                                 AppendVisibility::Planned
@@ -3088,17 +4530,57 @@ fn apply_update_payload<
                 collection_id: CollectionId::new(0),
                 memory,
             };
-            log.apply_append_record(handle, bytes, append_visibility)?;
+            log.apply_append_records(handle, bytes, append_visibility)?;
+        }
+        UPDATE_APPEND_BATCH => {
+            let first = read_handle(payload, &mut offset)?;
+            let count = usize::try_from(read_u32(payload, &mut offset)?)
+                .map_err(|_| ObjectLogError::LengthOverflow)?;
+            let len = usize::try_from(read_u32(payload, &mut offset)?)
+                .map_err(|_| ObjectLogError::LengthOverflow)?;
+            let end = offset
+                .checked_add(len)
+                .ok_or(ObjectLogError::LengthOverflow)?;
+            let records = payload
+                .get(offset..end)
+                .ok_or(ObjectLogError::InvalidEncoding)?;
+            offset = end;
+            let mut log = ObjectLog {
+                collection_id: CollectionId::new(0),
+                memory,
+            };
+            log.apply_append_batch(first, count, records, append_visibility)?;
         }
         UPDATE_TRUNCATE_HEAD => {
             let handle = read_handle(payload, &mut offset)?;
             let retained_start = read_handle(payload, &mut offset)?;
+            let chain_anchor = if memory.hash_chain {
+                Some(decode_chain_seal_body(read_bytes(
+                    payload,
+                    &mut offset,
+                    OBJECT_LOG_CHAIN_DIGEST_LEN,
+                )?)?)
+            } else {
+                None
+            };
             let mut freed = Vec::<u32, MAX_REGIONS>::new();
             let mut log = ObjectLog {
                 collection_id: CollectionId::new(0),
                 memory,
             };
-            log.apply_truncate_before(handle, retained_start, &mut freed)?;
+            log.apply_truncate_before(handle, retained_start, chain_anchor, &mut freed)?;
+        }
+        UPDATE_ENABLE_HASH_CHAIN => {
+            let mut log = ObjectLog {
+                collection_id: CollectionId::new(0),
+                memory,
+            };
+            log.apply_enable_hash_chain()?;
+        }
+        UPDATE_SET_RETENTION => {
+            let (retention, enforcement) = read_retention(payload, &mut offset)?;
+            memory.retention = retention;
+            memory.retention_enforcement = enforcement;
         }
         UPDATE_SET_LOG_METADATA => {
             let len = usize::try_from(read_u32(payload, &mut offset)?)
@@ -3136,6 +4618,166 @@ pub(crate) fn empty_snapshot() -> &'static [u8] {
     &EMPTY_SNAPSHOT
 }
 
+/// Returns the offset of the `u32` naming flushed data region
+/// `region_index` in an encoded snapshot, or `None` when the snapshot does
+/// not list it as a flushed region.
+pub(crate) fn snapshot_data_region_offset(
+    snapshot: &[u8],
+    region_index: u32,
+) -> Result<Option<usize>, ObjectLogError> {
+    let (mut offset, region_count, _) = snapshot_region_list(snapshot)?;
+    for _ in 0..region_count {
+        let field = offset;
+        let region = decode_region_metadata(snapshot, &mut offset)?;
+        if region.region_index == region_index && region.flushed {
+            return Ok(Some(field));
+        }
+    }
+    Ok(None)
+}
+
+/// Finds the flushed data region an encoded snapshot lists that is, or links
+/// through its large objects to, `region_index`, and returns that data region
+/// followed by every auxiliary region it links.
+///
+/// Returns `None` when no listed flushed region reaches `region_index`, or
+/// when the group it belongs to holds more than `MAX_REGIONS` regions.
+pub(crate) fn snapshot_region_group<
+    const REGION_SIZE: usize,
+    IO: FlashIo,
+    const MAX_REGIONS: usize,
+>(
+    flash: &mut IO,
+    workspace: &mut crate::StorageWorkspace<REGION_SIZE>,
+    metadata: StorageMetadata,
+    collection_id: CollectionId,
+    snapshot: &[u8],
+    region_index: u32,
+) -> Result<Option<Vec<u32, MAX_REGIONS>>, ObjectLogError> {
+    let (mut offset, region_count, log_metadata_len) = snapshot_region_list(snapshot)?;
+    for _ in 0..region_count {
+        let region = decode_region_metadata(snapshot, &mut offset)?;
+        if !region.flushed {
+            continue;
+        }
+        let mut group = Vec::new();
+        if group.push(region.region_index).is_err() {
+            return Ok(None);
+        }
+        match collect_committed_region_auxiliary_regions::<REGION_SIZE, IO, MAX_REGIONS>(
+            flash,
+            workspace,
+            metadata,
+            collection_id,
+            log_metadata_len,
+            region,
+            &mut group,
+        ) {
+            Ok(()) if group.contains(&region_index) => return Ok(Some(group)),
+            Ok(()) | Err(ObjectLogError::TooManyRegions) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(None)
+}
+
+/// Points every auxiliary-region link in a committed object-log region
+/// payload at `retarget(region)`: the first auxiliary region of each large
+/// object in a data region, or the next link of an auxiliary region. The
+/// checksums covering those links are recomputed.
+pub(crate) fn retarget_region_payload<const REGION_SIZE: usize>(
+    metadata: StorageMetadata,
+    collection_format: u16,
+    payload: &mut [u8],
+    retarget: impl Fn(u32) -> u32,
+) -> Result<(), ObjectLogError> {
+    if collection_format == OBJECT_LOG_AUX_V1_FORMAT {
+        let mut offset = AUX_PROLOGUE_PREFIX_LEN - size_of::<u32>();
+        let log_metadata_len = usize::try_from(read_u32(payload, &mut offset)?)
+            .map_err(|_| ObjectLogError::LengthOverflow)?;
+        let geometry = aux_geometry::<REGION_SIZE>(metadata, log_metadata_len)?;
+        let link_end = geometry
+            .next_link_offset
+            .checked_add(geometry.next_link_len)
+            .ok_or(ObjectLogError::LengthOverflow)?;
+        let link = payload
+            .get_mut(geometry.next_link_offset..link_end)
+            .ok_or(ObjectLogError::InvalidFrame)?;
+        if let Some(next) = decode_aux_next_link(link, metadata.erased_byte)? {
+            encode_aux_next_link(
+                AuxRegionPointer {
+                    region_index: retarget(next.region_index),
+                },
+                link,
+            )?;
+        }
+        return Ok(());
+    }
+    if collection_format != OBJECT_LOG_DATA_V1_FORMAT {
+        return Err(ObjectLogError::InvalidFrame);
+    }
+
+    let (_, log_metadata_len) = payload
+        .get(..DATA_PROLOGUE_FIXED_LEN)
+        .ok_or(ObjectLogError::InvalidFrame)
+        .and_then(decode_data_prologue_header)?;
+    let mut offset = data_prologue_len(log_metadata_len)?;
+    while offset < payload.len() {
+        if payload[offset..]
+            .iter()
+            .all(|byte| *byte == metadata.erased_byte)
+        {
+            break;
+        }
+        let record = decode_record_info_at(
+            u32::try_from(offset).map_err(|_| ObjectLogError::LengthOverflow)?,
+            &payload[offset..],
+        )?;
+        let record_end =
+            usize::try_from(record.record_end).map_err(|_| ObjectLogError::LengthOverflow)?;
+        let body_end = record
+            .body_start
+            .checked_add(record.body_len)
+            .ok_or(ObjectLogError::LengthOverflow)?;
+        if record_end > payload.len() || body_end > record_end {
+            return Err(ObjectLogError::InvalidFrame);
+        }
+        if record.record_type == RECORD_LARGE_RECORD_ENTRY {
+            let body = &payload[record.body_start..body_end];
+            validate_record_body(record.body_crc32c, body)?;
+            let large_entry = decode_large_entry_body(body)?;
+            if large_entry.total_object_len != u64::from(large_entry.tail_logical_len) {
+                encode_large_entry_record(
+                    large_entry.total_object_len,
+                    large_entry.tail_logical_len,
+                    AuxRegionPointer {
+                        region_index: retarget(large_entry.first_aux.region_index),
+                    },
+                    &mut payload[offset..record_end],
+                )?;
+            }
+        }
+        offset = record_end;
+    }
+    Ok(())
+}
+
+/// Returns the offset of the first region entry in an encoded snapshot, how
+/// many entries it lists, and the log metadata length.
+fn snapshot_region_list(snapshot: &[u8]) -> Result<(usize, u32, usize), ObjectLogError> {
+    let mut offset = 0usize;
+    if read_bytes(snapshot, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC.as_slice()
+        || read_u16(snapshot, &mut offset)? != SNAPSHOT_VERSION
+    {
+        return Err(ObjectLogError::InvalidEncoding);
+    }
+    let _flags = read_u16(snapshot, &mut offset)?;
+    let region_count = read_u32(snapshot, &mut offset)?;
+    let log_metadata_len = usize::try_from(read_u32(snapshot, &mut offset)?)
+        .map_err(|_| ObjectLogError::LengthOverflow)?;
+    Ok((offset, region_count, log_metadata_len))
+}
+
 pub(crate) fn collect_committed_regions<
     const REGION_SIZE: usize,
     IO: FlashIo,
@@ -3166,6 +4808,96 @@ pub(crate) fn collect_committed_regions<
     )
 }
 
+/// Verifies every checksum a committed object-log region image carries:
+/// record bodies and their chunks in data regions, and the prologue, chunk
+/// slots, and next link in auxiliary regions.
+///
+/// Returns the region offset of the first frame that fails.
+pub(crate) fn verify_committed_region_checksums<const REGION_SIZE: usize>(
+    metadata: StorageMetadata,
+    collection_format: u16,
+    region: &[u8],
+) -> Result<(), usize> {
+    let payload_capacity =
+        committed_payload_capacity::<REGION_SIZE>(metadata).map_err(|_| Header::ENCODED_LEN)?;
+    let committed_end = Header::ENCODED_LEN + payload_capacity;
+    let payload = region
+        .get(Header::ENCODED_LEN..committed_end)
+        .ok_or(Header::ENCODED_LEN)?;
+    if collection_format == OBJECT_LOG_AUX_V1_FORMAT {
+        return match verify_aux_region_checksums::<REGION_SIZE>(metadata, payload) {
+            Some(offset) => Err(Header::ENCODED_LEN + offset),
+            None => Ok(()),
+        };
+    }
+
+    let (_, log_metadata_len) = payload
+        .get(..DATA_PROLOGUE_FIXED_LEN)
+        .ok_or(ObjectLogError::InvalidFrame)
+        .and_then(decode_data_prologue_header)
+        .map_err(|_| Header::ENCODED_LEN)?;
+    let mut offset = data_prologue_len(log_metadata_len)
+        .map_err(|_| Header::ENCODED_LEN)?
+        .saturating_add(Header::ENCODED_LEN);
+    while offset < committed_end {
+        // Records end where the erased remainder of the region begins.
+        if region[offset..committed_end]
+            .iter()
+            .all(|byte| *byte == metadata.erased_byte)
+        {
+            break;
+        }
+        let record_offset = u32::try_from(offset).map_err(|_| offset)?;
+        let record = decode_record_info_at(record_offset, &region[offset..committed_end])
+            .ok()
+            .filter(|record| {
+                usize::try_from(record.record_end).is_ok_and(|end| end <= committed_end)
+            })
+            .ok_or(offset)?;
+        let body = &region[record.body_start..record.body_start + record.body_len];
+        validate_record_body(record.body_crc32c, body)
+            .and_then(|()| validate_record_body_shape(record.record_type, body))
+            .map_err(|_| offset)?;
+        offset = usize::try_from(record.record_end).map_err(|_| offset)?;
+    }
+    Ok(())
+}
+
+/// Returns the payload offset of the first damaged frame in an auxiliary
+/// region payload, if any.
+fn verify_aux_region_checksums<const REGION_SIZE: usize>(
+    metadata: StorageMetadata,
+    payload: &[u8],
+) -> Option<usize> {
+    let mut offset = AUX_PROLOGUE_PREFIX_LEN - size_of::<u32>();
+    let log_metadata_len = read_u32(payload, &mut offset)
+        .ok()
+        .and_then(|len| usize::try_from(len).ok());
+    let log_metadata = log_metadata_len.and_then(|len| {
+        payload.get(AUX_PROLOGUE_PREFIX_LEN..AUX_PROLOGUE_PREFIX_LEN.checked_add(len)?)
+    });
+    let Some(log_metadata) = log_metadata else {
+        return Some(0);
+    };
+    let Ok(geometry) = aux_geometry::<REGION_SIZE>(metadata, log_metadata.len()) else {
+        return Some(0);
+    };
+    if decode_aux_prologue(payload, geometry, log_metadata).is_err() {
+        return Some(0);
+    }
+    for slot_index in 0..geometry.chunk_slot_count {
+        if decode_aux_chunk_slot(payload, geometry, slot_index).is_err() {
+            return Some(geometry.prologue_len + slot_index * geometry.chunk_slot_len);
+        }
+    }
+    let link =
+        payload.get(geometry.next_link_offset..geometry.next_link_offset + geometry.next_link_len);
+    match link.map(|link| decode_aux_next_link(link, metadata.erased_byte)) {
+        Some(Ok(_)) => None,
+        _ => Some(geometry.next_link_offset),
+    }
+}
+
 fn read_committed_data_region_metadata<const REGION_SIZE: usize, IO: FlashIo>(
     flash: &mut IO,
     metadata: StorageMetadata,
@@ -3395,16 +5127,21 @@ const EMPTY_SNAPSHOT: [u8; 16] = [b'O', b'L', b'G', b'S', 4, 0, 0, 0, 0, 0, 0, 0
 fn encode_inline_append_update(
     handle: ObjectLogHandle,
     bytes: &[u8],
+    seal: Option<&[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
+    timestamp: Option<u64>,
     output: &mut [u8],
 ) -> Result<EncodedRecordUpdate, ObjectLogError> {
     let record_len = inline_record_len(bytes.len())?;
+    let sealed_len = record_len
+        .checked_add(record_trailer_extra_len(seal, timestamp))
+        .ok_or(ObjectLogError::LengthOverflow)?;
     let mut offset = 0usize;
     offset = write_u8(output, offset, UPDATE_APPEND)?;
     offset = write_handle(output, offset, handle)?;
     offset = write_u32(
         output,
         offset,
-        u32::try_from(record_len).map_err(|_| ObjectLogError::LengthOverflow)?,
+        u32::try_from(sealed_len).map_err(|_| ObjectLogError::LengthOverflow)?,
     )?;
     let record_start = offset;
     let record_end = record_start
@@ -3421,7 +5158,61 @@ fn encode_inline_append_update(
             })?,
     )?;
     Ok(EncodedRecordUpdate {
-        used: record_end,
+        used: encode_record_trailer(seal, timestamp, output, record_end)?,
+        record_start,
+    })
+}
+
+fn encode_batch_append_update(
+    first: ObjectLogHandle,
+    objects: &[&[u8]],
+    mut chain: Option<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
+    timestamp: Option<u64>,
+    output: &mut [u8],
+) -> Result<EncodedRecordUpdate, ObjectLogError> {
+    let seal_len = record_trailer_extra_len(chain.as_ref(), timestamp);
+    let mut records_len = 0usize;
+    for bytes in objects {
+        records_len = records_len
+            .checked_add(inline_record_len(bytes.len())?)
+            .and_then(|len| len.checked_add(seal_len))
+            .ok_or(ObjectLogError::LengthOverflow)?;
+    }
+    let mut offset = 0usize;
+    offset = write_u8(output, offset, UPDATE_APPEND_BATCH)?;
+    offset = write_handle(output, offset, first)?;
+    offset = write_u32(
+        output,
+        offset,
+        u32::try_from(objects.len()).map_err(|_| ObjectLogError::LengthOverflow)?,
+    )?;
+    offset = write_u32(
+        output,
+        offset,
+        u32::try_from(records_len).map_err(|_| ObjectLogError::LengthOverflow)?,
+    )?;
+    let record_start = offset;
+    for bytes in objects {
+        let record_end = offset
+            .checked_add(inline_record_len(bytes.len())?)
+            .ok_or(ObjectLogError::LengthOverflow)?;
+        let available = output.len();
+        encode_inline_record(
+            bytes,
+            output
+                .get_mut(offset..record_end)
+                .ok_or(ObjectLogError::BufferTooSmall {
+                    needed: record_end,
+                    available,
+                })?,
+        )?;
+        if let Some(previous) = chain.as_mut() {
+            *previous = chain_digest(previous, bytes)?;
+        }
+        offset = encode_record_trailer(chain.as_ref(), timestamp, output, record_end)?;
+    }
+    Ok(EncodedRecordUpdate {
+        used: offset,
         record_start,
     })
 }
@@ -3467,16 +5258,21 @@ fn encode_large_entry_append_update(
     total_object_len: u64,
     tail_logical_len: u32,
     first_aux: AuxRegionPointer,
+    seal: Option<&[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
+    timestamp: Option<u64>,
     output: &mut [u8],
 ) -> Result<EncodedRecordUpdate, ObjectLogError> {
     let record_len = large_entry_record_len()?;
+    let sealed_len = record_len
+        .checked_add(record_trailer_extra_len(seal, timestamp))
+        .ok_or(ObjectLogError::LengthOverflow)?;
     let mut offset = 0usize;
     offset = write_u8(output, offset, UPDATE_APPEND)?;
     offset = write_handle(output, offset, handle)?;
     offset = write_u32(
         output,
         offset,
-        u32::try_from(record_len).map_err(|_| ObjectLogError::LengthOverflow)?,
+        u32::try_from(sealed_len).map_err(|_| ObjectLogError::LengthOverflow)?,
     )?;
     let record_start = offset;
     let record_end = record_start
@@ -3495,7 +5291,7 @@ fn encode_large_entry_append_update(
             })?,
     )?;
     Ok(EncodedRecordUpdate {
-        used: record_end,
+        used: encode_record_trailer(seal, timestamp, output, record_end)?,
         record_start,
     })
 }
@@ -3503,12 +5299,17 @@ fn encode_large_entry_append_update(
 fn encode_truncate_update(
     handle: ObjectLogHandle,
     retained_start: ObjectLogHandle,
+    chain_anchor: Option<&[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
     output: &mut [u8],
 ) -> Result<usize, ObjectLogError> {
     let mut offset = 0usize;
     offset = write_u8(output, offset, UPDATE_TRUNCATE_HEAD)?;
     offset = write_handle(output, offset, handle)?;
-    write_handle(output, offset, retained_start)
+    offset = write_handle(output, offset, retained_start)?;
+    match chain_anchor {
+        Some(anchor) => write_bytes(output, offset, anchor),
+        None => Ok(offset),
+    }
 }
 
 fn encode_set_log_metadata_update(
@@ -3525,6 +5326,77 @@ fn encode_set_log_metadata_update(
     write_bytes(output, offset, log_metadata)
 }
 
+fn encode_set_retention_update(
+    retention: ObjectLogRetention,
+    enforcement: ObjectLogRetentionEnforcement,
+    output: &mut [u8],
+) -> Result<usize, ObjectLogError> {
+    let offset = write_u8(output, 0, UPDATE_SET_RETENTION)?;
+    write_retention(output, offset, retention, enforcement)
+}
+
+fn validate_retention(retention: ObjectLogRetention) -> Result<(), ObjectLogError> {
+    let zero_limit = match retention {
+        ObjectLogRetention::MaxObjects(limit) | ObjectLogRetention::MaxBytes(limit) => limit == 0,
+        ObjectLogRetention::MaxRegions(limit) => limit == 0,
+        ObjectLogRetention::Unbounded | ObjectLogRetention::MinTimestamp(_) => false,
+    };
+    if zero_limit {
+        return Err(ObjectLogError::InvalidRetention);
+    }
+    Ok(())
+}
+
+fn write_retention(
+    output: &mut [u8],
+    offset: usize,
+    retention: ObjectLogRetention,
+    enforcement: ObjectLogRetentionEnforcement,
+) -> Result<usize, ObjectLogError> {
+    let (kind, value) = match retention {
+        ObjectLogRetention::Unbounded => (0, 0),
+        ObjectLogRetention::MaxObjects(limit) => (1, limit),
+        ObjectLogRetention::MaxBytes(limit) => (2, limit),
+        ObjectLogRetention::MaxRegions(limit) => (
+            3,
+            u64::try_from(limit).map_err(|_| ObjectLogError::LengthOverflow)?,
+        ),
+        ObjectLogRetention::MinTimestamp(cutoff) => (4, cutoff),
+    };
+    let enforcement = match enforcement {
+        ObjectLogRetentionEnforcement::Explicit => 0,
+        ObjectLogRetentionEnforcement::OnAppend => 1,
+    };
+    let offset = write_u8(output, offset, kind)?;
+    let offset = write_u64(output, offset, value)?;
+    write_u8(output, offset, enforcement)
+}
+
+fn read_retention(
+    input: &[u8],
+    offset: &mut usize,
+) -> Result<(ObjectLogRetention, ObjectLogRetentionEnforcement), ObjectLogError> {
+    let kind = read_u8(input, offset)?;
+    let value = read_u64(input, offset)?;
+    let retention = match kind {
+        0 if value == 0 => ObjectLogRetention::Unbounded,
+        1 => ObjectLogRetention::MaxObjects(value),
+        2 => ObjectLogRetention::MaxBytes(value),
+        3 => ObjectLogRetention::MaxRegions(
+            usize::try_from(value).map_err(|_| ObjectLogError::InvalidEncoding)?,
+        ),
+        4 => ObjectLogRetention::MinTimestamp(value),
+        _ => return Err(ObjectLogError::InvalidEncoding),
+    };
+    validate_retention(retention).map_err(|_| ObjectLogError::InvalidEncoding)?;
+    let enforcement = match read_u8(input, offset)? {
+        0 => ObjectLogRetentionEnforcement::Explicit,
+        1 => ObjectLogRetentionEnforcement::OnAppend,
+        _ => return Err(ObjectLogError::InvalidEncoding),
+    };
+    Ok((retention, enforcement))
+}
+
 fn encode_materialized_region_update(
     region: ObjectLogRegion,
     output: &mut [u8],
@@ -3580,13 +5452,25 @@ fn decode_region_metadata(
 fn encode_snapshot<const MAX_REGIONS: usize, const LOG_METADATA_MAX: usize>(
     regions: &Vec<ObjectLogRegion, MAX_REGIONS>,
     log_metadata: &[u8],
+    chain: Option<(
+        &[u8; OBJECT_LOG_CHAIN_DIGEST_LEN],
+        &[u8; OBJECT_LOG_CHAIN_DIGEST_LEN],
+    )>,
+    retention: Option<(ObjectLogRetention, ObjectLogRetentionEnforcement)>,
     output: &mut [u8],
 ) -> Result<usize, ObjectLogError> {
     validate_log_metadata_len::<LOG_METADATA_MAX>(log_metadata.len())?;
+    let mut flags = 0;
+    if chain.is_some() {
+        flags |= SNAPSHOT_FLAG_HASH_CHAIN;
+    }
+    if retention.is_some() {
+        flags |= SNAPSHOT_FLAG_RETENTION;
+    }
     let mut offset = 0usize;
     offset = write_bytes(output, offset, &SNAPSHOT_MAGIC)?;
     offset = write_u16(output, offset, SNAPSHOT_VERSION)?;
-    offset = write_u16(output, offset, 0)?;
+    offset = write_u16(output, offset, flags)?;
     offset = write_u32(
         output,
         offset,
@@ -3602,6 +5486,13 @@ fn encode_snapshot<const MAX_REGIONS: usize, const LOG_METADATA_MAX: usize>(
         offset = encode_region_metadata(region, output, offset)?;
     }
     offset = write_bytes(output, offset, log_metadata)?;
+    if let Some((anchor, head)) = chain {
+        offset = write_bytes(output, offset, anchor)?;
+        offset = write_bytes(output, offset, head)?;
+    }
+    if let Some((retention, enforcement)) = retention {
+        offset = write_retention(output, offset, retention, enforcement)?;
+    }
     Ok(offset)
 }
 
@@ -3621,7 +5512,10 @@ fn decode_snapshot<
     if version != SNAPSHOT_VERSION {
         return Err(ObjectLogError::InvalidEncoding);
     }
-    let _reserved = read_u16(input, &mut offset)?;
+    let flags = read_u16(input, &mut offset)?;
+    if flags & !(SNAPSHOT_FLAG_HASH_CHAIN | SNAPSHOT_FLAG_RETENTION) != 0 {
+        return Err(ObjectLogError::InvalidEncoding);
+    }
     let region_count = usize::try_from(read_u32(input, &mut offset)?)
         .map_err(|_| ObjectLogError::LengthOverflow)?;
     let log_metadata_len = usize::try_from(read_u32(input, &mut offset)?)
@@ -3658,6 +5552,19 @@ fn decode_snapshot<
     let log_metadata = read_bytes(input, &mut offset, log_metadata_len)?;
     memory.log_metadata[..log_metadata_len].copy_from_slice(log_metadata);
     memory.log_metadata_len = log_metadata_len;
+    if flags & SNAPSHOT_FLAG_HASH_CHAIN != 0 {
+        memory.hash_chain = true;
+        memory.chain_anchor =
+            decode_chain_seal_body(read_bytes(input, &mut offset, OBJECT_LOG_CHAIN_DIGEST_LEN)?)?;
+        memory.chain_committed_head =
+            decode_chain_seal_body(read_bytes(input, &mut offset, OBJECT_LOG_CHAIN_DIGEST_LEN)?)?;
+        memory.chain_planned_head = memory.chain_committed_head;
+    }
+    if flags & SNAPSHOT_FLAG_RETENTION != 0 {
+        let (retention, enforcement) = read_retention(input, &mut offset)?;
+        memory.retention = retention;
+        memory.retention_enforcement = enforcement;
+    }
     if offset != input.len() {
         return Err(ObjectLogError::InvalidEncoding);
     }
@@ -3878,6 +5785,14 @@ fn validate_record_body_shape(record_type: u8, body: &[u8]) -> Result<(), Object
             let _ = decode_large_entry_body(body)?;
             Ok(())
         }
+        RECORD_CHAIN_SEAL => {
+            let _ = decode_chain_seal_body(body)?;
+            Ok(())
+        }
+        RECORD_TIMESTAMP => {
+            let _ = decode_timestamp_body(body)?;
+            Ok(())
+        }
         _ => Err(ObjectLogError::InvalidFrame),
     }
 }
@@ -3939,9 +5854,116 @@ fn decode_large_entry_body(body: &[u8]) -> Result<LargeRecordEntryInfo, ObjectLo
     })
 }
 
+fn decode_chain_seal_body(
+    body: &[u8],
+) -> Result<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN], ObjectLogError> {
+    body.try_into().map_err(|_| ObjectLogError::InvalidFrame)
+}
+
+fn encode_optional_chain_seal(
+    seal: Option<&[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
+    output: &mut [u8],
+    offset: usize,
+) -> Result<usize, ObjectLogError> {
+    let Some(digest) = seal else {
+        return Ok(offset);
+    };
+    let end = offset
+        .checked_add(CHAIN_SEAL_RECORD_LEN)
+        .ok_or(ObjectLogError::LengthOverflow)?;
+    let available = output.len();
+    encode_typed_record(
+        RECORD_CHAIN_SEAL,
+        digest,
+        output
+            .get_mut(offset..end)
+            .ok_or(ObjectLogError::BufferTooSmall {
+                needed: end,
+                available,
+            })?,
+    )?;
+    Ok(end)
+}
+
+fn sealed_extra_len(seal: Option<&[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>) -> usize {
+    if seal.is_some() {
+        CHAIN_SEAL_RECORD_LEN
+    } else {
+        0
+    }
+}
+
+/// Encodes the private records that follow a public record: the optional
+/// chain seal, then the optional timestamp record.
+fn encode_record_trailer(
+    seal: Option<&[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
+    timestamp: Option<u64>,
+    output: &mut [u8],
+    offset: usize,
+) -> Result<usize, ObjectLogError> {
+    let offset = encode_optional_chain_seal(seal, output, offset)?;
+    let Some(timestamp) = timestamp else {
+        return Ok(offset);
+    };
+    let end = offset
+        .checked_add(TIMESTAMP_RECORD_LEN)
+        .ok_or(ObjectLogError::LengthOverflow)?;
+    let available = output.len();
+    encode_typed_record(
+        RECORD_TIMESTAMP,
+        &timestamp.to_le_bytes(),
+        output
+            .get_mut(offset..end)
+            .ok_or(ObjectLogError::BufferTooSmall {
+                needed: end,
+                available,
+            })?,
+    )?;
+    Ok(end)
+}
+
+fn record_trailer_extra_len(
+    seal: Option<&[u8; OBJECT_LOG_CHAIN_DIGEST_LEN]>,
+    timestamp: Option<u64>,
+) -> usize {
+    let timestamp_len = if timestamp.is_some() {
+        TIMESTAMP_RECORD_LEN
+    } else {
+        0
+    };
+    sealed_extra_len(seal) + timestamp_len
+}
+
+fn decode_timestamp_body(body: &[u8]) -> Result<u64, ObjectLogError> {
+    body.try_into()
+        .map(u64::from_le_bytes)
+        .map_err(|_| ObjectLogError::InvalidFrame)
+}
+
+fn chain_hasher(previous: &[u8; OBJECT_LOG_CHAIN_DIGEST_LEN], object_len: u64) -> Sha256 {
+    let mut hasher = Sha256::new();
+    hasher.update(previous);
+    hasher.update(object_len.to_le_bytes());
+    hasher
+}
+
+fn chain_digest(
+    previous: &[u8; OBJECT_LOG_CHAIN_DIGEST_LEN],
+    bytes: &[u8],
+) -> Result<[u8; OBJECT_LOG_CHAIN_DIGEST_LEN], ObjectLogError> {
+    let object_len = u64::try_from(bytes.len()).map_err(|_| ObjectLogError::LengthOverflow)?;
+    let mut hasher = chain_hasher(previous, object_len);
+    hasher.update(bytes);
+    Ok(hasher.finalize().into())
+}
+
 fn validate_record_type(record_type: u8) -> Result<(), ObjectLogError> {
     match record_type {
-        RECORD_INLINE_OBJECT | RECORD_OBJECT_CHUNK | RECORD_LARGE_RECORD_ENTRY => Ok(()),
+        RECORD_INLINE_OBJECT
+        | RECORD_OBJECT_CHUNK
+        | RECORD_LARGE_RECORD_ENTRY
+        | RECORD_CHAIN_SEAL
+        | RECORD_TIMESTAMP => Ok(()),
         _ => Err(ObjectLogError::InvalidFrame),
     }
 }
@@ -28,7 +28,7 @@ fn assert_get<
     const MAX_REGIONS: usize,
     const LOG_METADATA_MAX: usize,
 >(
-    log: &ObjectLog<'_, REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
+    log: &mut ObjectLog<'_, REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
     storage: &mut Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
     handle: ObjectLogHandle,
     expected: &[u8],
@@ -51,7 +51,7 @@ fn assert_get_range<
     const MAX_REGIONS: usize,
     const LOG_METADATA_MAX: usize,
 >(
-    log: &ObjectLog<'_, REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
+    log: &mut ObjectLog<'_, REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
     storage: &mut Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
     handle: ObjectLogHandle,
     offset: u64,
@@ -170,7 +170,7 @@ fn assert_get_bytes<
     const MAX_REGIONS: usize,
     const LOG_METADATA_MAX: usize,
 >(
-    log: &ObjectLog<'_, REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
+    log: &mut ObjectLog<'_, REGION_SIZE, MAX_REGIONS, LOG_METADATA_MAX>,
     storage: &mut Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
     handle: ObjectLogHandle,
     expected: &[u8],
@@ -295,7 +295,7 @@ fn append_raw_inline_update<
     bytes: &[u8],
 ) {
     let mut payload = [0u8; REGION_SIZE];
-    let encoded = encode_inline_append_update(handle, bytes, &mut payload).unwrap();
+    let encoded = encode_inline_append_update(handle, bytes, None, None, &mut payload).unwrap();
     storage
         .append_raw_wal_record_for_test(WalRecord::Update {
             collection_id,
@@ -326,11 +326,11 @@ fn assert_replayed_inline_object<
     handle: ObjectLogHandle,
     expected: &[u8],
 ) {
-    let log = ObjectLog {
+    let mut log = ObjectLog {
         collection_id,
         memory,
     };
-    assert_get(&log, storage, handle, expected);
+    assert_get(&mut log, storage, handle, expected);
 }
 
 fn assert_no_replayed_inline_object<
@@ -346,7 +346,7 @@ fn assert_no_replayed_inline_object<
     collection_id: CollectionId,
     handle: ObjectLogHandle,
 ) {
-    let log = ObjectLog {
+    let mut log = ObjectLog {
         collection_id,
         memory,
     };
@@ -1199,7 +1199,7 @@ fn assert_object_log_state_application_rejects_malformed_edges() {
         retained_region.committed_end_offset,
     );
     assert!(matches!(
-        truncate_log.apply_truncate_before(public, invalid_retained, &mut freed),
+        truncate_log.apply_truncate_before(public, invalid_retained, None, &mut freed),
         Err(ObjectLogError::InvalidHandle)
     ));
 
@@ -1217,7 +1217,7 @@ fn assert_object_log_state_application_rejects_malformed_edges() {
         public_region.committed_end_offset,
     );
     assert!(matches!(
-        truncate_log.apply_truncate_before(invalid_public, retained, &mut freed),
+        truncate_log.apply_truncate_before(invalid_public, retained, None, &mut freed),
         Err(ObjectLogError::InvalidHandle)
     ));
 
@@ -1231,7 +1231,7 @@ fn assert_object_log_state_application_rejects_malformed_edges() {
     truncate_log.memory.regions.push(public_region).unwrap();
     freed.clear();
     assert!(matches!(
-        truncate_log.apply_truncate_before(retained, public, &mut freed),
+        truncate_log.apply_truncate_before(retained, public, None, &mut freed),
         Err(ObjectLogError::InvalidHandle)
     ));
     assert!(freed.is_empty());
@@ -1288,8 +1288,8 @@ fn assert_object_log_exact_fit_capacity_boundaries_are_stable() {
     assert_ne!(next.region_index, exact_handle.region_index);
     assert_eq!(next.sequence, exact_handle.sequence + 1);
     let mut scratch = std::vec![0u8; exact.len()];
-    assert_get_bytes(&log, &mut storage, exact_handle, &exact, &mut scratch);
-    assert_get(&log, &mut storage, next, b"x");
+    assert_get_bytes(&mut log, &mut storage, exact_handle, &exact, &mut scratch);
+    assert_get(&mut log, &mut storage, next, b"x");
 
     let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 8192>::new(0xff);
     let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
@@ -1498,7 +1498,7 @@ fn assert_object_log_generated_record_accepts_exact_empty_region_capacity() {
         record_capacity
     );
     let mut scratch = std::vec![0u8; body.len()];
-    assert_get_bytes(&log, &mut storage, handle, &body, &mut scratch);
+    assert_get_bytes(&mut log, &mut storage, handle, &body, &mut scratch);
 }
 
 fn assert_object_log_large_append_rejects_zero_chunk_capacity_frontier() {
@@ -1628,7 +1628,7 @@ fn assert_object_log_large_append_progresses_past_full_nonempty_frontier() {
         .unwrap();
     assert!(log.memory.regions.iter().any(|region| region.flushed));
     let mut scratch = std::vec![0u8; object.len()];
-    assert_get_bytes(&log, &mut storage, handle, &object, &mut scratch);
+    assert_get_bytes(&mut log, &mut storage, handle, &object, &mut scratch);
 }
 
 //= spec/object-log.md#api-and-handles
@@ -1654,7 +1654,7 @@ fn requirement_object_log_replays_unflushed_frontier_from_wal_updates() {
         let mut log = ObjectLog::new(&mut storage, &mut memory, b"log-meta").unwrap();
         let handle = append_with_scratch!(log, &mut storage, b"alpha").unwrap();
 
-        assert_get(&log, &mut storage, handle, b"alpha");
+        assert_get(&mut log, &mut storage, handle, b"alpha");
         (log.collection_id(), handle)
     };
 
@@ -1662,8 +1662,9 @@ fn requirement_object_log_replays_unflushed_frontier_from_wal_updates() {
         Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
             .unwrap();
     let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
-    let reopened_log = ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
-    assert_get(&reopened_log, &mut reopened, handle, b"alpha");
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    assert_get(&mut reopened_log, &mut reopened, handle, b"alpha");
 }
 
 fn assert_object_log_replay_ignores_other_collection_records() {
@@ -1717,8 +1718,8 @@ fn assert_object_log_replay_ignores_other_collection_records() {
         Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
             .unwrap();
     let mut memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
-    let log = ObjectLog::open(target_id, &mut reopened, &mut memory).unwrap();
-    assert_get(&log, &mut reopened, target_handle, b"alpha");
+    let mut log = ObjectLog::open(target_id, &mut reopened, &mut memory).unwrap();
+    assert_get(&mut log, &mut reopened, target_handle, b"alpha");
     assert_ne!(target_id, other_id);
 }
 
@@ -2068,7 +2069,7 @@ fn requirement_object_log_range_reads_return_requested_subrange() {
     let mut log = ObjectLog::new(&mut storage, &mut memory, b"log-meta").unwrap();
     let handle = append_with_scratch!(log, &mut storage, OBJECT).unwrap();
 
-    assert_get_range(&log, &mut storage, handle, 2, b"cdefg");
+    assert_get_range(&mut log, &mut storage, handle, 2, b"cdefg");
     let mut empty_scratch = [];
     assert_eq!(
         log.get_range(
@@ -2109,7 +2110,7 @@ fn requirement_object_log_range_reads_return_requested_subrange() {
     ));
 
     log.flush(&mut storage).unwrap();
-    assert_get_range(&log, &mut storage, handle, 10, b"klmn");
+    assert_get_range(&mut log, &mut storage, handle, 10, b"klmn");
 }
 
 //= spec/object-log.md#api-and-handles
@@ -2210,7 +2211,7 @@ fn assert_object_log_reads_accept_exact_scratch_lengths() {
     fill_pattern(&mut object);
     let large = append_with_scratch!(log, &mut storage, &object).unwrap();
     let mut exact_large = [0u8; 420];
-    assert_get_bytes(&log, &mut storage, large, &object, &mut exact_large);
+    assert_get_bytes(&mut log, &mut storage, large, &object, &mut exact_large);
     let mut exact_large_range = [0u8; 17];
     assert_eq!(
         log.get_range(
@@ -2402,12 +2403,12 @@ fn requirement_object_log_handles_survive_flush_and_reopen() {
 
         let first = append_with_scratch!(log, &mut storage, b"alpha").unwrap();
         log.flush(&mut storage).unwrap();
-        assert_get(&log, &mut storage, first, b"alpha");
+        assert_get(&mut log, &mut storage, first, b"alpha");
 
         let second = append_with_scratch!(log, &mut storage, b"beta").unwrap();
         assert_ne!(first.region_index, second.region_index);
         assert_ne!(first.sequence, second.sequence);
-        assert_get(&log, &mut storage, second, b"beta");
+        assert_get(&mut log, &mut storage, second, b"beta");
         (log.collection_id(), first, second)
     };
 
@@ -2415,9 +2416,10 @@ fn requirement_object_log_handles_survive_flush_and_reopen() {
         Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
             .unwrap();
     let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
-    let reopened_log = ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
-    assert_get(&reopened_log, &mut reopened, first, b"alpha");
-    assert_get(&reopened_log, &mut reopened, second, b"beta");
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    assert_get(&mut reopened_log, &mut reopened, first, b"alpha");
+    assert_get(&mut reopened_log, &mut reopened, second, b"beta");
 }
 
 fn assert_object_log_empty_or_flushed_frontiers_are_not_rematerialized() {
@@ -2464,7 +2466,7 @@ fn assert_object_log_empty_or_flushed_frontiers_are_not_rematerialized() {
         updates_before
     );
     let mut scratch = [0u8; 16];
-    assert_get_bytes(&log, &mut storage, handle, b"alpha", &mut scratch);
+    assert_get_bytes(&mut log, &mut storage, handle, b"alpha", &mut scratch);
 }
 
 //= spec/object-log.md#truncation
@@ -2500,12 +2502,12 @@ fn requirement_object_log_truncate_before_handle_retains_boundary_handle() {
         log.get(&mut storage, first, &mut scratch, |_| ()),
         Err(ObjectLogError::InvalidHandle)
     ));
-    assert_get(&log, &mut storage, second, b"beta");
+    assert_get(&mut log, &mut storage, second, b"beta");
     assert_ne!(storage.free_space_tail_region(), previous_tail);
     assert_eq!(storage.free_space_tail_region(), Some(first.region_index));
 
     let third = append_with_scratch!(log, &mut storage, b"gamma").unwrap();
-    assert_get(&log, &mut storage, third, b"gamma");
+    assert_get(&mut log, &mut storage, third, b"gamma");
 }
 
 //= spec/object-log.md#truncation
@@ -2552,7 +2554,13 @@ fn requirement_object_log_large_truncation_frees_auxiliary_chains() {
         log.get(&mut storage, obsolete_handle, &mut scratch, |_| ()),
         Err(ObjectLogError::InvalidHandle)
     ));
-    assert_get_bytes(&log, &mut storage, retained_handle, &retained, &mut scratch);
+    assert_get_bytes(
+        &mut log,
+        &mut storage,
+        retained_handle,
+        &retained,
+        &mut scratch,
+    );
     assert_eq!(log.first_handle(), Some(retained_handle));
     let dirty_regions = dirty_free_regions(&storage);
     assert!(dirty_regions.len() >= dirty_before + 2);
@@ -2607,7 +2615,7 @@ fn requirement_object_log_metadata_is_immutable_and_reopens_from_wal() {
             LOG_METADATA.len()
         );
         let handle = append_with_scratch!(log, &mut storage, b"alpha").unwrap();
-        assert_get(&log, &mut storage, handle, b"alpha");
+        assert_get(&mut log, &mut storage, handle, b"alpha");
         (log.collection_id(), handle)
     };
 
@@ -2615,7 +2623,8 @@ fn requirement_object_log_metadata_is_immutable_and_reopens_from_wal() {
         Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
             .unwrap();
     let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
-    let reopened_log = ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
     assert_eq!(
         reopened_log.get_log_metadata(|bytes| {
             assert_eq!(bytes, LOG_METADATA);
@@ -2623,7 +2632,7 @@ fn requirement_object_log_metadata_is_immutable_and_reopens_from_wal() {
         }),
         LOG_METADATA.len()
     );
-    assert_get(&reopened_log, &mut reopened, handle, b"alpha");
+    assert_get(&mut reopened_log, &mut reopened, handle, b"alpha");
 }
 
 //= spec/object-log.md#durability
@@ -2652,8 +2661,8 @@ fn requirement_object_log_data_regions_carry_immutable_log_metadata() {
         log.flush(&mut storage).unwrap();
         let second = append_with_scratch!(log, &mut storage, b"beta").unwrap();
         log.flush(&mut storage).unwrap();
-        assert_get(&log, &mut storage, first, b"alpha");
-        assert_get(&log, &mut storage, second, b"beta");
+        assert_get(&mut log, &mut storage, first, b"alpha");
+        assert_get(&mut log, &mut storage, second, b"beta");
         (log.collection_id(), first, second)
     };
 
@@ -2664,7 +2673,8 @@ fn requirement_object_log_data_regions_carry_immutable_log_metadata() {
         Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
             .unwrap();
     let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
-    let reopened_log = ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
     reopened
         .backing
         .write_region(
@@ -2987,7 +2997,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     let mut regions = Vec::<ObjectLogRegion, 4>::new();
     regions.push(valid_region).unwrap();
     let mut snapshot = [0u8; 160];
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     let mut memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
     decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory).unwrap();
 
@@ -2995,7 +3005,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     interior_first.first_committed_public_offset = Some(valid_region.start_offset + 1);
     regions.clear();
     regions.push(interior_first).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory).unwrap();
 
     let mut corrupt = snapshot;
@@ -3023,7 +3033,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.committed_end_offset = invalid_region.end_offset + 1;
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     assert!(matches!(
         decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory),
         Err(ObjectLogError::InvalidEncoding)
@@ -3033,7 +3043,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.committed_end_offset = invalid_region.start_offset - 1;
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     assert!(matches!(
         decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory),
         Err(ObjectLogError::InvalidEncoding)
@@ -3043,7 +3053,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.start_offset = object_start - 1;
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     assert!(matches!(
         decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory),
         Err(ObjectLogError::InvalidEncoding)
@@ -3053,7 +3063,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.first_committed_public_offset = Some(valid_region.committed_end_offset);
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     assert!(matches!(
         decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory),
         Err(ObjectLogError::InvalidEncoding)
@@ -3063,7 +3073,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.first_planned_public_offset = Some(valid_region.end_offset);
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     assert!(matches!(
         decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory),
         Err(ObjectLogError::InvalidEncoding)
@@ -3075,7 +3085,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.first_planned_public_offset = Some(valid_region.start_offset - 1);
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     assert!(matches!(
         decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory),
         Err(ObjectLogError::InvalidEncoding)
@@ -3088,7 +3098,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.first_planned_public_offset = None;
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory).unwrap();
 
     invalid_region = valid_region;
@@ -3097,7 +3107,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.first_planned_public_offset = None;
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     assert!(matches!(
         decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory),
         Err(ObjectLogError::InvalidEncoding)
@@ -3107,7 +3117,7 @@ fn assert_object_log_snapshot_decode_rejects_corrupt_region_metadata() {
     invalid_region.first_planned_public_offset = Some(valid_region.start_offset);
     regions.clear();
     regions.push(invalid_region).unwrap();
-    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, &mut snapshot).unwrap();
+    let used = encode_snapshot::<4, 16>(&regions, LOG_METADATA, None, None, &mut snapshot).unwrap();
     decode_snapshot::<REGION_SIZE, 4, 16>(&snapshot[..used], &mut memory).unwrap();
 }
 
@@ -3378,7 +3388,7 @@ fn requirement_object_log_committed_transaction_publishes_handles() {
     assert_eq!(log.next_handle(&mut storage, second).unwrap(), Some(third));
     assert_eq!(log.next_handle(&mut storage, third).unwrap(), Some(fourth));
     assert_eq!(log.next_handle(&mut storage, fourth).unwrap(), None);
-    assert_get(&log, &mut storage, first, b"before");
+    assert_get(&mut log, &mut storage, first, b"before");
     let mut scratch = [0u8; 256];
     assert_eq!(
         log.get(&mut storage, second, &mut scratch, |bytes| {
@@ -3445,7 +3455,7 @@ fn requirement_object_log_failed_transaction_rolls_back_allocations() {
         log.get(&mut storage, planned, &mut scratch, |_| ()),
         Err(ObjectLogError::InvalidHandle)
     ));
-    assert_get(&log, &mut storage, committed, b"committed");
+    assert_get(&mut log, &mut storage, committed, b"committed");
 
     let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 4096>::new(0xff);
     let (collection_id, planned) = {
@@ -3483,7 +3493,8 @@ fn requirement_object_log_failed_transaction_rolls_back_allocations() {
         Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
             .unwrap();
     let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
-    let reopened_log = ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
     let mut scratch = [0u8; 64];
     assert_eq!(reopened_log.first_handle(), None);
     assert!(matches!(
@@ -3520,7 +3531,7 @@ fn assert_object_log_update_payloads_validate_truncate_and_materialized_regions(
         .unwrap();
 
     let mut payload = [0u8; 64];
-    let used = encode_truncate_update(second, retained_start, &mut payload).unwrap();
+    let used = encode_truncate_update(second, retained_start, None, &mut payload).unwrap();
     assert_eq!(used, 1 + HANDLE_ENCODED_LEN * 2);
     apply_update_payload(&payload[..used], log.memory, AppendVisibility::Committed).unwrap();
     assert_eq!(log.first_handle(), Some(second));
@@ -3672,7 +3683,7 @@ fn requirement_object_log_inline_objects_use_inline_object_records() {
     assert_eq!(record.record_type, RECORD_INLINE_OBJECT);
     assert_eq!(record.body_len, b"inline".len());
     assert_eq!(log.first_handle(), Some(handle));
-    assert_get(&log, &mut storage, handle, b"inline");
+    assert_get(&mut log, &mut storage, handle, b"inline");
 }
 
 //= spec/object-log.md#durability
@@ -3719,7 +3730,7 @@ fn requirement_object_log_large_record_entry_layout() {
         .unwrap()
         .is_none());
     assert_get_bytes(
-        &log,
+        &mut log,
         &mut storage,
         handle,
         &object,
@@ -3865,7 +3876,7 @@ fn requirement_object_log_append_routing_classifies_by_chunk_count() {
     let (_, small_record) = record_info_for(&log, &mut storage, small_handle);
     assert_eq!(small_record.record_type, RECORD_INLINE_OBJECT);
     assert_get_bytes(
-        &log,
+        &mut log,
         &mut storage,
         small_handle,
         &small,
@@ -3889,7 +3900,7 @@ fn requirement_object_log_append_routing_classifies_by_chunk_count() {
     let (_, large_record) = record_info_for(&log, &mut storage, large_handle);
     assert_eq!(large_record.record_type, RECORD_LARGE_RECORD_ENTRY);
     assert_get_bytes(
-        &log,
+        &mut log,
         &mut storage,
         large_handle,
         &large,
@@ -3939,7 +3950,7 @@ fn requirement_object_log_large_append_rejects_no_progress_geometry() {
         log.next_handle(&mut storage, handle).unwrap(),
         Some(public_after)
     );
-    assert_get(&log, &mut storage, public_after, b"after");
+    assert_get(&mut log, &mut storage, public_after, b"after");
 }
 
 fn assert_object_log_rejects_malformed_auxiliary_large_object_reads() {
@@ -3977,7 +3988,7 @@ fn assert_object_log_rejects_malformed_auxiliary_large_object_reads() {
         .unwrap()
         .is_none());
     assert_get_bytes(
-        &log,
+        &mut log,
         &mut storage,
         handle,
         &object,
@@ -4442,7 +4453,7 @@ fn requirement_object_log_large_append_uses_auxiliary_scratch_buffer() {
             .committed_end_offset
     );
     assert_get_bytes(
-        &log,
+        &mut log,
         &mut storage,
         mixed_handle,
         &mixed,
@@ -4495,7 +4506,13 @@ fn requirement_object_log_auxiliary_regions_are_transaction_owned() {
         let committed_handle = append_with_scratch!(log, &mut storage, &object).unwrap();
         let entry = large_entry_for(&log, &mut storage, committed_handle);
         assert!(entry.total_object_len > u64::from(entry.tail_logical_len));
-        assert_get_bytes(&log, &mut storage, committed_handle, &object, &mut scratch);
+        assert_get_bytes(
+            &mut log,
+            &mut storage,
+            committed_handle,
+            &object,
+            &mut scratch,
+        );
         (log.collection_id(), committed_handle, object)
     };
 
@@ -4503,12 +4520,815 @@ fn requirement_object_log_auxiliary_regions_are_transaction_owned() {
         Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
             .unwrap();
     let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 16, 16>::new();
-    let reopened_log = ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
     assert_get_bytes(
-        &reopened_log,
+        &mut reopened_log,
         &mut reopened,
         committed_handle,
         &object,
         &mut std::vec![0; object.len()],
     );
 }
+
+//= spec/object-log.md#retention
+//= type=test
+//# `RING-OBJECT-033` Object-log retention MUST bound the committed live log by
+//# object count, live object payload bytes, or live ordinary data regions by
+//# truncating before a live boundary handle, MUST always retain the newest
+//# committed object, and MUST return fully obsolete data regions to Borromean
+//# storage.
+#[test]
+fn requirement_object_log_retention_bounds_live_prefix() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        &mut flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut log = ObjectLog::new(&mut storage, &mut memory, LOG_METADATA).unwrap();
+
+    let mut handles = std::vec::Vec::new();
+    for object in [b"alpha".as_slice(), b"beta", b"gamma", b"delta", b"epsilon"] {
+        handles.push(append_with_scratch!(log, &mut storage, object).unwrap());
+        log.flush(&mut storage).unwrap();
+    }
+    assert!(!log.enforce_retention(&mut storage).unwrap());
+
+    log.set_retention(
+        &mut storage,
+        ObjectLogRetention::MaxObjects(4),
+        ObjectLogRetentionEnforcement::Explicit,
+    )
+    .unwrap();
+    assert!(log.enforce_retention(&mut storage).unwrap());
+    assert_eq!(log.first_handle(), Some(handles[1]));
+    assert_eq!(
+        storage.free_space_tail_region(),
+        Some(handles[0].region_index)
+    );
+    let mut scratch = [0u8; 64];
+    assert!(matches!(
+        log.get(&mut storage, handles[0], &mut scratch, |_| ()),
+        Err(ObjectLogError::InvalidHandle)
+    ));
+    assert!(!log.enforce_retention(&mut storage).unwrap());
+
+    log.set_retention(
+        &mut storage,
+        ObjectLogRetention::MaxBytes(12),
+        ObjectLogRetentionEnforcement::Explicit,
+    )
+    .unwrap();
+    assert!(log.enforce_retention(&mut storage).unwrap());
+    assert_eq!(log.first_handle(), Some(handles[3]));
+    assert_eq!(
+        storage.free_space_tail_region(),
+        Some(handles[2].region_index)
+    );
+
+    let newest = append_with_scratch!(log, &mut storage, b"zeta").unwrap();
+    log.set_retention(
+        &mut storage,
+        ObjectLogRetention::MaxRegions(2),
+        ObjectLogRetentionEnforcement::Explicit,
+    )
+    .unwrap();
+    assert!(log.enforce_retention(&mut storage).unwrap());
+    assert_eq!(log.first_handle(), Some(handles[4]));
+    assert_eq!(
+        storage.free_space_tail_region(),
+        Some(handles[3].region_index)
+    );
+
+    log.set_retention(
+        &mut storage,
+        ObjectLogRetention::MaxBytes(1),
+        ObjectLogRetentionEnforcement::Explicit,
+    )
+    .unwrap();
+    assert!(log.enforce_retention(&mut storage).unwrap());
+    assert_eq!(log.first_handle(), Some(newest));
+    assert_get(&mut log, &mut storage, newest, b"zeta");
+    assert!(!log.enforce_retention(&mut storage).unwrap());
+}
+
+//= spec/object-log.md#retention
+//= type=test
+//# `RING-OBJECT-034` Object-log timestamp retention MUST read the timestamp
+//# from each object's record header, MUST discard only the prefix before the
+//# first object whose timestamp is at least the cutoff, and MUST reject untimed
+//# appends and enforcement that encounter objects without a header timestamp.
+#[test]
+fn requirement_object_log_timestamp_retention_discards_old_prefix() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        &mut flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut log = ObjectLog::new(&mut storage, &mut memory, LOG_METADATA).unwrap();
+    let mut large_scratch = [0u8; REGION_SIZE];
+
+    let untimed = append_with_scratch!(log, &mut storage, b"untimed").unwrap();
+    log.flush(&mut storage).unwrap();
+    let mut handles = std::vec::Vec::new();
+    for timestamp in [10u64, 30, 20, 40] {
+        handles.push(
+            log.append_timestamped(&mut storage, timestamp, b"sample", &mut large_scratch)
+                .unwrap(),
+        );
+        log.flush(&mut storage).unwrap();
+    }
+    assert_get(&mut log, &mut storage, handles[0], b"sample");
+
+    log.set_retention(
+        &mut storage,
+        ObjectLogRetention::MinTimestamp(25),
+        ObjectLogRetentionEnforcement::Explicit,
+    )
+    .unwrap();
+    assert!(matches!(
+        log.enforce_retention(&mut storage),
+        Err(ObjectLogError::MissingRetentionTimestamp)
+    ));
+    assert_eq!(log.first_handle(), Some(untimed));
+    assert!(matches!(
+        append_with_scratch!(log, &mut storage, b"untimed"),
+        Err(ObjectLogError::MissingRetentionTimestamp)
+    ));
+
+    log.truncate_before(&mut storage, handles[0]).unwrap();
+    assert!(log.enforce_retention(&mut storage).unwrap());
+    assert_eq!(log.first_handle(), Some(handles[1]));
+    assert_get(&mut log, &mut storage, handles[2], b"sample");
+    assert_eq!(
+        storage.free_space_tail_region(),
+        Some(handles[0].region_index)
+    );
+
+    let mut batch = heapless::Vec::<ObjectLogHandle, 2>::new();
+    log.append_batch_timestamped(&mut storage, 50, &[b"one", b"two"], &mut batch)
+        .unwrap();
+    log.set_retention(
+        &mut storage,
+        ObjectLogRetention::MinTimestamp(45),
+        ObjectLogRetentionEnforcement::Explicit,
+    )
+    .unwrap();
+    assert!(log.enforce_retention(&mut storage).unwrap());
+    assert_eq!(log.first_handle(), Some(batch[0]));
+    assert_get(&mut log, &mut storage, batch[1], b"two");
+}
+
+//= spec/object-log.md#retention
+//= type=test
+//# `RING-OBJECT-035` Object-log retention MUST reject zero bounds, MUST be
+//# enforced after each standalone append only when configured for append
+//# enforcement, and MUST persist across reopening the collection.
+#[test]
+fn requirement_object_log_retention_enforcement_modes() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let (collection_id, retained) = {
+        let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+            &mut flash,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+        let mut log = ObjectLog::new(&mut storage, &mut memory, LOG_METADATA).unwrap();
+
+        for retention in [
+            ObjectLogRetention::MaxObjects(0),
+            ObjectLogRetention::MaxBytes(0),
+            ObjectLogRetention::MaxRegions(0),
+        ] {
+            assert!(matches!(
+                log.set_retention(
+                    &mut storage,
+                    retention,
+                    ObjectLogRetentionEnforcement::OnAppend
+                ),
+                Err(ObjectLogError::InvalidRetention)
+            ));
+        }
+        assert_eq!(
+            log.retention(),
+            (
+                ObjectLogRetention::Unbounded,
+                ObjectLogRetentionEnforcement::Explicit
+            )
+        );
+
+        log.set_retention(
+            &mut storage,
+            ObjectLogRetention::MaxObjects(1),
+            ObjectLogRetentionEnforcement::Explicit,
+        )
+        .unwrap();
+        let first = append_with_scratch!(log, &mut storage, b"first").unwrap();
+        log.flush(&mut storage).unwrap();
+        let second = append_with_scratch!(log, &mut storage, b"second").unwrap();
+        assert_eq!(log.first_handle(), Some(first));
+
+        log.set_retention(
+            &mut storage,
+            ObjectLogRetention::MaxObjects(1),
+            ObjectLogRetentionEnforcement::OnAppend,
+        )
+        .unwrap();
+        log.flush(&mut storage).unwrap();
+        let third = append_with_scratch!(log, &mut storage, b"third").unwrap();
+        assert_eq!(log.first_handle(), Some(third));
+        let mut scratch = [0u8; 64];
+        for stale in [first, second] {
+            assert!(matches!(
+                log.get(&mut storage, stale, &mut scratch, |_| ()),
+                Err(ObjectLogError::InvalidHandle)
+            ));
+        }
+        (log.collection_id(), third)
+    };
+
+    let mut reopened =
+        Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
+            .unwrap();
+    let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    assert_eq!(
+        reopened_log.retention(),
+        (
+            ObjectLogRetention::MaxObjects(1),
+            ObjectLogRetentionEnforcement::OnAppend
+        )
+    );
+    let fourth = append_with_scratch!(reopened_log, &mut reopened, b"fourth").unwrap();
+    assert_eq!(reopened_log.first_handle(), Some(fourth));
+    assert_get(&mut reopened_log, &mut reopened, fourth, b"fourth");
+    let mut scratch = [0u8; 64];
+    assert!(matches!(
+        reopened_log.get(&mut reopened, retained, &mut scratch, |_| ()),
+        Err(ObjectLogError::InvalidHandle)
+    ));
+    reopened_log.flush(&mut reopened).unwrap();
+
+    let mut snapshotted =
+        Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
+            .unwrap();
+    let mut snapshotted_memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let snapshotted_log =
+        ObjectLog::open(collection_id, &mut snapshotted, &mut snapshotted_memory).unwrap();
+    assert_eq!(
+        snapshotted_log.retention(),
+        (
+            ObjectLogRetention::MaxObjects(1),
+            ObjectLogRetentionEnforcement::OnAppend
+        )
+    );
+}
+
+//= spec/object-log.md#retention
+//= type=test
+//# `RING-OBJECT-041` An append-time retention failure MUST NOT fail the append:
+//# the append MUST return the committed object's handle and the failure MUST be
+//# reported separately.
+#[test]
+fn requirement_object_log_append_reports_retention_failure_separately() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        &mut flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut log = ObjectLog::new(&mut storage, &mut memory, LOG_METADATA).unwrap();
+    let mut large_scratch = [0u8; REGION_SIZE];
+
+    let untimed = append_with_scratch!(log, &mut storage, b"untimed").unwrap();
+    log.set_retention(
+        &mut storage,
+        ObjectLogRetention::MinTimestamp(5),
+        ObjectLogRetentionEnforcement::OnAppend,
+    )
+    .unwrap();
+    let timed = log
+        .append_timestamped(&mut storage, 10, b"timed", &mut large_scratch)
+        .unwrap();
+    assert_get(&mut log, &mut storage, timed, b"timed");
+    assert_eq!(log.first_handle(), Some(untimed));
+    assert!(matches!(
+        log.take_retention_error(),
+        Some(ObjectLogError::MissingRetentionTimestamp)
+    ));
+    assert!(log.take_retention_error().is_none());
+}
+
+//= spec/object-log.md#retention
+//= type=test
+//# `RING-OBJECT-042` Append-time retention MUST maintain live object and byte
+//# totals incrementally rather than rescanning the live log on every append.
+#[test]
+fn requirement_object_log_retention_tracks_live_totals_incrementally() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        &mut flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut log = ObjectLog::new(&mut storage, &mut memory, LOG_METADATA).unwrap();
+
+    append_with_scratch!(log, &mut storage, b"one").unwrap();
+    assert!(log.memory.live_totals.is_none());
+    log.set_retention(
+        &mut storage,
+        ObjectLogRetention::MaxBytes(10),
+        ObjectLogRetentionEnforcement::OnAppend,
+    )
+    .unwrap();
+    for object in [b"two".as_slice(), b"three", b"four", b"five"] {
+        append_with_scratch!(log, &mut storage, object).unwrap();
+        log.flush(&mut storage).unwrap();
+        let totals = log.memory.live_totals.unwrap();
+        let mut objects = 0;
+        let mut bytes = 0;
+        let mut current = log.first_handle();
+        while let Some(handle) = current {
+            objects += 1;
+            bytes += log.get_object_len(&mut storage, handle).unwrap();
+            current = log.next_handle(&mut storage, handle).unwrap();
+        }
+        assert_eq!((totals.objects, totals.bytes), (objects, bytes));
+        assert!(bytes <= 10);
+    }
+
+    let head = log.first_handle().unwrap();
+    log.truncate_before(&mut storage, head).unwrap();
+    assert!(log.memory.live_totals.is_none());
+}
+
+//= spec/object-log.md#batch-append
+//= type=test
+//# `RING-OBJECT-036` Object-log batch append MUST persist every batched object
+//# with one WAL update whose handles name consecutive inline records in one
+//# ordinary data region, MUST make all batched handles committed together, and
+//# reopening MUST reconstruct the same handles from that update.
+#[test]
+fn requirement_object_log_batch_append_uses_one_wal_update() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let objects: [&[u8]; 4] = [b"one", b"two", b"three", b"four"];
+    let (collection_id, first, batch, spill, filler) = {
+        let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+            &mut flash,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+        let mut log = ObjectLog::new(&mut storage, &mut memory, LOG_METADATA).unwrap();
+        let first = append_with_scratch!(log, &mut storage, b"zero").unwrap();
+
+        let updates_before = count_wal_records(&mut storage, WalRecordType::Update);
+        let mut batch = Vec::<ObjectLogHandle, 8>::new();
+        log.append_batch(&mut storage, &objects, &mut batch)
+            .unwrap();
+        assert_eq!(
+            count_wal_records(&mut storage, WalRecordType::Update),
+            updates_before + 1
+        );
+        assert_eq!(batch.len(), objects.len());
+        let mut expected_offset = first.offset + inline_record_len(4).unwrap() as u32;
+        for (handle, object) in batch.iter().zip(objects) {
+            assert_eq!(handle.region_index, first.region_index);
+            assert_eq!(handle.sequence, first.sequence);
+            assert_eq!(handle.offset, expected_offset);
+            expected_offset += inline_record_len(object.len()).unwrap() as u32;
+            assert_get(&mut log, &mut storage, *handle, object);
+        }
+        let mut traversed = log.next_handle(&mut storage, first).unwrap();
+        for handle in &batch {
+            assert_eq!(traversed, Some(*handle));
+            traversed = log.next_handle(&mut storage, *handle).unwrap();
+        }
+        assert_eq!(traversed, None);
+
+        let chunk_capacity = log
+            .aux_geometry(storage.metadata())
+            .unwrap()
+            .chunk_logical_capacity;
+        let filler = std::vec![0x5au8; chunk_capacity];
+        let mut frontier = log.memory.regions.last().copied().unwrap();
+        while frontier.end_offset as usize + 2 * inline_record_len(filler.len()).unwrap()
+            <= Header::ENCODED_LEN
+                + committed_payload_capacity::<REGION_SIZE>(storage.metadata()).unwrap()
+        {
+            append_with_scratch!(log, &mut storage, &filler).unwrap();
+            frontier = log.memory.regions.last().copied().unwrap();
+        }
+        assert_eq!(frontier.region_index, first.region_index);
+        let mut spill = Vec::<ObjectLogHandle, 8>::new();
+        log.append_batch(&mut storage, &[&filler, &filler], &mut spill)
+            .unwrap();
+        assert_ne!(spill[0].region_index, first.region_index);
+        assert!(spill
+            .iter()
+            .all(|handle| handle.region_index == spill[0].region_index));
+        (log.collection_id(), first, batch, spill, filler)
+    };
+
+    let mut reopened =
+        Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
+            .unwrap();
+    let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    assert_get(&mut reopened_log, &mut reopened, first, b"zero");
+    for (handle, object) in batch.iter().zip(objects) {
+        assert_get(&mut reopened_log, &mut reopened, *handle, object);
+    }
+    let mut scratch = std::vec![0u8; filler.len()];
+    for handle in &spill {
+        assert_get_bytes(
+            &mut reopened_log,
+            &mut reopened,
+            *handle,
+            &filler,
+            &mut scratch,
+        );
+    }
+}
+
+//= spec/object-log.md#batch-append
+//= type=test
+//# `RING-OBJECT-037` Object-log batch append MUST reject batches containing an
+//# object that cannot be an inline record, batches whose encoded records exceed
+//# one ordinary data region, and batches with more objects than the caller's
+//# handle capacity, without making any batched object live.
+#[test]
+fn requirement_object_log_batch_append_rejects_invalid_batches() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        &mut flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut log = ObjectLog::new(&mut storage, &mut memory, LOG_METADATA).unwrap();
+    let large = patterned_vec(REGION_SIZE);
+    let chunk_capacity = log
+        .aux_geometry(storage.metadata())
+        .unwrap()
+        .chunk_logical_capacity;
+    let region_capacity = empty_region_record_capacity(
+        committed_payload_capacity::<REGION_SIZE>(storage.metadata()).unwrap(),
+        LOG_METADATA.len(),
+    )
+    .unwrap();
+    let medium = std::vec![0x11u8; chunk_capacity];
+    let overflow_count = region_capacity / inline_record_len(chunk_capacity).unwrap() + 1;
+    let overflow = std::vec![medium.as_slice(); overflow_count];
+    let mut handles = Vec::<ObjectLogHandle, 3>::new();
+    let mut roomy_handles = Vec::<ObjectLogHandle, 64>::new();
+
+    assert!(matches!(
+        log.append_batch(&mut storage, &[b"small", &large], &mut handles),
+        Err(ObjectLogError::BatchObjectNotInline { index: 1 })
+    ));
+    assert!(matches!(
+        log.append_batch(&mut storage, &overflow, &mut roomy_handles),
+        Err(ObjectLogError::ObjectTooLarge { .. })
+    ));
+    assert!(roomy_handles.is_empty());
+    assert!(matches!(
+        log.append_batch(&mut storage, &[b"a", b"b", b"c", b"d"], &mut handles),
+        Err(ObjectLogError::BufferTooSmall {
+            needed: 4,
+            available: 3
+        })
+    ));
+    assert!(handles.is_empty());
+    assert_eq!(log.first_handle(), None);
+
+    log.append_batch(&mut storage, &[b"a", b"b", b"c"], &mut handles)
+        .unwrap();
+    assert_eq!(log.first_handle(), Some(handles[0]));
+}
+
+//= spec/object-log.md#hash-chain
+//= type=test
+//# `RING-OBJECT-038` Hash-chain object logs MUST seal every committed public
+//# object with the SHA-256 digest of the previous seal, the object length, and the
+//# object bytes, MUST commit each seal atomically with its object, and MUST
+//# report the newest committed seal as the head digest across reopen.
+#[test]
+fn requirement_object_log_hash_chain_seals_objects_and_head() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let large = patterned_vec(REGION_SIZE * 2);
+    let (collection_id, first, last, head) = {
+        let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+            &mut flash,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+        let mut log = ObjectLog::new_hash_chained(&mut storage, &mut memory, LOG_METADATA).unwrap();
+        assert!(log.is_hash_chained());
+        assert_eq!(log.head_digest(), Some(CHAIN_GENESIS_DIGEST));
+
+        let first = append_with_scratch!(log, &mut storage, b"alpha").unwrap();
+        let mut expected = chain_digest(&CHAIN_GENESIS_DIGEST, b"alpha").unwrap();
+        assert_eq!(log.head_digest(), Some(expected));
+
+        let mut batch = Vec::<ObjectLogHandle, 2>::new();
+        log.append_batch(&mut storage, &[b"bravo", b"charlie"], &mut batch)
+            .unwrap();
+        expected = chain_digest(&expected, b"bravo").unwrap();
+        expected = chain_digest(&expected, b"charlie").unwrap();
+        assert_eq!(log.head_digest(), Some(expected));
+
+        let mut tx_memory = TransactionMemory::<REGION_COUNT>::new();
+        let mut tx = log
+            .begin_transaction_writer(&mut storage, &mut tx_memory)
+            .unwrap();
+        tx_append_with_scratch!(tx, &mut storage, b"rolled back").unwrap();
+        tx.rollback(&mut storage).unwrap();
+        assert_eq!(log.head_digest(), Some(expected));
+
+        log.flush(&mut storage).unwrap();
+        let last = append_with_scratch!(log, &mut storage, &large).unwrap();
+        expected = chain_digest(&expected, &large).unwrap();
+        assert_eq!(log.head_digest(), Some(expected));
+        assert_eq!(
+            log.next_handle(&mut storage, first).unwrap(),
+            Some(batch[0])
+        );
+
+        let mut scratch = [0u8; 16];
+        log.verify_chain(&mut storage, first, last, &mut scratch)
+            .unwrap();
+        log.verify_chain(&mut storage, batch[1], batch[1], &mut scratch)
+            .unwrap();
+        (log.collection_id(), first, last, expected)
+    };
+
+    let mut reopened =
+        Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
+            .unwrap();
+    let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    assert!(reopened_log.is_hash_chained());
+    assert_eq!(reopened_log.head_digest(), Some(head));
+    let mut scratch = [0u8; 16];
+    reopened_log
+        .verify_chain(&mut reopened, first, last, &mut scratch)
+        .unwrap();
+    let next = append_with_scratch!(reopened_log, &mut reopened, b"delta").unwrap();
+    assert_eq!(
+        reopened_log.head_digest(),
+        Some(chain_digest(&head, b"delta").unwrap())
+    );
+    reopened_log
+        .verify_chain(&mut reopened, first, next, &mut scratch)
+        .unwrap();
+}
+
+//= spec/object-log.md#hash-chain
+//= type=test
+//# `RING-OBJECT-039` Hash-chain truncation MUST durably record the seal of the
+//# last discarded object as the chain anchor, and chain verification MUST start
+//# from that anchor at the live head.
+#[test]
+fn requirement_object_log_hash_chain_truncation_records_anchor() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let (collection_id, retained, anchor) = {
+        let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+            &mut flash,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+        let mut log = ObjectLog::new_hash_chained(&mut storage, &mut memory, LOG_METADATA).unwrap();
+        append_with_scratch!(log, &mut storage, b"one").unwrap();
+        append_with_scratch!(log, &mut storage, b"two").unwrap();
+        log.flush(&mut storage).unwrap();
+        let retained = append_with_scratch!(log, &mut storage, b"three").unwrap();
+        let anchor = chain_digest(
+            &chain_digest(&CHAIN_GENESIS_DIGEST, b"one").unwrap(),
+            b"two",
+        )
+        .unwrap();
+
+        log.truncate_before(&mut storage, retained).unwrap();
+        assert_eq!(log.first_handle(), Some(retained));
+        assert_eq!(log.memory.chain_anchor, anchor);
+        assert_eq!(
+            log.head_digest(),
+            Some(chain_digest(&anchor, b"three").unwrap())
+        );
+        let mut scratch = [0u8; 8];
+        log.verify_chain(&mut storage, retained, retained, &mut scratch)
+            .unwrap();
+        (log.collection_id(), retained, anchor)
+    };
+
+    let mut reopened =
+        Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
+            .unwrap();
+    let mut reopened_memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut reopened_log =
+        ObjectLog::open(collection_id, &mut reopened, &mut reopened_memory).unwrap();
+    assert_eq!(reopened_log.memory.chain_anchor, anchor);
+    let mut scratch = [0u8; 8];
+    reopened_log
+        .verify_chain(&mut reopened, retained, retained, &mut scratch)
+        .unwrap();
+
+    reopened_log.flush(&mut reopened).unwrap();
+    let newest = append_with_scratch!(reopened_log, &mut reopened, b"four").unwrap();
+    reopened_log.flush(&mut reopened).unwrap();
+    reopened_log.truncate_before(&mut reopened, newest).unwrap();
+    let three = chain_digest(&anchor, b"three").unwrap();
+    assert_eq!(reopened_log.memory.chain_anchor, three);
+    reopened_log
+        .verify_chain(&mut reopened, newest, newest, &mut scratch)
+        .unwrap();
+}
+
+//= spec/object-log.md#hash-chain
+//= type=test
+//# `RING-OBJECT-040` Chain verification MUST recompute every seal in the
+//# requested live range, MUST reject a range whose object bytes or seals do not
+//# match, MUST check the newest object against the head digest, and MUST be
+//# refused for logs created without a hash chain.
+#[test]
+fn requirement_object_log_hash_chain_verification_detects_tampering() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 64;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        &mut flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let mut scratch = [0u8; 16];
+
+    let mut plain_memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut plain = ObjectLog::new(&mut storage, &mut plain_memory, LOG_METADATA).unwrap();
+    let plain_handle = append_with_scratch!(plain, &mut storage, b"plain").unwrap();
+    assert!(!plain.is_hash_chained());
+    assert_eq!(plain.head_digest(), None);
+    assert!(matches!(
+        plain.verify_chain(&mut storage, plain_handle, plain_handle, &mut scratch),
+        Err(ObjectLogError::HashChainDisabled)
+    ));
+
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut log = ObjectLog::new_hash_chained(&mut storage, &mut memory, LOG_METADATA).unwrap();
+    let first = append_with_scratch!(log, &mut storage, b"first").unwrap();
+    let middle = append_with_scratch!(log, &mut storage, b"middle").unwrap();
+    let last = append_with_scratch!(log, &mut storage, b"last").unwrap();
+    log.verify_chain(&mut storage, first, last, &mut scratch)
+        .unwrap();
+
+    let record_start = payload_offset(middle.offset).unwrap();
+    let record_end = record_start + inline_record_len(b"middle".len()).unwrap();
+    encode_inline_record(
+        b"MIDDLE",
+        &mut log.memory.frontier_payload[record_start..record_end],
+    )
+    .unwrap();
+    assert_get(&mut log, &mut storage, middle, b"MIDDLE");
+    assert!(matches!(
+        log.verify_chain(&mut storage, first, last, &mut scratch),
+        Err(ObjectLogError::HashChainMismatch { handle }) if handle == middle
+    ));
+    log.verify_chain(&mut storage, last, last, &mut scratch)
+        .unwrap();
+
+    log.memory.chain_committed_head = CHAIN_GENESIS_DIGEST;
+    assert!(matches!(
+        log.verify_chain(&mut storage, last, last, &mut scratch),
+        Err(ObjectLogError::HashChainMismatch { handle }) if handle == last
+    ));
+    assert!(matches!(
+        log.verify_chain(&mut storage, last, first, &mut scratch),
+        Err(ObjectLogError::InvalidHandle)
+    ));
+}
+
+//= spec/wear.md#bad-regions
+//= type=test
+//# `RING-WEAR-015` When writing an object-log data region fails with
+//# `BadRegion`, the flush MUST write its payload to a freshly reserved region
+//# within a collection transaction, name that region for the data region's
+//# sequence, and free the retired region; handles returned before the failure
+//# MUST still read their objects after reopen.
+#[test]
+fn requirement_object_log_flush_relocates_past_bad_region() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 32;
+
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 32768>::new(0xff);
+    let (collection_id, handles, batch, filler) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+        let mut log = ObjectLog::new(&mut storage, &mut memory, LOG_METADATA).unwrap();
+
+        // A plain flush writes the frontier outside any transaction.
+        let first = append_with_scratch!(log, &mut storage, b"first").unwrap();
+        let bad = log.memory.regions.last().unwrap().region_index;
+        storage
+            .with_io_workspace(|flash, _| flash.fail_region(bad))
+            .unwrap();
+        log.flush(&mut storage).unwrap();
+        assert_ne!(log.memory.regions.last().unwrap().region_index, bad);
+        assert!(storage.is_region_retired(bad));
+        assert!(storage.runtime().free_space().contains_free_region(bad));
+
+        // A batch that needs a new region materializes the frontier inside
+        // the batch transaction.
+        let second = append_with_scratch!(log, &mut storage, b"second").unwrap();
+        let chunk_capacity = log
+            .aux_geometry(storage.metadata())
+            .unwrap()
+            .chunk_logical_capacity;
+        let filler = std::vec![0x5au8; chunk_capacity];
+        while log.memory.regions.last().unwrap().end_offset as usize
+            + 2 * inline_record_len(filler.len()).unwrap()
+            <= Header::ENCODED_LEN
+                + committed_payload_capacity::<REGION_SIZE>(storage.metadata()).unwrap()
+        {
+            append_with_scratch!(log, &mut storage, &filler).unwrap();
+        }
+        let bad = log.memory.regions.last().unwrap().region_index;
+        storage
+            .with_io_workspace(|flash, _| flash.fail_region(bad))
+            .unwrap();
+        let mut batch = Vec::<ObjectLogHandle, 2>::new();
+        log.append_batch(&mut storage, &[&filler, &filler], &mut batch)
+            .unwrap();
+        assert!(log
+            .memory
+            .regions
+            .iter()
+            .all(|region| region.region_index != bad));
+        assert!(storage.is_region_retired(bad));
+        assert!(storage.runtime().free_space().contains_free_region(bad));
+        assert_eq!(storage.wear_report().retired_regions, 2);
+        (log.collection_id(), [first, second], batch, filler)
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 8, 16>::new();
+    let mut log = ObjectLog::open(collection_id, &mut storage, &mut memory).unwrap();
+    assert_get(&mut log, &mut storage, handles[0], b"first");
+    assert_get(&mut log, &mut storage, handles[1], b"second");
+    for handle in batch {
+        let mut scratch = [0u8; REGION_SIZE];
+        log.get(&mut storage, handle, &mut scratch, |bytes| {
+            assert_eq!(bytes, filler.as_slice());
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,1252 @@
+//! Durable FIFO queue collection APIs.
+
+use core::marker::PhantomData;
+use core::mem::size_of;
+
+use crc::{Crc, CRC_32_ISCSI};
+use heapless::Vec;
+use postcard::{from_bytes, to_slice};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::disk::Header;
+use crate::flash_io::FlashIo;
+use crate::mode::{CollectionFlushMode, CollectionUpdateMode, ReadMode, StorageMode};
+use crate::startup::StartupCollectionBasis;
+use crate::storage::{StorageRuntimeError, StorageVisitError};
+use crate::wal_record::WalRecord;
+use crate::{Collection, CollectionId, CollectionType, Storage, StorageMetadata};
+
+#[cfg(test)]
+mod tests;
+
+/// Committed-region format code for sealed queue segments.
+pub const QUEUE_SEGMENT_V1_FORMAT: u16 = 10;
+
+const SEGMENT_MAGIC: [u8; 4] = *b"DQSG";
+const SEGMENT_VERSION: u16 = 1;
+const SEGMENT_PROLOGUE_LEN: usize = SEGMENT_MAGIC.len() + size_of::<u16>() + size_of::<u32>();
+const SEGMENT_ITEMS_START: usize = Header::ENCODED_LEN + SEGMENT_PROLOGUE_LEN;
+const SEGMENT_CHECKSUM_LEN: usize = size_of::<u32>();
+const ITEM_HEADER_LEN: usize = size_of::<u32>();
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+const SNAPSHOT_MAGIC: [u8; 4] = *b"DQSN";
+const SNAPSHOT_VERSION: u16 = 1;
+const SNAPSHOT_FIXED_LEN: usize = SNAPSHOT_MAGIC.len()
+    + size_of::<u16>()
+    + size_of::<u64>()
+    + 2 * size_of::<u32>()
+    + size_of::<u32>();
+const EMPTY_SNAPSHOT: [u8; SNAPSHOT_FIXED_LEN] = [
+    b'D', b'Q', b'S', b'N', 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+const UPDATE_PUSH: u8 = 1;
+const UPDATE_POP: u8 = 2;
+const UPDATE_SEAL: u8 = 3;
+
+/// Errors returned by [`DurableQueue`].
+#[derive(Debug)]
+pub enum DurableQueueError {
+    /// Shared storage failed.
+    Storage(StorageRuntimeError),
+    /// The collection does not exist.
+    UnknownCollection(CollectionId),
+    /// The collection type did not match queue.
+    CollectionTypeMismatch {
+        collection_id: CollectionId,
+        actual: Option<u16>,
+    },
+    /// The collection was dropped.
+    DroppedCollection(CollectionId),
+    /// Encoded queue data was malformed.
+    InvalidEncoding,
+    /// Serialization or decode of an item failed.
+    SerializationError,
+    /// An encoded item does not fit in an empty queue segment.
+    ItemTooLarge { capacity: usize },
+    /// Sealing another segment would exceed the configured memory.
+    TooManySegments,
+    /// Checked arithmetic overflowed.
+    LengthOverflow,
+}
+
+impl From<StorageRuntimeError> for DurableQueueError {
+    fn from(error: StorageRuntimeError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+impl From<crate::StartupError> for DurableQueueError {
+    fn from(error: crate::StartupError) -> Self {
+        Self::Storage(error.into())
+    }
+}
+
+impl From<postcard::Error> for DurableQueueError {
+    fn from(_: postcard::Error) -> Self {
+        Self::SerializationError
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueueSegment {
+    region_index: u32,
+    item_count: u32,
+}
+
+#[derive(Clone, Copy)]
+enum QueueTransactionUpdate {
+    Pop { record_len: u32 },
+    Seal { region_index: u32 },
+}
+
+struct QueueReplayTransaction {
+    transaction_log_id: u32,
+    joined: bool,
+    update: Option<QueueTransactionUpdate>,
+}
+
+/// Caller-owned memory for a [`DurableQueue`].
+///
+/// Items pushed since the last sealed segment are buffered in a region-sized
+/// frontier, and `MAX_SEGMENTS` bounds how many sealed segments can hold
+/// unconsumed items at once.
+pub struct DurableQueueMemory<const REGION_SIZE: usize, const MAX_SEGMENTS: usize = 16> {
+    segments: Vec<QueueSegment, MAX_SEGMENTS>,
+    head_consumed: u32,
+    head_offset: usize,
+    frontier: [u8; REGION_SIZE],
+    frontier_len: usize,
+    frontier_count: u32,
+    len: u64,
+}
+
+impl<const REGION_SIZE: usize, const MAX_SEGMENTS: usize>
+    DurableQueueMemory<REGION_SIZE, MAX_SEGMENTS>
+{
+    /// Allocates empty queue memory.
+    pub fn new() -> Self {
+        Self {
+            segments: Vec::new(),
+            head_consumed: 0,
+            head_offset: 0,
+            frontier: [0; REGION_SIZE],
+            frontier_len: 0,
+            frontier_count: 0,
+            len: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.segments.clear();
+        self.head_consumed = 0;
+        self.head_offset = 0;
+        self.frontier.fill(0);
+        self.frontier_len = 0;
+        self.frontier_count = 0;
+        self.len = 0;
+    }
+}
+
+impl<const REGION_SIZE: usize, const MAX_SEGMENTS: usize> Default
+    for DurableQueueMemory<REGION_SIZE, MAX_SEGMENTS>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Durable FIFO queue of postcard-encoded `T` items.
+///
+/// Pushes and pops are WAL updates, so a popped item never reappears after
+/// reset. Pushed items are sealed into committed segment regions as the
+/// frontier fills, and a segment region is freed in the same transaction as
+/// the pop that consumes its last item.
+pub struct DurableQueue<'mem, T, const REGION_SIZE: usize, const MAX_SEGMENTS: usize = 16> {
+    collection_id: CollectionId,
+    memory: &'mem mut DurableQueueMemory<REGION_SIZE, MAX_SEGMENTS>,
+    relocation_epoch: u64,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T, const REGION_SIZE: usize, const MAX_SEGMENTS: usize> Collection
+    for DurableQueue<'_, T, REGION_SIZE, MAX_SEGMENTS>
+{
+    fn id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    fn collection_type(&self) -> CollectionType {
+        CollectionType::Queue
+    }
+}
+
+impl<'mem, T, const REGION_SIZE: usize, const MAX_SEGMENTS: usize>
+    DurableQueue<'mem, T, REGION_SIZE, MAX_SEGMENTS>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Creates a new empty queue collection.
+    pub fn new<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut DurableQueueMemory<REGION_SIZE, MAX_SEGMENTS>,
+    ) -> Result<Self, DurableQueueError> {
+        let collection_id = storage.allocate_collection_id()?;
+        memory.clear();
+        storage.append_new_collection(collection_id, CollectionType::QUEUE_CODE)?;
+        Ok(Self {
+            collection_id,
+            memory,
+            relocation_epoch: storage.runtime().relocation_epoch(),
+            item: PhantomData,
+        })
+    }
+
+    /// Opens an existing queue collection.
+    pub fn open<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut DurableQueueMemory<REGION_SIZE, MAX_SEGMENTS>,
+    ) -> Result<Self, DurableQueueError> {
+        validate_collection::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage,
+            collection_id,
+        )?;
+        memory.clear();
+        let mut queue = Self {
+            collection_id,
+            memory,
+            relocation_epoch: storage.runtime().relocation_epoch(),
+            item: PhantomData,
+        };
+        queue.replay(storage)?;
+        Ok(queue)
+    }
+
+    /// Returns the stable collection id.
+    pub fn collection_id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    /// Returns the number of items in the queue.
+    pub fn len(&self) -> u64 {
+        self.memory.len
+    }
+
+    /// Returns whether the queue holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.memory.len == 0
+    }
+
+    /// Appends `item` to the back of the queue.
+    ///
+    /// A frontier that cannot hold the encoded item is sealed into a segment
+    /// region first.
+    pub fn push<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        item: &T,
+    ) -> Result<(), DurableQueueError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.push_inner(storage, item);
+        storage.finish_mode();
+        result
+    }
+
+    /// Returns the item at the front of the queue without removing it.
+    pub fn peek<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Option<T>, DurableQueueError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self
+            .read_head(storage)
+            .map(|head| head.map(|(item, _)| item));
+        storage.finish_mode();
+        result
+    }
+
+    /// Removes and returns the item at the front of the queue.
+    ///
+    /// The pop is durable before this returns. Popping the last item of a
+    /// sealed segment frees that segment region.
+    pub fn pop<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Option<T>, DurableQueueError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.pop_inner(storage);
+        storage.finish_mode();
+        result
+    }
+
+    /// Seals buffered items into a segment region and records the queue
+    /// state in a WAL snapshot.
+    pub fn flush<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), DurableQueueError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::FlushingCollection(
+            CollectionFlushMode::CommitRegion,
+        ))?;
+        let result = self.seal_inner(storage);
+        storage.finish_mode();
+        result
+    }
+
+    /// Replays the queue again when a wear-leveling pass moved one of its
+    /// segments since this handle last replayed.
+    fn refresh<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), DurableQueueError> {
+        let epoch = storage.runtime().relocation_epoch();
+        if self.relocation_epoch != epoch {
+            self.memory.clear();
+            self.replay(storage)?;
+            self.relocation_epoch = epoch;
+        }
+        Ok(())
+    }
+
+    fn push_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        item: &T,
+    ) -> Result<(), DurableQueueError> {
+        let capacity = segment_capacity::<REGION_SIZE>(storage.metadata())?;
+        let encoded_len = match self.encode_into_frontier(item, capacity) {
+            Ok(len) => len,
+            Err(DurableQueueError::ItemTooLarge { .. }) if self.memory.frontier_count != 0 => {
+                self.seal_inner(storage)?;
+                self.encode_into_frontier(item, capacity)?
+            }
+            Err(error) => return Err(error),
+        };
+
+        let item_start = self.memory.frontier_len + ITEM_HEADER_LEN;
+        let item_bytes = self
+            .memory
+            .frontier
+            .get(item_start..item_start + encoded_len)
+            .ok_or(DurableQueueError::LengthOverflow)?;
+        let offset = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_PUSH)?;
+        let used = write_bytes(&mut storage.memory.payload_scratch, offset, item_bytes)?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        self.commit_frontier_item(encoded_len)
+    }
+
+    /// Encodes `item` after the buffered frontier items without making it
+    /// part of the frontier yet.
+    fn encode_into_frontier(
+        &mut self,
+        item: &T,
+        capacity: usize,
+    ) -> Result<usize, DurableQueueError> {
+        let item_start = self
+            .memory
+            .frontier_len
+            .checked_add(ITEM_HEADER_LEN)
+            .ok_or(DurableQueueError::LengthOverflow)?;
+        let target = self
+            .memory
+            .frontier
+            .get_mut(item_start..capacity)
+            .ok_or(DurableQueueError::ItemTooLarge { capacity })?;
+        match to_slice(item, target) {
+            Ok(encoded) => Ok(encoded.len()),
+            Err(postcard::Error::SerializeBufferFull) => {
+                Err(DurableQueueError::ItemTooLarge { capacity })
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn commit_frontier_item(&mut self, encoded_len: usize) -> Result<(), DurableQueueError> {
+        let len = u32::try_from(encoded_len).map_err(|_| DurableQueueError::LengthOverflow)?;
+        let end = write_u32(&mut self.memory.frontier, self.memory.frontier_len, len)?
+            .checked_add(encoded_len)
+            .ok_or(DurableQueueError::LengthOverflow)?;
+        self.memory.frontier_len = end;
+        self.memory.frontier_count = self
+            .memory
+            .frontier_count
+            .checked_add(1)
+            .ok_or(DurableQueueError::LengthOverflow)?;
+        self.memory.len = self
+            .memory
+            .len
+            .checked_add(1)
+            .ok_or(DurableQueueError::LengthOverflow)?;
+        Ok(())
+    }
+
+    fn pop_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Option<T>, DurableQueueError> {
+        let Some((item, record_len)) = self.read_head(storage)? else {
+            return Ok(None);
+        };
+        let mut used = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_POP)?;
+        used = write_u32(&mut storage.memory.payload_scratch, used, record_len)?;
+
+        let consumed_segment = self
+            .memory
+            .segments
+            .first()
+            .copied()
+            .filter(|segment| self.memory.head_consumed + 1 == segment.item_count);
+        match consumed_segment {
+            None => {
+                storage
+                    .memory
+                    .state
+                    .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                        storage.backing,
+                        &mut storage.memory.workspace,
+                        self.collection_id,
+                        &storage.memory.payload_scratch[..used],
+                    )?;
+            }
+            Some(segment) => {
+                self.pop_and_free_segment(storage, used, segment.region_index)?;
+            }
+        }
+        self.apply_pop(record_len)?;
+        Ok(Some(item))
+    }
+
+    /// Writes a pop that consumes the last item of a sealed segment and frees
+    /// that segment in the same collection transaction.
+    fn pop_and_free_segment<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        used: usize,
+        region_index: u32,
+    ) -> Result<(), DurableQueueError> {
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        storage
+            .memory
+            .state
+            .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                region_index,
+            )?;
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        Ok(())
+    }
+
+    /// Decodes the item at the front of the queue and returns it with its
+    /// encoded record length.
+    fn read_head<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Option<(T, u32)>, DurableQueueError> {
+        if self.memory.len == 0 {
+            return Ok(None);
+        }
+        let Some(segment) = self.memory.segments.first() else {
+            let mut offset = 0usize;
+            let item_len = usize::try_from(read_u32(&self.memory.frontier, &mut offset)?)
+                .map_err(|_| DurableQueueError::LengthOverflow)?;
+            let bytes = read_bytes(
+                &self.memory.frontier[..self.memory.frontier_len],
+                &mut offset,
+                item_len,
+            )?;
+            let item = from_bytes(bytes)?;
+            let record_len =
+                u32::try_from(offset).map_err(|_| DurableQueueError::LengthOverflow)?;
+            return Ok(Some((item, record_len)));
+        };
+
+        let flash = &mut *storage.backing;
+        let header = flash
+            .read_region(segment.region_index, 0, Header::ENCODED_LEN, Header::decode)
+            .map_err(StorageRuntimeError::from)?
+            .map_err(|_| DurableQueueError::InvalidEncoding)?;
+        if header.collection_id != self.collection_id
+            || header.collection_format != QUEUE_SEGMENT_V1_FORMAT
+        {
+            return Err(DurableQueueError::InvalidEncoding);
+        }
+        let item_offset = SEGMENT_ITEMS_START
+            .checked_add(self.memory.head_offset)
+            .ok_or(DurableQueueError::LengthOverflow)?;
+        let item_len = flash
+            .read_region(
+                segment.region_index,
+                item_offset,
+                ITEM_HEADER_LEN,
+                |bytes| read_u32(bytes, &mut 0),
+            )
+            .map_err(StorageRuntimeError::from)??;
+        let item_start = item_offset + ITEM_HEADER_LEN;
+        let item_len_usize =
+            usize::try_from(item_len).map_err(|_| DurableQueueError::LengthOverflow)?;
+        if item_start
+            .checked_add(item_len_usize)
+            .is_none_or(|end| end > REGION_SIZE)
+        {
+            return Err(DurableQueueError::InvalidEncoding);
+        }
+        let item = flash
+            .read_region(segment.region_index, item_start, item_len_usize, |bytes| {
+                from_bytes::<T>(bytes)
+            })
+            .map_err(StorageRuntimeError::from)??;
+        let record_len = item_len
+            .checked_add(ITEM_HEADER_LEN as u32)
+            .ok_or(DurableQueueError::LengthOverflow)?;
+        Ok(Some((item, record_len)))
+    }
+
+    fn seal_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), DurableQueueError> {
+        if self.memory.frontier_count == 0 {
+            return Ok(());
+        }
+        if self.memory.segments.is_full() {
+            return Err(DurableQueueError::TooManySegments);
+        }
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        let mut allocated = None;
+        let region_index = match self.seal_transactional(storage, &mut allocated) {
+            Ok(region_index) => region_index,
+            Err(error) => {
+                return match self.rollback_seal(storage, allocated) {
+                    Ok(()) => Err(error),
+                    Err(cleanup_error) => Err(cleanup_error),
+                };
+            }
+        };
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        self.apply_seal(region_index)?;
+
+        let used = self.encode_snapshot(&mut storage.memory.payload_scratch)?;
+        storage
+            .memory
+            .state
+            .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                CollectionType::QUEUE_CODE,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        Ok(())
+    }
+
+    fn seal_transactional<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        allocated: &mut Option<u32>,
+    ) -> Result<u32, DurableQueueError> {
+        let region_index = storage
+            .memory
+            .state
+            .reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &mut storage.memory.reclaim_source_regions,
+                &mut storage.memory.active_collections,
+                &mut storage.memory.reclaim_plan,
+                &mut storage.memory.open_plan,
+            )?;
+        *allocated = Some(region_index);
+
+        let mut used = write_bytes(&mut storage.memory.payload_scratch, 0, &SEGMENT_MAGIC)?;
+        used = write_u16(&mut storage.memory.payload_scratch, used, SEGMENT_VERSION)?;
+        used = write_u32(
+            &mut storage.memory.payload_scratch,
+            used,
+            self.memory.frontier_count,
+        )?;
+        used = write_bytes(
+            &mut storage.memory.payload_scratch,
+            used,
+            &self.memory.frontier[..self.memory.frontier_len],
+        )?;
+        let checksum = crc32(&storage.memory.payload_scratch[..used]);
+        used = write_u32(&mut storage.memory.payload_scratch, used, checksum)?;
+        let region_index = storage
+            .memory
+            .state
+            .write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                region_index,
+                self.collection_id,
+                QUEUE_SEGMENT_V1_FORMAT,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        *allocated = Some(region_index);
+
+        let mut used = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_SEAL)?;
+        used = write_u32(&mut storage.memory.payload_scratch, used, region_index)?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        Ok(region_index)
+    }
+
+    fn rollback_seal<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        allocated: Option<u32>,
+    ) -> Result<(), DurableQueueError> {
+        let mut first_error = None::<DurableQueueError>;
+        if let Err(error) = storage
+            .memory
+            .state
+            .rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )
+        {
+            first_error = Some(error.into());
+        }
+        if let Some(region_index) = allocated {
+            if let Err(error) = storage
+                .memory
+                .state
+                .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    CollectionId(0),
+                    region_index,
+                )
+            {
+                if first_error.is_none() {
+                    first_error = Some(error.into());
+                }
+            }
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn replay<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), DurableQueueError> {
+        let collection_id = self.collection_id;
+        let mut transaction = None::<QueueReplayTransaction>;
+        // WAL head reclaim drops a superseded snapshot but keeps the updates
+        // that followed it, so updates before the first retained basis are
+        // skipped.
+        let mut based = !storage
+            .memory
+            .state
+            .wal_has_collection_basis::<REGION_SIZE, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+                CollectionType::QUEUE_CODE,
+            )?;
+        let result =
+            storage
+                .memory
+                .state
+                .visit_wal_records::<REGION_SIZE, IO, DurableQueueError, _>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    |_flash, record| {
+                        match record {
+                            WalRecord::NewCollection {
+                                collection_id: seen,
+                                collection_type,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::QUEUE_CODE =>
+                            {
+                                based = true;
+                                self.memory.clear();
+                            }
+                            WalRecord::BeginTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                transaction = Some(QueueReplayTransaction {
+                                    transaction_log_id,
+                                    joined: transaction_log_id == 0,
+                                    update: None,
+                                });
+                            }
+                            WalRecord::AddTransactionCollection {
+                                collection_id: seen,
+                                ..
+                            } if seen == collection_id => {
+                                if let Some(open) = transaction.as_mut() {
+                                    open.joined = true;
+                                }
+                            }
+                            WalRecord::Snapshot {
+                                collection_id: seen,
+                                collection_type,
+                                payload,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::QUEUE_CODE =>
+                            {
+                                based = true;
+                                self.apply_snapshot(payload)?;
+                            }
+                            WalRecord::Update {
+                                collection_id: seen,
+                                payload,
+                            } if seen == collection_id && based => match transaction.as_mut() {
+                                Some(open) if open.joined => {
+                                    // Queue transactions carry exactly one pop or
+                                    // seal.
+                                    if open.update.is_some() {
+                                        return Err(DurableQueueError::InvalidEncoding);
+                                    }
+                                    open.update = Some(decode_transaction_update(payload)?);
+                                }
+                                _ => self.apply_update_payload(payload)?,
+                            },
+                            WalRecord::CommitTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    if let Some(update) =
+                                        transaction.take().and_then(|open| open.update)
+                                    {
+                                        self.apply_transaction_update(update)?;
+                                    }
+                                }
+                            }
+                            WalRecord::RollbackTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    transaction = None;
+                                }
+                            }
+                            WalRecord::DropCollection {
+                                collection_id: seen,
+                            } if seen == collection_id => {
+                                self.memory.clear();
+                            }
+                            _ => {}
+                        }
+                        Ok(())
+                    },
+                );
+        match result {
+            Ok(()) => Ok(()),
+            Err(StorageVisitError::Storage(error)) => Err(DurableQueueError::Storage(error)),
+            Err(StorageVisitError::Visitor(error)) => Err(error),
+        }
+    }
+
+    fn apply_update_payload(&mut self, payload: &[u8]) -> Result<(), DurableQueueError> {
+        let mut offset = 0usize;
+        if read_u8(payload, &mut offset)? == UPDATE_PUSH {
+            let item = payload
+                .get(offset..)
+                .ok_or(DurableQueueError::InvalidEncoding)?;
+            let start = self
+                .memory
+                .frontier_len
+                .checked_add(ITEM_HEADER_LEN)
+                .ok_or(DurableQueueError::LengthOverflow)?;
+            write_bytes(&mut self.memory.frontier, start, item)
+                .map_err(|_| DurableQueueError::InvalidEncoding)?;
+            return self.commit_frontier_item(item.len());
+        }
+        // Reclaim can copy a committed pop or seal out of its transaction, so
+        // they may also appear as plain updates.
+        self.apply_transaction_update(decode_transaction_update(payload)?)
+    }
+
+    fn apply_transaction_update(
+        &mut self,
+        update: QueueTransactionUpdate,
+    ) -> Result<(), DurableQueueError> {
+        match update {
+            QueueTransactionUpdate::Pop { record_len } => self.apply_pop(record_len),
+            QueueTransactionUpdate::Seal { region_index } => self.apply_seal(region_index),
+        }
+    }
+
+    fn apply_pop(&mut self, record_len: u32) -> Result<(), DurableQueueError> {
+        let record_len =
+            usize::try_from(record_len).map_err(|_| DurableQueueError::LengthOverflow)?;
+        if self.memory.len == 0 || record_len < ITEM_HEADER_LEN {
+            return Err(DurableQueueError::InvalidEncoding);
+        }
+        if let Some(segment) = self.memory.segments.first().copied() {
+            self.memory.head_offset = self
+                .memory
+                .head_offset
+                .checked_add(record_len)
+                .ok_or(DurableQueueError::LengthOverflow)?;
+            self.memory.head_consumed += 1;
+            if self.memory.head_consumed == segment.item_count {
+                self.memory.segments.remove(0);
+                self.memory.head_consumed = 0;
+                self.memory.head_offset = 0;
+            }
+        } else {
+            let stored_len = read_u32(&self.memory.frontier, &mut 0)?;
+            if self.memory.frontier_count == 0
+                || usize::try_from(stored_len).ok() != record_len.checked_sub(ITEM_HEADER_LEN)
+                || record_len > self.memory.frontier_len
+            {
+                return Err(DurableQueueError::InvalidEncoding);
+            }
+            self.memory
+                .frontier
+                .copy_within(record_len..self.memory.frontier_len, 0);
+            self.memory.frontier_len -= record_len;
+            self.memory.frontier_count -= 1;
+        }
+        self.memory.len -= 1;
+        Ok(())
+    }
+
+    fn apply_seal(&mut self, region_index: u32) -> Result<(), DurableQueueError> {
+        if self.memory.frontier_count == 0 {
+            return Err(DurableQueueError::InvalidEncoding);
+        }
+        self.memory
+            .segments
+            .push(QueueSegment {
+                region_index,
+                item_count: self.memory.frontier_count,
+            })
+            .map_err(|_| DurableQueueError::TooManySegments)?;
+        self.memory.frontier_len = 0;
+        self.memory.frontier_count = 0;
+        Ok(())
+    }
+
+    fn encode_snapshot(&self, output: &mut [u8]) -> Result<usize, DurableQueueError> {
+        // Snapshots are only written right after a seal, so the frontier is
+        // always empty and every item lives in a segment.
+        if self.memory.frontier_count != 0 {
+            return Err(DurableQueueError::InvalidEncoding);
+        }
+        let head_offset = u32::try_from(self.memory.head_offset)
+            .map_err(|_| DurableQueueError::LengthOverflow)?;
+        let segment_count = u32::try_from(self.memory.segments.len())
+            .map_err(|_| DurableQueueError::LengthOverflow)?;
+        let mut offset = write_bytes(output, 0, &SNAPSHOT_MAGIC)?;
+        offset = write_u16(output, offset, SNAPSHOT_VERSION)?;
+        offset = write_u64(output, offset, self.memory.len)?;
+        offset = write_u32(output, offset, self.memory.head_consumed)?;
+        offset = write_u32(output, offset, head_offset)?;
+        offset = write_u32(output, offset, segment_count)?;
+        for segment in self.memory.segments.iter() {
+            offset = write_u32(output, offset, segment.region_index)?;
+            offset = write_u32(output, offset, segment.item_count)?;
+        }
+        Ok(offset)
+    }
+
+    fn apply_snapshot(&mut self, payload: &[u8]) -> Result<(), DurableQueueError> {
+        let mut offset = 0usize;
+        if read_bytes(payload, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+            || read_u16(payload, &mut offset)? != SNAPSHOT_VERSION
+        {
+            return Err(DurableQueueError::InvalidEncoding);
+        }
+        self.memory.clear();
+        let len = read_u64(payload, &mut offset)?;
+        let head_consumed = read_u32(payload, &mut offset)?;
+        let head_offset = usize::try_from(read_u32(payload, &mut offset)?)
+            .map_err(|_| DurableQueueError::LengthOverflow)?;
+        let segment_count = read_u32(payload, &mut offset)?;
+        let mut items = 0u64;
+        for _ in 0..segment_count {
+            let segment = QueueSegment {
+                region_index: read_u32(payload, &mut offset)?,
+                item_count: read_u32(payload, &mut offset)?,
+            };
+            if segment.item_count == 0 {
+                return Err(DurableQueueError::InvalidEncoding);
+            }
+            items += u64::from(segment.item_count);
+            self.memory
+                .segments
+                .push(segment)
+                .map_err(|_| DurableQueueError::TooManySegments)?;
+        }
+        let head_valid = match self.memory.segments.first() {
+            Some(segment) => head_consumed < segment.item_count,
+            None => head_consumed == 0 && head_offset == 0,
+        };
+        if !head_valid
+            || items.checked_sub(u64::from(head_consumed)) != Some(len)
+            || offset != payload.len()
+        {
+            return Err(DurableQueueError::InvalidEncoding);
+        }
+        self.memory.len = len;
+        self.memory.head_consumed = head_consumed;
+        self.memory.head_offset = head_offset;
+        Ok(())
+    }
+}
+
+fn validate_collection<
+    IO: FlashIo,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+) -> Result<(), DurableQueueError> {
+    let collection = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .ok_or(DurableQueueError::UnknownCollection(collection_id))?;
+    if collection.basis() == StartupCollectionBasis::Dropped {
+        return Err(DurableQueueError::DroppedCollection(collection_id));
+    }
+    storage.runtime().ensure_not_quarantined(collection_id)?;
+    if collection.collection_type() != Some(CollectionType::QUEUE_CODE) {
+        return Err(DurableQueueError::CollectionTypeMismatch {
+            collection_id,
+            actual: collection.collection_type(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn empty_snapshot() -> &'static [u8] {
+    &EMPTY_SNAPSHOT
+}
+
+/// Returns the offset of the `u32` naming segment `region_index` in an
+/// encoded snapshot, or `None` when the snapshot does not list it.
+pub(crate) fn snapshot_segment_offset(
+    snapshot: &[u8],
+    region_index: u32,
+) -> Result<Option<usize>, DurableQueueError> {
+    let mut offset = 0usize;
+    if read_bytes(snapshot, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+        || read_u16(snapshot, &mut offset)? != SNAPSHOT_VERSION
+    {
+        return Err(DurableQueueError::InvalidEncoding);
+    }
+    let _len = read_u64(snapshot, &mut offset)?;
+    let _head_consumed = read_u32(snapshot, &mut offset)?;
+    let _head_offset = read_u32(snapshot, &mut offset)?;
+    let segment_count = read_u32(snapshot, &mut offset)?;
+    for _ in 0..segment_count {
+        let field = offset;
+        let segment = read_u32(snapshot, &mut offset)?;
+        let _item_count = read_u32(snapshot, &mut offset)?;
+        if segment == region_index {
+            return Ok(Some(field));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the frontier bytes one segment region can hold.
+fn segment_capacity<const REGION_SIZE: usize>(
+    metadata: StorageMetadata,
+) -> Result<usize, DurableQueueError> {
+    let granule = usize::try_from(metadata.wal_write_granule)
+        .map_err(|_| DurableQueueError::LengthOverflow)?;
+    if granule == 0 {
+        return Err(DurableQueueError::InvalidEncoding);
+    }
+    let capacity = (REGION_SIZE - REGION_SIZE % granule)
+        .checked_sub(SEGMENT_ITEMS_START + SEGMENT_CHECKSUM_LEN)
+        .ok_or(DurableQueueError::LengthOverflow)?;
+    Ok(capacity.min(REGION_SIZE))
+}
+
+/// Returns whether a `queue_segment_v1` payload frames its items and matches
+/// the CRC-32C that follows them.
+pub(crate) fn segment_checksum_matches(payload: &[u8]) -> bool {
+    decode_segment_checksum(payload)
+        .is_ok_and(|(items_end, checksum)| crc32(&payload[..items_end]) == checksum)
+}
+
+/// Walks a segment payload's items and returns where they end and the
+/// checksum stored after them.
+fn decode_segment_checksum(payload: &[u8]) -> Result<(usize, u32), DurableQueueError> {
+    let mut offset = 0usize;
+    if read_bytes(payload, &mut offset, SEGMENT_MAGIC.len())? != SEGMENT_MAGIC
+        || read_u16(payload, &mut offset)? != SEGMENT_VERSION
+    {
+        return Err(DurableQueueError::InvalidEncoding);
+    }
+    for _ in 0..read_u32(payload, &mut offset)? {
+        let item_len = usize::try_from(read_u32(payload, &mut offset)?)
+            .map_err(|_| DurableQueueError::LengthOverflow)?;
+        read_bytes(payload, &mut offset, item_len)?;
+    }
+    let items_end = offset;
+    Ok((items_end, read_u32(payload, &mut offset)?))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    CRC32C.checksum(bytes)
+}
+
+fn decode_transaction_update(payload: &[u8]) -> Result<QueueTransactionUpdate, DurableQueueError> {
+    let mut offset = 0usize;
+    let update = match read_u8(payload, &mut offset)? {
+        UPDATE_POP => QueueTransactionUpdate::Pop {
+            record_len: read_u32(payload, &mut offset)?,
+        },
+        UPDATE_SEAL => QueueTransactionUpdate::Seal {
+            region_index: read_u32(payload, &mut offset)?,
+        },
+        _ => return Err(DurableQueueError::InvalidEncoding),
+    };
+    if offset != payload.len() {
+        return Err(DurableQueueError::InvalidEncoding);
+    }
+    Ok(update)
+}
+
+fn write_u8(output: &mut [u8], offset: usize, value: u8) -> Result<usize, DurableQueueError> {
+    write_bytes(output, offset, &[value])
+}
+
+fn write_u16(output: &mut [u8], offset: usize, value: u16) -> Result<usize, DurableQueueError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u32(output: &mut [u8], offset: usize, value: u32) -> Result<usize, DurableQueueError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u64(output: &mut [u8], offset: usize, value: u64) -> Result<usize, DurableQueueError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_bytes(output: &mut [u8], offset: usize, bytes: &[u8]) -> Result<usize, DurableQueueError> {
+    let end = offset
+        .checked_add(bytes.len())
+        .ok_or(DurableQueueError::LengthOverflow)?;
+    let target = output
+        .get_mut(offset..end)
+        .ok_or(DurableQueueError::LengthOverflow)?;
+    target.copy_from_slice(bytes);
+    Ok(end)
+}
+
+fn read_u8(input: &[u8], offset: &mut usize) -> Result<u8, DurableQueueError> {
+    Ok(read_bytes(input, offset, size_of::<u8>())?[0])
+}
+
+fn read_u16(input: &[u8], offset: &mut usize) -> Result<u16, DurableQueueError> {
+    let mut bytes = [0u8; size_of::<u16>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u16>())?);
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(input: &[u8], offset: &mut usize) -> Result<u32, DurableQueueError> {
+    let mut bytes = [0u8; size_of::<u32>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u32>())?);
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &[u8], offset: &mut usize) -> Result<u64, DurableQueueError> {
+    let mut bytes = [0u8; size_of::<u64>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u64>())?);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_bytes<'a>(
+    input: &'a [u8],
+    offset: &mut usize,
+    len: usize,
+) -> Result<&'a [u8], DurableQueueError> {
+    let end = offset
+        .checked_add(len)
+        .ok_or(DurableQueueError::LengthOverflow)?;
+    let bytes = input
+        .get(*offset..end)
+        .ok_or(DurableQueueError::InvalidEncoding)?;
+    *offset = end;
+    Ok(bytes)
+}
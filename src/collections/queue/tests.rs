@@ -0,0 +1,275 @@
+use super::*;
+
+use serde::Deserialize;
+
+use crate::MockFlash;
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 64;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Job {
+    id: u32,
+    body: heapless::Vec<u8, 256>,
+}
+
+fn job(id: u32, len: usize) -> Job {
+    let mut body = heapless::Vec::new();
+    body.resize(len, id as u8).unwrap();
+    Job { id, body }
+}
+
+//= spec/queue.md#queue-state
+//= type=test
+//# `RING-QUEUE-001` `DurableQueue::new` MUST create a collection with
+//# `collection_type = 0x0004` whose queue is empty.
+#[test]
+fn requirement_queue_new_creates_empty_queue_collection() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<Job, REGION_SIZE>::new(&mut storage, &mut memory).unwrap();
+
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == queue.collection_id())
+            .and_then(|collection| collection.collection_type()),
+        Some(CollectionType::QUEUE_CODE)
+    );
+    assert_eq!(queue.collection_type().stable_code(), Some(4));
+    assert_eq!(queue.len(), 0);
+    assert!(queue.is_empty());
+    assert_eq!(queue.peek(&mut storage).unwrap(), None);
+    assert_eq!(queue.pop(&mut storage).unwrap(), None);
+}
+
+//= spec/queue.md#queue-state
+//= type=test
+//# `RING-QUEUE-002` Items MUST be returned by `peek` and `pop` in the order
+//# they were pushed, and `len` MUST count the pushed items not yet popped.
+#[test]
+fn requirement_queue_pops_items_in_push_order() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<Job, REGION_SIZE>::new(&mut storage, &mut memory).unwrap();
+
+    for id in 0..20 {
+        queue.push(&mut storage, &job(id, 40)).unwrap();
+    }
+    assert_eq!(queue.len(), 20);
+    assert!(!queue.memory.segments.is_empty());
+    for id in 0..20 {
+        assert_eq!(queue.peek(&mut storage).unwrap(), Some(job(id, 40)));
+        assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(id, 40)));
+        assert_eq!(queue.len(), u64::from(19 - id));
+    }
+    assert_eq!(queue.pop(&mut storage).unwrap(), None);
+
+    queue.push(&mut storage, &job(99, 1)).unwrap();
+    assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(99, 1)));
+}
+
+//= spec/queue.md#durable-storage
+//= type=test
+//# `RING-QUEUE-003` Pushes and pops MUST each be persisted through a WAL update
+//# so that reopening the queue restores the same items and a popped item
+//# never reappears.
+#[test]
+fn requirement_queue_pops_survive_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+        let mut queue = DurableQueue::<Job, REGION_SIZE>::new(&mut storage, &mut memory).unwrap();
+        for id in 0..14 {
+            queue.push(&mut storage, &job(id, 40)).unwrap();
+        }
+        for id in 0..5 {
+            assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(id, 40)));
+        }
+        queue.collection_id()
+    };
+
+    let id = {
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+        let mut queue =
+            DurableQueue::<Job, REGION_SIZE>::open(id, &mut storage, &mut memory).unwrap();
+        assert_eq!(queue.len(), 9);
+        assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(5, 40)));
+        queue.push(&mut storage, &job(14, 3)).unwrap();
+        queue.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<Job, REGION_SIZE>::open(id, &mut storage, &mut memory).unwrap();
+    assert_eq!(queue.len(), 9);
+    for id in 6..14 {
+        assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(id, 40)));
+    }
+    assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(14, 3)));
+    assert!(queue.is_empty());
+}
+
+//= spec/queue.md#durable-storage
+//= type=test
+//# `RING-QUEUE-004` Flushing MUST seal the buffered items into a committed
+//# segment region with format `QUEUE_SEGMENT_V1_FORMAT` and record the queue
+//# state in a WAL snapshot.
+#[test]
+fn requirement_queue_flush_seals_segment_and_snapshots_state() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+        let mut queue = DurableQueue::<Job, REGION_SIZE>::new(&mut storage, &mut memory).unwrap();
+        queue.push(&mut storage, &job(1, 10)).unwrap();
+        queue.push(&mut storage, &job(2, 10)).unwrap();
+        queue.flush(&mut storage).unwrap();
+        assert_eq!(queue.memory.frontier_count, 0);
+        let segment = queue.memory.segments[0];
+        assert_eq!(segment.item_count, 2);
+        let header = storage
+            .backing
+            .read_region(segment.region_index, 0, Header::ENCODED_LEN, Header::decode)
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.collection_id, queue.collection_id());
+        assert_eq!(header.collection_format, QUEUE_SEGMENT_V1_FORMAT);
+        queue.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == id)
+            .map(|collection| collection.basis()),
+        Some(StartupCollectionBasis::WalSnapshot)
+    );
+    let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<Job, REGION_SIZE>::open(id, &mut storage, &mut memory).unwrap();
+    assert_eq!(queue.memory.segments.len(), 1);
+    assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(1, 10)));
+    assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(2, 10)));
+}
+
+//= spec/queue.md#durable-storage
+//= type=test
+//# `RING-QUEUE-005` The pop that consumes the last item of a sealed segment
+//# MUST free that segment region in the same collection transaction.
+#[test]
+fn requirement_queue_frees_consumed_segment_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+        let mut queue = DurableQueue::<Job, REGION_SIZE>::new(&mut storage, &mut memory).unwrap();
+        queue.push(&mut storage, &job(1, 10)).unwrap();
+        queue.push(&mut storage, &job(2, 10)).unwrap();
+        queue.flush(&mut storage).unwrap();
+        queue.push(&mut storage, &job(3, 10)).unwrap();
+        queue.flush(&mut storage).unwrap();
+        let first = queue.memory.segments[0].region_index;
+        let second = queue.memory.segments[1].region_index;
+
+        assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(1, 10)));
+        assert_ne!(storage.free_space_tail_region(), Some(first));
+        assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(2, 10)));
+        assert_eq!(storage.free_space_tail_region(), Some(first));
+        assert_eq!(queue.memory.segments.len(), 1);
+        assert_eq!(queue.memory.segments[0].region_index, second);
+        queue.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<Job, REGION_SIZE>::open(id, &mut storage, &mut memory).unwrap();
+    assert_eq!(queue.len(), 1);
+    let second = queue.memory.segments[0].region_index;
+    assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(3, 10)));
+    assert_eq!(storage.free_space_tail_region(), Some(second));
+    assert!(queue.memory.segments.is_empty());
+}
+
+//= spec/queue.md#durable-storage
+//= type=test
+//# `RING-QUEUE-006` Pushing an item whose encoding does not fit in an empty
+//# segment MUST fail with `ItemTooLarge` before anything is written.
+#[test]
+fn requirement_queue_rejects_items_larger_than_a_segment() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<Job, REGION_SIZE>::new(&mut storage, &mut memory).unwrap();
+    queue.push(&mut storage, &job(1, 10)).unwrap();
+
+    let mut large_memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut large =
+        DurableQueue::<(u32, [Job; 2]), REGION_SIZE>::new(&mut storage, &mut large_memory).unwrap();
+    let mut oversized = job(3, 0);
+    oversized.body.resize(256, 3).unwrap();
+    assert!(matches!(
+        large.push(&mut storage, &(7, [oversized.clone(), oversized])),
+        Err(DurableQueueError::ItemTooLarge { .. })
+    ));
+    assert!(large.is_empty());
+    assert_eq!(large.memory.frontier_len, 0);
+
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue.pop(&mut storage).unwrap(), Some(job(1, 10)));
+}
+
+//= spec/queue.md#durable-storage
+//= type=test
+//# `RING-QUEUE-007` Opening a queue MUST reject unknown collection ids and
+//# collections of another type.
+#[test]
+fn requirement_queue_open_validates_collection_type() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+    assert!(matches!(
+        DurableQueue::<Job, REGION_SIZE>::open(CollectionId(77), &mut storage, &mut memory),
+        Err(DurableQueueError::UnknownCollection(CollectionId(77)))
+    ));
+
+    let other = storage.allocate_collection_id().unwrap();
+    storage
+        .append_new_collection(other, CollectionType::MAP_CODE)
+        .unwrap();
+    assert!(matches!(
+        DurableQueue::<Job, REGION_SIZE>::open(other, &mut storage, &mut memory),
+        Err(DurableQueueError::CollectionTypeMismatch {
+            actual: Some(CollectionType::MAP_CODE),
+            ..
+        })
+    ));
+}
+
+//= spec/queue.md#durable-storage
+//= type=test
+//# `RING-QUEUE-008` The empty queue snapshot used by WAL head reclaim MUST
+//# replay to the same state as a newly created queue.
+#[test]
+fn requirement_queue_empty_snapshot_replays_initial_state() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<Job, REGION_SIZE>::new(&mut storage, &mut memory).unwrap();
+    queue.push(&mut storage, &job(1, 10)).unwrap();
+    queue.flush(&mut storage).unwrap();
+
+    queue.apply_snapshot(empty_snapshot()).unwrap();
+    assert!(queue.is_empty());
+    assert!(queue.memory.segments.is_empty());
+    assert_eq!(queue.memory.frontier_len, 0);
+    assert_eq!(queue.memory.head_offset, 0);
+}
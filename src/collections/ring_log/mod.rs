@@ -0,0 +1,1215 @@
+//! Fixed-capacity ring log collection APIs.
+
+use core::mem::size_of;
+
+use crc::{Crc, CRC_32_ISCSI};
+use heapless::Deque;
+
+use crate::disk::Header;
+use crate::flash_io::FlashIo;
+use crate::mode::{CollectionFlushMode, CollectionUpdateMode, ReadMode, StorageMode};
+use crate::startup::StartupCollectionBasis;
+use crate::storage::{StorageRuntimeError, StorageVisitError};
+use crate::wal_record::WalRecord;
+use crate::{Collection, CollectionId, CollectionType, Storage, StorageMetadata};
+
+#[cfg(test)]
+mod tests;
+
+/// Committed-region format code for sealed ring log segments.
+pub const RING_LOG_SEGMENT_V1_FORMAT: u16 = 11;
+
+const SEGMENT_MAGIC: [u8; 4] = *b"RLSG";
+const SEGMENT_VERSION: u16 = 1;
+const SEGMENT_PROLOGUE_LEN: usize =
+    SEGMENT_MAGIC.len() + size_of::<u16>() + size_of::<u64>() + size_of::<u32>();
+const SEGMENT_RECORDS_START: usize = Header::ENCODED_LEN + SEGMENT_PROLOGUE_LEN;
+const SEGMENT_CHECKSUM_LEN: usize = size_of::<u32>();
+const RECORD_HEADER_LEN: usize = size_of::<u32>();
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+const SNAPSHOT_MAGIC: [u8; 4] = *b"RLSN";
+const SNAPSHOT_VERSION: u16 = 2;
+const SNAPSHOT_FIXED_LEN: usize =
+    SNAPSHOT_MAGIC.len() + size_of::<u16>() + 2 * size_of::<u32>() + 2 * size_of::<u64>();
+/// Empty ring with an unrecorded budget; the budget update written by
+/// [`RingLog::new`] always follows it.
+const EMPTY_SNAPSHOT: [u8; SNAPSHOT_FIXED_LEN] = [
+    b'R', b'L', b'S', b'N', 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0,
+];
+
+const UPDATE_APPEND: u8 = 1;
+const UPDATE_SEAL: u8 = 2;
+const UPDATE_BUDGET: u8 = 3;
+const UPDATE_EVICT: u8 = 4;
+
+/// Errors returned by [`RingLog`].
+#[derive(Debug)]
+pub enum RingLogError {
+    /// Shared storage failed.
+    Storage(StorageRuntimeError),
+    /// The collection does not exist.
+    UnknownCollection(CollectionId),
+    /// The collection type did not match ring log.
+    CollectionTypeMismatch {
+        collection_id: CollectionId,
+        actual: Option<u16>,
+    },
+    /// The collection was dropped.
+    DroppedCollection(CollectionId),
+    /// Encoded ring log data was malformed.
+    InvalidEncoding,
+    /// A record does not fit in an empty ring log segment.
+    RecordTooLarge { len: usize, capacity: usize },
+    /// The durable ring holds more segments than the configured region
+    /// budget.
+    TooManySegments,
+    /// The durable region budget exceeds the segments the caller's memory
+    /// can track.
+    BudgetExceedsMemory { budget: u32, capacity: usize },
+    /// Checked arithmetic overflowed.
+    LengthOverflow,
+}
+
+impl From<StorageRuntimeError> for RingLogError {
+    fn from(error: StorageRuntimeError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+impl From<crate::StartupError> for RingLogError {
+    fn from(error: crate::StartupError) -> Self {
+        Self::Storage(error.into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RingSegment {
+    region_index: u32,
+    first_sequence: u64,
+    record_count: u32,
+}
+
+struct RingReplayTransaction {
+    transaction_log_id: u32,
+    joined: bool,
+    /// The transaction's one seal or evict update, as its tag and region.
+    update: Option<(u8, u32)>,
+}
+
+/// Caller-owned memory for a [`RingLog`].
+///
+/// `REGIONS` is how many segments the memory can track. A new ring records it
+/// as its durable region budget, and an existing ring opens with any memory
+/// at least as large as the budget it was created with.
+pub struct RingLogMemory<const REGION_SIZE: usize, const REGIONS: usize> {
+    segments: Deque<RingSegment, REGIONS>,
+    budget: u32,
+    next_sequence: u64,
+    frontier: [u8; REGION_SIZE],
+    frontier_len: usize,
+    frontier_count: u32,
+}
+
+impl<const REGION_SIZE: usize, const REGIONS: usize> RingLogMemory<REGION_SIZE, REGIONS> {
+    /// Allocates empty ring log memory.
+    pub fn new() -> Self {
+        Self {
+            segments: Deque::new(),
+            budget: 0,
+            next_sequence: 0,
+            frontier: [0; REGION_SIZE],
+            frontier_len: 0,
+            frontier_count: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.segments.clear();
+        self.budget = 0;
+        self.next_sequence = 0;
+        self.frontier.fill(0);
+        self.frontier_len = 0;
+        self.frontier_count = 0;
+    }
+}
+
+impl<const REGION_SIZE: usize, const REGIONS: usize> Default
+    for RingLogMemory<REGION_SIZE, REGIONS>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Durable ring of at most a fixed budget of segment regions that overwrites
+/// its oldest records once full.
+///
+/// Every appended record gets the next sequence number and is a WAL update.
+/// Records are sealed into committed segment regions as the frontier fills.
+/// Each seal reserves a fresh region through the storage allocator, so
+/// rotation is wear leveled like any other allocation. A seal into a full
+/// ring first evicts and frees the oldest segment, so the ring never holds
+/// more than its budget even while the new segment is being written.
+pub struct RingLog<'mem, const REGION_SIZE: usize, const REGIONS: usize> {
+    collection_id: CollectionId,
+    memory: &'mem mut RingLogMemory<REGION_SIZE, REGIONS>,
+    relocation_epoch: u64,
+}
+
+impl<const REGION_SIZE: usize, const REGIONS: usize> Collection
+    for RingLog<'_, REGION_SIZE, REGIONS>
+{
+    fn id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    fn collection_type(&self) -> CollectionType {
+        CollectionType::RingLog
+    }
+}
+
+impl<'mem, const REGION_SIZE: usize, const REGIONS: usize> RingLog<'mem, REGION_SIZE, REGIONS> {
+    /// Creates a new empty ring log collection.
+    pub fn new<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut RingLogMemory<REGION_SIZE, REGIONS>,
+    ) -> Result<Self, RingLogError> {
+        if REGIONS == 0 {
+            return Err(RingLogError::TooManySegments);
+        }
+        let budget = u32::try_from(REGIONS).map_err(|_| RingLogError::LengthOverflow)?;
+        let collection_id = storage.allocate_collection_id()?;
+        memory.clear();
+        storage.append_new_collection(collection_id, CollectionType::RING_LOG_CODE)?;
+        let mut update = [0u8; 1 + size_of::<u32>()];
+        let used = encode_budget(&mut update, budget)?;
+        storage.append_update(collection_id, &update[..used])?;
+        memory.budget = budget;
+        Ok(Self {
+            collection_id,
+            memory,
+            relocation_epoch: storage.runtime().relocation_epoch(),
+        })
+    }
+
+    /// Opens an existing ring log collection.
+    pub fn open<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut RingLogMemory<REGION_SIZE, REGIONS>,
+    ) -> Result<Self, RingLogError> {
+        validate_collection::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage,
+            collection_id,
+        )?;
+        memory.clear();
+        let mut ring = Self {
+            collection_id,
+            memory,
+            relocation_epoch: storage.runtime().relocation_epoch(),
+        };
+        ring.replay(storage)?;
+        if ring.memory.budget == 0 {
+            return Err(RingLogError::InvalidEncoding);
+        }
+        Ok(ring)
+    }
+
+    /// Returns the stable collection id.
+    pub fn collection_id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    /// Returns the sequence of the oldest retained record.
+    ///
+    /// Equals [`Self::next_sequence`] when the ring holds no records.
+    pub fn oldest_sequence(&self) -> u64 {
+        match self.memory.segments.front() {
+            Some(segment) => segment.first_sequence,
+            None => self.frontier_first_sequence(),
+        }
+    }
+
+    /// Returns the sequence the next appended record will receive.
+    pub fn next_sequence(&self) -> u64 {
+        self.memory.next_sequence
+    }
+
+    /// Returns the number of retained records.
+    pub fn len(&self) -> u64 {
+        self.memory.next_sequence - self.oldest_sequence()
+    }
+
+    /// Returns whether the ring retains no records.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of sealed segment regions the ring owns.
+    pub fn region_count(&self) -> usize {
+        self.memory.segments.len()
+    }
+
+    /// Returns the durable region budget recorded when the ring was created.
+    pub fn region_budget(&self) -> usize {
+        self.memory.budget as usize
+    }
+
+    /// Appends `record` and returns its sequence number.
+    ///
+    /// A frontier that cannot hold the record is sealed into a segment region
+    /// first, overwriting the oldest segment when the ring is full.
+    pub fn append<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        record: &[u8],
+    ) -> Result<u64, RingLogError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.append_inner(storage, record);
+        storage.finish_mode();
+        result
+    }
+
+    /// Seals buffered records into a segment region and records the ring
+    /// head and tail in a WAL snapshot.
+    pub fn flush<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), RingLogError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::FlushingCollection(
+            CollectionFlushMode::CommitRegion,
+        ))?;
+        let result = self.seal_inner(storage);
+        storage.finish_mode();
+        result
+    }
+
+    /// Visits every retained record from oldest to newest with its sequence.
+    pub fn visit_records<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        visitor: F,
+    ) -> Result<(), RingLogError>
+    where
+        F: FnMut(u64, &[u8]) -> Result<(), RingLogError>,
+    {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self.visit_records_inner(storage, visitor);
+        storage.finish_mode();
+        result
+    }
+
+    /// Replays the ring again when a wear-leveling pass moved one of its
+    /// segments since this handle last replayed.
+    fn refresh<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), RingLogError> {
+        let epoch = storage.runtime().relocation_epoch();
+        if self.relocation_epoch != epoch {
+            self.memory.clear();
+            self.replay(storage)?;
+            self.relocation_epoch = epoch;
+        }
+        Ok(())
+    }
+
+    fn frontier_first_sequence(&self) -> u64 {
+        self.memory.next_sequence - u64::from(self.memory.frontier_count)
+    }
+
+    fn append_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        record: &[u8],
+    ) -> Result<u64, RingLogError> {
+        let capacity = segment_capacity::<REGION_SIZE>(storage.metadata())?;
+        let record_len = RECORD_HEADER_LEN
+            .checked_add(record.len())
+            .ok_or(RingLogError::LengthOverflow)?;
+        if record_len > capacity {
+            return Err(RingLogError::RecordTooLarge {
+                len: record.len(),
+                capacity: capacity - RECORD_HEADER_LEN,
+            });
+        }
+        if self.memory.frontier_len + record_len > capacity {
+            self.seal_inner(storage)?;
+        }
+
+        let offset = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_APPEND)?;
+        let used = write_bytes(&mut storage.memory.payload_scratch, offset, record)?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        self.apply_append(record)
+    }
+
+    fn visit_records_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        mut visitor: F,
+    ) -> Result<(), RingLogError>
+    where
+        F: FnMut(u64, &[u8]) -> Result<(), RingLogError>,
+    {
+        for segment in self.memory.segments.iter() {
+            self.visit_segment(storage.backing, segment, &mut visitor)?;
+        }
+
+        let mut sequence = self.frontier_first_sequence();
+        let mut offset = 0usize;
+        let frontier = &self.memory.frontier[..self.memory.frontier_len];
+        for _ in 0..self.memory.frontier_count {
+            let record_len = usize::try_from(read_u32(frontier, &mut offset)?)
+                .map_err(|_| RingLogError::LengthOverflow)?;
+            visitor(sequence, read_bytes(frontier, &mut offset, record_len)?)?;
+            sequence += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_segment<IO: FlashIo, F>(
+        &self,
+        flash: &mut IO,
+        segment: &RingSegment,
+        visitor: &mut F,
+    ) -> Result<(), RingLogError>
+    where
+        F: FnMut(u64, &[u8]) -> Result<(), RingLogError>,
+    {
+        let header = flash
+            .read_region(segment.region_index, 0, Header::ENCODED_LEN, Header::decode)
+            .map_err(StorageRuntimeError::from)?
+            .map_err(|_| RingLogError::InvalidEncoding)?;
+        if header.collection_id != self.collection_id
+            || header.collection_format != RING_LOG_SEGMENT_V1_FORMAT
+        {
+            return Err(RingLogError::InvalidEncoding);
+        }
+        let (first_sequence, record_count) = flash
+            .read_region(
+                segment.region_index,
+                Header::ENCODED_LEN,
+                SEGMENT_PROLOGUE_LEN,
+                decode_segment_prologue,
+            )
+            .map_err(StorageRuntimeError::from)??;
+        if first_sequence != segment.first_sequence || record_count != segment.record_count {
+            return Err(RingLogError::InvalidEncoding);
+        }
+
+        let mut offset = SEGMENT_RECORDS_START;
+        for index in 0..record_count {
+            let record_len = flash
+                .read_region(segment.region_index, offset, RECORD_HEADER_LEN, |bytes| {
+                    read_u32(bytes, &mut 0)
+                })
+                .map_err(StorageRuntimeError::from)??;
+            let record_len =
+                usize::try_from(record_len).map_err(|_| RingLogError::LengthOverflow)?;
+            let start = offset + RECORD_HEADER_LEN;
+            let end = start
+                .checked_add(record_len)
+                .filter(|end| *end <= REGION_SIZE)
+                .ok_or(RingLogError::InvalidEncoding)?;
+            flash
+                .read_region(segment.region_index, start, record_len, |bytes| {
+                    visitor(first_sequence + u64::from(index), bytes)
+                })
+                .map_err(StorageRuntimeError::from)??;
+            offset = end;
+        }
+        Ok(())
+    }
+
+    fn seal_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), RingLogError> {
+        if self.memory.frontier_count == 0 {
+            return Ok(());
+        }
+        if self.memory.segments.len() >= self.region_budget() {
+            self.evict_oldest(storage)?;
+        }
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        let mut allocated = None;
+        let region_index = match self.seal_transactional(storage, &mut allocated) {
+            Ok(region_index) => region_index,
+            Err(error) => {
+                return match self.rollback_seal(storage, allocated) {
+                    Ok(()) => Err(error),
+                    Err(cleanup_error) => Err(cleanup_error),
+                };
+            }
+        };
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        self.apply_seal(region_index)?;
+
+        let used = self.encode_snapshot(&mut storage.memory.payload_scratch)?;
+        storage
+            .memory
+            .state
+            .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                CollectionType::RING_LOG_CODE,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        Ok(())
+    }
+
+    /// Drops the oldest segment from the ring and frees its region, so the
+    /// seal that follows allocates with that region already returned.
+    fn evict_oldest<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), RingLogError> {
+        let oldest = self
+            .memory
+            .segments
+            .front()
+            .ok_or(RingLogError::TooManySegments)?
+            .region_index;
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        let used = encode_evict(&mut storage.memory.payload_scratch, oldest)?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        self.apply_evict(oldest)?;
+        storage
+            .memory
+            .state
+            .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                oldest,
+            )?;
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        Ok(())
+    }
+
+    /// Writes the frontier into a freshly reserved region and commits the
+    /// seal.
+    fn seal_transactional<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        allocated: &mut Option<u32>,
+    ) -> Result<u32, RingLogError> {
+        let region_index = storage
+            .memory
+            .state
+            .reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &mut storage.memory.reclaim_source_regions,
+                &mut storage.memory.active_collections,
+                &mut storage.memory.reclaim_plan,
+                &mut storage.memory.open_plan,
+            )?;
+        *allocated = Some(region_index);
+
+        let mut used = write_bytes(&mut storage.memory.payload_scratch, 0, &SEGMENT_MAGIC)?;
+        used = write_u16(&mut storage.memory.payload_scratch, used, SEGMENT_VERSION)?;
+        used = write_u64(
+            &mut storage.memory.payload_scratch,
+            used,
+            self.frontier_first_sequence(),
+        )?;
+        used = write_u32(
+            &mut storage.memory.payload_scratch,
+            used,
+            self.memory.frontier_count,
+        )?;
+        used = write_bytes(
+            &mut storage.memory.payload_scratch,
+            used,
+            &self.memory.frontier[..self.memory.frontier_len],
+        )?;
+        let checksum = crc32(&storage.memory.payload_scratch[..used]);
+        used = write_u32(&mut storage.memory.payload_scratch, used, checksum)?;
+        let region_index = storage
+            .memory
+            .state
+            .write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                region_index,
+                self.collection_id,
+                RING_LOG_SEGMENT_V1_FORMAT,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        *allocated = Some(region_index);
+
+        let used = encode_seal(&mut storage.memory.payload_scratch, region_index)?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        Ok(region_index)
+    }
+
+    fn rollback_seal<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        allocated: Option<u32>,
+    ) -> Result<(), RingLogError> {
+        let mut first_error = None::<RingLogError>;
+        if let Err(error) = storage
+            .memory
+            .state
+            .rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )
+        {
+            first_error = Some(error.into());
+        }
+        if let Some(region_index) = allocated {
+            if let Err(error) = storage
+                .memory
+                .state
+                .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    CollectionId(0),
+                    region_index,
+                )
+            {
+                if first_error.is_none() {
+                    first_error = Some(error.into());
+                }
+            }
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn replay<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), RingLogError> {
+        let collection_id = self.collection_id;
+        let mut transaction = None::<RingReplayTransaction>;
+        // WAL head reclaim drops a superseded snapshot but keeps the updates
+        // that followed it, so updates before the first retained basis are
+        // skipped.
+        let mut based = !storage
+            .memory
+            .state
+            .wal_has_collection_basis::<REGION_SIZE, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+                CollectionType::RING_LOG_CODE,
+            )?;
+        let result =
+            storage
+                .memory
+                .state
+                .visit_wal_records::<REGION_SIZE, IO, RingLogError, _>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    |_flash, record| {
+                        match record {
+                            WalRecord::NewCollection {
+                                collection_id: seen,
+                                collection_type,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::RING_LOG_CODE =>
+                            {
+                                based = true;
+                                self.memory.clear();
+                            }
+                            WalRecord::BeginTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                transaction = Some(RingReplayTransaction {
+                                    transaction_log_id,
+                                    joined: transaction_log_id == 0,
+                                    update: None,
+                                });
+                            }
+                            WalRecord::AddTransactionCollection {
+                                collection_id: seen,
+                                ..
+                            } if seen == collection_id => {
+                                if let Some(open) = transaction.as_mut() {
+                                    open.joined = true;
+                                }
+                            }
+                            WalRecord::Snapshot {
+                                collection_id: seen,
+                                collection_type,
+                                payload,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::RING_LOG_CODE =>
+                            {
+                                based = true;
+                                self.apply_snapshot(payload)?;
+                            }
+                            WalRecord::Update {
+                                collection_id: seen,
+                                payload,
+                            } if seen == collection_id && based => match transaction.as_mut() {
+                                Some(open) if open.joined => {
+                                    // Ring log transactions carry exactly one seal
+                                    // or evict.
+                                    if open.update.is_some() {
+                                        return Err(RingLogError::InvalidEncoding);
+                                    }
+                                    let tag = read_u8(payload, &mut 0)?;
+                                    if tag != UPDATE_SEAL && tag != UPDATE_EVICT {
+                                        return Err(RingLogError::InvalidEncoding);
+                                    }
+                                    open.update = Some((tag, decode_region_update(payload, tag)?));
+                                }
+                                _ => self.apply_update_payload(payload)?,
+                            },
+                            WalRecord::CommitTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    match transaction.take().and_then(|open| open.update) {
+                                        Some((UPDATE_EVICT, region_index)) => {
+                                            self.apply_evict(region_index)?;
+                                        }
+                                        Some((_, region_index)) => self.apply_seal(region_index)?,
+                                        None => {}
+                                    }
+                                }
+                            }
+                            WalRecord::RollbackTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    transaction = None;
+                                }
+                            }
+                            WalRecord::DropCollection {
+                                collection_id: seen,
+                            } if seen == collection_id => {
+                                self.memory.clear();
+                            }
+                            _ => {}
+                        }
+                        Ok(())
+                    },
+                );
+        match result {
+            Ok(()) => Ok(()),
+            Err(StorageVisitError::Storage(error)) => Err(RingLogError::Storage(error)),
+            Err(StorageVisitError::Visitor(error)) => Err(error),
+        }
+    }
+
+    fn apply_update_payload(&mut self, payload: &[u8]) -> Result<(), RingLogError> {
+        let mut offset = 0usize;
+        match read_u8(payload, &mut offset)? {
+            UPDATE_APPEND => {
+                let record = payload.get(offset..).ok_or(RingLogError::InvalidEncoding)?;
+                self.apply_append(record).map(|_| ())
+            }
+            UPDATE_BUDGET => self.apply_budget(decode_region_update(payload, UPDATE_BUDGET)?),
+            UPDATE_EVICT => self.apply_evict(decode_region_update(payload, UPDATE_EVICT)?),
+            // Reclaim can copy a committed seal or evict out of its
+            // transaction, so both may also appear as plain updates.
+            _ => self.apply_seal(decode_seal(payload)?),
+        }
+    }
+
+    fn apply_budget(&mut self, budget: u32) -> Result<(), RingLogError> {
+        if budget == 0 {
+            return Err(RingLogError::InvalidEncoding);
+        }
+        if budget as usize > REGIONS {
+            return Err(RingLogError::BudgetExceedsMemory {
+                budget,
+                capacity: REGIONS,
+            });
+        }
+        self.memory.budget = budget;
+        Ok(())
+    }
+
+    fn apply_evict(&mut self, region_index: u32) -> Result<(), RingLogError> {
+        let oldest = self
+            .memory
+            .segments
+            .pop_front()
+            .ok_or(RingLogError::InvalidEncoding)?;
+        if oldest.region_index != region_index {
+            return Err(RingLogError::InvalidEncoding);
+        }
+        Ok(())
+    }
+
+    fn apply_append(&mut self, record: &[u8]) -> Result<u64, RingLogError> {
+        let record_len = u32::try_from(record.len()).map_err(|_| RingLogError::InvalidEncoding)?;
+        let offset = write_u32(
+            &mut self.memory.frontier,
+            self.memory.frontier_len,
+            record_len,
+        )
+        .map_err(|_| RingLogError::InvalidEncoding)?;
+        let end = write_bytes(&mut self.memory.frontier, offset, record)
+            .map_err(|_| RingLogError::InvalidEncoding)?;
+        let sequence = self.memory.next_sequence;
+        self.memory.next_sequence = sequence
+            .checked_add(1)
+            .ok_or(RingLogError::LengthOverflow)?;
+        self.memory.frontier_len = end;
+        self.memory.frontier_count += 1;
+        Ok(sequence)
+    }
+
+    fn apply_seal(&mut self, region_index: u32) -> Result<(), RingLogError> {
+        if self.memory.frontier_count == 0 {
+            return Err(RingLogError::InvalidEncoding);
+        }
+        if self.memory.segments.len() >= self.region_budget() {
+            return Err(RingLogError::TooManySegments);
+        }
+        self.memory
+            .segments
+            .push_back(RingSegment {
+                region_index,
+                first_sequence: self.frontier_first_sequence(),
+                record_count: self.memory.frontier_count,
+            })
+            .map_err(|_| RingLogError::TooManySegments)?;
+        self.memory.frontier_len = 0;
+        self.memory.frontier_count = 0;
+        Ok(())
+    }
+
+    fn encode_snapshot(&self, output: &mut [u8]) -> Result<usize, RingLogError> {
+        // Snapshots are only written right after a seal, so the frontier is
+        // always empty and every record lives in a segment.
+        if self.memory.frontier_count != 0 {
+            return Err(RingLogError::InvalidEncoding);
+        }
+        let segment_count =
+            u32::try_from(self.memory.segments.len()).map_err(|_| RingLogError::LengthOverflow)?;
+        let mut offset = write_bytes(output, 0, &SNAPSHOT_MAGIC)?;
+        offset = write_u16(output, offset, SNAPSHOT_VERSION)?;
+        offset = write_u32(output, offset, self.memory.budget)?;
+        offset = write_u64(output, offset, self.oldest_sequence())?;
+        offset = write_u64(output, offset, self.memory.next_sequence)?;
+        offset = write_u32(output, offset, segment_count)?;
+        for segment in self.memory.segments.iter() {
+            offset = write_u32(output, offset, segment.region_index)?;
+            offset = write_u32(output, offset, segment.record_count)?;
+        }
+        Ok(offset)
+    }
+
+    fn apply_snapshot(&mut self, payload: &[u8]) -> Result<(), RingLogError> {
+        let mut offset = 0usize;
+        if read_bytes(payload, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+            || read_u16(payload, &mut offset)? != SNAPSHOT_VERSION
+        {
+            return Err(RingLogError::InvalidEncoding);
+        }
+        self.memory.clear();
+        let budget = read_u32(payload, &mut offset)?;
+        if budget != 0 {
+            self.apply_budget(budget)?;
+        }
+        let head = read_u64(payload, &mut offset)?;
+        let tail = read_u64(payload, &mut offset)?;
+        let segment_count = read_u32(payload, &mut offset)?;
+        let mut first_sequence = head;
+        for _ in 0..segment_count {
+            let region_index = read_u32(payload, &mut offset)?;
+            let record_count = read_u32(payload, &mut offset)?;
+            if record_count == 0 {
+                return Err(RingLogError::InvalidEncoding);
+            }
+            if self.memory.segments.len() >= self.region_budget() {
+                return Err(RingLogError::TooManySegments);
+            }
+            self.memory
+                .segments
+                .push_back(RingSegment {
+                    region_index,
+                    first_sequence,
+                    record_count,
+                })
+                .map_err(|_| RingLogError::TooManySegments)?;
+            first_sequence = first_sequence
+                .checked_add(u64::from(record_count))
+                .ok_or(RingLogError::InvalidEncoding)?;
+        }
+        if first_sequence != tail || offset != payload.len() {
+            return Err(RingLogError::InvalidEncoding);
+        }
+        self.memory.next_sequence = tail;
+        Ok(())
+    }
+}
+
+fn validate_collection<
+    IO: FlashIo,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+) -> Result<(), RingLogError> {
+    let collection = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .ok_or(RingLogError::UnknownCollection(collection_id))?;
+    if collection.basis() == StartupCollectionBasis::Dropped {
+        return Err(RingLogError::DroppedCollection(collection_id));
+    }
+    storage.runtime().ensure_not_quarantined(collection_id)?;
+    if collection.collection_type() != Some(CollectionType::RING_LOG_CODE) {
+        return Err(RingLogError::CollectionTypeMismatch {
+            collection_id,
+            actual: collection.collection_type(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn empty_snapshot() -> &'static [u8] {
+    &EMPTY_SNAPSHOT
+}
+
+/// Returns the offset of the `u32` naming segment `region_index` in an
+/// encoded snapshot, or `None` when the snapshot does not list it.
+pub(crate) fn snapshot_segment_offset(
+    snapshot: &[u8],
+    region_index: u32,
+) -> Result<Option<usize>, RingLogError> {
+    let mut offset = 0usize;
+    if read_bytes(snapshot, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+        || read_u16(snapshot, &mut offset)? != SNAPSHOT_VERSION
+    {
+        return Err(RingLogError::InvalidEncoding);
+    }
+    let _budget = read_u32(snapshot, &mut offset)?;
+    let _head = read_u64(snapshot, &mut offset)?;
+    let _tail = read_u64(snapshot, &mut offset)?;
+    let segment_count = read_u32(snapshot, &mut offset)?;
+    for _ in 0..segment_count {
+        let field = offset;
+        let segment = read_u32(snapshot, &mut offset)?;
+        let _record_count = read_u32(snapshot, &mut offset)?;
+        if segment == region_index {
+            return Ok(Some(field));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the frontier bytes one segment region can hold.
+fn segment_capacity<const REGION_SIZE: usize>(
+    metadata: StorageMetadata,
+) -> Result<usize, RingLogError> {
+    let granule =
+        usize::try_from(metadata.wal_write_granule).map_err(|_| RingLogError::LengthOverflow)?;
+    if granule == 0 {
+        return Err(RingLogError::InvalidEncoding);
+    }
+    (REGION_SIZE - REGION_SIZE % granule)
+        .checked_sub(SEGMENT_RECORDS_START + SEGMENT_CHECKSUM_LEN)
+        .filter(|capacity| *capacity > RECORD_HEADER_LEN)
+        .ok_or(RingLogError::LengthOverflow)
+}
+
+/// Returns whether a `ring_log_segment_v1` payload frames its records and
+/// matches the CRC-32C that follows them.
+pub(crate) fn segment_checksum_matches(payload: &[u8]) -> bool {
+    decode_segment_checksum(payload)
+        .is_ok_and(|(records_end, checksum)| crc32(&payload[..records_end]) == checksum)
+}
+
+/// Walks a segment payload's records and returns where they end and the
+/// checksum stored after them.
+fn decode_segment_checksum(payload: &[u8]) -> Result<(usize, u32), RingLogError> {
+    let (_, record_count) = decode_segment_prologue(payload)?;
+    let mut offset = SEGMENT_PROLOGUE_LEN;
+    for _ in 0..record_count {
+        let record_len = usize::try_from(read_u32(payload, &mut offset)?)
+            .map_err(|_| RingLogError::LengthOverflow)?;
+        read_bytes(payload, &mut offset, record_len)?;
+    }
+    let records_end = offset;
+    Ok((records_end, read_u32(payload, &mut offset)?))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    CRC32C.checksum(bytes)
+}
+
+fn decode_segment_prologue(bytes: &[u8]) -> Result<(u64, u32), RingLogError> {
+    let mut offset = 0usize;
+    if read_bytes(bytes, &mut offset, SEGMENT_MAGIC.len())? != SEGMENT_MAGIC
+        || read_u16(bytes, &mut offset)? != SEGMENT_VERSION
+    {
+        return Err(RingLogError::InvalidEncoding);
+    }
+    let first_sequence = read_u64(bytes, &mut offset)?;
+    let record_count = read_u32(bytes, &mut offset)?;
+    Ok((first_sequence, record_count))
+}
+
+fn encode_seal(output: &mut [u8], region_index: u32) -> Result<usize, RingLogError> {
+    let used = write_u8(output, 0, UPDATE_SEAL)?;
+    write_u32(output, used, region_index)
+}
+
+fn decode_seal(payload: &[u8]) -> Result<u32, RingLogError> {
+    decode_region_update(payload, UPDATE_SEAL)
+}
+
+fn encode_budget(output: &mut [u8], budget: u32) -> Result<usize, RingLogError> {
+    let used = write_u8(output, 0, UPDATE_BUDGET)?;
+    write_u32(output, used, budget)
+}
+
+fn encode_evict(output: &mut [u8], region_index: u32) -> Result<usize, RingLogError> {
+    let used = write_u8(output, 0, UPDATE_EVICT)?;
+    write_u32(output, used, region_index)
+}
+
+/// Decodes an update made of its tag and one `u32`.
+fn decode_region_update(payload: &[u8], tag: u8) -> Result<u32, RingLogError> {
+    let mut offset = 0usize;
+    if read_u8(payload, &mut offset)? != tag {
+        return Err(RingLogError::InvalidEncoding);
+    }
+    let value = read_u32(payload, &mut offset)?;
+    if offset != payload.len() {
+        return Err(RingLogError::InvalidEncoding);
+    }
+    Ok(value)
+}
+
+fn write_u8(output: &mut [u8], offset: usize, value: u8) -> Result<usize, RingLogError> {
+    write_bytes(output, offset, &[value])
+}
+
+fn write_u16(output: &mut [u8], offset: usize, value: u16) -> Result<usize, RingLogError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u32(output: &mut [u8], offset: usize, value: u32) -> Result<usize, RingLogError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u64(output: &mut [u8], offset: usize, value: u64) -> Result<usize, RingLogError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_bytes(output: &mut [u8], offset: usize, bytes: &[u8]) -> Result<usize, RingLogError> {
+    let end = offset
+        .checked_add(bytes.len())
+        .ok_or(RingLogError::LengthOverflow)?;
+    let target = output
+        .get_mut(offset..end)
+        .ok_or(RingLogError::LengthOverflow)?;
+    target.copy_from_slice(bytes);
+    Ok(end)
+}
+
+fn read_u8(input: &[u8], offset: &mut usize) -> Result<u8, RingLogError> {
+    Ok(read_bytes(input, offset, size_of::<u8>())?[0])
+}
+
+fn read_u16(input: &[u8], offset: &mut usize) -> Result<u16, RingLogError> {
+    let mut bytes = [0u8; size_of::<u16>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u16>())?);
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(input: &[u8], offset: &mut usize) -> Result<u32, RingLogError> {
+    let mut bytes = [0u8; size_of::<u32>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u32>())?);
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &[u8], offset: &mut usize) -> Result<u64, RingLogError> {
+    let mut bytes = [0u8; size_of::<u64>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u64>())?);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_bytes<'a>(
+    input: &'a [u8],
+    offset: &mut usize,
+    len: usize,
+) -> Result<&'a [u8], RingLogError> {
+    let end = offset
+        .checked_add(len)
+        .ok_or(RingLogError::LengthOverflow)?;
+    let bytes = input
+        .get(*offset..end)
+        .ok_or(RingLogError::InvalidEncoding)?;
+    *offset = end;
+    Ok(bytes)
+}
@@ -0,0 +1,353 @@
+use super::*;
+
+use crate::MockFlash;
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 64;
+const REGIONS: usize = 3;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestRing<'mem> = RingLog<'mem, REGION_SIZE, REGIONS>;
+type TestRingMemory = RingLogMemory<REGION_SIZE, REGIONS>;
+
+fn record(sequence: u64) -> [u8; 100] {
+    [sequence as u8; 100]
+}
+
+fn collect(
+    ring: &mut TestRing<'_>,
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+) -> std::vec::Vec<(u64, std::vec::Vec<u8>)> {
+    let mut records = std::vec::Vec::new();
+    ring.visit_records(storage, |sequence, bytes| {
+        records.push((sequence, bytes.to_vec()));
+        Ok(())
+    })
+    .unwrap();
+    records
+}
+
+fn expected(sequences: core::ops::Range<u64>) -> std::vec::Vec<(u64, std::vec::Vec<u8>)> {
+    sequences
+        .map(|sequence| (sequence, record(sequence).to_vec()))
+        .collect()
+}
+
+/// Appends records until `count` segments have been sealed in total.
+fn append_until_sealed<const SEGMENTS: usize>(
+    ring: &mut RingLog<'_, REGION_SIZE, SEGMENTS>,
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    count: usize,
+) {
+    let mut sealed = 0;
+    while sealed < count {
+        let before = ring.memory.frontier_count;
+        let sequence = ring.next_sequence();
+        ring.append(storage, &record(sequence)).unwrap();
+        if ring.memory.frontier_count <= before {
+            sealed += 1;
+        }
+    }
+}
+
+//= spec/ring-log.md#ring-state
+//= type=test
+//# `RING-LOG-001` `RingLog::new` MUST create a collection with
+//# `collection_type = 0x0005` that retains no records and whose next
+//# sequence is 0.
+#[test]
+fn requirement_ring_log_new_creates_empty_ring_collection() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestRingMemory::new();
+    let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == ring.collection_id())
+            .and_then(|collection| collection.collection_type()),
+        Some(CollectionType::RING_LOG_CODE)
+    );
+    assert_eq!(ring.collection_type().stable_code(), Some(5));
+    assert!(ring.is_empty());
+    assert_eq!(ring.oldest_sequence(), 0);
+    assert_eq!(ring.next_sequence(), 0);
+    assert_eq!(ring.region_count(), 0);
+    assert!(collect(&mut ring, &mut storage).is_empty());
+}
+
+//= spec/ring-log.md#ring-state
+//= type=test
+//# `RING-LOG-002` Appending MUST assign consecutive sequence numbers, and
+//# traversal MUST visit every retained record from oldest to newest, sealed
+//# segments first and then the buffered frontier.
+#[test]
+fn requirement_ring_log_visits_records_oldest_to_newest() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestRingMemory::new();
+    let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+
+    for sequence in 0..10 {
+        assert_eq!(
+            ring.append(&mut storage, &record(sequence)).unwrap(),
+            sequence
+        );
+    }
+    assert!(ring.region_count() > 0);
+    assert!(ring.memory.frontier_count > 0);
+    assert_eq!(ring.len(), 10);
+    assert_eq!(collect(&mut ring, &mut storage), expected(0..10));
+}
+
+//= spec/ring-log.md#rotation
+//= type=test
+//# `RING-LOG-003` Sealing a segment into a ring that already owns its full
+//# region budget MUST first record the eviction of the oldest segment and free
+//# its region before reserving the new segment region, advancing the oldest
+//# retained sequence past its records.
+#[test]
+fn requirement_ring_log_overwrites_oldest_segment_when_full() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestRingMemory::new();
+    let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+
+    append_until_sealed(&mut ring, &mut storage, REGIONS);
+    assert_eq!(ring.region_count(), REGIONS);
+    assert_eq!(ring.oldest_sequence(), 0);
+    let oldest = *ring.memory.segments.front().unwrap();
+
+    append_until_sealed(&mut ring, &mut storage, 1);
+    assert_eq!(ring.region_count(), REGIONS);
+    assert_eq!(storage.free_space_tail_region(), Some(oldest.region_index));
+    let mut freed_at = None;
+    let mut last_begin = None;
+    let mut index = 0usize;
+    storage
+        .visit_wal_records::<(), _>(|record| {
+            match record {
+                WalRecord::FreeRegion { region_index, .. }
+                    if region_index == oldest.region_index =>
+                {
+                    freed_at = Some(index);
+                }
+                WalRecord::BeginTransaction { .. } => last_begin = Some(index),
+                _ => {}
+            }
+            index += 1;
+            Ok(())
+        })
+        .unwrap();
+    assert!(freed_at.unwrap() < last_begin.unwrap());
+    assert_eq!(
+        ring.oldest_sequence(),
+        oldest.first_sequence + u64::from(oldest.record_count)
+    );
+    let next = ring.next_sequence();
+    assert_eq!(
+        collect(&mut ring, &mut storage),
+        expected(ring.oldest_sequence()..next)
+    );
+}
+
+//= spec/ring-log.md#rotation
+//= type=test
+//# `RING-LOG-004` Every sealed segment MUST be a region freshly reserved
+//# through the storage allocator, so rotation never rewrites the evicted
+//# region in place.
+#[test]
+fn requirement_ring_log_rotation_reserves_fresh_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestRingMemory::new();
+    let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+
+    let mut used = std::vec::Vec::new();
+    for _ in 0..3 * REGIONS {
+        append_until_sealed(&mut ring, &mut storage, 1);
+        let newest = *ring.memory.segments.back().unwrap();
+        assert!(!used.contains(&newest.region_index));
+        used.push(newest.region_index);
+        assert!(ring.region_count() <= REGIONS);
+    }
+}
+
+//= spec/ring-log.md#durable-storage
+//= type=test
+//# `RING-LOG-005` Appends MUST be persisted through WAL updates and each seal
+//# MUST record the ring head and tail in a WAL snapshot, so reopening the ring
+//# restores the same oldest sequence, next sequence, and records across a wrap.
+#[test]
+fn requirement_ring_log_reopen_restores_head_tail_across_wrap() {
+    let mut flash = TestFlash::new(0xff);
+    let (id, oldest, next) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut memory = TestRingMemory::new();
+        let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+        append_until_sealed(&mut ring, &mut storage, 2 * REGIONS + 1);
+        ring.append(&mut storage, &record(ring.next_sequence()))
+            .unwrap();
+        assert!(ring.oldest_sequence() > 0);
+        (
+            ring.collection_id(),
+            ring.oldest_sequence(),
+            ring.next_sequence(),
+        )
+    };
+
+    let id = {
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        assert_eq!(
+            storage
+                .collections()
+                .iter()
+                .find(|collection| collection.collection_id() == id)
+                .map(|collection| collection.basis()),
+            Some(StartupCollectionBasis::WalSnapshot)
+        );
+        let mut memory = TestRingMemory::new();
+        let mut ring = TestRing::open(id, &mut storage, &mut memory).unwrap();
+        assert_eq!(ring.oldest_sequence(), oldest);
+        assert_eq!(ring.next_sequence(), next);
+        assert_eq!(collect(&mut ring, &mut storage), expected(oldest..next));
+        ring.flush(&mut storage).unwrap();
+        ring.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = TestRingMemory::new();
+    let mut ring = TestRing::open(id, &mut storage, &mut memory).unwrap();
+    assert_eq!(ring.memory.frontier_count, 0);
+    assert_eq!(ring.next_sequence(), next);
+    let oldest = ring.oldest_sequence();
+    assert_eq!(collect(&mut ring, &mut storage), expected(oldest..next));
+}
+
+//= spec/ring-log.md#durable-storage
+//= type=test
+//# `RING-LOG-006` Appending a record that does not fit in an empty segment
+//# MUST fail with `RecordTooLarge` before anything is written.
+#[test]
+fn requirement_ring_log_rejects_records_larger_than_a_segment() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestRingMemory::new();
+    let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+    ring.append(&mut storage, b"kept").unwrap();
+
+    assert!(matches!(
+        ring.append(&mut storage, &[0u8; REGION_SIZE]),
+        Err(RingLogError::RecordTooLarge {
+            len: REGION_SIZE,
+            ..
+        })
+    ));
+    assert_eq!(ring.next_sequence(), 1);
+    assert_eq!(ring.region_count(), 0);
+    assert_eq!(ring.memory.frontier_count, 1);
+}
+
+//= spec/ring-log.md#durable-storage
+//= type=test
+//# `RING-LOG-007` Opening a ring log MUST reject unknown collection ids,
+//# collections of another type, and rings whose durable region budget exceeds
+//# the segments the caller's memory can track.
+#[test]
+fn requirement_ring_log_open_validates_collection() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestRingMemory::new();
+    assert!(matches!(
+        TestRing::open(CollectionId(77), &mut storage, &mut memory),
+        Err(RingLogError::UnknownCollection(CollectionId(77)))
+    ));
+
+    let other = storage.allocate_collection_id().unwrap();
+    storage
+        .append_new_collection(other, CollectionType::MAP_CODE)
+        .unwrap();
+    assert!(matches!(
+        TestRing::open(other, &mut storage, &mut memory),
+        Err(RingLogError::CollectionTypeMismatch {
+            actual: Some(CollectionType::MAP_CODE),
+            ..
+        })
+    ));
+
+    let id = {
+        let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+        append_until_sealed(&mut ring, &mut storage, REGIONS);
+        ring.collection_id()
+    };
+    let mut small = RingLogMemory::<REGION_SIZE, 2>::new();
+    assert!(matches!(
+        RingLog::open(id, &mut storage, &mut small),
+        Err(RingLogError::BudgetExceedsMemory {
+            budget: 3,
+            capacity: 2
+        })
+    ));
+}
+
+//= spec/ring-log.md#durable-storage
+//= type=test
+//# `RING-LOG-008` The empty ring log snapshot used by WAL head reclaim MUST
+//# replay to the same state as a newly created ring log before its budget
+//# update.
+#[test]
+fn requirement_ring_log_empty_snapshot_replays_initial_state() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestRingMemory::new();
+    let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+    append_until_sealed(&mut ring, &mut storage, 1);
+
+    ring.apply_snapshot(empty_snapshot()).unwrap();
+    assert!(ring.is_empty());
+    assert_eq!(ring.next_sequence(), 0);
+    assert_eq!(ring.region_count(), 0);
+    assert_eq!(ring.region_budget(), 0);
+    assert_eq!(ring.memory.frontier_len, 0);
+}
+
+//= spec/ring-log.md#durable-storage
+//= type=test
+//# `RING-LOG-009` The region budget MUST persist across reopening the ring:
+//# a ring opened with larger memory MUST keep rotating within the budget it
+//# was created with.
+#[test]
+fn requirement_ring_log_region_budget_persists_across_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut memory = TestRingMemory::new();
+        let mut ring = TestRing::new(&mut storage, &mut memory).unwrap();
+        ring.append(&mut storage, &record(0)).unwrap();
+        ring.collection_id()
+    };
+
+    // Reopened from WAL updates alone, before any snapshot.
+    let id = {
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        let mut memory = RingLogMemory::<REGION_SIZE, 8>::new();
+        let mut ring = RingLog::open(id, &mut storage, &mut memory).unwrap();
+        assert_eq!(ring.region_budget(), REGIONS);
+        for _ in 0..2 * REGIONS {
+            append_until_sealed(&mut ring, &mut storage, 1);
+            assert!(ring.region_count() <= REGIONS);
+        }
+        assert_eq!(ring.region_count(), REGIONS);
+        ring.collection_id()
+    };
+
+    // Reopened from a seal snapshot.
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = RingLogMemory::<REGION_SIZE, 8>::new();
+    let mut ring = RingLog::open(id, &mut storage, &mut memory).unwrap();
+    assert_eq!(ring.region_budget(), REGIONS);
+    ring.flush(&mut storage).unwrap();
+    assert_eq!(ring.region_count(), REGIONS);
+}
@@ -0,0 +1,330 @@
+//! Monotonic ID sequence collection APIs.
+
+use core::mem::size_of;
+
+use crate::flash_io::FlashIo;
+use crate::mode::{CollectionUpdateMode, StorageMode};
+use crate::startup::StartupCollectionBasis;
+use crate::storage::{StorageRuntimeError, StorageVisitError};
+use crate::wal_record::WalRecord;
+use crate::{Collection, CollectionId, CollectionType, Storage};
+
+#[cfg(test)]
+mod tests;
+
+/// Number of IDs a [`Sequence`] reserves with one WAL record by default.
+pub const DEFAULT_SEQUENCE_BLOCK_SIZE: u64 = 1000;
+
+const STATE_MAGIC: [u8; 4] = *b"SQSN";
+const STATE_VERSION: u16 = 1;
+const STATE_LEN: usize = STATE_MAGIC.len() + size_of::<u16>() + size_of::<u64>();
+const EMPTY_SNAPSHOT: [u8; STATE_LEN] = [b'S', b'Q', b'S', b'N', 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+/// Errors returned by [`Sequence`].
+#[derive(Debug)]
+pub enum SequenceError {
+    /// Shared storage failed.
+    Storage(StorageRuntimeError),
+    /// The collection does not exist.
+    UnknownCollection(CollectionId),
+    /// The collection type did not match sequence.
+    CollectionTypeMismatch {
+        collection_id: CollectionId,
+        actual: Option<u16>,
+    },
+    /// The collection was dropped.
+    DroppedCollection(CollectionId),
+    /// Encoded sequence data was malformed.
+    InvalidEncoding,
+    /// The block size was zero.
+    InvalidBlockSize,
+    /// Every ID up to `u64::MAX` has been handed out.
+    Exhausted,
+}
+
+impl From<StorageRuntimeError> for SequenceError {
+    fn from(error: StorageRuntimeError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+impl From<crate::StartupError> for SequenceError {
+    fn from(error: crate::StartupError) -> Self {
+        Self::Storage(error.into())
+    }
+}
+
+/// Durable generator of strictly increasing `u64` IDs.
+///
+/// The handle reserves IDs in blocks: each reservation records the end of the
+/// new block as one WAL snapshot, and [`Sequence::next`] then serves the block
+/// from RAM. After a reset the sequence resumes at the end of the last durable
+/// block, so IDs never repeat even though the unused rest of that block is
+/// skipped.
+pub struct Sequence {
+    collection_id: CollectionId,
+    next: u64,
+    reserved_end: u64,
+    block_size: u64,
+}
+
+impl Collection for Sequence {
+    fn id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    fn collection_type(&self) -> CollectionType {
+        CollectionType::Sequence
+    }
+}
+
+impl Sequence {
+    /// Creates a new sequence whose first ID is `first`.
+    ///
+    /// No block is reserved until the first call to [`Sequence::next`].
+    pub fn new<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        first: u64,
+    ) -> Result<Self, SequenceError> {
+        let collection_id = storage.allocate_collection_id()?;
+        storage.append_new_collection(collection_id, CollectionType::SEQUENCE_CODE)?;
+        write_reserved_end(storage, collection_id, first)?;
+        Ok(Self {
+            collection_id,
+            next: first,
+            reserved_end: first,
+            block_size: DEFAULT_SEQUENCE_BLOCK_SIZE,
+        })
+    }
+
+    /// Opens an existing sequence.
+    ///
+    /// The next ID is the end of the last durable block; IDs of that block
+    /// that were not handed out before the reset are skipped.
+    pub fn open<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Self, SequenceError> {
+        validate_collection::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage,
+            collection_id,
+        )?;
+        let reserved_end = Self::replay(storage, collection_id)?;
+        Ok(Self {
+            collection_id,
+            next: reserved_end,
+            reserved_end,
+            block_size: DEFAULT_SEQUENCE_BLOCK_SIZE,
+        })
+    }
+
+    /// Overrides how many IDs each reservation covers.
+    ///
+    /// The size applies from the next reservation.
+    pub fn with_block_size(mut self, block_size: u64) -> Result<Self, SequenceError> {
+        if block_size == 0 {
+            return Err(SequenceError::InvalidBlockSize);
+        }
+        self.block_size = block_size;
+        Ok(self)
+    }
+
+    /// Returns the configured reservation block size.
+    pub fn block_size(&self) -> u64 {
+        self.block_size
+    }
+
+    /// Returns the stable collection id.
+    pub fn collection_id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    /// Returns the ID the next call to [`Sequence::next`] hands out.
+    pub fn peek(&self) -> u64 {
+        self.next
+    }
+
+    /// Returns the exclusive end of the durably reserved block.
+    pub fn reserved_end(&self) -> u64 {
+        self.reserved_end
+    }
+
+    /// Returns the next ID, reserving a new block first when the current one
+    /// is used up.
+    ///
+    /// The ID is only returned once its block is durable. A failed
+    /// reservation leaves the handle unchanged.
+    pub fn next<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<u64, SequenceError> {
+        if self.next == self.reserved_end {
+            let reserved_end = self.reserved_end.saturating_add(self.block_size);
+            if reserved_end == self.reserved_end {
+                return Err(SequenceError::Exhausted);
+            }
+            write_reserved_end(storage, self.collection_id, reserved_end)?;
+            self.reserved_end = reserved_end;
+        }
+        let id = self.next;
+        self.next += 1;
+        Ok(id)
+    }
+
+    fn replay<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        collection_id: CollectionId,
+    ) -> Result<u64, SequenceError> {
+        let mut reserved_end = 0u64;
+        let result = storage
+            .memory
+            .state
+            .visit_wal_records::<REGION_SIZE, IO, SequenceError, _>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                |_flash, record| {
+                    match record {
+                        WalRecord::NewCollection {
+                            collection_id: seen,
+                            collection_type,
+                        } if seen == collection_id
+                            && collection_type == CollectionType::SEQUENCE_CODE =>
+                        {
+                            reserved_end = 0;
+                        }
+                        WalRecord::Snapshot {
+                            collection_id: seen,
+                            collection_type,
+                            payload,
+                        } if seen == collection_id
+                            && collection_type == CollectionType::SEQUENCE_CODE =>
+                        {
+                            reserved_end = decode_state(payload)?;
+                        }
+                        WalRecord::DropCollection {
+                            collection_id: seen,
+                        } if seen == collection_id => {
+                            reserved_end = 0;
+                        }
+                        _ => {}
+                    }
+                    Ok(())
+                },
+            );
+        match result {
+            Ok(()) => Ok(reserved_end),
+            Err(StorageVisitError::Storage(error)) => Err(SequenceError::Storage(error)),
+            Err(StorageVisitError::Visitor(error)) => Err(error),
+        }
+    }
+}
+
+/// Records `reserved_end` as the full sequence state in one WAL snapshot.
+fn write_reserved_end<
+    'db,
+    'storage_mem,
+    IO: FlashIo,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+    reserved_end: u64,
+) -> Result<(), SequenceError> {
+    let payload = encode_state(reserved_end);
+    storage.enter_mode(StorageMode::UpdatingCollection(
+        CollectionUpdateMode::Running,
+    ))?;
+    let result = storage
+        .memory
+        .state
+        .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+            storage.backing,
+            &mut storage.memory.workspace,
+            collection_id,
+            CollectionType::SEQUENCE_CODE,
+            &payload,
+        );
+    storage.finish_mode();
+    result?;
+    Ok(())
+}
+
+fn validate_collection<
+    IO: FlashIo,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+) -> Result<(), SequenceError> {
+    let collection = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .ok_or(SequenceError::UnknownCollection(collection_id))?;
+    if collection.basis() == StartupCollectionBasis::Dropped {
+        return Err(SequenceError::DroppedCollection(collection_id));
+    }
+    storage.runtime().ensure_not_quarantined(collection_id)?;
+    if collection.collection_type() != Some(CollectionType::SEQUENCE_CODE) {
+        return Err(SequenceError::CollectionTypeMismatch {
+            collection_id,
+            actual: collection.collection_type(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn empty_snapshot() -> &'static [u8] {
+    &EMPTY_SNAPSHOT
+}
+
+fn encode_state(reserved_end: u64) -> [u8; STATE_LEN] {
+    let mut payload = EMPTY_SNAPSHOT;
+    payload[STATE_MAGIC.len() + size_of::<u16>()..].copy_from_slice(&reserved_end.to_le_bytes());
+    payload
+}
+
+fn decode_state(payload: &[u8]) -> Result<u64, SequenceError> {
+    if payload.len() != STATE_LEN
+        || payload[..STATE_MAGIC.len()] != STATE_MAGIC
+        || payload[STATE_MAGIC.len()..STATE_MAGIC.len() + size_of::<u16>()]
+            != STATE_VERSION.to_le_bytes()
+    {
+        return Err(SequenceError::InvalidEncoding);
+    }
+    let mut bytes = [0u8; size_of::<u64>()];
+    bytes.copy_from_slice(&payload[STATE_MAGIC.len() + size_of::<u16>()..]);
+    Ok(u64::from_le_bytes(bytes))
+}
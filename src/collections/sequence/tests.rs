@@ -0,0 +1,193 @@
+use super::*;
+
+use crate::MockFlash;
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+
+fn snapshot_count(
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    collection_id: CollectionId,
+) -> usize {
+    let mut count = 0usize;
+    storage
+        .memory
+        .state
+        .visit_wal_records::<REGION_SIZE, TestFlash, SequenceError, _>(
+            storage.backing,
+            &mut storage.memory.workspace,
+            |_flash, record| {
+                if matches!(
+                    record,
+                    WalRecord::Snapshot { collection_id: seen, .. } if seen == collection_id
+                ) {
+                    count += 1;
+                }
+                Ok(())
+            },
+        )
+        .unwrap();
+    count
+}
+
+//= spec/sequence.md#block-reservation
+//= type=test
+//# `RING-SEQ-001` `Sequence::new` MUST create a collection with
+//# `collection_type = 0x0008` and record the first ID as the reserved end
+//# before returning.
+#[test]
+fn requirement_sequence_new_records_first_id() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let sequence = Sequence::new(&mut storage, 100).unwrap();
+        assert_eq!(sequence.collection_type(), CollectionType::Sequence);
+        assert_eq!(
+            storage
+                .collections()
+                .iter()
+                .find(|collection| collection.collection_id() == sequence.collection_id())
+                .and_then(|collection| collection.collection_type()),
+            Some(CollectionType::SEQUENCE_CODE)
+        );
+        assert_eq!(sequence.peek(), 100);
+        assert_eq!(sequence.reserved_end(), 100);
+        assert_eq!(snapshot_count(&mut storage, sequence.collection_id()), 1);
+        sequence.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut sequence = Sequence::open(id, &mut storage).unwrap();
+    assert_eq!(sequence.peek(), 100);
+    assert_eq!(sequence.next(&mut storage).unwrap(), 100);
+}
+
+//= spec/sequence.md#block-reservation
+//= type=test
+//# `RING-SEQ-002` `next` MUST return consecutive IDs from RAM while they are
+//# below the reserved end, and MUST append exactly one WAL snapshot
+//# advancing the reserved end by the block size before returning the first
+//# ID of a new block.
+#[test]
+fn requirement_sequence_reserves_one_snapshot_per_block() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut sequence = Sequence::new(&mut storage, 0)
+        .unwrap()
+        .with_block_size(10)
+        .unwrap();
+    let id = sequence.collection_id();
+
+    for expected in 0..10 {
+        assert_eq!(sequence.next(&mut storage).unwrap(), expected);
+        assert_eq!(sequence.reserved_end(), 10);
+    }
+    assert_eq!(snapshot_count(&mut storage, id), 2);
+
+    for expected in 10..25 {
+        assert_eq!(sequence.next(&mut storage).unwrap(), expected);
+    }
+    assert_eq!(sequence.reserved_end(), 30);
+    assert_eq!(snapshot_count(&mut storage, id), 4);
+}
+
+//= spec/sequence.md#block-reservation
+//= type=test
+//# `RING-SEQ-003` The block size MUST default to
+//# `DEFAULT_SEQUENCE_BLOCK_SIZE`, MUST be configurable per handle, and a zero
+//# block size MUST be rejected.
+#[test]
+fn requirement_sequence_block_size_configuration() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut sequence = Sequence::new(&mut storage, 1).unwrap();
+    assert_eq!(sequence.block_size(), DEFAULT_SEQUENCE_BLOCK_SIZE);
+    assert_eq!(sequence.next(&mut storage).unwrap(), 1);
+    assert_eq!(sequence.reserved_end(), 1 + DEFAULT_SEQUENCE_BLOCK_SIZE);
+
+    let sequence = sequence.with_block_size(4).unwrap();
+    assert_eq!(sequence.block_size(), 4);
+    assert!(matches!(
+        sequence.with_block_size(0),
+        Err(SequenceError::InvalidBlockSize)
+    ));
+}
+
+//= spec/sequence.md#monotonicity
+//= type=test
+//# `RING-SEQ-004` A reopened sequence MUST resume at the reserved end of the
+//# newest durable snapshot, so no ID returned before a reset is returned
+//# again.
+#[test]
+fn requirement_sequence_resumes_after_reserved_block() {
+    let mut flash = TestFlash::new(0xff);
+    let mut issued = std::vec::Vec::new();
+    let id = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut sequence = Sequence::new(&mut storage, 0)
+            .unwrap()
+            .with_block_size(8)
+            .unwrap();
+        for _ in 0..11 {
+            issued.push(sequence.next(&mut storage).unwrap());
+        }
+        sequence.collection_id()
+    };
+
+    for reset in 0..3 {
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        let mut sequence = Sequence::open(id, &mut storage)
+            .unwrap()
+            .with_block_size(8)
+            .unwrap();
+        assert_eq!(sequence.peek(), 16 + reset * 8);
+        for _ in 0..3 {
+            issued.push(sequence.next(&mut storage).unwrap());
+        }
+    }
+
+    assert!(issued.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(&issued[11..], &[16, 17, 18, 24, 25, 26, 32, 33, 34]);
+}
+
+//= spec/sequence.md#monotonicity
+//= type=test
+//# `RING-SEQ-005` A sequence whose reserved end is `u64::MAX` MUST report
+//# exhaustion instead of wrapping, and `Sequence::open` MUST reject
+//# collections that are not sequences.
+#[test]
+fn requirement_sequence_exhaustion_and_type_checks() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut sequence = Sequence::new(&mut storage, u64::MAX - 2).unwrap();
+    assert_eq!(sequence.next(&mut storage).unwrap(), u64::MAX - 2);
+    assert_eq!(sequence.next(&mut storage).unwrap(), u64::MAX - 1);
+    assert_eq!(sequence.reserved_end(), u64::MAX);
+    assert!(matches!(
+        sequence.next(&mut storage),
+        Err(SequenceError::Exhausted)
+    ));
+    assert_eq!(sequence.peek(), u64::MAX);
+
+    let empty = storage.allocate_collection_id().unwrap();
+    storage
+        .append_new_collection(empty, CollectionType::SEQUENCE_CODE)
+        .unwrap();
+    assert_eq!(Sequence::open(empty, &mut storage).unwrap().peek(), 0);
+    assert_eq!(decode_state(empty_snapshot()).unwrap(), 0);
+
+    let other = storage.allocate_collection_id().unwrap();
+    storage
+        .append_new_collection(other, CollectionType::CELL_CODE)
+        .unwrap();
+    assert!(matches!(
+        Sequence::open(other, &mut storage),
+        Err(SequenceError::CollectionTypeMismatch { .. })
+    ));
+    assert!(matches!(
+        Sequence::open(CollectionId(999), &mut storage),
+        Err(SequenceError::UnknownCollection(_))
+    ));
+}
@@ -0,0 +1,336 @@
+//! Durable ordered set collection APIs.
+//!
+//! An [`LsmSet`] is its own collection type, [`CollectionType::SET_CODE`],
+//! stored with the map's manifest, flush, compaction, and replay machinery.
+//! Its frontier, snapshots, and runs hold key-only member and removal entries
+//! that carry no value length, so sets and maps never open as each other.
+
+use core::ops::{Bound, RangeBounds};
+
+use heapless::Vec;
+
+use crate::collections::map::{
+    LsmKey, LsmMap, LsmMapError, LsmMapMemory, MapError, MapStorageError, DEFAULT_MAX_RUNS,
+};
+use crate::flash_io::FlashIo;
+use crate::{CollectionId, CollectionType, Storage};
+
+#[cfg(test)]
+mod tests;
+
+/// Members copied per range scan while a set operation fills its target.
+const SET_OPERATION_BATCH: usize = 16;
+
+/// Caller-owned memory for a durable set handle.
+pub struct LsmSetMemory<K, const MAX_RUNS: usize = DEFAULT_MAX_RUNS>
+where
+    K: LsmKey,
+{
+    map: LsmMapMemory<K, (), MAX_RUNS>,
+}
+
+impl<K, const MAX_RUNS: usize> LsmSetMemory<K, MAX_RUNS>
+where
+    K: LsmKey,
+{
+    /// Allocates caller-owned memory for a durable set handle.
+    pub fn new() -> Self {
+        Self {
+            map: LsmMapMemory::new(),
+        }
+    }
+}
+
+impl<K, const MAX_RUNS: usize> Default for LsmSetMemory<K, MAX_RUNS>
+where
+    K: LsmKey,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Small durable set handle built on the LSM map engine.
+pub struct LsmSet<'mem, K, const MAX_RUNS: usize = DEFAULT_MAX_RUNS>
+where
+    K: LsmKey,
+{
+    map: LsmMap<'mem, K, (), MAX_RUNS>,
+}
+
+impl<'set, K, const MAX_RUNS: usize> LsmSet<'set, K, MAX_RUNS>
+where
+    K: LsmKey,
+{
+    /// Creates a new durable set collection and returns its small handle.
+    pub fn new<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'set mut LsmSetMemory<K, MAX_RUNS>,
+    ) -> Result<Self, LsmMapError> {
+        Ok(Self {
+            map: LsmMap::new_with_type(storage, &mut memory.map, CollectionType::SET_CODE)?,
+        })
+    }
+
+    /// Opens and validates an existing durable set collection.
+    ///
+    /// Collections of any other type, including maps, are rejected with
+    /// [`MapStorageError::CollectionTypeMismatch`].
+    pub fn open<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'set mut LsmSetMemory<K, MAX_RUNS>,
+    ) -> Result<Self, LsmMapError> {
+        Ok(Self {
+            map: LsmMap::open_with_type(
+                collection_id,
+                storage,
+                &mut memory.map,
+                CollectionType::SET_CODE,
+            )?,
+        })
+    }
+
+    /// Returns the stable collection id for this durable set.
+    pub fn collection_id(&self) -> CollectionId {
+        self.map.collection_id()
+    }
+
+    /// Overrides the live-run threshold used by `insert` and `remove`.
+    pub fn with_compaction_run_target(self, run_target: usize) -> Result<Self, LsmMapError> {
+        Ok(Self {
+            map: self.map.with_compaction_run_target(run_target)?,
+        })
+    }
+
+    /// Returns the configured live-run compaction threshold.
+    pub fn compaction_run_target(&self) -> usize {
+        self.map.compaction_run_target()
+    }
+
+    /// Adds `key` to the set and reports whether compaction is now needed.
+    pub fn insert<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        key: K,
+    ) -> Result<bool, LsmMapError> {
+        self.map.set(storage, key, ())
+    }
+
+    /// Removes `key` from the set and reports whether compaction is now needed.
+    pub fn remove<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        key: K,
+    ) -> Result<bool, LsmMapError> {
+        self.map.delete(storage, key)
+    }
+
+    /// Returns whether `key` is currently a member of the set.
+    pub fn contains<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        key: &K,
+    ) -> Result<bool, LsmMapError> {
+        Ok(self.map.get(storage, key, |_, _| ())?.is_some())
+    }
+
+    /// Visits the members that fall in `range` in ascending key order.
+    pub fn range<
+        'db,
+        'mem,
+        R,
+        F,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        range: R,
+        mut visitor: F,
+    ) -> Result<(), LsmMapError>
+    where
+        R: RangeBounds<K>,
+        F: FnMut(K) -> Result<(), LsmMapError>,
+    {
+        self.map.range(storage, range, |key, ()| visitor(key))
+    }
+
+    /// Compacts selected committed runs and reports whether a replacement
+    /// manifest was committed.
+    pub fn compact_and_report<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<bool, LsmMapError> {
+        self.map.compact_and_report(storage)
+    }
+
+    /// Compacts selected committed runs. Having nothing to compact is success.
+    pub fn compact<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), LsmMapError> {
+        self.map.compact(storage)
+    }
+}
+
+impl<'set, K, const MAX_RUNS: usize> LsmSet<'set, K, MAX_RUNS>
+where
+    K: LsmKey + Clone,
+{
+    /// Inserts every member of `self` and `other` into `target`.
+    ///
+    /// Existing members of `target` are kept, so pass a freshly created set to
+    /// receive exactly the union.
+    pub fn union_into<
+        'db,
+        'mem,
+        'other,
+        'target,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        other: &mut LsmSet<'other, K, MAX_RUNS>,
+        target: &mut LsmSet<'target, K, MAX_RUNS>,
+    ) -> Result<(), LsmMapError> {
+        self.insert_members_into::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage, None, target,
+        )?;
+        other.insert_members_into::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage, None, target,
+        )
+    }
+
+    /// Inserts every member present in both `self` and `other` into `target`.
+    ///
+    /// Existing members of `target` are kept, so pass a freshly created set to
+    /// receive exactly the intersection.
+    pub fn intersection_into<
+        'db,
+        'mem,
+        'other,
+        'target,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        other: &mut LsmSet<'other, K, MAX_RUNS>,
+        target: &mut LsmSet<'target, K, MAX_RUNS>,
+    ) -> Result<(), LsmMapError> {
+        self.insert_members_into::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage,
+            Some(other),
+            target,
+        )
+    }
+
+    /// Copies members of `self`, optionally filtered by membership in
+    /// `filter`, into `target`.
+    ///
+    /// Sets share the storage-owned frontier buffer, so members are read in
+    /// bounded batches and each batch is written before the next scan resumes
+    /// after its last key.
+    fn insert_members_into<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        mut filter: Option<&mut LsmSet<'_, K, MAX_RUNS>>,
+        target: &mut LsmSet<'_, K, MAX_RUNS>,
+    ) -> Result<(), LsmMapError> {
+        let mut resume: Option<K> = None;
+        loop {
+            let mut batch = Vec::<K, SET_OPERATION_BATCH>::new();
+            let start = match resume.take() {
+                Some(key) => Bound::Excluded(key),
+                None => Bound::Unbounded,
+            };
+            self.map
+                .range_while(storage, (start, Bound::Unbounded), |key, ()| {
+                    batch
+                        .push(key)
+                        .map_err(|_| MapStorageError::Map(MapError::BufferTooSmall))?;
+                    Ok(!batch.is_full())
+                })?;
+            let complete = !batch.is_full();
+            resume = batch.last().cloned();
+
+            for key in batch {
+                if let Some(filter) = filter.as_deref_mut() {
+                    if !filter.contains(storage, &key)? {
+                        continue;
+                    }
+                }
+                if target.insert(storage, key)? {
+                    target.compact(storage)?;
+                }
+            }
+            if complete {
+                return Ok(());
+            }
+        }
+    }
+}
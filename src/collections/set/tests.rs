@@ -0,0 +1,258 @@
+use super::*;
+
+use crate::collections::map::MapFrontierMemory;
+use crate::{CollectionType, MockFlash, StorageFormatConfig};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 48;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestSet<'mem> = LsmSet<'mem, u16, 8>;
+type TestSetMemory = LsmSetMemory<u16, 8>;
+
+fn format_storage(
+    flash: &mut TestFlash,
+) -> Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT> {
+    Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        flash,
+        StorageFormatConfig::new(5, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap()
+}
+
+fn insert_all(
+    set: &mut TestSet<'_>,
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    keys: impl IntoIterator<Item = u16>,
+) {
+    for key in keys {
+        if set.insert(storage, key).unwrap() {
+            set.compact(storage).unwrap();
+        }
+    }
+}
+
+fn members(
+    set: &mut TestSet<'_>,
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    range: impl RangeBounds<u16>,
+) -> std::vec::Vec<u16> {
+    let mut keys = std::vec::Vec::new();
+    set.range(storage, range, |key| {
+        keys.push(key);
+        Ok(())
+    })
+    .unwrap();
+    keys
+}
+
+//= spec/set.md#set-model
+//= type=test
+//# `RING-SET-001` `LsmSet::new` MUST create an empty collection with
+//# `collection_type = 0x000a` whose runs use the map manifest layout.
+#[test]
+fn requirement_set_new_creates_empty_set_collection() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = format_storage(&mut flash);
+    let mut memory = TestSetMemory::new();
+    let mut set = TestSet::new(&mut storage, &mut memory).unwrap();
+
+    assert_eq!(CollectionType::SET_CODE, 0x000a);
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == set.collection_id())
+            .and_then(|collection| collection.collection_type()),
+        Some(CollectionType::SET_CODE)
+    );
+    assert!(!set.contains(&mut storage, &1).unwrap());
+    assert!(members(&mut set, &mut storage, ..).is_empty());
+}
+
+//= spec/set.md#set-model
+//= type=test
+//# `RING-SET-002` A member MUST be stored as a key-only member entry and a
+//# removal as a key-only removal entry, with no value length, in the frontier,
+//# snapshots, and runs.
+#[test]
+fn requirement_set_members_are_key_only_entries() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = format_storage(&mut flash);
+    let mut memory = TestSetMemory::new();
+    let mut set = TestSet::new(&mut storage, &mut memory).unwrap();
+    insert_all(&mut set, &mut storage, [3, 8]);
+    set.remove(&mut storage, 5).unwrap();
+    let id = set.collection_id();
+
+    let mut buffer = [0u8; REGION_SIZE];
+    let mut frontier_memory = MapFrontierMemory::<u16, 8>::new();
+    let frontier = storage
+        .open_map::<u16, (), 8>(id, &mut buffer, &mut frontier_memory)
+        .unwrap();
+    let mut snapshot = [0u8; REGION_SIZE];
+    let used = frontier.encode_snapshot_into(&mut snapshot).unwrap();
+    let snapshot = &snapshot[..used];
+
+    let contains = |entry: &[u8]| snapshot.windows(entry.len()).any(|bytes| bytes == entry);
+    for (kind, key) in [(3u8, 3u16), (4, 5), (3, 8)] {
+        let mut entry = std::vec![kind, 2, 0, 0, 0];
+        entry.extend_from_slice(&key.to_be_bytes());
+        assert!(contains(&entry), "key {key}");
+
+        let valued_kind = if kind == 3 { 1 } else { 2 };
+        let mut valued = std::vec![valued_kind, 2, 0, 0, 0, 0, 0, 0, 0];
+        valued.extend_from_slice(&key.to_be_bytes());
+        assert!(!contains(&valued), "key {key}");
+    }
+}
+
+//= spec/set.md#set-model
+//= type=test
+//# `RING-SET-003` `insert`, `remove`, and `contains` MUST follow newest-wins
+//# visibility across the frontier and retained runs, including removals that
+//# shadow members already compacted into runs.
+#[test]
+fn requirement_set_membership_follows_newest_entry() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = format_storage(&mut flash);
+    let mut memory = TestSetMemory::new();
+    let mut set = TestSet::new(&mut storage, &mut memory).unwrap();
+
+    insert_all(&mut set, &mut storage, 0..100);
+    for key in (0..100).step_by(3) {
+        if set.remove(&mut storage, key).unwrap() {
+            set.compact(&mut storage).unwrap();
+        }
+    }
+    insert_all(&mut set, &mut storage, [9]);
+
+    for key in 0..100 {
+        assert_eq!(
+            set.contains(&mut storage, &key).unwrap(),
+            key % 3 != 0 || key == 9,
+            "key {key}"
+        );
+    }
+}
+
+//= spec/set.md#set-model
+//= type=test
+//# `RING-SET-004` Reopening storage MUST restore the same members through the
+//# map replay path, and `LsmSet::open` MUST reject collections that are not
+//# sets, including maps.
+#[test]
+fn requirement_set_survives_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let (id, other, map_id) = {
+        let mut storage = format_storage(&mut flash);
+        let mut memory = TestSetMemory::new();
+        let mut set = TestSet::new(&mut storage, &mut memory).unwrap();
+        insert_all(&mut set, &mut storage, 0..40);
+        set.remove(&mut storage, 7).unwrap();
+        let other = storage.allocate_collection_id().unwrap();
+        storage
+            .append_new_collection(other, CollectionType::RING_LOG_CODE)
+            .unwrap();
+        let mut map_memory = LsmMapMemory::<u16, (), 8>::new();
+        let mut map = LsmMap::new(&mut storage, &mut map_memory).unwrap();
+        map.set(&mut storage, 3, ()).unwrap();
+        (set.collection_id(), other, map.collection_id())
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = TestSetMemory::new();
+    let mut set = TestSet::open(id, &mut storage, &mut memory).unwrap();
+    let expected: std::vec::Vec<u16> = (0..40).filter(|key| *key != 7).collect();
+    assert_eq!(members(&mut set, &mut storage, ..), expected);
+
+    assert!(TestSet::open(other, &mut storage, &mut memory).is_err());
+    assert!(matches!(
+        TestSet::open(map_id, &mut storage, &mut memory),
+        Err(MapStorageError::CollectionTypeMismatch {
+            expected: CollectionType::SET_CODE,
+            actual: Some(CollectionType::MAP_CODE),
+            ..
+        })
+    ));
+}
+
+//= spec/set.md#set-model
+//= type=test
+//# `RING-SET-007` `LsmMap::open` MUST reject set collections.
+#[test]
+fn requirement_map_open_rejects_set() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = format_storage(&mut flash);
+    let mut memory = TestSetMemory::new();
+    let mut set = TestSet::new(&mut storage, &mut memory).unwrap();
+    insert_all(&mut set, &mut storage, [1, 2]);
+    let id = set.collection_id();
+
+    let mut map_memory = LsmMapMemory::<u16, (), 8>::new();
+    assert!(matches!(
+        LsmMap::open(id, &mut storage, &mut map_memory),
+        Err(MapStorageError::CollectionTypeMismatch {
+            expected: CollectionType::MAP_CODE,
+            actual: Some(CollectionType::SET_CODE),
+            ..
+        })
+    ));
+}
+
+//= spec/set.md#range-scans-and-set-operations
+//= type=test
+//# `RING-SET-005` `range` MUST visit the members within the requested bounds in
+//# ascending order exactly once and MUST skip removed keys.
+#[test]
+fn requirement_set_range_visits_members_in_order() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = format_storage(&mut flash);
+    let mut memory = TestSetMemory::new();
+    let mut set = TestSet::new(&mut storage, &mut memory).unwrap();
+
+    insert_all(&mut set, &mut storage, (0..120).rev());
+    for key in [20, 21, 50] {
+        set.remove(&mut storage, key).unwrap();
+    }
+    insert_all(&mut set, &mut storage, [21]);
+
+    let expected: std::vec::Vec<u16> = (18..=52).filter(|key| ![20, 50].contains(key)).collect();
+    assert_eq!(members(&mut set, &mut storage, 18..=52), expected);
+    assert_eq!(members(&mut set, &mut storage, 117..), [117, 118, 119]);
+    assert_eq!(members(&mut set, &mut storage, ..).len(), 118);
+}
+
+//= spec/set.md#range-scans-and-set-operations
+//= type=test
+//# `RING-SET-006` `union_into` MUST insert every member of both sets into the
+//# target and `intersection_into` MUST insert exactly the members present in
+//# both sets, leaving both source sets unchanged.
+#[test]
+fn requirement_set_union_and_intersection_fill_target() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = format_storage(&mut flash);
+    let mut left_memory = TestSetMemory::new();
+    let mut right_memory = TestSetMemory::new();
+    let mut union_memory = TestSetMemory::new();
+    let mut intersection_memory = TestSetMemory::new();
+    let mut left = TestSet::new(&mut storage, &mut left_memory).unwrap();
+    let mut right = TestSet::new(&mut storage, &mut right_memory).unwrap();
+    let mut union = TestSet::new(&mut storage, &mut union_memory).unwrap();
+    let mut intersection = TestSet::new(&mut storage, &mut intersection_memory).unwrap();
+
+    insert_all(&mut left, &mut storage, (0..60).step_by(2));
+    insert_all(&mut right, &mut storage, (0..60).step_by(3));
+    left.union_into(&mut storage, &mut right, &mut union)
+        .unwrap();
+    left.intersection_into(&mut storage, &mut right, &mut intersection)
+        .unwrap();
+
+    let expected: std::vec::Vec<u16> = (0..60).filter(|key| key % 2 == 0 || key % 3 == 0).collect();
+    assert_eq!(members(&mut union, &mut storage, ..), expected);
+    let expected: std::vec::Vec<u16> = (0..60).step_by(6).collect();
+    assert_eq!(members(&mut intersection, &mut storage, ..), expected);
+    assert_eq!(members(&mut left, &mut storage, ..).len(), 30);
+    assert_eq!(members(&mut right, &mut storage, ..).len(), 20);
+}
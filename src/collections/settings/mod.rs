@@ -0,0 +1,590 @@
+//! Typed hierarchical settings built on the map collection.
+//!
+//! [`Settings`] stores postcard-encoded values in an [`LsmMap`] keyed by
+//! `/`-separated paths such as `net/wifi/ssid`. String keys keep their raw
+//! byte order, so every key below a path prefix is one contiguous map range
+//! and subtree operations are range scans. The schema version lives under the
+//! empty key, which no valid setting path can name.
+
+use core::ops::Bound;
+
+use heapless::{String, Vec};
+use postcard::{from_bytes, to_slice};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::collections::map::{
+    LsmMap, LsmMapError, LsmMapMemory, MapError, MapStorageError, DEFAULT_MAX_RUNS,
+};
+use crate::flash_io::FlashIo;
+use crate::storage::StorageRuntimeError;
+use crate::{CollectionId, Storage, TransactionMemory};
+
+#[cfg(test)]
+mod tests;
+
+/// Default capacity, in bytes, of one settings key path.
+pub const DEFAULT_SETTINGS_KEY_LEN: usize = 48;
+
+/// Default capacity, in bytes, of one encoded settings value.
+pub const DEFAULT_SETTINGS_VALUE_LEN: usize = 64;
+
+/// Keys removed per range scan while a subtree is reset.
+const RESET_BATCH: usize = 16;
+
+/// Map key type used by [`Settings`].
+pub type SettingsKey<const KEY_LEN: usize> = String<KEY_LEN>;
+
+/// Map value type used by [`Settings`]: one postcard-encoded setting value.
+pub type SettingsValue<const VALUE_LEN: usize> = Vec<u8, VALUE_LEN>;
+
+/// Errors returned by [`Settings`].
+#[derive(Debug)]
+pub enum SettingsError {
+    /// The underlying map failed.
+    Map(LsmMapError),
+    /// A key path was empty, too long, or had an empty `/` segment.
+    InvalidKey,
+    /// The encoded value does not fit in the configured value capacity.
+    ValueTooLarge { capacity: usize },
+    /// Postcard failed to encode or decode a value.
+    SerializationError,
+    /// The stored schema is newer than the version this build understands.
+    SchemaTooNew { stored: u32, supported: u32 },
+}
+
+impl From<LsmMapError> for SettingsError {
+    fn from(error: LsmMapError) -> Self {
+        Self::Map(error)
+    }
+}
+
+impl From<StorageRuntimeError> for SettingsError {
+    fn from(error: StorageRuntimeError) -> Self {
+        Self::Map(MapStorageError::from(error))
+    }
+}
+
+impl From<postcard::Error> for SettingsError {
+    fn from(_: postcard::Error) -> Self {
+        Self::SerializationError
+    }
+}
+
+/// Compile-time description of one typed setting and its default value.
+///
+/// ```
+/// use borromean::Setting;
+///
+/// const WIFI_CHANNEL: Setting<u8> = Setting::new("net/wifi/channel", 6);
+/// assert_eq!(WIFI_CHANNEL.key(), "net/wifi/channel");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Setting<T> {
+    key: &'static str,
+    default: T,
+}
+
+impl<T> Setting<T> {
+    /// Describes the setting stored at `key` that reads as `default` while
+    /// absent.
+    pub const fn new(key: &'static str, default: T) -> Self {
+        Self { key, default }
+    }
+
+    /// Returns the setting key path.
+    pub const fn key(&self) -> &'static str {
+        self.key
+    }
+
+    /// Returns the value reported while the key is absent.
+    pub const fn default_value(&self) -> &T {
+        &self.default
+    }
+}
+
+/// Caller-owned memory for a [`Settings`] handle.
+pub struct SettingsMemory<
+    const KEY_LEN: usize = DEFAULT_SETTINGS_KEY_LEN,
+    const VALUE_LEN: usize = DEFAULT_SETTINGS_VALUE_LEN,
+    const MAX_RUNS: usize = DEFAULT_MAX_RUNS,
+> {
+    map: LsmMapMemory<SettingsKey<KEY_LEN>, SettingsValue<VALUE_LEN>, MAX_RUNS>,
+}
+
+impl<const KEY_LEN: usize, const VALUE_LEN: usize, const MAX_RUNS: usize>
+    SettingsMemory<KEY_LEN, VALUE_LEN, MAX_RUNS>
+{
+    /// Allocates caller-owned memory for a settings handle.
+    pub fn new() -> Self {
+        Self {
+            map: LsmMapMemory::new(),
+        }
+    }
+}
+
+impl<const KEY_LEN: usize, const VALUE_LEN: usize, const MAX_RUNS: usize> Default
+    for SettingsMemory<KEY_LEN, VALUE_LEN, MAX_RUNS>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Typed hierarchical settings store with defaults and schema versioning.
+///
+/// Writes compact the underlying map on their own once it asks for
+/// compaction, except inside a migration or subtree reset, where compaction
+/// waits until the transaction commits.
+pub struct Settings<
+    'mem,
+    const KEY_LEN: usize = DEFAULT_SETTINGS_KEY_LEN,
+    const VALUE_LEN: usize = DEFAULT_SETTINGS_VALUE_LEN,
+    const MAX_RUNS: usize = DEFAULT_MAX_RUNS,
+> {
+    map: LsmMap<'mem, SettingsKey<KEY_LEN>, SettingsValue<VALUE_LEN>, MAX_RUNS>,
+    schema_version: u32,
+    transaction_open: bool,
+    compaction_pending: bool,
+}
+
+impl<'mem, const KEY_LEN: usize, const VALUE_LEN: usize, const MAX_RUNS: usize>
+    Settings<'mem, KEY_LEN, VALUE_LEN, MAX_RUNS>
+{
+    /// Creates an empty settings collection at `schema_version`.
+    pub fn new<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut SettingsMemory<KEY_LEN, VALUE_LEN, MAX_RUNS>,
+        schema_version: u32,
+    ) -> Result<Self, SettingsError> {
+        let mut settings = Self {
+            map: LsmMap::new(storage, &mut memory.map)?,
+            schema_version: 0,
+            transaction_open: false,
+            compaction_pending: false,
+        };
+        settings.write_schema_version(storage, schema_version)?;
+        Ok(settings)
+    }
+
+    /// Opens an existing settings collection and migrates it to
+    /// `schema_version`.
+    ///
+    /// `migrate` runs once per version step with the version being migrated
+    /// from. Each step and its schema version bump commit in one transaction,
+    /// so a reset during migration resumes at the first unfinished step. A
+    /// collection without a recorded version is treated as version 0.
+    pub fn open<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut SettingsMemory<KEY_LEN, VALUE_LEN, MAX_RUNS>,
+        schema_version: u32,
+        mut migrate: F,
+    ) -> Result<Self, SettingsError>
+    where
+        F: FnMut(
+            &mut Self,
+            &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+            u32,
+        ) -> Result<(), SettingsError>,
+    {
+        let mut settings = Self {
+            map: LsmMap::open(collection_id, storage, &mut memory.map)?,
+            schema_version: 0,
+            transaction_open: false,
+            compaction_pending: false,
+        };
+        settings.schema_version = settings
+            .load_encoded(storage, &SettingsKey::new())?
+            .unwrap_or(0);
+        if settings.schema_version > schema_version {
+            return Err(SettingsError::SchemaTooNew {
+                stored: settings.schema_version,
+                supported: schema_version,
+            });
+        }
+        while settings.schema_version < schema_version {
+            let from = settings.schema_version;
+            settings.in_transaction(storage, |settings, storage| {
+                migrate(settings, storage, from)?;
+                settings.write_schema_version(storage, from + 1)
+            })?;
+        }
+        Ok(settings)
+    }
+
+    /// Returns the stable collection id of the underlying map.
+    pub fn collection_id(&self) -> CollectionId {
+        self.map.collection_id()
+    }
+
+    /// Returns the schema version the stored settings conform to.
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// Returns the value of `setting`, or its default while the key is absent.
+    pub fn get<
+        'db,
+        'storage_mem,
+        T,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        setting: &Setting<T>,
+    ) -> Result<T, SettingsError>
+    where
+        T: Clone + DeserializeOwned,
+    {
+        Ok(self
+            .load(storage, setting.key())?
+            .unwrap_or_else(|| setting.default.clone()))
+    }
+
+    /// Durably stores `value` for `setting`.
+    pub fn set<
+        'db,
+        'storage_mem,
+        T,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        setting: &Setting<T>,
+        value: &T,
+    ) -> Result<(), SettingsError>
+    where
+        T: Serialize,
+    {
+        self.store(storage, setting.key(), value)
+    }
+
+    /// Reads the value stored at `key` without applying a default.
+    ///
+    /// Migrations use this to read keys whose type differs between schema
+    /// versions.
+    pub fn load<
+        'db,
+        'storage_mem,
+        T,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        key: &str,
+    ) -> Result<Option<T>, SettingsError>
+    where
+        T: DeserializeOwned,
+    {
+        let key = settings_key::<KEY_LEN>(key)?;
+        self.load_encoded(storage, &key)
+    }
+
+    /// Durably stores `value` at `key`.
+    pub fn store<
+        'db,
+        'storage_mem,
+        T,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        key: &str,
+        value: &T,
+    ) -> Result<(), SettingsError>
+    where
+        T: Serialize,
+    {
+        let key = settings_key::<KEY_LEN>(key)?;
+        self.store_encoded(storage, key, value)
+    }
+
+    /// Removes the value stored at `key`, so typed reads see the default.
+    pub fn remove<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        key: &str,
+    ) -> Result<(), SettingsError> {
+        let key = settings_key::<KEY_LEN>(key)?;
+        let compaction_needed = self.map.delete(storage, key)?;
+        self.after_write(storage, compaction_needed)
+    }
+
+    /// Removes `prefix` and every key below it in one transaction.
+    ///
+    /// `net/wifi` removes `net/wifi` and `net/wifi/ssid` but not
+    /// `net/wifi2`. An empty prefix resets every setting while keeping the
+    /// schema version.
+    pub fn reset_subtree<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        prefix: &str,
+    ) -> Result<(), SettingsError> {
+        let prefix = if prefix.is_empty() {
+            SettingsKey::new()
+        } else {
+            settings_key::<KEY_LEN>(prefix)?
+        };
+        if self.transaction_open {
+            return self.remove_subtree(storage, &prefix);
+        }
+        self.in_transaction(storage, |settings, storage| {
+            settings.remove_subtree(storage, &prefix)
+        })
+    }
+
+    /// Runs `operation` inside one map transaction and compacts afterwards
+    /// when any write asked for it.
+    fn in_transaction<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        operation: F,
+    ) -> Result<(), SettingsError>
+    where
+        F: FnOnce(
+            &mut Self,
+            &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        ) -> Result<(), SettingsError>,
+    {
+        let mut transaction_memory = TransactionMemory::<REGION_COUNT>::new();
+        let schema_version = self.schema_version;
+        let writer = storage.begin_transaction(self.collection_id(), &mut transaction_memory)?;
+        self.transaction_open = true;
+        let result = operation(self, storage);
+        self.transaction_open = false;
+        if let Err(error) = result {
+            self.schema_version = schema_version;
+            self.compaction_pending = false;
+            writer.rollback(storage)?;
+            return Err(error);
+        }
+        writer.commit(storage)?;
+        if core::mem::take(&mut self.compaction_pending) {
+            self.map.compact(storage)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes every key below `prefix` in bounded batches.
+    ///
+    /// Map handles share the storage-owned frontier buffer, so keys are read
+    /// in batches and each batch is deleted before the scan resumes after its
+    /// last key.
+    fn remove_subtree<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        prefix: &SettingsKey<KEY_LEN>,
+    ) -> Result<(), SettingsError> {
+        let mut start = Bound::Included(prefix.clone());
+        loop {
+            let mut batch = Vec::<SettingsKey<KEY_LEN>, RESET_BATCH>::new();
+            let mut complete = true;
+            self.map
+                .range_while(storage, (start, Bound::Unbounded), |key, _| {
+                    if !key.starts_with(prefix.as_str()) {
+                        return Ok(false);
+                    }
+                    if in_subtree(&key, prefix) {
+                        batch
+                            .push(key)
+                            .map_err(|_| MapStorageError::Map(MapError::BufferTooSmall))?;
+                    }
+                    complete = !batch.is_full();
+                    Ok(complete)
+                })?;
+
+            let Some(last) = batch.last().cloned() else {
+                return Ok(());
+            };
+            for key in batch {
+                let compaction_needed = self.map.delete(storage, key)?;
+                self.after_write(storage, compaction_needed)?;
+            }
+            if complete {
+                return Ok(());
+            }
+            start = Bound::Excluded(last);
+        }
+    }
+
+    fn write_schema_version<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        schema_version: u32,
+    ) -> Result<(), SettingsError> {
+        self.store_encoded(storage, SettingsKey::new(), &schema_version)?;
+        self.schema_version = schema_version;
+        Ok(())
+    }
+
+    fn load_encoded<
+        'db,
+        'storage_mem,
+        T,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        key: &SettingsKey<KEY_LEN>,
+    ) -> Result<Option<T>, SettingsError>
+    where
+        T: DeserializeOwned,
+    {
+        match self
+            .map
+            .get(storage, key, |_, value| from_bytes::<T>(value))?
+        {
+            Some(value) => Ok(Some(value?)),
+            None => Ok(None),
+        }
+    }
+
+    fn store_encoded<
+        'db,
+        'storage_mem,
+        T,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        key: SettingsKey<KEY_LEN>,
+        value: &T,
+    ) -> Result<(), SettingsError>
+    where
+        T: Serialize,
+    {
+        let mut buffer = [0u8; VALUE_LEN];
+        let encoded = match to_slice(value, &mut buffer) {
+            Ok(bytes) => SettingsValue::<VALUE_LEN>::from_slice(bytes).map_err(|()| {
+                SettingsError::ValueTooLarge {
+                    capacity: VALUE_LEN,
+                }
+            })?,
+            Err(postcard::Error::SerializeBufferFull) => {
+                return Err(SettingsError::ValueTooLarge {
+                    capacity: VALUE_LEN,
+                });
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let compaction_needed = self.map.set(storage, key, encoded)?;
+        self.after_write(storage, compaction_needed)
+    }
+
+    fn after_write<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        compaction_needed: bool,
+    ) -> Result<(), SettingsError> {
+        if !compaction_needed {
+            return Ok(());
+        }
+        if self.transaction_open {
+            self.compaction_pending = true;
+            return Ok(());
+        }
+        self.map.compact(storage)?;
+        Ok(())
+    }
+}
+
+/// Validates a `/`-separated settings key path.
+fn settings_key<const KEY_LEN: usize>(key: &str) -> Result<SettingsKey<KEY_LEN>, SettingsError> {
+    if key.is_empty() || key.split('/').any(str::is_empty) {
+        return Err(SettingsError::InvalidKey);
+    }
+    SettingsKey::try_from(key).map_err(|()| SettingsError::InvalidKey)
+}
+
+/// Returns whether `key` is `prefix` itself or a path below it.
+fn in_subtree<const KEY_LEN: usize>(
+    key: &SettingsKey<KEY_LEN>,
+    prefix: &SettingsKey<KEY_LEN>,
+) -> bool {
+    if key.is_empty() {
+        // The schema version is not a setting.
+        return false;
+    }
+    prefix.is_empty()
+        || key
+            .as_bytes()
+            .get(prefix.len())
+            .is_none_or(|separator| *separator == b'/')
+}
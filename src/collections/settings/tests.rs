@@ -0,0 +1,309 @@
+use super::*;
+
+use crate::{MockFlash, StorageFormatConfig};
+
+const REGION_SIZE: usize = 1024;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestStorage<'a> = Storage<'a, 'static, TestFlash, REGION_SIZE, REGION_COUNT>;
+type TestSettings<'mem> = Settings<'mem, 32, 32, 8>;
+type TestSettingsMemory = SettingsMemory<32, 32, 8>;
+
+const WIFI_SSID: Setting<String<16>> = Setting::new("net/wifi/ssid", String::new());
+const WIFI_CHANNEL: Setting<u8> = Setting::new("net/wifi/channel", 6);
+const WIFI_ENABLED: Setting<bool> = Setting::new("net/wifi/enabled", true);
+const WIFI2_CHANNEL: Setting<u8> = Setting::new("net/wifi2/channel", 11);
+const ETH_DHCP: Setting<bool> = Setting::new("net/eth/dhcp", true);
+const LOG_LEVEL: Setting<u8> = Setting::new("log/level", 2);
+
+fn format_storage(flash: &mut TestFlash) -> TestStorage<'_> {
+    Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        flash,
+        StorageFormatConfig::new(3, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap()
+}
+
+fn no_migrations(
+    _: &mut TestSettings<'_>,
+    _: &mut TestStorage<'_>,
+    from: u32,
+) -> Result<(), SettingsError> {
+    panic!("unexpected migration from version {from}");
+}
+
+fn load<T: DeserializeOwned>(
+    settings: &mut TestSettings<'_>,
+    storage: &mut TestStorage<'_>,
+    key: &str,
+) -> Option<T> {
+    settings.load(storage, key).unwrap()
+}
+
+fn ssid(name: &str) -> String<16> {
+    String::try_from(name).unwrap()
+}
+
+//= spec/settings.md#typed-settings
+//= type=test
+//# `RING-SETTINGS-001` Reading a setting whose key is absent MUST return the
+//# compile-time default from its `Setting` descriptor without writing it.
+#[test]
+fn requirement_settings_absent_keys_read_defaults() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = format_storage(&mut flash);
+    let mut memory = TestSettingsMemory::new();
+    let mut settings = TestSettings::new(&mut storage, &mut memory, 1).unwrap();
+
+    assert_eq!(settings.get(&mut storage, &WIFI_CHANNEL).unwrap(), 6);
+    assert!(settings.get(&mut storage, &WIFI_ENABLED).unwrap());
+    assert_eq!(settings.get(&mut storage, &WIFI_SSID).unwrap(), "");
+    assert_eq!(
+        load::<u8>(&mut settings, &mut storage, WIFI_CHANNEL.key()),
+        None
+    );
+}
+
+//= spec/settings.md#typed-settings
+//= type=test
+//# `RING-SETTINGS-002` Stored values MUST round-trip with their own types,
+//# survive reopening storage, and read as the default again once removed.
+#[test]
+fn requirement_settings_typed_values_survive_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = format_storage(&mut flash);
+        let mut memory = TestSettingsMemory::new();
+        let mut settings = TestSettings::new(&mut storage, &mut memory, 1).unwrap();
+        settings
+            .set(&mut storage, &WIFI_SSID, &ssid("borromean"))
+            .unwrap();
+        settings.set(&mut storage, &WIFI_CHANNEL, &13).unwrap();
+        settings.set(&mut storage, &WIFI_ENABLED, &false).unwrap();
+        settings.set(&mut storage, &LOG_LEVEL, &4).unwrap();
+        settings.remove(&mut storage, LOG_LEVEL.key()).unwrap();
+        settings.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = TestSettingsMemory::new();
+    let mut settings = TestSettings::open(id, &mut storage, &mut memory, 1, no_migrations).unwrap();
+    assert_eq!(
+        settings.get(&mut storage, &WIFI_SSID).unwrap(),
+        ssid("borromean")
+    );
+    assert_eq!(settings.get(&mut storage, &WIFI_CHANNEL).unwrap(), 13);
+    assert!(!settings.get(&mut storage, &WIFI_ENABLED).unwrap());
+    assert_eq!(settings.get(&mut storage, &LOG_LEVEL).unwrap(), 2);
+}
+
+//= spec/settings.md#typed-settings
+//= type=test
+//# `RING-SETTINGS-003` Keys MUST be non-empty `/`-separated paths without
+//# empty segments that fit the key capacity, and values MUST fit the value
+//# capacity, otherwise the write MUST fail without changing the store.
+#[test]
+fn requirement_settings_reject_invalid_keys_and_values() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = format_storage(&mut flash);
+    let mut memory = TestSettingsMemory::new();
+    let mut settings = TestSettings::new(&mut storage, &mut memory, 1).unwrap();
+
+    for key in [
+        "",
+        "/net",
+        "net/",
+        "net//wifi",
+        "a/very/long/path/that/overflows/32",
+    ] {
+        assert!(
+            matches!(
+                settings.store(&mut storage, key, &1u8),
+                Err(SettingsError::InvalidKey)
+            ),
+            "{key}"
+        );
+    }
+    assert!(matches!(
+        settings.store(
+            &mut storage,
+            "blob",
+            &(ssid("0123456789abcdef"), ssid("0123456789abcdef"))
+        ),
+        Err(SettingsError::ValueTooLarge { capacity: 32 })
+    ));
+    assert_eq!(
+        load::<(String<16>, String<16>)>(&mut settings, &mut storage, "blob"),
+        None
+    );
+}
+
+//= spec/settings.md#factory-reset
+//= type=test
+//# `RING-SETTINGS-004` `reset_subtree` MUST remove the prefix key and every
+//# key below it in one transaction, MUST keep keys that only share leading
+//# characters with the prefix, and an empty prefix MUST reset every setting
+//# while keeping the schema version.
+#[test]
+fn requirement_settings_reset_subtree_removes_prefix() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = format_storage(&mut flash);
+        let mut memory = TestSettingsMemory::new();
+        let mut settings = TestSettings::new(&mut storage, &mut memory, 4).unwrap();
+        settings.set(&mut storage, &WIFI_CHANNEL, &1).unwrap();
+        settings.set(&mut storage, &WIFI_ENABLED, &false).unwrap();
+        settings.set(&mut storage, &WIFI2_CHANNEL, &3).unwrap();
+        settings.set(&mut storage, &ETH_DHCP, &false).unwrap();
+        settings.set(&mut storage, &LOG_LEVEL, &0).unwrap();
+        settings.store(&mut storage, "net/wifi", &9u8).unwrap();
+        for index in 0..40u8 {
+            let mut key = String::<32>::try_from("net/wifi/known/").unwrap();
+            key.push_str(itoa(index).as_str()).unwrap();
+            settings.store(&mut storage, &key, &index).unwrap();
+        }
+
+        settings.reset_subtree(&mut storage, "net/wifi").unwrap();
+        settings.collection_id()
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = TestSettingsMemory::new();
+    let mut settings = TestSettings::open(id, &mut storage, &mut memory, 4, no_migrations).unwrap();
+    assert_eq!(settings.get(&mut storage, &WIFI_CHANNEL).unwrap(), 6);
+    assert!(settings.get(&mut storage, &WIFI_ENABLED).unwrap());
+    assert_eq!(load::<u8>(&mut settings, &mut storage, "net/wifi"), None);
+    assert_eq!(
+        load::<u8>(&mut settings, &mut storage, "net/wifi/known/17"),
+        None
+    );
+    assert_eq!(settings.get(&mut storage, &WIFI2_CHANNEL).unwrap(), 3);
+    assert!(!settings.get(&mut storage, &ETH_DHCP).unwrap());
+    assert_eq!(settings.get(&mut storage, &LOG_LEVEL).unwrap(), 0);
+
+    settings.reset_subtree(&mut storage, "").unwrap();
+    assert_eq!(settings.get(&mut storage, &WIFI2_CHANNEL).unwrap(), 11);
+    assert!(settings.get(&mut storage, &ETH_DHCP).unwrap());
+    assert_eq!(settings.get(&mut storage, &LOG_LEVEL).unwrap(), 2);
+    assert_eq!(settings.schema_version(), 4);
+}
+
+//= spec/settings.md#schema-versions
+//= type=test
+//# `RING-SETTINGS-005` `Settings::open` MUST run the migration callback once
+//# for each version between the stored and requested schema versions, in
+//# order, and MUST commit each step together with its version bump.
+#[test]
+fn requirement_settings_open_runs_migrations_in_order() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = format_storage(&mut flash);
+        let mut memory = TestSettingsMemory::new();
+        let mut settings = TestSettings::new(&mut storage, &mut memory, 1).unwrap();
+        settings.store(&mut storage, "net/port", &8080u16).unwrap();
+        settings.collection_id()
+    };
+
+    {
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        let mut memory = TestSettingsMemory::new();
+        let mut steps = std::vec::Vec::new();
+        let mut settings = TestSettings::open(
+            id,
+            &mut storage,
+            &mut memory,
+            3,
+            |settings, storage, from| {
+                steps.push(from);
+                match from {
+                    // Version 2 widened the port to u32.
+                    1 => {
+                        let port: u16 = settings.load(storage, "net/port")?.unwrap_or(80);
+                        settings.store(storage, "net/port", &u32::from(port))
+                    }
+                    // Version 3 moved it under net/http.
+                    _ => {
+                        let port: u32 = settings.load(storage, "net/port")?.unwrap_or(80);
+                        settings.remove(storage, "net/port")?;
+                        settings.store(storage, "net/http/port", &port)
+                    }
+                }
+            },
+        )
+        .unwrap();
+        assert_eq!(steps, [1, 2]);
+        assert_eq!(settings.schema_version(), 3);
+        assert_eq!(
+            load::<u32>(&mut settings, &mut storage, "net/http/port"),
+            Some(8080)
+        );
+    }
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = TestSettingsMemory::new();
+    let mut settings = TestSettings::open(id, &mut storage, &mut memory, 3, no_migrations).unwrap();
+    assert_eq!(load::<u32>(&mut settings, &mut storage, "net/port"), None);
+}
+
+//= spec/settings.md#schema-versions
+//= type=test
+//# `RING-SETTINGS-006` A failed migration step MUST roll back its writes and
+//# leave the last completed version stored, and opening a store whose schema
+//# is newer than requested MUST fail with `SchemaTooNew`.
+#[test]
+fn requirement_settings_failed_migration_rolls_back() {
+    let mut flash = TestFlash::new(0xff);
+    let id = {
+        let mut storage = format_storage(&mut flash);
+        let mut memory = TestSettingsMemory::new();
+        let settings = TestSettings::new(&mut storage, &mut memory, 2).unwrap();
+        settings.collection_id()
+    };
+
+    {
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        let mut memory = TestSettingsMemory::new();
+        assert!(matches!(
+            TestSettings::open(id, &mut storage, &mut memory, 1, no_migrations),
+            Err(SettingsError::SchemaTooNew {
+                stored: 2,
+                supported: 1
+            })
+        ));
+        let mut memory = TestSettingsMemory::new();
+        let result = TestSettings::open(
+            id,
+            &mut storage,
+            &mut memory,
+            4,
+            |settings, storage, from| {
+                settings.store(storage, "log/level", &from)?;
+                if from == 3 {
+                    return Err(SettingsError::InvalidKey);
+                }
+                Ok(())
+            },
+        );
+        assert!(matches!(result, Err(SettingsError::InvalidKey)));
+    }
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let mut memory = TestSettingsMemory::new();
+    let mut settings = TestSettings::open(id, &mut storage, &mut memory, 3, no_migrations).unwrap();
+    assert_eq!(settings.schema_version(), 3);
+    assert_eq!(
+        load::<u32>(&mut settings, &mut storage, "log/level"),
+        Some(2)
+    );
+}
+
+fn itoa(value: u8) -> String<3> {
+    let mut digits = String::new();
+    if value >= 10 {
+        digits.push(char::from(b'0' + value / 10)).unwrap();
+    }
+    digits.push(char::from(b'0' + value % 10)).unwrap();
+    digits
+}
@@ -0,0 +1,237 @@
+//! Bit-packed sample encoding for time-series segments.
+//!
+//! Timestamps are stored as delta-of-delta values in prefix-coded buckets and
+//! values as the XOR of their IEEE 754 bits with the previous value of the
+//! same series, keeping only the meaningful bits between the leading and
+//! trailing zeros.
+
+const NO_WINDOW: u8 = u8::MAX;
+
+/// Timestamp delta-of-delta buckets as `(prefix, prefix_bits, value_bits)`.
+const DOD_BUCKETS: [(u64, u32, u32); 3] = [(0b10, 2, 7), (0b110, 3, 9), (0b1110, 4, 12)];
+const DOD_WIDE_PREFIX: u64 = 0b1111;
+
+/// Leading-zero counts are stored in five bits.
+const MAX_LEADING_ZEROS: u32 = 31;
+
+#[derive(Debug)]
+pub(super) struct BitOverflow;
+
+/// Writes bits most-significant first after `bit_len` existing bits.
+pub(super) struct BitWriter<'a> {
+    bytes: &'a mut [u8],
+    bit_len: usize,
+}
+
+impl<'a> BitWriter<'a> {
+    pub(super) fn new(bytes: &'a mut [u8], bit_len: usize) -> Self {
+        Self { bytes, bit_len }
+    }
+
+    pub(super) fn bit_len(&self) -> usize {
+        self.bit_len
+    }
+
+    pub(super) fn write(&mut self, value: u64, bits: u32) -> Result<(), BitOverflow> {
+        for shift in (0..bits).rev() {
+            let byte = self.bytes.get_mut(self.bit_len / 8).ok_or(BitOverflow)?;
+            let mask = 0x80u8 >> (self.bit_len % 8);
+            if (value >> shift) & 1 == 1 {
+                *byte |= mask;
+            } else {
+                *byte &= !mask;
+            }
+            self.bit_len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Reads bits most-significant first from the first `bit_len` bits.
+pub(super) struct BitReader<'a> {
+    bytes: &'a [u8],
+    bit_pos: usize,
+    bit_len: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(super) fn new(bytes: &'a [u8], bit_len: usize) -> Self {
+        Self {
+            bytes,
+            bit_pos: 0,
+            bit_len,
+        }
+    }
+
+    pub(super) fn is_exhausted(&self) -> bool {
+        self.bit_pos == self.bit_len
+    }
+
+    pub(super) fn read(&mut self, bits: u32) -> Option<u64> {
+        let mut value = 0u64;
+        for _ in 0..bits {
+            if self.bit_pos >= self.bit_len {
+                return None;
+            }
+            let byte = *self.bytes.get(self.bit_pos / 8)?;
+            let bit = (byte >> (7 - self.bit_pos % 8)) & 1;
+            value = (value << 1) | u64::from(bit);
+            self.bit_pos += 1;
+        }
+        Some(value)
+    }
+}
+
+/// Per-series encoder and decoder state within one segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SeriesCodec {
+    last_time: i64,
+    last_delta: i64,
+    last_bits: u64,
+    leading: u8,
+    trailing: u8,
+}
+
+impl SeriesCodec {
+    /// Writes the first sample of a series in full.
+    pub(super) fn encode_first(
+        writer: &mut BitWriter<'_>,
+        time: i64,
+        bits: u64,
+    ) -> Result<Self, BitOverflow> {
+        writer.write(time as u64, 64)?;
+        writer.write(bits, 64)?;
+        Ok(Self::first(time, bits))
+    }
+
+    pub(super) fn decode_first(reader: &mut BitReader<'_>) -> Option<(Self, i64, u64)> {
+        let time = reader.read(64)? as i64;
+        let bits = reader.read(64)?;
+        Some((Self::first(time, bits), time, bits))
+    }
+
+    fn first(time: i64, bits: u64) -> Self {
+        Self {
+            last_time: time,
+            last_delta: 0,
+            last_bits: bits,
+            leading: NO_WINDOW,
+            trailing: NO_WINDOW,
+        }
+    }
+
+    pub(super) fn encode(
+        &mut self,
+        writer: &mut BitWriter<'_>,
+        time: i64,
+        bits: u64,
+    ) -> Result<(), BitOverflow> {
+        let delta = time.wrapping_sub(self.last_time);
+        let dod = delta.wrapping_sub(self.last_delta);
+        if dod == 0 {
+            writer.write(0, 1)?;
+        } else if let Some((prefix, prefix_bits, value_bits)) = DOD_BUCKETS
+            .iter()
+            .copied()
+            .find(|(_, _, value_bits)| fits_signed(dod, *value_bits))
+        {
+            writer.write(prefix, prefix_bits)?;
+            writer.write(dod as u64 & low_mask(value_bits), value_bits)?;
+        } else {
+            writer.write(DOD_WIDE_PREFIX, 4)?;
+            writer.write(dod as u64, 64)?;
+        }
+
+        let xor = bits ^ self.last_bits;
+        if xor == 0 {
+            writer.write(0, 1)?;
+        } else {
+            writer.write(1, 1)?;
+            let leading = xor.leading_zeros().min(MAX_LEADING_ZEROS);
+            let trailing = xor.trailing_zeros();
+            if self.leading != NO_WINDOW
+                && leading >= u32::from(self.leading)
+                && trailing >= u32::from(self.trailing)
+            {
+                let meaningful = 64 - u32::from(self.leading) - u32::from(self.trailing);
+                writer.write(0, 1)?;
+                writer.write(xor >> self.trailing, meaningful)?;
+            } else {
+                let meaningful = 64 - leading - trailing;
+                writer.write(1, 1)?;
+                writer.write(u64::from(leading), 5)?;
+                writer.write(u64::from(meaningful - 1), 6)?;
+                writer.write(xor >> trailing, meaningful)?;
+                self.leading = leading as u8;
+                self.trailing = trailing as u8;
+            }
+        }
+
+        self.last_time = time;
+        self.last_delta = delta;
+        self.last_bits = bits;
+        Ok(())
+    }
+
+    pub(super) fn decode(&mut self, reader: &mut BitReader<'_>) -> Option<(i64, u64)> {
+        let dod = if reader.read(1)? == 0 {
+            0
+        } else {
+            let mut dod = None;
+            let mut prefix_len = 1;
+            for (_, _, value_bits) in DOD_BUCKETS {
+                if reader.read(1)? == 0 {
+                    dod = Some(sign_extend(reader.read(value_bits)?, value_bits));
+                    break;
+                }
+                prefix_len += 1;
+            }
+            match dod {
+                Some(dod) => dod,
+                None if prefix_len == 4 => reader.read(64)? as i64,
+                None => return None,
+            }
+        };
+        let delta = self.last_delta.wrapping_add(dod);
+        let time = self.last_time.wrapping_add(delta);
+
+        let bits = if reader.read(1)? == 0 {
+            self.last_bits
+        } else {
+            let xor = if reader.read(1)? == 0 {
+                if self.leading == NO_WINDOW {
+                    return None;
+                }
+                let meaningful = 64 - u32::from(self.leading) - u32::from(self.trailing);
+                reader.read(meaningful)? << self.trailing
+            } else {
+                let leading = reader.read(5)? as u32;
+                let meaningful = reader.read(6)? as u32 + 1;
+                let trailing = 64u32.checked_sub(leading + meaningful)?;
+                self.leading = leading as u8;
+                self.trailing = trailing as u8;
+                reader.read(meaningful)? << trailing
+            };
+            self.last_bits ^ xor
+        };
+
+        self.last_time = time;
+        self.last_delta = delta;
+        self.last_bits = bits;
+        Some((time, bits))
+    }
+}
+
+fn fits_signed(value: i64, bits: u32) -> bool {
+    let limit = 1i64 << (bits - 1);
+    (-limit..limit).contains(&value)
+}
+
+fn low_mask(bits: u32) -> u64 {
+    (1u64 << bits) - 1
+}
+
+fn sign_extend(value: u64, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((value << shift) as i64) >> shift
+}
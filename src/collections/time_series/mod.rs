@@ -0,0 +1,1454 @@
+//! Compressed time-series collection APIs.
+
+use core::mem::size_of;
+use core::ops::Range;
+
+use crc::{Crc, CRC_32_ISCSI};
+use heapless::Vec;
+
+use crate::disk::Header;
+use crate::flash_io::FlashIo;
+use crate::mode::{CollectionFlushMode, CollectionUpdateMode, ReadMode, StorageMode};
+use crate::startup::StartupCollectionBasis;
+use crate::storage::{StorageRuntimeError, StorageVisitError};
+use crate::wal_record::WalRecord;
+use crate::{Collection, CollectionId, CollectionType, Storage, StorageMetadata};
+
+mod encoding;
+
+use encoding::{BitOverflow, BitReader, BitWriter, SeriesCodec};
+
+#[cfg(test)]
+mod tests;
+
+/// Committed-region format code for sealed time-series segments.
+pub const TIME_SERIES_SEGMENT_V1_FORMAT: u16 = 12;
+
+const SEGMENT_MAGIC: [u8; 4] = *b"TSSG";
+const SEGMENT_VERSION: u16 = 1;
+const SEGMENT_PROLOGUE_LEN: usize = SEGMENT_MAGIC.len()
+    + size_of::<u16>()
+    + 2 * size_of::<i64>()
+    + size_of::<u32>()
+    + size_of::<u16>()
+    + size_of::<u32>();
+const SUMMARY_ENCODED_LEN: usize =
+    2 * size_of::<u32>() + 2 * size_of::<i64>() + 3 * size_of::<u64>();
+const SEGMENT_CHECKSUM_LEN: usize = size_of::<u32>();
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+const SNAPSHOT_MAGIC: [u8; 4] = *b"TSSN";
+const SNAPSHOT_VERSION: u16 = 1;
+const SNAPSHOT_FIXED_LEN: usize = SNAPSHOT_MAGIC.len() + size_of::<u16>() + size_of::<u32>();
+const EMPTY_SNAPSHOT: [u8; SNAPSHOT_FIXED_LEN] = [b'T', b'S', b'S', b'N', 1, 0, 0, 0, 0, 0];
+
+const UPDATE_APPEND: u8 = 1;
+const UPDATE_SEAL: u8 = 2;
+
+/// Series table indexes are stored in one byte per sample.
+const SERIES_INDEX_BITS: u32 = 8;
+
+/// Errors returned by [`TimeSeries`].
+#[derive(Debug)]
+pub enum TimeSeriesError {
+    /// Shared storage failed.
+    Storage(StorageRuntimeError),
+    /// The collection does not exist.
+    UnknownCollection(CollectionId),
+    /// The collection type did not match time series.
+    CollectionTypeMismatch {
+        collection_id: CollectionId,
+        actual: Option<u16>,
+    },
+    /// The collection was dropped.
+    DroppedCollection(CollectionId),
+    /// Encoded time-series data was malformed.
+    InvalidEncoding,
+    /// `MAX_SERIES` is zero or exceeds the one-byte series table index.
+    InvalidSeriesLimit,
+    /// Sealing another segment would exceed the configured memory.
+    TooManySegments,
+    /// Checked arithmetic overflowed.
+    LengthOverflow,
+}
+
+impl From<StorageRuntimeError> for TimeSeriesError {
+    fn from(error: StorageRuntimeError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+impl From<crate::StartupError> for TimeSeriesError {
+    fn from(error: crate::StartupError) -> Self {
+        Self::Storage(error.into())
+    }
+}
+
+/// Aggregate of one series within one segment.
+///
+/// Summaries are stored in each sealed segment's prologue, so coarse views of
+/// a series can be built without decoding its samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeSeriesSummary {
+    /// Series the samples belong to.
+    pub series: u32,
+    /// Number of samples.
+    pub sample_count: u32,
+    /// Earliest sample timestamp.
+    pub min_time: i64,
+    /// Latest sample timestamp.
+    pub max_time: i64,
+    /// Smallest sample value, ignoring NaN.
+    pub min_value: f64,
+    /// Largest sample value, ignoring NaN.
+    pub max_value: f64,
+    /// Sum of all sample values.
+    pub sum: f64,
+}
+
+impl TimeSeriesSummary {
+    fn first(series: u32, time: i64, value: f64) -> Self {
+        Self {
+            series,
+            sample_count: 1,
+            min_time: time,
+            max_time: time,
+            min_value: value,
+            max_value: value,
+            sum: value,
+        }
+    }
+
+    fn add(&mut self, time: i64, value: f64) {
+        self.sample_count += 1;
+        self.min_time = self.min_time.min(time);
+        self.max_time = self.max_time.max(time);
+        self.min_value = self.min_value.min(value);
+        self.max_value = self.max_value.max(value);
+        self.sum += value;
+    }
+
+    fn overlaps(&self, range: &Range<i64>) -> bool {
+        self.min_time < range.end && self.max_time >= range.start
+    }
+
+    fn encode(&self, output: &mut [u8], offset: usize) -> Result<usize, TimeSeriesError> {
+        let mut offset = write_u32(output, offset, self.series)?;
+        offset = write_u32(output, offset, self.sample_count)?;
+        offset = write_u64(output, offset, self.min_time as u64)?;
+        offset = write_u64(output, offset, self.max_time as u64)?;
+        offset = write_u64(output, offset, self.min_value.to_bits())?;
+        offset = write_u64(output, offset, self.max_value.to_bits())?;
+        write_u64(output, offset, self.sum.to_bits())
+    }
+
+    fn decode(input: &[u8], offset: &mut usize) -> Result<Self, TimeSeriesError> {
+        Ok(Self {
+            series: read_u32(input, offset)?,
+            sample_count: read_u32(input, offset)?,
+            min_time: read_u64(input, offset)? as i64,
+            max_time: read_u64(input, offset)? as i64,
+            min_value: f64::from_bits(read_u64(input, offset)?),
+            max_value: f64::from_bits(read_u64(input, offset)?),
+            sum: f64::from_bits(read_u64(input, offset)?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimeSegment {
+    region_index: u32,
+    min_time: i64,
+    max_time: i64,
+    sample_count: u32,
+}
+
+impl TimeSegment {
+    fn overlaps(&self, range: &Range<i64>) -> bool {
+        self.min_time < range.end && self.max_time >= range.start
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FrontierSeries {
+    codec: SeriesCodec,
+    summary: TimeSeriesSummary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SegmentPrologue {
+    min_time: i64,
+    max_time: i64,
+    sample_count: u32,
+    series_count: u16,
+    bit_len: u32,
+}
+
+struct TimeSeriesReplayTransaction {
+    transaction_log_id: u32,
+    joined: bool,
+    seal: Option<u32>,
+}
+
+/// Caller-owned memory for a [`TimeSeries`].
+///
+/// Samples appended since the last sealed segment are bit-packed into a
+/// region-sized frontier. `MAX_SERIES` bounds how many distinct series one
+/// segment holds, and `MAX_SEGMENTS` bounds how many sealed segments the
+/// collection tracks.
+pub struct TimeSeriesMemory<
+    const REGION_SIZE: usize,
+    const MAX_SERIES: usize = 8,
+    const MAX_SEGMENTS: usize = 16,
+> {
+    segments: Vec<TimeSegment, MAX_SEGMENTS>,
+    frontier: [u8; REGION_SIZE],
+    frontier_bits: usize,
+    frontier_series: Vec<FrontierSeries, MAX_SERIES>,
+    frontier_last: Option<usize>,
+    frontier_samples: u32,
+    sample_count: u64,
+}
+
+impl<const REGION_SIZE: usize, const MAX_SERIES: usize, const MAX_SEGMENTS: usize>
+    TimeSeriesMemory<REGION_SIZE, MAX_SERIES, MAX_SEGMENTS>
+{
+    /// Allocates empty time-series memory.
+    pub fn new() -> Self {
+        Self {
+            segments: Vec::new(),
+            frontier: [0; REGION_SIZE],
+            frontier_bits: 0,
+            frontier_series: Vec::new(),
+            frontier_last: None,
+            frontier_samples: 0,
+            sample_count: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.segments.clear();
+        self.clear_frontier();
+        self.sample_count = 0;
+    }
+
+    fn clear_frontier(&mut self) {
+        self.frontier.fill(0);
+        self.frontier_bits = 0;
+        self.frontier_series.clear();
+        self.frontier_last = None;
+        self.frontier_samples = 0;
+    }
+}
+
+impl<const REGION_SIZE: usize, const MAX_SERIES: usize, const MAX_SEGMENTS: usize> Default
+    for TimeSeriesMemory<REGION_SIZE, MAX_SERIES, MAX_SEGMENTS>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Durable collection of `(series, timestamp, value)` samples.
+///
+/// Each appended sample is a WAL update. Samples are packed into committed
+/// segment regions with delta-of-delta timestamps and XOR-compressed values,
+/// and every segment prologue records its time bounds and a per-series
+/// [`TimeSeriesSummary`] so queries skip segments outside the requested
+/// range.
+pub struct TimeSeries<
+    'mem,
+    const REGION_SIZE: usize,
+    const MAX_SERIES: usize = 8,
+    const MAX_SEGMENTS: usize = 16,
+> {
+    collection_id: CollectionId,
+    memory: &'mem mut TimeSeriesMemory<REGION_SIZE, MAX_SERIES, MAX_SEGMENTS>,
+    relocation_epoch: u64,
+}
+
+impl<const REGION_SIZE: usize, const MAX_SERIES: usize, const MAX_SEGMENTS: usize> Collection
+    for TimeSeries<'_, REGION_SIZE, MAX_SERIES, MAX_SEGMENTS>
+{
+    fn id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    fn collection_type(&self) -> CollectionType {
+        CollectionType::TimeSeries
+    }
+}
+
+impl<'mem, const REGION_SIZE: usize, const MAX_SERIES: usize, const MAX_SEGMENTS: usize>
+    TimeSeries<'mem, REGION_SIZE, MAX_SERIES, MAX_SEGMENTS>
+{
+    /// Creates a new empty time-series collection.
+    pub fn new<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut TimeSeriesMemory<REGION_SIZE, MAX_SERIES, MAX_SEGMENTS>,
+    ) -> Result<Self, TimeSeriesError> {
+        validate_series_limit::<MAX_SERIES>()?;
+        let collection_id = storage.allocate_collection_id()?;
+        memory.clear();
+        storage.append_new_collection(collection_id, CollectionType::TIME_SERIES_CODE)?;
+        Ok(Self {
+            collection_id,
+            memory,
+            relocation_epoch: storage.runtime().relocation_epoch(),
+        })
+    }
+
+    /// Opens an existing time-series collection.
+    pub fn open<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'mem mut TimeSeriesMemory<REGION_SIZE, MAX_SERIES, MAX_SEGMENTS>,
+    ) -> Result<Self, TimeSeriesError> {
+        validate_series_limit::<MAX_SERIES>()?;
+        validate_collection::<IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>(
+            storage,
+            collection_id,
+        )?;
+        memory.clear();
+        let mut series = Self {
+            collection_id,
+            memory,
+            relocation_epoch: storage.runtime().relocation_epoch(),
+        };
+        series.replay(storage)?;
+        Ok(series)
+    }
+
+    /// Returns the stable collection id.
+    pub fn collection_id(&self) -> CollectionId {
+        self.collection_id
+    }
+
+    /// Returns the number of stored samples across all series.
+    pub fn sample_count(&self) -> u64 {
+        self.memory.sample_count
+    }
+
+    /// Returns the number of sealed segment regions.
+    pub fn segment_count(&self) -> usize {
+        self.memory.segments.len()
+    }
+
+    /// Appends one sample to `series`.
+    ///
+    /// A frontier that cannot hold the encoded sample is sealed into a
+    /// segment region first.
+    pub fn append<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        series: u32,
+        time: i64,
+        value: f64,
+    ) -> Result<(), TimeSeriesError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::UpdatingCollection(
+            CollectionUpdateMode::Running,
+        ))?;
+        let result = self.append_inner(storage, series, time, value);
+        storage.finish_mode();
+        result
+    }
+
+    /// Seals buffered samples into a segment region and records the segment
+    /// list in a WAL snapshot.
+    pub fn flush<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), TimeSeriesError> {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::FlushingCollection(
+            CollectionFlushMode::CommitRegion,
+        ))?;
+        let result = self.seal_inner(storage);
+        storage.finish_mode();
+        result
+    }
+
+    /// Visits the samples of `series` whose timestamps fall in `range`.
+    ///
+    /// Samples are visited segment by segment from oldest to newest and in
+    /// append order within a segment. Segments whose time bounds miss
+    /// `range` are not read.
+    pub fn query<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        series: u32,
+        range: Range<i64>,
+        visitor: F,
+    ) -> Result<(), TimeSeriesError>
+    where
+        F: FnMut(i64, f64) -> Result<(), TimeSeriesError>,
+    {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self.query_inner(storage, series, range, visitor);
+        storage.finish_mode();
+        result
+    }
+
+    /// Visits the per-segment summaries of `series` that overlap `range`,
+    /// oldest first, ending with the unsealed frontier.
+    pub fn region_summaries<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        series: u32,
+        range: Range<i64>,
+        visitor: F,
+    ) -> Result<(), TimeSeriesError>
+    where
+        F: FnMut(&TimeSeriesSummary) -> Result<(), TimeSeriesError>,
+    {
+        self.refresh(storage)?;
+        storage.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self.region_summaries_inner(storage, series, range, visitor);
+        storage.finish_mode();
+        result
+    }
+
+    /// Replays the series again when a wear-leveling pass moved one of its
+    /// segments since this handle last replayed.
+    fn refresh<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), TimeSeriesError> {
+        let epoch = storage.runtime().relocation_epoch();
+        if self.relocation_epoch != epoch {
+            self.memory.clear();
+            self.replay(storage)?;
+            self.relocation_epoch = epoch;
+        }
+        Ok(())
+    }
+
+    fn append_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        series: u32,
+        time: i64,
+        value: f64,
+    ) -> Result<(), TimeSeriesError> {
+        let capacity = segment_capacity::<REGION_SIZE>(storage.metadata())?;
+        let bits = value.to_bits();
+        if self.encode_sample(series, time, bits, capacity)?.is_none() {
+            self.seal_inner(storage)?;
+            if self.encode_sample(series, time, bits, capacity)?.is_none() {
+                return Err(TimeSeriesError::LengthOverflow);
+            }
+        }
+
+        let mut used = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_APPEND)?;
+        used = write_u32(&mut storage.memory.payload_scratch, used, series)?;
+        used = write_u64(&mut storage.memory.payload_scratch, used, time as u64)?;
+        used = write_u64(&mut storage.memory.payload_scratch, used, bits)?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        self.apply_append(series, time, bits, capacity)
+    }
+
+    /// Encodes a sample after the frontier bits without committing it.
+    ///
+    /// Returns the frontier bit length and series state after the sample, or
+    /// `None` when the segment cannot hold it.
+    fn encode_sample(
+        &mut self,
+        series: u32,
+        time: i64,
+        bits: u64,
+        capacity: usize,
+    ) -> Result<Option<(usize, FrontierSeries)>, TimeSeriesError> {
+        let position = self
+            .memory
+            .frontier_series
+            .iter()
+            .position(|entry| entry.summary.series == series);
+        let series_count = match position {
+            Some(_) => self.memory.frontier_series.len(),
+            None if self.memory.frontier_series.is_full() => return Ok(None),
+            None => self.memory.frontier_series.len() + 1,
+        };
+        let Some(stream_capacity) = SUMMARY_ENCODED_LEN
+            .checked_mul(series_count)
+            .and_then(|summaries| summaries.checked_add(SEGMENT_PROLOGUE_LEN))
+            .and_then(|prologue| capacity.checked_sub(prologue))
+        else {
+            return Ok(None);
+        };
+        let stream_capacity = stream_capacity.min(REGION_SIZE);
+
+        let value = f64::from_bits(bits);
+        let mut writer = BitWriter::new(
+            &mut self.memory.frontier[..stream_capacity],
+            self.memory.frontier_bits,
+        );
+        // Bits past the frontier length are scratch, so a sample that does
+        // not fit leaves the committed frontier untouched.
+        let entry = match position {
+            Some(index) => {
+                let mut entry = self.memory.frontier_series[index];
+                let same_series = self.memory.frontier_last == Some(index);
+                if encode_known_sample(
+                    &mut writer,
+                    index,
+                    same_series,
+                    &mut entry.codec,
+                    time,
+                    bits,
+                )
+                .is_err()
+                {
+                    return Ok(None);
+                }
+                entry.summary.add(time, value);
+                entry
+            }
+            None => {
+                let index = self.memory.frontier_series.len();
+                match encode_new_sample(&mut writer, index, series, time, bits) {
+                    Ok(codec) => FrontierSeries {
+                        codec,
+                        summary: TimeSeriesSummary::first(series, time, value),
+                    },
+                    Err(_) => return Ok(None),
+                }
+            }
+        };
+        Ok(Some((writer.bit_len(), entry)))
+    }
+
+    fn apply_append(
+        &mut self,
+        series: u32,
+        time: i64,
+        bits: u64,
+        capacity: usize,
+    ) -> Result<(), TimeSeriesError> {
+        let (bit_len, entry) = self
+            .encode_sample(series, time, bits, capacity)?
+            .ok_or(TimeSeriesError::InvalidEncoding)?;
+        let index = match self
+            .memory
+            .frontier_series
+            .iter()
+            .position(|existing| existing.summary.series == series)
+        {
+            Some(index) => {
+                self.memory.frontier_series[index] = entry;
+                index
+            }
+            None => {
+                self.memory
+                    .frontier_series
+                    .push(entry)
+                    .map_err(|_| TimeSeriesError::InvalidEncoding)?;
+                self.memory.frontier_series.len() - 1
+            }
+        };
+        self.memory.frontier_bits = bit_len;
+        self.memory.frontier_last = Some(index);
+        self.memory.frontier_samples += 1;
+        self.memory.sample_count = self
+            .memory
+            .sample_count
+            .checked_add(1)
+            .ok_or(TimeSeriesError::LengthOverflow)?;
+        Ok(())
+    }
+
+    fn frontier_prologue(&self) -> Result<SegmentPrologue, TimeSeriesError> {
+        let mut series = self.memory.frontier_series.iter();
+        let first = series.next().ok_or(TimeSeriesError::InvalidEncoding)?;
+        let (min_time, max_time) = series.fold(
+            (first.summary.min_time, first.summary.max_time),
+            |(min, max), entry| {
+                (
+                    min.min(entry.summary.min_time),
+                    max.max(entry.summary.max_time),
+                )
+            },
+        );
+        Ok(SegmentPrologue {
+            min_time,
+            max_time,
+            sample_count: self.memory.frontier_samples,
+            series_count: u16::try_from(self.memory.frontier_series.len())
+                .map_err(|_| TimeSeriesError::LengthOverflow)?,
+            bit_len: u32::try_from(self.memory.frontier_bits)
+                .map_err(|_| TimeSeriesError::LengthOverflow)?,
+        })
+    }
+
+    fn query_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        series: u32,
+        range: Range<i64>,
+        mut visitor: F,
+    ) -> Result<(), TimeSeriesError>
+    where
+        F: FnMut(i64, f64) -> Result<(), TimeSeriesError>,
+    {
+        let mut matching = |sample_series: u32, time: i64, bits: u64| {
+            if sample_series == series && range.contains(&time) {
+                visitor(time, f64::from_bits(bits))
+            } else {
+                Ok(())
+            }
+        };
+        for segment in self.memory.segments.iter() {
+            if !segment.overlaps(&range) {
+                continue;
+            }
+            let prologue = self.read_segment_prologue(storage.backing, segment)?;
+            let stream_start = stream_offset(prologue.series_count)?;
+            let stream_len = stream_bytes(prologue.bit_len)?;
+            storage
+                .backing
+                .read_region(segment.region_index, stream_start, stream_len, |bytes| {
+                    decode_samples::<MAX_SERIES, _>(
+                        bytes,
+                        prologue.bit_len as usize,
+                        prologue.sample_count,
+                        &mut matching,
+                    )
+                })
+                .map_err(StorageRuntimeError::from)??;
+        }
+        if self.memory.frontier_samples != 0 {
+            decode_samples::<MAX_SERIES, _>(
+                &self.memory.frontier,
+                self.memory.frontier_bits,
+                self.memory.frontier_samples,
+                &mut matching,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn region_summaries_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        F,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        series: u32,
+        range: Range<i64>,
+        mut visitor: F,
+    ) -> Result<(), TimeSeriesError>
+    where
+        F: FnMut(&TimeSeriesSummary) -> Result<(), TimeSeriesError>,
+    {
+        for segment in self.memory.segments.iter() {
+            if !segment.overlaps(&range) {
+                continue;
+            }
+            let prologue = self.read_segment_prologue(storage.backing, segment)?;
+            let summaries_len = SUMMARY_ENCODED_LEN
+                .checked_mul(usize::from(prologue.series_count))
+                .ok_or(TimeSeriesError::LengthOverflow)?;
+            storage
+                .backing
+                .read_region(
+                    segment.region_index,
+                    Header::ENCODED_LEN + SEGMENT_PROLOGUE_LEN,
+                    summaries_len,
+                    |bytes| {
+                        let mut offset = 0usize;
+                        for _ in 0..prologue.series_count {
+                            let summary = TimeSeriesSummary::decode(bytes, &mut offset)?;
+                            if summary.series == series && summary.overlaps(&range) {
+                                visitor(&summary)?;
+                            }
+                        }
+                        Ok::<(), TimeSeriesError>(())
+                    },
+                )
+                .map_err(StorageRuntimeError::from)??;
+        }
+        for entry in self.memory.frontier_series.iter() {
+            if entry.summary.series == series && entry.summary.overlaps(&range) {
+                visitor(&entry.summary)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_segment_prologue<IO: FlashIo>(
+        &self,
+        flash: &mut IO,
+        segment: &TimeSegment,
+    ) -> Result<SegmentPrologue, TimeSeriesError> {
+        let header = flash
+            .read_region(segment.region_index, 0, Header::ENCODED_LEN, Header::decode)
+            .map_err(StorageRuntimeError::from)?
+            .map_err(|_| TimeSeriesError::InvalidEncoding)?;
+        if header.collection_id != self.collection_id
+            || header.collection_format != TIME_SERIES_SEGMENT_V1_FORMAT
+        {
+            return Err(TimeSeriesError::InvalidEncoding);
+        }
+        let prologue = flash
+            .read_region(
+                segment.region_index,
+                Header::ENCODED_LEN,
+                SEGMENT_PROLOGUE_LEN,
+                decode_segment_prologue,
+            )
+            .map_err(StorageRuntimeError::from)??;
+        if prologue.min_time != segment.min_time
+            || prologue.max_time != segment.max_time
+            || prologue.sample_count != segment.sample_count
+            || stream_offset(prologue.series_count)?
+                .checked_add(stream_bytes(prologue.bit_len)?)
+                .is_none_or(|end| end > REGION_SIZE)
+        {
+            return Err(TimeSeriesError::InvalidEncoding);
+        }
+        Ok(prologue)
+    }
+
+    fn seal_inner<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), TimeSeriesError> {
+        if self.memory.frontier_samples == 0 {
+            return Ok(());
+        }
+        if self.memory.segments.is_full() {
+            return Err(TimeSeriesError::TooManySegments);
+        }
+        storage
+            .memory
+            .state
+            .begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        let mut allocated = None;
+        let region_index = match self.seal_transactional(storage, &mut allocated) {
+            Ok(region_index) => region_index,
+            Err(error) => {
+                return match self.rollback_seal(storage, allocated) {
+                    Ok(()) => Err(error),
+                    Err(cleanup_error) => Err(cleanup_error),
+                };
+            }
+        };
+        storage
+            .memory
+            .state
+            .finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        self.apply_seal(region_index)?;
+
+        let used = self.encode_snapshot(&mut storage.memory.payload_scratch)?;
+        storage
+            .memory
+            .state
+            .append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                CollectionType::TIME_SERIES_CODE,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        Ok(())
+    }
+
+    fn seal_transactional<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        allocated: &mut Option<u32>,
+    ) -> Result<u32, TimeSeriesError> {
+        let region_index = storage
+            .memory
+            .state
+            .reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &mut storage.memory.reclaim_source_regions,
+                &mut storage.memory.active_collections,
+                &mut storage.memory.reclaim_plan,
+                &mut storage.memory.open_plan,
+            )?;
+        *allocated = Some(region_index);
+
+        let prologue = self.frontier_prologue()?;
+        let output = &mut storage.memory.payload_scratch;
+        let mut used = write_bytes(output, 0, &SEGMENT_MAGIC)?;
+        used = write_u16(output, used, SEGMENT_VERSION)?;
+        used = write_u64(output, used, prologue.min_time as u64)?;
+        used = write_u64(output, used, prologue.max_time as u64)?;
+        used = write_u32(output, used, prologue.sample_count)?;
+        used = write_u16(output, used, prologue.series_count)?;
+        used = write_u32(output, used, prologue.bit_len)?;
+        for entry in self.memory.frontier_series.iter() {
+            used = entry.summary.encode(output, used)?;
+        }
+        let stream_len = stream_bytes(prologue.bit_len)?;
+        used = write_bytes(output, used, &self.memory.frontier[..stream_len])?;
+        let checksum = crc32(&output[..used]);
+        used = write_u32(output, used, checksum)?;
+        let region_index = storage
+            .memory
+            .state
+            .write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                region_index,
+                self.collection_id,
+                TIME_SERIES_SEGMENT_V1_FORMAT,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        *allocated = Some(region_index);
+
+        let mut used = write_u8(&mut storage.memory.payload_scratch, 0, UPDATE_SEAL)?;
+        used = write_u32(&mut storage.memory.payload_scratch, used, region_index)?;
+        storage
+            .memory
+            .state
+            .append_update_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+                &storage.memory.payload_scratch[..used],
+            )?;
+        storage
+            .memory
+            .state
+            .commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )?;
+        Ok(region_index)
+    }
+
+    fn rollback_seal<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        allocated: Option<u32>,
+    ) -> Result<(), TimeSeriesError> {
+        let mut first_error = None::<TimeSeriesError>;
+        if let Err(error) = storage
+            .memory
+            .state
+            .rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                self.collection_id,
+            )
+        {
+            first_error = Some(error.into());
+        }
+        if let Some(region_index) = allocated {
+            if let Err(error) = storage
+                .memory
+                .state
+                .append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    CollectionId(0),
+                    region_index,
+                )
+            {
+                if first_error.is_none() {
+                    first_error = Some(error.into());
+                }
+            }
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn replay<
+        'db,
+        'storage_mem,
+        IO: FlashIo,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'storage_mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), TimeSeriesError> {
+        let collection_id = self.collection_id;
+        let capacity = segment_capacity::<REGION_SIZE>(storage.metadata())?;
+        let mut transaction = None::<TimeSeriesReplayTransaction>;
+        // WAL head reclaim drops a superseded snapshot but keeps the updates
+        // that followed it, so updates before the first retained basis are
+        // skipped.
+        let mut based = !storage
+            .memory
+            .state
+            .wal_has_collection_basis::<REGION_SIZE, IO>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                collection_id,
+                CollectionType::TIME_SERIES_CODE,
+            )?;
+        let result =
+            storage
+                .memory
+                .state
+                .visit_wal_records::<REGION_SIZE, IO, TimeSeriesError, _>(
+                    storage.backing,
+                    &mut storage.memory.workspace,
+                    |_flash, record| {
+                        match record {
+                            WalRecord::NewCollection {
+                                collection_id: seen,
+                                collection_type,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::TIME_SERIES_CODE =>
+                            {
+                                based = true;
+                                self.memory.clear();
+                            }
+                            WalRecord::BeginTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                transaction = Some(TimeSeriesReplayTransaction {
+                                    transaction_log_id,
+                                    joined: transaction_log_id == 0,
+                                    seal: None,
+                                });
+                            }
+                            WalRecord::AddTransactionCollection {
+                                collection_id: seen,
+                                ..
+                            } if seen == collection_id => {
+                                if let Some(open) = transaction.as_mut() {
+                                    open.joined = true;
+                                }
+                            }
+                            WalRecord::Snapshot {
+                                collection_id: seen,
+                                collection_type,
+                                payload,
+                            } if seen == collection_id
+                                && collection_type == CollectionType::TIME_SERIES_CODE =>
+                            {
+                                based = true;
+                                self.apply_snapshot(payload)?;
+                            }
+                            WalRecord::Update {
+                                collection_id: seen,
+                                payload,
+                            } if seen == collection_id && based => match transaction.as_mut() {
+                                Some(open) if open.joined => {
+                                    // Time-series transactions carry exactly one
+                                    // seal.
+                                    if open.seal.is_some() {
+                                        return Err(TimeSeriesError::InvalidEncoding);
+                                    }
+                                    open.seal = Some(decode_seal(payload)?);
+                                }
+                                _ => self.apply_update_payload(payload, capacity)?,
+                            },
+                            WalRecord::CommitTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    if let Some(region_index) =
+                                        transaction.take().and_then(|open| open.seal)
+                                    {
+                                        self.apply_seal(region_index)?;
+                                    }
+                                }
+                            }
+                            WalRecord::RollbackTransaction {
+                                transaction_log_id, ..
+                            } => {
+                                if transaction.as_ref().is_some_and(|open| {
+                                    open.transaction_log_id == transaction_log_id
+                                }) {
+                                    transaction = None;
+                                }
+                            }
+                            WalRecord::DropCollection {
+                                collection_id: seen,
+                            } if seen == collection_id => {
+                                self.memory.clear();
+                            }
+                            _ => {}
+                        }
+                        Ok(())
+                    },
+                );
+        match result {
+            Ok(()) => Ok(()),
+            Err(StorageVisitError::Storage(error)) => Err(TimeSeriesError::Storage(error)),
+            Err(StorageVisitError::Visitor(error)) => Err(error),
+        }
+    }
+
+    fn apply_update_payload(
+        &mut self,
+        payload: &[u8],
+        capacity: usize,
+    ) -> Result<(), TimeSeriesError> {
+        let mut offset = 0usize;
+        if read_u8(payload, &mut offset)? == UPDATE_APPEND {
+            let series = read_u32(payload, &mut offset)?;
+            let time = read_u64(payload, &mut offset)? as i64;
+            let bits = read_u64(payload, &mut offset)?;
+            if offset != payload.len() {
+                return Err(TimeSeriesError::InvalidEncoding);
+            }
+            return self.apply_append(series, time, bits, capacity);
+        }
+        // Reclaim can copy a committed seal out of its transaction, so it may
+        // also appear as a plain update.
+        self.apply_seal(decode_seal(payload)?)
+    }
+
+    fn apply_seal(&mut self, region_index: u32) -> Result<(), TimeSeriesError> {
+        let prologue = self.frontier_prologue()?;
+        self.memory
+            .segments
+            .push(TimeSegment {
+                region_index,
+                min_time: prologue.min_time,
+                max_time: prologue.max_time,
+                sample_count: prologue.sample_count,
+            })
+            .map_err(|_| TimeSeriesError::TooManySegments)?;
+        self.memory.clear_frontier();
+        Ok(())
+    }
+
+    fn encode_snapshot(&self, output: &mut [u8]) -> Result<usize, TimeSeriesError> {
+        // Snapshots are only written right after a seal, so the frontier is
+        // always empty and every sample lives in a segment.
+        if self.memory.frontier_samples != 0 {
+            return Err(TimeSeriesError::InvalidEncoding);
+        }
+        let segment_count = u32::try_from(self.memory.segments.len())
+            .map_err(|_| TimeSeriesError::LengthOverflow)?;
+        let mut offset = write_bytes(output, 0, &SNAPSHOT_MAGIC)?;
+        offset = write_u16(output, offset, SNAPSHOT_VERSION)?;
+        offset = write_u32(output, offset, segment_count)?;
+        for segment in self.memory.segments.iter() {
+            offset = write_u32(output, offset, segment.region_index)?;
+            offset = write_u64(output, offset, segment.min_time as u64)?;
+            offset = write_u64(output, offset, segment.max_time as u64)?;
+            offset = write_u32(output, offset, segment.sample_count)?;
+        }
+        Ok(offset)
+    }
+
+    fn apply_snapshot(&mut self, payload: &[u8]) -> Result<(), TimeSeriesError> {
+        let mut offset = 0usize;
+        if read_bytes(payload, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+            || read_u16(payload, &mut offset)? != SNAPSHOT_VERSION
+        {
+            return Err(TimeSeriesError::InvalidEncoding);
+        }
+        self.memory.clear();
+        let segment_count = read_u32(payload, &mut offset)?;
+        for _ in 0..segment_count {
+            let segment = TimeSegment {
+                region_index: read_u32(payload, &mut offset)?,
+                min_time: read_u64(payload, &mut offset)? as i64,
+                max_time: read_u64(payload, &mut offset)? as i64,
+                sample_count: read_u32(payload, &mut offset)?,
+            };
+            if segment.sample_count == 0 || segment.min_time > segment.max_time {
+                return Err(TimeSeriesError::InvalidEncoding);
+            }
+            self.memory.sample_count += u64::from(segment.sample_count);
+            self.memory
+                .segments
+                .push(segment)
+                .map_err(|_| TimeSeriesError::TooManySegments)?;
+        }
+        if offset != payload.len() {
+            return Err(TimeSeriesError::InvalidEncoding);
+        }
+        Ok(())
+    }
+}
+
+fn validate_series_limit<const MAX_SERIES: usize>() -> Result<(), TimeSeriesError> {
+    if MAX_SERIES == 0 || MAX_SERIES > 1 << SERIES_INDEX_BITS {
+        return Err(TimeSeriesError::InvalidSeriesLimit);
+    }
+    Ok(())
+}
+
+fn validate_collection<
+    IO: FlashIo,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+) -> Result<(), TimeSeriesError> {
+    let collection = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .ok_or(TimeSeriesError::UnknownCollection(collection_id))?;
+    if collection.basis() == StartupCollectionBasis::Dropped {
+        return Err(TimeSeriesError::DroppedCollection(collection_id));
+    }
+    storage.runtime().ensure_not_quarantined(collection_id)?;
+    if collection.collection_type() != Some(CollectionType::TIME_SERIES_CODE) {
+        return Err(TimeSeriesError::CollectionTypeMismatch {
+            collection_id,
+            actual: collection.collection_type(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn empty_snapshot() -> &'static [u8] {
+    &EMPTY_SNAPSHOT
+}
+
+/// Returns the offset of the `u32` naming segment `region_index` in an
+/// encoded snapshot, or `None` when the snapshot does not list it.
+pub(crate) fn snapshot_segment_offset(
+    snapshot: &[u8],
+    region_index: u32,
+) -> Result<Option<usize>, TimeSeriesError> {
+    let mut offset = 0usize;
+    if read_bytes(snapshot, &mut offset, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC
+        || read_u16(snapshot, &mut offset)? != SNAPSHOT_VERSION
+    {
+        return Err(TimeSeriesError::InvalidEncoding);
+    }
+    let segment_count = read_u32(snapshot, &mut offset)?;
+    for _ in 0..segment_count {
+        let field = offset;
+        let segment = read_u32(snapshot, &mut offset)?;
+        let _min_time = read_u64(snapshot, &mut offset)?;
+        let _max_time = read_u64(snapshot, &mut offset)?;
+        let _sample_count = read_u32(snapshot, &mut offset)?;
+        if segment == region_index {
+            return Ok(Some(field));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the payload bytes one segment region can hold.
+fn segment_capacity<const REGION_SIZE: usize>(
+    metadata: StorageMetadata,
+) -> Result<usize, TimeSeriesError> {
+    let granule =
+        usize::try_from(metadata.wal_write_granule).map_err(|_| TimeSeriesError::LengthOverflow)?;
+    if granule == 0 {
+        return Err(TimeSeriesError::InvalidEncoding);
+    }
+    (REGION_SIZE - REGION_SIZE % granule)
+        .checked_sub(Header::ENCODED_LEN + SEGMENT_CHECKSUM_LEN)
+        .ok_or(TimeSeriesError::LengthOverflow)
+}
+
+/// Returns whether a `time_series_segment_v1` payload matches the CRC-32C
+/// that follows its sample bitstream.
+pub(crate) fn segment_checksum_matches(payload: &[u8]) -> bool {
+    decode_segment_checksum(payload)
+        .is_ok_and(|(stream_end, checksum)| crc32(&payload[..stream_end]) == checksum)
+}
+
+/// Returns where a segment payload's bitstream ends and the checksum stored
+/// after it.
+fn decode_segment_checksum(payload: &[u8]) -> Result<(usize, u32), TimeSeriesError> {
+    let prologue = decode_segment_prologue(payload)?;
+    let mut offset = (stream_offset(prologue.series_count)? - Header::ENCODED_LEN)
+        .checked_add(stream_bytes(prologue.bit_len)?)
+        .ok_or(TimeSeriesError::LengthOverflow)?;
+    let stream_end = offset;
+    Ok((stream_end, read_u32(payload, &mut offset)?))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    CRC32C.checksum(bytes)
+}
+
+/// Returns the region offset of the sample bit stream.
+fn stream_offset(series_count: u16) -> Result<usize, TimeSeriesError> {
+    SUMMARY_ENCODED_LEN
+        .checked_mul(usize::from(series_count))
+        .and_then(|summaries| summaries.checked_add(Header::ENCODED_LEN + SEGMENT_PROLOGUE_LEN))
+        .ok_or(TimeSeriesError::LengthOverflow)
+}
+
+fn stream_bytes(bit_len: u32) -> Result<usize, TimeSeriesError> {
+    usize::try_from(bit_len.div_ceil(8)).map_err(|_| TimeSeriesError::LengthOverflow)
+}
+
+/// Writes a sample of a series already in the segment's series table.
+fn encode_known_sample(
+    writer: &mut BitWriter<'_>,
+    index: usize,
+    same_series: bool,
+    codec: &mut SeriesCodec,
+    time: i64,
+    bits: u64,
+) -> Result<(), BitOverflow> {
+    if same_series {
+        writer.write(0, 1)?;
+    } else {
+        writer.write(1, 1)?;
+        writer.write(index as u64, SERIES_INDEX_BITS)?;
+    }
+    codec.encode(writer, time, bits)
+}
+
+/// Writes the first sample of a series, appending it to the series table.
+fn encode_new_sample(
+    writer: &mut BitWriter<'_>,
+    index: usize,
+    series: u32,
+    time: i64,
+    bits: u64,
+) -> Result<SeriesCodec, BitOverflow> {
+    writer.write(1, 1)?;
+    writer.write(index as u64, SERIES_INDEX_BITS)?;
+    writer.write(u64::from(series), 32)?;
+    SeriesCodec::encode_first(writer, time, bits)
+}
+
+/// Decodes `sample_count` samples and passes each `(series, time, bits)` to
+/// `visitor`.
+fn decode_samples<const MAX_SERIES: usize, F>(
+    bytes: &[u8],
+    bit_len: usize,
+    sample_count: u32,
+    visitor: &mut F,
+) -> Result<(), TimeSeriesError>
+where
+    F: FnMut(u32, i64, u64) -> Result<(), TimeSeriesError>,
+{
+    let mut reader = BitReader::new(bytes, bit_len);
+    let mut table = Vec::<(u32, SeriesCodec), MAX_SERIES>::new();
+    let mut last = None::<usize>;
+    for _ in 0..sample_count {
+        let same_series = reader.read(1).ok_or(TimeSeriesError::InvalidEncoding)? == 0;
+        let index = if same_series {
+            last.ok_or(TimeSeriesError::InvalidEncoding)?
+        } else {
+            reader
+                .read(SERIES_INDEX_BITS)
+                .ok_or(TimeSeriesError::InvalidEncoding)? as usize
+        };
+        let (series, time, bits) = if index == table.len() && !same_series {
+            let series = reader.read(32).ok_or(TimeSeriesError::InvalidEncoding)? as u32;
+            let (codec, time, bits) =
+                SeriesCodec::decode_first(&mut reader).ok_or(TimeSeriesError::InvalidEncoding)?;
+            table
+                .push((series, codec))
+                .map_err(|_| TimeSeriesError::InvalidEncoding)?;
+            (series, time, bits)
+        } else {
+            let (series, codec) = table
+                .get_mut(index)
+                .ok_or(TimeSeriesError::InvalidEncoding)?;
+            let (time, bits) = codec
+                .decode(&mut reader)
+                .ok_or(TimeSeriesError::InvalidEncoding)?;
+            (*series, time, bits)
+        };
+        last = Some(index);
+        visitor(series, time, bits)?;
+    }
+    if !reader.is_exhausted() {
+        return Err(TimeSeriesError::InvalidEncoding);
+    }
+    Ok(())
+}
+
+fn decode_segment_prologue(bytes: &[u8]) -> Result<SegmentPrologue, TimeSeriesError> {
+    let mut offset = 0usize;
+    if read_bytes(bytes, &mut offset, SEGMENT_MAGIC.len())? != SEGMENT_MAGIC
+        || read_u16(bytes, &mut offset)? != SEGMENT_VERSION
+    {
+        return Err(TimeSeriesError::InvalidEncoding);
+    }
+    Ok(SegmentPrologue {
+        min_time: read_u64(bytes, &mut offset)? as i64,
+        max_time: read_u64(bytes, &mut offset)? as i64,
+        sample_count: read_u32(bytes, &mut offset)?,
+        series_count: read_u16(bytes, &mut offset)?,
+        bit_len: read_u32(bytes, &mut offset)?,
+    })
+}
+
+fn decode_seal(payload: &[u8]) -> Result<u32, TimeSeriesError> {
+    let mut offset = 0usize;
+    if read_u8(payload, &mut offset)? != UPDATE_SEAL {
+        return Err(TimeSeriesError::InvalidEncoding);
+    }
+    let region_index = read_u32(payload, &mut offset)?;
+    if offset != payload.len() {
+        return Err(TimeSeriesError::InvalidEncoding);
+    }
+    Ok(region_index)
+}
+
+fn write_u8(output: &mut [u8], offset: usize, value: u8) -> Result<usize, TimeSeriesError> {
+    write_bytes(output, offset, &[value])
+}
+
+fn write_u16(output: &mut [u8], offset: usize, value: u16) -> Result<usize, TimeSeriesError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u32(output: &mut [u8], offset: usize, value: u32) -> Result<usize, TimeSeriesError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_u64(output: &mut [u8], offset: usize, value: u64) -> Result<usize, TimeSeriesError> {
+    write_bytes(output, offset, &value.to_le_bytes())
+}
+
+fn write_bytes(output: &mut [u8], offset: usize, bytes: &[u8]) -> Result<usize, TimeSeriesError> {
+    let end = offset
+        .checked_add(bytes.len())
+        .ok_or(TimeSeriesError::LengthOverflow)?;
+    let target = output
+        .get_mut(offset..end)
+        .ok_or(TimeSeriesError::LengthOverflow)?;
+    target.copy_from_slice(bytes);
+    Ok(end)
+}
+
+fn read_u8(input: &[u8], offset: &mut usize) -> Result<u8, TimeSeriesError> {
+    Ok(read_bytes(input, offset, size_of::<u8>())?[0])
+}
+
+fn read_u16(input: &[u8], offset: &mut usize) -> Result<u16, TimeSeriesError> {
+    let mut bytes = [0u8; size_of::<u16>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u16>())?);
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(input: &[u8], offset: &mut usize) -> Result<u32, TimeSeriesError> {
+    let mut bytes = [0u8; size_of::<u32>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u32>())?);
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &[u8], offset: &mut usize) -> Result<u64, TimeSeriesError> {
+    let mut bytes = [0u8; size_of::<u64>()];
+    bytes.copy_from_slice(read_bytes(input, offset, size_of::<u64>())?);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_bytes<'a>(
+    input: &'a [u8],
+    offset: &mut usize,
+    len: usize,
+) -> Result<&'a [u8], TimeSeriesError> {
+    let end = offset
+        .checked_add(len)
+        .ok_or(TimeSeriesError::LengthOverflow)?;
+    let bytes = input
+        .get(*offset..end)
+        .ok_or(TimeSeriesError::InvalidEncoding)?;
+    *offset = end;
+    Ok(bytes)
+}
@@ -0,0 +1,362 @@
+use super::*;
+
+use crate::MockFlash;
+
+const REGION_SIZE: usize = 1024;
+const REGION_COUNT: usize = 32;
+const MAX_SERIES: usize = 4;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestSeries<'mem> = TimeSeries<'mem, REGION_SIZE, MAX_SERIES>;
+type TestSeriesMemory = TimeSeriesMemory<REGION_SIZE, MAX_SERIES>;
+
+fn query(
+    series: &mut TestSeries<'_>,
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    id: u32,
+    range: Range<i64>,
+) -> std::vec::Vec<(i64, u64)> {
+    let mut samples = std::vec::Vec::new();
+    series
+        .query(storage, id, range, |time, value| {
+            samples.push((time, value.to_bits()));
+            Ok(())
+        })
+        .unwrap();
+    samples
+}
+
+fn summaries(
+    series: &mut TestSeries<'_>,
+    storage: &mut Storage<'_, 'static, TestFlash, REGION_SIZE, REGION_COUNT>,
+    id: u32,
+    range: Range<i64>,
+) -> std::vec::Vec<TimeSeriesSummary> {
+    let mut summaries = std::vec::Vec::new();
+    series
+        .region_summaries(storage, id, range, |summary| {
+            summaries.push(*summary);
+            Ok(())
+        })
+        .unwrap();
+    summaries
+}
+
+/// Regular one-second samples with a slowly drifting value.
+fn sensor_value(index: i64) -> f64 {
+    20.0 + (index / 10) as f64 * 0.25
+}
+
+//= spec/time-series.md#time-series-state
+//= type=test
+//# `RING-TS-001` `TimeSeries::new` MUST create a collection with
+//# `collection_type = 0x0006` that holds no samples.
+#[test]
+fn requirement_time_series_new_creates_empty_collection() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestSeriesMemory::new();
+    let mut series = TestSeries::new(&mut storage, &mut memory).unwrap();
+
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == series.collection_id())
+            .and_then(|collection| collection.collection_type()),
+        Some(CollectionType::TIME_SERIES_CODE)
+    );
+    assert_eq!(series.collection_type().stable_code(), Some(6));
+    assert_eq!(series.sample_count(), 0);
+    assert_eq!(series.segment_count(), 0);
+    assert!(query(&mut series, &mut storage, 1, i64::MIN..i64::MAX).is_empty());
+}
+
+//= spec/time-series.md#time-series-state
+//= type=test
+//# `RING-TS-002` `query(series, t0..t1, visitor)` MUST visit exactly the
+//# samples of `series` whose timestamps lie in `t0..t1`, bit-exact and in
+//# append order, across sealed segments and the buffered frontier.
+#[test]
+fn requirement_time_series_query_returns_exact_samples_in_range() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestSeriesMemory::new();
+    let mut series = TestSeries::new(&mut storage, &mut memory).unwrap();
+
+    let edge_samples = [
+        (i64::MIN, f64::NEG_INFINITY),
+        (-5, -0.0),
+        (0, f64::NAN),
+        (1, f64::MIN_POSITIVE),
+        (1_000_000_007, f64::MAX),
+        (i64::MAX, 1.5),
+    ];
+    let mut expected_edges = std::vec::Vec::new();
+    for (time, value) in edge_samples {
+        series.append(&mut storage, 9, time, value).unwrap();
+        expected_edges.push((time, value.to_bits()));
+    }
+    for index in 0..150 {
+        series
+            .append(&mut storage, 1, 1_000 + index, sensor_value(index))
+            .unwrap();
+        series
+            .append(&mut storage, 2, 1_000 + 3 * index, -(index as f64))
+            .unwrap();
+    }
+    series.flush(&mut storage).unwrap();
+    for index in 150..200 {
+        series
+            .append(&mut storage, 1, 1_000 + index, sensor_value(index))
+            .unwrap();
+    }
+    assert!(series.segment_count() > 0);
+    assert_eq!(series.sample_count(), 6 + 300 + 50);
+
+    assert_eq!(
+        query(&mut series, &mut storage, 9, i64::MIN..i64::MAX),
+        expected_edges[..5]
+    );
+    let expected: std::vec::Vec<_> = (100..180)
+        .map(|index| (1_000 + index, sensor_value(index).to_bits()))
+        .collect();
+    assert_eq!(query(&mut series, &mut storage, 1, 1_100..1_180), expected);
+    let expected: std::vec::Vec<_> = (0..100)
+        .map(|index| (1_000 + 3 * index, (-(index as f64)).to_bits()))
+        .collect();
+    assert_eq!(query(&mut series, &mut storage, 2, 0..1_300), expected);
+    assert!(query(&mut series, &mut storage, 3, i64::MIN..i64::MAX).is_empty());
+}
+
+//= spec/time-series.md#segment-encoding
+//= type=test
+//# `RING-TS-003` Segments MUST store timestamps as delta-of-delta values and
+//# sample values as XOR against the previous value of the same series, so a
+//# regular series with repeating values costs a few bits per sample.
+#[test]
+fn requirement_time_series_packs_regular_samples_densely() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestSeriesMemory::new();
+    let mut series = TestSeries::new(&mut storage, &mut memory).unwrap();
+
+    for index in 0..300 {
+        series
+            .append(&mut storage, 7, 60 * index, sensor_value(index))
+            .unwrap();
+    }
+    // A raw (series, timestamp, value) sample is 20 bytes, so 300 of them
+    // would need about six regions.
+    assert_eq!(series.segment_count(), 0);
+    assert!(series.memory.frontier_bits < 300 * 8);
+
+    series.flush(&mut storage).unwrap();
+    assert_eq!(series.segment_count(), 1);
+    assert_eq!(query(&mut series, &mut storage, 7, 0..i64::MAX).len(), 300);
+}
+
+//= spec/time-series.md#segment-encoding
+//= type=test
+//# `RING-TS-004` Each sealed segment MUST record the minimum and maximum
+//# sample timestamps in its prologue, and queries MUST NOT read segments whose
+//# time bounds do not overlap the requested range.
+#[test]
+fn requirement_time_series_segments_record_time_bounds() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestSeriesMemory::new();
+    let mut series = TestSeries::new(&mut storage, &mut memory).unwrap();
+
+    for index in 0..10 {
+        series.append(&mut storage, 1, 100 - index, 1.0).unwrap();
+    }
+    series.flush(&mut storage).unwrap();
+    for index in 0..10 {
+        series.append(&mut storage, 1, 500 + index, 2.0).unwrap();
+    }
+    series.flush(&mut storage).unwrap();
+
+    let first = series.memory.segments[0];
+    let prologue = series
+        .read_segment_prologue(storage.backing, &first)
+        .unwrap();
+    assert_eq!((prologue.min_time, prologue.max_time), (91, 100));
+    assert_eq!(prologue.sample_count, 10);
+
+    // Destroying the first segment must not affect a query that its time
+    // bounds exclude.
+    storage.backing.erase_region(first.region_index).unwrap();
+    assert_eq!(query(&mut series, &mut storage, 1, 200..1_000).len(), 10);
+    assert!(series
+        .query(&mut storage, 1, 0..1_000, |_, _| Ok(()))
+        .is_err());
+}
+
+//= spec/time-series.md#segment-encoding
+//= type=test
+//# `RING-TS-005` Each sealed segment MUST carry a per-series summary of sample
+//# count, time bounds, minimum, maximum, and sum, and `region_summaries` MUST
+//# visit the summaries of the requested series that overlap the range,
+//# oldest first and ending with the frontier.
+#[test]
+fn requirement_time_series_region_summaries_aggregate_each_segment() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestSeriesMemory::new();
+    let mut series = TestSeries::new(&mut storage, &mut memory).unwrap();
+
+    for (time, value) in [(10, 4.0), (11, -2.0), (12, 1.0)] {
+        series.append(&mut storage, 1, time, value).unwrap();
+        series.append(&mut storage, 2, time, 100.0).unwrap();
+    }
+    series.flush(&mut storage).unwrap();
+    for (time, value) in [(20, 8.0), (25, 6.0)] {
+        series.append(&mut storage, 1, time, value).unwrap();
+    }
+
+    assert_eq!(
+        summaries(&mut series, &mut storage, 1, 0..100),
+        [
+            TimeSeriesSummary {
+                series: 1,
+                sample_count: 3,
+                min_time: 10,
+                max_time: 12,
+                min_value: -2.0,
+                max_value: 4.0,
+                sum: 3.0,
+            },
+            TimeSeriesSummary {
+                series: 1,
+                sample_count: 2,
+                min_time: 20,
+                max_time: 25,
+                min_value: 6.0,
+                max_value: 8.0,
+                sum: 14.0,
+            },
+        ]
+    );
+    assert_eq!(summaries(&mut series, &mut storage, 1, 15..100).len(), 1);
+    assert_eq!(summaries(&mut series, &mut storage, 2, 0..100).len(), 1);
+}
+
+//= spec/time-series.md#segment-encoding
+//= type=test
+//# `RING-TS-006` Appending a sample for a new series to a frontier that
+//# already holds `MAX_SERIES` series MUST seal the frontier first.
+#[test]
+fn requirement_time_series_seals_when_series_table_is_full() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestSeriesMemory::new();
+    let mut series = TestSeries::new(&mut storage, &mut memory).unwrap();
+
+    for id in 0..MAX_SERIES as u32 {
+        series.append(&mut storage, id, 1, f64::from(id)).unwrap();
+    }
+    assert_eq!(series.segment_count(), 0);
+    series.append(&mut storage, 99, 2, 99.0).unwrap();
+    assert_eq!(series.segment_count(), 1);
+    assert_eq!(series.memory.frontier_series.len(), 1);
+    for id in 0..MAX_SERIES as u32 {
+        assert_eq!(
+            query(&mut series, &mut storage, id, 0..10),
+            [(1, f64::from(id).to_bits())]
+        );
+    }
+}
+
+//= spec/time-series.md#durable-storage
+//= type=test
+//# `RING-TS-007` Appends MUST be persisted through WAL updates and each seal
+//# MUST record the segment list in a WAL snapshot, so reopening the collection
+//# restores every sample.
+#[test]
+fn requirement_time_series_samples_survive_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let (id, count) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut memory = TestSeriesMemory::new();
+        let mut series = TestSeries::new(&mut storage, &mut memory).unwrap();
+        for index in 0..300 {
+            series
+                .append(&mut storage, 1, 17 * index, sensor_value(index))
+                .unwrap();
+        }
+        series.flush(&mut storage).unwrap();
+        for index in 300..340 {
+            series
+                .append(&mut storage, 1, 17 * index, sensor_value(index))
+                .unwrap();
+        }
+        (series.collection_id(), series.sample_count())
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == id)
+            .map(|collection| collection.basis()),
+        Some(StartupCollectionBasis::WalSnapshot)
+    );
+    let mut memory = TestSeriesMemory::new();
+    let mut series = TestSeries::open(id, &mut storage, &mut memory).unwrap();
+    assert_eq!(series.sample_count(), count);
+    let expected: std::vec::Vec<_> = (0..340)
+        .map(|index| (17 * index, sensor_value(index).to_bits()))
+        .collect();
+    assert_eq!(query(&mut series, &mut storage, 1, 0..i64::MAX), expected);
+
+    series.append(&mut storage, 1, 17 * 340, 0.5).unwrap();
+    assert_eq!(series.sample_count(), count + 1);
+}
+
+//= spec/time-series.md#durable-storage
+//= type=test
+//# `RING-TS-008` Opening a time series MUST reject unknown collection ids and
+//# collections of another type.
+#[test]
+fn requirement_time_series_open_validates_collection_type() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestSeriesMemory::new();
+    assert!(matches!(
+        TestSeries::open(CollectionId(77), &mut storage, &mut memory),
+        Err(TimeSeriesError::UnknownCollection(CollectionId(77)))
+    ));
+
+    let other = storage.allocate_collection_id().unwrap();
+    storage
+        .append_new_collection(other, CollectionType::MAP_CODE)
+        .unwrap();
+    assert!(matches!(
+        TestSeries::open(other, &mut storage, &mut memory),
+        Err(TimeSeriesError::CollectionTypeMismatch {
+            actual: Some(CollectionType::MAP_CODE),
+            ..
+        })
+    ));
+}
+
+//= spec/time-series.md#durable-storage
+//= type=test
+//# `RING-TS-009` The empty time-series snapshot used by WAL head reclaim MUST
+//# replay to the same state as a newly created time series.
+#[test]
+fn requirement_time_series_empty_snapshot_replays_initial_state() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut memory = TestSeriesMemory::new();
+    let mut series = TestSeries::new(&mut storage, &mut memory).unwrap();
+    series.append(&mut storage, 1, 1, 1.0).unwrap();
+    series.flush(&mut storage).unwrap();
+
+    series.apply_snapshot(empty_snapshot()).unwrap();
+    assert_eq!(series.sample_count(), 0);
+    assert_eq!(series.segment_count(), 0);
+    assert_eq!(series.memory.frontier_bits, 0);
+}
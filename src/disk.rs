@@ -13,6 +13,8 @@ pub const MAIN_WAL_V2_FORMAT: u16 = 0;
 pub const TRANSACTION_LOG_V2_FORMAT: u16 = 1;
 /// Stable `collection_format` reserved for free-space metadata regions.
 pub const FREE_SPACE_V2_FORMAT: u16 = 2;
+/// Stable `collection_format` reserved for free-space wear-table regions.
+pub const FREE_SPACE_WEAR_V1_FORMAT: u16 = 14;
 /// Backwards-compatible name for the current main-WAL format.
 pub const WAL_V1_FORMAT: u16 = MAIN_WAL_V2_FORMAT;
 
@@ -163,6 +165,13 @@ impl StorageMetadata {
 
         self.validate()?;
 
+        let offset = self.encode_fields_into(buffer)?;
+        let checksum = crc32(&buffer[..offset]);
+        let offset = write_u32(buffer, offset, checksum)?;
+        Ok(offset)
+    }
+
+    fn encode_fields_into(&self, buffer: &mut [u8]) -> Result<usize, DiskError> {
         let mut offset = 0;
         offset = write_u32(buffer, offset, self.storage_version)?;
         offset = write_u32(buffer, offset, self.region_size)?;
@@ -171,11 +180,7 @@ impl StorageMetadata {
         offset = write_u32(buffer, offset, self.transaction_log_count)?;
         offset = write_u32(buffer, offset, self.wal_write_granule)?;
         offset = write_u8(buffer, offset, self.erased_byte)?;
-        offset = write_u8(buffer, offset, self.wal_record_magic)?;
-
-        let checksum = crc32(&buffer[..offset]);
-        let offset = write_u32(buffer, offset, checksum)?;
-        Ok(offset)
+        write_u8(buffer, offset, self.wal_record_magic)
     }
 
     /// Decodes metadata from a CRC-protected byte slice.
@@ -183,14 +188,7 @@ impl StorageMetadata {
         ensure_len(buffer, Self::ENCODED_LEN)?;
 
         let mut offset = 0;
-        let storage_version = read_u32(buffer, &mut offset)?;
-        let region_size = read_u32(buffer, &mut offset)?;
-        let region_count = read_u32(buffer, &mut offset)?;
-        let min_free_regions = read_u32(buffer, &mut offset)?;
-        let transaction_log_count = read_u32(buffer, &mut offset)?;
-        let wal_write_granule = read_u32(buffer, &mut offset)?;
-        let erased_byte = read_u8(buffer, &mut offset)?;
-        let wal_record_magic = read_u8(buffer, &mut offset)?;
+        let metadata = Self::decode_fields(buffer, &mut offset)?;
         let checksum = read_u32(buffer, &mut offset)?;
 
         let expected = crc32(&buffer[..offset - size_of::<u32>()]);
@@ -198,20 +196,23 @@ impl StorageMetadata {
             return Err(DiskError::InvalidChecksum);
         }
 
-        let metadata = Self {
-            storage_version,
-            region_size,
-            region_count,
-            min_free_regions,
-            transaction_log_count,
-            wal_write_granule,
-            erased_byte,
-            wal_record_magic,
-        };
         metadata.validate()?;
         Ok(metadata)
     }
 
+    fn decode_fields(buffer: &[u8], offset: &mut usize) -> Result<Self, DiskError> {
+        Ok(Self {
+            storage_version: read_u32(buffer, offset)?,
+            region_size: read_u32(buffer, offset)?,
+            region_count: read_u32(buffer, offset)?,
+            min_free_regions: read_u32(buffer, offset)?,
+            transaction_log_count: read_u32(buffer, offset)?,
+            wal_write_granule: read_u32(buffer, offset)?,
+            erased_byte: read_u8(buffer, offset)?,
+            wal_record_magic: read_u8(buffer, offset)?,
+        })
+    }
+
     /// Returns the first byte offset usable for WAL records in a WAL region.
     pub fn wal_record_area_offset(&self) -> Result<usize, DiskError> {
         let granule = usize::try_from(self.wal_write_granule)
@@ -226,6 +227,156 @@ impl StorageMetadata {
     }
 }
 
+/// Number of redundant [`MetadataCopy`] slots kept in the metadata region.
+pub const METADATA_COPY_COUNT: usize = 2;
+
+/// One sequence-numbered [`StorageMetadata`] copy stored in a metadata region slot.
+///
+/// The copy encodes the metadata fields, then `sequence`, then one CRC-32C
+/// over both, in place of the bare `metadata_checksum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataCopy {
+    /// Metadata carried by this copy.
+    pub metadata: StorageMetadata,
+    /// Write sequence; of two valid copies the serially newer one is current.
+    pub sequence: u16,
+}
+
+impl MetadataCopy {
+    /// Encoded byte length of [`MetadataCopy`].
+    pub const ENCODED_LEN: usize = StorageMetadata::ENCODED_LEN + size_of::<u16>();
+    /// Smallest metadata region that can hold every copy slot.
+    pub const MIN_REGION_LEN: usize = Self::ENCODED_LEN * METADATA_COPY_COUNT;
+
+    /// Encodes the copy into `buffer` and returns the encoded length.
+    pub fn encode_into(&self, buffer: &mut [u8]) -> Result<usize, DiskError> {
+        ensure_len(buffer, Self::ENCODED_LEN)?;
+        self.metadata.validate()?;
+
+        let offset = self.metadata.encode_fields_into(buffer)?;
+        let offset = write_u16(buffer, offset, self.sequence)?;
+        let checksum = crc32(&buffer[..offset]);
+        let offset = write_u32(buffer, offset, checksum)?;
+        Ok(offset)
+    }
+
+    /// Decodes a copy from a CRC-protected byte slice.
+    pub fn decode(buffer: &[u8]) -> Result<Self, DiskError> {
+        ensure_len(buffer, Self::ENCODED_LEN)?;
+
+        let mut offset = 0;
+        let metadata = StorageMetadata::decode_fields(buffer, &mut offset)?;
+        let sequence = read_u16(buffer, &mut offset)?;
+        let checksum = read_u32(buffer, &mut offset)?;
+
+        let expected = crc32(&buffer[..offset - size_of::<u32>()]);
+        if checksum != expected {
+            return Err(DiskError::InvalidChecksum);
+        }
+
+        metadata.validate()?;
+        Ok(Self { metadata, sequence })
+    }
+
+    /// Returns whether this copy was written after `other`.
+    ///
+    /// Sequences compare in serial-number order so a wrapped counter still
+    /// reads as newer.
+    pub fn is_newer_than(&self, other: &Self) -> bool {
+        let distance = self.sequence.wrapping_sub(other.sequence);
+        distance != 0 && distance < 0x8000
+    }
+}
+
+/// Returns the byte offset of metadata copy slot `copy_index` in a metadata region.
+///
+/// Each slot starts on a `region_size / METADATA_COPY_COUNT` boundary so the
+/// copies land in different flash pages whenever the region spans several.
+pub fn metadata_copy_offset(region_size: usize, copy_index: usize) -> Result<usize, DiskError> {
+    if region_size < MetadataCopy::MIN_REGION_LEN {
+        return Err(DiskError::BufferTooSmall {
+            needed: MetadataCopy::MIN_REGION_LEN,
+            available: region_size,
+        });
+    }
+    if copy_index >= METADATA_COPY_COUNT {
+        return Err(DiskError::BufferTooSmall {
+            needed: copy_index
+                .saturating_add(1)
+                .saturating_mul(region_size / METADATA_COPY_COUNT),
+            available: region_size,
+        });
+    }
+    Ok(copy_index * (region_size / METADATA_COPY_COUNT))
+}
+
+/// Newest valid [`MetadataCopy`] found in a metadata region and its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataCopySelection {
+    /// Slot holding the selected copy.
+    pub copy_index: usize,
+    /// Selected copy.
+    pub copy: MetadataCopy,
+}
+
+impl MetadataCopySelection {
+    /// Returns the slot and sequence the next metadata write should use.
+    ///
+    /// The next write always targets the slot that does not hold the current
+    /// copy, so a torn write can only lose the copy being written.
+    pub fn next_write(selection: Option<Self>) -> (usize, u16) {
+        match selection {
+            Some(selection) => (
+                (selection.copy_index + 1) % METADATA_COPY_COUNT,
+                selection.copy.sequence.wrapping_add(1),
+            ),
+            None => (0, 0),
+        }
+    }
+}
+
+/// Selects the newest valid metadata copy stored in `region`.
+///
+/// A region written before metadata copies existed holds one bare
+/// [`StorageMetadata`] at offset `0`; it is accepted as slot `0` with
+/// sequence `0` when neither slot decodes. If nothing decodes, the error of
+/// the first copy whose checksum matched is returned, so an unsupported
+/// `storage_version` is reported as such; otherwise the slot `0` error is.
+pub fn select_metadata_copy(region: &[u8]) -> Result<MetadataCopySelection, DiskError> {
+    let mut selected: Option<MetadataCopySelection> = None;
+    let mut first_error = None;
+    for copy_index in 0..METADATA_COPY_COUNT {
+        let offset = metadata_copy_offset(region.len(), copy_index)?;
+        match MetadataCopy::decode(&region[offset..]) {
+            Ok(copy) => {
+                if selected.is_none_or(|current| copy.is_newer_than(&current.copy)) {
+                    selected = Some(MetadataCopySelection { copy_index, copy });
+                }
+            }
+            Err(error) => {
+                if first_error.is_none_or(|first| first == DiskError::InvalidChecksum) {
+                    first_error = Some(error);
+                }
+            }
+        }
+    }
+
+    if let Some(selected) = selected {
+        return Ok(selected);
+    }
+    match StorageMetadata::decode(region) {
+        Ok(metadata) => Ok(MetadataCopySelection {
+            copy_index: 0,
+            copy: MetadataCopy {
+                metadata,
+                sequence: 0,
+            },
+        }),
+        Err(DiskError::InvalidChecksum) => Err(first_error.unwrap_or(DiskError::InvalidChecksum)),
+        Err(error) => Err(error),
+    }
+}
+
 pub(crate) fn encode_log_region_prefix(
     buffer: &mut [u8],
     metadata: StorageMetadata,
@@ -478,6 +629,99 @@ pub fn free_space_entries_checksum(entries: &[u8]) -> u32 {
     crc32(entries)
 }
 
+/// High bit of a `free_space_wear_v1` entry marking its region as retired.
+///
+/// The low 31 bits hold the region's erase count.
+pub const FREE_SPACE_WEAR_RETIRED_FLAG: u32 = 1 << 31;
+
+/// Returns how many erase counts fit in one `free_space_wear_v1` region.
+pub fn free_space_wear_counts_per_region(region_size: usize) -> usize {
+    region_size.saturating_sub(Header::ENCODED_LEN + FreeSpaceWearPrologue::ENCODED_LEN)
+        / FreeSpaceWearPrologue::COUNT_ENCODED_LEN
+}
+
+/// Encodes one region segment of the free-space wear table.
+pub fn encode_free_space_wear_segment(
+    buffer: &mut [u8],
+    metadata: StorageMetadata,
+    sequence: u64,
+    region_index: u32,
+    erased_through: FreeQueuePosition,
+    next_wear_region: Option<u32>,
+    counts: &[u32],
+) -> Result<usize, DiskError> {
+    if region_index >= metadata.region_count {
+        return Err(DiskError::InvalidRegionIndex {
+            region_index,
+            region_count: metadata.region_count,
+        });
+    }
+    let counts_offset = Header::ENCODED_LEN + FreeSpaceWearPrologue::ENCODED_LEN;
+    let counts_len = counts
+        .len()
+        .checked_mul(FreeSpaceWearPrologue::COUNT_ENCODED_LEN)
+        .ok_or(DiskError::BufferTooSmall {
+            needed: usize::MAX,
+            available: buffer.len(),
+        })?;
+    ensure_len(buffer, counts_offset + counts_len)?;
+    buffer.fill(metadata.erased_byte);
+
+    let count_len = u32::try_from(counts.len()).map_err(|_| DiskError::BufferTooSmall {
+        needed: counts.len(),
+        available: u32::MAX as usize,
+    })?;
+    let header = Header {
+        sequence,
+        collection_id: CollectionId(0),
+        collection_format: FREE_SPACE_WEAR_V1_FORMAT,
+    };
+    header.encode_into(buffer)?;
+
+    let mut offset = counts_offset;
+    for count in counts.iter().copied() {
+        offset = write_u32(buffer, offset, count)?;
+    }
+    let prologue = FreeSpaceWearPrologue {
+        erased_through,
+        next_wear_region,
+        count_len,
+        counts_checksum: crc32(&buffer[counts_offset..offset]),
+    };
+    prologue.encode_into(
+        &mut buffer[Header::ENCODED_LEN..counts_offset],
+        metadata.region_count,
+    )?;
+    Ok(offset)
+}
+
+/// Decodes the erase counts stored after a [`FreeSpaceWearPrologue`].
+pub fn decode_free_space_wear_counts(
+    counts: &[u8],
+    prologue: FreeSpaceWearPrologue,
+    mut visit: impl FnMut(u32),
+) -> Result<(), DiskError> {
+    let count_len = usize::try_from(prologue.count_len).map_err(|_| DiskError::BufferTooSmall {
+        needed: usize::MAX,
+        available: counts.len(),
+    })?;
+    let counts_len = count_len
+        .checked_mul(FreeSpaceWearPrologue::COUNT_ENCODED_LEN)
+        .ok_or(DiskError::BufferTooSmall {
+            needed: usize::MAX,
+            available: counts.len(),
+        })?;
+    ensure_len(counts, counts_len)?;
+    if crc32(&counts[..counts_len]) != prologue.counts_checksum {
+        return Err(DiskError::InvalidChecksum);
+    }
+    let mut offset = 0;
+    for _ in 0..count_len {
+        visit(read_u32(counts, &mut offset)?);
+    }
+    Ok(())
+}
+
 /// Per-region header shared by WAL and committed collection regions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Header {
@@ -785,6 +1029,83 @@ impl FreeSpaceRegionPrologue {
     }
 }
 
+/// Prologue stored in each `free_space_wear_v1` wear-table region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpaceWearPrologue {
+    /// Free-queue position up to which erased entries are included in the
+    /// counts.
+    pub erased_through: FreeQueuePosition,
+    /// Next wear-table region in region-index order.
+    pub next_wear_region: Option<u32>,
+    /// Number of erase counts stored in this region.
+    pub count_len: u32,
+    /// CRC-32C of the stored erase-count bytes.
+    pub counts_checksum: u32,
+}
+
+impl FreeSpaceWearPrologue {
+    /// Encoded byte length of [`FreeSpaceWearPrologue`].
+    pub const ENCODED_LEN: usize =
+        FreeQueuePosition::ENCODED_LEN + size_of::<u8>() + size_of::<u32>() * 4;
+    /// Encoded byte length of one erase count.
+    pub const COUNT_ENCODED_LEN: usize = size_of::<u32>();
+
+    /// Encodes this prologue into `buffer`.
+    pub fn encode_into(&self, buffer: &mut [u8], region_count: u32) -> Result<usize, DiskError> {
+        self.erased_through.validate(region_count)?;
+        if let Some(next) = self.next_wear_region {
+            if next >= region_count {
+                return Err(DiskError::InvalidRegionIndex {
+                    region_index: next,
+                    region_count,
+                });
+            }
+        }
+        ensure_len(buffer, Self::ENCODED_LEN)?;
+
+        let mut offset = 0;
+        offset = self.erased_through.encode_into_at(buffer, offset)?;
+        offset = write_opt_region_index(buffer, offset, self.next_wear_region)?;
+        offset = write_u32(buffer, offset, self.count_len)?;
+        offset = write_u32(buffer, offset, self.counts_checksum)?;
+        let checksum = crc32(&buffer[..offset]);
+        let offset = write_u32(buffer, offset, checksum)?;
+        Ok(offset)
+    }
+
+    /// Decodes this prologue from `buffer`.
+    pub fn decode(buffer: &[u8], region_count: u32) -> Result<Self, DiskError> {
+        ensure_len(buffer, Self::ENCODED_LEN)?;
+        let mut offset = 0;
+        let erased_through = FreeQueuePosition::decode_from(buffer, &mut offset)?;
+        let next_wear_region = read_opt_region_index(buffer, &mut offset)?;
+        let count_len = read_u32(buffer, &mut offset)?;
+        let counts_checksum = read_u32(buffer, &mut offset)?;
+        let checksum = read_u32(buffer, &mut offset)?;
+
+        let expected = crc32(&buffer[..offset - size_of::<u32>()]);
+        if checksum != expected {
+            return Err(DiskError::InvalidChecksum);
+        }
+        erased_through.validate(region_count)?;
+        if let Some(next) = next_wear_region {
+            if next >= region_count {
+                return Err(DiskError::InvalidRegionIndex {
+                    region_index: next,
+                    region_count,
+                });
+            }
+        }
+
+        Ok(Self {
+            erased_through,
+            next_wear_region,
+            count_len,
+            counts_checksum,
+        })
+    }
+}
+
 /// One materialized free-space FIFO entry.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FreeSpaceEntry {
@@ -245,7 +245,7 @@ fn requirement_storage_metadata_decode_rejects_bad_checksum() {
 
 //= spec/ring/05-disk-format.md#storage-metadata
 //= type=test
-//# `RING-META-005` Any bytes in the metadata region after the encoded `StorageMetadata` are
+//# `RING-META-005` Any bytes in the metadata region outside the encoded metadata copies are
 //# reserved, MUST be left erased by formatting, and MUST be ignored on read.
 #[test]
 fn requirement_storage_metadata_decode_ignores_reserved_trailing_bytes() {
@@ -255,6 +255,155 @@ fn requirement_storage_metadata_decode_ignores_reserved_trailing_bytes() {
     buffer[StorageMetadata::ENCODED_LEN..].fill(0x13);
 
     assert_eq!(StorageMetadata::decode(&buffer).unwrap(), metadata);
+
+    let mut region = [0x13u8; 128];
+    for (copy_index, sequence) in [(0, 4), (1, 5)] {
+        let offset = metadata_copy_offset(region.len(), copy_index).unwrap();
+        MetadataCopy { metadata, sequence }
+            .encode_into(&mut region[offset..])
+            .unwrap();
+    }
+    assert_eq!(select_metadata_copy(&region).unwrap().copy.sequence, 5);
+}
+
+fn metadata_region_with_copies(copies: &[(usize, u16)]) -> [u8; 128] {
+    let metadata = StorageMetadata::new(4096, 32, 3, 8, 0xff, 0xa5).unwrap();
+    let mut region = [0xffu8; 128];
+    for &(copy_index, sequence) in copies {
+        let offset = metadata_copy_offset(region.len(), copy_index).unwrap();
+        MetadataCopy { metadata, sequence }
+            .encode_into(&mut region[offset..])
+            .unwrap();
+    }
+    region
+}
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-008` The metadata region MUST hold two `MetadataCopy`
+//# slots, with slot `i` starting at byte `i * (region_size / 2)`, and
+//# `region_size` MUST be at least twice the encoded `MetadataCopy` length.
+#[test]
+fn requirement_metadata_copies_occupy_region_halves() {
+    assert_eq!(METADATA_COPY_COUNT, 2);
+    assert_eq!(MetadataCopy::ENCODED_LEN, StorageMetadata::ENCODED_LEN + 2);
+    assert_eq!(metadata_copy_offset(4096, 0), Ok(0));
+    assert_eq!(metadata_copy_offset(4096, 1), Ok(2048));
+    assert_eq!(metadata_copy_offset(97, 1), Ok(48));
+    assert_eq!(
+        metadata_copy_offset(MetadataCopy::MIN_REGION_LEN - 1, 0),
+        Err(DiskError::BufferTooSmall {
+            needed: MetadataCopy::MIN_REGION_LEN,
+            available: MetadataCopy::MIN_REGION_LEN - 1,
+        })
+    );
+    assert!(metadata_copy_offset(4096, 2).is_err());
+}
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-009` `copy_checksum` MUST be CRC-32C over every earlier
+//# `MetadataCopy` field in on-disk order.
+#[test]
+fn requirement_metadata_copy_checksum_covers_metadata_and_sequence() {
+    let metadata = StorageMetadata::new(4096, 32, 3, 8, 0xff, 0xa5).unwrap();
+    let copy = MetadataCopy {
+        metadata,
+        sequence: 0x1234,
+    };
+    let mut buffer = [0u8; MetadataCopy::ENCODED_LEN];
+    assert_eq!(copy.encode_into(&mut buffer), Ok(MetadataCopy::ENCODED_LEN));
+
+    let mut metadata_bytes = [0u8; StorageMetadata::ENCODED_LEN];
+    metadata.encode_into(&mut metadata_bytes).unwrap();
+    let fields_len = StorageMetadata::ENCODED_LEN - size_of::<u32>();
+    assert_eq!(buffer[..fields_len], metadata_bytes[..fields_len]);
+    assert_eq!(buffer[fields_len..fields_len + 2], 0x1234u16.to_le_bytes());
+    let checksum_offset = MetadataCopy::ENCODED_LEN - size_of::<u32>();
+    assert_eq!(
+        buffer[checksum_offset..],
+        crc32(&buffer[..checksum_offset]).to_le_bytes()
+    );
+    assert_eq!(MetadataCopy::decode(&buffer), Ok(copy));
+
+    buffer[fields_len] ^= 0x01;
+    assert_eq!(
+        MetadataCopy::decode(&buffer),
+        Err(DiskError::InvalidChecksum)
+    );
+}
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-010` Reading metadata MUST ignore a slot whose
+//# `copy_checksum` or metadata validation fails and MUST return the valid
+//# copy whose `sequence` is newer in 16-bit serial-number order.
+#[test]
+fn requirement_select_metadata_copy_prefers_newest_valid_copy() {
+    let region = metadata_region_with_copies(&[(0, 7), (1, 8)]);
+    let selection = select_metadata_copy(&region).unwrap();
+    assert_eq!((selection.copy_index, selection.copy.sequence), (1, 8));
+
+    let mut corrupted = region;
+    corrupted[metadata_copy_offset(128, 1).unwrap()] ^= 0x01;
+    let selection = select_metadata_copy(&corrupted).unwrap();
+    assert_eq!((selection.copy_index, selection.copy.sequence), (0, 7));
+
+    let wrapped = metadata_region_with_copies(&[(0, 0), (1, u16::MAX)]);
+    let selection = select_metadata_copy(&wrapped).unwrap();
+    assert_eq!((selection.copy_index, selection.copy.sequence), (0, 0));
+
+    let mut both_corrupted = region;
+    both_corrupted[0] ^= 0x01;
+    both_corrupted[metadata_copy_offset(128, 1).unwrap()] ^= 0x01;
+    assert_eq!(
+        select_metadata_copy(&both_corrupted),
+        Err(DiskError::InvalidChecksum)
+    );
+}
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-011` A metadata write MUST target the slot that does not
+//# hold the current valid copy and MUST use the current `sequence` plus
+//# one, wrapping at `2^16`; with no valid copy it MUST use slot `0` and
+//# `sequence = 0`.
+#[test]
+fn requirement_metadata_writes_alternate_slots() {
+    assert_eq!(MetadataCopySelection::next_write(None), (0, 0));
+
+    let region = metadata_region_with_copies(&[(0, 7), (1, 8)]);
+    let selection = select_metadata_copy(&region).ok();
+    assert_eq!(MetadataCopySelection::next_write(selection), (0, 9));
+
+    let region = metadata_region_with_copies(&[(0, u16::MAX)]);
+    let selection = select_metadata_copy(&region).ok();
+    assert_eq!(MetadataCopySelection::next_write(selection), (1, 0));
+}
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-014` When neither slot holds a valid `MetadataCopy`, a
+//# valid bare `StorageMetadata` at offset `0` MUST be read as slot `0`
+//# with `sequence = 0`.
+#[test]
+fn requirement_select_metadata_copy_reads_bare_metadata() {
+    let metadata = StorageMetadata::new(4096, 32, 3, 8, 0xff, 0xa5).unwrap();
+    let mut region = [0xffu8; 128];
+    metadata.encode_into(&mut region).unwrap();
+
+    let selection = select_metadata_copy(&region).unwrap();
+    assert_eq!(
+        selection,
+        MetadataCopySelection {
+            copy_index: 0,
+            copy: MetadataCopy {
+                metadata,
+                sequence: 0,
+            },
+        }
+    );
+    assert_eq!(MetadataCopySelection::next_write(Some(selection)), (1, 1));
 }
 
 //= spec/ring/04-wal-records.md#encoding-helper-requirements
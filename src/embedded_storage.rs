@@ -2,8 +2,10 @@ use embedded_storage_traits::nor_flash::{NorFlash, NorFlashError, NorFlashErrorK
 
 use crate::disk::{
     encode_free_space_region_segment, encode_log_region_prefix,
-    free_queue_position_for_contiguous_metadata, DiskError, FreeSpaceCursors, FreeSpaceEntry,
-    FreeSpaceRegionPrologue, Header, StorageMetadata, WalRegionPrologue, MAIN_WAL_V2_FORMAT,
+    free_queue_position_for_contiguous_metadata, metadata_copy_offset, select_metadata_copy,
+    DiskError, FreeSpaceCursors, FreeSpaceEntry, FreeSpaceRegionPrologue, Header, MetadataCopy,
+    MetadataCopySelection, StorageMetadata, WalRegionPrologue, MAIN_WAL_V2_FORMAT,
+    METADATA_COPY_COUNT,
 };
 use crate::flash_io::{FlashIo, StorageFormatError, StorageIoError};
 
@@ -250,7 +252,7 @@ where
         Ok(())
     }
 
-    /// Reads formatted storage metadata.
+    /// Reads the newest valid storage metadata copy.
     pub fn read_metadata(&mut self) -> Result<Option<StorageMetadata>, EmbeddedStorageError> {
         self.read_absolute(0, REGION_SIZE, |_| ())?;
         if self.scratch[..REGION_SIZE]
@@ -260,22 +262,58 @@ where
             return Ok(None);
         }
 
-        let metadata = StorageMetadata::decode(&self.scratch[..REGION_SIZE])?;
+        let metadata = select_metadata_copy(&self.scratch[..REGION_SIZE])?
+            .copy
+            .metadata;
         self.validate_metadata(metadata)?;
         Ok(Some(metadata))
     }
 
-    /// Writes formatted storage metadata into the metadata region.
+    /// Writes a new metadata copy into the slot not holding the current copy.
+    ///
+    /// A programmed target slot is erased first when its bounds are
+    /// erase-aligned. Otherwise its erase block is erased and the current copy
+    /// is written back after the new one. When no valid copy remains, the
+    /// whole metadata region is erased instead because nothing can be lost.
     pub fn write_metadata(
         &mut self,
         metadata: StorageMetadata,
     ) -> Result<(), EmbeddedStorageError> {
         self.validate_metadata(metadata)?;
-        self.strict_write_absolute_with(0, REGION_SIZE, |target, erased_byte| {
-            target.fill(erased_byte);
-            metadata.encode_into(target)?;
-            Ok(())
-        })
+        self.read_absolute(0, REGION_SIZE, |_| ())?;
+        let selection = select_metadata_copy(&self.scratch[..REGION_SIZE]).ok();
+        let (copy_index, sequence) = MetadataCopySelection::next_write(selection);
+        let offset = metadata_copy_offset(REGION_SIZE, copy_index)?;
+        let slot_len = REGION_SIZE / METADATA_COPY_COUNT;
+        let slot_blank = self.scratch[offset..offset + slot_len]
+            .iter()
+            .all(|byte| *byte == self.options.erased_byte);
+        if !slot_blank {
+            if selection.is_none() {
+                self.erase_metadata_region()?;
+            } else if offset.is_multiple_of(FLASH::ERASE_SIZE)
+                && slot_len.is_multiple_of(FLASH::ERASE_SIZE)
+            {
+                self.erase_absolute_range(offset, slot_len)?;
+            } else {
+                let block_start = align_down(offset, FLASH::ERASE_SIZE)?;
+                let block_end = align_up(checked_end(offset, slot_len)?, FLASH::ERASE_SIZE)?;
+                self.erase_absolute_range(block_start, block_end - block_start)?;
+                self.write_metadata_copy(offset, slot_len, MetadataCopy { metadata, sequence })?;
+                // The erase block also held the current copy, so it is
+                // written back after the new copy to keep a fallback.
+                if let Some(current) = selection {
+                    let current_offset = metadata_copy_offset(REGION_SIZE, current.copy_index)?;
+                    if current_offset < block_end
+                        && block_start < checked_end(current_offset, slot_len)?
+                    {
+                        self.write_metadata_copy(current_offset, slot_len, current.copy)?;
+                    }
+                }
+                return Ok(());
+            }
+        }
+        self.write_metadata_copy(offset, slot_len, MetadataCopy { metadata, sequence })
     }
 
     /// Reads bytes from a single data region and passes them to `read`.
@@ -390,7 +428,7 @@ where
                 .ok_or(EmbeddedStorageFormatError::RegionCountTooLarge)?;
         }
         let free_space_entry_count = region_count - 1 - metadata_region_count;
-        let min_region_size_usize = StorageMetadata::ENCODED_LEN
+        let min_region_size_usize = MetadataCopy::MIN_REGION_LEN
             .max(wal_header_len)
             .max(wal_record_area_offset)
             .max(free_space_entries_offset + FreeSpaceEntry::ENCODED_LEN);
@@ -403,7 +441,9 @@ where
         }
 
         self.erase_metadata_region()?;
-        self.write_metadata(metadata)?;
+        for _ in 0..METADATA_COPY_COUNT {
+            self.write_metadata(metadata)?;
+        }
         self.sync()?;
 
         for region_index in 0..region_count {
@@ -556,6 +596,19 @@ where
         Ok(())
     }
 
+    fn write_metadata_copy(
+        &mut self,
+        offset: usize,
+        slot_len: usize,
+        copy: MetadataCopy,
+    ) -> Result<(), EmbeddedStorageError> {
+        self.strict_write_absolute_with(offset, slot_len, |target, erased_byte| {
+            target.fill(erased_byte);
+            copy.encode_into(target)?;
+            Ok(())
+        })
+    }
+
     fn erase_metadata_region(&mut self) -> Result<(), EmbeddedStorageError> {
         self.erase_absolute_range(0, REGION_SIZE)
     }
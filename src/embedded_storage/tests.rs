@@ -215,9 +215,14 @@ fn requirement_embedded_storage_uses_configured_erased_byte() {
 
     let mut metadata_region = [0xff; 128];
     backing.read_storage(0, &mut metadata_region).unwrap();
-    assert!(metadata_region[StorageMetadata::ENCODED_LEN..]
-        .iter()
-        .all(|byte| *byte == 0x00));
+    for copy_index in 0..crate::METADATA_COPY_COUNT {
+        let offset = crate::metadata_copy_offset(128, copy_index).unwrap();
+        assert!(
+            metadata_region[offset + crate::MetadataCopy::ENCODED_LEN..offset + 64]
+                .iter()
+                .all(|byte| *byte == 0x00)
+        );
+    }
 }
 
 //= spec/embedded-storage.md#backend-behavior
@@ -401,3 +406,63 @@ fn requirement_embedded_storage_works_with_generic_storage_api_non_ff() {
         Some(70)
     );
 }
+
+//= spec/embedded-storage.md#backend-behavior
+//= type=test
+//# `RING-EMBEDDED-008` Metadata writes MUST erase a programmed target
+//# copy slot alone when the slot bounds are erase-aligned, MUST erase the
+//# whole metadata region instead when no valid copy remains, and otherwise
+//# MUST erase the containing erase block, write the new copy, and then
+//# write the current copy back so the slot can be rewritten again.
+#[test]
+fn requirement_embedded_storage_metadata_writes_erase_only_target_slot() {
+    let flash = TestNorFlash::<512, 1, 8, 64, 256>::new(0xff);
+    let mut backing =
+        EmbeddedStorageFlash::<_, 128, 3>::new(flash, EmbeddedStorageOptions::new(0xff)).unwrap();
+    let metadata = backing.format_empty_store(1, 8, 0xa5).unwrap();
+    let operations_before = backing.inner().operations().len();
+
+    backing.write_metadata(metadata).unwrap();
+    assert!(backing.inner().operations()[operations_before..]
+        .contains(&TestNorOperation::Erase { from: 0, to: 64 }));
+    let bytes = backing.inner().bytes;
+    assert_eq!(
+        crate::MetadataCopy::decode(&bytes[..64]).unwrap().sequence,
+        2
+    );
+    assert_eq!(
+        crate::MetadataCopy::decode(&bytes[64..128])
+            .unwrap()
+            .sequence,
+        1
+    );
+
+    backing.inner_mut().bytes[0] ^= 0x01;
+    backing.inner_mut().bytes[64] ^= 0x01;
+    let operations_before = backing.inner().operations().len();
+    backing.write_metadata(metadata).unwrap();
+    assert!(backing.inner().operations()[operations_before..]
+        .contains(&TestNorOperation::Erase { from: 0, to: 128 }));
+    assert_eq!(backing.read_metadata().unwrap(), Some(metadata));
+
+    let flash = TestNorFlash::<512, 1, 8, 128, 256>::new(0xff);
+    let mut backing =
+        EmbeddedStorageFlash::<_, 128, 3>::new(flash, EmbeddedStorageOptions::new(0xff)).unwrap();
+    let metadata = backing.format_empty_store(1, 8, 0xa5).unwrap();
+    for sequence in 2..=4 {
+        let operations_before = backing.inner().operations().len();
+        backing.write_metadata(metadata).unwrap();
+        assert!(backing.inner().operations()[operations_before..]
+            .contains(&TestNorOperation::Erase { from: 0, to: 128 }));
+        let bytes = backing.inner().bytes;
+        let copies = [
+            crate::MetadataCopy::decode(&bytes[..64]).unwrap().sequence,
+            crate::MetadataCopy::decode(&bytes[64..128])
+                .unwrap()
+                .sequence,
+        ];
+        assert!(copies.contains(&sequence));
+        assert!(copies.contains(&(sequence - 1)));
+        assert_eq!(backing.read_metadata().unwrap(), Some(metadata));
+    }
+}
@@ -1,6 +1,6 @@
 use core::ops::Range;
 
-use memmap2::{MmapMut, MmapOptions};
+use memmap2::{Mmap, MmapMut, MmapOptions};
 use std::fs::{File, OpenOptions};
 use std::io;
 use std::os::fd::AsRawFd;
@@ -9,8 +9,9 @@ use std::time::Instant;
 
 use crate::disk::{
     encode_free_space_region_segment, encode_log_region_prefix,
-    free_queue_position_for_contiguous_metadata, DiskError, FreeSpaceCursors, FreeSpaceEntry,
-    FreeSpaceRegionPrologue, Header, StorageMetadata, MAIN_WAL_V2_FORMAT,
+    free_queue_position_for_contiguous_metadata, metadata_copy_offset, select_metadata_copy,
+    DiskError, FreeSpaceCursors, FreeSpaceEntry, FreeSpaceRegionPrologue, Header, MetadataCopy,
+    MetadataCopySelection, StorageMetadata, MAIN_WAL_V2_FORMAT, METADATA_COPY_COUNT,
 };
 use crate::flash_io::{FlashIo, StorageFormatError, StorageIoError};
 
@@ -199,7 +200,7 @@ pub enum FileBackingOperation {
     Fallocate,
     /// Resizing the file failed.
     SetLen,
-    /// Creating the mutable mmap failed.
+    /// Creating the mmap failed.
     Mmap,
     /// Applying `madvise()` failed.
     Madvise,
@@ -229,6 +230,9 @@ pub enum FileBackingError {
     InvalidRegionIndex(u32),
     /// A byte-range operation exceeded the backing storage bounds.
     OutOfBounds,
+    /// A write, erase, or metadata write reached a backing opened with
+    /// [`FileBacking::open_read_only`].
+    ReadOnly,
     /// An OS operation failed.
     Io {
         /// Operation that failed.
@@ -489,10 +493,46 @@ impl FileBackingGeometry {
     }
 }
 
-/// Linux host-file storage backing implemented with a mutable mmap.
+/// Mapping of the database file.
+enum FileMap {
+    /// Mapping created by `create_new` or `open_existing`.
+    ReadWrite(MmapMut),
+    /// Mapping created by `open_read_only` over a read-only descriptor.
+    ReadOnly(Mmap),
+}
+
+impl FileMap {
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Self::ReadWrite(map) => map,
+            Self::ReadOnly(map) => map,
+        }
+    }
+
+    fn bytes_mut(&mut self) -> Result<&mut [u8], FileBackingError> {
+        match self {
+            Self::ReadWrite(map) => Ok(map),
+            Self::ReadOnly(_) => Err(FileBackingError::ReadOnly),
+        }
+    }
+
+    fn address(&self) -> *mut u8 {
+        self.bytes().as_ptr().cast_mut()
+    }
+
+    fn flush_range(&self, offset: usize, len: usize) -> io::Result<()> {
+        match self {
+            Self::ReadWrite(map) => map.flush_range(offset, len),
+            Self::ReadOnly(_) => Ok(()),
+        }
+    }
+}
+
+/// Linux host-file storage backing implemented with an mmap, mutable unless
+/// opened with [`FileBacking::open_read_only`].
 pub struct FileBacking<const REGION_SIZE: usize, const REGION_COUNT: usize> {
     file: File,
-    map: MmapMut,
+    map: FileMap,
     options: FileBackingOptions,
     geometry: FileBackingGeometry,
     dirty_range: Option<Range<usize>>,
@@ -531,6 +571,26 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
         Self::open_existing_with_os(path.as_ref(), options, scratch, &mut os)
     }
 
+    /// Opens an existing database file without write access and maps it
+    /// read-only.
+    ///
+    /// Writes, erases, and metadata writes fail with
+    /// [`FileBackingError::ReadOnly`]; wrap the backing in
+    /// [`ReadOnlyFlash`](crate::ReadOnlyFlash) to open storage over it.
+    pub fn open_read_only(
+        path: impl AsRef<Path>,
+        options: FileBackingOptions,
+        scratch: &mut FileBackingScratch,
+    ) -> Result<Self, FileBackingError> {
+        let mut os = LinuxFileBackingOs;
+        Self::open_read_only_with_os(path.as_ref(), options, scratch, &mut os)
+    }
+
+    /// Returns whether this backing was opened with [`Self::open_read_only`].
+    pub fn is_read_only(&self) -> bool {
+        matches!(self.map, FileMap::ReadOnly(_))
+    }
+
     /// Returns file geometry discovered at create/open time.
     pub fn geometry(&self) -> FileBackingGeometry {
         self.geometry
@@ -544,11 +604,11 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
     /// Reads from the metadata region plus data regions as one contiguous space.
     pub fn read_storage(&self, offset: usize, buffer: &mut [u8]) -> Result<(), FileBackingError> {
         let range = checked_range(offset, buffer.len(), self.geometry.file_len)?;
-        buffer.copy_from_slice(&self.map[range]);
+        buffer.copy_from_slice(&self.map.bytes()[range]);
         Ok(())
     }
 
-    /// Reads formatted storage metadata.
+    /// Reads the newest valid storage metadata copy.
     pub fn read_metadata(&mut self) -> Result<Option<StorageMetadata>, FileBackingError> {
         let metadata_region = self.metadata_region();
         if metadata_region
@@ -558,21 +618,25 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
             return Ok(None);
         }
 
-        let metadata = StorageMetadata::decode(metadata_region)?;
+        let metadata = select_metadata_copy(metadata_region)?.copy.metadata;
         self.validate_metadata(metadata)?;
         Ok(Some(metadata))
     }
 
-    /// Writes formatted storage metadata into the metadata region.
+    /// Writes a new metadata copy into the slot not holding the current copy.
     pub fn write_metadata(&mut self, metadata: StorageMetadata) -> Result<(), FileBackingError> {
         self.validate_metadata(metadata)?;
         let erased_byte = self.options.erased_byte;
+        let (copy_index, sequence) =
+            MetadataCopySelection::next_write(select_metadata_copy(self.metadata_region()).ok());
+        let offset = metadata_copy_offset(REGION_SIZE, copy_index)?;
+        let slot_range = offset..offset + REGION_SIZE / METADATA_COPY_COUNT;
         {
-            let metadata_region = self.metadata_region_mut();
-            metadata_region.fill(erased_byte);
-            metadata.encode_into(metadata_region)?;
+            let slot = &mut self.metadata_region_mut()?[slot_range.clone()];
+            slot.fill(erased_byte);
+            MetadataCopy { metadata, sequence }.encode_into(slot)?;
         }
-        self.mark_dirty_range(0..REGION_SIZE);
+        self.mark_dirty_range(slot_range);
         Ok(())
     }
 
@@ -588,7 +652,7 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
         F: FnOnce(&[u8]) -> R,
     {
         let range = self.region_range(region_index, offset, len)?;
-        Ok(read(&self.map[range]))
+        Ok(read(&self.map.bytes()[range]))
     }
 
     /// Writes bytes to a single data region.
@@ -599,7 +663,7 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
         data: &[u8],
     ) -> Result<(), FileBackingError> {
         let range = self.region_range(region_index, offset, data.len())?;
-        self.map[range.clone()].copy_from_slice(data);
+        self.map.bytes_mut()?[range.clone()].copy_from_slice(data);
         self.mark_dirty_range(range);
         Ok(())
     }
@@ -607,7 +671,8 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
     /// Erases a single data region to the configured erased byte.
     pub fn erase_region(&mut self, region_index: u32) -> Result<(), FileBackingError> {
         let range = self.region_range(region_index, 0, REGION_SIZE)?;
-        self.map[range.clone()].fill(self.options.erased_byte);
+        let erased_byte = self.options.erased_byte;
+        self.map.bytes_mut()?[range.clone()].fill(erased_byte);
         self.mark_dirty_range(range);
         Ok(())
     }
@@ -690,16 +755,19 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
                 .ok_or(FileBackingFormatError::RegionCountTooLarge)?;
         }
         let free_space_entry_count = region_count - 1 - metadata_region_count;
-        let free_space_region_len = free_space_entries_offset + FreeSpaceEntry::ENCODED_LEN;
-        if REGION_SIZE < free_space_region_len {
-            let min_region_size = u32::try_from(free_space_region_len).unwrap_or(u32::MAX);
+        let min_region_size_usize = MetadataCopy::MIN_REGION_LEN
+            .max(free_space_entries_offset + FreeSpaceEntry::ENCODED_LEN);
+        if REGION_SIZE < min_region_size_usize {
+            let min_region_size = u32::try_from(min_region_size_usize).unwrap_or(u32::MAX);
             return Err(FileBackingFormatError::RegionSizeTooSmall {
                 region_size,
                 min_region_size,
             });
         }
 
-        self.write_metadata(metadata)?;
+        for _ in 0..METADATA_COPY_COUNT {
+            self.write_metadata(metadata)?;
+        }
 
         for region_index in 0..region_count {
             self.erase_region(region_index)?;
@@ -806,16 +874,16 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
             os.set_len(&file, geometry.file_len)?;
         }
 
-        let map = unsafe { MmapOptions::new().len(geometry.file_len).map_mut(&file) }
+        let mut map = unsafe { MmapOptions::new().len(geometry.file_len).map_mut(&file) }
             .map_err(|error| FileBackingError::from_io_error(FileBackingOperation::Mmap, error))?;
+        map.fill(options.erased_byte);
         let mut backing = Self {
             file,
-            map,
+            map: FileMap::ReadWrite(map),
             options,
             geometry,
             dirty_range: None,
         };
-        backing.map.fill(options.erased_byte);
         backing.mark_dirty_range(0..geometry.file_len);
         backing.apply_madvise_with_os(os)?;
         if options.sync_on_create {
@@ -842,7 +910,33 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
             .map_err(|error| FileBackingError::from_io_error(FileBackingOperation::Mmap, error))?;
         let mut backing = Self {
             file,
-            map,
+            map: FileMap::ReadWrite(map),
+            options,
+            geometry,
+            dirty_range: None,
+        };
+        backing.apply_madvise_with_os(os)?;
+        Ok(backing)
+    }
+
+    fn open_read_only_with_os<OS: FileBackingOs>(
+        path: &Path,
+        options: FileBackingOptions,
+        scratch: &mut FileBackingScratch,
+        os: &mut OS,
+    ) -> Result<Self, FileBackingError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .open(path)
+            .map_err(|error| FileBackingError::from_io_error(FileBackingOperation::Open, error))?;
+        let geometry =
+            FileBackingGeometry::discover::<REGION_SIZE, REGION_COUNT, _>(&file, os, scratch)?;
+        geometry.validate_existing_file_len(&file)?;
+        let map = unsafe { MmapOptions::new().len(geometry.file_len).map(&file) }
+            .map_err(|error| FileBackingError::from_io_error(FileBackingOperation::Mmap, error))?;
+        let mut backing = Self {
+            file,
+            map: FileMap::ReadOnly(map),
             options,
             geometry,
             dirty_range: None,
@@ -856,7 +950,7 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
         os: &mut OS,
     ) -> Result<(), FileBackingError> {
         os.madvise(
-            self.map.as_mut_ptr(),
+            self.map.address(),
             self.geometry.file_len,
             self.options.madvise_policy,
         )
@@ -945,11 +1039,11 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize> FileBacking<REGION_SIZ
     }
 
     fn metadata_region(&self) -> &[u8] {
-        &self.map[..REGION_SIZE]
+        &self.map.bytes()[..REGION_SIZE]
     }
 
-    fn metadata_region_mut(&mut self) -> &mut [u8] {
-        &mut self.map[..REGION_SIZE]
+    fn metadata_region_mut(&mut self) -> Result<&mut [u8], FileBackingError> {
+        Ok(&mut self.map.bytes_mut()?[..REGION_SIZE])
     }
 
     fn validate_metadata(&self, metadata: StorageMetadata) -> Result<(), FileBackingError> {
@@ -594,7 +594,9 @@ fn requirement_file_backing_sync_report_syncs_file_for_metadata_write() {
     let metadata =
         StorageMetadata::new(REGION_SIZE as u32, REGION_COUNT as u32, 0, 8, 0xff, 0xa5).unwrap();
 
-    backing.write_metadata(metadata).unwrap();
+    for _ in 0..crate::METADATA_COPY_COUNT {
+        backing.write_metadata(metadata).unwrap();
+    }
     let report = backing.sync_with_os_report(&mut os).unwrap();
 
     assert_eq!(report.dirty_range_start, Some(0));
@@ -708,3 +710,77 @@ fn requirement_file_backing_works_with_generic_storage_api() {
         Some(70)
     );
 }
+
+//= spec/file.md#read-only-open
+//= type=test
+//# `RING-FILE-024` `FileBacking::open_read_only` MUST open the file without
+//# write access and map it read-only, so it opens a file whose permissions
+//# deny writes, and its writes, erases, and metadata writes MUST fail with
+//# `FileBackingError::ReadOnly` without changing the file.
+#[test]
+fn requirement_file_backing_open_read_only_maps_without_write_access() {
+    use std::os::unix::fs::PermissionsExt;
+
+    const REGION_SIZE: usize = 4096;
+    const REGION_COUNT: usize = 5;
+
+    let temp = TempFile::new("read-only");
+    let map_id = {
+        let mut options = FileBackingOptions::new(0xff);
+        options.allocation_policy = AllocationPolicy::FallbackOnUnsupported;
+        let mut backing = FileBacking::<REGION_SIZE, REGION_COUNT>::create_new(
+            &temp.path,
+            options,
+            crate::test_file_backing_scratch(),
+        )
+        .unwrap();
+        let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT, 8>::format(
+            &mut backing,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut map =
+            LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+        map.set(&mut storage, 7, 70).unwrap();
+        map.collection_id()
+    };
+    std::fs::set_permissions(&temp.path, std::fs::Permissions::from_mode(0o444)).unwrap();
+    let before = std::fs::read(&temp.path).unwrap();
+
+    let mut backing = FileBacking::<REGION_SIZE, REGION_COUNT>::open_read_only(
+        &temp.path,
+        FileBackingOptions::new(0xff),
+        crate::test_file_backing_scratch(),
+    )
+    .unwrap();
+    assert!(backing.is_read_only());
+    let metadata = backing.read_metadata().unwrap().unwrap();
+    assert_eq!(
+        backing.write_region(0, 0, &[0]),
+        Err(FileBackingError::ReadOnly)
+    );
+    assert_eq!(backing.erase_region(0), Err(FileBackingError::ReadOnly));
+    assert_eq!(
+        backing.write_metadata(metadata),
+        Err(FileBackingError::ReadOnly)
+    );
+    backing.sync().unwrap();
+
+    {
+        let mut flash = crate::ReadOnlyFlash::<_, REGION_SIZE>::new(&mut backing);
+        let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT, 8>::open_read_only(
+            &mut flash,
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut map =
+            LsmMap::<u16, u16, 8>::open(map_id, &mut storage, crate::test_lsm_map_memory())
+                .unwrap();
+        assert_eq!(
+            map.get(&mut storage, &7, |_, value| *value).unwrap(),
+            Some(70)
+        );
+    }
+    assert_eq!(std::fs::read(&temp.path).unwrap(), before);
+}
@@ -12,11 +12,29 @@ pub enum StorageIoError {
     /// The Linux file-backed mmap backend failed.
     #[cfg(all(feature = "file-backing", target_os = "linux"))]
     FileBacking(crate::file_backing::FileBackingError),
+    /// The backend permanently failed to program or erase a region.
+    ///
+    /// Backends return this only for failures that retrying cannot fix, such
+    /// as worn-out silicon. Borromean retires the region and keeps it out of
+    /// the free-space rotation.
+    BadRegion(u32),
+    /// The [`ReadOnlyFlash`](crate::ReadOnlyFlash) wrapper rejected the
+    /// access.
+    ReadOnly(crate::read_only::ReadOnlyFlashError),
 }
 
 impl From<MockError> for StorageIoError {
     fn from(error: MockError) -> Self {
-        Self::Mock(error)
+        match error {
+            MockError::BadRegion(region_index) => Self::BadRegion(region_index),
+            error => Self::Mock(error),
+        }
+    }
+}
+
+impl From<crate::read_only::ReadOnlyFlashError> for StorageIoError {
+    fn from(error: crate::read_only::ReadOnlyFlashError) -> Self {
+        Self::ReadOnly(error)
     }
 }
 
@@ -45,6 +63,9 @@ pub enum StorageFormatError {
     /// The Linux file-backed mmap backend failed during formatting.
     #[cfg(all(feature = "file-backing", target_os = "linux"))]
     FileBacking(crate::file_backing::FileBackingFormatError),
+    /// [`ReadOnlyFlash`](crate::ReadOnlyFlash) never formats the wrapped
+    /// device.
+    ReadOnly,
 }
 
 impl From<MockFormatError> for StorageFormatError {
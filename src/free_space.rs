@@ -1,8 +1,9 @@
 use heapless::Vec;
 
-use crate::disk::FreeQueuePosition;
+use crate::disk::{FreeQueuePosition, FREE_SPACE_WEAR_RETIRED_FLAG};
 
 pub(crate) const MAX_FREE_QUEUE_ENTRIES: usize = 4096;
+pub(crate) const MAX_WEAR_TABLE_REGIONS: usize = 64;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum FreeSpaceError {
@@ -35,6 +36,9 @@ pub(crate) struct FreeSpaceState {
     ready_boundary: u32,
     append_tail: u32,
     queue: Vec<u32, MAX_FREE_QUEUE_ENTRIES>,
+    wear_regions: Vec<u32, MAX_WEAR_TABLE_REGIONS>,
+    erase_counts: Vec<u32, MAX_FREE_QUEUE_ENTRIES>,
+    erased_through: u32,
 }
 
 impl FreeSpaceState {
@@ -47,6 +51,9 @@ impl FreeSpaceState {
             ready_boundary: 0,
             append_tail: 0,
             queue: Vec::new(),
+            wear_regions: Vec::new(),
+            erase_counts: Vec::new(),
+            erased_through: 0,
         }
     }
 
@@ -101,6 +108,7 @@ impl FreeSpaceState {
         self.allocation_head = allocation_head;
         self.ready_boundary = ready_boundary;
         self.append_tail = append_tail;
+        self.erased_through = ready_boundary;
         self.queue.clear();
         for entry in entries.iter().copied() {
             self.queue
@@ -200,7 +208,7 @@ impl FreeSpaceState {
 
     pub(crate) fn position_after_allocation(&self) -> Result<FreeQueuePosition, FreeSpaceError> {
         let next = self
-            .allocation_head
+            .next_allocation_index()
             .checked_add(1)
             .ok_or(FreeSpaceError::InvalidCursor)?;
         Ok(self.position(next))
@@ -226,7 +234,12 @@ impl FreeSpaceState {
     }
 
     pub(crate) fn ready_count(&self) -> u32 {
-        self.ready_boundary.saturating_sub(self.allocation_head)
+        let retired = (self.allocation_head..self.ready_boundary)
+            .filter(|entry_index| self.entry_retired(*entry_index))
+            .count();
+        self.ready_boundary
+            .saturating_sub(self.allocation_head)
+            .saturating_sub(u32::try_from(retired).unwrap_or(u32::MAX))
     }
 
     pub(crate) fn dirty_count(&self) -> u32 {
@@ -234,41 +247,106 @@ impl FreeSpaceState {
     }
 
     pub(crate) fn next_ready_region(&self) -> Result<u32, FreeSpaceError> {
-        if self.allocation_head >= self.ready_boundary {
+        let entry_index = self.next_allocation_index();
+        if entry_index >= self.ready_boundary {
             return Err(FreeSpaceError::ReadyRangeEmpty);
         }
         self.queue
-            .get(usize::try_from(self.allocation_head).map_err(|_| FreeSpaceError::InvalidCursor)?)
+            .get(usize::try_from(entry_index).map_err(|_| FreeSpaceError::InvalidCursor)?)
             .copied()
             .ok_or(FreeSpaceError::InvalidCursor)
     }
 
+    /// Pops `region_index`, stepping over retired entries in front of it.
+    ///
+    /// The region itself may be retired: a transaction can allocate a region
+    /// that a later `retire_region` record retires before the transaction's
+    /// allocations replay at commit.
     pub(crate) fn apply_allocate(
         &mut self,
         region_index: u32,
         allocation_head_after: FreeQueuePosition,
     ) -> Result<(), FreeSpaceError> {
-        let expected_region = self.next_ready_region()?;
-        if expected_region != region_index {
-            return Err(FreeSpaceError::RegionMismatch {
-                expected: expected_region,
-                actual: region_index,
-            });
+        let mut entry_index = self.allocation_head;
+        loop {
+            if entry_index >= self.ready_boundary {
+                return Err(FreeSpaceError::ReadyRangeEmpty);
+            }
+            let entry = self.entry(entry_index)?;
+            if entry == region_index {
+                break;
+            }
+            if !self.is_retired(entry) {
+                return Err(FreeSpaceError::RegionMismatch {
+                    expected: entry,
+                    actual: region_index,
+                });
+            }
+            entry_index = entry_index
+                .checked_add(1)
+                .ok_or(FreeSpaceError::InvalidCursor)?;
         }
-        let expected_position = self.position_after_allocation()?;
+        let next = entry_index
+            .checked_add(1)
+            .ok_or(FreeSpaceError::InvalidCursor)?;
+        let expected_position = self.position(next);
         if allocation_head_after != expected_position {
             return Err(FreeSpaceError::InvalidPosition {
                 expected: expected_position,
                 actual: allocation_head_after,
             });
         }
-        self.allocation_head = self
-            .allocation_head
-            .checked_add(1)
+        self.allocation_head = next;
+        Ok(())
+    }
+
+    /// Marks `region_index` as permanently out of rotation.
+    pub(crate) fn retire_region(&mut self, region_index: u32) -> Result<(), FreeSpaceError> {
+        let slot = usize::try_from(region_index)
+            .ok()
+            .and_then(|region_index| self.erase_counts.get_mut(region_index))
             .ok_or(FreeSpaceError::InvalidCursor)?;
+        *slot |= FREE_SPACE_WEAR_RETIRED_FLAG;
         Ok(())
     }
 
+    /// Copies the retirements recorded in `other`; they stay durable when
+    /// the allocator state around them is rolled back.
+    pub(crate) fn keep_retirements_from(&mut self, other: &Self) {
+        for (entry, current) in self.erase_counts.iter_mut().zip(other.erase_counts.iter()) {
+            *entry |= current & FREE_SPACE_WEAR_RETIRED_FLAG;
+        }
+    }
+
+    pub(crate) fn is_retired(&self, region_index: u32) -> bool {
+        usize::try_from(region_index)
+            .ok()
+            .and_then(|region_index| self.erase_counts.get(region_index))
+            .is_some_and(|entry| entry & FREE_SPACE_WEAR_RETIRED_FLAG != 0)
+    }
+
+    /// Returns the queue index the next allocation pops, skipping retired
+    /// entries; the allocation head itself when no entry qualifies.
+    fn next_allocation_index(&self) -> u32 {
+        let end = u32::try_from(self.queue.len()).unwrap_or(u32::MAX);
+        (self.allocation_head..end)
+            .find(|entry_index| !self.entry_retired(*entry_index))
+            .unwrap_or(self.allocation_head)
+    }
+
+    fn entry(&self, entry_index: u32) -> Result<u32, FreeSpaceError> {
+        usize::try_from(entry_index)
+            .ok()
+            .and_then(|entry_index| self.queue.get(entry_index))
+            .copied()
+            .ok_or(FreeSpaceError::InvalidCursor)
+    }
+
+    fn entry_retired(&self, entry_index: u32) -> bool {
+        self.entry(entry_index)
+            .is_ok_and(|region_index| self.is_retired(region_index))
+    }
+
     pub(crate) fn apply_free(
         &mut self,
         region_index: u32,
@@ -309,13 +387,127 @@ impl FreeSpaceState {
                 actual: ready_boundary_after,
             });
         }
+        let start = self.ready_boundary;
         self.ready_boundary = self
             .ready_boundary
             .checked_add(count)
             .ok_or(FreeSpaceError::InvalidCursor)?;
+        self.count_erased_entries(start, self.ready_boundary);
         Ok(())
     }
 
+    /// Counts an `erase_free_region_span` record that replay skipped because
+    /// the materialized cursors already include it.
+    pub(crate) fn note_replayed_erase(
+        &mut self,
+        count: u32,
+        ready_boundary_after: FreeQueuePosition,
+    ) {
+        let Ok(end) = Self::index_for_position(
+            self.metadata_regions.as_slice(),
+            self.entries_per_region,
+            ready_boundary_after,
+        ) else {
+            return;
+        };
+        let end = end.min(self.ready_boundary);
+        self.count_erased_entries(end.saturating_sub(count), end);
+    }
+
+    /// Resets every erase count to zero for a store of `region_count`
+    /// regions.
+    pub(crate) fn reset_erase_counts(&mut self, region_count: u32) -> Result<(), FreeSpaceError> {
+        self.erase_counts.clear();
+        for _ in 0..region_count {
+            self.erase_counts
+                .push(0)
+                .map_err(|_| FreeSpaceError::QueueOverflow)?;
+        }
+        self.erased_through = 0;
+        Ok(())
+    }
+
+    /// Restores the free-queue position a loaded wear table counts through.
+    pub(crate) fn set_erased_through(
+        &mut self,
+        erased_through: FreeQueuePosition,
+    ) -> Result<(), FreeSpaceError> {
+        let erased_through = Self::index_for_position(
+            self.metadata_regions.as_slice(),
+            self.entries_per_region,
+            erased_through,
+        )?;
+        if erased_through > self.ready_boundary {
+            return Err(FreeSpaceError::InvalidCursor);
+        }
+        self.erased_through = erased_through;
+        Ok(())
+    }
+
+    pub(crate) fn set_erase_count(
+        &mut self,
+        region_index: u32,
+        count: u32,
+    ) -> Result<(), FreeSpaceError> {
+        let slot = usize::try_from(region_index)
+            .ok()
+            .and_then(|region_index| self.erase_counts.get_mut(region_index))
+            .ok_or(FreeSpaceError::InvalidCursor)?;
+        *slot = count;
+        Ok(())
+    }
+
+    /// Returns the wear-table entries: erase counts tagged with
+    /// [`FREE_SPACE_WEAR_RETIRED_FLAG`] for retired regions.
+    pub(crate) fn wear_entries(&self) -> &[u32] {
+        self.erase_counts.as_slice()
+    }
+
+    pub(crate) fn erase_count(&self, region_index: u32) -> u32 {
+        usize::try_from(region_index)
+            .ok()
+            .and_then(|region_index| self.erase_counts.get(region_index))
+            .map_or(0, |entry| entry & !FREE_SPACE_WEAR_RETIRED_FLAG)
+    }
+
+    pub(crate) fn erased_through_position(&self) -> FreeQueuePosition {
+        self.position(self.erased_through)
+    }
+
+    pub(crate) fn wear_regions(&self) -> &[u32] {
+        self.wear_regions.as_slice()
+    }
+
+    pub(crate) fn push_wear_region(&mut self, region_index: u32) -> Result<(), FreeSpaceError> {
+        self.wear_regions
+            .push(region_index)
+            .map_err(|_| FreeSpaceError::QueueOverflow)
+    }
+
+    fn count_erased_entries(&mut self, start: u32, end: u32) {
+        let start = start.max(self.erased_through);
+        if start >= end {
+            return;
+        }
+        for entry_index in start..end {
+            let Some(region_index) = usize::try_from(entry_index)
+                .ok()
+                .and_then(|entry_index| self.queue.get(entry_index))
+            else {
+                break;
+            };
+            if let Some(entry) = usize::try_from(*region_index)
+                .ok()
+                .and_then(|region_index| self.erase_counts.get_mut(region_index))
+            {
+                if *entry & FREE_SPACE_WEAR_RETIRED_FLAG == 0 {
+                    *entry = entry.saturating_add(1).min(!FREE_SPACE_WEAR_RETIRED_FLAG);
+                }
+            }
+        }
+        self.erased_through = end;
+    }
+
     pub(crate) fn entries(&self) -> &[u32] {
         self.queue.as_slice()
     }
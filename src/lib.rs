@@ -100,6 +100,137 @@ pub(crate) fn test_storage_memory<
     std::boxed::Box::leak(std::boxed::Box::new(StorageMemory::new()))
 }
 
+#[cfg(test)]
+pub(crate) fn test_format_storage<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_LOG: usize,
+>(
+    backing: &mut MockFlash<REGION_SIZE, REGION_COUNT, MAX_LOG>,
+) -> Storage<'_, 'static, MockFlash<REGION_SIZE, REGION_COUNT, MAX_LOG>, REGION_SIZE, REGION_COUNT>
+{
+    Storage::format(
+        backing,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        test_storage_memory(),
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+pub(crate) fn test_plugin_storage_memory<const REGION_SIZE: usize, const REGION_COUNT: usize>(
+    plugins: &'static [&'static dyn CollectionTypePlugin],
+) -> &'static mut StorageMemory<REGION_SIZE, REGION_COUNT> {
+    let memory = test_storage_memory();
+    memory.register_collection_types(CollectionTypeRegistry::new(plugins).unwrap());
+    memory
+}
+
+#[cfg(test)]
+pub(crate) fn test_format_plugin_storage<
+    'db,
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_LOG: usize,
+>(
+    backing: &'db mut MockFlash<REGION_SIZE, REGION_COUNT, MAX_LOG>,
+    plugins: &'static [&'static dyn CollectionTypePlugin],
+) -> Storage<'db, 'static, MockFlash<REGION_SIZE, REGION_COUNT, MAX_LOG>, REGION_SIZE, REGION_COUNT>
+{
+    Storage::format(
+        backing,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        test_plugin_storage_memory(plugins),
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+pub(crate) fn test_reopen_storage<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_LOG: usize,
+>(
+    backing: &mut MockFlash<REGION_SIZE, REGION_COUNT, MAX_LOG>,
+) -> Storage<'_, 'static, MockFlash<REGION_SIZE, REGION_COUNT, MAX_LOG>, REGION_SIZE, REGION_COUNT>
+{
+    Storage::open(backing, test_storage_memory()).unwrap()
+}
+
+/// Creates a map and flushes two batches of keys into separate runs.
+#[cfg(test)]
+pub(crate) fn test_flushed_map<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_LOG: usize,
+>(
+    storage: &mut Storage<
+        '_,
+        'static,
+        MockFlash<REGION_SIZE, REGION_COUNT, MAX_LOG>,
+        REGION_SIZE,
+        REGION_COUNT,
+    >,
+) -> CollectionId {
+    let mut map = LsmMap::<u16, u16, 8>::new(storage, test_lsm_map_memory()).unwrap();
+    let collection_id = map.collection_id();
+    for batch in 0..2u16 {
+        for key in batch * 20..batch * 20 + 20 {
+            map.set(storage, key, key + 1000).unwrap();
+        }
+        let mut buffer = [0u8; REGION_SIZE];
+        let mut frontier = storage
+            .open_map::<u16, u16, 8>(collection_id, &mut buffer, test_map_frontier_memory())
+            .unwrap();
+        storage.flush_map(&mut frontier).unwrap();
+    }
+    collection_id
+}
+
+/// Returns the head region replay recorded for `collection_id`.
+#[cfg(test)]
+pub(crate) fn test_head_region<IO: FlashIo, const REGION_SIZE: usize, const REGION_COUNT: usize>(
+    storage: &Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT>,
+    collection_id: CollectionId,
+) -> u32 {
+    let basis = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .map(|collection| collection.basis())
+        .unwrap();
+    let StartupCollectionBasis::Region(head_region) = basis else {
+        panic!("collection has no region basis");
+    };
+    head_region
+}
+
+/// Flips the low bit of one byte of a region, bypassing program rules.
+#[cfg(test)]
+pub(crate) fn test_flip_byte<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    const MAX_LOG: usize,
+>(
+    storage: &mut Storage<
+        '_,
+        'static,
+        MockFlash<REGION_SIZE, REGION_COUNT, MAX_LOG>,
+        REGION_SIZE,
+        REGION_COUNT,
+    >,
+    region_index: u32,
+    offset: usize,
+) {
+    storage
+        .with_io_workspace(|flash, _| {
+            let mut bytes = *flash.region_bytes(region_index).unwrap();
+            bytes[offset] ^= 0x01;
+            flash.write_region(region_index, 0, &bytes)
+        })
+        .unwrap();
+}
+
 #[cfg(test)]
 pub(crate) fn test_lsm_map_memory<K, V, const MAX_RUNS: usize>(
 ) -> &'static mut LsmMapMemory<K, V, MAX_RUNS>
@@ -222,6 +353,46 @@ pub use storage::*;
 
 pub(crate) mod transaction_log;
 
+/// Plugin hooks for collection types defined outside Borromean.
+pub mod collection_type;
+pub use collection_type::*;
+
+/// Named collection catalog kept by storage.
+pub mod catalog;
+pub use catalog::*;
+
+/// Space accounting snapshots returned by [`Storage::usage_report`].
+pub mod usage;
+pub use usage::*;
+
+/// Flash wear telemetry returned by [`Storage::wear_report`].
+pub mod wear;
+pub use wear::*;
+
+/// In-place rewrites of retired on-disk formats.
+pub mod migration;
+pub use migration::*;
+
+/// Whole-store consistency checks run by [`Storage::check`].
+pub mod check;
+pub use check::*;
+
+/// Incremental background scrub run by [`Storage::scrub`].
+pub mod scrub;
+pub use scrub::*;
+
+/// Per-collection quarantine used by [`Storage::open_salvage`].
+pub mod salvage;
+pub use salvage::*;
+
+/// RAM-overlay backing used by [`Storage::open_read_only`].
+pub mod read_only;
+pub use read_only::*;
+
+/// Recovery report returned by [`Storage::open_with_report`].
+pub mod recovery;
+pub use recovery::*;
+
 /// Advanced reference types for WAL record encoding and decoding.
 pub mod wal_record;
 pub use wal_record::*;
@@ -245,9 +416,12 @@ pub use vec_like::*;
 
 use core::fmt::Debug;
 use core::future::Future;
+use core::ops::RangeBounds;
 use heapless::Vec;
 use serde::{Deserialize, Serialize};
 
+use crate::recovery::ReadCounter;
+
 #[cfg(feature = "perf-counters")]
 use crate::perf_metrics::{StoragePerfCounter, StoragePerfTimer, StoragePerfTimerGuard};
 
@@ -284,23 +458,51 @@ pub enum CollectionType {
     Free, // Used for free regions
     /// Write-ahead-log collection type.
     Wal, // Write-ahead log
-    /// Experimental channel collection type.
-    Channel, // FIFO queue
+    /// Durable channel collection type.
+    Channel, // Replicated command log
     /// Durable map collection type.
     Map, // Key-value store
     /// WAL-backed opaque object log collection type.
     ObjectLog, // Opaque object log
+    /// Durable FIFO queue collection type.
+    Queue, // FIFO queue
+    /// Fixed-capacity ring log collection type.
+    RingLog, // Overwriting ring buffer
+    /// Compressed time-series collection type.
+    TimeSeries, // Delta-encoded samples
+    /// Single-value cell collection type.
+    Cell, // One snapshot payload
+    /// Monotonic ID sequence collection type.
+    Sequence, // Reserved ID blocks
+    /// Storage-private named collection catalog type.
+    Catalog, // Name to collection map
+    /// Durable ordered set collection type.
+    Set, // Key-only LSM runs
 }
 
 impl CollectionType {
     /// Stable on-disk code reserved for WAL collections.
     pub const WAL_CODE: u16 = 0;
-    /// Stable on-disk code reserved for the experimental channel type.
+    /// Stable on-disk code reserved for durable channel collections.
     pub const CHANNEL_CODE: u16 = 1;
     /// Stable on-disk code reserved for durable map collections.
     pub const MAP_CODE: u16 = 2;
     /// Stable on-disk code reserved for durable object-log collections.
     pub const OBJECT_LOG_CODE: u16 = 3;
+    /// Stable on-disk code reserved for durable FIFO queue collections.
+    pub const QUEUE_CODE: u16 = 4;
+    /// Stable on-disk code reserved for fixed-capacity ring log collections.
+    pub const RING_LOG_CODE: u16 = 5;
+    /// Stable on-disk code reserved for compressed time-series collections.
+    pub const TIME_SERIES_CODE: u16 = 6;
+    /// Stable on-disk code reserved for single-value cell collections.
+    pub const CELL_CODE: u16 = 7;
+    /// Stable on-disk code reserved for monotonic ID sequence collections.
+    pub const SEQUENCE_CODE: u16 = 8;
+    /// Stable on-disk code reserved for the storage-private collection catalog.
+    pub const CATALOG_CODE: u16 = 9;
+    /// Stable on-disk code reserved for durable ordered set collections.
+    pub const SET_CODE: u16 = 10;
 
     /// Returns the stable on-disk code for durable collection kinds.
     pub fn stable_code(self) -> Option<u16> {
@@ -309,6 +511,13 @@ impl CollectionType {
             Self::Channel => Some(Self::CHANNEL_CODE),
             Self::Map => Some(Self::MAP_CODE),
             Self::ObjectLog => Some(Self::OBJECT_LOG_CODE),
+            Self::Queue => Some(Self::QUEUE_CODE),
+            Self::RingLog => Some(Self::RING_LOG_CODE),
+            Self::TimeSeries => Some(Self::TIME_SERIES_CODE),
+            Self::Cell => Some(Self::CELL_CODE),
+            Self::Sequence => Some(Self::SEQUENCE_CODE),
+            Self::Catalog => Some(Self::CATALOG_CODE),
+            Self::Set => Some(Self::SET_CODE),
             Self::Uninitialized | Self::Free => None,
         }
     }
@@ -403,6 +612,7 @@ pub struct StorageMemory<
     #[cfg(feature = "perf-counters")]
     pub(crate) perf_metrics: StoragePerfMetrics,
     pub(crate) mode: StorageMode,
+    pub(crate) read_only: bool,
 }
 
 impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_COLLECTIONS: usize>
@@ -429,8 +639,21 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_COLLECTIONS:
             #[cfg(feature = "perf-counters")]
             perf_metrics: StoragePerfMetrics::default(),
             mode: StorageMode::Idle,
+            read_only: false,
         }
     }
+
+    /// Registers downstream collection types accepted by storage opened or
+    /// formatted with this memory.
+    pub fn register_collection_types(&mut self, collection_types: CollectionTypeRegistry) {
+        self.state.set_collection_types(collection_types);
+        self.open_plan.set_collection_types(collection_types);
+    }
+
+    /// Returns the registered downstream collection types.
+    pub fn collection_types(&self) -> CollectionTypeRegistry {
+        self.state.collection_types()
+    }
 }
 
 impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_COLLECTIONS: usize> Default
@@ -644,14 +867,19 @@ where
     }
 }
 
-/// Errors returned while opening storage through [`Storage::open`] or
-/// [`Storage::open_future`].
+/// Errors returned while opening storage through [`Storage::open`],
+/// [`Storage::open_salvage`], or [`Storage::open_future`].
 #[derive(Debug)]
 pub enum StorageOpenError {
     /// The shared storage runtime rejected the open path.
     Runtime(StorageRuntimeError),
     /// Replay discovered a live collection type that this build does not support.
     UnsupportedLiveCollectionType(u16),
+    /// A registered collection type plugin rejected a retained WAL record.
+    CollectionReplayRejected {
+        collection_id: CollectionId,
+        collection_type: u16,
+    },
     /// Map-specific validation failed while opening live map collections.
     Map(MapStorageError),
 }
@@ -778,7 +1006,11 @@ where
     if collection.basis() == StartupCollectionBasis::Dropped {
         return Err(MapStorageError::DroppedCollection(collection_id));
     }
-    if collection.collection_type() != Some(CollectionType::MAP_CODE) {
+    state.ensure_not_quarantined(collection_id)?;
+    if !collection
+        .collection_type()
+        .is_some_and(is_lsm_collection_type)
+    {
         return Err(MapStorageError::CollectionTypeMismatch {
             collection_id,
             expected: CollectionType::MAP_CODE,
@@ -1054,7 +1286,8 @@ where
         collection_id,
         collection_scratch,
         retained_runs,
-    )?;
+    )?
+    .with_collection_type(opened.collection_type());
     if let Some(run) = replacement_run {
         replacement.push_retained_run(run)?;
     }
@@ -1140,6 +1373,33 @@ impl<
         backing: &'db mut IO,
         memory: &'mem mut StorageMemory<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
     ) -> Result<Self, StorageOpenError> {
+        Self::open_with_report(backing, memory).map(|(storage, _)| storage)
+    }
+
+    /// Opens an already formatted store like [`Self::open`] and reports the
+    /// recovery work replay did.
+    ///
+    /// The [`RecoveryReport`] lists the WAL records replayed, torn tail bytes
+    /// discarded, transactions rolled back or finished, regions whose reclaim
+    /// was resumed, and the region bytes read during the open.
+    pub fn open_with_report(
+        backing: &'db mut IO,
+        memory: &'mem mut StorageMemory<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(Self, RecoveryReport<REGION_COUNT>), StorageOpenError> {
+        let mut counter = ReadCounter::new(backing);
+        Storage::<ReadCounter<'_, IO>, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>::open_inner(
+            &mut counter,
+            &mut *memory,
+        )?;
+        let mut report = memory.open_plan.take_recovery_report();
+        report.bytes_read = counter.bytes_read();
+        Ok((Self { backing, memory }, report))
+    }
+
+    fn open_inner(
+        backing: &'db mut IO,
+        memory: &'mem mut StorageMemory<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<(), StorageOpenError> {
         storage::reopen_without_reclaim_recovery_into::<
             REGION_SIZE,
             REGION_COUNT,
@@ -1151,11 +1411,65 @@ impl<
             &mut memory.state,
             &mut memory.open_plan,
         )?;
-        let storage = Self::from_initialized_memory(backing, memory)?;
+        let mut storage = Self::from_initialized_memory(backing, memory)?;
+        storage.validate_live_collections()?;
+        storage.replay_plugin_collections()?;
+        Ok(())
+    }
+
+    /// Opens an already formatted store, quarantining each live collection
+    /// whose durable state cannot be recovered instead of failing the open.
+    ///
+    /// A collection is quarantined when its type is unsupported, its head
+    /// region is corrupt or foreign, a plugin rejects its replay, or a region
+    /// reachable from its head is corrupt. Every other collection opens
+    /// normally. Damage to storage metadata, the WAL chain, or the free-space
+    /// queue still fails the open. [`Self::quarantined_collections`] lists
+    /// what was lost; [`Self::drop_quarantined`] reclaims it. Until then,
+    /// [`Self::open`] keeps failing on the same store.
+    pub fn open_salvage(
+        backing: &'db mut IO,
+        memory: &'mem mut StorageMemory<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Self, StorageOpenError> {
+        storage::salvage_reopen_into::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+            backing,
+            &mut memory.workspace,
+            &mut memory.state,
+            &mut memory.open_plan,
+        )?;
+        let mut storage = Self::from_initialized_memory(backing, memory)?;
         storage.validate_live_collections()?;
+        storage.replay_plugin_collections_with(true)?;
+        salvage::quarantine_invalid_regions::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+            &mut storage.memory.state,
+            storage.backing,
+            &mut storage.memory.workspace,
+            &mut storage.memory.wal_chain_scratch,
+        )?;
         Ok(storage)
     }
 
+    /// Rebuilds lost storage metadata from region contents, then opens the
+    /// store.
+    ///
+    /// Use this only after [`Self::open`] fails because neither metadata copy
+    /// is readable. Region size and count come from the const geometry; the
+    /// erased byte, WAL record magic, and WAL write granule are derived from
+    /// the media. `min_free_regions` is not recorded in any region, so it
+    /// must be the value the store was formatted with.
+    pub fn recover_metadata(
+        backing: &'db mut IO,
+        min_free_regions: u32,
+        memory: &'mem mut StorageMemory<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Self, StorageOpenError> {
+        startup::rebuild_metadata_from_headers::<REGION_SIZE, REGION_COUNT, IO>(
+            backing,
+            &mut memory.workspace,
+            min_free_regions,
+        )?;
+        Self::open(backing, memory)
+    }
+
     /// Consumes the storage context and returns its bound backing object.
     pub fn into_backing(self) -> &'db mut IO {
         self.backing
@@ -1166,6 +1480,12 @@ impl<
         self.memory.mode
     }
 
+    /// Returns whether this storage was opened with [`Storage::open_read_only`]
+    /// and rejects every operation that would write.
+    pub fn is_read_only(&self) -> bool {
+        self.memory.read_only
+    }
+
     /// Returns current performance metrics for this storage context.
     #[cfg(feature = "perf-counters")]
     pub fn perf_metrics(&self) -> StoragePerfMetrics {
@@ -1251,7 +1571,7 @@ impl<
         }
     }
 
-    fn invalidate_map_frontier_buffer(&mut self, collection_id: CollectionId) {
+    pub(crate) fn invalidate_map_frontier_buffer(&mut self, collection_id: CollectionId) {
         if let FrontierBufferOwner::Map {
             collection_id: active,
             generation,
@@ -1347,6 +1667,9 @@ impl<
                 actual: self.memory.mode,
             });
         }
+        if self.memory.read_only && !next.is_read_only() {
+            return Err(StorageRuntimeError::ReadOnly);
+        }
         self.memory.mode = next;
         Ok(())
     }
@@ -1448,6 +1771,11 @@ impl<
         self.memory.state.free_space_entries()
     }
 
+    #[cfg(test)]
+    pub(crate) fn free_space_wear_regions(&self) -> &[u32] {
+        self.memory.state.free_space_wear_regions()
+    }
+
     /// Returns a region reserved for WAL rotation but not yet linked.
     pub fn ready_region(&self) -> Option<u32> {
         self.memory.state.ready_region()
@@ -1463,6 +1791,222 @@ impl<
         self.memory.state.collections()
     }
 
+    /// Returns per-region erase count statistics from the free-space
+    /// collection.
+    pub fn wear_report(&self) -> WearReport {
+        self.memory.state.wear_report()
+    }
+
+    /// Returns whether the region was retired after a permanent program or
+    /// erase failure.
+    pub fn is_region_retired(&self, region_index: u32) -> bool {
+        self.memory.state.is_region_retired(region_index)
+    }
+
+    /// Returns how the store's regions and retained WAL bytes divide between
+    /// collections, the WAL, and free space.
+    pub fn usage_report(
+        &mut self,
+    ) -> Result<StorageUsageReport<MAX_COLLECTIONS>, StorageRuntimeError> {
+        self.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self
+            .memory
+            .state
+            .usage_report::<REGION_SIZE, REGION_COUNT, IO>(
+                self.backing,
+                &mut self.memory.workspace,
+                &mut self.memory.wal_chain_scratch,
+            );
+        self.finish_mode();
+        result
+    }
+
+    /// Walks the metadata, the WAL chain, the free-space queue, and every
+    /// live collection's committed regions, and reports each inconsistency
+    /// to `sink`.
+    ///
+    /// The check never writes to flash. I/O failures end the pass with an
+    /// error; everything else becomes a [`CheckFinding`].
+    pub fn check<S: CheckReportSink + ?Sized>(
+        &mut self,
+        sink: &mut S,
+    ) -> Result<CheckSummary, StorageRuntimeError> {
+        self.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = check::check_storage::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS, S>(
+            &self.memory.state,
+            self.backing,
+            &mut self.memory.workspace,
+            &mut self.memory.wal_chain_scratch,
+            &mut self.memory.reclaim_source_regions,
+            sink,
+        );
+        self.finish_mode();
+        result
+    }
+
+    /// Checks a store that [`Self::open`] rejects, one region at a time.
+    ///
+    /// Reports unreadable metadata or metadata for another geometry, every
+    /// programmed region whose header does not decode, and damaged records
+    /// inside each main-WAL region. Without an open there is no WAL chain,
+    /// free-space queue, or live collection state, so nothing else is
+    /// checked. The pass never writes to flash.
+    pub fn check_regions<S: CheckReportSink + ?Sized>(
+        backing: &mut IO,
+        memory: &mut StorageMemory<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        sink: &mut S,
+    ) -> Result<CheckSummary, StorageRuntimeError> {
+        check::check_regions::<REGION_SIZE, REGION_COUNT, IO, S>(
+            backing,
+            &mut memory.workspace,
+            sink,
+        )
+    }
+
+    /// Reads at most `max_regions` live regions starting at the scrub cursor
+    /// and verifies every checksum their format carries, reporting each
+    /// damaged region and its owning collection to `sink`.
+    ///
+    /// Queued and retired regions are skipped without counting against
+    /// `max_regions`. The cursor advances past every region examined; a call
+    /// stops once it wraps to the start of the region space and counts the
+    /// completed pass. The new cursor is appended to the WAL as a
+    /// `scrub_progress` record, so [`Self::open`] resumes the pass where the
+    /// last call stopped. Read-only storage keeps the cursor in RAM only.
+    pub fn scrub<S: ScrubReportSink + ?Sized>(
+        &mut self,
+        max_regions: u32,
+        sink: &mut S,
+    ) -> Result<ScrubReport, StorageRuntimeError> {
+        self.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let mut cursor = self.memory.state.scrub_cursor();
+        let result = scrub::scrub_storage::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS, S>(
+            &self.memory.state,
+            self.backing,
+            &mut self.memory.workspace,
+            &mut self.memory.wal_chain_scratch,
+            &mut cursor,
+            max_regions,
+            sink,
+        );
+        self.finish_mode();
+        let report = result?;
+        if self.memory.read_only {
+            self.memory.state.set_scrub_cursor(cursor);
+            return Ok(report);
+        }
+        self.run_storage_operation(StorageMode::AppendingWal(WalAppendMode::Running), |this| {
+            this.memory
+                .state
+                .record_scrub_progress::<REGION_SIZE, REGION_COUNT, IO>(
+                    this.backing,
+                    &mut this.memory.workspace,
+                    cursor,
+                )
+        })?;
+        Ok(report)
+    }
+
+    /// Returns the position the next [`Self::scrub`] call starts from.
+    pub fn scrub_cursor(&self) -> ScrubCursor {
+        self.memory.state.scrub_cursor()
+    }
+
+    /// Returns the collections quarantined by [`Self::open_salvage`].
+    ///
+    /// The list is empty after [`Self::open`].
+    pub fn quarantined_collections(&self) -> &[QuarantinedCollection] {
+        self.memory.state.quarantined_collections()
+    }
+
+    /// Passes the raw image of every region holding data of a quarantined
+    /// collection to `visitor`, in region order.
+    ///
+    /// The head region is always visited; other regions are matched by the
+    /// collection id in their header. Nothing is decoded or verified.
+    pub fn read_quarantined_regions(
+        &mut self,
+        collection_id: CollectionId,
+        visitor: impl FnMut(u32, &[u8]),
+    ) -> Result<(), StorageRuntimeError> {
+        self.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = salvage::visit_quarantined_regions::<REGION_SIZE, IO, MAX_COLLECTIONS>(
+            &self.memory.state,
+            self.backing,
+            collection_id,
+            visitor,
+        );
+        self.finish_mode();
+        result
+    }
+
+    /// Drops a quarantined collection and queues every region holding its
+    /// data for reclaim, returning the number of regions queued.
+    pub fn drop_quarantined(
+        &mut self,
+        collection_id: CollectionId,
+    ) -> Result<u32, StorageRuntimeError> {
+        self.run_storage_operation(
+            StorageMode::DroppingCollection(CollectionDropMode::Running),
+            |this| {
+                salvage::drop_quarantined::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+                    &mut this.memory.state,
+                    this.backing,
+                    &mut this.memory.workspace,
+                    collection_id,
+                )
+            },
+        )
+    }
+
+    /// Runs one static wear-leveling pass within `budget`.
+    ///
+    /// The pass copies the coldest committed regions into fresh regions,
+    /// retargets the owning map's head or collection snapshot at the copies
+    /// through a collection transaction, and frees the old regions into the
+    /// FIFO. Channel, object-log, queue, ring-log, and time-series handles
+    /// replay their collection before their next operation after a pass moves
+    /// one of their regions; a moved channel segment gives its commands new
+    /// addresses. Call it manually or periodically with a small budget.
+    pub fn wear_level(
+        &mut self,
+        budget: WearLevelBudget,
+    ) -> Result<WearLevelReport, StorageRuntimeError> {
+        let result = self.run_storage_operation(
+            StorageMode::CompactingCollection(CollectionCompactionMode::Running),
+            |this| {
+                wear::wear_level::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+                    &mut this.memory.state,
+                    this.backing,
+                    &mut this.memory.workspace,
+                    &mut this.memory.payload_scratch,
+                    &mut this.memory.reclaim_source_regions,
+                    &mut this.memory.active_collections,
+                    &mut this.memory.reclaim_plan,
+                    &mut this.memory.open_plan,
+                    budget,
+                )
+            },
+        );
+        if let FrontierBufferOwner::Map { collection_id, .. } = self.memory.frontier_buffer_owner {
+            if !matches!(result, Ok(report) if report.retargeted_heads == 0) {
+                self.invalidate_map_frontier_buffer(collection_id);
+            }
+        }
+        result
+    }
+
+    /// Lists live collections whose head region still uses a retired
+    /// committed-region format.
+    pub fn migration_plan(
+        &mut self,
+    ) -> Result<MigrationPlan<MAX_COLLECTIONS>, StorageRuntimeError> {
+        self.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = migration::migration_plan(&self.memory.state, self.backing);
+        self.finish_mode();
+        result
+    }
+
     pub(crate) fn allocate_collection_id(&self) -> Result<CollectionId, StorageRuntimeError> {
         let mut next = 1u64;
         for collection in self.collections() {
@@ -1492,7 +2036,9 @@ impl<
 
     pub(crate) fn validate_live_collections(&self) -> Result<(), StorageOpenError> {
         for collection in self.collections() {
-            if collection.basis() == StartupCollectionBasis::Dropped {
+            if collection.basis() == StartupCollectionBasis::Dropped
+                || self.memory.state.is_quarantined(collection.collection_id())
+            {
                 continue;
             }
 
@@ -1501,7 +2047,17 @@ impl<
             };
 
             match collection_type {
-                CollectionType::MAP_CODE | CollectionType::OBJECT_LOG_CODE => {}
+                CollectionType::CHANNEL_CODE
+                | CollectionType::MAP_CODE
+                | CollectionType::OBJECT_LOG_CODE
+                | CollectionType::QUEUE_CODE
+                | CollectionType::RING_LOG_CODE
+                | CollectionType::TIME_SERIES_CODE
+                | CollectionType::CELL_CODE
+                | CollectionType::SEQUENCE_CODE
+                | CollectionType::CATALOG_CODE
+                | CollectionType::SET_CODE => {}
+                other if self.memory.state.collection_types().contains(other) => {}
                 other => return Err(StorageOpenError::UnsupportedLiveCollectionType(other)),
             }
         }
@@ -1509,6 +2065,70 @@ impl<
         Ok(())
     }
 
+    /// Passes every retained WAL record of a live plugin collection to its
+    /// registered replay hook.
+    pub(crate) fn replay_plugin_collections(&mut self) -> Result<(), StorageOpenError> {
+        self.replay_plugin_collections_with(false)
+    }
+
+    /// Replays live plugin collections; with `salvage`, a collection whose
+    /// records the plugin rejects is quarantined instead of failing the open.
+    fn replay_plugin_collections_with(&mut self, salvage: bool) -> Result<(), StorageOpenError> {
+        let collection_types = self.memory.state.collection_types();
+        if collection_types.is_empty() {
+            return Ok(());
+        }
+        let mut rejected = Vec::<StartupCollection, MAX_COLLECTIONS>::new();
+        let state = &self.memory.state;
+        let collections = state.collections();
+        let result = state.visit_wal_records::<REGION_SIZE, IO, StorageOpenError, _>(
+            self.backing,
+            &mut self.memory.workspace,
+            |_flash, record| {
+                let Some(collection_id) = collection_type::replayed_collection_id(record) else {
+                    return Ok(());
+                };
+                let Some(collection) = collections.iter().find(|collection| {
+                    collection.collection_id() == collection_id
+                        && collection.basis() != StartupCollectionBasis::Dropped
+                }) else {
+                    return Ok(());
+                };
+                if state.is_quarantined(collection_id) || rejected.contains(collection) {
+                    return Ok(());
+                }
+                let Some(collection_type) = collection.collection_type() else {
+                    return Ok(());
+                };
+                let Some(plugin) = collection_types.get(collection_type) else {
+                    return Ok(());
+                };
+                match plugin.replay(collection_id, record) {
+                    Ok(()) => Ok(()),
+                    Err(_) if salvage => rejected
+                        .push(*collection)
+                        .map_err(|_| StorageRuntimeError::TooManyTrackedCollections.into()),
+                    Err(_) => Err(StorageOpenError::CollectionReplayRejected {
+                        collection_id,
+                        collection_type,
+                    }),
+                }
+            },
+        );
+        match result {
+            Ok(()) => {}
+            Err(StorageVisitError::Storage(error)) => return Err(error.into()),
+            Err(StorageVisitError::Visitor(error)) => return Err(error),
+        }
+        for collection in rejected {
+            self.memory.state.quarantine(QuarantinedCollection::new(
+                &collection,
+                QuarantineReason::ReplayRejected,
+            ))?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn from_initialized_memory(
         backing: &'db mut IO,
         memory: &'mem mut StorageMemory<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
@@ -1529,6 +2149,7 @@ impl<
             memory.perf_metrics = StoragePerfMetrics::default();
         }
         memory.mode = StorageMode::Idle;
+        memory.read_only = false;
 
         Ok(Self { backing, memory })
     }
@@ -1549,6 +2170,7 @@ impl<
         if collection.basis() == StartupCollectionBasis::Dropped {
             return Err(StorageRuntimeError::DroppedCollection(collection_id));
         }
+        self.memory.state.ensure_not_quarantined(collection_id)?;
         Ok(())
     }
 
@@ -1683,7 +2305,7 @@ impl<
         self.rollback_transaction_inner(collection_id, memory)
     }
 
-    fn clear_dirty_frontier(&mut self, collection_id: CollectionId) {
+    pub(crate) fn clear_dirty_frontier(&mut self, collection_id: CollectionId) {
         if let Some(index) = self
             .memory
             .dirty_frontiers
@@ -1816,6 +2438,27 @@ impl<
         })
     }
 
+    /// Visits every retained WAL record from the WAL head to the tail.
+    ///
+    /// Collection types registered through [`CollectionTypeRegistry`] use this
+    /// to rebuild handle state after open.
+    pub fn visit_wal_records<E, F>(&mut self, mut visitor: F) -> Result<(), StorageVisitError<E>>
+    where
+        F: for<'record> FnMut(WalRecord<'record>) -> Result<(), E>,
+    {
+        self.enter_mode(StorageMode::ReadingStorage(ReadMode::Running))?;
+        let result = self
+            .memory
+            .state
+            .visit_wal_records::<REGION_SIZE, IO, E, _>(
+                self.backing,
+                &mut self.memory.workspace,
+                |_flash, record| visitor(record),
+            );
+        self.finish_mode();
+        result
+    }
+
     /// Appends a `drop_collection` WAL record.
     pub fn append_drop_collection(
         &mut self,
@@ -1840,6 +2483,39 @@ impl<
         )
     }
 
+    /// Drops a collection and begins reclaim for its last region basis,
+    /// returning that region.
+    ///
+    /// A registered plugin collection also frees every region its head
+    /// references through [`CollectionTypePlugin::referenced_regions`], in
+    /// the same collection transaction as the drop.
+    pub fn drop_collection(
+        &mut self,
+        collection_id: CollectionId,
+    ) -> Result<Option<u32>, StorageRuntimeError> {
+        self.run_storage_operation(
+            StorageMode::DroppingCollection(CollectionDropMode::Running),
+            |this| {
+                this.invalidate_map_frontier_buffer(collection_id);
+                this.memory.state.ensure_not_quarantined(collection_id)?;
+                let result = this
+                    .memory
+                    .state
+                    .drop_collection_with_record_and_free_regions::<REGION_SIZE, REGION_COUNT, IO>(
+                        this.backing,
+                        &mut this.memory.workspace,
+                        collection_id,
+                        None,
+                        &mut this.memory.wal_chain_scratch,
+                    );
+                if result.is_ok() {
+                    this.clear_dirty_frontier(collection_id);
+                }
+                result
+            },
+        )
+    }
+
     /// Appends an `allocate_region` WAL record for the ready free-space head.
     pub fn append_allocate_region_for_test(
         &mut self,
@@ -1888,13 +2564,16 @@ impl<
     }
 
     /// Writes and syncs a committed collection region.
+    ///
+    /// Returns the region that holds the payload, which differs from
+    /// `region_index` when the backend reported that region bad.
     pub fn write_committed_region(
         &mut self,
         region_index: u32,
         collection_id: CollectionId,
         collection_format: u16,
         payload: &[u8],
-    ) -> Result<(), StorageRuntimeError> {
+    ) -> Result<u32, StorageRuntimeError> {
         self.run_storage_operation(
             StorageMode::WritingCommittedRegion(CommittedRegionWriteMode::Running),
             |this| {
@@ -2019,6 +2698,11 @@ impl<
         run_once(move || self.create_map(collection_id))
     }
 
+    /// Creates a new durable ordered set collection.
+    pub fn create_set(&mut self, collection_id: CollectionId) -> Result<(), StorageRuntimeError> {
+        self.append_new_collection(collection_id, CollectionType::SET_CODE)
+    }
+
     pub(crate) fn flush_map_inner<K, V, const MAX_RUNS: usize>(
         &mut self,
         map: &mut MapFrontier<'_, K, V, MAX_RUNS>,
@@ -2105,7 +2789,11 @@ impl<
                 if collection.basis() == StartupCollectionBasis::Dropped {
                     return Err(MapStorageError::DroppedCollection(collection_id));
                 }
-                if collection.collection_type() != Some(CollectionType::MAP_CODE) {
+                this.memory.state.ensure_not_quarantined(collection_id)?;
+                if !collection
+                    .collection_type()
+                    .is_some_and(is_lsm_collection_type)
+                {
                     return Err(MapStorageError::CollectionTypeMismatch {
                         collection_id,
                         expected: CollectionType::MAP_CODE,
@@ -2369,7 +3057,11 @@ impl<
                 if collection.basis() == StartupCollectionBasis::Dropped {
                     return Err(MapStorageError::DroppedCollection(collection_id));
                 }
-                if collection.collection_type() != Some(CollectionType::MAP_CODE) {
+                this.memory.state.ensure_not_quarantined(collection_id)?;
+                if !collection
+                    .collection_type()
+                    .is_some_and(is_lsm_collection_type)
+                {
                     return Err(MapStorageError::CollectionTypeMismatch {
                         collection_id,
                         expected: CollectionType::MAP_CODE,
@@ -2402,6 +3094,68 @@ impl<
         run_once(move || self.drop_map(collection_id))
     }
 
+    /// Rewrites a map whose head is a retired `map_region_v2` snapshot
+    /// region into the current manifest and run formats.
+    ///
+    /// The snapshot and any updates retained after it are loaded into
+    /// `buffer`, then written as a frontier run and a manifest whose head
+    /// replaces the old region inside one collection transaction. Returns the
+    /// new manifest region, or `None` when the map already uses current
+    /// formats.
+    pub fn migrate_map<'a, K, V, const MAX_RUNS: usize>(
+        &mut self,
+        collection_id: CollectionId,
+        buffer: &'a mut [u8],
+        memory: &'a mut crate::collections::map::MapFrontierMemory<K, MAX_RUNS>,
+    ) -> Result<Option<u32>, MapStorageError>
+    where
+        K: LsmKey,
+        V: LsmValue,
+    {
+        self.run_map_operation(
+            StorageMode::FlushingCollection(CollectionFlushMode::CommitRegion),
+            |this| {
+                let Some(collection) = this
+                    .collections()
+                    .iter()
+                    .find(|collection| collection.collection_id() == collection_id)
+                    .copied()
+                else {
+                    return Err(MapStorageError::UnknownCollection(collection_id));
+                };
+                if collection.basis() == StartupCollectionBasis::Dropped {
+                    return Err(MapStorageError::DroppedCollection(collection_id));
+                }
+                this.memory.state.ensure_not_quarantined(collection_id)?;
+                if collection.collection_type() != Some(CollectionType::MAP_CODE) {
+                    return Err(MapStorageError::CollectionTypeMismatch {
+                        collection_id,
+                        expected: CollectionType::MAP_CODE,
+                        actual: collection.collection_type(),
+                    });
+                }
+                if migration::pending_migration(this.backing, &collection)?.is_none() {
+                    return Ok(None);
+                }
+                let mut map = MapFrontier::<K, V, MAX_RUNS>::open_legacy_from_storage::<
+                    REGION_SIZE,
+                    REGION_COUNT,
+                    IO,
+                    MAX_COLLECTIONS,
+                >(
+                    &this.memory.state,
+                    this.backing,
+                    &mut this.memory.workspace,
+                    &mut this.memory.collection_scratch,
+                    collection_id,
+                    buffer,
+                    memory,
+                )?;
+                this.flush_map_inner::<K, V, MAX_RUNS>(&mut map).map(Some)
+            },
+        )
+    }
+
     /// Opens a live map collection into a caller-owned frontier buffer.
     pub fn open_map<'a, K, V, const MAX_RUNS: usize>(
         &mut self,
@@ -2457,9 +3211,25 @@ where
     >(
         storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         memory: &'map mut LsmMapMemory<K, V, MAX_RUNS>,
+    ) -> Result<Self, LsmMapError> {
+        Self::new_with_type(storage, memory, CollectionType::MAP_CODE)
+    }
+
+    /// Creates a new LSM collection stored as `collection_type`.
+    pub(crate) fn new_with_type<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'map mut LsmMapMemory<K, V, MAX_RUNS>,
+        collection_type: u16,
     ) -> Result<Self, LsmMapError> {
         let collection_id = storage.allocate_map_collection_id()?;
-        storage.create_map(collection_id)?;
+        storage.append_new_collection(collection_id, collection_type)?;
         Ok(Self::from_collection_id(
             collection_id,
             Self::default_compaction_run_target(),
@@ -2468,6 +3238,9 @@ where
     }
 
     /// Opens and validates an existing durable map collection.
+    ///
+    /// Collections of any other type, including sets, are rejected with
+    /// [`MapStorageError::CollectionTypeMismatch`].
     pub fn open<
         'db,
         'mem,
@@ -2479,12 +3252,30 @@ where
         collection_id: CollectionId,
         storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
         memory: &'map mut LsmMapMemory<K, V, MAX_RUNS>,
+    ) -> Result<Self, LsmMapError> {
+        Self::open_with_type(collection_id, storage, memory, CollectionType::MAP_CODE)
+    }
+
+    /// Opens an existing LSM collection that must be stored as
+    /// `collection_type`.
+    pub(crate) fn open_with_type<
+        'db,
+        'mem,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        collection_id: CollectionId,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        memory: &'map mut LsmMapMemory<K, V, MAX_RUNS>,
+        collection_type: u16,
     ) -> Result<Self, LsmMapError> {
         storage
             .enter_mode(StorageMode::LoadingCollection(CollectionLoadMode::Running))
             .map_err(MapStorageError::from)?;
         let result: Result<(), MapStorageError> = (|| {
-            let _frontier = MapFrontier::<K, V, MAX_RUNS>::open_from_storage::<
+            let frontier = MapFrontier::<K, V, MAX_RUNS>::open_from_storage::<
                 REGION_SIZE,
                 REGION_COUNT,
                 IO,
@@ -2498,6 +3289,13 @@ where
                 &mut storage.memory.open_scratch,
                 &mut memory.frontier,
             )?;
+            if frontier.collection_type() != collection_type {
+                return Err(MapStorageError::CollectionTypeMismatch {
+                    collection_id,
+                    expected: collection_type,
+                    actual: Some(frontier.collection_type()),
+                });
+            }
             Ok(())
         })();
         storage.finish_mode();
@@ -2598,6 +3396,87 @@ where
         }
     }
 
+    /// Visits visible entries whose keys fall in `range` in ascending key
+    /// order, passing each key and value by value.
+    pub fn range<
+        'db,
+        'mem,
+        R,
+        F,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        range: R,
+        mut visitor: F,
+    ) -> Result<(), LsmMapError>
+    where
+        R: RangeBounds<K>,
+        F: FnMut(K, V) -> Result<(), LsmMapError>,
+    {
+        self.range_while(storage, range, |key, value| {
+            visitor(key, value)?;
+            Ok(true)
+        })
+    }
+
+    /// Like [`Self::range`], but stops once `visitor` returns `false`.
+    pub(crate) fn range_while<
+        'db,
+        'mem,
+        R,
+        F,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+    >(
+        &mut self,
+        storage: &mut Storage<'db, 'mem, IO, REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+        range: R,
+        mut visitor: F,
+    ) -> Result<(), LsmMapError>
+    where
+        R: RangeBounds<K>,
+        F: FnMut(K, V) -> Result<bool, LsmMapError>,
+    {
+        storage
+            .enter_mode(StorageMode::ReadingStorage(ReadMode::Running))
+            .map_err(MapStorageError::from)?;
+        let result = (|| {
+            storage
+                .ensure_map_frontier_cached::<K, V, MAX_RUNS>(self.collection_id, self.memory)?;
+            let cached_frontier = self
+                .memory
+                .cached_frontier
+                .take()
+                .ok_or(MapStorageError::UnknownCollection(self.collection_id))?;
+            let buffer_generation = cached_frontier.buffer_generation;
+            let frontier = MapFrontier::<K, V, MAX_RUNS>::from_state(
+                cached_frontier.state,
+                &mut storage.memory.open_scratch,
+                &mut self.memory.frontier,
+            );
+            let result = frontier.visit_range::<REGION_SIZE, IO, R, F>(
+                storage.backing,
+                &mut storage.memory.workspace,
+                &range,
+                &mut self.memory.compaction_cursors,
+                &mut visitor,
+            );
+            self.memory.cached_frontier = Some(crate::collections::map::CachedMapFrontier {
+                buffer_generation,
+                state: frontier.into_state(),
+            });
+            result
+        })();
+        storage.finish_mode();
+        result
+    }
+
     /// Sets `key` to `value` and reports whether compaction is now needed.
     pub fn set<
         'db,
@@ -0,0 +1,124 @@
+//! In-place format migrations.
+//!
+//! Retired committed-region formats stay readable only long enough to be
+//! rewritten. [`Storage::migration_plan`] lists the collection heads that
+//! still use a retired format, and [`Storage::migrate_map`] rewrites one map
+//! through the same collection transaction a flush uses, so a crash leaves
+//! either the old head or the new one.
+//!
+//! [`FORMAT_MIGRATIONS`] is the registry of supported rewrites. Each entry
+//! names the collection type, the retired format, and the format written in
+//! its place.
+//!
+//! [`Storage::migration_plan`]: crate::Storage::migration_plan
+//! [`Storage::migrate_map`]: crate::Storage::migrate_map
+
+use heapless::Vec;
+use serde::Serialize;
+
+use crate::collections::map::{MAP_MANIFEST_V2_FORMAT, MAP_REGION_V2_FORMAT};
+use crate::flash_io::FlashIo;
+use crate::startup::{StartupCollection, StartupCollectionBasis};
+use crate::storage::{StorageRuntime, StorageRuntimeError};
+use crate::{CollectionId, CollectionType, Header};
+
+#[cfg(test)]
+mod tests;
+
+/// One supported rewrite from a retired committed-region format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FormatMigration {
+    /// Collection type whose head region uses `from_format`.
+    pub collection_type: u16,
+    /// Retired committed-region format found in the head region header.
+    pub from_format: u16,
+    /// Committed-region format of the head written in its place.
+    pub to_format: u16,
+}
+
+/// Every retired committed-region format storage can rewrite in place.
+pub const FORMAT_MIGRATIONS: &[FormatMigration] = &[FormatMigration {
+    collection_type: CollectionType::MAP_CODE,
+    from_format: MAP_REGION_V2_FORMAT,
+    to_format: MAP_MANIFEST_V2_FORMAT,
+}];
+
+impl FormatMigration {
+    /// Returns the registered migration for a head region of
+    /// `collection_type` written in `format`, if that format is retired.
+    pub fn find(collection_type: u16, format: u16) -> Option<Self> {
+        FORMAT_MIGRATIONS
+            .iter()
+            .find(|migration| {
+                migration.collection_type == collection_type && migration.from_format == format
+            })
+            .copied()
+    }
+}
+
+/// A live collection whose head region still uses a retired format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PendingMigration {
+    /// Collection that needs rewriting.
+    pub collection_id: CollectionId,
+    /// Head region written in the retired format.
+    pub head_region: u32,
+    /// Registered rewrite for that head.
+    pub migration: FormatMigration,
+}
+
+/// Collections an open store still holds in retired formats.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationPlan<const MAX_COLLECTIONS: usize> {
+    /// Pending rewrites in collection replay order.
+    pub pending: Vec<PendingMigration, MAX_COLLECTIONS>,
+}
+
+impl<const MAX_COLLECTIONS: usize> MigrationPlan<MAX_COLLECTIONS> {
+    /// Returns whether every live collection already uses current formats.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Returns the registered migration for `collection`'s head, if it has one.
+pub(crate) fn pending_migration<IO: FlashIo>(
+    flash: &mut IO,
+    collection: &StartupCollection,
+) -> Result<Option<PendingMigration>, StorageRuntimeError> {
+    let StartupCollectionBasis::Region(head_region) = collection.basis() else {
+        return Ok(None);
+    };
+    let Some(collection_type) = collection.collection_type() else {
+        return Ok(None);
+    };
+    let header = flash
+        .read_region(head_region, 0, Header::ENCODED_LEN, Header::decode)
+        .map_err(StorageRuntimeError::from)?
+        .map_err(|error| StorageRuntimeError::Startup(error.into()))?;
+    Ok(
+        FormatMigration::find(collection_type, header.collection_format).map(|migration| {
+            PendingMigration {
+                collection_id: collection.collection_id(),
+                head_region,
+                migration,
+            }
+        }),
+    )
+}
+
+/// Scans every live collection head for a retired format.
+pub(crate) fn migration_plan<IO: FlashIo, const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+) -> Result<MigrationPlan<MAX_COLLECTIONS>, StorageRuntimeError> {
+    let mut pending = Vec::new();
+    for collection in storage.collections() {
+        if let Some(migration) = pending_migration(flash, collection)? {
+            pending
+                .push(migration)
+                .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)?;
+        }
+    }
+    Ok(MigrationPlan { pending })
+}
@@ -0,0 +1,391 @@
+use super::*;
+
+use crate::collections::cell::Cell;
+use crate::startup::{StartupError, StartupOpenPlan};
+use crate::storage::WalHeadReclaimPlan;
+use crate::tests::PowerCutFlash;
+use crate::{
+    DiskError, LsmMap, MapStorageError, MapUpdate, MockError, MockFlash, MockOperation, Storage,
+    StorageMetadata, StorageOpenError, STORAGE_VERSION,
+};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 16;
+const LEGACY_MAP_ID: CollectionId = CollectionId(7);
+
+/// Storage image written by the 0.2.0 baseline release: the same map as
+/// [`write_legacy_map`] on a `TestFlash` formatted with
+/// `StorageFormatConfig::new(2, 8, 0xa5)`, dumped with
+/// `MockFlash::read_storage`. Its metadata region holds one bare copy.
+const MAP_REGION_V2_IMAGE: &[u8] = include_bytes!("fixtures/map_region_v2.img");
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 4096>;
+type TestStorage<'a, IO = TestFlash> = Storage<'a, 'static, IO, REGION_SIZE, REGION_COUNT>;
+
+fn open_storage<IO: FlashIo>(flash: &mut IO) -> TestStorage<'_, IO> {
+    Storage::<_, REGION_SIZE, REGION_COUNT>::open(flash, crate::test_storage_memory()).unwrap()
+}
+
+fn fixture_flash() -> TestFlash {
+    TestFlash::from_image(0xff, MAP_REGION_V2_IMAGE).unwrap()
+}
+
+/// Writes a map the way stores did before manifests: one `map_region_v2`
+/// snapshot region named by the head, plus updates appended after it.
+fn write_legacy_map(storage: &mut TestStorage<'_>) -> u32 {
+    storage.create_map(LEGACY_MAP_ID).unwrap();
+    let mut buffer = [0u8; REGION_SIZE];
+    let mut payload = [0u8; REGION_SIZE];
+    let used = {
+        let mut frontier = storage
+            .open_map::<u16, u16, 8>(
+                LEGACY_MAP_ID,
+                &mut buffer,
+                crate::test_map_frontier_memory(),
+            )
+            .unwrap();
+        for key in 0..12u16 {
+            frontier.set(storage, key, key * 10).unwrap();
+        }
+        frontier.encode_region_into(&mut payload).unwrap()
+    };
+    let region_index = storage
+        .with_runtime_io_workspace(|runtime, flash, workspace| {
+            let region_index = runtime.reserve_next_region::<REGION_SIZE, REGION_COUNT, _>(
+                flash,
+                workspace,
+                &mut heapless::Vec::new(),
+                &mut heapless::Vec::new(),
+                &mut WalHeadReclaimPlan::empty(),
+                &mut StartupOpenPlan::empty(),
+            )?;
+            runtime.write_committed_region::<REGION_SIZE, REGION_COUNT, _>(
+                flash,
+                workspace,
+                region_index,
+                LEGACY_MAP_ID,
+                MAP_REGION_V2_FORMAT,
+                &payload[..used],
+            )
+        })
+        .unwrap();
+    storage
+        .append_head(LEGACY_MAP_ID, CollectionType::MAP_CODE, region_index)
+        .unwrap();
+    storage
+        .append_map_update(
+            LEGACY_MAP_ID,
+            &MapUpdate::Set {
+                key: 100u16,
+                value: 1000u16,
+            },
+        )
+        .unwrap();
+    storage
+        .append_map_update::<u16, u16>(LEGACY_MAP_ID, &MapUpdate::Delete { key: 3 })
+        .unwrap();
+    region_index
+}
+
+/// Checks the contents [`write_legacy_map`] leaves behind.
+fn assert_legacy_contents<IO: FlashIo>(storage: &mut TestStorage<'_, IO>) {
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(LEGACY_MAP_ID, storage, crate::test_lsm_map_memory()).unwrap();
+    for key in 0..12u16 {
+        let expected = (key != 3).then_some(key * 10);
+        assert_eq!(map.get(storage, &key, |_, value| *value).unwrap(), expected);
+    }
+    assert_eq!(
+        map.get(storage, &100, |_, value| *value).unwrap(),
+        Some(1000)
+    );
+}
+
+fn head_format<IO: FlashIo>(storage: &mut TestStorage<'_, IO>) -> u16 {
+    let collection = *storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == LEGACY_MAP_ID)
+        .unwrap();
+    let StartupCollectionBasis::Region(head_region) = collection.basis() else {
+        panic!("map has no region basis");
+    };
+    storage
+        .with_io_workspace(|flash, _| {
+            flash.read_region(head_region, 0, Header::ENCODED_LEN, Header::decode)
+        })
+        .unwrap()
+        .unwrap()
+        .collection_format
+}
+
+fn migrate(storage: &mut TestStorage<'_, impl FlashIo>) -> Result<Option<u32>, MapStorageError> {
+    let mut buffer = [0u8; REGION_SIZE];
+    storage.migrate_map::<u16, u16, 8>(
+        LEGACY_MAP_ID,
+        &mut buffer,
+        crate::test_map_frontier_memory(),
+    )
+}
+
+fn flash_writes(operations: &[MockOperation]) -> usize {
+    operations
+        .iter()
+        .filter(|operation| {
+            matches!(
+                operation,
+                MockOperation::WriteMetadata
+                    | MockOperation::WriteRegion { .. }
+                    | MockOperation::EraseRegion { .. }
+            )
+        })
+        .count()
+}
+
+//= spec/migration.md#format-registry
+//= type=test
+//# `RING-MIGRATE-001` `FormatMigration::find` MUST return the registered
+//# migration for a collection type and a retired head format, and MUST
+//# return `None` for a current format.
+#[test]
+fn requirement_format_registry_names_retired_map_region() {
+    assert_eq!(
+        FormatMigration::find(CollectionType::MAP_CODE, MAP_REGION_V2_FORMAT),
+        Some(FormatMigration {
+            collection_type: CollectionType::MAP_CODE,
+            from_format: MAP_REGION_V2_FORMAT,
+            to_format: MAP_MANIFEST_V2_FORMAT,
+        })
+    );
+    assert_eq!(
+        FormatMigration::find(CollectionType::MAP_CODE, MAP_MANIFEST_V2_FORMAT),
+        None
+    );
+    assert_eq!(
+        FormatMigration::find(CollectionType::CELL_CODE, MAP_REGION_V2_FORMAT),
+        None
+    );
+}
+
+//= spec/migration.md#format-registry
+//= type=test
+//# `RING-MIGRATE-002` `Storage::migration_plan` MUST list every live
+//# collection whose head region header carries a registered retired format,
+//# and MUST NOT write to flash.
+#[test]
+fn requirement_migration_plan_lists_retired_heads() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut current =
+        LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+    current.set(&mut storage, 1, 2).unwrap();
+    let mut buffer = [0u8; REGION_SIZE];
+    let mut frontier = storage
+        .open_map::<u16, u16, 8>(
+            current.collection_id(),
+            &mut buffer,
+            crate::test_map_frontier_memory(),
+        )
+        .unwrap();
+    storage.flush_map(&mut frontier).unwrap();
+    Cell::new(&mut storage, 5u32).unwrap();
+    let head_region = write_legacy_map(&mut storage);
+
+    storage.with_io_workspace(|flash, _| flash.clear_operations());
+    let plan = storage.migration_plan().unwrap();
+    assert_eq!(
+        plan.pending.as_slice(),
+        &[PendingMigration {
+            collection_id: LEGACY_MAP_ID,
+            head_region,
+            migration: FORMAT_MIGRATIONS[0],
+        }]
+    );
+    let operations = storage.with_io_workspace(|flash, _| flash.operations().to_vec());
+    assert_eq!(flash_writes(&operations), 0);
+
+    migrate(&mut storage).unwrap();
+    assert!(storage.migration_plan().unwrap().is_empty());
+}
+
+//= spec/migration.md#map-region-migration
+//= type=test
+//# `RING-MIGRATE-003` `Storage::migrate_map` MUST load the retired snapshot
+//# and every update retained after its head, write them as a frontier run
+//# and a `map_manifest_v2` head, and free the retired region, so the map
+//# opens through `open_map` with the same contents.
+#[test]
+fn requirement_migrate_map_rewrites_region_v2_head() {
+    let mut flash = TestFlash::new(0xff);
+    let retired_region = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let retired_region = write_legacy_map(&mut storage);
+        let mut buffer = [0u8; REGION_SIZE];
+        assert!(matches!(
+            storage.open_map::<u16, u16, 8>(
+                LEGACY_MAP_ID,
+                &mut buffer,
+                crate::test_map_frontier_memory(),
+            ),
+            Err(MapStorageError::UnsupportedRegionFormat {
+                actual: MAP_REGION_V2_FORMAT,
+                ..
+            })
+        ));
+
+        let manifest_region = migrate(&mut storage).unwrap().unwrap();
+        assert_ne!(manifest_region, retired_region);
+        assert_eq!(head_format(&mut storage), MAP_MANIFEST_V2_FORMAT);
+        assert_legacy_contents(&mut storage);
+        retired_region
+    };
+
+    let mut storage = open_storage(&mut flash);
+    assert_eq!(head_format(&mut storage), MAP_MANIFEST_V2_FORMAT);
+    assert!(storage.free_space_entries().contains(&retired_region));
+    assert_legacy_contents(&mut storage);
+}
+
+//= spec/migration.md#map-region-migration
+//= type=test
+//# `RING-MIGRATE-004` The new head and the `free_region` record for the
+//# retired region MUST be appended inside one collection transaction, so a
+//# crash between any two durability barriers MUST reopen to either the
+//# retired head or the new head, each holding the original contents.
+#[test]
+fn requirement_migrate_map_survives_power_loss_at_every_sync() {
+    let mut flash = fixture_flash();
+    let total_syncs = {
+        let mut storage = open_storage(&mut flash);
+        storage.with_io_workspace(|flash, _| flash.clear_operations());
+        migrate(&mut storage).unwrap().unwrap();
+        storage.with_io_workspace(|flash, _| {
+            flash
+                .operations()
+                .iter()
+                .filter(|operation| **operation == MockOperation::Sync)
+                .count()
+        })
+    };
+    assert!(total_syncs > 0);
+
+    let mut retired_heads = 0;
+    for syncs_left in 0..total_syncs {
+        let mut flash = fixture_flash();
+        {
+            let mut power_cut = PowerCutFlash {
+                flash: &mut flash,
+                syncs_left,
+            };
+            let mut storage = open_storage(&mut power_cut);
+            assert!(migrate(&mut storage).is_err());
+        }
+
+        let mut storage = open_storage(&mut flash);
+        let format = head_format(&mut storage);
+        match format {
+            MAP_REGION_V2_FORMAT => retired_heads += 1,
+            MAP_MANIFEST_V2_FORMAT => {}
+            other => panic!("unexpected head format {other:#x}"),
+        }
+        assert_eq!(
+            migrate(&mut storage).unwrap().is_some(),
+            format == MAP_REGION_V2_FORMAT
+        );
+        assert_legacy_contents(&mut storage);
+    }
+    assert!(retired_heads > 0);
+}
+
+//= spec/migration.md#map-region-migration
+//= type=test
+//# `RING-MIGRATE-005` `Storage::migrate_map` MUST return `None` without
+//# writing to flash when the map head already uses current formats, and
+//# MUST reject unknown, dropped, and non-map collections.
+#[test]
+fn requirement_migrate_map_skips_current_and_rejects_other_collections() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    write_legacy_map(&mut storage);
+    migrate(&mut storage).unwrap().unwrap();
+
+    storage.with_io_workspace(|flash, _| flash.clear_operations());
+    assert_eq!(migrate(&mut storage).unwrap(), None);
+    let operations = storage.with_io_workspace(|flash, _| flash.operations().to_vec());
+    assert_eq!(flash_writes(&operations), 0);
+
+    let cell_id = Cell::new(&mut storage, 5u32).unwrap().collection_id();
+    let mut buffer = [0u8; REGION_SIZE];
+    assert!(matches!(
+        storage.migrate_map::<u16, u16, 8>(cell_id, &mut buffer, crate::test_map_frontier_memory()),
+        Err(MapStorageError::CollectionTypeMismatch { .. })
+    ));
+    assert!(matches!(
+        storage.migrate_map::<u16, u16, 8>(
+            CollectionId(99),
+            &mut buffer,
+            crate::test_map_frontier_memory()
+        ),
+        Err(MapStorageError::UnknownCollection(CollectionId(99)))
+    ));
+    storage.drop_map(LEGACY_MAP_ID).unwrap();
+    assert!(matches!(
+        migrate(&mut storage),
+        Err(MapStorageError::DroppedCollection(LEGACY_MAP_ID))
+    ));
+}
+
+//= spec/migration.md#fixtures
+//= type=test
+//# `RING-MIGRATE-006` The repository MUST keep a storage image holding a map
+//# written as a `map_region_v2` head with updates retained after it, and
+//# migrating that image MUST preserve every key and value it holds.
+#[test]
+fn requirement_map_region_v2_fixture_migrates() {
+    let mut flash = fixture_flash();
+    {
+        let mut storage = open_storage(&mut flash);
+        assert_eq!(head_format(&mut storage), MAP_REGION_V2_FORMAT);
+        let collection = *storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == LEGACY_MAP_ID)
+            .unwrap();
+        assert_eq!(collection.pending_update_count(), 2);
+        assert_eq!(storage.migration_plan().unwrap().pending.len(), 1);
+        migrate(&mut storage).unwrap().unwrap();
+    }
+
+    let mut storage = open_storage(&mut flash);
+    assert!(storage.migration_plan().unwrap().is_empty());
+    assert_legacy_contents(&mut storage);
+}
+
+//= spec/migration.md#storage-version
+//= type=test
+//# `RING-MIGRATE-007` `Storage::open` MUST reject metadata whose
+//# `storage_version` differs from `STORAGE_VERSION` with
+//# `DiskError::UnsupportedStorageVersion` and MUST NOT write to flash.
+#[test]
+fn requirement_open_rejects_other_storage_versions() {
+    for storage_version in [STORAGE_VERSION - 1, STORAGE_VERSION + 1] {
+        // The fixture holds one bare metadata copy: the fields, then a
+        // CRC-32C over them.
+        let mut image = MAP_REGION_V2_IMAGE.to_vec();
+        let fields_len = StorageMetadata::ENCODED_LEN - 4;
+        image[..4].copy_from_slice(&storage_version.to_le_bytes());
+        let checksum = crc::Crc::<u32>::new(&crc::CRC_32_ISCSI).checksum(&image[..fields_len]);
+        image[fields_len..StorageMetadata::ENCODED_LEN].copy_from_slice(&checksum.to_le_bytes());
+        let mut flash = TestFlash::from_image(0xff, &image).unwrap();
+
+        assert!(matches!(
+            Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory()),
+            Err(StorageOpenError::Runtime(StorageRuntimeError::Startup(
+                StartupError::Mock(
+                    MockError::Disk(DiskError::UnsupportedStorageVersion(version))
+                )
+            ))) if version == storage_version
+        ));
+        assert_eq!(flash_writes(flash.operations()), 0);
+    }
+}
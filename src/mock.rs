@@ -2,8 +2,9 @@ use heapless::Vec;
 
 use crate::disk::{
     encode_free_space_region_segment, encode_log_region_prefix,
-    free_queue_position_for_contiguous_metadata, DiskError, FreeQueuePosition, FreeSpaceCursors,
-    FreeSpaceEntry, FreeSpaceRegionPrologue, StorageMetadata, MAIN_WAL_V2_FORMAT,
+    free_queue_position_for_contiguous_metadata, metadata_copy_offset, select_metadata_copy,
+    DiskError, FreeQueuePosition, FreeSpaceCursors, FreeSpaceEntry, FreeSpaceRegionPrologue,
+    MetadataCopy, MetadataCopySelection, StorageMetadata, MAIN_WAL_V2_FORMAT, METADATA_COPY_COUNT,
 };
 
 #[cfg(test)]
@@ -18,6 +19,10 @@ pub enum MockError {
     OutOfBounds,
     /// The operation log reached its configured maximum length.
     LogFull,
+    /// The region was marked bad and refuses program and erase operations.
+    BadRegion(u32),
+    /// The metadata region held no decodable metadata copy.
+    Disk(DiskError),
 }
 
 /// Errors returned while formatting [`MockFlash`].
@@ -99,6 +104,7 @@ pub struct MockFlash<const REGION_SIZE: usize, const REGION_COUNT: usize, const
     metadata_region: [u8; REGION_SIZE],
     regions: [[u8; REGION_SIZE]; REGION_COUNT],
     erased_byte: u8,
+    bad_regions: [bool; REGION_COUNT],
     log: Vec<MockOperation, MAX_LOG>,
     log_enabled: bool,
 }
@@ -113,11 +119,38 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
             metadata_region: [erased_byte; REGION_SIZE],
             regions: core::array::from_fn(|_| [erased_byte; REGION_SIZE]),
             erased_byte,
+            bad_regions: [false; REGION_COUNT],
             log: Vec::new(),
             log_enabled: true,
         }
     }
 
+    /// Creates a mock device holding `image`, laid out as
+    /// [`Self::read_storage`] reads it: the metadata region followed by every
+    /// data region.
+    pub fn from_image(erased_byte: u8, image: &[u8]) -> Result<Self, MockError> {
+        let expected_len = REGION_SIZE
+            .checked_mul(REGION_COUNT + 1)
+            .ok_or(MockError::OutOfBounds)?;
+        if image.len() != expected_len {
+            return Err(MockError::OutOfBounds);
+        }
+        let mut flash = Self::new(erased_byte);
+        let (metadata_region, regions) = image.split_at(REGION_SIZE);
+        flash.metadata_region.copy_from_slice(metadata_region);
+        for (region, bytes) in flash
+            .regions
+            .iter_mut()
+            .zip(regions.chunks_exact(REGION_SIZE))
+        {
+            region.copy_from_slice(bytes);
+        }
+        flash.metadata = select_metadata_copy(&flash.metadata_region)
+            .ok()
+            .map(|selection| selection.copy.metadata);
+        Ok(flash)
+    }
+
     /// Returns the current formatted metadata, if present.
     pub fn metadata(&self) -> Option<&StorageMetadata> {
         self.metadata.as_ref()
@@ -180,6 +213,32 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
         self.region(region_index)
     }
 
+    /// Flips one byte inside metadata copy slot `copy_index`, modelling a
+    /// corrupted metadata copy.
+    pub fn corrupt_metadata_copy(&mut self, copy_index: usize) -> Result<(), MockError> {
+        let offset =
+            metadata_copy_offset(REGION_SIZE, copy_index).map_err(|_| MockError::OutOfBounds)?;
+        let byte = self
+            .metadata_region
+            .get_mut(offset)
+            .ok_or(MockError::OutOfBounds)?;
+        *byte ^= 0x01;
+        Ok(())
+    }
+
+    /// Marks a region bad so later program and erase operations on it fail
+    /// with [`MockError::BadRegion`], modelling worn-out silicon.
+    pub fn fail_region(&mut self, region_index: u32) -> Result<(), MockError> {
+        let index = usize::try_from(region_index)
+            .map_err(|_| MockError::InvalidRegionIndex(region_index))?;
+        let bad = self
+            .bad_regions
+            .get_mut(index)
+            .ok_or(MockError::InvalidRegionIndex(region_index))?;
+        *bad = true;
+        Ok(())
+    }
+
     /// Clears the recorded operation log.
     pub fn clear_operations(&mut self) {
         self.log.clear();
@@ -193,21 +252,34 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
         }
     }
 
-    /// Reads formatted storage metadata.
+    /// Reads the newest valid storage metadata copy.
     pub fn read_metadata(&mut self) -> Result<Option<StorageMetadata>, MockError> {
         self.log(MockOperation::ReadMetadata)?;
-        Ok(self.metadata)
+        if self
+            .metadata_region
+            .iter()
+            .all(|byte| *byte == self.erased_byte)
+        {
+            return Ok(None);
+        }
+        let selection = select_metadata_copy(&self.metadata_region).map_err(MockError::Disk)?;
+        Ok(Some(selection.copy.metadata))
     }
 
-    /// Writes formatted storage metadata and updates the metadata region bytes.
+    /// Writes a new metadata copy into the slot not holding the current copy.
     pub fn write_metadata(&mut self, metadata: StorageMetadata) -> Result<(), MockError> {
         self.log(MockOperation::WriteMetadata)?;
-        if REGION_SIZE < StorageMetadata::ENCODED_LEN {
+        if REGION_SIZE < MetadataCopy::MIN_REGION_LEN {
             return Err(MockError::OutOfBounds);
         }
-        self.metadata_region.fill(self.erased_byte);
-        metadata
-            .encode_into(&mut self.metadata_region)
+        let (copy_index, sequence) =
+            MetadataCopySelection::next_write(select_metadata_copy(&self.metadata_region).ok());
+        let offset =
+            metadata_copy_offset(REGION_SIZE, copy_index).map_err(|_| MockError::OutOfBounds)?;
+        let slot = &mut self.metadata_region[offset..offset + REGION_SIZE / METADATA_COPY_COUNT];
+        slot.fill(self.erased_byte);
+        MetadataCopy { metadata, sequence }
+            .encode_into(slot)
             .map_err(|_| MockError::OutOfBounds)?;
         self.metadata = Some(metadata);
         Ok(())
@@ -247,6 +319,7 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
             offset,
             len: data.len(),
         })?;
+        self.ensure_good_region(region_index)?;
         let region = self.region_mut(region_index)?;
         let end = offset
             .checked_add(data.len())
@@ -259,6 +332,7 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
     /// Erases a single region to the configured erased byte.
     pub fn erase_region(&mut self, region_index: u32) -> Result<(), MockError> {
         self.log(MockOperation::EraseRegion { region_index })?;
+        self.ensure_good_region(region_index)?;
         let erased_byte = self.erased_byte;
         let region = self.region_mut(region_index)?;
         region.fill(erased_byte);
@@ -338,7 +412,7 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
                 .ok_or(MockFormatError::RegionCountTooLarge)?;
         }
         let free_space_entry_count = region_count - 1 - metadata_region_count;
-        let min_region_size_usize = StorageMetadata::ENCODED_LEN
+        let min_region_size_usize = MetadataCopy::MIN_REGION_LEN
             .max(wal_record_area_offset)
             .max(free_space_entries_offset + FreeSpaceEntry::ENCODED_LEN);
         if REGION_SIZE < min_region_size_usize {
@@ -349,7 +423,9 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
             });
         }
 
-        self.write_metadata(metadata)?;
+        for _ in 0..METADATA_COPY_COUNT {
+            self.write_metadata(metadata)?;
+        }
 
         for region_index in 0..region_count {
             self.erase_region(region_index)?;
@@ -431,6 +507,18 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
         self.log.push(operation).map_err(|_| MockError::LogFull)
     }
 
+    fn ensure_good_region(&self, region_index: u32) -> Result<(), MockError> {
+        let bad = usize::try_from(region_index)
+            .ok()
+            .and_then(|index| self.bad_regions.get(index))
+            .copied()
+            .unwrap_or(false);
+        if bad {
+            return Err(MockError::BadRegion(region_index));
+        }
+        Ok(())
+    }
+
     fn region(&self, region_index: u32) -> Result<&[u8; REGION_SIZE], MockError> {
         let index = usize::try_from(region_index)
             .map_err(|_| MockError::InvalidRegionIndex(region_index))?;
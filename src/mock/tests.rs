@@ -1,5 +1,8 @@
 use super::*;
-use crate::disk::{FreeSpaceEntry, FreeSpaceRegionPrologue, StorageMetadata, FREE_SPACE_V2_FORMAT};
+use crate::disk::{
+    metadata_copy_offset, select_metadata_copy, FreeSpaceEntry, FreeSpaceRegionPrologue,
+    MetadataCopy, StorageMetadata, FREE_SPACE_V2_FORMAT, METADATA_COPY_COUNT,
+};
 
 //= spec/ring/01-theory.md#core-requirements
 //= type=test
@@ -93,7 +96,7 @@ fn requirement_read_storage_spans_metadata_and_data_regions_with_bounds_checks()
 //= spec/mock.md#mock-flash-requirements
 //= type=test
 //# `RING-IMPL-REGRESSION-039` Mock flash metadata writes MUST fail without changing metadata when
-//# the metadata region is smaller than encoded StorageMetadata.
+//# the metadata region is smaller than every encoded metadata copy slot.
 #[test]
 fn requirement_write_metadata_requires_metadata_region_large_enough() {
     let mut flash = MockFlash::<8, 4, 8>::new(0xff);
@@ -106,19 +109,22 @@ fn requirement_write_metadata_requires_metadata_region_large_enough() {
 //= spec/mock.md#mock-flash-requirements
 //= type=test
 //# `RING-IMPL-REGRESSION-040` Mock flash metadata writes MUST succeed when the metadata region
-//# exactly matches encoded StorageMetadata and persist decodable metadata.
+//# exactly holds every encoded metadata copy slot and persist decodable metadata.
 #[test]
 fn requirement_write_metadata_accepts_exact_metadata_region_size() {
-    let mut flash = MockFlash::<{ StorageMetadata::ENCODED_LEN }, 4, 8>::new(0xff);
+    let mut flash = MockFlash::<{ MetadataCopy::MIN_REGION_LEN }, 4, 8>::new(0xff);
     let metadata =
-        StorageMetadata::new(StorageMetadata::ENCODED_LEN as u32, 4, 1, 4, 0xff, 0xa5).unwrap();
+        StorageMetadata::new(MetadataCopy::MIN_REGION_LEN as u32, 4, 1, 4, 0xff, 0xa5).unwrap();
 
     flash.write_metadata(metadata).unwrap();
 
     assert_eq!(flash.metadata(), Some(&metadata));
     assert_eq!(
-        StorageMetadata::decode(&flash.metadata_region[..]).unwrap(),
-        metadata
+        MetadataCopy::decode(&flash.metadata_region[..]).unwrap(),
+        MetadataCopy {
+            metadata,
+            sequence: 0,
+        }
     );
 }
 
@@ -444,7 +450,7 @@ fn requirement_format_empty_store_leaves_free_data_regions_erased() {
 //= spec/mock.md#mock-flash-requirements
 //= type=test
 //# `RING-IMPL-REGRESSION-045` Formatting an empty mock store MUST leave reserved bytes after
-//# encoded StorageMetadata erased.
+//# each encoded metadata copy erased.
 #[test]
 fn requirement_format_empty_store_leaves_reserved_metadata_bytes_erased() {
     let mut flash = MockFlash::<96, 4, 32>::new(0xff);
@@ -452,9 +458,15 @@ fn requirement_format_empty_store_leaves_reserved_metadata_bytes_erased() {
 
     let mut metadata_region = [0u8; 96];
     flash.read_storage(0, &mut metadata_region).unwrap();
-    assert!(metadata_region[StorageMetadata::ENCODED_LEN..]
-        .iter()
-        .all(|byte| *byte == 0xff));
+    for copy_index in 0..METADATA_COPY_COUNT {
+        let offset = metadata_copy_offset(96, copy_index).unwrap();
+        MetadataCopy::decode(&metadata_region[offset..]).unwrap();
+        assert!(
+            metadata_region[offset + MetadataCopy::ENCODED_LEN..offset + 48]
+                .iter()
+                .all(|byte| *byte == 0xff)
+        );
+    }
 }
 
 //= spec/ring/05-disk-format.md#storage-requirements
@@ -471,7 +483,13 @@ fn requirement_format_empty_store_places_region_zero_immediately_after_metadata_
 
     let mut metadata_region = [0u8; 96];
     flash.read_storage(0, &mut metadata_region).unwrap();
-    assert_eq!(StorageMetadata::decode(&metadata_region).unwrap(), metadata);
+    assert_eq!(
+        select_metadata_copy(&metadata_region)
+            .unwrap()
+            .copy
+            .metadata,
+        metadata
+    );
 
     let mut header_bytes = [0u8; Header::ENCODED_LEN];
     flash.read_storage(96, &mut header_bytes).unwrap();
@@ -496,10 +514,78 @@ fn requirement_format_empty_store_begins_with_static_metadata_region() {
 
     let mut metadata_region = [0u8; 96];
     flash.read_storage(0, &mut metadata_region).unwrap();
-    assert_eq!(StorageMetadata::decode(&metadata_region).unwrap(), metadata);
+    assert_eq!(
+        select_metadata_copy(&metadata_region)
+            .unwrap()
+            .copy
+            .metadata,
+        metadata
+    );
 
     let mut region_zero_prefix = [0u8; Header::ENCODED_LEN];
     flash.read_storage(96, &mut region_zero_prefix).unwrap();
     let wal_header = Header::decode(&region_zero_prefix).unwrap();
     assert_eq!(wal_header.collection_id, CollectionId(0));
 }
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-012` Formatting MUST write both slots, leaving slot `0`
+//# with `sequence = 0` and slot `1` with `sequence = 1`.
+#[test]
+fn requirement_format_empty_store_writes_both_metadata_copies() {
+    let mut flash = MockFlash::<96, 4, 32>::new(0xff);
+    let metadata = flash.format_empty_store(1, 8, 0xa5).unwrap();
+
+    for (copy_index, sequence) in [(0, 0), (1, 1)] {
+        let offset = metadata_copy_offset(96, copy_index).unwrap();
+        assert_eq!(
+            MetadataCopy::decode(&flash.metadata_region[offset..]).unwrap(),
+            MetadataCopy { metadata, sequence }
+        );
+    }
+
+    flash.corrupt_metadata_copy(1).unwrap();
+    assert_eq!(flash.read_metadata().unwrap(), Some(metadata));
+    flash.write_metadata(metadata).unwrap();
+    let offset = metadata_copy_offset(96, 1).unwrap();
+    assert_eq!(
+        MetadataCopy::decode(&flash.metadata_region[offset..])
+            .unwrap()
+            .sequence,
+        1
+    );
+
+    flash.corrupt_metadata_copy(0).unwrap();
+    flash.corrupt_metadata_copy(1).unwrap();
+    assert_eq!(
+        flash.read_metadata(),
+        Err(MockError::Disk(DiskError::InvalidChecksum))
+    );
+}
+
+//= spec/mock.md#mock-flash-requirements
+//= type=test
+//# `RING-IMPL-REGRESSION-159` Loading a mock flash device from a storage
+//# image MUST reject an image whose length is not the metadata region plus
+//# every data region, and MUST otherwise read back the image byte for byte
+//# and expose its metadata.
+#[test]
+fn requirement_from_image_round_trips_storage_bytes() {
+    let mut flash = MockFlash::<96, 4, 32>::new(0xff);
+    let metadata = flash.format_empty_store(1, 8, 0xa5).unwrap();
+    flash.write_region(2, 5, &[1, 2, 3]).unwrap();
+    let mut image = [0u8; 96 * 5];
+    flash.read_storage(0, &mut image).unwrap();
+
+    assert!(matches!(
+        MockFlash::<96, 4, 32>::from_image(0xff, &image[..96 * 4]),
+        Err(MockError::OutOfBounds)
+    ));
+    let mut loaded = MockFlash::<96, 4, 32>::from_image(0xff, &image).unwrap();
+    let mut read_back = [0u8; 96 * 5];
+    loaded.read_storage(0, &mut read_back).unwrap();
+    assert_eq!(read_back, image);
+    assert_eq!(loaded.metadata(), Some(&metadata));
+    assert_eq!(loaded.read_metadata().unwrap(), Some(metadata));
+}
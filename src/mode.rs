@@ -24,6 +24,11 @@ impl StorageMode {
     pub(crate) const fn expected_idle() -> Self {
         Self::Idle
     }
+
+    /// Returns whether the mode only reads durable state.
+    pub(crate) const fn is_read_only(self) -> bool {
+        matches!(self, Self::ReadingStorage(_) | Self::LoadingCollection(_))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
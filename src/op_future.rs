@@ -806,8 +806,18 @@ where
 
                     if !matches!(
                         collection_type,
-                        CollectionType::MAP_CODE | CollectionType::OBJECT_LOG_CODE
-                    ) {
+                        CollectionType::CHANNEL_CODE
+                            | CollectionType::MAP_CODE
+                            | CollectionType::OBJECT_LOG_CODE
+                            | CollectionType::QUEUE_CODE
+                            | CollectionType::RING_LOG_CODE
+                            | CollectionType::TIME_SERIES_CODE
+                            | CollectionType::CELL_CODE
+                            | CollectionType::SEQUENCE_CODE
+                            | CollectionType::CATALOG_CODE
+                            | CollectionType::SET_CODE
+                    ) && !runtime.collection_types().contains(collection_type)
+                    {
                         return Poll::Ready(Err(StorageOpenError::UnsupportedLiveCollectionType(
                             collection_type,
                         )));
@@ -823,7 +833,9 @@ where
                     StorageRuntimeError::StorageMemoryUninitialized,
                 ))?;
                 this.phase = OpenStoragePhase::Done;
-                Poll::Ready(Storage::from_initialized_memory(backing, memory).map_err(Into::into))
+                let mut storage = Storage::from_initialized_memory(backing, memory)?;
+                storage.replay_plugin_collections()?;
+                Poll::Ready(Ok(storage))
             }
             OpenStoragePhase::Done => Poll::Pending,
         }
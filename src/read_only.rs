@@ -0,0 +1,256 @@
+//! Read-only open that never writes to the backing device.
+//!
+//! [`Storage::open_read_only`](crate::Storage::open_read_only) opens a store
+//! through [`ReadOnlyFlash`], which wraps the caller's backing. Recovery runs
+//! exactly as in [`Storage::open`](crate::Storage::open), but every record,
+//! rotation, or erase it would make lands in a bounded RAM overlay instead of
+//! on the device. Once the open finishes the overlay is sealed and every
+//! mutating API fails with
+//! [`StorageRuntimeError::ReadOnly`](crate::StorageRuntimeError::ReadOnly).
+
+use heapless::Vec;
+
+use crate::flash_io::{FlashIo, StorageFormatError, StorageIoError};
+use crate::{Storage, StorageMemory, StorageMetadata, StorageOpenError};
+
+#[cfg(test)]
+mod tests;
+
+/// Errors returned by [`ReadOnlyFlash`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadOnlyFlashError {
+    /// A program, erase, or metadata write was issued after the open sealed
+    /// the overlay.
+    WriteRejected,
+    /// Open recovery touched more regions than the overlay can hold.
+    OverlayFull,
+    /// A region index or byte range fell outside the storage geometry.
+    OutOfBounds,
+    /// An overlaid write needed storage metadata but the device has none.
+    MissingMetadata,
+}
+
+struct OverlayRegion<const REGION_SIZE: usize> {
+    region_index: u32,
+    bytes: [u8; REGION_SIZE],
+}
+
+/// Backing wrapper that keeps every write in RAM.
+///
+/// Reads return overlaid bytes for regions written through the wrapper and
+/// device bytes for every other region. Nothing is ever forwarded to the
+/// wrapped device's `write_region`, `erase_region`, `write_metadata`, or
+/// `sync`. `OVERLAY_REGIONS` bounds how many distinct regions open recovery
+/// may rewrite; a store that needs more fails the open with
+/// [`ReadOnlyFlashError::OverlayFull`].
+pub struct ReadOnlyFlash<'a, IO, const REGION_SIZE: usize, const OVERLAY_REGIONS: usize = 4> {
+    inner: &'a mut IO,
+    overlay: Vec<OverlayRegion<REGION_SIZE>, OVERLAY_REGIONS>,
+    metadata: Option<StorageMetadata>,
+    sealed: bool,
+}
+
+impl<'a, IO: FlashIo, const REGION_SIZE: usize, const OVERLAY_REGIONS: usize>
+    ReadOnlyFlash<'a, IO, REGION_SIZE, OVERLAY_REGIONS>
+{
+    /// Wraps `inner` with an empty overlay.
+    pub fn new(inner: &'a mut IO) -> Self {
+        Self {
+            inner,
+            overlay: Vec::new(),
+            metadata: None,
+            sealed: false,
+        }
+    }
+
+    /// Returns the wrapped device.
+    pub fn into_inner(self) -> &'a mut IO {
+        self.inner
+    }
+
+    /// Returns whether the overlay rejects further writes.
+    pub fn is_sealed(&self) -> bool {
+        self.sealed
+    }
+
+    /// Iterates the regions whose contents exist only in the overlay, in the
+    /// order recovery first wrote them.
+    pub fn overlaid_regions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.overlay.iter().map(|region| region.region_index)
+    }
+
+    pub(crate) fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    pub(crate) fn unseal(&mut self) {
+        self.sealed = false;
+    }
+
+    fn ensure_writable(&self) -> Result<(), StorageIoError> {
+        if self.sealed {
+            return Err(StorageIoError::ReadOnly(ReadOnlyFlashError::WriteRejected));
+        }
+        Ok(())
+    }
+
+    fn overlay_position(&self, region_index: u32) -> Option<usize> {
+        self.overlay
+            .iter()
+            .position(|region| region.region_index == region_index)
+    }
+
+    /// Returns the overlay slot for `region_index`, creating it from the
+    /// device image or, when `erase` is set, from the erased byte.
+    fn overlay_region(
+        &mut self,
+        region_index: u32,
+        erase: bool,
+    ) -> Result<&mut [u8; REGION_SIZE], StorageIoError> {
+        let metadata = FlashIo::read_metadata(self)?.ok_or(StorageIoError::ReadOnly(
+            ReadOnlyFlashError::MissingMetadata,
+        ))?;
+        if region_index >= metadata.region_count {
+            return Err(StorageIoError::ReadOnly(ReadOnlyFlashError::OutOfBounds));
+        }
+        let index = match self.overlay_position(region_index) {
+            Some(index) => index,
+            None => {
+                let mut bytes = [metadata.erased_byte; REGION_SIZE];
+                if !erase {
+                    self.inner
+                        .read_region(region_index, 0, REGION_SIZE, |image| {
+                            bytes.copy_from_slice(image)
+                        })?;
+                }
+                self.overlay
+                    .push(OverlayRegion {
+                        region_index,
+                        bytes,
+                    })
+                    .map_err(|_| StorageIoError::ReadOnly(ReadOnlyFlashError::OverlayFull))?;
+                self.overlay.len() - 1
+            }
+        };
+        self.overlay
+            .get_mut(index)
+            .map(|region| &mut region.bytes)
+            .ok_or(StorageIoError::ReadOnly(ReadOnlyFlashError::OverlayFull))
+    }
+}
+
+impl<IO: FlashIo, const REGION_SIZE: usize, const OVERLAY_REGIONS: usize> FlashIo
+    for ReadOnlyFlash<'_, IO, REGION_SIZE, OVERLAY_REGIONS>
+{
+    fn read_metadata(&mut self) -> Result<Option<StorageMetadata>, StorageIoError> {
+        match self.metadata {
+            Some(metadata) => Ok(Some(metadata)),
+            None => self.inner.read_metadata(),
+        }
+    }
+
+    fn write_metadata(&mut self, metadata: StorageMetadata) -> Result<(), StorageIoError> {
+        self.ensure_writable()?;
+        self.metadata = Some(metadata);
+        Ok(())
+    }
+
+    fn read_region<R, F>(
+        &mut self,
+        region_index: u32,
+        offset: usize,
+        len: usize,
+        read: F,
+    ) -> Result<R, StorageIoError>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        let Some(index) = self.overlay_position(region_index) else {
+            return self.inner.read_region(region_index, offset, len, read);
+        };
+        let bytes = offset
+            .checked_add(len)
+            .and_then(|end| self.overlay.get(index)?.bytes.get(offset..end))
+            .ok_or(StorageIoError::ReadOnly(ReadOnlyFlashError::OutOfBounds))?;
+        Ok(read(bytes))
+    }
+
+    fn write_region(
+        &mut self,
+        region_index: u32,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), StorageIoError> {
+        self.ensure_writable()?;
+        let region = self.overlay_region(region_index, false)?;
+        offset
+            .checked_add(data.len())
+            .and_then(|end| region.get_mut(offset..end))
+            .ok_or(StorageIoError::ReadOnly(ReadOnlyFlashError::OutOfBounds))?
+            .copy_from_slice(data);
+        Ok(())
+    }
+
+    fn erase_region(&mut self, region_index: u32) -> Result<(), StorageIoError> {
+        self.ensure_writable()?;
+        let erased_byte = FlashIo::read_metadata(self)?
+            .ok_or(StorageIoError::ReadOnly(
+                ReadOnlyFlashError::MissingMetadata,
+            ))?
+            .erased_byte;
+        self.overlay_region(region_index, true)?.fill(erased_byte);
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<(), StorageIoError> {
+        self.ensure_writable()
+    }
+
+    fn format_empty_store(
+        &mut self,
+        _min_free_regions: u32,
+        _wal_write_granule: u32,
+        _wal_record_magic: u8,
+    ) -> Result<StorageMetadata, StorageFormatError> {
+        Err(StorageFormatError::ReadOnly)
+    }
+}
+
+impl<
+        'db,
+        'mem,
+        'io,
+        IO: FlashIo,
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        const MAX_COLLECTIONS: usize,
+        const OVERLAY_REGIONS: usize,
+    >
+    Storage<
+        'db,
+        'mem,
+        ReadOnlyFlash<'io, IO, REGION_SIZE, OVERLAY_REGIONS>,
+        REGION_SIZE,
+        REGION_COUNT,
+        MAX_COLLECTIONS,
+    >
+{
+    /// Opens an already formatted store without writing to the device.
+    ///
+    /// Replay and recovery run as in [`Self::open`], but any recovery record,
+    /// rotation, or transaction rollback is kept in the overlay of `backing`,
+    /// so reads see the recovered store while the device stays bit-for-bit
+    /// unchanged. After the open, every operation that would write fails
+    /// with [`StorageRuntimeError::ReadOnly`](crate::StorageRuntimeError::ReadOnly);
+    /// reads, [`Self::check`], and [`Self::scrub`] still work.
+    pub fn open_read_only(
+        backing: &'db mut ReadOnlyFlash<'io, IO, REGION_SIZE, OVERLAY_REGIONS>,
+        memory: &'mem mut StorageMemory<REGION_SIZE, REGION_COUNT, MAX_COLLECTIONS>,
+    ) -> Result<Self, StorageOpenError> {
+        backing.unseal();
+        let storage = Self::open(backing, memory)?;
+        storage.backing.seal();
+        storage.memory.read_only = true;
+        Ok(storage)
+    }
+}
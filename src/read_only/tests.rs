@@ -0,0 +1,275 @@
+use super::*;
+
+use crate::collections::cell::{Cell, CellError};
+use crate::collections::map::MapStorageError;
+use crate::{
+    CollectionId, CollectionType, LsmMap, MockFlash, MockOperation, StorageFormatConfig,
+    StorageRuntimeError, TransactionMemory, WearLevelBudget,
+};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestReadOnlyFlash<'a> = ReadOnlyFlash<'a, TestFlash, REGION_SIZE>;
+
+/// Formats a store holding one cell and one map, then leaves a map
+/// transaction open so the next open has to append its rollback.
+fn interrupted_store(flash: &mut TestFlash) -> (CollectionId, CollectionId) {
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::format(
+        flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let mut cell = Cell::new(&mut storage, std::vec![7u8; 4]).unwrap();
+    cell.set(&mut storage, std::vec![9u8; 4]).unwrap();
+    let mut map = LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+    map.set(&mut storage, 1, 100).unwrap();
+    map.set(&mut storage, 2, 200).unwrap();
+    let map_id = map.collection_id();
+
+    let mut buffer = [0u8; REGION_SIZE];
+    let mut frontier = storage
+        .open_map::<u16, u16, 8>(map_id, &mut buffer, crate::test_map_frontier_memory())
+        .unwrap();
+    storage.flush_map(&mut frontier).unwrap();
+    let mut transaction = TransactionMemory::<REGION_COUNT>::new();
+    let _writer = storage.begin_transaction(map_id, &mut transaction).unwrap();
+    frontier.set_in_memory(3, 300).unwrap();
+    storage.flush_map(&mut frontier).unwrap();
+    (cell.collection_id(), map_id)
+}
+
+fn device_image(flash: &TestFlash) -> std::vec::Vec<[u8; REGION_SIZE]> {
+    (0..REGION_COUNT as u32)
+        .map(|region_index| *flash.region_bytes(region_index).unwrap())
+        .collect()
+}
+
+fn only_reads(flash: &TestFlash) -> bool {
+    flash.operations().iter().all(|operation| {
+        matches!(
+            operation,
+            MockOperation::ReadMetadata | MockOperation::ReadRegion { .. }
+        )
+    })
+}
+
+fn cell_value<IO: FlashIo>(
+    storage: &mut Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT>,
+    collection_id: CollectionId,
+) -> std::vec::Vec<u8> {
+    Cell::<std::vec::Vec<u8>>::open(collection_id, storage)
+        .unwrap()
+        .get()
+        .clone()
+}
+
+fn map_value<IO: FlashIo>(
+    storage: &mut Storage<'_, '_, IO, REGION_SIZE, REGION_COUNT>,
+    collection_id: CollectionId,
+    key: u16,
+) -> Option<u16> {
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(collection_id, storage, crate::test_lsm_map_memory()).unwrap();
+    map.get(storage, &key, |_, value| *value).unwrap()
+}
+
+//= spec/read-only.md#read-only-open
+//= type=test
+//# `RING-READONLY-001` `Storage::open_read_only` MUST run the same replay
+//# and recovery as `Storage::open`, but MUST NOT write, erase, or sync the
+//# wrapped device or write its metadata, even when recovery has to append
+//# records.
+#[test]
+fn requirement_read_only_open_rolls_back_transaction_without_device_writes() {
+    let mut flash = TestFlash::new(0xff);
+    interrupted_store(&mut flash);
+    let before = device_image(&flash);
+    flash.clear_operations();
+
+    let mut backing = TestReadOnlyFlash::new(&mut flash);
+    let storage = Storage::<_, REGION_SIZE, REGION_COUNT>::open_read_only(
+        &mut backing,
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    assert!(storage.is_read_only());
+    let tail = storage.wal_tail();
+    assert!(backing.is_sealed());
+    assert!(backing.overlaid_regions().any(|region| region == tail));
+    let flash = backing.into_inner();
+    assert!(only_reads(flash));
+    assert_eq!(device_image(flash), before);
+
+    // A normal open of the same bytes has to write the rollback records.
+    flash.clear_operations();
+    Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut *flash, crate::test_storage_memory())
+        .unwrap();
+    assert!(!only_reads(flash));
+}
+
+//= spec/read-only.md#read-only-open
+//= type=test
+//# `RING-READONLY-002` Reads through a read-only open MUST return the same
+//# committed contents as a normal open of the same device.
+#[test]
+fn requirement_read_only_open_reads_match_normal_open() {
+    let mut flash = TestFlash::new(0xff);
+    let (cell_id, map_id) = interrupted_store(&mut flash);
+    flash.clear_operations();
+
+    {
+        let mut backing = TestReadOnlyFlash::new(&mut flash);
+        let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::open_read_only(
+            &mut backing,
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        assert_eq!(cell_value(&mut storage, cell_id), std::vec![9u8; 4]);
+        assert_eq!(map_value(&mut storage, map_id, 1), Some(100));
+        assert_eq!(map_value(&mut storage, map_id, 2), Some(200));
+        assert_eq!(map_value(&mut storage, map_id, 3), None);
+        let summary = storage.check(&mut |finding| panic!("{finding:?}")).unwrap();
+        assert!(summary.is_clean());
+        storage
+            .scrub(u32::MAX, &mut |damage| panic!("{damage:?}"))
+            .unwrap();
+        assert_eq!(storage.scrub_cursor().completed_passes, 1);
+    }
+    assert!(only_reads(&flash));
+
+    let mut storage =
+        Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
+            .unwrap();
+    assert_eq!(cell_value(&mut storage, cell_id), std::vec![9u8; 4]);
+    assert_eq!(map_value(&mut storage, map_id, 1), Some(100));
+    assert_eq!(map_value(&mut storage, map_id, 2), Some(200));
+    assert_eq!(map_value(&mut storage, map_id, 3), None);
+}
+
+//= spec/read-only.md#read-only-open
+//= type=test
+//# `RING-READONLY-003` When recovery would rewrite more regions than the
+//# overlay holds, the open MUST fail with `OverlayFull` and the device
+//# MUST stay unchanged.
+#[test]
+fn requirement_read_only_open_fails_when_overlay_is_full() {
+    let mut flash = TestFlash::new(0xff);
+    interrupted_store(&mut flash);
+    let before = device_image(&flash);
+    flash.clear_operations();
+
+    let mut backing = ReadOnlyFlash::<_, REGION_SIZE, 0>::new(&mut flash);
+    let result = Storage::<_, REGION_SIZE, REGION_COUNT>::open_read_only(
+        &mut backing,
+        crate::test_storage_memory(),
+    );
+    assert!(matches!(
+        result,
+        Err(StorageOpenError::Runtime(StorageRuntimeError::Startup(
+            crate::StartupError::ReadOnlyFlash(ReadOnlyFlashError::OverlayFull)
+        )))
+    ));
+    assert!(only_reads(&flash));
+    assert_eq!(device_image(&flash), before);
+}
+
+//= spec/read-only.md#rejected-writes
+//= type=test
+//# `RING-READONLY-004` After a read-only open, every operation that would
+//# write MUST fail with `ReadOnly` before changing any in-memory state.
+#[test]
+fn requirement_read_only_storage_rejects_mutating_operations() {
+    let mut flash = TestFlash::new(0xff);
+    let (cell_id, map_id) = interrupted_store(&mut flash);
+    flash.clear_operations();
+
+    let mut backing = TestReadOnlyFlash::new(&mut flash);
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::open_read_only(
+        &mut backing,
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let collections = storage.collections().len();
+    let append_offset = storage.wal_append_offset();
+
+    assert!(matches!(
+        Cell::new(&mut storage, std::vec![1u8; 4]),
+        Err(CellError::Storage(StorageRuntimeError::ReadOnly))
+    ));
+    let mut cell = Cell::<std::vec::Vec<u8>>::open(cell_id, &mut storage).unwrap();
+    assert!(matches!(
+        cell.set(&mut storage, std::vec![1u8; 4]),
+        Err(CellError::Storage(StorageRuntimeError::ReadOnly))
+    ));
+    assert_eq!(
+        storage.append_update(cell_id, &[1, 2, 3, 4]),
+        Err(StorageRuntimeError::ReadOnly)
+    );
+    assert_eq!(
+        storage.append_new_collection(CollectionId(40), CollectionType::MAP_CODE),
+        Err(StorageRuntimeError::ReadOnly)
+    );
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(map_id, &mut storage, crate::test_lsm_map_memory()).unwrap();
+    assert!(matches!(
+        map.set(&mut storage, 3, 300),
+        Err(MapStorageError::Storage(StorageRuntimeError::ReadOnly))
+    ));
+    assert!(matches!(
+        storage.drop_map(map_id),
+        Err(MapStorageError::Storage(StorageRuntimeError::ReadOnly))
+    ));
+    let mut transaction = TransactionMemory::<REGION_COUNT>::new();
+    assert!(matches!(
+        storage.begin_transaction(cell_id, &mut transaction),
+        Err(StorageRuntimeError::ReadOnly)
+    ));
+    assert_eq!(
+        storage.wear_level(WearLevelBudget::new(4, 0)).map(|_| ()),
+        Err(StorageRuntimeError::ReadOnly)
+    );
+
+    assert_eq!(storage.collections().len(), collections);
+    assert_eq!(storage.wal_append_offset(), append_offset);
+    assert_eq!(cell_value(&mut storage, cell_id), std::vec![9u8; 4]);
+    assert_eq!(map_value(&mut storage, map_id, 3), None);
+    assert!(only_reads(&flash));
+}
+
+//= spec/read-only.md#rejected-writes
+//= type=test
+//# `RING-READONLY-005` Once the open returns, `ReadOnlyFlash` MUST reject
+//# every program, erase, metadata write, sync, and format with
+//# `WriteRejected` or `StorageFormatError::ReadOnly` and MUST NOT forward it
+//# to the wrapped device.
+#[test]
+fn requirement_sealed_read_only_flash_rejects_direct_writes() {
+    let mut flash = TestFlash::new(0xff);
+    interrupted_store(&mut flash);
+    let before = device_image(&flash);
+    flash.clear_operations();
+
+    let mut backing = TestReadOnlyFlash::new(&mut flash);
+    let storage = Storage::<_, REGION_SIZE, REGION_COUNT>::open_read_only(
+        &mut backing,
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let metadata = storage.metadata();
+
+    let rejected = Err(StorageIoError::ReadOnly(ReadOnlyFlashError::WriteRejected));
+    assert_eq!(backing.write_region(3, 0, &[0]), rejected);
+    assert_eq!(backing.erase_region(3), rejected);
+    assert_eq!(backing.write_metadata(metadata), rejected);
+    assert_eq!(backing.sync(), rejected);
+    assert_eq!(
+        backing.format_empty_store(2, 8, 0xa5),
+        Err(StorageFormatError::ReadOnly)
+    );
+    let flash = backing.into_inner();
+    assert!(only_reads(flash));
+    assert_eq!(device_image(flash), before);
+}
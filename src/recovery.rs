@@ -0,0 +1,176 @@
+//! Structured report of the recovery work done by an open.
+//!
+//! [`Storage::open_with_report`](crate::Storage::open_with_report) returns a
+//! [`RecoveryReport`] next to the opened storage. It records what replay found
+//! after an interrupted run: torn WAL bytes, transactions that open rolled back
+//! or finished, regions it queued for reclaim, and how much it read. A clean
+//! shutdown produces a report for which [`RecoveryReport::is_clean`] holds.
+
+use heapless::Vec;
+use serde::Serialize;
+
+use crate::flash_io::{FlashIo, StorageFormatError, StorageIoError};
+use crate::storage::TRANSACTION_SLOT_COUNT;
+use crate::{CollectionId, StorageMetadata};
+
+#[cfg(test)]
+mod tests;
+
+/// Transactions one open can resolve: one per transaction-log slot, one
+/// inline rollback, and one finished inline rollback cleanup.
+pub const MAX_RECOVERED_TRANSACTIONS: usize = TRANSACTION_SLOT_COUNT + 2;
+
+/// Torn bytes at the end of the WAL tail that replay discarded.
+///
+/// The bytes stay on flash and every replay skips them again until
+/// [`Storage::append_wal_recovery`](crate::Storage::append_wal_recovery)
+/// closes the boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TornWalTail {
+    /// WAL tail region holding the torn bytes.
+    pub region_index: u32,
+    /// Offset of the first byte that did not decode as a WAL record.
+    pub offset: usize,
+    /// Bytes from `offset` to the first erased record slot.
+    pub len: usize,
+}
+
+/// What open did with an unfinished transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TransactionRecoveryOutcome {
+    /// The transaction never committed; open rolled it back.
+    RolledBack,
+    /// The commit was durable; open finished its region cleanup.
+    CommitFinished,
+    /// The rollback was durable; open finished its region cleanup.
+    RollbackFinished,
+}
+
+/// One transaction resolved by open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TransactionRecovery {
+    /// Transaction-log slot, or `None` for an inline transaction.
+    pub transaction_log_id: Option<u32>,
+    /// Collection the transaction was scoped to, when replay saw it.
+    pub collection_id: Option<CollectionId>,
+    /// Recovery action taken.
+    pub outcome: TransactionRecoveryOutcome,
+}
+
+/// Recovery work done by one open.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecoveryReport<const REGION_COUNT: usize> {
+    /// Main-WAL records decoded by the final replay pass.
+    pub wal_records_replayed: u32,
+    /// Torn bytes discarded at the WAL tail, if any.
+    pub torn_tail: Option<TornWalTail>,
+    /// New WAL tail of an interrupted rotation that open completed.
+    pub completed_rotation: Option<u32>,
+    /// Unfinished transactions, in the order open resolved them.
+    pub transactions: Vec<TransactionRecovery, MAX_RECOVERED_TRANSACTIONS>,
+    /// Regions whose pending reclaim open resumed by appending their
+    /// `free_region` record, in append order.
+    pub reclaimed_regions: Vec<u32, REGION_COUNT>,
+    /// Region bytes read from the backing during the open.
+    pub bytes_read: u64,
+}
+
+impl<const REGION_COUNT: usize> RecoveryReport<REGION_COUNT> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            wal_records_replayed: 0,
+            torn_tail: None,
+            completed_rotation: None,
+            transactions: Vec::new(),
+            reclaimed_regions: Vec::new(),
+            bytes_read: 0,
+        }
+    }
+
+    /// Returns whether open found nothing to recover.
+    pub fn is_clean(&self) -> bool {
+        self.torn_tail.is_none()
+            && self.completed_rotation.is_none()
+            && self.transactions.is_empty()
+            && self.reclaimed_regions.is_empty()
+    }
+}
+
+impl<const REGION_COUNT: usize> Default for RecoveryReport<REGION_COUNT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Backing wrapper that counts region bytes read through it.
+pub(crate) struct ReadCounter<'a, IO> {
+    inner: &'a mut IO,
+    bytes_read: u64,
+}
+
+impl<'a, IO: FlashIo> ReadCounter<'a, IO> {
+    pub(crate) fn new(inner: &'a mut IO) -> Self {
+        Self {
+            inner,
+            bytes_read: 0,
+        }
+    }
+
+    pub(crate) fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+}
+
+impl<IO: FlashIo> FlashIo for ReadCounter<'_, IO> {
+    fn read_metadata(&mut self) -> Result<Option<StorageMetadata>, StorageIoError> {
+        self.inner.read_metadata()
+    }
+
+    fn write_metadata(&mut self, metadata: StorageMetadata) -> Result<(), StorageIoError> {
+        self.inner.write_metadata(metadata)
+    }
+
+    fn read_region<R, F>(
+        &mut self,
+        region_index: u32,
+        offset: usize,
+        len: usize,
+        read: F,
+    ) -> Result<R, StorageIoError>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        let result = self.inner.read_region(region_index, offset, len, read)?;
+        self.bytes_read = self
+            .bytes_read
+            .saturating_add(u64::try_from(len).unwrap_or(u64::MAX));
+        Ok(result)
+    }
+
+    fn write_region(
+        &mut self,
+        region_index: u32,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), StorageIoError> {
+        self.inner.write_region(region_index, offset, data)
+    }
+
+    fn erase_region(&mut self, region_index: u32) -> Result<(), StorageIoError> {
+        self.inner.erase_region(region_index)
+    }
+
+    fn sync(&mut self) -> Result<(), StorageIoError> {
+        self.inner.sync()
+    }
+
+    fn format_empty_store(
+        &mut self,
+        min_free_regions: u32,
+        wal_write_granule: u32,
+        wal_record_magic: u8,
+    ) -> Result<StorageMetadata, StorageFormatError> {
+        self.inner
+            .format_empty_store(min_free_regions, wal_write_granule, wal_record_magic)
+    }
+}
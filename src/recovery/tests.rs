@@ -0,0 +1,268 @@
+use super::*;
+
+use crate::collections::map::MapFrontier;
+use crate::wal_record::decode_record;
+use crate::{LsmMap, MockFlash, MockOperation, Storage, TransactionMemory, WalRecordType};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestStorage<'a> = Storage<'a, 'static, TestFlash, REGION_SIZE, REGION_COUNT>;
+
+fn open_with_report(flash: &mut TestFlash) -> (TestStorage<'_>, RecoveryReport<REGION_COUNT>) {
+    Storage::<_, REGION_SIZE, REGION_COUNT>::open_with_report(flash, crate::test_storage_memory())
+        .unwrap()
+}
+
+/// Creates a map whose only region is one flushed run holding `1 => 100`,
+/// and returns the map and that region.
+///
+/// Unlike [`crate::test_flushed_map`], the map has a single region, so it is
+/// exactly the region a later transactional flush replaces.
+fn single_run_map(storage: &mut TestStorage<'_>) -> (CollectionId, u32) {
+    let collection_id = CollectionId(40);
+    storage.create_map(collection_id).unwrap();
+    let mut buffer = [0u8; REGION_SIZE];
+    let mut map = MapFrontier::<u16, u16, 8>::new(
+        collection_id,
+        &mut buffer,
+        crate::test_map_frontier_memory(),
+    )
+    .unwrap();
+    map.set_in_memory(1, 100).unwrap();
+    let region_index = storage.flush_map(&mut map).unwrap();
+    (collection_id, region_index)
+}
+
+/// Flushes `2 => 200` into a new run inside a transaction on `collection_id`
+/// and returns the run region, leaving the transaction to the caller.
+fn flush_in_transaction<'tx>(
+    storage: &mut TestStorage<'_>,
+    collection_id: CollectionId,
+    transaction: &'tx mut TransactionMemory<REGION_COUNT>,
+) -> (crate::TransactionWriter<'tx, REGION_COUNT>, u32) {
+    let writer = storage
+        .begin_transaction(collection_id, transaction)
+        .unwrap();
+    let mut buffer = [0u8; REGION_SIZE];
+    let mut map = storage
+        .open_map::<u16, u16, 8>(
+            collection_id,
+            &mut buffer,
+            crate::test_map_frontier_memory(),
+        )
+        .unwrap();
+    map.set_in_memory(2, 200).unwrap();
+    let region_index = storage.flush_map(&mut map).unwrap();
+    (writer, region_index)
+}
+
+/// Returns the offset of the first `target` record after the first `after`
+/// record in WAL region `region_index`.
+fn record_offset(
+    flash: &TestFlash,
+    region_index: u32,
+    after: WalRecordType,
+    target: WalRecordType,
+) -> usize {
+    let metadata = *flash.metadata().unwrap();
+    let bytes = flash.region_bytes(region_index).unwrap();
+    let mut scratch = [0u8; REGION_SIZE];
+    let mut offset = metadata.wal_record_area_offset().unwrap();
+    let mut seen_after = false;
+    loop {
+        let decoded = decode_record(&bytes[offset..], metadata, &mut scratch).unwrap();
+        let record_type = decoded.record.record_type();
+        if seen_after && record_type == target {
+            return offset;
+        }
+        seen_after |= record_type == after;
+        offset += decoded.encoded_len;
+    }
+}
+
+/// Erases WAL region `region_index` from `offset` on, as if power failed
+/// before those records were programmed.
+fn cut_wal_at(flash: &mut TestFlash, region_index: u32, offset: usize) {
+    flash
+        .write_region(region_index, offset, &[0xff; REGION_SIZE][offset..])
+        .unwrap();
+}
+
+fn region_bytes_read(flash: &TestFlash) -> u64 {
+    flash
+        .operations()
+        .iter()
+        .map(|operation| match operation {
+            MockOperation::ReadRegion { len, .. } => *len as u64,
+            _ => 0,
+        })
+        .sum()
+}
+
+//= spec/recovery-report.md#recovery-report
+//= type=test
+//# `RING-RECOVERY-001` After a clean shutdown, `Storage::open_with_report`
+//# MUST return a report with no torn tail, no recovered transactions, and
+//# no reclaimed regions, and MUST count every main-WAL record it replayed.
+#[test]
+fn requirement_clean_open_reports_replayed_records_only() {
+    let mut flash = TestFlash::new(0xff);
+    {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut map =
+            LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+        for key in 0..5 {
+            map.set(&mut storage, key, key * 10).unwrap();
+        }
+    }
+
+    let (mut storage, report) = open_with_report(&mut flash);
+    assert!(report.is_clean());
+    assert_eq!(report.torn_tail, None);
+    assert_eq!(report.completed_rotation, None);
+    let mut retained = 0u32;
+    storage
+        .visit_wal_records::<(), _>(|_| {
+            retained += 1;
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(report.wal_records_replayed, retained);
+}
+
+//= spec/recovery-report.md#recovery-report
+//= type=test
+//# `RING-RECOVERY-002` When the WAL tail ends in bytes that do not decode,
+//# the report MUST name the tail region, the offset of the first torn byte,
+//# and the number of bytes up to the first erased record slot.
+#[test]
+fn requirement_torn_tail_is_reported_with_location() {
+    let mut flash = TestFlash::new(0xff);
+    let (tail, offset) = {
+        let storage = crate::test_format_storage(&mut flash);
+        (storage.wal_tail(), storage.wal_append_offset())
+    };
+    flash.write_region(tail, offset, &[0x10; 8]).unwrap();
+
+    let (mut storage, report) = open_with_report(&mut flash);
+    assert_eq!(
+        report.torn_tail,
+        Some(TornWalTail {
+            region_index: tail,
+            offset,
+            len: 8,
+        })
+    );
+    assert!(!report.is_clean());
+    assert!(storage.pending_wal_recovery_boundary());
+
+    storage.append_wal_recovery().unwrap();
+    storage.create_map(CollectionId(41)).unwrap();
+    let (_, report) = open_with_report(&mut flash);
+    assert!(report.is_clean());
+}
+
+//= spec/recovery-report.md#recovery-report
+//= type=test
+//# `RING-RECOVERY-003` When open rolls back an uncommitted transaction, the
+//# report MUST list it as `RolledBack` with its transaction-log slot, and
+//# MUST list every region whose reclaim the rollback queued.
+#[test]
+fn requirement_rolled_back_transaction_and_reclaims_are_reported() {
+    let mut flash = TestFlash::new(0xff);
+    let allocated = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let (collection_id, _) = single_run_map(&mut storage);
+        let mut transaction = TransactionMemory::<REGION_COUNT>::new();
+        let (_writer, allocated) =
+            flush_in_transaction(&mut storage, collection_id, &mut transaction);
+        allocated
+    };
+
+    let (_, report) = open_with_report(&mut flash);
+    assert_eq!(
+        report.transactions.as_slice(),
+        &[TransactionRecovery {
+            transaction_log_id: Some(0),
+            // Only the allocation reached the transaction log before the
+            // crash, so nothing on flash names the collection.
+            collection_id: None,
+            outcome: TransactionRecoveryOutcome::RolledBack,
+        }]
+    );
+    assert!(report.reclaimed_regions.contains(&allocated));
+
+    let (_, report) = open_with_report(&mut flash);
+    assert!(report.is_clean());
+}
+
+//= spec/recovery-report.md#recovery-report
+//= type=test
+//# `RING-RECOVERY-004` When a commit was durable but its cleanup was cut
+//# off, the report MUST list the transaction as `CommitFinished` and MUST
+//# list the replaced regions whose reclaim open resumed.
+#[test]
+fn requirement_finished_commit_cleanup_is_reported() {
+    let mut flash = TestFlash::new(0xff);
+    let (collection_id, replaced, tail) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let (collection_id, replaced) = single_run_map(&mut storage);
+        let mut transaction = TransactionMemory::<REGION_COUNT>::new();
+        let (writer, _) = flush_in_transaction(&mut storage, collection_id, &mut transaction);
+        writer.commit(&mut storage).unwrap();
+        (collection_id, replaced, storage.wal_tail())
+    };
+    let offset = record_offset(
+        &flash,
+        tail,
+        WalRecordType::CommitTransaction,
+        WalRecordType::FreeRegion,
+    );
+    cut_wal_at(&mut flash, tail, offset);
+
+    let (mut storage, report) = open_with_report(&mut flash);
+    assert_eq!(
+        report.transactions.as_slice(),
+        &[TransactionRecovery {
+            transaction_log_id: Some(0),
+            collection_id: Some(collection_id),
+            outcome: TransactionRecoveryOutcome::CommitFinished,
+        }]
+    );
+    assert_eq!(report.reclaimed_regions.as_slice(), &[replaced]);
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(collection_id, &mut storage, crate::test_lsm_map_memory())
+            .unwrap();
+    assert_eq!(
+        map.get(&mut storage, &2, |_, value| *value).unwrap(),
+        Some(200)
+    );
+}
+
+//= spec/recovery-report.md#recovery-report
+//= type=test
+//# `RING-RECOVERY-005` `bytes_read` MUST equal the region bytes the open read
+//# from the backing, and `Storage::open` MUST leave the store in the same
+//# state as `Storage::open_with_report`.
+#[test]
+fn requirement_report_counts_region_bytes_read() {
+    let mut flash = TestFlash::new(0xff);
+    {
+        let mut storage = crate::test_format_storage(&mut flash);
+        single_run_map(&mut storage);
+    }
+
+    flash.clear_operations();
+    let (storage, report) = open_with_report(&mut flash);
+    let (wal_tail, append_offset) = (storage.wal_tail(), storage.wal_append_offset());
+    assert_eq!(report.bytes_read, region_bytes_read(&flash));
+    assert!(report.bytes_read > 0);
+
+    let storage =
+        Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_storage_memory())
+            .unwrap();
+    assert_eq!(storage.wal_tail(), wal_tail);
+    assert_eq!(storage.wal_append_offset(), append_offset);
+}
@@ -0,0 +1,220 @@
+//! Salvage open with per-collection quarantine.
+//!
+//! [`Storage::open_salvage`](crate::Storage::open_salvage) replays the WAL
+//! like [`Storage::open`](crate::Storage::open), but a collection whose
+//! durable state cannot be trusted is quarantined instead of failing the
+//! whole open. Every other collection opens normally. A quarantined
+//! collection rejects all typed access; its regions stay readable as raw
+//! bytes until [`Storage::drop_quarantined`](crate::Storage::drop_quarantined)
+//! drops it and queues its regions for reclaim.
+
+use heapless::Vec;
+use serde::Serialize;
+
+use crate::check::{collect_owned_regions, OwnedRegionsError};
+use crate::flash_io::FlashIo;
+use crate::startup::{StartupCollection, StartupCollectionBasis};
+use crate::storage::{StorageRuntime, StorageRuntimeError};
+use crate::workspace::StorageWorkspace;
+use crate::{CollectionId, Header};
+
+#[cfg(test)]
+mod tests;
+
+/// Why [`Storage::open_salvage`](crate::Storage::open_salvage) quarantined a
+/// collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum QuarantineReason {
+    /// The collection type is unknown or not supported by this build.
+    UnsupportedType(u16),
+    /// The committed head region failed its checksum, names another
+    /// collection, or was rejected by the type's plugin.
+    InvalidHead,
+    /// A registered collection type plugin rejected a retained WAL record.
+    ReplayRejected,
+    /// A region reachable from the committed head is missing or corrupt.
+    InvalidRegions,
+}
+
+/// One collection isolated by a salvage open.
+///
+/// The list returned by
+/// [`Storage::quarantined_collections`](crate::Storage::quarantined_collections)
+/// is the salvage report: each entry is data the store could not recover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QuarantinedCollection {
+    /// Quarantined collection.
+    pub collection_id: CollectionId,
+    /// Collection type retained by the WAL, if one is known.
+    pub collection_type: Option<u16>,
+    /// Committed head region, when the collection basis is a region.
+    pub head_region: Option<u32>,
+    /// First failure found for the collection.
+    pub reason: QuarantineReason,
+}
+
+impl QuarantinedCollection {
+    pub(crate) fn new(collection: &StartupCollection, reason: QuarantineReason) -> Self {
+        Self {
+            collection_id: collection.collection_id(),
+            collection_type: collection.collection_type(),
+            head_region: match collection.basis() {
+                StartupCollectionBasis::Region(region_index) => Some(region_index),
+                _ => None,
+            },
+            reason,
+        }
+    }
+}
+
+/// Quarantines every live region-based collection whose committed regions
+/// do not form a readable structure.
+///
+/// Only corruption quarantines a collection; backend failures are returned.
+pub(crate) fn quarantine_invalid_regions<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &mut StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    owned: &mut Vec<u32, REGION_COUNT>,
+) -> Result<(), StorageRuntimeError> {
+    let mut index = 0;
+    while let Some(collection) = storage.collections().get(index).copied() {
+        index += 1;
+        let StartupCollectionBasis::Region(head_region) = collection.basis() else {
+            continue;
+        };
+        if storage.is_quarantined(collection.collection_id()) {
+            continue;
+        }
+        // The salvage startup pass already verified this header.
+        let Ok(header) = flash.read_region(head_region, 0, Header::ENCODED_LEN, Header::decode)?
+        else {
+            continue;
+        };
+        owned.clear();
+        let result = collect_owned_regions::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            workspace,
+            collection,
+            head_region,
+            header.collection_format,
+            owned,
+            |_| {},
+        );
+        match result {
+            Ok(()) => {}
+            Err(OwnedRegionsError::Storage(error)) => return Err(error),
+            Err(OwnedRegionsError::InvalidMap(_) | OwnedRegionsError::InvalidCommittedRegions) => {
+                storage.quarantine(QuarantinedCollection::new(
+                    &collection,
+                    QuarantineReason::InvalidRegions,
+                ))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether `region_index` holds raw data of the quarantined
+/// `collection`: its head, or any allocated region whose header names it.
+fn is_quarantined_region<IO: FlashIo, const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    collection: &QuarantinedCollection,
+    region_index: u32,
+) -> Result<bool, StorageRuntimeError> {
+    if collection.head_region == Some(region_index) {
+        return Ok(true);
+    }
+    if storage.free_space().contains_free_region(region_index)
+        || storage.is_region_retired(region_index)
+        || storage.ready_region() == Some(region_index)
+    {
+        return Ok(false);
+    }
+    let header = flash.read_region(region_index, 0, Header::ENCODED_LEN, Header::decode)?;
+    Ok(header.is_ok_and(|header| header.collection_id == collection.collection_id))
+}
+
+fn quarantined_collection<const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+) -> Result<QuarantinedCollection, StorageRuntimeError> {
+    storage
+        .quarantined_collections()
+        .iter()
+        .find(|collection| collection.collection_id == collection_id)
+        .copied()
+        .ok_or(StorageRuntimeError::CollectionNotQuarantined(collection_id))
+}
+
+/// Passes the full image of every region holding data of a quarantined
+/// collection to `visitor`, in region order.
+pub(crate) fn visit_quarantined_regions<
+    const REGION_SIZE: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    collection_id: CollectionId,
+    mut visitor: impl FnMut(u32, &[u8]),
+) -> Result<(), StorageRuntimeError> {
+    let collection = quarantined_collection(storage, collection_id)?;
+    for region_index in 0..storage.metadata().region_count {
+        if !is_quarantined_region(storage, flash, &collection, region_index)? {
+            continue;
+        }
+        flash.read_region(region_index, 0, REGION_SIZE, |bytes| {
+            visitor(region_index, bytes)
+        })?;
+    }
+    Ok(())
+}
+
+/// Drops a quarantined collection and queues every region holding its data
+/// for reclaim, returning the number of regions queued.
+///
+/// Regions beyond the head are freed first, so an interrupted drop leaves the
+/// collection live and quarantined again on the next salvage open.
+pub(crate) fn drop_quarantined<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &mut StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    collection_id: CollectionId,
+) -> Result<u32, StorageRuntimeError> {
+    let collection = quarantined_collection(storage, collection_id)?;
+    let mut freed = 0u32;
+    for region_index in 0..storage.metadata().region_count {
+        if collection.head_region == Some(region_index)
+            || !is_quarantined_region(storage, flash, &collection, region_index)?
+        {
+            continue;
+        }
+        storage.append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            CollectionId(0),
+            region_index,
+        )?;
+        freed = freed.saturating_add(1);
+    }
+    let head = storage.drop_collection_and_begin_reclaim::<REGION_SIZE, REGION_COUNT, IO>(
+        flash,
+        workspace,
+        collection_id,
+    )?;
+    storage.release_quarantine(collection_id);
+    Ok(freed.saturating_add(u32::from(head.is_some())))
+}
@@ -0,0 +1,433 @@
+use super::*;
+
+use crate::collections::cell::{Cell, CellError};
+use crate::collections::map::{MapStorageError, MAP_RUN_V2_FORMAT};
+use crate::{
+    CheckFinding, CollectionPluginError, CollectionType, CollectionTypePlugin, LsmMap, MockFlash,
+    MockOperation, Storage, StorageOpenError, TransactionMemory, WalRecord,
+};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+const COUNTER_CODE: u16 = 0x8001;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestStorage<'a> = Storage<'a, 'static, TestFlash, REGION_SIZE, REGION_COUNT>;
+
+/// Plugin whose replay hook accepts only eight-byte updates.
+struct CounterPlugin;
+
+impl CollectionTypePlugin for CounterPlugin {
+    fn code(&self) -> u16 {
+        COUNTER_CODE
+    }
+
+    fn empty_snapshot(&self) -> &'static [u8] {
+        &[0; 8]
+    }
+
+    fn replay(
+        &self,
+        _collection_id: CollectionId,
+        record: WalRecord<'_>,
+    ) -> Result<(), CollectionPluginError> {
+        match record {
+            WalRecord::Update { payload, .. } if payload.len() != 8 => Err(CollectionPluginError),
+            _ => Ok(()),
+        }
+    }
+}
+
+static COUNTER_TYPES: [&dyn CollectionTypePlugin; 1] = [&CounterPlugin];
+
+fn salvage_storage(flash: &mut TestFlash) -> TestStorage<'_> {
+    Storage::<_, REGION_SIZE, REGION_COUNT>::open_salvage(
+        flash,
+        crate::test_plugin_storage_memory(&COUNTER_TYPES),
+    )
+    .unwrap()
+}
+
+/// Creates a cell whose value is large enough to live in a committed region.
+fn region_cell(storage: &mut TestStorage<'_>, fill: u8) -> CollectionId {
+    let mut cell = Cell::new(storage, std::vec![0u8; 4]).unwrap();
+    cell.set(storage, std::vec![fill; 300]).unwrap();
+    assert!(cell.region_index().is_some());
+    cell.collection_id()
+}
+
+fn cell_value(
+    storage: &mut TestStorage<'_>,
+    collection_id: CollectionId,
+) -> Result<std::vec::Vec<u8>, CellError> {
+    Cell::<std::vec::Vec<u8>>::open(collection_id, storage).map(|cell| cell.get().clone())
+}
+
+fn map_value(
+    storage: &mut TestStorage<'_>,
+    collection_id: CollectionId,
+    key: u16,
+) -> Result<Option<u16>, MapStorageError> {
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(collection_id, storage, crate::test_lsm_map_memory())?;
+    map.get(storage, &key, |_, value| *value)
+}
+
+/// Returns the regions outside the free queue whose header names
+/// `collection_id` and `format`.
+fn live_regions_with_format(
+    storage: &mut TestStorage<'_>,
+    collection_id: CollectionId,
+    format: u16,
+) -> std::vec::Vec<u32> {
+    let free_space = storage.runtime().free_space().clone();
+    storage.with_io_workspace(|flash, _| {
+        (0..REGION_COUNT as u32)
+            .filter(|region_index| !free_space.contains_free_region(*region_index))
+            .filter(|region_index| {
+                let bytes = flash.region_bytes(*region_index).unwrap();
+                Header::decode(&bytes[..Header::ENCODED_LEN]).is_ok_and(|header| {
+                    header.collection_id == collection_id && header.collection_format == format
+                })
+            })
+            .collect()
+    })
+}
+
+/// Formats a store with two region cells and a flushed map, then corrupts the
+/// header of the map's manifest region.
+fn store_with_corrupt_map(flash: &mut TestFlash) -> (CollectionId, CollectionId, CollectionId) {
+    let mut storage = crate::test_format_plugin_storage(flash, &COUNTER_TYPES);
+    let first = region_cell(&mut storage, 0x11);
+    let map = crate::test_flushed_map(&mut storage);
+    let second = region_cell(&mut storage, 0x22);
+    let manifest = crate::test_head_region(&storage, map);
+    crate::test_flip_byte(&mut storage, manifest, 2);
+    (first, map, second)
+}
+
+//= spec/salvage.md#salvage-open
+//= type=test
+//# `RING-SALVAGE-001` When one collection's head region is corrupt,
+//# `Storage::open_salvage` MUST succeed, MUST quarantine that collection
+//# with `InvalidHead`, and MUST open every other collection with its
+//# committed contents, while `Storage::open` keeps failing.
+#[test]
+fn requirement_salvage_open_quarantines_corrupt_head() {
+    let mut flash = TestFlash::new(0xff);
+    let (first, map, second) = store_with_corrupt_map(&mut flash);
+    assert!(Storage::<_, REGION_SIZE, REGION_COUNT>::open(
+        &mut flash,
+        crate::test_plugin_storage_memory(&COUNTER_TYPES)
+    )
+    .is_err());
+
+    {
+        let mut storage = salvage_storage(&mut flash);
+        assert_eq!(
+            storage.quarantined_collections(),
+            [QuarantinedCollection {
+                collection_id: map,
+                collection_type: Some(CollectionType::MAP_CODE),
+                head_region: Some(crate::test_head_region(&storage, map)),
+                reason: QuarantineReason::InvalidHead,
+            }]
+        );
+        assert_eq!(
+            cell_value(&mut storage, first).unwrap(),
+            std::vec![0x11; 300]
+        );
+        assert_eq!(
+            cell_value(&mut storage, second).unwrap(),
+            std::vec![0x22; 300]
+        );
+    }
+    assert!(Storage::<_, REGION_SIZE, REGION_COUNT>::open(
+        &mut flash,
+        crate::test_plugin_storage_memory(&COUNTER_TYPES)
+    )
+    .is_err());
+}
+
+//= spec/salvage.md#salvage-open
+//= type=test
+//# `RING-SALVAGE-002` A live collection whose type this build does not
+//# support MUST be quarantined with `UnsupportedType`, and a live plugin
+//# collection whose retained record the plugin rejects MUST be quarantined
+//# with `ReplayRejected`.
+#[test]
+fn requirement_salvage_open_quarantines_unsupported_and_rejected_types() {
+    let mut flash = TestFlash::new(0xff);
+    let (cell, accepted, rejected) = {
+        let mut storage = crate::test_format_plugin_storage(&mut flash, &COUNTER_TYPES);
+        let cell = region_cell(&mut storage, 0x33);
+        let accepted = storage.allocate_collection_id().unwrap();
+        storage
+            .append_new_collection(accepted, COUNTER_CODE)
+            .unwrap();
+        storage
+            .append_update(accepted, &7u64.to_le_bytes())
+            .unwrap();
+        let rejected = storage.allocate_collection_id().unwrap();
+        storage
+            .append_new_collection(rejected, COUNTER_CODE)
+            .unwrap();
+        storage.append_update(rejected, b"bad").unwrap();
+        (cell, accepted, rejected)
+    };
+    assert!(matches!(
+        Storage::<_, REGION_SIZE, REGION_COUNT>::open(&mut flash, crate::test_plugin_storage_memory(&COUNTER_TYPES)),
+        Err(StorageOpenError::CollectionReplayRejected { collection_id, .. })
+            if collection_id == rejected
+    ));
+
+    {
+        let mut storage = salvage_storage(&mut flash);
+        assert_eq!(
+            storage.quarantined_collections(),
+            [QuarantinedCollection {
+                collection_id: rejected,
+                collection_type: Some(COUNTER_CODE),
+                head_region: None,
+                reason: QuarantineReason::ReplayRejected,
+            }]
+        );
+        assert!(!storage.runtime().is_quarantined(accepted));
+        assert_eq!(
+            cell_value(&mut storage, cell).unwrap(),
+            std::vec![0x33; 300]
+        );
+    }
+
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::open_salvage(
+        &mut flash,
+        crate::test_storage_memory(),
+    )
+    .unwrap();
+    let reasons = storage
+        .quarantined_collections()
+        .iter()
+        .map(|collection| (collection.collection_id, collection.reason))
+        .collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        reasons,
+        [
+            (accepted, QuarantineReason::UnsupportedType(COUNTER_CODE)),
+            (rejected, QuarantineReason::UnsupportedType(COUNTER_CODE)),
+        ]
+    );
+    assert_eq!(
+        cell_value(&mut storage, cell).unwrap(),
+        std::vec![0x33; 300]
+    );
+}
+
+//= spec/salvage.md#salvage-open
+//= type=test
+//# `RING-SALVAGE-003` A region-based collection whose head is intact but
+//# which reaches a corrupt region MUST be quarantined with
+//# `InvalidRegions`.
+#[test]
+fn requirement_salvage_open_quarantines_corrupt_reachable_region() {
+    let mut flash = TestFlash::new(0xff);
+    let (cell, map) = {
+        let mut storage = crate::test_format_plugin_storage(&mut flash, &COUNTER_TYPES);
+        let cell = region_cell(&mut storage, 0x44);
+        let map = crate::test_flushed_map(&mut storage);
+        let run = live_regions_with_format(&mut storage, map, MAP_RUN_V2_FORMAT)[0];
+        crate::test_flip_byte(&mut storage, run, 2);
+        (cell, map)
+    };
+
+    let mut storage = salvage_storage(&mut flash);
+    assert_eq!(
+        storage.quarantined_collections(),
+        [QuarantinedCollection {
+            collection_id: map,
+            collection_type: Some(CollectionType::MAP_CODE),
+            head_region: Some(crate::test_head_region(&storage, map)),
+            reason: QuarantineReason::InvalidRegions,
+        }]
+    );
+    assert_eq!(
+        cell_value(&mut storage, cell).unwrap(),
+        std::vec![0x44; 300]
+    );
+}
+
+//= spec/salvage.md#salvage-open
+//= type=test
+//# `RING-SALVAGE-004` On an undamaged store, `Storage::open_salvage` MUST
+//# quarantine nothing and MUST NOT write, erase, or sync flash.
+#[test]
+fn requirement_salvage_open_of_clean_store_is_read_only() {
+    let mut flash = TestFlash::new(0xff);
+    let (cell, map) = {
+        let mut storage = crate::test_format_plugin_storage(&mut flash, &COUNTER_TYPES);
+        (
+            region_cell(&mut storage, 0x55),
+            crate::test_flushed_map(&mut storage),
+        )
+    };
+    let collections = {
+        let storage = Storage::<_, REGION_SIZE, REGION_COUNT>::open(
+            &mut flash,
+            crate::test_plugin_storage_memory(&COUNTER_TYPES),
+        )
+        .unwrap();
+        storage.collections().to_vec()
+    };
+
+    flash.clear_operations();
+    let mut storage = salvage_storage(&mut flash);
+    let operations = storage.with_io_workspace(|flash, _| flash.operations().to_vec());
+    assert!(operations.iter().all(|operation| matches!(
+        operation,
+        MockOperation::ReadMetadata | MockOperation::ReadRegion { .. }
+    )));
+    assert!(storage.quarantined_collections().is_empty());
+    assert_eq!(storage.collections(), collections.as_slice());
+    assert_eq!(
+        cell_value(&mut storage, cell).unwrap(),
+        std::vec![0x55; 300]
+    );
+    assert_eq!(map_value(&mut storage, map, 25).unwrap(), Some(1025));
+}
+
+//= spec/salvage.md#quarantined-collections
+//= type=test
+//# `RING-SALVAGE-005` Every typed handle, WAL append, and transaction on a
+//# quarantined collection MUST fail with `QuarantinedCollection`, and its
+//# collection id MUST stay reserved.
+#[test]
+fn requirement_quarantined_collection_rejects_access() {
+    let mut flash = TestFlash::new(0xff);
+    let (_, map, _) = store_with_corrupt_map(&mut flash);
+    let quarantined = StorageRuntimeError::QuarantinedCollection(map);
+
+    let mut storage = salvage_storage(&mut flash);
+    assert!(matches!(
+        map_value(&mut storage, map, 0),
+        Err(MapStorageError::Storage(error)) if error == quarantined
+    ));
+    assert!(matches!(
+        cell_value(&mut storage, map),
+        Err(CellError::Storage(error)) if error == quarantined
+    ));
+    assert_eq!(
+        storage.append_update(map, b"value").unwrap_err(),
+        quarantined
+    );
+    assert_eq!(
+        storage
+            .append_snapshot(map, CollectionType::MAP_CODE, b"value")
+            .unwrap_err(),
+        quarantined
+    );
+    let mut transaction = TransactionMemory::<REGION_COUNT>::new();
+    assert!(matches!(
+        storage.begin_transaction(map, &mut transaction),
+        Err(error) if error == quarantined
+    ));
+    assert!(storage.allocate_collection_id().unwrap() > map);
+}
+
+//= spec/salvage.md#quarantined-collections
+//= type=test
+//# `RING-SALVAGE-006` `Storage::read_quarantined_regions` MUST pass the
+//# unverified image of the head region and of every allocated region whose
+//# header names the collection, in region order, and MUST reject a
+//# collection that is not quarantined with `CollectionNotQuarantined`.
+#[test]
+fn requirement_read_quarantined_regions_returns_raw_images() {
+    let mut flash = TestFlash::new(0xff);
+    let (first, map, _) = store_with_corrupt_map(&mut flash);
+
+    let mut storage = salvage_storage(&mut flash);
+    let mut expected = live_regions_with_format(&mut storage, map, MAP_RUN_V2_FORMAT);
+    assert_eq!(expected.len(), 2);
+    expected.push(crate::test_head_region(&storage, map));
+    expected.sort_unstable();
+
+    let mut visited = std::vec::Vec::new();
+    let mut images = std::vec::Vec::new();
+    storage
+        .read_quarantined_regions(map, |region_index, bytes| {
+            visited.push(region_index);
+            images.push(bytes.to_vec());
+        })
+        .unwrap();
+    assert_eq!(visited, expected);
+    for (region_index, image) in visited.iter().zip(&images) {
+        let stored =
+            storage.with_io_workspace(|flash, _| *flash.region_bytes(*region_index).unwrap());
+        assert_eq!(image.as_slice(), stored.as_slice());
+    }
+
+    assert_eq!(
+        storage
+            .read_quarantined_regions(first, |_, _| {})
+            .unwrap_err(),
+        StorageRuntimeError::CollectionNotQuarantined(first)
+    );
+}
+
+//= spec/salvage.md#quarantined-collections
+//= type=test
+//# `RING-SALVAGE-007` `Storage::drop_quarantined` MUST durably drop the
+//# collection, MUST queue every region `read_quarantined_regions` visits
+//# for reclaim, and MUST remove the collection from the report, after which
+//# `Storage::open` MUST succeed.
+#[test]
+fn requirement_drop_quarantined_reclaims_every_region() {
+    let mut flash = TestFlash::new(0xff);
+    let (first, map, second) = store_with_corrupt_map(&mut flash);
+    {
+        let mut storage = salvage_storage(&mut flash);
+        let mut regions = std::vec::Vec::new();
+        storage
+            .read_quarantined_regions(map, |region_index, _| regions.push(region_index))
+            .unwrap();
+        assert_eq!(
+            storage.drop_quarantined(first).unwrap_err(),
+            StorageRuntimeError::CollectionNotQuarantined(first)
+        );
+
+        assert_eq!(
+            storage.drop_quarantined(map).unwrap() as usize,
+            regions.len()
+        );
+        assert!(storage.quarantined_collections().is_empty());
+        for region_index in regions {
+            assert!(storage
+                .runtime()
+                .free_space()
+                .contains_free_region(region_index));
+        }
+    }
+
+    let mut storage = Storage::<_, REGION_SIZE, REGION_COUNT>::open(
+        &mut flash,
+        crate::test_plugin_storage_memory(&COUNTER_TYPES),
+    )
+    .unwrap();
+    let basis = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == map)
+        .map(|collection| collection.basis());
+    assert_eq!(basis, Some(StartupCollectionBasis::Dropped));
+    let mut findings = std::vec::Vec::<CheckFinding>::new();
+    storage
+        .check(&mut |finding| findings.push(finding))
+        .unwrap();
+    assert!(findings.is_empty(), "{findings:?}");
+    assert_eq!(
+        cell_value(&mut storage, first).unwrap(),
+        std::vec![0x11; 300]
+    );
+    assert_eq!(
+        cell_value(&mut storage, second).unwrap(),
+        std::vec![0x22; 300]
+    );
+}
@@ -0,0 +1,398 @@
+//! Incremental background scrub.
+//!
+//! [`Storage::scrub`](crate::Storage::scrub) reads a bounded number of live
+//! regions per call and verifies every checksum their format carries, so bit
+//! rot in rarely read regions surfaces before a `get` trips over it. Each call
+//! appends its [`ScrubCursor`] to the WAL as a `scrub_progress` record, and
+//! open restores the last one, so a pass resumes across resets.
+
+use heapless::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::check::{collect_owned_regions, scan_wal_region, OwnedRegionsError};
+use crate::collections::cell::{self, CELL_VALUE_V1_FORMAT};
+use crate::collections::channel::{self, CHANNEL_SEGMENT_V1_FORMAT};
+use crate::collections::map::{self, MAP_RUN_V2_FORMAT};
+use crate::collections::object_log::{self, OBJECT_LOG_AUX_V1_FORMAT, OBJECT_LOG_DATA_V1_FORMAT};
+use crate::collections::queue::{self, QUEUE_SEGMENT_V1_FORMAT};
+use crate::collections::ring_log::{self, RING_LOG_SEGMENT_V1_FORMAT};
+use crate::collections::time_series::{self, TIME_SERIES_SEGMENT_V1_FORMAT};
+use crate::disk::{
+    decode_free_space_wear_counts, free_space_entries_checksum, FreeSpaceRegionPrologue,
+    FreeSpaceWearPrologue, LogRegionPrologue, FREE_SPACE_V2_FORMAT, FREE_SPACE_WEAR_V1_FORMAT,
+    MAIN_WAL_V2_FORMAT, TRANSACTION_LOG_V2_FORMAT,
+};
+use crate::flash_io::FlashIo;
+use crate::startup::{StartupCollectionBasis, StartupError};
+use crate::storage::{StorageRuntime, StorageRuntimeError};
+use crate::workspace::StorageWorkspace;
+use crate::{CollectionId, Header};
+
+#[cfg(test)]
+mod tests;
+
+/// Position of an incremental scrub, as returned by
+/// [`Storage::scrub_cursor`](crate::Storage::scrub_cursor).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrubCursor {
+    /// Region the next call examines first.
+    pub next_region: u32,
+    /// Full passes over the region space completed so far.
+    pub completed_passes: u32,
+}
+
+impl ScrubCursor {
+    /// Returns a cursor at the start of the first pass.
+    pub const fn new() -> Self {
+        Self {
+            next_region: 0,
+            completed_passes: 0,
+        }
+    }
+}
+
+/// Checksum or framing a scrubbed region failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ScrubDamageKind {
+    /// The region header failed its checksum or could not be decoded.
+    Header,
+    /// The log prologue of a main-WAL or transaction-log region failed its
+    /// checksum.
+    LogPrologue,
+    /// A WAL record failed to decode and no later `wal_recovery` record
+    /// closes the gap.
+    WalRecord {
+        /// Byte offset of the first damaged granule.
+        offset: usize,
+    },
+    /// A free-space metadata prologue or its entries failed their checksum.
+    FreeSpaceRegion,
+    /// A wear-table prologue or its erase counts failed their checksum.
+    WearTable,
+    /// A map run segment does not frame its keys and entries.
+    RunSegment,
+    /// An object-log record, chunk slot, or auxiliary link failed its
+    /// checksum.
+    ObjectLogRecord {
+        /// Byte offset of the damaged frame.
+        offset: usize,
+    },
+    /// A channel, queue, ring-log, or time-series segment, or a cell value
+    /// region, does not frame its contents or failed its trailing checksum.
+    CollectionChecksum,
+}
+
+/// One damaged region found by [`Storage::scrub`](crate::Storage::scrub).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ScrubDamage {
+    /// Region holding the damaged bytes.
+    pub region_index: u32,
+    /// Collection that owns the region, or `None` when the header is damaged
+    /// and no live state names the region. Storage-owned regions report
+    /// collection id zero.
+    pub collection_id: Option<CollectionId>,
+    /// What failed to verify.
+    pub kind: ScrubDamageKind,
+}
+
+/// Receives damaged regions from [`Storage::scrub`](crate::Storage::scrub).
+pub trait ScrubReportSink {
+    /// Records one damaged region.
+    fn report(&mut self, damage: ScrubDamage);
+}
+
+impl<F: FnMut(ScrubDamage)> ScrubReportSink for F {
+    fn report(&mut self, damage: ScrubDamage) {
+        self(damage);
+    }
+}
+
+/// Keeps the first `N` damaged regions and drops the rest; the report still
+/// counts them.
+impl<const N: usize> ScrubReportSink for Vec<ScrubDamage, N> {
+    fn report(&mut self, damage: ScrubDamage) {
+        let _ = self.push(damage);
+    }
+}
+
+/// Totals for one [`Storage::scrub`](crate::Storage::scrub) call.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ScrubReport {
+    /// Regions read from flash, counted against the call's budget.
+    pub regions_read: u32,
+    /// Damaged regions sent to the sink.
+    pub damaged_regions: u32,
+    /// Whether the cursor wrapped to the start of a new pass.
+    pub pass_completed: bool,
+}
+
+/// Advances `cursor` no further than the end of the current pass, reading at
+/// most `max_regions` live regions and reporting each damaged one to `sink`.
+///
+/// Queued and retired regions are skipped without I/O. `regions` is scratch
+/// for resolving the owner of a region whose header is damaged.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn scrub_storage<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+    S: ScrubReportSink + ?Sized,
+>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    regions: &mut Vec<u32, REGION_COUNT>,
+    cursor: &mut ScrubCursor,
+    max_regions: u32,
+    sink: &mut S,
+) -> Result<ScrubReport, StorageRuntimeError> {
+    let metadata = storage.metadata();
+    let mut report = ScrubReport::default();
+    if cursor.next_region >= metadata.region_count {
+        cursor.next_region = 0;
+    }
+
+    while !report.pass_completed && report.regions_read < max_regions {
+        let region_index = cursor.next_region;
+        cursor.next_region = region_index.saturating_add(1);
+        if cursor.next_region >= metadata.region_count {
+            cursor.next_region = 0;
+            cursor.completed_passes = cursor.completed_passes.saturating_add(1);
+            report.pass_completed = true;
+        }
+        if storage.free_space().contains_free_region(region_index)
+            || storage.is_region_retired(region_index)
+        {
+            continue;
+        }
+
+        report.regions_read = report.regions_read.saturating_add(1);
+        let (region_bytes, _) = workspace.scan_buffers();
+        flash.read_region(region_index, 0, region_bytes.len(), |bytes| {
+            region_bytes.copy_from_slice(bytes);
+        })?;
+        if region_bytes
+            .iter()
+            .all(|byte| *byte == metadata.erased_byte)
+        {
+            continue;
+        }
+        let Some((collection_id, kind)) =
+            verify_region::<REGION_SIZE, MAX_COLLECTIONS>(storage, workspace, region_index)?
+        else {
+            continue;
+        };
+        let collection_id = match collection_id {
+            Some(collection_id) => Some(collection_id),
+            None => region_owner::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+                storage,
+                flash,
+                workspace,
+                regions,
+                region_index,
+            )?,
+        };
+        report.damaged_regions = report.damaged_regions.saturating_add(1);
+        sink.report(ScrubDamage {
+            region_index,
+            collection_id,
+            kind,
+        });
+    }
+    Ok(report)
+}
+
+/// Verifies the region image in the workspace scan buffer.
+///
+/// Returns the damage found, with the owner the header names when the header
+/// itself verified.
+fn verify_region<const REGION_SIZE: usize, const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    region_index: u32,
+) -> Result<Option<(Option<CollectionId>, ScrubDamageKind)>, StorageRuntimeError> {
+    let metadata = storage.metadata();
+    let region_size = usize::try_from(metadata.region_size)
+        .ok()
+        .filter(|region_size| *region_size <= REGION_SIZE)
+        .ok_or(StorageRuntimeError::Startup(StartupError::LengthOverflow))?;
+    let (region_bytes, logical_scratch) = workspace.scan_buffers();
+    let region_bytes = &region_bytes[..region_size];
+    let Ok(header) = Header::decode(&region_bytes[..Header::ENCODED_LEN]) else {
+        return Ok(Some((None, ScrubDamageKind::Header)));
+    };
+    let payload = &region_bytes[Header::ENCODED_LEN..];
+    let region_count = metadata.region_count;
+
+    let kind = match header.collection_format {
+        MAIN_WAL_V2_FORMAT | TRANSACTION_LOG_V2_FORMAT
+            if LogRegionPrologue::decode(payload, region_count).is_err() =>
+        {
+            Some(ScrubDamageKind::LogPrologue)
+        }
+        MAIN_WAL_V2_FORMAT => {
+            let is_tail = region_index == storage.wal_tail();
+            let limit = if is_tail {
+                storage.wal_append_offset()
+            } else {
+                region_size
+            };
+            let mut closed_corrupt_offset = None;
+            let scan = scan_wal_region(metadata, region_bytes, limit, logical_scratch, |offset| {
+                closed_corrupt_offset.get_or_insert(offset);
+            })?;
+            // Replay leaves a torn tail open until the next append closes it.
+            let open_corrupt_offset = scan
+                .open_corrupt_offset
+                .filter(|_| !(is_tail && storage.pending_wal_recovery_boundary()));
+            closed_corrupt_offset
+                .or(open_corrupt_offset)
+                .map(|offset| ScrubDamageKind::WalRecord { offset })
+        }
+        FREE_SPACE_V2_FORMAT => {
+            let intact = FreeSpaceRegionPrologue::decode(payload, region_count)
+                .ok()
+                .and_then(|prologue| {
+                    let start = FreeSpaceRegionPrologue::ENCODED_LEN;
+                    let len = usize::try_from(prologue.entry_count)
+                        .ok()?
+                        .checked_mul(size_of::<u32>())?;
+                    let entries = payload.get(start..start.checked_add(len)?)?;
+                    Some(free_space_entries_checksum(entries) == prologue.entries_checksum)
+                })
+                .unwrap_or(false);
+            (!intact).then_some(ScrubDamageKind::FreeSpaceRegion)
+        }
+        FREE_SPACE_WEAR_V1_FORMAT => {
+            let intact = FreeSpaceWearPrologue::decode(payload, region_count)
+                .ok()
+                .and_then(|prologue| {
+                    let counts = payload.get(FreeSpaceWearPrologue::ENCODED_LEN..)?;
+                    decode_free_space_wear_counts(counts, prologue, |_| {}).ok()
+                })
+                .is_some();
+            (!intact).then_some(ScrubDamageKind::WearTable)
+        }
+        MAP_RUN_V2_FORMAT => {
+            (!map::run_segment_frames(payload)).then_some(ScrubDamageKind::RunSegment)
+        }
+        OBJECT_LOG_DATA_V1_FORMAT | OBJECT_LOG_AUX_V1_FORMAT => {
+            object_log::verify_committed_region_checksums::<REGION_SIZE>(
+                metadata,
+                header.collection_format,
+                region_bytes,
+            )
+            .err()
+            .map(|offset| ScrubDamageKind::ObjectLogRecord { offset })
+        }
+        CHANNEL_SEGMENT_V1_FORMAT => (!channel::segment_checksum_matches(payload))
+            .then_some(ScrubDamageKind::CollectionChecksum),
+        QUEUE_SEGMENT_V1_FORMAT => (!queue::segment_checksum_matches(payload))
+            .then_some(ScrubDamageKind::CollectionChecksum),
+        RING_LOG_SEGMENT_V1_FORMAT => (!ring_log::segment_checksum_matches(payload))
+            .then_some(ScrubDamageKind::CollectionChecksum),
+        TIME_SERIES_SEGMENT_V1_FORMAT => (!time_series::segment_checksum_matches(payload))
+            .then_some(ScrubDamageKind::CollectionChecksum),
+        CELL_VALUE_V1_FORMAT => {
+            (!cell::value_checksum_matches(payload)).then_some(ScrubDamageKind::CollectionChecksum)
+        }
+        _ => None,
+    };
+    Ok(kind.map(|kind| (Some(header.collection_id), kind)))
+}
+
+/// Finds the owner of a region whose header no longer names it: storage
+/// metadata and the WAL chain report collection id zero, then each live
+/// collection's committed regions are searched.
+fn region_owner<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    regions: &mut Vec<u32, REGION_COUNT>,
+    region_index: u32,
+) -> Result<Option<CollectionId>, StorageRuntimeError> {
+    let free_space = storage.free_space();
+    if free_space.metadata_regions().contains(&region_index)
+        || free_space.wear_regions().contains(&region_index)
+        || wal_chain_contains::<REGION_SIZE, IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            workspace,
+            region_index,
+        )?
+    {
+        return Ok(Some(CollectionId(0)));
+    }
+
+    for collection in storage.collections().iter().copied() {
+        let StartupCollectionBasis::Region(head_region) = collection.basis() else {
+            continue;
+        };
+        if head_region == region_index {
+            return Ok(Some(collection.collection_id()));
+        }
+        let head_format = flash.read_region(head_region, 0, Header::ENCODED_LEN, |bytes| {
+            Header::decode(bytes).map(|header| header.collection_format)
+        })?;
+        let Ok(head_format) = head_format else {
+            continue;
+        };
+        regions.clear();
+        // A collection whose state fails to enumerate still names the
+        // regions collected before the failure.
+        let result = collect_owned_regions::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            workspace,
+            collection,
+            head_region,
+            head_format,
+            regions,
+            |_| {},
+        );
+        if let Err(OwnedRegionsError::Storage(error)) = result {
+            return Err(error);
+        }
+        if regions.contains(&region_index) {
+            return Ok(Some(collection.collection_id()));
+        }
+    }
+    Ok(None)
+}
+
+/// Follows the WAL from head to tail through `link` records and returns
+/// whether `region_index` is part of the chain.
+fn wal_chain_contains<const REGION_SIZE: usize, IO: FlashIo, const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    region_index: u32,
+) -> Result<bool, StorageRuntimeError> {
+    let metadata = storage.metadata();
+    let mut current_region = storage.wal_head();
+    for _ in 0..metadata.region_count {
+        if current_region == region_index {
+            return Ok(true);
+        }
+        if current_region == storage.wal_tail() || current_region >= metadata.region_count {
+            break;
+        }
+        let (region_bytes, logical_scratch) = workspace.scan_buffers();
+        flash.read_region(current_region, 0, region_bytes.len(), |bytes| {
+            region_bytes.copy_from_slice(bytes);
+        })?;
+        let limit = usize::try_from(metadata.region_size).unwrap_or(REGION_SIZE);
+        let scan = scan_wal_region(metadata, region_bytes, limit, logical_scratch, |_| {})?;
+        let Some(next_region) = scan.next_region else {
+            break;
+        };
+        current_region = next_region;
+    }
+    Ok(false)
+}
@@ -0,0 +1,459 @@
+use super::*;
+
+use crate::collections::cell::Cell;
+use crate::collections::channel::{
+    AddCommand, Channel, CommandAddress, MemberId, MemberSequence, MessageId,
+};
+use crate::collections::object_log::{ObjectLog, ObjectLogMemory};
+use crate::collections::queue::{DurableQueue, DurableQueueMemory};
+use crate::collections::ring_log::{RingLog, RingLogMemory};
+use crate::collections::time_series::{TimeSeries, TimeSeriesMemory};
+use crate::vec_like::VecLikeSlice;
+use crate::{CollectionType, MockFlash, MockOperation, Storage};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestStorage<'a> = Storage<'a, 'static, TestFlash, REGION_SIZE, REGION_COUNT>;
+type Damages = Vec<ScrubDamage, 32>;
+
+/// Runs one full pass from the start of the region space.
+fn scrub_pass(storage: &mut TestStorage<'_>) -> (ScrubReport, Damages) {
+    assert_eq!(storage.scrub_cursor().next_region, 0);
+    let mut damages = Damages::new();
+    let report = storage.scrub(u32::MAX, &mut damages).unwrap();
+    assert!(report.pass_completed);
+    assert_eq!(report.damaged_regions as usize, damages.len());
+    (report, damages)
+}
+
+/// Appends one inline and one auxiliary-backed object and flushes the log.
+fn flushed_object_log(storage: &mut TestStorage<'_>) -> CollectionId {
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 16, 16>::new();
+    let mut log = ObjectLog::new(storage, &mut memory, b"log").unwrap();
+    let mut scratch = [0u8; REGION_SIZE];
+    log.append(storage, b"object", &mut scratch).unwrap();
+    let large = (0..1200u32)
+        .map(|byte| byte as u8)
+        .collect::<std::vec::Vec<_>>();
+    log.append(storage, &large, &mut scratch).unwrap();
+    log.flush(storage).unwrap();
+    log.collection_id()
+}
+
+/// Returns the first region whose header names `collection_id` and `format`.
+fn region_with_format(
+    storage: &mut TestStorage<'_>,
+    collection_id: CollectionId,
+    format: u16,
+) -> u32 {
+    storage.with_io_workspace(|flash, _| {
+        (0..REGION_COUNT as u32)
+            .find(|region_index| {
+                let bytes = flash.region_bytes(*region_index).unwrap();
+                Header::decode(&bytes[..Header::ENCODED_LEN]).is_ok_and(|header| {
+                    header.collection_id == collection_id && header.collection_format == format
+                })
+            })
+            .unwrap()
+    })
+}
+
+//= spec/scrub.md#scrub-pass
+//= type=test
+//# `RING-SCRUB-001` `Storage::scrub` MUST NOT write, erase, or sync flash
+//# other than to append its `scrub_progress` record, and a full pass over a
+//# store written only through the public API MUST report no damaged
+//# regions.
+#[test]
+fn requirement_scrub_is_read_only_and_clean_for_public_writes() {
+    let mut flash = TestFlash::new(0xff);
+    {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut cell = Cell::new(&mut storage, std::vec![0u8; 4]).unwrap();
+        cell.set(&mut storage, std::vec![1u8; 300]).unwrap();
+        flushed_object_log(&mut storage);
+        crate::test_flushed_map(&mut storage);
+        let (report, damages) = scrub_pass(&mut storage);
+        assert_eq!(report.damaged_regions, 0, "{damages:?}");
+    }
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    storage.with_io_workspace(|flash, _| flash.clear_operations());
+    let (report, damages) = scrub_pass(&mut storage);
+    assert_eq!(report.damaged_regions, 0, "{damages:?}");
+    assert!(report.regions_read > 0);
+    let wal_tail = storage.wal_tail();
+    let operations = storage.with_io_workspace(|flash, _| flash.operations().to_vec());
+    assert!(operations.iter().all(|operation| match operation {
+        MockOperation::ReadRegion { .. } | MockOperation::Sync => true,
+        MockOperation::WriteRegion { region_index, .. } => *region_index == wal_tail,
+        _ => false,
+    }));
+    let writes = operations
+        .iter()
+        .filter(|operation| matches!(operation, MockOperation::WriteRegion { .. }))
+        .count();
+    assert_eq!(writes, 1);
+}
+
+//= spec/scrub.md#scrub-pass
+//= type=test
+//# `RING-SCRUB-002` Each call MUST read at most `max_regions` regions, MUST
+//# skip queued and retired regions without reading them, and MUST continue
+//# from the cursor so consecutive calls cover every region once per pass.
+#[test]
+fn requirement_scrub_is_bounded_and_resumes_from_cursor() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    crate::test_flushed_map(&mut storage);
+    scrub_pass(&mut storage);
+    let live_regions = |storage: &TestStorage<'_>| {
+        let free_space = storage.memory.state.free_space();
+        (0..REGION_COUNT as u32)
+            .filter(|region_index| !free_space.contains_free_region(*region_index))
+            .collect::<std::vec::Vec<_>>()
+    };
+    let live_before = live_regions(&storage);
+
+    let mut regions_read = 0;
+    let mut calls = 0;
+    let mut read = std::vec::Vec::new();
+    loop {
+        storage.with_io_workspace(|flash, _| flash.clear_operations());
+        let report = storage
+            .scrub(2, &mut |damage| panic!("{damage:?}"))
+            .unwrap();
+        assert!(report.regions_read <= 2);
+        regions_read += report.regions_read;
+        calls += 1;
+
+        let operations = storage.with_io_workspace(|flash, _| flash.operations().to_vec());
+        let free_space = storage.memory.state.free_space();
+        for operation in operations {
+            let MockOperation::ReadRegion { region_index, .. } = operation else {
+                continue;
+            };
+            assert!(!free_space.contains_free_region(region_index));
+            assert!(!read.contains(&region_index));
+            read.push(region_index);
+        }
+        if report.pass_completed {
+            break;
+        }
+        assert!(storage.scrub_cursor().next_region > 0);
+    }
+    assert!(calls > 1);
+    assert_eq!(storage.scrub_cursor().completed_passes, 2);
+    assert_eq!(read.len() as u32, regions_read);
+    // The progress appends may rotate the WAL into a region the pass has
+    // already passed, so only regions live throughout must have been read.
+    for region_index in live_regions(&storage) {
+        if live_before.contains(&region_index) {
+            assert!(read.contains(&region_index), "{region_index}");
+        }
+    }
+}
+
+//= spec/scrub.md#scrub-pass
+//= type=test
+//# `RING-SCRUB-003` `Storage::open` MUST restore the cursor from the last
+//# `scrub_progress` record, also after WAL head reclaim, so a pass
+//# interrupted by a reset resumes at the region after the last one
+//# examined.
+#[test]
+fn requirement_scrub_resumes_after_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let saved;
+    {
+        let mut storage = crate::test_format_storage(&mut flash);
+        crate::test_flushed_map(&mut storage);
+        storage
+            .scrub(3, &mut |damage| panic!("{damage:?}"))
+            .unwrap();
+        saved = storage.scrub_cursor();
+        assert!(saved.next_region > 0);
+        assert_eq!(saved.completed_passes, 0);
+
+        // Reclaim past the region holding the record so only the copy
+        // reclaim carried forward remains.
+        let record_region = storage.wal_tail();
+        let mut cell = Cell::new(&mut storage, 0u32).unwrap();
+        let mut reclaimed = false;
+        for value in 1..256u32 {
+            cell.set(&mut storage, value).unwrap();
+            if storage.wal_head() != storage.wal_tail() {
+                let head = storage.wal_head();
+                storage.reclaim_wal_head().unwrap();
+                if head == record_region {
+                    reclaimed = true;
+                    break;
+                }
+            }
+        }
+        assert!(reclaimed);
+    }
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(storage.scrub_cursor(), saved);
+    storage.with_io_workspace(|flash, _| flash.clear_operations());
+    storage
+        .scrub(1, &mut |damage| panic!("{damage:?}"))
+        .unwrap();
+    let operations = storage.with_io_workspace(|flash, _| flash.operations().to_vec());
+    let MockOperation::ReadRegion { region_index, .. } = operations[0] else {
+        panic!("unexpected {:?}", operations[0]);
+    };
+    assert!(region_index >= saved.next_region);
+    assert_eq!(storage.scrub_cursor().next_region, region_index + 1);
+}
+
+//= spec/scrub.md#verification
+//= type=test
+//# `RING-SCRUB-004` A region whose header fails its CRC MUST be reported as
+//# `Header` damage, attributed to the collection whose live state names the
+//# region, to collection id zero for storage-owned regions, or to no
+//# collection when nothing names it.
+#[test]
+fn requirement_scrub_reports_header_damage_with_owner() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let map_id = crate::test_flushed_map(&mut storage);
+    let manifest = crate::test_head_region(&storage, map_id);
+    let leaked = storage.reserve_next_region().unwrap();
+    storage
+        .write_committed_region(leaked, CollectionId(0x7777), 1, &[0u8; 16])
+        .unwrap();
+    let wal_head = storage.wal_head();
+    for region_index in [manifest, leaked, wal_head] {
+        crate::test_flip_byte(&mut storage, region_index, Header::ENCODED_LEN - 1);
+    }
+
+    let (_, damages) = scrub_pass(&mut storage);
+    assert_eq!(damages.len(), 3, "{damages:?}");
+    for (region_index, collection_id) in [
+        (manifest, Some(map_id)),
+        (leaked, None),
+        (wal_head, Some(CollectionId(0))),
+    ] {
+        assert!(
+            damages.contains(&ScrubDamage {
+                region_index,
+                collection_id,
+                kind: ScrubDamageKind::Header,
+            }),
+            "{damages:?}"
+        );
+    }
+}
+
+//= spec/scrub.md#verification
+//= type=test
+//# `RING-SCRUB-005` The scrub MUST verify main-WAL and transaction-log
+//# prologues, every retained WAL record not closed by a later
+//# `wal_recovery` record, and the free-space and wear-table checksums.
+#[test]
+fn requirement_scrub_verifies_wal_and_free_space_checksums() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut cell = Cell::new(&mut storage, 0u32).unwrap();
+    for value in 1..4u32 {
+        cell.set(&mut storage, value).unwrap();
+    }
+    let wal_head = storage.wal_head();
+    let record_offset = storage.metadata().wal_record_area_offset().unwrap();
+    crate::test_flip_byte(&mut storage, wal_head, record_offset + 1);
+    let free_space_region = storage.memory.state.free_space().metadata_regions()[0];
+    crate::test_flip_byte(
+        &mut storage,
+        free_space_region,
+        Header::ENCODED_LEN + FreeSpaceRegionPrologue::ENCODED_LEN,
+    );
+
+    let (_, damages) = scrub_pass(&mut storage);
+    assert!(damages.contains(&ScrubDamage {
+        region_index: wal_head,
+        collection_id: Some(CollectionId(0)),
+        kind: ScrubDamageKind::WalRecord {
+            offset: record_offset
+        },
+    }));
+    assert!(damages.contains(&ScrubDamage {
+        region_index: free_space_region,
+        collection_id: Some(CollectionId(0)),
+        kind: ScrubDamageKind::FreeSpaceRegion,
+    }));
+    assert_eq!(damages.len(), 2, "{damages:?}");
+
+    crate::test_flip_byte(&mut storage, wal_head, Header::ENCODED_LEN);
+    let (_, damages) = scrub_pass(&mut storage);
+    assert!(damages.contains(&ScrubDamage {
+        region_index: wal_head,
+        collection_id: Some(CollectionId(0)),
+        kind: ScrubDamageKind::LogPrologue,
+    }));
+}
+
+//= spec/scrub.md#verification
+//= type=test
+//# `RING-SCRUB-006` A map run segment whose keys or snapshot entries overrun
+//# its framing MUST be reported as `RunSegment` damage owned by its map.
+#[test]
+fn requirement_scrub_verifies_map_run_framing() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let map_id = crate::test_flushed_map(&mut storage);
+    let (_, damages) = scrub_pass(&mut storage);
+    assert!(damages.is_empty(), "{damages:?}");
+
+    let run = region_with_format(&mut storage, map_id, MAP_RUN_V2_FORMAT);
+    // The high byte of the snapshot length.
+    crate::test_flip_byte(&mut storage, run, Header::ENCODED_LEN + 27);
+    let (_, damages) = scrub_pass(&mut storage);
+    assert_eq!(
+        damages.as_slice(),
+        &[ScrubDamage {
+            region_index: run,
+            collection_id: Some(map_id),
+            kind: ScrubDamageKind::RunSegment,
+        }]
+    );
+}
+
+//= spec/scrub.md#verification
+//= type=test
+//# `RING-SCRUB-007` The scrub MUST verify every object-log record body and
+//# chunk CRC in data regions and the prologue, chunk slots, and next link in
+//# auxiliary regions, reporting the offset of the first damaged frame.
+#[test]
+fn requirement_scrub_verifies_object_log_chunks() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let log_id = flushed_object_log(&mut storage);
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == log_id)
+            .and_then(|collection| collection.collection_type()),
+        Some(CollectionType::OBJECT_LOG_CODE)
+    );
+
+    let data = region_with_format(&mut storage, log_id, OBJECT_LOG_DATA_V1_FORMAT);
+    let aux = region_with_format(&mut storage, log_id, OBJECT_LOG_AUX_V1_FORMAT);
+    crate::test_flip_byte(&mut storage, aux, REGION_SIZE / 2);
+    let (_, damages) = scrub_pass(&mut storage);
+    let [damage] = damages.as_slice() else {
+        panic!("{damages:?}");
+    };
+    assert_eq!(damage.region_index, aux);
+    assert_eq!(damage.collection_id, Some(log_id));
+    assert!(matches!(
+        damage.kind,
+        ScrubDamageKind::ObjectLogRecord { offset }
+            if offset > Header::ENCODED_LEN && offset <= REGION_SIZE / 2
+    ));
+
+    crate::test_flip_byte(&mut storage, aux, REGION_SIZE / 2);
+    let record_offset = Header::ENCODED_LEN + 64;
+    crate::test_flip_byte(&mut storage, data, record_offset);
+    let (_, damages) = scrub_pass(&mut storage);
+    let [damage] = damages.as_slice() else {
+        panic!("{damages:?}");
+    };
+    assert_eq!(damage.region_index, data);
+    assert_eq!(damage.collection_id, Some(log_id));
+    assert!(matches!(
+        damage.kind,
+        ScrubDamageKind::ObjectLogRecord { offset } if offset <= record_offset
+    ));
+}
+
+/// Creates one of each collection whose regions carry a trailing checksum,
+/// seals or flushes it, and returns its id with the format of that region.
+fn checksummed_collections(storage: &mut TestStorage<'_>) -> [(CollectionId, u16); 5] {
+    let mut queue_memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<u32, REGION_SIZE>::new(storage, &mut queue_memory).unwrap();
+    queue.push(storage, &7).unwrap();
+    queue.flush(storage).unwrap();
+
+    let mut ring_memory = RingLogMemory::<REGION_SIZE, 2>::new();
+    let mut ring = RingLog::<REGION_SIZE, 2>::new(storage, &mut ring_memory).unwrap();
+    ring.append(storage, b"record").unwrap();
+    ring.flush(storage).unwrap();
+
+    let mut series_memory = TimeSeriesMemory::<REGION_SIZE, 1>::new();
+    let mut series = TimeSeries::<REGION_SIZE, 1>::new(storage, &mut series_memory).unwrap();
+    series.append(storage, 1, 10, 1.5).unwrap();
+    series.flush(storage).unwrap();
+
+    let mut members_data = [MemberSequence::default(); 1];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 1];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data: [AddCommand<u32, 8>; 1] = core::array::from_fn(|_| AddCommand::default());
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    let author = MemberId::new(1);
+    let mut channel =
+        Channel::<_, _, _, _, 8, 1>::new(storage, author, &mut pending, &mut members, &mut updates)
+            .unwrap();
+    channel
+        .add_command(
+            storage,
+            CommandAddress::zero(),
+            author,
+            MessageId::new(1),
+            &[3; 8],
+        )
+        .unwrap();
+    channel.flush(storage).unwrap();
+
+    let mut cell = Cell::new(storage, std::vec![0u8; 4]).unwrap();
+    cell.set(storage, std::vec![1u8; 300]).unwrap();
+
+    [
+        (queue.collection_id(), QUEUE_SEGMENT_V1_FORMAT),
+        (ring.collection_id(), RING_LOG_SEGMENT_V1_FORMAT),
+        (series.collection_id(), TIME_SERIES_SEGMENT_V1_FORMAT),
+        (channel.id(), CHANNEL_SEGMENT_V1_FORMAT),
+        (cell.collection_id(), CELL_VALUE_V1_FORMAT),
+    ]
+}
+
+//= spec/scrub.md#verification
+//= type=test
+//# `RING-SCRUB-008` The scrub MUST verify the trailing CRC of every channel,
+//# queue, ring-log, and time-series segment and every cell value region,
+//# reporting a mismatch as `CollectionChecksum` damage owned by the
+//# collection.
+#[test]
+fn requirement_scrub_verifies_collection_checksums() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let collections = checksummed_collections(&mut storage);
+    let (_, damages) = scrub_pass(&mut storage);
+    assert!(damages.is_empty(), "{damages:?}");
+
+    for (collection_id, format) in collections {
+        let region = region_with_format(&mut storage, collection_id, format);
+        // The last programmed byte lies in the trailing checksum, so the
+        // framing still decodes and only the CRC comparison can catch it.
+        let offset = storage.with_io_workspace(|flash, _| {
+            let bytes = flash.region_bytes(region).unwrap();
+            bytes.iter().rposition(|byte| *byte != 0xff).unwrap()
+        });
+        crate::test_flip_byte(&mut storage, region, offset);
+        let (_, damages) = scrub_pass(&mut storage);
+        assert_eq!(
+            damages.as_slice(),
+            &[ScrubDamage {
+                region_index: region,
+                collection_id: Some(collection_id),
+                kind: ScrubDamageKind::CollectionChecksum,
+            }],
+            "format {format}"
+        );
+        crate::test_flip_byte(&mut storage, region, offset);
+    }
+}
@@ -1,13 +1,20 @@
 use heapless::Vec;
 
+use crate::collection_type::{CollectionPluginError, CollectionTypeRegistry};
 use crate::disk::{
-    free_space_entries_checksum, DiskError, FreeQueuePosition, FreeSpaceCursors, FreeSpaceEntry,
-    FreeSpaceRegionPrologue, Header, StorageMetadata, WalRegionPrologue, FREE_SPACE_V2_FORMAT,
-    TRANSACTION_LOG_V2_FORMAT, WAL_V1_FORMAT,
+    decode_free_space_wear_counts, free_space_entries_checksum, DiskError, FreeQueuePosition,
+    FreeSpaceCursors, FreeSpaceEntry, FreeSpaceRegionPrologue, FreeSpaceWearPrologue, Header,
+    StorageMetadata, WalRegionPrologue, FREE_SPACE_V2_FORMAT, FREE_SPACE_WEAR_V1_FORMAT,
+    METADATA_COPY_COUNT, TRANSACTION_LOG_V2_FORMAT, WAL_V1_FORMAT,
 };
 use crate::flash_io::FlashIo;
 use crate::flash_io::StorageIoError;
 use crate::free_space::{FreeSpaceError, FreeSpaceState};
+use crate::recovery::{
+    RecoveryReport, TornWalTail, TransactionRecovery, TransactionRecoveryOutcome,
+};
+use crate::salvage::{QuarantineReason, QuarantinedCollection};
+use crate::scrub::ScrubCursor;
 use crate::storage::{
     RetainedTransactionLog, StorageRuntime, StorageRuntimeError, TransactionLogOutcome,
     MAX_RETAINED_TRANSACTION_LOGS, MAX_RETAINED_TRANSACTION_LOG_REGIONS, TRANSACTION_SLOT_COUNT,
@@ -36,10 +43,27 @@ pub enum StartupError {
     /// The Linux file-backed mmap backend failed.
     #[cfg(all(feature = "file-backing", target_os = "linux"))]
     FileBacking(crate::file_backing::FileBackingError),
+    /// The backend permanently failed to program or erase a region.
+    BadRegion(u32),
+    /// The [`ReadOnlyFlash`](crate::ReadOnlyFlash) overlay failed.
+    ReadOnlyFlash(crate::read_only::ReadOnlyFlashError),
     /// WAL record decoding failed.
     WalRecord(WalRecordError),
     /// Metadata was missing from the device.
     MissingMetadata,
+    /// Metadata recovery found a readable metadata copy and left it untouched.
+    MetadataIntact,
+    /// Metadata recovery found main-WAL regions whose first record bytes sit
+    /// at different offsets or hold different magic bytes.
+    WalRecordMagicMismatch {
+        /// WAL region that disagreed with the regions before it.
+        region_index: u32,
+        /// Byte found at that region's first record offset.
+        found: u8,
+    },
+    /// Metadata recovery found no main-WAL record to derive the record magic
+    /// and write granule from.
+    NoWalRecordToRecover,
     /// Replay could not identify a WAL tail.
     NoWalTailCandidate,
     /// Two candidate WAL tails used the same sequence number.
@@ -181,6 +205,8 @@ impl From<StorageIoError> for StartupError {
             StorageIoError::EmbeddedStorage(error) => Self::EmbeddedStorage(error),
             #[cfg(all(feature = "file-backing", target_os = "linux"))]
             StorageIoError::FileBacking(error) => Self::FileBacking(error),
+            StorageIoError::BadRegion(region_index) => Self::BadRegion(region_index),
+            StorageIoError::ReadOnly(error) => Self::ReadOnlyFlash(error),
         }
     }
 }
@@ -465,6 +491,9 @@ pub(crate) struct StartupOpenPlan<const REGION_COUNT: usize, const MAX_COLLECTIO
     transaction_old_regions: Vec<u32, REGION_COUNT>,
     transaction_new_regions: Vec<u32, REGION_COUNT>,
     retained_transaction_logs: Vec<RetainedTransactionLog, MAX_RETAINED_TRANSACTION_LOGS>,
+    scrub_cursor: ScrubCursor,
+    collection_types: CollectionTypeRegistry,
+    report: RecoveryReport<REGION_COUNT>,
 }
 
 impl<const REGION_COUNT: usize, const MAX_COLLECTIONS: usize>
@@ -505,9 +534,54 @@ impl<const REGION_COUNT: usize, const MAX_COLLECTIONS: usize>
             transaction_old_regions: Vec::new(),
             transaction_new_regions: Vec::new(),
             retained_transaction_logs: Vec::new(),
+            scrub_cursor: ScrubCursor::new(),
+            collection_types: CollectionTypeRegistry::default(),
+            report: RecoveryReport::new(),
         }
     }
 
+    pub(crate) fn set_collection_types(&mut self, collection_types: CollectionTypeRegistry) {
+        self.collection_types = collection_types;
+    }
+
+    /// Starts an empty recovery report. Replay passes repeated after a
+    /// transaction recovery keep adding to it.
+    pub(crate) fn begin_recovery_report(&mut self) {
+        self.report = RecoveryReport::new();
+    }
+
+    pub(crate) fn take_recovery_report(&mut self) -> RecoveryReport<REGION_COUNT> {
+        core::mem::take(&mut self.report)
+    }
+
+    /// Records the torn bytes ending the WAL tail, keeping the location the
+    /// first replay pass found when recovery makes replay run again.
+    fn report_torn_tail(&mut self, region_index: u32, torn: bool, offset: usize, end: usize) {
+        if torn && self.report.torn_tail.is_none() {
+            self.report.torn_tail = Some(TornWalTail {
+                region_index,
+                offset,
+                len: end.saturating_sub(offset),
+            });
+        }
+    }
+
+    fn report_transaction(
+        &mut self,
+        transaction_log_id: Option<u32>,
+        collection_id: Option<CollectionId>,
+        outcome: TransactionRecoveryOutcome,
+    ) -> Result<(), StartupError> {
+        self.report
+            .transactions
+            .push(TransactionRecovery {
+                transaction_log_id,
+                collection_id,
+                outcome,
+            })
+            .map_err(|_| StartupError::LengthOverflow)
+    }
+
     fn reset(
         &mut self,
         metadata: StorageMetadata,
@@ -537,6 +611,7 @@ impl<const REGION_COUNT: usize, const MAX_COLLECTIONS: usize>
         self.transaction_old_regions.clear();
         self.transaction_new_regions.clear();
         self.retained_transaction_logs.clear();
+        self.scrub_cursor = ScrubCursor::new();
         Ok(())
     }
 
@@ -666,6 +741,7 @@ pub(crate) fn open_formatted_store<
     workspace: &mut StorageWorkspace<REGION_SIZE>,
     plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
 ) -> Result<StartupState<MAX_COLLECTIONS>, StartupError> {
+    plan.begin_recovery_report();
     begin_open_formatted_store::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
         flash, workspace, plan,
     )?;
@@ -703,7 +779,7 @@ pub(crate) fn begin_open_formatted_store<
     let free_space =
         load_initial_free_space_from_flash::<REGION_SIZE, IO>(flash, workspace, metadata)?;
 
-    let (known_tail, max_seen_sequence) = locate_wal_tail::<REGION_SIZE, _>(flash, metadata)?;
+    let (known_tail, max_seen_sequence) = locate_wal_tail(flash, metadata.region_count)?;
     let tail_prologue = read_wal_prologue(flash, known_tail, metadata.region_count)?;
     let mut wal_head_candidate = tail_prologue.log_head_region_index;
     let tail_scan = scan_wal_region::<REGION_SIZE, _, _>(
@@ -742,6 +818,209 @@ pub(crate) fn begin_open_formatted_store<
     Ok(())
 }
 
+/// Rebuilds lost storage metadata from region contents and rewrites every
+/// copy.
+///
+/// Region size and count come from the const geometry, and the
+/// transaction-log count is the one every format writes. The erased byte is
+/// the fill of a region that holds no header, the WAL record magic is the
+/// first non-erased byte after the prologue of every main-WAL region, and the
+/// WAL write granule is the alignment under which those regions decode
+/// cleanly.
+/// `min_free_regions` is a policy value no region records, so the caller must
+/// supply it. The rebuilt metadata must walk the whole main-WAL chain from the
+/// tail prologue's head to the tail before any copy is written.
+pub(crate) fn rebuild_metadata_from_headers<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    min_free_regions: u32,
+) -> Result<StorageMetadata, StartupError> {
+    if let Ok(Some(_)) = flash.read_metadata() {
+        return Err(StartupError::MetadataIntact);
+    }
+
+    let region_size = u32::try_from(REGION_SIZE).map_err(|_| StartupError::LengthOverflow)?;
+    let region_count = u32::try_from(REGION_COUNT).map_err(|_| StartupError::LengthOverflow)?;
+    let (wal_tail, _) = locate_wal_tail(flash, region_count)?;
+    let wal_head = read_wal_prologue(flash, wal_tail, region_count)?.log_head_region_index;
+
+    let erased_byte = match recover_erased_byte::<REGION_SIZE, _>(flash, workspace, region_count)? {
+        Some(erased_byte) => erased_byte,
+        // Every region holds data, so fall back to the end of the WAL tail,
+        // which stays erased until the tail fills.
+        None => flash
+            .read_region(wal_tail, REGION_SIZE - 1, 1, |bytes| bytes.first().copied())?
+            .ok_or(StartupError::LengthOverflow)?,
+    };
+    let (record_offset, wal_record_magic) =
+        recover_wal_record_start::<REGION_SIZE, _>(flash, workspace, region_count, erased_byte)?;
+    let wal_write_granule = recover_wal_write_granule::<REGION_SIZE, _>(
+        flash,
+        workspace,
+        region_count,
+        record_offset,
+        |granule| {
+            StorageMetadata::new(
+                region_size,
+                region_count,
+                min_free_regions,
+                granule,
+                erased_byte,
+                wal_record_magic,
+            )
+        },
+    )?;
+    let metadata = StorageMetadata::new(
+        region_size,
+        region_count,
+        min_free_regions,
+        wal_write_granule,
+        erased_byte,
+        wal_record_magic,
+    )?;
+
+    let mut wal_chain = Vec::<u32, REGION_COUNT>::new();
+    walk_wal_chain::<REGION_SIZE, REGION_COUNT, _>(
+        flash,
+        workspace,
+        metadata,
+        wal_head,
+        wal_tail,
+        &mut wal_chain,
+    )?;
+
+    for _ in 0..METADATA_COPY_COUNT {
+        flash.write_metadata(metadata)?;
+    }
+    flash.sync()?;
+    Ok(metadata)
+}
+
+/// Returns the byte filling the first region that holds no valid header and
+/// no other value, if any region is still erased.
+fn recover_erased_byte<const REGION_SIZE: usize, IO: FlashIo>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    region_count: u32,
+) -> Result<Option<u8>, StartupError> {
+    let (region_bytes, _) = workspace.scan_buffers();
+    for region_index in 0..region_count {
+        flash.read_region(region_index, 0, REGION_SIZE, |bytes| {
+            region_bytes.copy_from_slice(bytes)
+        })?;
+        let fill = region_bytes[0];
+        if Header::decode(&region_bytes[..Header::ENCODED_LEN]).is_err()
+            && region_bytes.iter().all(|byte| *byte == fill)
+        {
+            return Ok(Some(fill));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the offset and value of the first record byte shared by every
+/// main-WAL region that holds a record.
+fn recover_wal_record_start<const REGION_SIZE: usize, IO: FlashIo>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    region_count: u32,
+    erased_byte: u8,
+) -> Result<(usize, u8), StartupError> {
+    let prefix_len = Header::ENCODED_LEN + WalRegionPrologue::ENCODED_LEN;
+    let (region_bytes, _) = workspace.scan_buffers();
+    let mut record_start: Option<(usize, u8)> = None;
+    for region_index in 0..region_count {
+        flash.read_region(region_index, 0, REGION_SIZE, |bytes| {
+            region_bytes.copy_from_slice(bytes)
+        })?;
+        if !is_main_wal_region(&region_bytes[..]) {
+            continue;
+        }
+        let Some(offset) = region_bytes[prefix_len..]
+            .iter()
+            .position(|byte| *byte != erased_byte)
+            .map(|position| prefix_len + position)
+        else {
+            continue;
+        };
+        let found = (offset, region_bytes[offset]);
+        match record_start {
+            None => record_start = Some(found),
+            Some(expected) if expected != found => {
+                return Err(StartupError::WalRecordMagicMismatch {
+                    region_index,
+                    found: found.1,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    record_start.ok_or(StartupError::NoWalRecordToRecover)
+}
+
+/// Returns the WAL write granule that places the record area at
+/// `record_offset` and decodes the most main-WAL records with the fewest
+/// damaged spans, preferring the largest such granule.
+fn recover_wal_write_granule<const REGION_SIZE: usize, IO: FlashIo>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    region_count: u32,
+    record_offset: usize,
+    metadata_for: impl Fn(u32) -> Result<StorageMetadata, DiskError>,
+) -> Result<u32, StartupError> {
+    let prefix_len = Header::ENCODED_LEN + WalRegionPrologue::ENCODED_LEN;
+    let mut best: Option<(usize, u32, u32)> = None;
+    for granule in 1..=record_offset {
+        if prefix_len.div_ceil(granule) * granule != record_offset {
+            continue;
+        }
+        let granule = u32::try_from(granule).map_err(|_| StartupError::LengthOverflow)?;
+        let metadata = metadata_for(granule)?;
+        let mut damaged = 0usize;
+        let mut records = 0u32;
+        for region_index in 0..region_count {
+            let (region_bytes, logical_scratch) = workspace.scan_buffers();
+            flash.read_region(region_index, 0, REGION_SIZE, |bytes| {
+                region_bytes.copy_from_slice(bytes)
+            })?;
+            if !is_main_wal_region(&region_bytes[..]) {
+                continue;
+            }
+            let scan = crate::check::scan_wal_region(
+                metadata,
+                &region_bytes[..],
+                REGION_SIZE,
+                logical_scratch,
+                |_| damaged += 1,
+            )
+            .map_err(|error| match error {
+                StorageRuntimeError::Startup(error) => error,
+                _ => StartupError::LengthOverflow,
+            })?;
+            records = records.saturating_add(scan.records);
+        }
+        let better = best.is_none_or(|(best_damaged, best_records, _)| {
+            (damaged, core::cmp::Reverse(records))
+                <= (best_damaged, core::cmp::Reverse(best_records))
+        });
+        if better {
+            best = Some((damaged, records, granule));
+        }
+    }
+    best.map(|(_, _, granule)| granule)
+        .ok_or(StartupError::NoWalRecordToRecover)
+}
+
+fn is_main_wal_region(region_bytes: &[u8]) -> bool {
+    Header::decode(&region_bytes[..Header::ENCODED_LEN]).is_ok_and(|header| {
+        header.collection_id == CollectionId(0) && header.collection_format == WAL_V1_FORMAT
+    })
+}
+
 pub(crate) fn recover_open_rotation<
     const REGION_SIZE: usize,
     IO: FlashIo,
@@ -769,6 +1048,7 @@ pub(crate) fn recover_open_rotation<
         &mut plan.max_seen_sequence,
     )? {
         plan.wal_tail = recovered_tail;
+        plan.report.completed_rotation = Some(recovered_tail);
     }
 
     Ok(())
@@ -845,6 +1125,7 @@ fn replay_open_wal_chain_once<
     plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
 ) -> Result<ReplayWalChainOutcome, StartupError> {
     let mut replay_state = OpenWalReplayState::default();
+    plan.report.wal_records_replayed = 0;
 
     let wal_chain_len = plan.wal_chain.len();
     for index in 0..wal_chain_len {
@@ -898,6 +1179,7 @@ fn replay_open_wal_chain_once<
                 record_count: open_inline.seen_record_count,
             },
         )?;
+        plan.report_transaction(None, None, TransactionRecoveryOutcome::RolledBack)?;
         let _ = append_recovered_transaction_allocation_frees::<
             REGION_SIZE,
             REGION_COUNT,
@@ -917,6 +1199,7 @@ fn replay_open_wal_chain_once<
         >(flash, workspace, plan, CollectionId(0))?;
         plan.clear_transaction_recovery_scratch();
         if appended {
+            plan.report_transaction(None, None, TransactionRecoveryOutcome::RollbackFinished)?;
             return Ok(ReplayWalChainOutcome::RecoveredTransaction);
         }
     }
@@ -950,6 +1233,7 @@ fn replay_open_wal_region<
         .map_err(|_| StartupError::LengthOverflow)?;
     let mut offset = plan.metadata.wal_record_area_offset()?;
     let mut pending_boundary_open = false;
+    let mut torn_offset = offset;
     let mut reload_region = true;
 
     while offset < region_size {
@@ -972,11 +1256,15 @@ fn replay_open_wal_region<
             if is_tail {
                 plan.wal_append_offset = offset;
                 plan.pending_wal_recovery_boundary = pending_boundary_open;
+                plan.report_torn_tail(region_index, pending_boundary_open, torn_offset, offset);
             }
             return Ok(());
         }
 
         if start_byte != plan.metadata.wal_record_magic {
+            if !pending_boundary_open {
+                torn_offset = offset;
+            }
             pending_boundary_open = true;
             offset = offset
                 .checked_add(granule)
@@ -1029,10 +1317,15 @@ fn replay_open_wal_region<
                     if decoded.record.record_type() == crate::WalRecordType::WalRecovery {
                         pending_boundary_open = false;
                     }
+                    plan.report.wal_records_replayed =
+                        plan.report.wal_records_replayed.saturating_add(1);
 
                     step
                 }
                 Err(_) => {
+                    if !pending_boundary_open {
+                        torn_offset = offset;
+                    }
                     pending_boundary_open = true;
                     ReplayStep::Advance {
                         next_offset: offset
@@ -1108,6 +1401,12 @@ fn replay_open_wal_region<
     if is_tail {
         plan.wal_append_offset = region_size;
         plan.pending_wal_recovery_boundary = pending_boundary_open;
+        plan.report_torn_tail(
+            region_index,
+            pending_boundary_open,
+            torn_offset,
+            region_size,
+        );
     }
     Ok(())
 }
@@ -1328,11 +1627,23 @@ fn classify_replay_record<const REGION_COUNT: usize, const MAX_COLLECTIONS: usiz
                 apply_open_replay_record(plan, record)?;
             } else {
                 observe_transaction_recovery_record(plan, transaction, record)?;
+                if let WalRecord::AllocateRegion {
+                    region_index,
+                    allocation_head_after,
+                } = record
+                {
+                    skip_unreplayed_transaction_allocations(
+                        plan,
+                        region_index,
+                        allocation_head_after,
+                    )?;
+                }
                 if matches!(
                     record,
                     WalRecord::AllocateRegion { .. }
                         | WalRecord::FreeRegion { .. }
                         | WalRecord::EraseFreeRegionSpan { .. }
+                        | WalRecord::RetireRegion { .. }
                 ) {
                     apply_open_replay_allocator_record(plan, record)?;
                 }
@@ -1358,6 +1669,14 @@ fn observe_transaction_recovery_record<const REGION_COUNT: usize, const MAX_COLL
         WalRecord::FreeIntent { region_index, .. } => {
             push_unique_region(&mut plan.transaction_free_intents, region_index)?;
         }
+        WalRecord::Link {
+            next_region_index, ..
+        } => {
+            // A rotation inside the transaction linked the region it
+            // allocated into the WAL, so rollback must not free it.
+            plan.transaction_allocations
+                .retain(|region_index| *region_index != next_region_index);
+        }
         _ => {}
     }
 
@@ -1396,6 +1715,8 @@ fn transaction_record_collection_id(record: WalRecord<'_>) -> Option<CollectionI
         WalRecord::AllocateRegion { .. }
         | WalRecord::EraseFreeRegionSpan { .. }
         | WalRecord::FreeRegion { .. }
+        | WalRecord::RetireRegion { .. }
+        | WalRecord::ScrubProgress { .. }
         | WalRecord::BeginInlineTransaction { .. }
         | WalRecord::CommitInlineTransaction { .. }
         | WalRecord::RollbackInlineTransaction { .. }
@@ -1477,7 +1798,9 @@ fn validate_transaction_log_private_record<
             }
             Ok(())
         }
-        WalRecord::FreeRegion { .. } => Err(StartupError::InvalidTransactionEnrollment {
+        WalRecord::FreeRegion { .. }
+        | WalRecord::RetireRegion { .. }
+        | WalRecord::ScrubProgress { .. } => Err(StartupError::InvalidTransactionEnrollment {
             collection_id: CollectionId(0),
         }),
         WalRecord::FreeIntent {
@@ -1600,16 +1923,21 @@ fn load_initial_free_space_from_flash<const REGION_SIZE: usize, IO: FlashIo>(
         }
         u32::try_from(entries_per_region).map_err(|_| StartupError::LengthOverflow)?
     };
+    let mut wear_root = None;
     for _ in 0..metadata.region_count {
-        metadata_regions
-            .push(region_index)
-            .map_err(|_| StartupError::InvalidFreeSpaceCollection)?;
         let region_bytes = workspace.committed_write_buffer();
         flash.read_region(region_index, 0, REGION_SIZE, |bytes| {
             region_bytes.copy_from_slice(bytes);
         })?;
 
         let header = Header::decode(&region_bytes[..Header::ENCODED_LEN])?;
+        if first_prologue.is_some() && header.collection_format == FREE_SPACE_WEAR_V1_FORMAT {
+            wear_root = Some(region_index);
+            break;
+        }
+        metadata_regions
+            .push(region_index)
+            .map_err(|_| StartupError::InvalidFreeSpaceCollection)?;
         if header.collection_id != CollectionId(0)
             || header.collection_format != FREE_SPACE_V2_FORMAT
             || header.sequence != expected_sequence
@@ -1681,9 +2009,66 @@ fn load_initial_free_space_from_flash<const REGION_SIZE: usize, IO: FlashIo>(
         prologue.append_tail,
         entries.as_slice(),
     )?;
+    state.reset_erase_counts(metadata.region_count)?;
+    if let Some(wear_root) = wear_root {
+        load_free_space_wear_table::<REGION_SIZE, IO>(
+            flash, workspace, metadata, wear_root, &mut state,
+        )?;
+    }
     Ok(state)
 }
 
+/// Loads the erase counts from the wear-table chain that follows the last
+/// free-space metadata region.
+fn load_free_space_wear_table<const REGION_SIZE: usize, IO: FlashIo>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    metadata: StorageMetadata,
+    wear_root: u32,
+    state: &mut FreeSpaceState,
+) -> Result<(), StartupError> {
+    let mut region_index = wear_root;
+    let mut next_count_region = 0u32;
+    for sequence in 0..u64::from(metadata.region_count) {
+        let region_bytes = workspace.committed_write_buffer();
+        flash.read_region(region_index, 0, REGION_SIZE, |bytes| {
+            region_bytes.copy_from_slice(bytes);
+        })?;
+        let header = Header::decode(&region_bytes[..Header::ENCODED_LEN])?;
+        if header.collection_id != CollectionId(0)
+            || header.collection_format != FREE_SPACE_WEAR_V1_FORMAT
+            || header.sequence != sequence
+        {
+            return Err(StartupError::InvalidFreeSpaceCollection);
+        }
+        let prologue_end = Header::ENCODED_LEN
+            .checked_add(FreeSpaceWearPrologue::ENCODED_LEN)
+            .ok_or(StartupError::LengthOverflow)?;
+        let prologue = FreeSpaceWearPrologue::decode(
+            &region_bytes[Header::ENCODED_LEN..prologue_end],
+            metadata.region_count,
+        )?;
+        if sequence == 0 {
+            state.set_erased_through(prologue.erased_through)?;
+        }
+        state.push_wear_region(region_index)?;
+        let mut result = Ok(());
+        decode_free_space_wear_counts(&region_bytes[prologue_end..], prologue, |count| {
+            if result.is_ok() {
+                result = state.set_erase_count(next_count_region, count);
+                next_count_region = next_count_region.saturating_add(1);
+            }
+        })?;
+        result?;
+
+        let Some(next) = prologue.next_wear_region else {
+            return Ok(());
+        };
+        region_index = next;
+    }
+    Err(StartupError::InvalidFreeSpaceCollection)
+}
+
 fn recover_unfinished_transaction<
     const REGION_SIZE: usize,
     const REGION_COUNT: usize,
@@ -1695,6 +2080,14 @@ fn recover_unfinished_transaction<
     plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
     open_transaction: OpenTransactionReplay,
 ) -> Result<(), StartupError> {
+    let mut reported_collection = open_transaction.collection_id;
+    let outcome = if open_transaction.commit_seen {
+        TransactionRecoveryOutcome::CommitFinished
+    } else if open_transaction.rollback_seen {
+        TransactionRecoveryOutcome::RollbackFinished
+    } else {
+        TransactionRecoveryOutcome::RolledBack
+    };
     if open_transaction.commit_seen {
         let range = open_transaction
             .committed_range
@@ -1748,14 +2141,16 @@ fn recover_unfinished_transaction<
             IO,
             MAX_COLLECTIONS,
         >(flash, workspace, plan, open_transaction.start)?;
-        let _ = replay_transaction_log_range::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
-            flash,
-            workspace,
-            plan,
-            range,
-            None,
-            TransactionReplayMode::ApplyRollbackCleanupOnly,
-        )?;
+        let replay_result =
+            replay_transaction_log_range::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+                flash,
+                workspace,
+                plan,
+                range,
+                None,
+                TransactionReplayMode::ApplyRollbackCleanupOnly,
+            )?;
+        reported_collection = reported_collection.or(replay_result.collection_id);
         let collection_id = transaction_collection_id(&open_transaction).unwrap_or(CollectionId(0));
         append_recovery_record_with_rotation::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
             flash,
@@ -1783,6 +2178,11 @@ fn recover_unfinished_transaction<
         )?;
     }
 
+    plan.report_transaction(
+        Some(open_transaction.transaction_log_id),
+        reported_collection,
+        outcome,
+    )?;
     plan.clear_transaction_recovery_scratch();
     Ok(())
 }
@@ -1873,6 +2273,7 @@ fn derive_transaction_cleanup_regions<
         flash,
         workspace,
         plan.metadata,
+        plan.collection_types,
         old_collection,
         &mut plan.transaction_old_regions,
     )?;
@@ -1880,6 +2281,7 @@ fn derive_transaction_cleanup_regions<
         flash,
         workspace,
         plan.metadata,
+        plan.collection_types,
         new_collection,
         &mut plan.transaction_new_regions,
     )?;
@@ -1889,7 +2291,7 @@ fn derive_transaction_cleanup_regions<
     Ok(())
 }
 
-fn collect_collection_committed_regions<
+pub(crate) fn collect_collection_committed_regions<
     const REGION_SIZE: usize,
     const REGION_COUNT: usize,
     IO: FlashIo,
@@ -1898,6 +2300,7 @@ fn collect_collection_committed_regions<
     flash: &mut IO,
     workspace: &mut StorageWorkspace<REGION_SIZE>,
     metadata: StorageMetadata,
+    collection_types: CollectionTypeRegistry,
     collection: Option<StartupCollection>,
     regions: &mut Vec<u32, REGION_COUNT>,
 ) -> Result<(), StartupError> {
@@ -1908,7 +2311,9 @@ fn collect_collection_committed_regions<
         return Ok(());
     };
     match collection.collection_type() {
-        Some(CollectionType::MAP_CODE) => {
+        Some(collection_type)
+            if crate::collections::map::is_lsm_collection_type(collection_type) =>
+        {
             crate::collections::map::collect_map_head_regions::<REGION_SIZE, IO, REGION_COUNT>(
                 flash,
                 workspace,
@@ -1940,11 +2345,75 @@ fn collect_collection_committed_regions<
                 region_index: head_region,
             })
         }
-        Some(other) => Err(StartupError::UnsupportedLiveCollectionType(other)),
+        Some(other) => match collection_types.get(other) {
+            Some(plugin) => collect_plugin_committed_regions::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                metadata,
+                plugin,
+                collection.collection_id(),
+                head_region,
+                regions,
+            ),
+            None => Err(StartupError::UnsupportedLiveCollectionType(other)),
+        },
         None => Ok(()),
     }
 }
 
+fn collect_plugin_committed_regions<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    metadata: StorageMetadata,
+    plugin: &dyn crate::CollectionTypePlugin,
+    collection_id: CollectionId,
+    head_region: u32,
+    regions: &mut Vec<u32, REGION_COUNT>,
+) -> Result<(), StartupError> {
+    let invalid_head = StartupError::InvalidCommittedRegionHead {
+        collection_id,
+        region_index: head_region,
+    };
+    let payload_end = usize::try_from(metadata.region_size)
+        .ok()
+        .filter(|payload_end| (Header::ENCODED_LEN..=REGION_SIZE).contains(payload_end))
+        .ok_or(StartupError::LengthOverflow)?;
+    let (region_bytes, _) = workspace.scan_buffers();
+    flash.read_region(head_region, 0, REGION_SIZE, |bytes| {
+        region_bytes.copy_from_slice(bytes);
+    })?;
+    let header = Header::decode(&region_bytes[..Header::ENCODED_LEN])?;
+    if header.collection_id != collection_id {
+        return Err(invalid_head);
+    }
+    push_unique_region(regions, head_region)?;
+
+    let mut push_error = None;
+    let result = plugin.referenced_regions(
+        collection_id,
+        head_region,
+        &region_bytes[Header::ENCODED_LEN..payload_end],
+        &mut |region_index| {
+            if region_index >= metadata.region_count {
+                return Err(CollectionPluginError);
+            }
+            push_unique_region(regions, region_index).map_err(|error| {
+                push_error = Some(error);
+                CollectionPluginError
+            })
+        },
+    );
+    match (result, push_error) {
+        (_, Some(error)) => Err(error),
+        (Ok(()), None) => Ok(()),
+        (Err(CollectionPluginError), None) => Err(invalid_head),
+    }
+}
+
 fn recover_abandoned_transaction_log_regions<
     const REGION_SIZE: usize,
     const REGION_COUNT: usize,
@@ -2040,7 +2509,8 @@ fn append_recovery_free_region_with_rotation<
             region_index,
             append_tail_after: plan.free_space.position_after_append()?,
         },
-    )
+    )?;
+    push_unique_region(&mut plan.report.reclaimed_regions, region_index)
 }
 
 fn append_recovery_record_with_rotation<
@@ -2863,18 +3333,17 @@ fn apply_transaction_replay_record<const REGION_COUNT: usize, const MAX_COLLECTI
     match mode {
         TransactionReplayMode::ApplyFullInterval => apply_open_replay_record(plan, record),
         TransactionReplayMode::ApplyRollbackCleanupOnly => match record {
-            WalRecord::AllocateRegion { .. } | WalRecord::EraseFreeRegionSpan { .. } => {
-                apply_open_replay_allocator_record(plan, record)
-            }
+            WalRecord::AllocateRegion { .. }
+            | WalRecord::EraseFreeRegionSpan { .. }
+            | WalRecord::RetireRegion { .. } => apply_open_replay_allocator_record(plan, record),
             WalRecord::FreeRegion { .. } => Ok(()),
             _ => Ok(()),
         },
         TransactionReplayMode::SkipTransactionCollectionData(collection_id) => match record {
             WalRecord::AllocateRegion { .. }
             | WalRecord::FreeRegion { .. }
-            | WalRecord::EraseFreeRegionSpan { .. } => {
-                apply_open_replay_allocator_record(plan, record)
-            }
+            | WalRecord::EraseFreeRegionSpan { .. }
+            | WalRecord::RetireRegion { .. } => apply_open_replay_allocator_record(plan, record),
             _ if wal_record_collection_id(record) == Some(collection_id) => Ok(()),
             _ => apply_open_replay_record(plan, record),
         },
@@ -2893,6 +3362,8 @@ fn wal_record_collection_id(record: WalRecord<'_>) -> Option<CollectionId> {
         WalRecord::AllocateRegion { .. }
         | WalRecord::EraseFreeRegionSpan { .. }
         | WalRecord::FreeRegion { .. }
+        | WalRecord::RetireRegion { .. }
+        | WalRecord::ScrubProgress { .. }
         | WalRecord::BeginInlineTransaction { .. }
         | WalRecord::CommitInlineTransaction { .. }
         | WalRecord::RollbackInlineTransaction { .. }
@@ -2909,6 +3380,18 @@ fn apply_open_replay_record<const REGION_COUNT: usize, const MAX_COLLECTIONS: us
     plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
     record: WalRecord<'_>,
 ) -> Result<(), StartupError> {
+    if let WalRecord::ScrubProgress {
+        next_region,
+        completed_passes,
+    } = record
+    {
+        ensure_region_index_in_range(next_region, plan.metadata.region_count)?;
+        plan.scrub_cursor = ScrubCursor {
+            next_region,
+            completed_passes,
+        };
+        return Ok(());
+    }
     apply_wal_record(
         plan.metadata,
         record,
@@ -2918,6 +3401,40 @@ fn apply_open_replay_record<const REGION_COUNT: usize, const MAX_COLLECTIONS: us
     )
 }
 
+/// Advances the allocation head over regions the open transaction allocated
+/// privately before a main-WAL `allocate_region`.
+///
+/// Transaction allocation entries live in the transaction log and are only
+/// replayed at the commit or rollback record, so a rotation or recovery
+/// allocation written after them names a region past the replayed head. The
+/// transaction-log replay later skips those entries as already applied and
+/// still records them as transaction allocations.
+fn skip_unreplayed_transaction_allocations<
+    const REGION_COUNT: usize,
+    const MAX_COLLECTIONS: usize,
+>(
+    plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+    region_index: u32,
+    allocation_head_after: FreeQueuePosition,
+) -> Result<(), StartupError> {
+    let current_allocation_head = plan.free_space.allocation_head_position();
+    if allocation_head_after == current_allocation_head
+        || free_queue_position_at_or_before(allocation_head_after, current_allocation_head)
+    {
+        return Ok(());
+    }
+    for _ in 0..plan.metadata.region_count {
+        let next_region_index = plan.free_space.next_ready_region()?;
+        let position_after = plan.free_space.position_after_allocation()?;
+        if next_region_index == region_index || position_after == allocation_head_after {
+            return Ok(());
+        }
+        plan.free_space
+            .apply_allocate(next_region_index, position_after)?;
+    }
+    Err(StartupError::InvalidFreeSpaceCollection)
+}
+
 fn apply_open_replay_allocator_record<const REGION_COUNT: usize, const MAX_COLLECTIONS: usize>(
     plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
     record: WalRecord<'_>,
@@ -2962,6 +3479,13 @@ fn apply_open_replay_allocator_record<const REGION_COUNT: usize, const MAX_COLLE
             &mut plan.free_space,
             &mut plan.ready_region,
         ),
+        WalRecord::RetireRegion { region_index } => apply_wal_record(
+            plan.metadata,
+            WalRecord::RetireRegion { region_index },
+            &mut plan.collections,
+            &mut plan.free_space,
+            &mut plan.ready_region,
+        ),
         _ => Ok(()),
     }
 }
@@ -2976,8 +3500,8 @@ pub(crate) fn finish_open_formatted_store<
     flash: &mut IO,
     plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
 ) -> Result<StartupState<MAX_COLLECTIONS>, StartupError> {
-    validate_live_collection_types(&plan.collections)?;
-    validate_live_region_bases(flash, &plan.collections)?;
+    validate_live_collection_types(&plan.collections, plan.collection_types)?;
+    validate_live_region_bases(flash, &plan.collections, plan.collection_types)?;
     Ok(StartupState {
         metadata: plan.metadata,
         wal_head: plan.wal_head_candidate,
@@ -3002,8 +3526,8 @@ pub(crate) fn finish_open_formatted_store_into_runtime<
     plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
     runtime: &mut StorageRuntime<MAX_COLLECTIONS>,
 ) -> Result<(), StorageRuntimeError> {
-    validate_live_collection_types(&plan.collections)?;
-    validate_live_region_bases(flash, &plan.collections)?;
+    validate_live_collection_types(&plan.collections, plan.collection_types)?;
+    validate_live_region_bases(flash, &plan.collections, plan.collection_types)?;
     runtime.replace_from_startup_parts(
         plan.metadata,
         plan.wal_head_candidate,
@@ -3015,19 +3539,71 @@ pub(crate) fn finish_open_formatted_store_into_runtime<
         plan.collections.as_slice(),
         plan.retained_transaction_logs.as_slice(),
         plan.pending_wal_recovery_boundary,
+        plan.scrub_cursor,
     )
 }
 
-fn locate_wal_tail<const REGION_SIZE: usize, IO: FlashIo>(
+/// Finishes a salvage open: a live collection that fails its type or head
+/// validation is quarantined instead of failing the open.
+///
+/// Backend failures still fail the open.
+pub(crate) fn salvage_open_formatted_store_into_runtime<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
     flash: &mut IO,
-    metadata: StorageMetadata,
+    plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+    runtime: &mut StorageRuntime<MAX_COLLECTIONS>,
+) -> Result<(), StorageRuntimeError> {
+    let mut quarantined = Vec::<QuarantinedCollection, MAX_COLLECTIONS>::new();
+    for collection in plan.collections.iter() {
+        let result = validate_live_collection_type(collection, plan.collection_types)
+            .and_then(|()| validate_live_region_basis(flash, collection, plan.collection_types));
+        let reason = match result {
+            Ok(()) => continue,
+            Err(StartupError::UnsupportedLiveCollectionType(collection_type)) => {
+                QuarantineReason::UnsupportedType(collection_type)
+            }
+            Err(StartupError::Disk(_) | StartupError::InvalidCommittedRegionHead { .. }) => {
+                QuarantineReason::InvalidHead
+            }
+            Err(error) => return Err(error.into()),
+        };
+        quarantined
+            .push(QuarantinedCollection::new(collection, reason))
+            .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)?;
+    }
+    runtime.replace_from_startup_parts(
+        plan.metadata,
+        plan.wal_head_candidate,
+        plan.wal_tail,
+        plan.wal_append_offset,
+        plan.free_space.clone(),
+        plan.ready_region,
+        plan.max_seen_sequence,
+        plan.collections.as_slice(),
+        plan.retained_transaction_logs.as_slice(),
+        plan.pending_wal_recovery_boundary,
+        plan.scrub_cursor,
+    )?;
+    for collection in quarantined {
+        runtime.quarantine(collection)?;
+    }
+    Ok(())
+}
+
+fn locate_wal_tail<IO: FlashIo>(
+    flash: &mut IO,
+    region_count: u32,
 ) -> Result<(u32, u64), StartupError> {
     let mut max_seen_sequence = 0u64;
     let mut wal_tail = None;
     let mut wal_tail_sequence = 0u64;
     let mut duplicate_tail = false;
 
-    for region_index in 0..metadata.region_count {
+    for region_index in 0..region_count {
         let Ok(header) = flash.read_region(region_index, 0, Header::ENCODED_LEN, Header::decode)?
         else {
             continue;
@@ -3605,11 +4181,16 @@ pub(crate) fn apply_wal_record<const MAX_COLLECTIONS: usize>(
                 ready_boundary_after,
                 free_space.ready_boundary_position(),
             ) {
+                free_space.note_replayed_erase(count, ready_boundary_after);
                 return Ok(());
             }
             free_space.apply_erase(count, ready_boundary_after)?;
         }
-        WalRecord::WalRecovery => {}
+        WalRecord::RetireRegion { region_index } => {
+            ensure_region_index_in_range(region_index, metadata.region_count)?;
+            free_space.retire_region(region_index)?;
+        }
+        WalRecord::WalRecovery | WalRecord::ScrubProgress { .. } => {}
         WalRecord::BeginInlineTransaction { .. }
         | WalRecord::CommitInlineTransaction { .. }
         | WalRecord::RollbackInlineTransaction { .. }
@@ -3692,24 +4273,44 @@ fn has_valid_wal_target<IO: FlashIo>(
         .is_ok())
 }
 
-fn validate_live_collection_types(collections: &[StartupCollection]) -> Result<(), StartupError> {
+fn validate_live_collection_types(
+    collections: &[StartupCollection],
+    collection_types: CollectionTypeRegistry,
+) -> Result<(), StartupError> {
     for collection in collections {
-        if collection.basis == StartupCollectionBasis::Dropped {
-            continue;
-        }
+        validate_live_collection_type(collection, collection_types)?;
+    }
 
-        let Some(collection_type) = collection.collection_type else {
-            return Err(StartupError::UnsupportedLiveCollectionType(0xffff));
-        };
+    Ok(())
+}
 
-        if !matches!(
-            collection_type,
-            CollectionType::CHANNEL_CODE
-                | CollectionType::MAP_CODE
-                | CollectionType::OBJECT_LOG_CODE
-        ) {
-            return Err(StartupError::UnsupportedLiveCollectionType(collection_type));
-        }
+fn validate_live_collection_type(
+    collection: &StartupCollection,
+    collection_types: CollectionTypeRegistry,
+) -> Result<(), StartupError> {
+    if collection.basis == StartupCollectionBasis::Dropped {
+        return Ok(());
+    }
+
+    let Some(collection_type) = collection.collection_type else {
+        return Err(StartupError::UnsupportedLiveCollectionType(0xffff));
+    };
+
+    if !matches!(
+        collection_type,
+        CollectionType::CHANNEL_CODE
+            | CollectionType::MAP_CODE
+            | CollectionType::OBJECT_LOG_CODE
+            | CollectionType::QUEUE_CODE
+            | CollectionType::RING_LOG_CODE
+            | CollectionType::TIME_SERIES_CODE
+            | CollectionType::CELL_CODE
+            | CollectionType::SEQUENCE_CODE
+            | CollectionType::CATALOG_CODE
+            | CollectionType::SET_CODE
+    ) && !collection_types.contains(collection_type)
+    {
+        return Err(StartupError::UnsupportedLiveCollectionType(collection_type));
     }
 
     Ok(())
@@ -3718,19 +4319,42 @@ fn validate_live_collection_types(collections: &[StartupCollection]) -> Result<(
 fn validate_live_region_bases<IO: FlashIo>(
     flash: &mut IO,
     collections: &[StartupCollection],
+    collection_types: CollectionTypeRegistry,
 ) -> Result<(), StartupError> {
     for collection in collections {
-        let StartupCollectionBasis::Region(region_index) = collection.basis else {
-            continue;
-        };
+        validate_live_region_basis(flash, collection, collection_types)?;
+    }
 
-        let region_header = read_region_header(flash, region_index)?;
-        if region_header.collection_id != collection.collection_id {
-            return Err(StartupError::InvalidCommittedRegionHead {
-                collection_id: collection.collection_id,
-                region_index,
-            });
-        }
+    Ok(())
+}
+
+fn validate_live_region_basis<IO: FlashIo>(
+    flash: &mut IO,
+    collection: &StartupCollection,
+    collection_types: CollectionTypeRegistry,
+) -> Result<(), StartupError> {
+    let StartupCollectionBasis::Region(region_index) = collection.basis else {
+        return Ok(());
+    };
+
+    let region_header = read_region_header(flash, region_index)?;
+    let plugin_rejected = collection
+        .collection_type
+        .and_then(|collection_type| collection_types.get(collection_type))
+        .is_some_and(|plugin| {
+            plugin
+                .validate_head(
+                    collection.collection_id,
+                    region_index,
+                    region_header.collection_format,
+                )
+                .is_err()
+        });
+    if region_header.collection_id != collection.collection_id || plugin_rejected {
+        return Err(StartupError::InvalidCommittedRegionHead {
+            collection_id: collection.collection_id,
+            region_index,
+        });
     }
 
     Ok(())
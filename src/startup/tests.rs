@@ -2967,7 +2967,10 @@ fn requirement_validate_live_collection_types_ignores_unsupported_dropped_tombst
         pending_update_count: 0,
     }];
 
-    assert_eq!(validate_live_collection_types(&collections), Ok(()));
+    assert_eq!(
+        validate_live_collection_types(&collections, CollectionTypeRegistry::default()),
+        Ok(())
+    );
 }
 
 //= spec/ring/06-startup-replay.md#startup-replay-implementation-requirements
@@ -3039,7 +3042,7 @@ fn requirement_validate_live_region_bases_rejects_committed_region_for_different
     }];
 
     assert_eq!(
-        validate_live_region_bases(&mut flash, &collections),
+        validate_live_region_bases(&mut flash, &collections, CollectionTypeRegistry::default()),
         Err(StartupError::InvalidCommittedRegionHead {
             collection_id: CollectionId(7),
             region_index: 2,
@@ -4441,6 +4444,73 @@ fn requirement_allocate_region_advances_the_free_space_head() {
     assert_eq!(ready_region, None);
 }
 
+//= spec/ring/06-startup-replay.md#startup-replay-algorithm
+//= type=test
+//# A main-WAL `allocate_region` that appears while a full transaction
+//# is open and its commit has not been replayed MAY name a region past
+//# the replayed `allocation_head`, because the transaction's private
+//# allocation entries replay only at its commit or rollback record.
+//# Replay MUST step `allocation_head` over ready entries until the next
+//# entry names `region_index` before validating the record as above,
+#[test]
+fn requirement_open_transaction_allocate_region_steps_over_private_allocations() {
+    let mut flash = MockFlash::<128, 8, 64>::new(0xff);
+    let metadata = flash.format_empty_store(1, 8, 0xa5).unwrap();
+    let wal_offset = metadata.wal_record_area_offset().unwrap();
+    let mut plan = startup_plan_with_append_offset::<8>(metadata, 0, 0, wal_offset);
+
+    let mut private = plan.free_space.clone();
+    for region_index in [2, 3] {
+        let allocation_head_after = private.position_after_allocation().unwrap();
+        private
+            .apply_allocate(region_index, allocation_head_after)
+            .unwrap();
+    }
+    let allocation_head_after = private.position_after_allocation().unwrap();
+
+    skip_unreplayed_transaction_allocations(&mut plan, 4, allocation_head_after).unwrap();
+    assert_eq!(plan.free_space.allocation_head(), 2);
+    assert_eq!(plan.free_space.next_ready_region(), Ok(4));
+
+    apply_open_replay_allocator_record(
+        &mut plan,
+        WalRecord::AllocateRegion {
+            region_index: 4,
+            allocation_head_after,
+        },
+    )
+    .unwrap();
+    assert_eq!(plan.free_space.allocation_head(), 3);
+    assert_eq!(plan.free_space.next_ready_region(), Ok(5));
+}
+
+//= spec/ring/06-startup-replay.md#startup-replay-algorithm
+//= type=test
+//# MUST reject the record if the ready range ends first,
+#[test]
+fn requirement_open_transaction_allocate_region_without_private_allocations_keeps_head() {
+    let mut flash = MockFlash::<128, 8, 64>::new(0xff);
+    let metadata = flash.format_empty_store(1, 8, 0xa5).unwrap();
+    let wal_offset = metadata.wal_record_area_offset().unwrap();
+    let mut plan = startup_plan_with_append_offset::<8>(metadata, 0, 0, wal_offset);
+    let allocation_head_after = plan.free_space.position_after_allocation().unwrap();
+
+    skip_unreplayed_transaction_allocations(&mut plan, 2, allocation_head_after).unwrap();
+    assert_eq!(plan.free_space.allocation_head(), 0);
+    assert_eq!(plan.free_space.next_ready_region(), Ok(2));
+
+    let mut past_ready_range = plan.free_space.clone();
+    past_ready_range
+        .replace_from_parts(1, 0, 0, 0, &[])
+        .unwrap();
+    let mut exhausted = startup_plan_with_append_offset::<8>(metadata, 0, 0, wal_offset);
+    exhausted.free_space = past_ready_range;
+    assert_eq!(
+        skip_unreplayed_transaction_allocations(&mut exhausted, 4, allocation_head_after),
+        Err(StartupError::InvalidFreeSpaceCollection)
+    );
+}
+
 //= spec/ring/05-disk-format.md#storage-requirements
 //= type=test
 //# `RING-STORAGE-007` Allocator queue links and cursor state MUST NOT
@@ -4641,3 +4711,123 @@ fn assert_startup_rejects_invalid_retained_map_update_payload() {
         Err(MapStorageError::Map(MapError::SerializationError))
     ));
 }
+
+fn format_store_with_cell(flash: &mut MockFlash<512, 6, 4096>) -> CollectionId {
+    format_store_with_cell_using(flash, StorageFormatConfig::new(1, 8, 0xa5))
+}
+
+fn format_store_with_cell_using(
+    flash: &mut MockFlash<512, 6, 4096>,
+    config: StorageFormatConfig,
+) -> CollectionId {
+    let mut storage =
+        Storage::<_, 512, 6>::format(flash, config, crate::test_storage_memory()).unwrap();
+    let mut cell = crate::Cell::new(&mut storage, 11u32).unwrap();
+    cell.set(&mut storage, 12).unwrap();
+    cell.collection_id()
+}
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-013` Opening MUST succeed from the remaining valid copy
+//# when either single copy is corrupted.
+#[test]
+fn requirement_open_survives_one_corrupted_metadata_copy() {
+    for copy_index in 0..crate::METADATA_COPY_COUNT {
+        let mut flash = MockFlash::<512, 6, 4096>::new(0xff);
+        let collection_id = format_store_with_cell(&mut flash);
+        flash.corrupt_metadata_copy(copy_index).unwrap();
+
+        let mut storage =
+            Storage::<_, 512, 6>::open(&mut flash, crate::test_storage_memory()).unwrap();
+        let cell = crate::Cell::<u32>::open(collection_id, &mut storage).unwrap();
+        assert_eq!(*cell.get(), 12);
+    }
+}
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-015` Metadata recovery MUST refuse to run while a valid
+//# copy is readable. It MUST take only `min_free_regions` from the caller,
+//# derive `erased_byte`, `wal_record_magic`, and `wal_write_granule` from
+//# the media, and reject the rebuild unless the whole main-WAL chain from
+//# the tail prologue's head to the tail validates against the rebuilt
+//# metadata. It MUST write both copies and sync before opening the store.
+#[test]
+fn requirement_recover_metadata_rebuilds_lost_copies_from_headers() {
+    let mut flash = MockFlash::<512, 6, 4096>::new(0xff);
+    let collection_id = format_store_with_cell(&mut flash);
+    let formatted = *flash.metadata().unwrap();
+
+    assert!(matches!(
+        Storage::<_, 512, 6>::recover_metadata(&mut flash, 1, crate::test_storage_memory()),
+        Err(crate::StorageOpenError::Runtime(
+            StorageRuntimeError::Startup(StartupError::MetadataIntact)
+        ))
+    ));
+
+    flash.corrupt_metadata_copy(0).unwrap();
+    flash.corrupt_metadata_copy(1).unwrap();
+    assert!(Storage::<_, 512, 6>::open(&mut flash, crate::test_storage_memory()).is_err());
+
+    flash.clear_operations();
+    {
+        let mut storage =
+            Storage::<_, 512, 6>::recover_metadata(&mut flash, 1, crate::test_storage_memory())
+                .unwrap();
+        assert_eq!(storage.metadata(), formatted);
+        let cell = crate::Cell::<u32>::open(collection_id, &mut storage).unwrap();
+        assert_eq!(*cell.get(), 12);
+    }
+    let operations = flash.operations();
+    let writes = operations
+        .iter()
+        .filter(|operation| **operation == crate::MockOperation::WriteMetadata)
+        .count();
+    assert_eq!(writes, crate::METADATA_COPY_COUNT);
+    let last_write = operations
+        .iter()
+        .rposition(|operation| *operation == crate::MockOperation::WriteMetadata)
+        .unwrap();
+    assert_eq!(operations[last_write + 1], crate::MockOperation::Sync);
+
+    let mut storage = Storage::<_, 512, 6>::open(&mut flash, crate::test_storage_memory()).unwrap();
+    assert_eq!(storage.metadata(), formatted);
+    let cell = crate::Cell::<u32>::open(collection_id, &mut storage).unwrap();
+    assert_eq!(*cell.get(), 12);
+
+    // Other encoding parameters are derived the same way.
+    let mut flash = MockFlash::<512, 6, 4096>::new(0x00);
+    let collection_id =
+        format_store_with_cell_using(&mut flash, StorageFormatConfig::new(2, 4, 0x5a));
+    let formatted = *flash.metadata().unwrap();
+    flash.corrupt_metadata_copy(0).unwrap();
+    flash.corrupt_metadata_copy(1).unwrap();
+    let mut storage =
+        Storage::<_, 512, 6>::recover_metadata(&mut flash, 2, crate::test_storage_memory())
+            .unwrap();
+    assert_eq!(storage.metadata(), formatted);
+    let cell = crate::Cell::<u32>::open(collection_id, &mut storage).unwrap();
+    assert_eq!(*cell.get(), 12);
+}
+
+//= spec/ring/05-disk-format.md#metadata-copies
+//= type=test
+//# `RING-META-016` Metadata recovery MUST fail with
+//# `NoWalRecordToRecover`, writing nothing, when no main-WAL region holds
+//# a record to derive the record magic and write granule from.
+#[test]
+fn requirement_recover_metadata_needs_a_wal_record() {
+    let mut flash = MockFlash::<512, 6, 4096>::new(0xff);
+    flash.format_empty_store(1, 8, 0xa5).unwrap();
+    flash.corrupt_metadata_copy(0).unwrap();
+    flash.corrupt_metadata_copy(1).unwrap();
+
+    assert!(matches!(
+        Storage::<_, 512, 6>::recover_metadata(&mut flash, 1, crate::test_storage_memory()),
+        Err(crate::StorageOpenError::Runtime(
+            StorageRuntimeError::Startup(StartupError::NoWalRecordToRecover)
+        ))
+    ));
+    assert!(flash.read_metadata().is_err());
+}
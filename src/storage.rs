@@ -2,8 +2,10 @@
 
 use heapless::Vec;
 
+use crate::collection_type::CollectionTypeRegistry;
 use crate::disk::{
-    encode_free_space_region_segment, encode_transaction_log_region_prefix_with_cursors,
+    encode_free_space_region_segment, encode_free_space_wear_segment,
+    encode_transaction_log_region_prefix_with_cursors, free_space_wear_counts_per_region,
     FreeQueuePosition, FreeSpaceCursors, FreeSpaceEntry, FreeSpaceRegionPrologue, Header,
     WalRegionPrologue, TRANSACTION_LOG_V2_FORMAT,
 };
@@ -11,6 +13,9 @@ use crate::flash_io::{FlashIo, StorageFormatError, StorageIoError};
 use crate::free_space::{FreeSpaceError, FreeSpaceState};
 use crate::mock::{MockError, MockFormatError};
 use crate::mode::StorageMode;
+use crate::read_only::ReadOnlyFlashError;
+use crate::salvage::QuarantinedCollection;
+use crate::scrub::ScrubCursor;
 use crate::startup::{apply_wal_record, StartupCollection, StartupError, StartupOpenPlan};
 use crate::transaction_log::{
     decode_private_suffix_entry, encode_private_suffix_entry, log_position,
@@ -64,6 +69,13 @@ pub enum StorageRuntimeError {
     FileBacking(crate::file_backing::FileBackingError),
     /// Startup replay or recovery failed.
     Startup(StartupError),
+    /// The backend permanently failed to program or erase a region.
+    BadRegion(u32),
+    /// The storage was opened with [`Storage::open_read_only`](crate::Storage::open_read_only)
+    /// and the operation would write to it.
+    ReadOnly,
+    /// The [`ReadOnlyFlash`](crate::ReadOnlyFlash) overlay failed.
+    ReadOnlyFlash(ReadOnlyFlashError),
     /// WAL record encoding or decoding failed.
     WalRecord(WalRecordError),
     /// The configured collection capacity was exceeded.
@@ -78,6 +90,10 @@ pub enum StorageRuntimeError {
     UnknownCollection(CollectionId),
     /// A referenced collection was already dropped.
     DroppedCollection(CollectionId),
+    /// A referenced collection was quarantined by a salvage open.
+    QuarantinedCollection(CollectionId),
+    /// A quarantine-only operation named a collection that is not quarantined.
+    CollectionNotQuarantined(CollectionId),
     /// A retained record changed collection type unexpectedly.
     CollectionTypeMismatch {
         /// Collection being validated.
@@ -220,6 +236,7 @@ impl From<StorageFormatError> for StorageRuntimeError {
             StorageFormatError::EmbeddedStorage(error) => Self::EmbeddedStorageFormat(error),
             #[cfg(all(feature = "file-backing", target_os = "linux"))]
             StorageFormatError::FileBacking(error) => Self::FileBackingFormat(error),
+            StorageFormatError::ReadOnly => Self::ReadOnly,
         }
     }
 }
@@ -238,6 +255,9 @@ impl From<StorageIoError> for StorageRuntimeError {
             StorageIoError::EmbeddedStorage(error) => Self::EmbeddedStorage(error),
             #[cfg(all(feature = "file-backing", target_os = "linux"))]
             StorageIoError::FileBacking(error) => Self::FileBacking(error),
+            StorageIoError::BadRegion(region_index) => Self::BadRegion(region_index),
+            StorageIoError::ReadOnly(ReadOnlyFlashError::WriteRejected) => Self::ReadOnly,
+            StorageIoError::ReadOnly(error) => Self::ReadOnlyFlash(error),
         }
     }
 }
@@ -479,7 +499,9 @@ pub(crate) struct WalHeadReclaimPlan<const MAX_COLLECTIONS: usize> {
     source_tail: u32,
     source_tail_append_offset: usize,
     original_collections: Vec<StartupCollection, MAX_COLLECTIONS>,
+    superseded_collections: Vec<CollectionId, MAX_COLLECTIONS>,
     imported_transaction_logs: Vec<TransactionLogRange, MAX_RETAINED_TRANSACTION_LOGS>,
+    retired_regions_materialized: bool,
 }
 
 impl<const MAX_COLLECTIONS: usize> WalHeadReclaimPlan<MAX_COLLECTIONS> {
@@ -489,12 +511,15 @@ impl<const MAX_COLLECTIONS: usize> WalHeadReclaimPlan<MAX_COLLECTIONS> {
             source_tail: 0,
             source_tail_append_offset: 0,
             original_collections: Vec::new(),
+            superseded_collections: Vec::new(),
             imported_transaction_logs: Vec::new(),
+            retired_regions_materialized: false,
         }
     }
 
     pub(crate) fn clear(&mut self) {
         self.original_collections.clear();
+        self.superseded_collections.clear();
         self.imported_transaction_logs.clear();
     }
 
@@ -526,6 +551,10 @@ pub struct StorageRuntime<const MAX_COLLECTIONS: usize = 8> {
     transaction_original_free_space: Option<FreeSpaceState>,
     transaction_original_ready_region: Option<u32>,
     transaction_original_ready_region_valid: bool,
+    collection_types: CollectionTypeRegistry,
+    quarantined: Vec<QuarantinedCollection, MAX_COLLECTIONS>,
+    relocation_epoch: u64,
+    scrub_cursor: ScrubCursor,
 }
 
 impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
@@ -557,9 +586,22 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             transaction_original_free_space: None,
             transaction_original_ready_region: None,
             transaction_original_ready_region_valid: false,
+            collection_types: CollectionTypeRegistry::default(),
+            quarantined: Vec::new(),
+            relocation_epoch: 0,
+            scrub_cursor: ScrubCursor::new(),
         }
     }
 
+    pub(crate) fn set_collection_types(&mut self, collection_types: CollectionTypeRegistry) {
+        self.collection_types = collection_types;
+    }
+
+    /// Returns the downstream collection types this runtime accepts.
+    pub fn collection_types(&self) -> CollectionTypeRegistry {
+        self.collection_types
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn replace_from_startup_parts(
         &mut self,
@@ -573,6 +615,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collections: &[StartupCollection],
         retained_transaction_logs: &[RetainedTransactionLog],
         pending_wal_recovery_boundary: bool,
+        scrub_cursor: ScrubCursor,
     ) -> Result<(), StorageRuntimeError> {
         let slot_count = u32::try_from(TRANSACTION_SLOT_COUNT)
             .map_err(|_| StorageRuntimeError::WalRotationRequired)?;
@@ -589,6 +632,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 .push(collection)
                 .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)?;
         }
+        self.quarantined.clear();
 
         self.metadata = metadata;
         self.wal_head = wal_head;
@@ -597,7 +641,9 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         self.free_space = free_space;
         self.ready_region = ready_region;
         self.max_seen_sequence = max_seen_sequence;
+        self.relocation_epoch = 0;
         self.pending_wal_recovery_boundary = pending_wal_recovery_boundary;
+        self.scrub_cursor = scrub_cursor;
         self.transaction_slots = core::array::from_fn(|_| TransactionSlot::empty());
         self.retained_transaction_logs.clear();
         for retained in retained_transaction_logs.iter().cloned() {
@@ -609,7 +655,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         Ok(())
     }
 
-    fn validate_supported_user_collection_type(
+    pub(crate) fn validate_supported_user_collection_type(
+        &self,
         collection_id: CollectionId,
         collection_type: u16,
     ) -> Result<(), StorageRuntimeError> {
@@ -618,8 +665,17 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         }
         if !matches!(
             collection_type,
-            CollectionType::MAP_CODE | CollectionType::OBJECT_LOG_CODE
-        ) {
+            CollectionType::CHANNEL_CODE
+                | CollectionType::MAP_CODE
+                | CollectionType::OBJECT_LOG_CODE
+                | CollectionType::QUEUE_CODE
+                | CollectionType::RING_LOG_CODE
+                | CollectionType::TIME_SERIES_CODE
+                | CollectionType::CELL_CODE
+                | CollectionType::SEQUENCE_CODE
+                | CollectionType::SET_CODE
+        ) && !self.collection_types.contains(collection_type)
+        {
             return Err(StorageRuntimeError::UnsupportedCollectionType(
                 collection_type,
             ));
@@ -628,6 +684,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
     }
 
     fn validate_supported_head_collection_type(
+        &self,
         collection_id: CollectionId,
         collection_type: u16,
     ) -> Result<(), StorageRuntimeError> {
@@ -642,7 +699,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             });
         }
 
-        Self::validate_supported_user_collection_type(collection_id, collection_type)
+        self.validate_supported_user_collection_type(collection_id, collection_type)
     }
 
     /// Returns storage metadata recovered from disk.
@@ -675,6 +732,10 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         self.free_space.entries().last().copied()
     }
 
+    pub(crate) fn free_space(&self) -> &FreeSpaceState {
+        &self.free_space
+    }
+
     #[cfg(test)]
     pub(crate) fn free_space_cursors(&self) -> (u32, u32, u32, u32, u32) {
         (
@@ -691,6 +752,17 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         self.free_space.entries()
     }
 
+    #[cfg(test)]
+    pub(crate) fn free_space_wear_regions(&self) -> &[u32] {
+        self.free_space.wear_regions()
+    }
+
+    /// Returns whether the region was retired after a permanent program or
+    /// erase failure.
+    pub fn is_region_retired(&self, region_index: u32) -> bool {
+        self.free_space.is_retired(region_index)
+    }
+
     /// Returns the current free-space allocation cursor.
     pub fn allocation_head(&self) -> FreeQueuePosition {
         self.free_space.allocation_head_position()
@@ -716,6 +788,62 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         self.max_seen_sequence
     }
 
+    /// Returns the epoch of the last wear-leveling or bad-region move of
+    /// regions that collection handles cache.
+    ///
+    /// Handles record the epoch when they replay and replay again once it
+    /// changes. It is zero until a move, then the newest sequence the move
+    /// wrote, so it never returns to a value a handle recorded earlier.
+    pub(crate) fn relocation_epoch(&self) -> u64 {
+        self.relocation_epoch
+    }
+
+    /// Records that regions named by collection snapshots moved.
+    pub(crate) fn note_region_relocation(&mut self) {
+        self.relocation_epoch = self.max_seen_sequence;
+    }
+
+    /// Returns the collections quarantined by a salvage open.
+    pub fn quarantined_collections(&self) -> &[QuarantinedCollection] {
+        &self.quarantined
+    }
+
+    /// Returns whether a salvage open quarantined `collection_id`.
+    pub fn is_quarantined(&self, collection_id: CollectionId) -> bool {
+        self.quarantined
+            .iter()
+            .any(|collection| collection.collection_id == collection_id)
+    }
+
+    /// Rejects typed access to a quarantined collection.
+    pub(crate) fn ensure_not_quarantined(
+        &self,
+        collection_id: CollectionId,
+    ) -> Result<(), StorageRuntimeError> {
+        if self.is_quarantined(collection_id) {
+            return Err(StorageRuntimeError::QuarantinedCollection(collection_id));
+        }
+        Ok(())
+    }
+
+    /// Records a quarantined collection, keeping the first reason found.
+    pub(crate) fn quarantine(
+        &mut self,
+        collection: QuarantinedCollection,
+    ) -> Result<(), StorageRuntimeError> {
+        if self.is_quarantined(collection.collection_id) {
+            return Ok(());
+        }
+        self.quarantined
+            .push(collection)
+            .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)
+    }
+
+    pub(crate) fn release_quarantine(&mut self, collection_id: CollectionId) {
+        self.quarantined
+            .retain(|collection| collection.collection_id != collection_id);
+    }
+
     /// Returns the replay-tracked collections.
     pub fn collections(&self) -> &[StartupCollection] {
         self.collections.as_slice()
@@ -775,6 +903,11 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
     ) -> Result<u32, StorageRuntimeError> {
         let transaction_owned_allocation = if collection_id != CollectionId(0) {
             self.require_collection_transaction(collection_id)?;
+            self.ensure_transaction_allocation_entry_room::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                TransactionAllocationPurpose::DataRegion,
+            )?;
             true
         } else {
             false
@@ -826,6 +959,10 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
     }
 
     /// Writes a committed collection region and syncs it durably.
+    ///
+    /// If the backend reports `region_index` bad, the region is retired and
+    /// the payload is written to a freshly allocated region instead. Returns
+    /// the region that holds the payload.
     pub fn write_committed_region<
         const REGION_SIZE: usize,
         const REGION_COUNT: usize,
@@ -838,6 +975,131 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collection_id: CollectionId,
         collection_format: u16,
         payload: &[u8],
+    ) -> Result<u32, StorageRuntimeError> {
+        let mut region_index = region_index;
+        for _attempt in 0..self.metadata.region_count {
+            match self.program_committed_region::<REGION_SIZE, IO>(
+                flash,
+                workspace,
+                region_index,
+                collection_id,
+                collection_format,
+                payload,
+            ) {
+                Ok(()) => return Ok(region_index),
+                Err(StorageRuntimeError::BadRegion(failed)) if failed == region_index => {
+                    region_index = self
+                        .replace_bad_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
+                            flash,
+                            workspace,
+                            collection_id,
+                            region_index,
+                        )?;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Err(StorageRuntimeError::BadRegion(region_index))
+    }
+
+    /// Writes a committed region that other durable state already names,
+    /// such as a linked segment, so it cannot move.
+    ///
+    /// If the backend reports `region_index` bad, the region is retired and
+    /// `BadRegion` is returned so the collection can move the data itself.
+    pub(crate) fn write_committed_region_in_place<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        region_index: u32,
+        collection_id: CollectionId,
+        collection_format: u16,
+        payload: &[u8],
+    ) -> Result<(), StorageRuntimeError> {
+        match self.program_committed_region::<REGION_SIZE, IO>(
+            flash,
+            workspace,
+            region_index,
+            collection_id,
+            collection_format,
+            payload,
+        ) {
+            Err(StorageRuntimeError::BadRegion(failed)) if failed == region_index => {
+                self.retire_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    flash,
+                    workspace,
+                    region_index,
+                )?;
+                Err(StorageRuntimeError::BadRegion(region_index))
+            }
+            result => result,
+        }
+    }
+
+    /// Retires a region that failed to program and allocates its
+    /// replacement the same way the failed region was allocated: through the
+    /// open transaction of `collection_id`, or the main WAL otherwise.
+    fn replace_bad_committed_region<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        collection_id: CollectionId,
+        region_index: u32,
+    ) -> Result<u32, StorageRuntimeError> {
+        self.retire_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            region_index,
+        )?;
+        match self.active_transaction_snapshot() {
+            None => self.allocate_privileged_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                flash, workspace,
+            ),
+            Some(open) if open.collection_id == collection_id => {
+                self.ensure_transaction_allocation_entry_room::<REGION_SIZE, REGION_COUNT, IO>(
+                    flash,
+                    workspace,
+                    TransactionAllocationPurpose::DataRegion,
+                )?;
+                let replacement = self
+                    .free_space
+                    .next_ready_region()
+                    .map_err(|_| StorageRuntimeError::NoFreeRegionForRotation)?;
+                let allocation_head_after = self.free_space.position_after_allocation()?;
+                self.append_transaction_allocation_entry_with_rotation::<
+                    REGION_SIZE,
+                    REGION_COUNT,
+                    IO,
+                >(
+                    flash,
+                    workspace,
+                    collection_id,
+                    replacement,
+                    allocation_head_after,
+                    TransactionAllocationPurpose::DataRegion,
+                )?;
+                Ok(replacement)
+            }
+            Some(_) => Err(StorageRuntimeError::BadRegion(region_index)),
+        }
+    }
+
+    fn program_committed_region<const REGION_SIZE: usize, IO: FlashIo>(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        region_index: u32,
+        collection_id: CollectionId,
+        collection_format: u16,
+        payload: &[u8],
     ) -> Result<(), StorageRuntimeError> {
         let payload_capacity = committed_payload_capacity::<REGION_SIZE>(self.metadata)?;
         if payload.len() > payload_capacity {
@@ -948,6 +1210,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             WalRecord::AllocateRegion { .. }
             | WalRecord::EraseFreeRegionSpan { .. }
             | WalRecord::FreeRegion { .. }
+            | WalRecord::RetireRegion { .. }
+            | WalRecord::ScrubProgress { .. }
             | WalRecord::BeginInlineTransaction { .. }
             | WalRecord::CommitInlineTransaction { .. }
             | WalRecord::RollbackInlineTransaction { .. }
@@ -1110,7 +1374,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
     }
 
     fn restore_transaction_runtime_snapshot(&mut self) -> Result<(), StorageRuntimeError> {
-        if let Some(free_space) = self.transaction_original_free_space.clone() {
+        if let Some(mut free_space) = self.transaction_original_free_space.clone() {
+            free_space.keep_retirements_from(&self.free_space);
             self.free_space = free_space;
         }
         if self.transaction_original_ready_region_valid {
@@ -1143,6 +1408,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         })
     }
 
+    /// Writes a committed region whose payload was staged in the workspace,
+    /// returning the region that holds it; see [`Self::write_committed_region`].
     pub(crate) fn write_committed_region_from_workspace_payload<
         const REGION_SIZE: usize,
         const REGION_COUNT: usize,
@@ -1155,6 +1422,77 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collection_id: CollectionId,
         collection_format: u16,
         payload_len: usize,
+    ) -> Result<u32, StorageRuntimeError> {
+        match self.program_committed_region_from_workspace_payload::<REGION_SIZE, IO>(
+            flash,
+            workspace,
+            region_index,
+            collection_id,
+            collection_format,
+            payload_len,
+        ) {
+            Ok(()) => Ok(region_index),
+            Err(StorageRuntimeError::BadRegion(failed)) if failed == region_index => self
+                .rewrite_workspace_payload_elsewhere::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                region_index,
+                collection_id,
+                collection_format,
+                payload_len,
+            ),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Moves a staged payload out of the workspace, whose buffers retiring
+    /// the bad region reuses, and writes it to a replacement region.
+    fn rewrite_workspace_payload_elsewhere<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        region_index: u32,
+        collection_id: CollectionId,
+        collection_format: u16,
+        payload_len: usize,
+    ) -> Result<u32, StorageRuntimeError> {
+        let mut payload = [self.metadata.erased_byte; REGION_SIZE];
+        let (_, payload_source) = workspace.committed_write_buffers();
+        let source = payload_source.get(..payload_len).ok_or(
+            StorageRuntimeError::CommittedRegionTooLarge {
+                payload_len,
+                capacity: REGION_SIZE,
+            },
+        )?;
+        payload[..payload_len].copy_from_slice(source);
+        let replacement = self.replace_bad_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+            region_index,
+        )?;
+        self.write_committed_region::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            replacement,
+            collection_id,
+            collection_format,
+            &payload[..payload_len],
+        )
+    }
+
+    fn program_committed_region_from_workspace_payload<const REGION_SIZE: usize, IO: FlashIo>(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        region_index: u32,
+        collection_id: CollectionId,
+        collection_format: u16,
+        payload_len: usize,
     ) -> Result<(), StorageRuntimeError> {
         let payload_capacity = committed_payload_capacity::<REGION_SIZE>(self.metadata)?;
         if payload_len > payload_capacity {
@@ -1226,12 +1564,12 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collection_id: CollectionId,
         collection_type: u16,
     ) -> Result<(), StorageRuntimeError> {
-        Self::validate_supported_user_collection_type(collection_id, collection_type)?;
+        self.validate_supported_user_collection_type(collection_id, collection_type)?;
         if self.find_collection(collection_id).is_some() {
             return Err(StorageRuntimeError::DuplicateCollection(collection_id));
         }
 
-        self.append_record::<REGION_SIZE, REGION_COUNT, IO>(
+        self.append_record_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
             flash,
             workspace,
             WalRecord::NewCollection {
@@ -1255,6 +1593,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         if collection.basis() == StartupCollectionBasis::Dropped {
             return Err(StorageRuntimeError::DroppedCollection(collection_id));
         }
+        self.ensure_not_quarantined(collection_id)?;
 
         if self.transaction_open_for(collection_id) {
             return self
@@ -1297,6 +1636,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         if collection.basis() == StartupCollectionBasis::Dropped {
             return Err(StorageRuntimeError::DroppedCollection(collection_id));
         }
+        self.ensure_not_quarantined(collection_id)?;
 
         if self.transaction_open_for(collection_id) {
             return self
@@ -1340,6 +1680,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         if collection.basis() == StartupCollectionBasis::Dropped {
             return Err(StorageRuntimeError::DroppedCollection(collection_id));
         }
+        self.ensure_not_quarantined(collection_id)?;
 
         metrics.increment(StoragePerfCounter::WalUpdateRecords);
         if self.transaction_open_for(collection_id) {
@@ -1374,11 +1715,12 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collection_type: u16,
         payload: &[u8],
     ) -> Result<(), StorageRuntimeError> {
-        Self::validate_supported_user_collection_type(collection_id, collection_type)?;
+        self.validate_supported_user_collection_type(collection_id, collection_type)?;
         if let Some(collection) = self.find_collection(collection_id) {
             if collection.basis() == StartupCollectionBasis::Dropped {
                 return Err(StorageRuntimeError::DroppedCollection(collection_id));
             }
+            self.ensure_not_quarantined(collection_id)?;
             if let Some(expected) = collection.collection_type() {
                 if expected != collection_type {
                     return Err(StorageRuntimeError::CollectionTypeMismatch {
@@ -1428,11 +1770,12 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collection_type: u16,
         payload: &[u8],
     ) -> Result<(), StorageRuntimeError> {
-        Self::validate_supported_user_collection_type(collection_id, collection_type)?;
+        self.validate_supported_user_collection_type(collection_id, collection_type)?;
         if let Some(collection) = self.find_collection(collection_id) {
             if collection.basis() == StartupCollectionBasis::Dropped {
                 return Err(StorageRuntimeError::DroppedCollection(collection_id));
             }
+            self.ensure_not_quarantined(collection_id)?;
             if let Some(expected) = collection.collection_type() {
                 if expected != collection_type {
                     return Err(StorageRuntimeError::CollectionTypeMismatch {
@@ -1514,6 +1857,27 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         flash: &mut IO,
         workspace: &mut StorageWorkspace<REGION_SIZE>,
         collection_id: CollectionId,
+    ) -> Result<Option<u32>, StorageRuntimeError> {
+        self.drop_collection_with_record_and_begin_reclaim::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+            None,
+        )
+    }
+
+    /// Drops a collection in the same atomic record group as `trailing` and
+    /// begins reclaim for its previous region basis, if any.
+    pub(crate) fn drop_collection_with_record_and_begin_reclaim<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        collection_id: CollectionId,
+        trailing: Option<WalRecord<'_>>,
     ) -> Result<Option<u32>, StorageRuntimeError> {
         let collection = self
             .find_collection(collection_id)
@@ -1527,36 +1891,175 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             _ => None,
         };
 
-        if let Some(region_index) = previous_region {
-            self.ensure_free_space_metadata_capacity_for_len::<REGION_SIZE, REGION_COUNT, IO>(
-                flash,
-                workspace,
-                self.free_space.entries().len().saturating_add(1),
-            )?;
-            let records = [
-                WalRecord::DropCollection { collection_id },
-                WalRecord::FreeRegion {
+        let free_record = match previous_region {
+            Some(region_index) => {
+                self.ensure_free_space_metadata_capacity_for_len::<REGION_SIZE, REGION_COUNT, IO>(
+                    flash,
+                    workspace,
+                    self.free_space.entries().len().saturating_add(1),
+                )?;
+                Some(WalRecord::FreeRegion {
                     region_index,
                     append_tail_after: self.free_space.position_after_append()?,
-                },
-            ];
-            self.append_internal_atomic_records_for_collection_with_rotation::<
-                REGION_SIZE,
-                REGION_COUNT,
-                IO,
-            >(flash, workspace, collection_id, &records)?;
-        } else {
-            let records = [WalRecord::DropCollection { collection_id }];
-            self.append_internal_atomic_records_for_collection_with_rotation::<
-                REGION_SIZE,
-                REGION_COUNT,
-                IO,
-            >(flash, workspace, collection_id, &records)?;
+                })
+            }
+            None => None,
+        };
+        let drop_record = WalRecord::DropCollection { collection_id };
+        match (free_record, trailing) {
+            (Some(free_record), Some(trailing)) => self
+                .append_internal_atomic_records_for_collection_with_rotation::<
+                    REGION_SIZE,
+                    REGION_COUNT,
+                    IO,
+                >(flash, workspace, collection_id, &[drop_record, free_record, trailing])?,
+            (Some(free_record), None) => self
+                .append_internal_atomic_records_for_collection_with_rotation::<
+                    REGION_SIZE,
+                    REGION_COUNT,
+                    IO,
+                >(flash, workspace, collection_id, &[drop_record, free_record])?,
+            (None, Some(trailing)) => self
+                .append_internal_atomic_records_for_collection_with_rotation::<
+                    REGION_SIZE,
+                    REGION_COUNT,
+                    IO,
+                >(flash, workspace, collection_id, &[drop_record, trailing])?,
+            (None, None) => self
+                .append_internal_atomic_records_for_collection_with_rotation::<
+                    REGION_SIZE,
+                    REGION_COUNT,
+                    IO,
+                >(flash, workspace, collection_id, &[drop_record])?,
         }
 
         Ok(previous_region)
     }
 
+    /// Drops a collection like
+    /// [`Self::drop_collection_with_record_and_begin_reclaim`], also freeing
+    /// every region a plugin collection's head references.
+    ///
+    /// A registered plugin type names the regions beyond its head through
+    /// [`crate::CollectionTypePlugin::referenced_regions`]. Its drop and the
+    /// frees commit in one collection transaction, so recovery finishes the
+    /// frees of an interrupted drop, and `trailing` follows as its own inline
+    /// transaction. `regions` is scratch for the collected region list.
+    pub(crate) fn drop_collection_with_record_and_free_regions<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        collection_id: CollectionId,
+        trailing: Option<WalRecord<'_>>,
+        regions: &mut Vec<u32, REGION_COUNT>,
+    ) -> Result<Option<u32>, StorageRuntimeError> {
+        let collection = *self
+            .find_collection(collection_id)
+            .ok_or(StorageRuntimeError::UnknownCollection(collection_id))?;
+        let StartupCollectionBasis::Region(head_region) = collection.basis() else {
+            return self
+                .drop_collection_with_record_and_begin_reclaim::<REGION_SIZE, REGION_COUNT, IO>(
+                    flash,
+                    workspace,
+                    collection_id,
+                    trailing,
+                );
+        };
+        if !collection
+            .collection_type()
+            .is_some_and(|code| self.collection_types.contains(code))
+        {
+            return self
+                .drop_collection_with_record_and_begin_reclaim::<REGION_SIZE, REGION_COUNT, IO>(
+                    flash,
+                    workspace,
+                    collection_id,
+                    trailing,
+                );
+        }
+        regions.clear();
+        crate::startup::collect_collection_committed_regions::<
+            REGION_SIZE,
+            REGION_COUNT,
+            IO,
+            MAX_COLLECTIONS,
+        >(
+            flash,
+            workspace,
+            self.metadata,
+            self.collection_types,
+            Some(collection),
+            regions,
+        )?;
+
+        self.begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+        )?;
+        if let Err(error) = self.stage_collection_drop_and_frees::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+            regions,
+        ) {
+            let _ = self.rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                collection_id,
+            );
+            return Err(error);
+        }
+        self.finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+        )?;
+        if let Some(trailing) = trailing {
+            self.append_inline_transaction_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                &[trailing],
+            )?;
+        }
+        Ok(Some(head_region))
+    }
+
+    fn stage_collection_drop_and_frees<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        collection_id: CollectionId,
+        regions: &[u32],
+    ) -> Result<(), StorageRuntimeError> {
+        for region_index in regions.iter().copied() {
+            self.append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                collection_id,
+                region_index,
+            )?;
+        }
+        self.append_drop_collection::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+        )?;
+        self.commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+        )
+    }
+
     /// Appends a `head` record pointing at a committed region.
     pub fn append_head<const REGION_SIZE: usize, const REGION_COUNT: usize, IO: FlashIo>(
         &mut self,
@@ -1566,12 +2069,13 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collection_type: u16,
         region_index: u32,
     ) -> Result<(), StorageRuntimeError> {
-        Self::validate_supported_head_collection_type(collection_id, collection_type)?;
+        self.validate_supported_head_collection_type(collection_id, collection_type)?;
         let collection = self.find_collection(collection_id);
         if let Some(collection) = collection {
             if collection.basis() == StartupCollectionBasis::Dropped {
                 return Err(StorageRuntimeError::DroppedCollection(collection_id));
             }
+            self.ensure_not_quarantined(collection_id)?;
             if let Some(expected) = collection.collection_type() {
                 if expected != collection_type {
                     return Err(StorageRuntimeError::CollectionTypeMismatch {
@@ -1640,12 +2144,13 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collection_type: u16,
         region_index: u32,
     ) -> Result<(), StorageRuntimeError> {
-        Self::validate_supported_head_collection_type(collection_id, collection_type)?;
+        self.validate_supported_head_collection_type(collection_id, collection_type)?;
         let collection = self.find_collection(collection_id);
         if let Some(collection) = collection {
             if collection.basis() == StartupCollectionBasis::Dropped {
                 return Err(StorageRuntimeError::DroppedCollection(collection_id));
             }
+            self.ensure_not_quarantined(collection_id)?;
             if let Some(expected) = collection.collection_type() {
                 if expected != collection_type {
                     return Err(StorageRuntimeError::CollectionTypeMismatch {
@@ -1825,16 +2330,36 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         purpose: TransactionAllocationPurpose,
     ) -> Result<(), StorageRuntimeError> {
         self.require_collection_transaction(collection_id)?;
+        self.ensure_transaction_allocation_entry_room::<REGION_SIZE, REGION_COUNT, IO>(
+            flash, workspace, purpose,
+        )?;
+        self.append_transaction_allocation_entry::<REGION_SIZE, IO>(
+            flash,
+            workspace,
+            collection_id,
+            region_index,
+            allocation_head_after,
+            purpose,
+        )
+    }
+
+    /// Grows the open transaction log until one more allocation entry fits.
+    ///
+    /// Growing allocates the next ready region, so this must run before a
+    /// caller picks the region its own allocation entry will name.
+    fn ensure_transaction_allocation_entry_room<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        purpose: TransactionAllocationPurpose,
+    ) -> Result<(), StorageRuntimeError> {
         for _attempt in 0..self.metadata.region_count {
             if self.transaction_allocation_entry_fits(purpose)? {
-                return self.append_transaction_allocation_entry::<REGION_SIZE, IO>(
-                    flash,
-                    workspace,
-                    collection_id,
-                    region_index,
-                    allocation_head_after,
-                    purpose,
-                );
+                return Ok(());
             }
             self.grow_transaction_log::<REGION_SIZE, REGION_COUNT, IO>(flash, workspace)?;
         }
@@ -2050,6 +2575,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             workspace,
             self.free_space.entries().len(),
         )?;
+        self.ensure_free_space_wear_table::<REGION_SIZE, REGION_COUNT, IO>(flash, workspace)?;
         self.materialize_free_space_collection::<REGION_SIZE, IO>(flash)?;
         self.prepare_wal_head_reclaim::<REGION_SIZE, IO>(flash, workspace, plan)?;
         source_regions.clear();
@@ -2418,6 +2944,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 encoded_len,
                 false,
                 false,
+                false,
             ) {
                 Ok(()) => return Ok(()),
                 Err(StorageRuntimeError::WalRotationRequired) => {
@@ -2447,31 +2974,125 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         if self.free_space.dirty_count() < count {
             return Err(StorageRuntimeError::InvalidFreeSpaceCommand);
         }
-        let start = self.free_space.ready_boundary();
-        let end = start
+        let end = self
+            .free_space
+            .ready_boundary()
             .checked_add(count)
             .ok_or(StorageRuntimeError::InvalidFreeSpaceCommand)?;
-        for entry_index in start..end {
-            let region_index = *self
-                .free_space
-                .entries()
-                .get(
-                    usize::try_from(entry_index)
-                        .map_err(|_| StorageRuntimeError::InvalidFreeSpaceCommand)?,
-                )
-                .ok_or(StorageRuntimeError::InvalidFreeSpaceCommand)?;
-            flash.erase_region(region_index)?;
+        for _attempt in 0..=count {
+            let start = self.free_space.ready_boundary();
+            if start >= end {
+                return Ok(());
+            }
+            let mut bad_region = None;
+            let mut erased_end = end;
+            for entry_index in start..end {
+                let region_index = *self
+                    .free_space
+                    .entries()
+                    .get(
+                        usize::try_from(entry_index)
+                            .map_err(|_| StorageRuntimeError::InvalidFreeSpaceCommand)?,
+                    )
+                    .ok_or(StorageRuntimeError::InvalidFreeSpaceCommand)?;
+                if self.free_space.is_retired(region_index) {
+                    continue;
+                }
+                match flash.erase_region(region_index) {
+                    Ok(()) => {}
+                    Err(StorageIoError::BadRegion(failed)) if failed == region_index => {
+                        bad_region = Some(region_index);
+                        erased_end = entry_index;
+                        break;
+                    }
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            // Publish the erased prefix first so the retire below has ready
+            // regions to rotate into; the bad entry itself stays dirty until
+            // it is retired, so nothing can allocate it.
+            if erased_end > start {
+                flash.sync()?;
+                let erased = erased_end - start;
+                let ready_boundary_after = self.free_space.position_after_erase(erased)?;
+                self.append_record::<REGION_SIZE, REGION_COUNT, IO>(
+                    flash,
+                    workspace,
+                    WalRecord::EraseFreeRegionSpan {
+                        count: erased,
+                        ready_boundary_after,
+                    },
+                )?;
+            }
+            let Some(region_index) = bad_region else {
+                return Ok(());
+            };
+            self.retire_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                region_index,
+            )?;
         }
-        flash.sync()?;
-        let ready_boundary_after = self.free_space.position_after_erase(count)?;
-        self.append_record::<REGION_SIZE, REGION_COUNT, IO>(
+        Err(StorageRuntimeError::InvalidFreeSpaceCommand)
+    }
+
+    /// Appends `retire_region` for a region the backend reported bad.
+    fn retire_region_with_rotation<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        region_index: u32,
+    ) -> Result<(), StorageRuntimeError> {
+        if self.free_space.is_retired(region_index) {
+            return Ok(());
+        }
+        let record = WalRecord::RetireRegion { region_index };
+        if self.free_space.ready_count() == 0 {
+            // Rotating now would erase the dirty span holding this region
+            // again, so the record must fit the current tail.
+            return self.append_record::<REGION_SIZE, REGION_COUNT, IO>(flash, workspace, record);
+        }
+        self.append_record_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(flash, workspace, record)
+    }
+
+    /// Returns the position the next scrub call starts from.
+    pub fn scrub_cursor(&self) -> ScrubCursor {
+        self.scrub_cursor
+    }
+
+    /// Moves the scrub to `cursor` without recording it on flash.
+    pub(crate) fn set_scrub_cursor(&mut self, cursor: ScrubCursor) {
+        self.scrub_cursor = cursor;
+    }
+
+    /// Appends `scrub_progress` when `cursor` moved since the last record.
+    pub(crate) fn record_scrub_progress<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        cursor: ScrubCursor,
+    ) -> Result<(), StorageRuntimeError> {
+        if cursor == self.scrub_cursor {
+            return Ok(());
+        }
+        self.append_record_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
             flash,
             workspace,
-            WalRecord::EraseFreeRegionSpan {
-                count,
-                ready_boundary_after,
+            WalRecord::ScrubProgress {
+                next_region: cursor.next_region,
+                completed_passes: cursor.completed_passes,
             },
-        )
+        )?;
+        self.scrub_cursor = cursor;
+        Ok(())
     }
 
     /// Appends a `wal_recovery` record for an open recovery boundary.
@@ -3669,23 +4290,22 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         Ok((record_count, body_len_u32, total_len))
     }
 
-    fn ensure_inline_transaction_append_room<const REGION_SIZE: usize>(
+    fn ensure_inline_transaction_append_room<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
         &self,
         workspace: &mut StorageWorkspace<REGION_SIZE>,
+        flash: &mut IO,
         records: &[WalRecord<'_>],
     ) -> Result<(u32, u32), StorageRuntimeError> {
         let (record_count, body_len, total_len) =
             self.inline_transaction_total_len(workspace, records)?;
-        let append_limit = wal_record_append_limit(self.metadata)
-            .map_err(|error| StorageRuntimeError::Startup(error.into()))?;
-        if self
-            .wal_append_offset
-            .checked_add(total_len)
-            .is_some_and(|end| end <= append_limit)
-        {
-            return Ok((record_count, body_len));
-        }
-        Err(StorageRuntimeError::WalRotationRequired)
+        self.ensure_encoded_append_reserve::<REGION_SIZE, REGION_COUNT, IO>(
+            workspace, flash, total_len, false, false, false,
+        )?;
+        Ok((record_count, body_len))
     }
 
     fn write_inline_record_raw_advance<
@@ -3748,7 +4368,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             }
             WalRecord::FreeRegion { .. }
             | WalRecord::AllocateRegion { .. }
-            | WalRecord::EraseFreeRegionSpan { .. } => {
+            | WalRecord::EraseFreeRegionSpan { .. }
+            | WalRecord::RetireRegion { .. } => {
                 self.materialize_free_space_collection::<REGION_SIZE, IO>(flash)?;
             }
             _ => {}
@@ -3771,8 +4392,10 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 open.collection_id,
             ));
         }
-        let (record_count, body_len) =
-            self.ensure_inline_transaction_append_room(workspace, records)?;
+        let (record_count, body_len) = self
+            .ensure_inline_transaction_append_room::<REGION_SIZE, REGION_COUNT, IO>(
+                workspace, flash, records,
+            )?;
         self.write_inline_record_raw_advance::<REGION_SIZE, REGION_COUNT, IO>(
             flash,
             workspace,
@@ -3799,7 +4422,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         Ok(())
     }
 
-    fn append_inline_transaction_with_rotation<
+    pub(crate) fn append_inline_transaction_with_rotation<
         const REGION_SIZE: usize,
         const REGION_COUNT: usize,
         IO: FlashIo,
@@ -3983,6 +4606,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 encoded_len,
                 false,
                 false,
+                false,
             ) {
                 Ok(()) => return Ok(()),
                 Err(StorageRuntimeError::WalRotationRequired) => {
@@ -4149,7 +4773,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             }
             WalRecord::FreeRegion { .. }
             | WalRecord::AllocateRegion { .. }
-            | WalRecord::EraseFreeRegionSpan { .. } => {
+            | WalRecord::EraseFreeRegionSpan { .. }
+            | WalRecord::RetireRegion { .. } => {
                 self.materialize_free_space_collection::<REGION_SIZE, IO>(flash)?;
             }
             _ => {}
@@ -4233,6 +4858,106 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         Ok(())
     }
 
+    /// Allocates the wear-table chain that persists erase counts.
+    ///
+    /// Until the chain exists, replay rebuilds the counts from the retained
+    /// `erase_free_region_span` records, so WAL head reclaim allocates it
+    /// before it discards any of them. Allocation is skipped while the free
+    /// range is at or below the configured reserve.
+    fn ensure_free_space_wear_table<
+        const REGION_SIZE: usize,
+        const REGION_COUNT: usize,
+        IO: FlashIo,
+    >(
+        &mut self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+    ) -> Result<(), StorageRuntimeError> {
+        let required_wear_regions = self.required_free_space_wear_regions::<REGION_SIZE>();
+        while self.free_space.wear_regions().len() < required_wear_regions {
+            let free_regions = self
+                .free_space
+                .ready_count()
+                .saturating_add(self.free_space.dirty_count());
+            if free_regions <= self.metadata.min_free_regions {
+                return Ok(());
+            }
+            let region_index = self
+                .allocate_privileged_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+                    flash, workspace,
+                )?;
+            self.free_space.push_wear_region(region_index)?;
+            self.materialize_free_space_collection::<REGION_SIZE, IO>(flash)?;
+        }
+        Ok(())
+    }
+
+    /// Returns how many wear-table regions hold one erase count per region,
+    /// or zero when the table does not fit the supported chain length.
+    fn required_free_space_wear_regions<const REGION_SIZE: usize>(&self) -> usize {
+        let counts_per_region = free_space_wear_counts_per_region(REGION_SIZE);
+        let Ok(region_count) = usize::try_from(self.metadata.region_count) else {
+            return 0;
+        };
+        if counts_per_region == 0 {
+            return 0;
+        }
+        let required = region_count.div_ceil(counts_per_region);
+        if required > crate::free_space::MAX_WEAR_TABLE_REGIONS {
+            return 0;
+        }
+        required
+    }
+
+    /// Returns whether every wear-table region is allocated, so the table
+    /// carries the erase counts and the retired-region flags durably.
+    fn free_space_wear_table_complete<const REGION_SIZE: usize>(&self) -> bool {
+        let wear_regions = self.free_space.wear_regions();
+        !wear_regions.is_empty()
+            && wear_regions.len() >= self.required_free_space_wear_regions::<REGION_SIZE>()
+    }
+
+    /// Rewrites the wear-table chain once every region of it is allocated,
+    /// skipping segments whose encoding is already on flash.
+    fn materialize_free_space_wear_table<const REGION_SIZE: usize, IO: FlashIo>(
+        &self,
+        flash: &mut IO,
+    ) -> Result<Option<u32>, StorageRuntimeError> {
+        if !self.free_space_wear_table_complete::<REGION_SIZE>() {
+            return Ok(None);
+        }
+        let wear_regions = self.free_space.wear_regions();
+        let counts_per_region = free_space_wear_counts_per_region(REGION_SIZE);
+        let counts = self.free_space.wear_entries();
+        let mut region = [self.metadata.erased_byte; REGION_SIZE];
+        for (index, region_index) in wear_regions.iter().copied().enumerate() {
+            let start = index
+                .checked_mul(counts_per_region)
+                .ok_or(StorageRuntimeError::InvalidFreeSpaceCommand)?
+                .min(counts.len());
+            let end = start.saturating_add(counts_per_region).min(counts.len());
+            let len = encode_free_space_wear_segment(
+                &mut region,
+                self.metadata,
+                u64::try_from(index).map_err(|_| StorageRuntimeError::InvalidFreeSpaceCommand)?,
+                region_index,
+                self.free_space.erased_through_position(),
+                wear_regions.get(index + 1).copied(),
+                &counts[start..end],
+            )
+            .map_err(|error| StorageRuntimeError::Startup(error.into()))?;
+            let mut unchanged = false;
+            flash.read_region(region_index, 0, len, |bytes| {
+                unchanged = bytes == &region[..len];
+            })?;
+            if !unchanged {
+                flash.erase_region(region_index)?;
+                flash.write_region(region_index, 0, &region[..len])?;
+            }
+        }
+        Ok(wear_regions.first().copied())
+    }
+
     fn materialize_free_space_collection<const REGION_SIZE: usize, IO: FlashIo>(
         &self,
         flash: &mut IO,
@@ -4257,6 +4982,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             });
         }
 
+        let wear_root = self.materialize_free_space_wear_table::<REGION_SIZE, IO>(flash)?;
         let mut region = [self.metadata.erased_byte; REGION_SIZE];
         for (index, region_index) in chain_regions.iter().copied().enumerate() {
             let start = index
@@ -4269,7 +4995,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             let next_metadata_region = if index + 1 < required_regions {
                 chain_regions.get(index + 1).copied()
             } else {
-                None
+                wear_root
             };
             let len = encode_free_space_region_segment(
                 &mut region,
@@ -4335,7 +5061,10 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 self.metadata.region_count,
             )
             .map_err(|error| StorageRuntimeError::Startup(error.into()))?;
-            let Some(next) = prologue.next_metadata_region else {
+            let Some(next) = prologue
+                .next_metadata_region
+                .filter(|next| !self.free_space.wear_regions().contains(next))
+            else {
                 return Ok(regions);
             };
             current = next;
@@ -4451,6 +5180,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 encoded_len,
                 false,
                 false,
+                false,
             ) {
                 Ok(()) => {
                     {
@@ -4530,13 +5260,27 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         collection_type: u16,
     ) -> Result<(), StorageRuntimeError> {
         let payload = match collection_type {
-            crate::CollectionType::MAP_CODE => crate::EMPTY_MAP_SNAPSHOT.as_slice(),
+            crate::CollectionType::CHANNEL_CODE => crate::collections::channel::empty_snapshot(),
+            crate::CollectionType::MAP_CODE | crate::CollectionType::SET_CODE => {
+                crate::EMPTY_MAP_SNAPSHOT.as_slice()
+            }
             crate::CollectionType::OBJECT_LOG_CODE => {
                 crate::collections::object_log::empty_snapshot()
             }
-            other => {
-                return Err(StorageRuntimeError::WalHeadReclaimUnsupportedCollectionType(other))
+            crate::CollectionType::QUEUE_CODE => crate::collections::queue::empty_snapshot(),
+            crate::CollectionType::RING_LOG_CODE => crate::collections::ring_log::empty_snapshot(),
+            crate::CollectionType::TIME_SERIES_CODE => {
+                crate::collections::time_series::empty_snapshot()
             }
+            crate::CollectionType::CELL_CODE => crate::collections::cell::empty_snapshot(),
+            crate::CollectionType::SEQUENCE_CODE => crate::collections::sequence::empty_snapshot(),
+            crate::CollectionType::CATALOG_CODE => crate::catalog::empty_snapshot(),
+            other => match self.collection_types.get(other) {
+                Some(plugin) => plugin.empty_snapshot(),
+                None => {
+                    return Err(StorageRuntimeError::WalHeadReclaimUnsupportedCollectionType(other))
+                }
+            },
         };
 
         self.append_record_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
@@ -4633,6 +5377,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             encoded_len,
             matches!(record, WalRecord::AllocateRegion { .. }),
             matches!(record, WalRecord::EraseFreeRegionSpan { .. }),
+            matches!(record, WalRecord::RetireRegion { .. }),
         )
     }
 
@@ -4647,6 +5392,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         encoded_len: usize,
         allocate_region: bool,
         erase_free_region_span: bool,
+        retire_region: bool,
     ) -> Result<(), StorageRuntimeError> {
         let end = self
             .wal_append_offset
@@ -4679,6 +5425,24 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                             .map_err(|_| StorageRuntimeError::InvalidFreeSpaceCommand)?,
                     )
                     .ok_or(StorageRuntimeError::InvalidFreeSpaceCommand)?;
+                // Erasing may find the first dirty region bad, and its
+                // `retire_region` must land before the erase span does.
+                let first_dirty_region = *self
+                    .free_space
+                    .entries()
+                    .get(
+                        usize::try_from(self.free_space.ready_boundary())
+                            .map_err(|_| StorageRuntimeError::InvalidFreeSpaceCommand)?,
+                    )
+                    .ok_or(StorageRuntimeError::InvalidFreeSpaceCommand)?;
+                let retire_len = encode_record_into(
+                    WalRecord::RetireRegion {
+                        region_index: first_dirty_region,
+                    },
+                    self.metadata,
+                    physical,
+                    logical,
+                )?;
                 let allocation_head_after = self.free_space.position_after_allocation()?;
                 let reserves = self.rotation_reserves::<REGION_SIZE, REGION_COUNT>(
                     workspace,
@@ -4687,10 +5451,15 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 )?;
                 let required = if erase_free_region_span {
                     reserves.rotation_reserve
-                } else {
+                } else if retire_region {
                     erase_len
                         .checked_add(reserves.rotation_reserve)
                         .ok_or(StorageRuntimeError::WalRotationRequired)?
+                } else {
+                    erase_len
+                        .checked_add(retire_len)
+                        .and_then(|len| len.checked_add(reserves.rotation_reserve))
+                        .ok_or(StorageRuntimeError::WalRotationRequired)?
                 };
                 if remaining_after < required {
                     return Err(StorageRuntimeError::WalRotationRequired);
@@ -4877,9 +5646,20 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
     fn classify_wal_head_record_for_reclaim(
         &self,
         original_collections: &[StartupCollection],
+        superseded_collections: &[CollectionId],
+        retired_regions_materialized: bool,
         active_collections: &mut Vec<CollectionId, MAX_COLLECTIONS>,
         record: WalRecord<'_>,
     ) -> Result<WalHeadReclaimAction, StorageRuntimeError> {
+        if let WalRecord::NewCollection { collection_id, .. }
+        | WalRecord::Update { collection_id, .. }
+        | WalRecord::Snapshot { collection_id, .. }
+        | WalRecord::Head { collection_id, .. } = record
+        {
+            if superseded_collections.contains(&collection_id) {
+                return Ok(WalHeadReclaimAction::Skip);
+            }
+        }
         match record {
             WalRecord::NewCollection {
                 collection_id,
@@ -4890,13 +5670,27 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 if should_rewrite {
                     activate_collection(active_collections, collection_id)?;
                     return match collection_type {
-                        crate::CollectionType::MAP_CODE
-                        | crate::CollectionType::OBJECT_LOG_CODE => {
+                        crate::CollectionType::CHANNEL_CODE
+                        | crate::CollectionType::MAP_CODE
+                        | crate::CollectionType::OBJECT_LOG_CODE
+                        | crate::CollectionType::QUEUE_CODE
+                        | crate::CollectionType::RING_LOG_CODE
+                        | crate::CollectionType::TIME_SERIES_CODE
+                        | crate::CollectionType::CELL_CODE
+                        | crate::CollectionType::SEQUENCE_CODE
+                        | crate::CollectionType::CATALOG_CODE
+                        | crate::CollectionType::SET_CODE => {
                             Ok(WalHeadReclaimAction::RewriteEmptyBasisAsSnapshot {
                                 collection_id,
                                 collection_type,
                             })
                         }
+                        other if self.collection_types.contains(other) => {
+                            Ok(WalHeadReclaimAction::RewriteEmptyBasisAsSnapshot {
+                                collection_id,
+                                collection_type: other,
+                            })
+                        }
                         other => {
                             Err(StorageRuntimeError::WalHeadReclaimUnsupportedCollectionType(other))
                         }
@@ -4923,11 +5717,31 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                     WalHeadReclaimAction::CopyEncoded
                 } else {
                     WalHeadReclaimAction::Skip
-                })
-            }
-            WalRecord::AllocateRegion { .. }
-            | WalRecord::EraseFreeRegionSpan { .. }
-            | WalRecord::FreeRegion { .. } => Ok(WalHeadReclaimAction::CopyEncoded),
+                })
+            }
+            WalRecord::AllocateRegion { .. }
+            | WalRecord::EraseFreeRegionSpan { .. }
+            | WalRecord::FreeRegion { .. } => Ok(WalHeadReclaimAction::CopyEncoded),
+            WalRecord::RetireRegion { .. } => Ok(if retired_regions_materialized {
+                WalHeadReclaimAction::Skip
+            } else {
+                WalHeadReclaimAction::CopyEncoded
+            }),
+            WalRecord::ScrubProgress {
+                next_region,
+                completed_passes,
+            } => Ok(
+                if self.scrub_cursor
+                    == (ScrubCursor {
+                        next_region,
+                        completed_passes,
+                    })
+                {
+                    WalHeadReclaimAction::CopyEncoded
+                } else {
+                    WalHeadReclaimAction::Skip
+                },
+            ),
             WalRecord::Head {
                 collection_id: CollectionId(0),
                 region_index,
@@ -5120,8 +5934,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
 
     pub(crate) fn prepare_wal_head_reclaim<const REGION_SIZE: usize, IO: FlashIo>(
         &self,
-        _flash: &mut IO,
-        _workspace: &mut StorageWorkspace<REGION_SIZE>,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
         plan: &mut WalHeadReclaimPlan<MAX_COLLECTIONS>,
     ) -> Result<(), StorageRuntimeError> {
         if self.wal_head == self.wal_tail {
@@ -5139,6 +5953,7 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         plan.old_head = self.wal_head;
         plan.source_tail = self.wal_tail;
         plan.source_tail_append_offset = self.wal_append_offset;
+        plan.retired_regions_materialized = self.free_space_wear_table_complete::<REGION_SIZE>();
         plan.original_collections.clear();
         plan.imported_transaction_logs.clear();
         for collection in self.collections.iter().copied() {
@@ -5146,9 +5961,136 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 .push(collection)
                 .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)?;
         }
+
+        self.collect_superseded_wal_head_collections::<REGION_SIZE, IO>(flash, workspace, plan)?;
         Ok(())
     }
 
+    /// Records collections whose basis is replaced after the WAL head region.
+    ///
+    /// Records copied out of the head land after every later region, so a
+    /// head-region basis copied for one of these collections would replay over
+    /// the newer one. Transaction logs referenced from the head region are
+    /// imported by the reclaim itself, so their records do not count as newer.
+    fn collect_superseded_wal_head_collections<const REGION_SIZE: usize, IO: FlashIo>(
+        &self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        plan: &mut WalHeadReclaimPlan<MAX_COLLECTIONS>,
+    ) -> Result<(), StorageRuntimeError> {
+        plan.superseded_collections.clear();
+        let metadata = self.metadata;
+        let region_size = usize::try_from(metadata.region_size)
+            .map_err(|_| StorageRuntimeError::WalRotationRequired)?;
+        let granule = usize::try_from(metadata.wal_write_granule)
+            .map_err(|_| StorageRuntimeError::WalRotationRequired)?;
+        plan.imported_transaction_logs.clear();
+        let mut current_region = plan.old_head;
+
+        for _ in 0..metadata.region_count {
+            let is_head = current_region == plan.old_head;
+            let is_tail = current_region == plan.source_tail;
+            let limit = if is_tail {
+                plan.source_tail_append_offset
+            } else {
+                region_size
+            };
+            {
+                let (region_bytes, _) = workspace.scan_buffers();
+                flash.read_region(current_region, 0, region_bytes.len(), |bytes| {
+                    region_bytes.copy_from_slice(bytes);
+                })?;
+            }
+
+            let mut offset = metadata
+                .wal_record_area_offset()
+                .map_err(|error| StorageRuntimeError::Startup(error.into()))?;
+            let mut next_region = None;
+            while offset < limit && next_region.is_none() {
+                let (encoded_len, committed_log) = {
+                    let (region_bytes, logical_scratch) = workspace.scan_buffers();
+                    if region_bytes[offset] == metadata.erased_byte {
+                        break;
+                    }
+                    let decoded = if region_bytes[offset] == metadata.wal_record_magic {
+                        decode_record(&region_bytes[offset..limit], metadata, logical_scratch).ok()
+                    } else {
+                        None
+                    };
+                    let Some(decoded) = decoded else {
+                        offset = offset
+                            .checked_add(granule)
+                            .ok_or(StorageRuntimeError::WalRotationRequired)?;
+                        continue;
+                    };
+                    if let WalRecord::Link {
+                        next_region_index, ..
+                    } = decoded.record
+                    {
+                        next_region = Some(next_region_index);
+                    }
+                    let retained = self.retained_transaction_log_for_reclaim(decoded.record);
+                    if is_head {
+                        if let Some(retained) = retained {
+                            Self::remember_imported_transaction_log(plan, &retained)?;
+                        }
+                        (decoded.encoded_len, None)
+                    } else {
+                        note_superseded_collection(
+                            &mut plan.superseded_collections,
+                            decoded.record,
+                        )?;
+                        let committed_log = match (decoded.record, retained) {
+                            (WalRecord::CommitTransaction { seal, .. }, Some(retained))
+                                if !Self::transaction_log_already_imported(plan, &retained) =>
+                            {
+                                Some((retained.range, seal))
+                            }
+                            _ => None,
+                        };
+                        (decoded.encoded_len, committed_log)
+                    }
+                };
+                if let Some((range, seal)) = committed_log {
+                    let superseded = &mut plan.superseded_collections;
+                    visit_transaction_log_range::<REGION_SIZE, IO, StorageRuntimeError, _>(
+                        flash,
+                        workspace,
+                        metadata,
+                        range,
+                        seal,
+                        &mut |_flash, record| note_superseded_collection(superseded, record),
+                    )
+                    .map_err(|error| match error {
+                        StorageVisitError::Storage(error) | StorageVisitError::Visitor(error) => {
+                            error
+                        }
+                    })?;
+                    let (region_bytes, _) = workspace.scan_buffers();
+                    flash.read_region(current_region, 0, region_bytes.len(), |bytes| {
+                        region_bytes.copy_from_slice(bytes);
+                    })?;
+                }
+                offset = offset
+                    .checked_add(encoded_len)
+                    .ok_or(StorageRuntimeError::WalRotationRequired)?;
+            }
+
+            if is_tail {
+                plan.imported_transaction_logs.clear();
+                return Ok(());
+            }
+            current_region =
+                next_region.ok_or(StorageRuntimeError::Startup(StartupError::BrokenWalChain {
+                    region_index: current_region,
+                }))?;
+        }
+
+        Err(StorageRuntimeError::Startup(StartupError::BrokenWalChain {
+            region_index: current_region,
+        }))
+    }
+
     pub(crate) fn collect_wal_head_reclaim_regions<
         const REGION_SIZE: usize,
         const REGION_COUNT: usize,
@@ -5332,6 +6274,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                     let retained_to_import = self.retained_transaction_log_for_reclaim(record);
                     let reclaim_action = self.classify_wal_head_record_for_reclaim(
                         &plan.original_collections,
+                        &plan.superseded_collections,
+                        plan.retired_regions_materialized,
                         active_collections,
                         record,
                     )?;
@@ -5658,6 +6602,8 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
                 let decoded = decode_private_suffix_entry(&region_bytes[offset..end])?;
                 let action = self.classify_wal_head_record_for_reclaim(
                     &plan.original_collections,
+                    &plan.superseded_collections,
+                    plan.retired_regions_materialized,
                     active_collections,
                     decoded.record,
                 )?;
@@ -5725,6 +6671,201 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         )
     }
 
+    /// Summarizes the free-space collection's per-region erase counts.
+    pub(crate) fn wear_report(&self) -> crate::WearReport {
+        crate::WearReport::from_wear_entries(self.free_space.wear_entries())
+    }
+
+    /// Returns the recorded erase count for one region.
+    pub(crate) fn region_erase_count(&self, region_index: u32) -> u32 {
+        self.free_space.erase_count(region_index)
+    }
+
+    /// Returns the payload bytes a committed region can hold after its header.
+    pub(crate) fn committed_payload_capacity<const REGION_SIZE: usize>(
+        &self,
+    ) -> Result<usize, StorageRuntimeError> {
+        committed_payload_capacity::<REGION_SIZE>(self.metadata)
+    }
+
+    /// Builds a space accounting snapshot of the store.
+    ///
+    /// `wal_regions` is scratch for the retained WAL chain.
+    pub(crate) fn usage_report<const REGION_SIZE: usize, const REGION_COUNT: usize, IO: FlashIo>(
+        &self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        wal_regions: &mut Vec<u32, REGION_COUNT>,
+    ) -> Result<crate::StorageUsageReport<MAX_COLLECTIONS>, StorageRuntimeError> {
+        let metadata = self.metadata;
+        let mut report = crate::StorageUsageReport {
+            region_count: metadata.region_count,
+            ready_regions: self.free_space.ready_count(),
+            dirty_regions: self.free_space.dirty_count(),
+            free_regions: self
+                .free_space
+                .ready_count()
+                .saturating_add(self.free_space.dirty_count()),
+            ..Default::default()
+        };
+        for collection in self.collections.iter() {
+            if collection.basis() == StartupCollectionBasis::Dropped {
+                continue;
+            }
+            report
+                .collections
+                .push(crate::CollectionUsage {
+                    collection_id: collection.collection_id(),
+                    collection_type: collection.collection_type(),
+                    manifest_regions: 0,
+                    run_regions: 0,
+                    object_log_data_regions: 0,
+                    object_log_aux_regions: 0,
+                    other_regions: 0,
+                    wal_records: 0,
+                    wal_bytes: 0,
+                })
+                .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)?;
+        }
+
+        self.account_wal_usage::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            wal_regions,
+            &mut report,
+        )?;
+
+        for region_index in 0..metadata.region_count {
+            if wal_regions.contains(&region_index)
+                || self.free_space.contains_free_region(region_index)
+            {
+                continue;
+            }
+            if self.free_space.metadata_regions().contains(&region_index)
+                || self.free_space.wear_regions().contains(&region_index)
+            {
+                report.free_space_metadata_regions += 1;
+                continue;
+            }
+            let Ok(header) =
+                read_header_from_flash::<REGION_SIZE, REGION_COUNT, IO>(flash, region_index)
+            else {
+                report.unattributed_regions += 1;
+                continue;
+            };
+            if header.collection_id == CollectionId(0) {
+                if header.collection_format == TRANSACTION_LOG_V2_FORMAT {
+                    report.transaction_log_regions += 1;
+                } else {
+                    report.unattributed_regions += 1;
+                }
+                continue;
+            }
+            let Some(usage) = report.collection_mut(header.collection_id) else {
+                report.unattributed_regions += 1;
+                continue;
+            };
+            match header.collection_format {
+                crate::MAP_MANIFEST_V2_FORMAT => usage.manifest_regions += 1,
+                crate::MAP_RUN_V2_FORMAT => usage.run_regions += 1,
+                crate::OBJECT_LOG_DATA_V1_FORMAT => usage.object_log_data_regions += 1,
+                crate::OBJECT_LOG_AUX_V1_FORMAT => usage.object_log_aux_regions += 1,
+                _ => usage.other_regions += 1,
+            }
+        }
+        Ok(report)
+    }
+
+    /// Walks the retained WAL chain without expanding transaction logs and
+    /// charges each record's encoded length to the collection it addresses.
+    fn account_wal_usage<const REGION_SIZE: usize, const REGION_COUNT: usize, IO: FlashIo>(
+        &self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        wal_regions: &mut Vec<u32, REGION_COUNT>,
+        report: &mut crate::StorageUsageReport<MAX_COLLECTIONS>,
+    ) -> Result<(), StorageRuntimeError> {
+        let metadata = self.metadata;
+        let region_size = usize::try_from(metadata.region_size)
+            .map_err(|_| StorageRuntimeError::WalRotationRequired)?;
+        let granule = usize::try_from(metadata.wal_write_granule)
+            .map_err(|_| StorageRuntimeError::WalRotationRequired)?;
+        wal_regions.clear();
+        let mut current_region = self.wal_head;
+
+        for _ in 0..metadata.region_count {
+            wal_regions
+                .push(current_region)
+                .map_err(|_| StorageRuntimeError::WalRotationRequired)?;
+            let is_tail = current_region == self.wal_tail;
+            let limit = if is_tail {
+                self.wal_append_offset
+            } else {
+                region_size
+            };
+            let (region_bytes, logical_scratch) = workspace.scan_buffers();
+            flash.read_region(current_region, 0, region_bytes.len(), |bytes| {
+                region_bytes.copy_from_slice(bytes);
+            })?;
+
+            let mut offset = metadata
+                .wal_record_area_offset()
+                .map_err(|error| StorageRuntimeError::Startup(error.into()))?;
+            let mut next_region = None;
+            while offset < limit && next_region.is_none() {
+                if region_bytes[offset] == metadata.erased_byte {
+                    break;
+                }
+                let decoded = if region_bytes[offset] == metadata.wal_record_magic {
+                    decode_record(&region_bytes[offset..limit], metadata, logical_scratch).ok()
+                } else {
+                    None
+                };
+                let Some(decoded) = decoded else {
+                    offset = offset
+                        .checked_add(granule)
+                        .ok_or(StorageRuntimeError::WalRotationRequired)?;
+                    continue;
+                };
+                let encoded_len = decoded.encoded_len as u64;
+                report.wal_bytes += encoded_len;
+                let collection_id = match decoded.record {
+                    WalRecord::Link {
+                        next_region_index, ..
+                    } => {
+                        next_region = Some(next_region_index);
+                        None
+                    }
+                    record => crate::collection_type::replayed_collection_id(record),
+                };
+                match collection_id.and_then(|id| report.collection_mut(id)) {
+                    Some(usage) => {
+                        usage.wal_records += 1;
+                        usage.wal_bytes += encoded_len;
+                    }
+                    None => report.storage_wal_bytes += encoded_len,
+                }
+                offset = offset
+                    .checked_add(decoded.encoded_len)
+                    .ok_or(StorageRuntimeError::WalRotationRequired)?;
+            }
+
+            if is_tail {
+                report.wal_regions = u32::try_from(wal_regions.len())
+                    .map_err(|_| StorageRuntimeError::WalRotationRequired)?;
+                return Ok(());
+            }
+            current_region =
+                next_region.ok_or(StorageRuntimeError::Startup(StartupError::BrokenWalChain {
+                    region_index: current_region,
+                }))?;
+        }
+
+        Err(StorageRuntimeError::Startup(StartupError::BrokenWalChain {
+            region_index: current_region,
+        }))
+    }
+
     fn free_region_count<const REGION_SIZE: usize, const REGION_COUNT: usize, IO: FlashIo>(
         &self,
         _flash: &mut IO,
@@ -5746,7 +6887,9 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
             if head_region == target_region_index {
                 return Ok(true);
             }
-            if collection.collection_type() == Some(CollectionType::MAP_CODE)
+            if collection
+                .collection_type()
+                .is_some_and(crate::collections::map::is_lsm_collection_type)
                 && crate::collections::map::map_head_region_references_region::<REGION_SIZE, IO>(
                     flash,
                     workspace,
@@ -5802,6 +6945,25 @@ fn find_collection_in(
         .find(|collection| collection.collection_id() == collection_id)
 }
 
+fn note_superseded_collection<const MAX_COLLECTIONS: usize>(
+    superseded_collections: &mut Vec<CollectionId, MAX_COLLECTIONS>,
+    record: WalRecord<'_>,
+) -> Result<(), StorageRuntimeError> {
+    let collection_id = match record {
+        WalRecord::NewCollection { collection_id, .. }
+        | WalRecord::Snapshot { collection_id, .. }
+        | WalRecord::Head { collection_id, .. }
+        | WalRecord::DropCollection { collection_id } => collection_id,
+        _ => return Ok(()),
+    };
+    if collection_id == CollectionId(0) || superseded_collections.contains(&collection_id) {
+        return Ok(());
+    }
+    superseded_collections
+        .push(collection_id)
+        .map_err(|_| StorageRuntimeError::TooManyTrackedCollections)
+}
+
 fn activate_collection<const MAX_COLLECTIONS: usize>(
     active_collections: &mut Vec<CollectionId, MAX_COLLECTIONS>,
     collection_id: CollectionId,
@@ -5859,6 +7021,37 @@ impl<const MAX_COLLECTIONS: usize> StorageRuntime<MAX_COLLECTIONS> {
         )
     }
 
+    /// Returns whether the WAL still holds a `new_collection` or `snapshot`
+    /// record for `collection_id` with `collection_type`.
+    pub(crate) fn wal_has_collection_basis<const REGION_SIZE: usize, IO: FlashIo>(
+        &self,
+        flash: &mut IO,
+        workspace: &mut StorageWorkspace<REGION_SIZE>,
+        collection_id: CollectionId,
+        collection_type: u16,
+    ) -> Result<bool, StorageRuntimeError> {
+        let result =
+            self.visit_wal_records::<REGION_SIZE, IO, (), _>(flash, workspace, |_flash, record| {
+                match record {
+                    WalRecord::NewCollection {
+                        collection_id: seen,
+                        collection_type: seen_type,
+                    }
+                    | WalRecord::Snapshot {
+                        collection_id: seen,
+                        collection_type: seen_type,
+                        ..
+                    } if seen == collection_id && seen_type == collection_type => Err(()),
+                    _ => Ok(()),
+                }
+            });
+        match result {
+            Ok(()) => Ok(false),
+            Err(StorageVisitError::Visitor(())) => Ok(true),
+            Err(StorageVisitError::Storage(error)) => Err(error),
+        }
+    }
+
     #[cfg(feature = "perf-counters")]
     pub(crate) fn visit_wal_records_metered<const REGION_SIZE: usize, IO: FlashIo, E, F>(
         &self,
@@ -6403,16 +7596,34 @@ pub(crate) fn reopen_without_reclaim_recovery_into<
     runtime: &mut StorageRuntime<MAX_COLLECTIONS>,
     open_plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
 ) -> Result<(), StorageRuntimeError> {
-    crate::startup::begin_open_formatted_store::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+    replay_open_plan::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
         flash, workspace, open_plan,
     )?;
-    crate::startup::recover_open_rotation::<REGION_SIZE, IO, REGION_COUNT, MAX_COLLECTIONS>(
-        flash, workspace, open_plan,
-    )?;
-    crate::startup::replay_open_wal_chain::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+    crate::startup::finish_open_formatted_store_into_runtime::<
+        REGION_SIZE,
+        REGION_COUNT,
+        IO,
+        MAX_COLLECTIONS,
+    >(flash, open_plan, runtime)
+}
+
+/// Reopens a formatted store like [`reopen_without_reclaim_recovery_into`],
+/// quarantining live collections that fail startup validation.
+pub(crate) fn salvage_reopen_into<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    runtime: &mut StorageRuntime<MAX_COLLECTIONS>,
+    open_plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+) -> Result<(), StorageRuntimeError> {
+    replay_open_plan::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
         flash, workspace, open_plan,
     )?;
-    crate::startup::finish_open_formatted_store_into_runtime::<
+    crate::startup::salvage_open_formatted_store_into_runtime::<
         REGION_SIZE,
         REGION_COUNT,
         IO,
@@ -6420,6 +7631,29 @@ pub(crate) fn reopen_without_reclaim_recovery_into<
     >(flash, open_plan, runtime)
 }
 
+fn replay_open_plan<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    open_plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+) -> Result<(), StorageRuntimeError> {
+    open_plan.begin_recovery_report();
+    crate::startup::begin_open_formatted_store::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+        flash, workspace, open_plan,
+    )?;
+    crate::startup::recover_open_rotation::<REGION_SIZE, IO, REGION_COUNT, MAX_COLLECTIONS>(
+        flash, workspace, open_plan,
+    )?;
+    crate::startup::replay_open_wal_chain::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+        flash, workspace, open_plan,
+    )?;
+    Ok(())
+}
+
 fn read_header_from_flash<const REGION_SIZE: usize, const REGION_COUNT: usize, IO: FlashIo>(
     flash: &mut IO,
     region_index: u32,
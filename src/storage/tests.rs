@@ -1498,17 +1498,13 @@ fn requirement_user_collection_ids_are_nonzero_u64_values_and_are_not_recycled()
 #[test]
 fn requirement_wal_collection_type_is_reserved_for_collection_id_zero() {
     assert_eq!(
-        StorageRuntime::<8>::validate_supported_head_collection_type(
-            CollectionId(0),
-            CollectionType::WAL_CODE,
-        ),
+        StorageRuntime::<8>::empty()
+            .validate_supported_head_collection_type(CollectionId(0), CollectionType::WAL_CODE,),
         Ok(())
     );
     assert_eq!(
-        StorageRuntime::<8>::validate_supported_head_collection_type(
-            CollectionId(0),
-            CollectionType::MAP_CODE,
-        ),
+        StorageRuntime::<8>::empty()
+            .validate_supported_head_collection_type(CollectionId(0), CollectionType::MAP_CODE,),
         Err(StorageRuntimeError::CollectionTypeMismatch {
             collection_id: CollectionId(0),
             expected: CollectionType::WAL_CODE,
@@ -2513,6 +2509,7 @@ fn requirement_ensure_encoded_append_reserve_rejects_allocate_region_when_no_fre
             &mut flash,
             1,
             true,
+            false,
             false
         ),
         Err(StorageRuntimeError::WalRotationRequired)
@@ -2546,6 +2543,7 @@ fn requirement_ensure_encoded_append_reserve_accepts_allocate_region_at_exact_ro
             encoded_len,
             true,
             false,
+            false,
         ),
         Ok(())
     );
@@ -2604,6 +2602,8 @@ fn requirement_classify_wal_head_reclaim_copies_only_the_retained_region_head()
         state
             .classify_wal_head_record_for_reclaim(
                 &original_collections,
+                &[],
+                false,
                 &mut active_collections,
                 WalRecord::Head {
                     collection_id: CollectionId(7),
@@ -2618,6 +2618,8 @@ fn requirement_classify_wal_head_reclaim_copies_only_the_retained_region_head()
         state
             .classify_wal_head_record_for_reclaim(
                 &original_collections,
+                &[],
+                false,
                 &mut active_collections,
                 WalRecord::Head {
                     collection_id: CollectionId(7),
@@ -2654,6 +2656,8 @@ fn requirement_classify_wal_head_reclaim_copies_only_retained_drop_tombstones()
         live_state
             .classify_wal_head_record_for_reclaim(
                 &live_collections,
+                &[],
+                false,
                 &mut active_collections,
                 WalRecord::DropCollection {
                     collection_id: CollectionId(7),
@@ -2671,6 +2675,8 @@ fn requirement_classify_wal_head_reclaim_copies_only_retained_drop_tombstones()
         live_state
             .classify_wal_head_record_for_reclaim(
                 &dropped_collections,
+                &[],
+                false,
                 &mut active_collections,
                 WalRecord::DropCollection {
                     collection_id: CollectionId(7),
@@ -2734,7 +2740,9 @@ fn requirement_copy_live_wal_head_reclaim_state_stops_when_a_record_ends_at_regi
         source_tail: state.wal_tail(),
         source_tail_append_offset: 128,
         original_collections: Vec::new(),
+        superseded_collections: Vec::new(),
         imported_transaction_logs: Vec::new(),
+        retired_regions_materialized: false,
     };
 
     let mut active_collections = Vec::<CollectionId, 8>::new();
@@ -280,6 +280,9 @@ fn run_wal_lifecycle_reuses_every_region_across_reclaim_cycles() {
         service_storage_lifecycle(&mut storage, 4, &mut stats);
         mark_current_wal_chain(&mut storage, &mut seen_regions).unwrap();
         storage.with_io_workspace(|flash, _workspace| flash.clear_operations());
+        for region_index in storage.free_space_wear_regions().to_vec() {
+            mark_region(&mut seen_regions, region_index);
+        }
 
         if seen_region_count(&seen_regions) == REGION_COUNT && stats.wal_reclaims >= 3 {
             break;
@@ -1009,6 +1009,42 @@ fn requirement_public_object_log_transaction_writer_rolls_back_appends() {
     assert_eq!(reopened_log.first_handle(), None);
 }
 
+//= spec/ring/09-implementation-coverage.md#storage-runtime-state-requirements
+//= type=test
+//# `RING-IMPL-REGRESSION-161` Rolling back an object-log transaction
+//# writer MUST return each region the transaction allocated to the
+//# free-space queue exactly once.
+#[test]
+fn requirement_object_log_transaction_rollback_frees_regions_once() {
+    const REGION_SIZE: usize = 512;
+    const REGION_COUNT: usize = 12;
+    let mut flash = MockFlash::<REGION_SIZE, REGION_COUNT, 4096>::new(0xff);
+    let mut storage_memory = StorageMemory::<REGION_SIZE, REGION_COUNT>::new();
+    let mut storage = Storage::format(
+        &mut flash,
+        StorageFormatConfig::new(2, 8, 0xa5),
+        &mut storage_memory,
+    )
+    .unwrap();
+    let mut log_memory = ObjectLogMemory::<REGION_SIZE>::new();
+    let mut log = ObjectLog::<REGION_SIZE>::new(&mut storage, &mut log_memory, b"meta").unwrap();
+    let mut transaction_memory = TransactionMemory::<REGION_COUNT>::new();
+    let mut large_scratch = [0u8; REGION_SIZE];
+    {
+        let mut transaction = log
+            .begin_transaction_writer(&mut storage, &mut transaction_memory)
+            .unwrap();
+        transaction
+            .append(&mut storage, b"rolled back", &mut large_scratch)
+            .unwrap();
+        transaction.rollback(&mut storage).unwrap();
+    }
+
+    let mut findings = heapless::Vec::<CheckFinding, 8>::new();
+    let summary = storage.check(&mut findings).unwrap();
+    assert!(summary.is_clean(), "{findings:?}");
+}
+
 //= spec/ring/09-implementation-coverage.md#storage-runtime-state-requirements
 //= type=test
 //# `RING-IMPL-REGRESSION-092` `CollectionId` helpers MUST expose little-endian bytes and
@@ -1443,6 +1479,82 @@ impl<const REGION_SIZE: usize, const REGION_COUNT: usize, const MAX_LOG: usize>
     }
 }
 
+/// Backing that models power loss after the first `syncs_left` durability
+/// barriers: writes before the cut persist and every later program or erase
+/// fails.
+pub(crate) struct PowerCutFlash<'a, IO> {
+    pub(crate) flash: &'a mut IO,
+    pub(crate) syncs_left: usize,
+}
+
+impl<IO> PowerCutFlash<'_, IO> {
+    fn ensure_powered(&self) -> Result<(), StorageIoError> {
+        if self.syncs_left == 0 {
+            return Err(StorageIoError::Mock(MockError::OutOfBounds));
+        }
+        Ok(())
+    }
+}
+
+impl<IO: FlashIo> FlashIo for PowerCutFlash<'_, IO> {
+    fn read_metadata(&mut self) -> Result<Option<StorageMetadata>, StorageIoError> {
+        FlashIo::read_metadata(self.flash)
+    }
+
+    fn write_metadata(&mut self, metadata: StorageMetadata) -> Result<(), StorageIoError> {
+        self.ensure_powered()?;
+        FlashIo::write_metadata(self.flash, metadata)
+    }
+
+    fn read_region<R, F>(
+        &mut self,
+        region_index: u32,
+        offset: usize,
+        len: usize,
+        read: F,
+    ) -> Result<R, StorageIoError>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        FlashIo::read_region(self.flash, region_index, offset, len, read)
+    }
+
+    fn write_region(
+        &mut self,
+        region_index: u32,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), StorageIoError> {
+        self.ensure_powered()?;
+        FlashIo::write_region(self.flash, region_index, offset, data)
+    }
+
+    fn erase_region(&mut self, region_index: u32) -> Result<(), StorageIoError> {
+        self.ensure_powered()?;
+        FlashIo::erase_region(self.flash, region_index)
+    }
+
+    fn sync(&mut self) -> Result<(), StorageIoError> {
+        self.ensure_powered()?;
+        self.syncs_left -= 1;
+        FlashIo::sync(self.flash)
+    }
+
+    fn format_empty_store(
+        &mut self,
+        min_free_regions: u32,
+        wal_write_granule: u32,
+        wal_record_magic: u8,
+    ) -> Result<StorageMetadata, StorageFormatError> {
+        FlashIo::format_empty_store(
+            self.flash,
+            min_free_regions,
+            wal_write_granule,
+            wal_record_magic,
+        )
+    }
+}
+
 fn rotate_wal_tail_for_collection<'db, IO: FlashIo, const REGION_COUNT: usize>(
     storage: &mut Storage<'db, 'db, IO, 512, REGION_COUNT>,
     collection_id: CollectionId,
@@ -2075,7 +2187,9 @@ fn requirement_storage_append_and_reopen_round_trip_through_flash() {
 //# `RING-FORMAT-012` Every non-WAL `collection_type` that may appear durably on disk MUST have a
 //# corresponding normative collection specification.
 #[test]
-fn requirement_storage_append_new_collection_rejects_unsupported_channel_collection() {
+fn requirement_storage_append_new_collection_rejects_unspecified_collection_type() {
+    const UNSPECIFIED_CODE: u16 = 0x00fe;
+
     let mut flash = MockFlash::<256, 4, 256>::new(0xff);
     let mut workspace = StorageWorkspace::<256>::new();
     assert!(matches!(
@@ -2085,9 +2199,9 @@ fn requirement_storage_append_new_collection_rejects_unsupported_channel_collect
             crate::test_storage_memory()
         )
         .unwrap()
-        .append_new_collection(CollectionId(22), CollectionType::CHANNEL_CODE,),
+        .append_new_collection(CollectionId(22), UNSPECIFIED_CODE),
         Err(StorageRuntimeError::UnsupportedCollectionType(
-            CollectionType::CHANNEL_CODE
+            UNSPECIFIED_CODE
         ))
     ));
 }
@@ -4282,3 +4396,277 @@ fn requirement_storage_drop_map_from_snapshot_basis_has_no_region_reclaim() {
         StartupCollectionBasis::Dropped
     );
 }
+
+//= spec/map.md#map-storage-integration-requirements
+//= type=test
+//# `RING-IMPL-REGRESSION-158` WAL-head reclaim MUST keep a map whose
+//# committed basis was written by a transaction that began in the reclaimed
+//# region, so the map MUST stay listed and reopen with its flushed values.
+#[test]
+fn requirement_wal_head_reclaim_keeps_map_flushed_in_reclaimed_region() {
+    let mut flash = MockFlash::<512, 32, 32768>::new(0xff);
+    let collection_id = {
+        let mut storage = Storage::<_, 512, 32>::format(
+            &mut flash,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut map =
+            LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+        let collection_id = map.collection_id();
+        for key in 0..3u16 {
+            for value in 0..2u16 {
+                map.set(&mut storage, key, key * 10 + value).unwrap();
+                let mut buffer = [0u8; 512];
+                let mut frontier = storage
+                    .open_map::<u16, u16, 8>(
+                        collection_id,
+                        &mut buffer,
+                        crate::test_map_frontier_memory(),
+                    )
+                    .unwrap();
+                storage.flush_map(&mut frontier).unwrap();
+            }
+        }
+
+        let mut other =
+            LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+        let mut reclaims = 0;
+        for value in 0..12u16 {
+            other.set(&mut storage, 1, value).unwrap();
+            other.set(&mut storage, 2, value).unwrap();
+            if storage.wal_head() != storage.wal_tail() {
+                storage.reclaim_wal_head().unwrap();
+                reclaims += 1;
+                assert!(storage
+                    .collections()
+                    .iter()
+                    .any(|collection| collection.collection_id() == collection_id));
+            }
+        }
+        assert!(reclaims > 1);
+        collection_id
+    };
+
+    let mut storage =
+        Storage::<_, 512, 32>::open(&mut flash, crate::test_storage_memory()).unwrap();
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(collection_id, &mut storage, crate::test_lsm_map_memory())
+            .unwrap();
+    for key in 0..3u16 {
+        assert_eq!(
+            map.get(&mut storage, &key, |_, value| *value).unwrap(),
+            Some(key * 10 + 1)
+        );
+    }
+}
+
+//= spec/ring/09-implementation-coverage.md#storage-runtime-state-requirements
+//= type=test
+//# `RING-IMPL-REGRESSION-160` Startup replay MUST accept a main-WAL
+//# `allocate_region` inside an open transaction that lands past regions
+//# the transaction allocated privately, so a map flush cut at any
+//# durability barrier MUST reopen with its previous contents.
+#[test]
+fn requirement_map_flush_reopens_after_power_loss_at_every_sync() {
+    fn setup(flash: &mut MockFlash<512, 16, 8192>, keys: u16) -> CollectionId {
+        let mut storage = Storage::<_, 512, 16>::format(
+            flash,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut map =
+            LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+        for key in 0..keys {
+            map.set(&mut storage, key, key).unwrap();
+        }
+        map.collection_id()
+    }
+
+    fn flush<IO: FlashIo>(flash: &mut IO, collection_id: CollectionId) -> bool {
+        let Ok(mut storage) = Storage::<_, 512, 16>::open(flash, crate::test_storage_memory())
+        else {
+            return false;
+        };
+        let mut buffer = [0u8; 512];
+        storage
+            .open_map::<u16, u16, 8>(
+                collection_id,
+                &mut buffer,
+                crate::test_map_frontier_memory(),
+            )
+            .and_then(|mut frontier| storage.flush_map(&mut frontier))
+            .is_ok()
+    }
+
+    for keys in 1..24u16 {
+        let mut flash = MockFlash::<512, 16, 8192>::new(0xff);
+        let collection_id = setup(&mut flash, keys);
+        flash.clear_operations();
+        assert!(flush(&mut flash, collection_id));
+        let total_syncs = flash
+            .operations()
+            .iter()
+            .filter(|operation| **operation == MockOperation::Sync)
+            .count();
+
+        for syncs_left in 0..total_syncs {
+            let mut flash = MockFlash::<512, 16, 8192>::new(0xff);
+            let collection_id = setup(&mut flash, keys);
+            let mut power_cut = PowerCutFlash {
+                flash: &mut flash,
+                syncs_left,
+            };
+            assert!(!flush(&mut power_cut, collection_id));
+
+            let mut storage =
+                Storage::<_, 512, 16>::open(&mut flash, crate::test_storage_memory()).unwrap();
+            let mut map = LsmMap::<u16, u16, 8>::open(
+                collection_id,
+                &mut storage,
+                crate::test_lsm_map_memory(),
+            )
+            .unwrap();
+            for key in 0..keys {
+                assert_eq!(
+                    map.get(&mut storage, &key, |_, value| *value).unwrap(),
+                    Some(key)
+                );
+            }
+        }
+    }
+}
+
+//= spec/ring/09-implementation-coverage.md#storage-runtime-state-requirements
+//= type=test
+//# `RING-IMPL-REGRESSION-162` Startup rollback of an open transaction
+//# MUST NOT free a region a WAL rotation inside it linked into the WAL,
+//# so a map flush cut at any durability barrier MUST reopen with a clean
+//# `Storage::check`.
+#[test]
+fn requirement_map_flush_cut_at_every_sync_reopens_clean() {
+    fn setup(flash: &mut MockFlash<512, 16, 8192>, keys: u16) -> CollectionId {
+        let mut storage = Storage::<_, 512, 16>::format(
+            flash,
+            StorageFormatConfig::new(2, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut map =
+            LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+        for key in 0..keys {
+            map.set(&mut storage, key, key).unwrap();
+        }
+        map.collection_id()
+    }
+
+    fn flush<IO: FlashIo>(flash: &mut IO, collection_id: CollectionId) -> bool {
+        let Ok(mut storage) = Storage::<_, 512, 16>::open(flash, crate::test_storage_memory())
+        else {
+            return false;
+        };
+        let mut buffer = [0u8; 512];
+        storage
+            .open_map::<u16, u16, 8>(
+                collection_id,
+                &mut buffer,
+                crate::test_map_frontier_memory(),
+            )
+            .and_then(|mut frontier| storage.flush_map(&mut frontier))
+            .is_ok()
+    }
+
+    for keys in 1..24u16 {
+        for syncs_left in 0.. {
+            let mut flash = MockFlash::<512, 16, 8192>::new(0xff);
+            let collection_id = setup(&mut flash, keys);
+            let mut power_cut = PowerCutFlash {
+                flash: &mut flash,
+                syncs_left,
+            };
+            let completed = flush(&mut power_cut, collection_id);
+
+            let mut storage =
+                Storage::<_, 512, 16>::open(&mut flash, crate::test_storage_memory()).unwrap();
+            let mut findings = heapless::Vec::<CheckFinding, 8>::new();
+            let summary = storage.check(&mut findings).unwrap();
+            assert!(
+                summary.is_clean(),
+                "keys={keys} syncs_left={syncs_left}: {findings:?}"
+            );
+            if completed {
+                break;
+            }
+        }
+    }
+}
+
+//= spec/map.md#map-storage-integration-requirements
+//= type=test
+//# `RING-IMPL-REGRESSION-163` WAL-head reclaim MUST NOT copy a map basis from
+//# the reclaimed region when a later WAL region already holds a newer basis
+//# for that map, so reopen MUST load the newer basis.
+#[test]
+fn requirement_wal_head_reclaim_keeps_newer_map_basis_from_later_region() {
+    let mut flash = MockFlash::<512, 32, 32768>::new(0xff);
+    let collection_id = {
+        let mut storage = Storage::<_, 512, 32>::format(
+            &mut flash,
+            StorageFormatConfig::new(4, 8, 0xa5),
+            crate::test_storage_memory(),
+        )
+        .unwrap();
+        let mut map =
+            LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+        let collection_id = map.collection_id();
+        let snapshot = |storage: &mut Storage<'_, 'static, _, 512, 32>| {
+            let mut buffer = [0u8; 512];
+            let frontier = storage
+                .open_map::<u16, u16, 8>(
+                    collection_id,
+                    &mut buffer,
+                    crate::test_map_frontier_memory(),
+                )
+                .unwrap();
+            storage.snapshot_map(&frontier).unwrap();
+        };
+
+        map.set(&mut storage, 1, 10).unwrap();
+        snapshot(&mut storage);
+        let head = storage.wal_head();
+
+        let mut other =
+            LsmMap::<u16, u16, 8>::new(&mut storage, crate::test_lsm_map_memory()).unwrap();
+        let mut value = 0;
+        while storage.wal_tail() == head {
+            other.set(&mut storage, 1, value).unwrap();
+            value += 1;
+        }
+        map.set(&mut storage, 1, 20).unwrap();
+        snapshot(&mut storage);
+
+        storage.reclaim_wal_head().unwrap();
+        assert_ne!(storage.wal_head(), head);
+        collection_id
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(
+        storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == collection_id)
+            .unwrap()
+            .basis(),
+        StartupCollectionBasis::WalSnapshot
+    );
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(collection_id, &mut storage, crate::test_lsm_map_memory())
+            .unwrap();
+    assert_eq!(
+        map.get(&mut storage, &1, |_, value| *value).unwrap(),
+        Some(20)
+    );
+}
@@ -0,0 +1,97 @@
+//! Space accounting snapshots for telemetry.
+//!
+//! [`Storage::usage_report`](crate::Storage::usage_report) walks the retained
+//! WAL and every region header once and returns a [`StorageUsageReport`]. A
+//! region belongs to the live collection named in its header unless the WAL
+//! chain or the free-space queue holds it, so the report also covers regions
+//! such as cell values that no collection basis lists.
+
+use heapless::Vec;
+use serde::Serialize;
+
+use crate::CollectionId;
+
+#[cfg(test)]
+mod tests;
+
+/// Flash usage attributed to one live collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CollectionUsage {
+    /// Stable collection id.
+    pub collection_id: CollectionId,
+    /// Stable collection type code, if replay recorded one.
+    pub collection_type: Option<u16>,
+    /// Map manifest regions.
+    pub manifest_regions: u32,
+    /// Map run regions.
+    pub run_regions: u32,
+    /// Object-log data regions.
+    pub object_log_data_regions: u32,
+    /// Object-log auxiliary regions.
+    pub object_log_aux_regions: u32,
+    /// Committed regions in any other collection format.
+    pub other_regions: u32,
+    /// Retained WAL records addressed to the collection.
+    pub wal_records: u32,
+    /// Encoded bytes of those WAL records.
+    pub wal_bytes: u64,
+}
+
+impl CollectionUsage {
+    /// Returns every region the collection owns outside the WAL.
+    pub fn owned_regions(&self) -> u32 {
+        self.manifest_regions
+            + self.run_regions
+            + self.object_log_data_regions
+            + self.object_log_aux_regions
+            + self.other_regions
+    }
+}
+
+/// Whole-store space accounting returned by
+/// [`Storage::usage_report`](crate::Storage::usage_report).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageUsageReport<const MAX_COLLECTIONS: usize = 8> {
+    /// Formatted region count.
+    pub region_count: u32,
+    /// Regions in the retained WAL chain.
+    pub wal_regions: u32,
+    /// Encoded bytes of every retained WAL record.
+    pub wal_bytes: u64,
+    /// Encoded bytes of retained WAL records that address no user
+    /// collection, such as links, free-space records, and transaction markers.
+    pub storage_wal_bytes: u64,
+    /// Regions queued in the free-space collection.
+    pub free_regions: u32,
+    /// Free regions already erased and ready for allocation.
+    pub ready_regions: u32,
+    /// Free regions still waiting for erase.
+    pub dirty_regions: u32,
+    /// Free-space metadata regions.
+    pub free_space_metadata_regions: u32,
+    /// Transaction-log regions.
+    pub transaction_log_regions: u32,
+    /// Regions holding no live state, such as erased regions outside the free
+    /// queue and regions whose header names a dropped collection.
+    pub unattributed_regions: u32,
+    /// Usage of each live collection in replay order.
+    pub collections: Vec<CollectionUsage, MAX_COLLECTIONS>,
+}
+
+impl<const MAX_COLLECTIONS: usize> StorageUsageReport<MAX_COLLECTIONS> {
+    /// Returns the usage entry for `collection_id`, if it is live.
+    pub fn collection(&self, collection_id: CollectionId) -> Option<&CollectionUsage> {
+        self.collections
+            .iter()
+            .find(|usage| usage.collection_id == collection_id)
+    }
+
+    pub(crate) fn collection_mut(
+        &mut self,
+        collection_id: CollectionId,
+    ) -> Option<&mut CollectionUsage> {
+        self.collections
+            .iter_mut()
+            .find(|usage| usage.collection_id == collection_id)
+    }
+}
@@ -0,0 +1,160 @@
+use super::*;
+
+use crate::collections::cell::Cell;
+use crate::collections::object_log::{ObjectLog, ObjectLogMemory};
+use crate::MockFlash;
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+
+fn accounted_regions(report: &StorageUsageReport) -> u32 {
+    report.wal_regions
+        + report.free_regions
+        + report.free_space_metadata_regions
+        + report.transaction_log_regions
+        + report.unattributed_regions
+        + report
+            .collections
+            .iter()
+            .map(CollectionUsage::owned_regions)
+            .sum::<u32>()
+}
+
+fn attributed_wal_bytes(report: &StorageUsageReport) -> u64 {
+    report.storage_wal_bytes
+        + report
+            .collections
+            .iter()
+            .map(|usage| usage.wal_bytes)
+            .sum::<u64>()
+}
+
+//= spec/usage.md#report
+//= type=test
+//# `RING-USAGE-001` `Storage::usage_report` MUST place every formatted region
+//# in exactly one of the WAL, free, free-space metadata, transaction-log,
+//# collection-owned, or unattributed counts, and the free count MUST equal the
+//# ready count plus the dirty count.
+#[test]
+fn requirement_usage_report_accounts_for_every_region() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let report = storage.usage_report().unwrap();
+    assert_eq!(report.region_count, REGION_COUNT as u32);
+    assert_eq!(report.wal_regions, 1);
+    assert!(report.collections.is_empty());
+    assert_eq!(report.unattributed_regions, 0);
+    assert_eq!(accounted_regions(&report), report.region_count);
+    assert_eq!(
+        report.free_regions,
+        report.ready_regions + report.dirty_regions
+    );
+
+    let mut cell = Cell::new(&mut storage, [7u8; 4]).unwrap();
+    cell.set(&mut storage, [9u8; 4]).unwrap();
+    let report = storage.usage_report().unwrap();
+    assert_eq!(report.collections.len(), 1);
+    assert_eq!(accounted_regions(&report), report.region_count);
+    assert_eq!(
+        report.free_regions,
+        report.ready_regions + report.dirty_regions
+    );
+}
+
+//= spec/usage.md#report
+//= type=test
+//# `RING-USAGE-002` Each live collection's entry MUST count the retained WAL
+//# records addressed to it and their encoded bytes, and the collection
+//# entries plus the storage-owned WAL bytes MUST sum to the total retained WAL
+//# bytes.
+#[test]
+fn requirement_usage_report_attributes_wal_bytes() {
+    let mut flash = TestFlash::new(0xff);
+    let (busy, quiet) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut busy = Cell::new(&mut storage, 0u32).unwrap();
+        let quiet = Cell::new(&mut storage, 0u32).unwrap();
+        for value in 1..4u32 {
+            busy.set(&mut storage, value).unwrap();
+        }
+        (busy.collection_id(), quiet.collection_id())
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    let report = storage.usage_report().unwrap();
+    let busy = report.collection(busy).unwrap();
+    let quiet = report.collection(quiet).unwrap();
+    assert_eq!(busy.collection_type, Some(crate::CollectionType::CELL_CODE));
+    assert_eq!(busy.wal_records, quiet.wal_records + 3);
+    assert!(busy.wal_bytes > quiet.wal_bytes);
+    assert_eq!(attributed_wal_bytes(&report), report.wal_bytes);
+}
+
+//= spec/usage.md#report
+//= type=test
+//# `RING-USAGE-003` A committed region MUST be charged to the live collection
+//# named in its header, by region format, until the region returns to the
+//# free queue.
+#[test]
+fn requirement_usage_report_charges_regions_to_their_collection() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut cell = Cell::new(&mut storage, std::vec![0u8; 4]).unwrap();
+    cell.set(&mut storage, std::vec![1u8; 300]).unwrap();
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 16, 16>::new();
+    let mut log = ObjectLog::new(&mut storage, &mut memory, b"log").unwrap();
+    let mut scratch = [0u8; REGION_SIZE];
+    log.append(&mut storage, b"object", &mut scratch).unwrap();
+    log.flush(&mut storage).unwrap();
+
+    let before = storage.usage_report().unwrap();
+    let usage = before.collection(cell.collection_id()).unwrap();
+    assert_eq!(usage.other_regions, 1);
+    assert_eq!(usage.owned_regions(), 1);
+    let usage = before.collection(log.collection_id()).unwrap();
+    assert!(usage.object_log_data_regions >= 1);
+    assert_eq!(usage.manifest_regions + usage.run_regions, 0);
+    assert_eq!(accounted_regions(&before), before.region_count);
+
+    cell.set(&mut storage, std::vec![2u8; 4]).unwrap();
+    let after = storage.usage_report().unwrap();
+    assert_eq!(
+        after
+            .collection(cell.collection_id())
+            .unwrap()
+            .owned_regions(),
+        0
+    );
+    assert_eq!(after.dirty_regions, before.dirty_regions + 1);
+    assert_eq!(accounted_regions(&after), after.region_count);
+}
+
+//= spec/usage.md#report
+//= type=test
+//# `RING-USAGE-004` Dropped collections MUST NOT appear in the report, and
+//# regions whose header names a dropped collection MUST be counted as
+//# unattributed until they are freed.
+#[test]
+fn requirement_usage_report_omits_dropped_collections() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut dropped = Cell::new(&mut storage, std::vec![0u8; 4]).unwrap();
+    dropped.set(&mut storage, std::vec![1u8; 300]).unwrap();
+    let kept = Cell::new(&mut storage, 0u32).unwrap();
+
+    storage
+        .memory
+        .state
+        .drop_collection_and_begin_reclaim::<REGION_SIZE, REGION_COUNT, _>(
+            storage.backing,
+            &mut storage.memory.workspace,
+            dropped.collection_id(),
+        )
+        .unwrap();
+    let report = storage.usage_report().unwrap();
+    assert!(report.collection(dropped.collection_id()).is_none());
+    assert!(report.collection(kept.collection_id()).is_some());
+    assert_eq!(accounted_regions(&report), report.region_count);
+}
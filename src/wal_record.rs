@@ -141,6 +141,10 @@ pub enum WalRecordType {
     RollbackInlineTransaction,
     /// Stages a transaction-private free intent.
     FreeIntent,
+    /// Removes a region that failed to program or erase from rotation.
+    RetireRegion,
+    /// Records the position of the background scrub.
+    ScrubProgress,
 }
 
 impl WalRecordType {
@@ -166,6 +170,8 @@ impl WalRecordType {
             Self::AddTransactionCollection => 0x11,
             Self::RollbackInlineTransaction => 0x12,
             Self::FreeIntent => 0x13,
+            Self::RetireRegion => 0x14,
+            Self::ScrubProgress => 0x15,
         }
     }
 
@@ -191,6 +197,8 @@ impl WalRecordType {
             0x11 => Ok(Self::AddTransactionCollection),
             0x12 => Ok(Self::RollbackInlineTransaction),
             0x13 => Ok(Self::FreeIntent),
+            0x14 => Ok(Self::RetireRegion),
+            0x15 => Ok(Self::ScrubProgress),
             _ => Err(WalRecordError::InvalidRecordType(code)),
         }
     }
@@ -369,6 +377,18 @@ pub enum WalRecord<'a> {
         /// Region that remains live until the transaction commits.
         region_index: u32,
     },
+    /// `retire_region(region_index)`.
+    RetireRegion {
+        /// Region the backend reported as permanently failed.
+        region_index: u32,
+    },
+    /// `scrub_progress(next_region, completed_passes)`.
+    ScrubProgress {
+        /// Region the next scrub call examines first.
+        next_region: u32,
+        /// Full scrub passes completed so far.
+        completed_passes: u32,
+    },
     /// `wal_recovery()`.
     WalRecovery,
 }
@@ -396,6 +416,8 @@ impl<'a> WalRecord<'a> {
             Self::AddTransactionCollection { .. } => WalRecordType::AddTransactionCollection,
             Self::RollbackInlineTransaction { .. } => WalRecordType::RollbackInlineTransaction,
             Self::FreeIntent { .. } => WalRecordType::FreeIntent,
+            Self::RetireRegion { .. } => WalRecordType::RetireRegion,
+            Self::ScrubProgress { .. } => WalRecordType::ScrubProgress,
         }
     }
 }
@@ -739,6 +761,18 @@ fn encode_logical_record(
             offset = write_u32(buffer, offset, size_of::<u32>() as u32)?;
             offset = write_u32(buffer, offset, region_index)?;
         }
+        WalRecord::RetireRegion { region_index } => {
+            offset = write_u32(buffer, offset, size_of::<u32>() as u32)?;
+            offset = write_u32(buffer, offset, region_index)?;
+        }
+        WalRecord::ScrubProgress {
+            next_region,
+            completed_passes,
+        } => {
+            offset = write_u32(buffer, offset, (2 * size_of::<u32>()) as u32)?;
+            offset = write_u32(buffer, offset, next_region)?;
+            offset = write_u32(buffer, offset, completed_passes)?;
+        }
         WalRecord::WalRecovery => {
             offset = write_u32(buffer, offset, 0)?;
         }
@@ -978,6 +1012,32 @@ fn parse_logical_record(logical: &[u8]) -> Result<WalRecord<'_>, WalRecordError>
                 region_index,
             })
         }
+        WalRecordType::RetireRegion => {
+            let payload_len = read_u32(logical, &mut offset)?;
+            if payload_len != size_of::<u32>() as u32 {
+                return Err(WalRecordError::PayloadLengthMismatch {
+                    record_type,
+                    payload_len,
+                });
+            }
+            let region_index = read_u32(logical, &mut offset)?;
+            Ok(WalRecord::RetireRegion { region_index })
+        }
+        WalRecordType::ScrubProgress => {
+            let payload_len = read_u32(logical, &mut offset)?;
+            if payload_len != (2 * size_of::<u32>()) as u32 {
+                return Err(WalRecordError::PayloadLengthMismatch {
+                    record_type,
+                    payload_len,
+                });
+            }
+            let next_region = read_u32(logical, &mut offset)?;
+            let completed_passes = read_u32(logical, &mut offset)?;
+            Ok(WalRecord::ScrubProgress {
+                next_region,
+                completed_passes,
+            })
+        }
     }
 }
 
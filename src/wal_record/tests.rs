@@ -713,7 +713,9 @@ fn requirement_decode_rejects_invalid_escape_sequence() {
 //# `rollback_transaction = 0x10`,
 //# `add_transaction_collection = 0x11`,
 //# `rollback_inline_transaction = 0x12`,
-//# `free_intent = 0x13`.
+//# `free_intent = 0x13`,
+//# `retire_region = 0x14`,
+//# `scrub_progress = 0x15`.
 #[test]
 fn requirement_record_types_use_canonical_byte_codes() {
     let canonical_codes = [
@@ -736,6 +738,8 @@ fn requirement_record_types_use_canonical_byte_codes() {
         (WalRecordType::AddTransactionCollection, 0x11),
         (WalRecordType::RollbackInlineTransaction, 0x12),
         (WalRecordType::FreeIntent, 0x13),
+        (WalRecordType::RetireRegion, 0x14),
+        (WalRecordType::ScrubProgress, 0x15),
     ];
 
     for (record_type, code) in canonical_codes {
@@ -744,8 +748,8 @@ fn requirement_record_types_use_canonical_byte_codes() {
     }
 
     assert_eq!(
-        WalRecordType::decode(0x14),
-        Err(WalRecordError::InvalidRecordType(0x14))
+        WalRecordType::decode(0x16),
+        Err(WalRecordError::InvalidRecordType(0x16))
     );
 }
 
@@ -788,6 +792,56 @@ fn requirement_free_intent_round_trips_collection_and_region() {
     assert_eq!(decoded.record, record);
 }
 
+//= spec/ring/04-wal-records.md#wal-record-types
+//= type=test
+//# `RING-WAL-PAYLOAD-020` `retire_region`
+//# Main-WAL-only allocator command. Payload is `region_index:u32`.
+#[test]
+fn requirement_retire_region_round_trips_region() {
+    let metadata = metadata(4);
+    let record = WalRecord::RetireRegion { region_index: 3 };
+
+    let (logical, logical_len) = encode_logical(record);
+    assert_eq!(logical[0], WalRecordType::RetireRegion.code());
+    assert_eq!(&logical[1..5], 4u32.to_le_bytes().as_slice());
+    assert_eq!(&logical[5..9], 3u32.to_le_bytes().as_slice());
+    assert_eq!(logical_len, 9 + size_of::<u32>());
+
+    let (physical, encoded_len) = encode_physical(record, metadata);
+    let mut decode_scratch = [0u8; 128];
+    let decoded = decode_record(&physical[..encoded_len], metadata, &mut decode_scratch).unwrap();
+
+    assert_eq!(decoded.record, record);
+}
+
+//= spec/ring/04-wal-records.md#wal-record-types
+//= type=test
+//# `RING-WAL-PAYLOAD-021` `scrub_progress`
+//# Main-WAL-only log control record. Payload is
+//# `next_region:u32, completed_passes:u32`, the background scrub cursor
+//# after a `Storage::scrub` call.
+#[test]
+fn requirement_scrub_progress_round_trips_cursor() {
+    let metadata = metadata(4);
+    let record = WalRecord::ScrubProgress {
+        next_region: 3,
+        completed_passes: 7,
+    };
+
+    let (logical, logical_len) = encode_logical(record);
+    assert_eq!(logical[0], WalRecordType::ScrubProgress.code());
+    assert_eq!(&logical[1..5], 8u32.to_le_bytes().as_slice());
+    assert_eq!(&logical[5..9], 3u32.to_le_bytes().as_slice());
+    assert_eq!(&logical[9..13], 7u32.to_le_bytes().as_slice());
+    assert_eq!(logical_len, 9 + 2 * size_of::<u32>());
+
+    let (physical, encoded_len) = encode_physical(record, metadata);
+    let mut decode_scratch = [0u8; 128];
+    let decoded = decode_record(&physical[..encoded_len], metadata, &mut decode_scratch).unwrap();
+
+    assert_eq!(decoded.record, record);
+}
+
 //= spec/ring/04-wal-records.md#wal-record-types
 //= type=test
 //# `RING-WAL-LAYOUT-002` The logical field order before byte-stuffing
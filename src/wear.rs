@@ -0,0 +1,854 @@
+//! Flash wear telemetry.
+//!
+//! The free-space collection counts every erase it issues through
+//! `erase_free_region_span` and persists the counts in a wear-table chain
+//! linked after its metadata regions. [`Storage::wear_report`] summarizes
+//! those counts.
+//!
+//! [`Storage::wear_level`] runs a static wear-leveling pass: it copies cold
+//! committed regions into fresh regions so the regions they pinned rejoin the
+//! free FIFO. A moved map region is reached again through a rewritten map
+//! head; a moved channel, object-log, queue, ring-log, or time-series region
+//! through a rewritten collection snapshot. Open handles of those types
+//! replay their collection before their next operation after a move.
+//!
+//! [`Storage::wear_report`]: crate::Storage::wear_report
+//! [`Storage::wear_level`]: crate::Storage::wear_level
+
+use heapless::Vec;
+use serde::Serialize;
+
+use core::mem::size_of;
+
+use crate::collections::channel::{self, ChannelError, CHANNEL_SEGMENT_V1_FORMAT};
+use crate::collections::map::{self, MapHeadRegion, MapStorageError};
+use crate::collections::object_log::{
+    self, ObjectLogError, OBJECT_LOG_AUX_V1_FORMAT, OBJECT_LOG_DATA_V1_FORMAT,
+};
+use crate::collections::queue::{self, QUEUE_SEGMENT_V1_FORMAT};
+use crate::collections::ring_log::{self, RING_LOG_SEGMENT_V1_FORMAT};
+use crate::collections::time_series::{self, TIME_SERIES_SEGMENT_V1_FORMAT};
+use crate::disk::{Header, FREE_SPACE_WEAR_RETIRED_FLAG};
+use crate::flash_io::FlashIo;
+use crate::startup::{StartupCollectionBasis, StartupOpenPlan};
+use crate::storage::{StorageRuntime, StorageRuntimeError, StorageVisitError, WalHeadReclaimPlan};
+use crate::wal_record::WalRecord;
+use crate::workspace::StorageWorkspace;
+use crate::{CollectionId, CollectionType};
+
+#[cfg(test)]
+mod tests;
+
+/// Number of buckets in [`WearReport::histogram`].
+pub const WEAR_HISTOGRAM_BUCKETS: usize = 8;
+
+/// Summary of per-region erase counts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WearReport {
+    /// Number of in-rotation regions the counts cover.
+    pub region_count: u32,
+    /// Regions retired after a permanent program or erase failure; their
+    /// counts are left out of every other field.
+    pub retired_regions: u32,
+    /// Lowest erase count of any region.
+    pub min_erase_count: u32,
+    /// Highest erase count of any region.
+    pub max_erase_count: u32,
+    /// Mean erase count, rounded down.
+    pub mean_erase_count: u32,
+    /// Sum of every region's erase count.
+    pub total_erase_count: u64,
+    /// Width of each histogram bucket in erase counts.
+    pub bucket_width: u32,
+    /// Regions per bucket; bucket `i` starts at
+    /// `min_erase_count + i * bucket_width`.
+    pub histogram: [u32; WEAR_HISTOGRAM_BUCKETS],
+}
+
+impl WearReport {
+    pub(crate) fn from_wear_entries(entries: &[u32]) -> Self {
+        let retired_regions = entries
+            .iter()
+            .filter(|entry| **entry & FREE_SPACE_WEAR_RETIRED_FLAG != 0)
+            .count();
+        let retired_regions = u32::try_from(retired_regions).unwrap_or(u32::MAX);
+        let counts = || {
+            entries
+                .iter()
+                .copied()
+                .filter(|entry| entry & FREE_SPACE_WEAR_RETIRED_FLAG == 0)
+        };
+        let Some(min_erase_count) = counts().min() else {
+            return Self {
+                retired_regions,
+                ..Self::default()
+            };
+        };
+        let max_erase_count = counts().max().unwrap_or(min_erase_count);
+        let total_erase_count = counts().map(u64::from).sum::<u64>();
+        let region_count = u32::try_from(counts().count()).unwrap_or(u32::MAX);
+        let mean_erase_count =
+            u32::try_from(total_erase_count / u64::from(region_count.max(1))).unwrap_or(u32::MAX);
+        let span = u64::from(max_erase_count - min_erase_count) + 1;
+        let buckets = WEAR_HISTOGRAM_BUCKETS as u64;
+        let bucket_width = u32::try_from(span.div_ceil(buckets)).unwrap_or(u32::MAX);
+
+        let mut histogram = [0u32; WEAR_HISTOGRAM_BUCKETS];
+        for count in counts() {
+            let bucket = ((count - min_erase_count) / bucket_width) as usize;
+            histogram[bucket.min(WEAR_HISTOGRAM_BUCKETS - 1)] += 1;
+        }
+
+        Self {
+            region_count,
+            retired_regions,
+            min_erase_count,
+            max_erase_count,
+            mean_erase_count,
+            total_erase_count,
+            bucket_width,
+            histogram,
+        }
+    }
+}
+
+/// Deepest run-chain region, and longest channel segment chain, a
+/// wear-leveling pass will relocate.
+///
+/// Relocating a run region rewrites every region above it in the chain, and
+/// relocating a channel segment rewrites every flushed segment of the
+/// channel, so deeper regions and longer channels are left in place.
+pub const MAX_WEAR_LEVEL_CHAIN_REGIONS: usize = 16;
+
+/// Limits for one [`Storage::wear_level`] pass.
+///
+/// [`Storage::wear_level`]: crate::Storage::wear_level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WearLevelBudget {
+    /// Maximum number of regions the pass may copy.
+    pub max_regions: usize,
+    /// A region is cold when its erase count is at least this far below the
+    /// highest erase count in the store.
+    pub min_erase_gap: u32,
+}
+
+impl WearLevelBudget {
+    /// Creates a wear-leveling budget.
+    pub const fn new(max_regions: usize, min_erase_gap: u32) -> Self {
+        Self {
+            max_regions,
+            min_erase_gap,
+        }
+    }
+}
+
+/// Outcome of one [`Storage::wear_level`] pass.
+///
+/// [`Storage::wear_level`]: crate::Storage::wear_level
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WearLevelReport {
+    /// Regions copied into fresh regions, including rewritten manifests.
+    pub relocated_regions: usize,
+    /// Map heads and collection snapshots retargeted at relocated regions.
+    pub retargeted_heads: usize,
+    /// Cold committed regions of live collections that the pass has no way
+    /// to move, counted when the pass began: cell values and regions of
+    /// registered plugin types.
+    pub pinned_regions: usize,
+    /// Whether the pass stopped because no cold region remained, rather than
+    /// because the budget ran out.
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum WearLevelTarget {
+    /// A manifest or run region reached from a map head.
+    Map {
+        head_region: u32,
+        region: MapHeadRegion,
+    },
+    /// A segment or data region named by the collection's WAL snapshot, or
+    /// an object-log auxiliary region linked from such a data region.
+    Snapshot { collection_type: u16 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WearLevelCandidate {
+    collection_id: CollectionId,
+    region_index: u32,
+    sequence: u64,
+    erase_count: u32,
+    target: WearLevelTarget,
+}
+
+impl WearLevelCandidate {
+    /// Orders candidates coldest first; the region index breaks ties so every
+    /// candidate has a distinct key.
+    fn key(&self) -> (u32, u64, u32) {
+        (self.erase_count, self.sequence, self.region_index)
+    }
+}
+
+/// Result of trying to move one snapshot-named region.
+enum SnapshotRelocation {
+    Relocated(usize),
+    /// The snapshot does not reach the region, or the channel chain or
+    /// object-log region group it sits in is longer than
+    /// [`MAX_WEAR_LEVEL_CHAIN_REGIONS`].
+    Skipped,
+    OverBudget,
+}
+
+/// Relocates the coldest eligible regions until the budget or the candidates
+/// run out.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn wear_level<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &mut StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    snapshot_scratch: &mut [u8],
+    reclaim_source_regions: &mut Vec<u32, REGION_COUNT>,
+    active_collections: &mut Vec<CollectionId, MAX_COLLECTIONS>,
+    reclaim_plan: &mut WalHeadReclaimPlan<MAX_COLLECTIONS>,
+    open_plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+    budget: WearLevelBudget,
+) -> Result<WearLevelReport, StorageRuntimeError> {
+    // Regions written by this pass carry a newer sequence and are never
+    // picked again, so repeated passes cannot bounce the same data around.
+    let pass_sequence = storage.max_seen_sequence();
+    let mut report = WearLevelReport {
+        pinned_regions: count_pinned_regions::<IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            budget.min_erase_gap,
+        )?,
+        ..WearLevelReport::default()
+    };
+    // Segment candidates whose snapshot did not name them are skipped by
+    // searching only above the last skipped key.
+    let mut skipped_above = None;
+    loop {
+        let remaining = budget.max_regions.saturating_sub(report.relocated_regions);
+        let map_candidate = coldest_map_candidate::<REGION_SIZE, IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            workspace,
+            pass_sequence,
+            budget.min_erase_gap,
+        )?;
+        let segment_candidate = coldest_segment_candidate::<IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            pass_sequence,
+            budget.min_erase_gap,
+            skipped_above,
+        )?;
+        let candidate = match (map_candidate, segment_candidate) {
+            (Some((map, _)), Some(segment)) if segment.key() < map.key() => Some((segment, 1)),
+            (Some((map, cost)), _) => Some((map, cost)),
+            (None, Some(segment)) => Some((segment, 1)),
+            (None, None) => None,
+        };
+        let Some((candidate, cost)) = candidate else {
+            report.complete = true;
+            return Ok(report);
+        };
+        match candidate.target {
+            WearLevelTarget::Map {
+                head_region,
+                region,
+            } => {
+                if cost > remaining {
+                    return Ok(report);
+                }
+                let copied = map::relocate_map_head_region::<
+                    REGION_SIZE,
+                    REGION_COUNT,
+                    IO,
+                    MAX_COLLECTIONS,
+                    MAX_WEAR_LEVEL_CHAIN_REGIONS,
+                >(
+                    storage,
+                    flash,
+                    workspace,
+                    reclaim_source_regions,
+                    active_collections,
+                    reclaim_plan,
+                    open_plan,
+                    candidate.collection_id,
+                    head_region,
+                    region,
+                )
+                .map_err(|error| map_storage_error(error, candidate.collection_id, head_region))?;
+                report.relocated_regions += copied;
+                report.retargeted_heads += 1;
+            }
+            WearLevelTarget::Snapshot { collection_type } => {
+                match relocate_snapshot_region::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+                    storage,
+                    flash,
+                    workspace,
+                    snapshot_scratch,
+                    reclaim_source_regions,
+                    active_collections,
+                    reclaim_plan,
+                    open_plan,
+                    candidate.collection_id,
+                    collection_type,
+                    candidate.region_index,
+                    remaining,
+                )? {
+                    SnapshotRelocation::Relocated(copied) => {
+                        report.relocated_regions += copied;
+                        report.retargeted_heads += 1;
+                        storage.note_region_relocation();
+                    }
+                    SnapshotRelocation::Skipped => skipped_above = Some(candidate.key()),
+                    SnapshotRelocation::OverBudget => return Ok(report),
+                }
+            }
+        }
+    }
+}
+
+fn coldest_map_candidate<const REGION_SIZE: usize, IO: FlashIo, const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    pass_sequence: u64,
+    min_erase_gap: u32,
+) -> Result<Option<(WearLevelCandidate, usize)>, StorageRuntimeError> {
+    let max_erase_count = storage.wear_report().max_erase_count;
+    let mut coldest: Option<(WearLevelCandidate, usize)> = None;
+    for collection in storage.collections() {
+        // Heads are only retargeted for maps whose state is fully captured by
+        // their manifest; a new head would discard retained updates.
+        let StartupCollectionBasis::Region(head_region) = collection.basis() else {
+            continue;
+        };
+        if !collection
+            .collection_type()
+            .is_some_and(crate::collections::map::is_lsm_collection_type)
+            || collection.pending_update_count() != 0
+        {
+            continue;
+        }
+        let collection_id = collection.collection_id();
+        map::visit_map_head_regions::<REGION_SIZE, IO, _>(
+            flash,
+            workspace,
+            storage.metadata(),
+            collection_id,
+            head_region,
+            |region| {
+                let depth = region.run.map_or(0, |(_, depth)| depth as usize);
+                if region.sequence > pass_sequence || depth >= MAX_WEAR_LEVEL_CHAIN_REGIONS {
+                    return Ok(());
+                }
+                let erase_count = storage.region_erase_count(region.region_index);
+                if erase_count.saturating_add(min_erase_gap) > max_erase_count {
+                    return Ok(());
+                }
+                let candidate = WearLevelCandidate {
+                    collection_id,
+                    region_index: region.region_index,
+                    sequence: region.sequence,
+                    erase_count,
+                    target: WearLevelTarget::Map {
+                        head_region,
+                        region,
+                    },
+                };
+                if coldest.is_none_or(|(current, _)| candidate.key() < current.key()) {
+                    let cost = if region.run.is_some() { depth + 2 } else { 1 };
+                    coldest = Some((candidate, cost));
+                }
+                Ok(())
+            },
+        )
+        .map_err(|error| map_storage_error(error, collection_id, head_region))?;
+    }
+    Ok(coldest)
+}
+
+/// Returns whether a wear-leveling pass can move a region of
+/// `collection_format` owned by a collection of `collection_type` by
+/// rewriting the collection's snapshot.
+fn is_snapshot_region(collection_type: u16, collection_format: u16) -> bool {
+    match collection_type {
+        CollectionType::CHANNEL_CODE => collection_format == CHANNEL_SEGMENT_V1_FORMAT,
+        CollectionType::OBJECT_LOG_CODE => {
+            collection_format == OBJECT_LOG_DATA_V1_FORMAT
+                || collection_format == OBJECT_LOG_AUX_V1_FORMAT
+        }
+        CollectionType::QUEUE_CODE => collection_format == QUEUE_SEGMENT_V1_FORMAT,
+        CollectionType::RING_LOG_CODE => collection_format == RING_LOG_SEGMENT_V1_FORMAT,
+        CollectionType::TIME_SERIES_CODE => collection_format == TIME_SERIES_SEGMENT_V1_FORMAT,
+        _ => false,
+    }
+}
+
+/// Finds the coldest cold region whose header names a collection that a
+/// snapshot rewrite can move, with a key above `skipped_above`.
+fn coldest_segment_candidate<IO: FlashIo, const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    pass_sequence: u64,
+    min_erase_gap: u32,
+    skipped_above: Option<(u32, u64, u32)>,
+) -> Result<Option<WearLevelCandidate>, StorageRuntimeError> {
+    let max_erase_count = storage.wear_report().max_erase_count;
+    let mut coldest: Option<WearLevelCandidate> = None;
+    for region_index in 0..storage.metadata().region_count {
+        let erase_count = storage.region_erase_count(region_index);
+        if storage.is_region_retired(region_index)
+            || storage.free_space().contains_free_region(region_index)
+            || erase_count.saturating_add(min_erase_gap) > max_erase_count
+        {
+            continue;
+        }
+        let header = flash.read_region(region_index, 0, Header::ENCODED_LEN, |bytes| {
+            Header::decode(bytes).ok()
+        })?;
+        let Some(header) = header else {
+            continue;
+        };
+        if header.sequence > pass_sequence {
+            continue;
+        }
+        // The snapshot is the collection's whole state only when no update
+        // follows it, so a rewritten snapshot cannot drop one.
+        let Some(collection_type) = storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == header.collection_id)
+            .filter(|collection| {
+                collection.basis() == StartupCollectionBasis::WalSnapshot
+                    && collection.pending_update_count() == 0
+                    && storage.ensure_not_quarantined(header.collection_id).is_ok()
+            })
+            .and_then(|collection| collection.collection_type())
+        else {
+            continue;
+        };
+        if !is_snapshot_region(collection_type, header.collection_format) {
+            continue;
+        }
+        let candidate = WearLevelCandidate {
+            collection_id: header.collection_id,
+            region_index,
+            sequence: header.sequence,
+            erase_count,
+            target: WearLevelTarget::Snapshot { collection_type },
+        };
+        if skipped_above.is_some_and(|skipped| candidate.key() <= skipped) {
+            continue;
+        }
+        if coldest.is_none_or(|current| candidate.key() < current.key()) {
+            coldest = Some(candidate);
+        }
+    }
+    Ok(coldest)
+}
+
+/// Moves `region_index` by copying it to a fresh region and writing a new
+/// snapshot that names the copy, in one collection transaction.
+///
+/// Channel segments name their successor and the commands they hold name
+/// earlier commands by address, so a channel segment moves with every other
+/// flushed segment of its channel. An object-log data region moves with the
+/// auxiliary regions its large objects link, and an auxiliary region with
+/// the data region that links it.
+#[allow(clippy::too_many_arguments)]
+fn relocate_snapshot_region<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &mut StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    snapshot_scratch: &mut [u8],
+    reclaim_source_regions: &mut Vec<u32, REGION_COUNT>,
+    active_collections: &mut Vec<CollectionId, MAX_COLLECTIONS>,
+    reclaim_plan: &mut WalHeadReclaimPlan<MAX_COLLECTIONS>,
+    open_plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+    collection_type: u16,
+    region_index: u32,
+    remaining: usize,
+) -> Result<SnapshotRelocation, StorageRuntimeError> {
+    let invalid = || StorageRuntimeError::InvalidHeadTarget {
+        collection_id,
+        region_index,
+    };
+    let Some(snapshot_len) = load_snapshot::<REGION_SIZE, IO, MAX_COLLECTIONS>(
+        storage,
+        flash,
+        workspace,
+        collection_id,
+        snapshot_scratch,
+    )?
+    else {
+        return Ok(SnapshotRelocation::Skipped);
+    };
+    let snapshot = snapshot_scratch
+        .get_mut(..snapshot_len)
+        .ok_or_else(invalid)?;
+    let mut chain = Vec::<u32, MAX_WEAR_LEVEL_CHAIN_REGIONS>::new();
+    match collection_type {
+        CollectionType::CHANNEL_CODE => {
+            let segments = channel::snapshot_segment_chain::<IO, MAX_WEAR_LEVEL_CHAIN_REGIONS>(
+                flash,
+                collection_id,
+                storage.metadata().region_count,
+                snapshot,
+            )
+            .map_err(|error| match error {
+                ChannelError::Storage(error) => error,
+                _ => invalid(),
+            })?;
+            match segments {
+                Some(segments) if segments.contains(&region_index) => chain = segments,
+                _ => return Ok(SnapshotRelocation::Skipped),
+            }
+        }
+        CollectionType::OBJECT_LOG_CODE => {
+            let group =
+                object_log::snapshot_region_group::<REGION_SIZE, IO, MAX_WEAR_LEVEL_CHAIN_REGIONS>(
+                    flash,
+                    workspace,
+                    storage.metadata(),
+                    collection_id,
+                    snapshot,
+                    region_index,
+                )
+                .map_err(|error| match error {
+                    ObjectLogError::Storage(error) => error,
+                    _ => invalid(),
+                })?;
+            match group {
+                Some(group) => chain = group,
+                None => return Ok(SnapshotRelocation::Skipped),
+            }
+        }
+        _ => {
+            if snapshot_region_offset(collection_type, snapshot, region_index)
+                .map_err(|()| invalid())?
+                .is_none()
+            {
+                return Ok(SnapshotRelocation::Skipped);
+            }
+            chain.push(region_index).map_err(|_| invalid())?;
+        }
+    }
+    if chain.len() > remaining {
+        return Ok(SnapshotRelocation::OverBudget);
+    }
+
+    storage.ensure_foreground_allocation_headroom_for::<REGION_SIZE, REGION_COUNT, IO>(
+        flash,
+        workspace,
+        reclaim_source_regions,
+        active_collections,
+        reclaim_plan,
+        open_plan,
+        u32::try_from(chain.len()).map_err(|_| invalid())?,
+    )?;
+    storage.begin_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+        flash,
+        workspace,
+        collection_id,
+    )?;
+    let result =
+        relocate_snapshot_chain_in_transaction::<REGION_SIZE, REGION_COUNT, IO, MAX_COLLECTIONS>(
+            storage,
+            flash,
+            workspace,
+            snapshot,
+            reclaim_source_regions,
+            active_collections,
+            reclaim_plan,
+            open_plan,
+            collection_id,
+            collection_type,
+            &chain,
+        );
+    match result {
+        Ok(()) => {
+            storage.commit_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                collection_id,
+            )?;
+            storage.finish_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                collection_id,
+            )?;
+            Ok(SnapshotRelocation::Relocated(chain.len()))
+        }
+        Err(error) => {
+            let _ = storage.rollback_collection_transaction::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                collection_id,
+            );
+            Err(error)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn relocate_snapshot_chain_in_transaction<
+    const REGION_SIZE: usize,
+    const REGION_COUNT: usize,
+    IO: FlashIo,
+    const MAX_COLLECTIONS: usize,
+>(
+    storage: &mut StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    snapshot: &mut [u8],
+    reclaim_source_regions: &mut Vec<u32, REGION_COUNT>,
+    active_collections: &mut Vec<CollectionId, MAX_COLLECTIONS>,
+    reclaim_plan: &mut WalHeadReclaimPlan<MAX_COLLECTIONS>,
+    open_plan: &mut StartupOpenPlan<REGION_COUNT, MAX_COLLECTIONS>,
+    collection_id: CollectionId,
+    collection_type: u16,
+    chain: &[u32],
+) -> Result<(), StorageRuntimeError> {
+    let invalid = |region_index| StorageRuntimeError::InvalidHeadTarget {
+        collection_id,
+        region_index,
+    };
+    // Every copy is reserved first because channel segments and object-log
+    // regions name each other.
+    let mut copies = Vec::<u32, MAX_WEAR_LEVEL_CHAIN_REGIONS>::new();
+    for old_region in chain.iter().copied() {
+        let new_region = storage.reserve_next_region_for::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+            reclaim_source_regions,
+            active_collections,
+            reclaim_plan,
+            open_plan,
+        )?;
+        copies.push(new_region).map_err(|_| invalid(old_region))?;
+    }
+    let retarget = |region: u32| {
+        chain
+            .iter()
+            .position(|old| *old == region)
+            .map_or(region, |index| copies[index])
+    };
+    let payload_len = storage.committed_payload_capacity::<REGION_SIZE>()?;
+    for (old_region, new_region) in chain.iter().copied().zip(copies.iter().copied()) {
+        let collection_format = flash
+            .read_region(old_region, 0, Header::ENCODED_LEN, Header::decode)?
+            .map_err(|_| invalid(old_region))?
+            .collection_format;
+        let metadata = storage.metadata();
+        let (payload, _) = workspace.encode_buffers();
+        let payload = payload
+            .get_mut(..payload_len)
+            .ok_or_else(|| invalid(old_region))?;
+        flash.read_region(old_region, Header::ENCODED_LEN, payload_len, |bytes| {
+            payload.copy_from_slice(bytes);
+        })?;
+        match collection_type {
+            CollectionType::CHANNEL_CODE => channel::retarget_segment_payload(payload, retarget)
+                .map_err(|_| invalid(old_region))?,
+            CollectionType::OBJECT_LOG_CODE => object_log::retarget_region_payload::<REGION_SIZE>(
+                metadata,
+                collection_format,
+                payload,
+                retarget,
+            )
+            .map_err(|_| invalid(old_region))?,
+            _ => {}
+        }
+        let written = storage
+            .write_committed_region_from_workspace_payload::<REGION_SIZE, REGION_COUNT, IO>(
+                flash,
+                workspace,
+                new_region,
+                collection_id,
+                collection_format,
+                payload_len,
+            )?;
+        // Copies already written name the reserved region, so a copy that
+        // had to move past a bad region fails the whole move.
+        if written != new_region {
+            return Err(StorageRuntimeError::BadRegion(new_region));
+        }
+    }
+    if collection_type == CollectionType::CHANNEL_CODE {
+        channel::retarget_snapshot(snapshot, retarget).map_err(|_| invalid(chain[0]))?;
+    } else {
+        let offset = snapshot_region_offset(collection_type, snapshot, chain[0])
+            .map_err(|()| invalid(chain[0]))?
+            .ok_or_else(|| invalid(chain[0]))?;
+        snapshot
+            .get_mut(offset..offset + size_of::<u32>())
+            .ok_or_else(|| invalid(chain[0]))?
+            .copy_from_slice(&copies[0].to_le_bytes());
+    }
+    storage.append_snapshot_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+        flash,
+        workspace,
+        collection_id,
+        collection_type,
+        snapshot,
+    )?;
+    for old_region in chain.iter().copied() {
+        storage.append_free_region_with_rotation::<REGION_SIZE, REGION_COUNT, IO>(
+            flash,
+            workspace,
+            collection_id,
+            old_region,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns where a queue, ring-log, time-series, or object-log snapshot
+/// names `region_index`.
+fn snapshot_region_offset(
+    collection_type: u16,
+    snapshot: &[u8],
+    region_index: u32,
+) -> Result<Option<usize>, ()> {
+    match collection_type {
+        CollectionType::OBJECT_LOG_CODE => {
+            object_log::snapshot_data_region_offset(snapshot, region_index).map_err(|_| ())
+        }
+        CollectionType::QUEUE_CODE => {
+            queue::snapshot_segment_offset(snapshot, region_index).map_err(|_| ())
+        }
+        CollectionType::RING_LOG_CODE => {
+            ring_log::snapshot_segment_offset(snapshot, region_index).map_err(|_| ())
+        }
+        CollectionType::TIME_SERIES_CODE => {
+            time_series::snapshot_segment_offset(snapshot, region_index).map_err(|_| ())
+        }
+        _ => Err(()),
+    }
+}
+
+/// Copies the newest WAL snapshot of `collection_id` into `output` and
+/// returns its length.
+fn load_snapshot<const REGION_SIZE: usize, IO: FlashIo, const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    workspace: &mut StorageWorkspace<REGION_SIZE>,
+    collection_id: CollectionId,
+    output: &mut [u8],
+) -> Result<Option<usize>, StorageRuntimeError> {
+    let mut snapshot_len = None;
+    storage
+        .visit_wal_records::<REGION_SIZE, IO, StorageRuntimeError, _>(
+            flash,
+            workspace,
+            |_flash, record| {
+                match record {
+                    WalRecord::Snapshot {
+                        collection_id: seen,
+                        payload,
+                        ..
+                    } if seen == collection_id => {
+                        output
+                            .get_mut(..payload.len())
+                            .ok_or(StorageRuntimeError::InvalidHeadTarget {
+                                collection_id,
+                                region_index: 0,
+                            })?
+                            .copy_from_slice(payload);
+                        snapshot_len = Some(payload.len());
+                    }
+                    WalRecord::NewCollection {
+                        collection_id: seen,
+                        ..
+                    }
+                    | WalRecord::DropCollection {
+                        collection_id: seen,
+                    } if seen == collection_id => snapshot_len = None,
+                    _ => {}
+                }
+                Ok(())
+            },
+        )
+        .map_err(|error| match error {
+            StorageVisitError::Storage(error) | StorageVisitError::Visitor(error) => error,
+        })?;
+    Ok(snapshot_len)
+}
+
+/// Counts cold regions whose header names a live collection whose regions
+/// the pass cannot move.
+///
+/// Map regions are moved by retargeting the map head, and channel, object-log,
+/// queue, ring-log, and time-series regions by rewriting the collection's
+/// snapshot. Cell and plugin regions have no relocation path.
+fn count_pinned_regions<IO: FlashIo, const MAX_COLLECTIONS: usize>(
+    storage: &StorageRuntime<MAX_COLLECTIONS>,
+    flash: &mut IO,
+    min_erase_gap: u32,
+) -> Result<usize, StorageRuntimeError> {
+    let max_erase_count = storage.wear_report().max_erase_count;
+    let mut pinned = 0;
+    for region_index in 0..storage.metadata().region_count {
+        if storage.is_region_retired(region_index)
+            || storage.free_space().contains_free_region(region_index)
+            || storage
+                .region_erase_count(region_index)
+                .saturating_add(min_erase_gap)
+                > max_erase_count
+        {
+            continue;
+        }
+        let header = flash.read_region(region_index, 0, Header::ENCODED_LEN, |bytes| {
+            Header::decode(bytes).ok()
+        })?;
+        let Some(header) = header else {
+            continue;
+        };
+        let pinned_type = storage
+            .collections()
+            .iter()
+            .find(|collection| collection.collection_id() == header.collection_id)
+            .filter(|collection| collection.basis() != StartupCollectionBasis::Dropped)
+            .and_then(|collection| collection.collection_type())
+            .is_some_and(|collection_type| {
+                !crate::collections::map::is_lsm_collection_type(collection_type)
+                    && !is_snapshot_region(collection_type, header.collection_format)
+            });
+        if pinned_type && header.collection_id != CollectionId(0) {
+            pinned += 1;
+        }
+    }
+    Ok(pinned)
+}
+
+fn map_storage_error(
+    error: MapStorageError,
+    collection_id: CollectionId,
+    head_region: u32,
+) -> StorageRuntimeError {
+    match error {
+        MapStorageError::Storage(error) => error,
+        MapStorageError::Mock(error) => StorageRuntimeError::Mock(error),
+        MapStorageError::Disk(error) => StorageRuntimeError::Startup(error.into()),
+        _ => StorageRuntimeError::InvalidHeadTarget {
+            collection_id,
+            region_index: head_region,
+        },
+    }
+}
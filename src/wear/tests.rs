@@ -0,0 +1,698 @@
+use super::*;
+
+use crate::collections::cell::Cell;
+use crate::collections::channel::{
+    AddCommand, Channel, CommandAddress, MemberId, MemberSequence, MessageId,
+};
+use crate::collections::map::collect_map_head_regions;
+use crate::collections::object_log::{ObjectLog, ObjectLogHandle, ObjectLogMemory};
+use crate::collections::queue::{DurableQueue, DurableQueueMemory};
+use crate::vec_like::VecLikeSlice;
+use crate::{
+    decode_free_space_wear_counts, CollectionId, CollectionType, FreeSpaceWearPrologue, Header,
+    LsmMap, MockFlash, StartupCollectionBasis, Storage, FREE_SPACE_WEAR_V1_FORMAT,
+    MAP_REGION_V2_FORMAT,
+};
+
+const REGION_SIZE: usize = 512;
+const REGION_COUNT: usize = 32;
+
+type TestFlash = MockFlash<REGION_SIZE, REGION_COUNT, 32768>;
+type TestStorage<'a> = Storage<'a, 'static, TestFlash, REGION_SIZE, REGION_COUNT>;
+
+/// Rewrites a cell and reclaims the WAL head until at least one dirty span
+/// has been erased.
+fn churn_until_erased(storage: &mut TestStorage<'_>) {
+    let mut cell = Cell::new(storage, 0u32).unwrap();
+    let (_, initial_ready_boundary, ..) = storage.free_space_cursors();
+    for value in 1..256u32 {
+        cell.set(storage, value).unwrap();
+        if storage.wal_head() != storage.wal_tail() {
+            storage.reclaim_wal_head().unwrap();
+        }
+        if storage.free_space_cursors().1 > initial_ready_boundary {
+            return;
+        }
+    }
+    panic!("no dirty span was erased");
+}
+
+fn map_regions(storage: &mut TestStorage<'_>, collection_id: CollectionId) -> std::vec::Vec<u32> {
+    let head_region = storage
+        .collections()
+        .iter()
+        .find(|collection| collection.collection_id() == collection_id)
+        .map(|collection| collection.basis())
+        .unwrap();
+    let StartupCollectionBasis::Region(head_region) = head_region else {
+        panic!("map has no region basis");
+    };
+    let metadata = storage.metadata();
+    let mut regions = heapless::Vec::<u32, REGION_COUNT>::new();
+    storage.with_io_workspace(|flash, workspace| {
+        collect_map_head_regions::<REGION_SIZE, _, REGION_COUNT>(
+            flash,
+            workspace,
+            metadata,
+            collection_id,
+            head_region,
+            &mut regions,
+        )
+        .unwrap()
+    });
+    regions.to_vec()
+}
+
+/// Reads the entries of the single-region wear table back from flash.
+fn persisted_wear_entries(storage: &mut TestStorage<'_>) -> std::vec::Vec<u32> {
+    let wear_region = storage.free_space_wear_regions()[0];
+    storage.with_io_workspace(|flash, _| {
+        flash
+            .read_region(wear_region, 0, REGION_SIZE, |bytes| {
+                let header = Header::decode(bytes).unwrap();
+                assert_eq!(header.collection_format, FREE_SPACE_WEAR_V1_FORMAT);
+                let counts_start = Header::ENCODED_LEN + FreeSpaceWearPrologue::ENCODED_LEN;
+                let prologue = FreeSpaceWearPrologue::decode(
+                    &bytes[Header::ENCODED_LEN..counts_start],
+                    REGION_COUNT as u32,
+                )
+                .unwrap();
+                let mut counts = std::vec::Vec::new();
+                decode_free_space_wear_counts(&bytes[counts_start..], prologue, |count| {
+                    counts.push(count)
+                })
+                .unwrap();
+                counts
+            })
+            .unwrap()
+    })
+}
+
+/// Rewrites a cell and reclaims the WAL head `rounds` times, failing the
+/// first dirty free-space entry seen when `fail_dirty` is set. Returns the
+/// failed region.
+fn churn_with_bad_dirty_region(
+    storage: &mut TestStorage<'_>,
+    cell: &mut Cell<u32>,
+    rounds: u32,
+    fail_dirty: bool,
+) -> Option<u32> {
+    let mut failed = None;
+    for value in 0..rounds {
+        cell.set(storage, value).unwrap();
+        if storage.wal_head() != storage.wal_tail() {
+            storage.reclaim_wal_head().unwrap();
+        }
+        let (_, ready_boundary, append_tail, ..) = storage.free_space_cursors();
+        if fail_dirty && failed.is_none() && ready_boundary < append_tail {
+            let region = storage.free_space_entries()[ready_boundary as usize];
+            storage
+                .with_io_workspace(|flash, _| flash.fail_region(region))
+                .unwrap();
+            failed = Some(region);
+        }
+    }
+    failed
+}
+
+/// Lists the regions whose header names `collection_id`.
+fn regions_owned_by(
+    storage: &mut TestStorage<'_>,
+    collection_id: CollectionId,
+) -> std::vec::Vec<u32> {
+    (0..REGION_COUNT as u32)
+        .filter(|region| {
+            storage.with_io_workspace(|flash, _| {
+                flash
+                    .read_region(*region, 0, Header::ENCODED_LEN, |bytes| {
+                        Header::decode(bytes)
+                            .is_ok_and(|header| header.collection_id == collection_id)
+                    })
+                    .unwrap()
+            })
+        })
+        .collect()
+}
+
+fn assert_map_contents(storage: &mut TestStorage<'_>, collection_id: CollectionId) {
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(collection_id, storage, crate::test_lsm_map_memory()).unwrap();
+    for key in 0..40u16 {
+        assert_eq!(
+            map.get(storage, &key, |_, value| *value).unwrap(),
+            Some(key + 1000)
+        );
+    }
+}
+
+//= spec/wear.md#erase-counts
+//= type=test
+//# `RING-WEAR-001` Applying `erase_free_region_span(count,
+//# ready_boundary_after)` MUST add one to the erase count of each region in
+//# the erased span, so the total count equals the number of erased entries.
+#[test]
+fn requirement_wear_erase_spans_increment_erased_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let (_, initial_ready_boundary, ..) = storage.free_space_cursors();
+    assert_eq!(storage.wear_report().total_erase_count, 0);
+
+    churn_until_erased(&mut storage);
+    let (_, ready_boundary, ..) = storage.free_space_cursors();
+    let erased =
+        &storage.free_space_entries()[initial_ready_boundary as usize..ready_boundary as usize];
+    let report = storage.wear_report();
+    assert_eq!(report.total_erase_count, erased.len() as u64);
+    assert_eq!(report.max_erase_count, 1);
+    assert_eq!(
+        report.histogram[0],
+        REGION_COUNT as u32 - erased.len() as u32
+    );
+    assert_eq!(report.histogram[1], erased.len() as u32);
+}
+
+//= spec/wear.md#erase-counts
+//= type=test
+//# `RING-WEAR-002` Erase counts MUST survive reopen: storage MUST persist
+//# them in a `free_space_wear_v1` chain linked from the last free-space
+//# metadata region, and replay MUST count a retained erase span exactly once.
+#[test]
+fn requirement_wear_counts_survive_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let before = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        churn_until_erased(&mut storage);
+        assert_eq!(storage.free_space_wear_regions().len(), 1);
+        let persisted = persisted_wear_entries(&mut storage);
+        assert_eq!(persisted.len(), REGION_COUNT);
+        let report = storage.wear_report();
+        assert_eq!(WearReport::from_wear_entries(&persisted), report);
+        report
+    };
+    assert!(before.total_erase_count > 0);
+
+    for _ in 0..2 {
+        let storage = crate::test_reopen_storage(&mut flash);
+        assert_eq!(storage.wear_report(), before);
+    }
+}
+
+//= spec/wear.md#wear-report
+//= type=test
+//# `RING-WEAR-003` `Storage::wear_report` MUST return the minimum, maximum,
+//# and rounded-down mean erase counts over every region not retired, a
+//# histogram of `WEAR_HISTOGRAM_BUCKETS` equal-width buckets starting at the
+//# minimum, and the number of retired regions.
+#[test]
+fn requirement_wear_report_summarizes_counts() {
+    let report = WearReport::from_wear_entries(&[3, 4, 4, 10, 19, 3]);
+    assert_eq!(report.region_count, 6);
+    assert_eq!(report.min_erase_count, 3);
+    assert_eq!(report.max_erase_count, 19);
+    assert_eq!(report.total_erase_count, 43);
+    assert_eq!(report.mean_erase_count, 7);
+    assert_eq!(report.bucket_width, 3);
+    assert_eq!(report.histogram, [4, 0, 1, 0, 0, 1, 0, 0]);
+
+    let flat = WearReport::from_wear_entries(&[5; 4]);
+    assert_eq!(flat.bucket_width, 1);
+    assert_eq!(flat.histogram, [4, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(WearReport::from_wear_entries(&[]), WearReport::default());
+
+    let retired = FREE_SPACE_WEAR_RETIRED_FLAG | 1;
+    let report = WearReport::from_wear_entries(&[3, retired, 4, 4, 10, 19, 3]);
+    assert_eq!(report.region_count, 6);
+    assert_eq!(report.min_erase_count, 3);
+    assert_eq!(report.total_erase_count, 43);
+    assert_eq!(report.retired_regions, 1);
+    assert_eq!(
+        WearReport::from_wear_entries(&[retired]),
+        WearReport {
+            retired_regions: 1,
+            ..WearReport::default()
+        }
+    );
+}
+
+//= spec/wear.md#static-wear-leveling
+//= type=test
+//# `RING-WEAR-004` `Storage::wear_level` MUST copy each selected region, and
+//# every run-chain region above it, into freshly allocated regions, append a
+//# `head` record naming a rewritten manifest, and free every replaced region
+//# into the free-space FIFO, all within one collection transaction.
+#[test]
+fn requirement_wear_level_relocates_map_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let collection_id;
+    let relocated;
+    {
+        let mut storage = crate::test_format_storage(&mut flash);
+        collection_id = crate::test_flushed_map(&mut storage);
+        let before = map_regions(&mut storage, collection_id);
+        assert!(before.len() >= 3);
+
+        let report = storage
+            .wear_level(WearLevelBudget::new(usize::MAX, 0))
+            .unwrap();
+        assert!(report.complete);
+        assert!(report.retargeted_heads >= 1);
+        assert!(report.relocated_regions >= before.len());
+        assert!(!storage.runtime().transaction_open_for(collection_id));
+
+        relocated = map_regions(&mut storage, collection_id);
+        assert_eq!(relocated.len(), before.len());
+        for region in before.iter() {
+            assert!(!relocated.contains(region));
+            assert!(storage.free_space_entries().contains(region));
+        }
+        assert_map_contents(&mut storage, collection_id);
+    }
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(map_regions(&mut storage, collection_id), relocated);
+    assert_map_contents(&mut storage, collection_id);
+}
+
+//= spec/wear.md#static-wear-leveling
+//= type=test
+//# `RING-WEAR-005` A region MUST be selected only when it is a manifest or
+//# run-chain region of a map whose basis is a committed region with no
+//# retained updates, it was committed before the pass began, and its erase
+//# count is at least `min_erase_gap` below the highest erase count; the
+//# coldest such region MUST be selected first.
+#[test]
+fn requirement_wear_level_selects_only_cold_idle_map_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let collection_id = crate::test_flushed_map(&mut storage);
+    let before = map_regions(&mut storage, collection_id);
+
+    // Every count is still zero, so no region is below the highest count.
+    let report = storage
+        .wear_level(WearLevelBudget::new(usize::MAX, 1))
+        .unwrap();
+    assert_eq!(report.relocated_regions, 0);
+    assert!(report.complete);
+
+    // Retained updates would be discarded by a new head.
+    let mut map =
+        LsmMap::<u16, u16, 8>::open(collection_id, &mut storage, crate::test_lsm_map_memory())
+            .unwrap();
+    map.set(&mut storage, 0, 2000).unwrap();
+    let report = storage
+        .wear_level(WearLevelBudget::new(usize::MAX, 0))
+        .unwrap();
+    assert_eq!(
+        report,
+        WearLevelReport {
+            complete: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(map_regions(&mut storage, collection_id), before);
+
+    let mut buffer = [0u8; REGION_SIZE];
+    let mut frontier = storage
+        .open_map::<u16, u16, 8>(
+            collection_id,
+            &mut buffer,
+            crate::test_map_frontier_memory(),
+        )
+        .unwrap();
+    storage.flush_map(&mut frontier).unwrap();
+    churn_until_erased(&mut storage);
+    assert_eq!(storage.wear_report().max_erase_count, 1);
+    let flushed = map_regions(&mut storage, collection_id);
+    let cold: std::vec::Vec<u32> = flushed
+        .iter()
+        .copied()
+        .filter(|region| storage.runtime().region_erase_count(*region) == 0)
+        .collect();
+    assert!(!cold.is_empty());
+
+    let report = storage
+        .wear_level(WearLevelBudget::new(usize::MAX, 1))
+        .unwrap();
+    assert!(report.complete);
+    assert!(report.relocated_regions >= cold.len());
+    let relocated = map_regions(&mut storage, collection_id);
+    for region in cold.iter() {
+        assert!(!relocated.contains(region));
+    }
+}
+
+//= spec/wear.md#static-wear-leveling
+//= type=test
+//# `RING-WEAR-006` A pass MUST NOT copy more than `max_regions` regions, and
+//# its report MUST set `complete` only when no selectable region remains.
+#[test]
+fn requirement_wear_level_respects_budget() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let collection_id = crate::test_flushed_map(&mut storage);
+    let before = map_regions(&mut storage, collection_id);
+
+    let report = storage.wear_level(WearLevelBudget::new(0, 0)).unwrap();
+    assert_eq!(report, WearLevelReport::default());
+    assert_eq!(map_regions(&mut storage, collection_id), before);
+
+    let report = storage.wear_level(WearLevelBudget::new(2, 0)).unwrap();
+    assert!(report.relocated_regions > 0);
+    assert!(report.relocated_regions <= 2);
+    assert!(!report.complete);
+
+    let report = storage
+        .wear_level(WearLevelBudget::new(usize::MAX, 0))
+        .unwrap();
+    assert!(report.complete);
+    let relocated = map_regions(&mut storage, collection_id);
+    for region in before.iter() {
+        assert!(!relocated.contains(region));
+    }
+    assert_map_contents(&mut storage, collection_id);
+}
+
+//= spec/wear.md#bad-regions
+//= type=test
+//# `RING-WEAR-007` When erasing a dirty free-space entry fails with
+//# `BadRegion`, storage MUST publish the entries erased before it, retire the
+//# region, and continue the span; a retired region MUST NOT be allocated.
+#[test]
+fn requirement_bad_region_erase_failure_retires_region() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut cell = Cell::new(&mut storage, 0u32).unwrap();
+    let bad = churn_with_bad_dirty_region(&mut storage, &mut cell, 48, true).unwrap();
+    // The mock rejects every program of the bad region, so any allocation of
+    // it would fail this churn.
+    churn_with_bad_dirty_region(&mut storage, &mut cell, 32, false);
+
+    let report = storage.wear_report();
+    assert_eq!(report.retired_regions, 1);
+    assert_eq!(report.region_count, REGION_COUNT as u32 - 1);
+    assert!(storage.is_region_retired(bad));
+    assert_eq!(*cell.get(), 31);
+}
+
+//= spec/wear.md#bad-regions
+//= type=test
+//# `RING-WEAR-008` When writing a committed region fails with `BadRegion`,
+//# `write_committed_region` MUST retire the region, write the payload to a
+//# newly allocated region, and return that region.
+#[test]
+fn requirement_bad_region_program_failure_relocates_payload() {
+    let mut flash = TestFlash::new(0xff);
+    let (cell_id, next) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let collection_id = storage.allocate_collection_id().unwrap();
+        storage
+            .append_new_collection(collection_id, CollectionType::MAP_CODE)
+            .unwrap();
+        let bad = storage.reserve_next_region().unwrap();
+        storage
+            .with_io_workspace(|flash, _| flash.fail_region(bad))
+            .unwrap();
+
+        let written = storage
+            .write_committed_region(bad, collection_id, MAP_REGION_V2_FORMAT, &[1, 2, 3])
+            .unwrap();
+        assert_ne!(written, bad);
+        let header =
+            Header::decode(&storage.backing.region_bytes(written).unwrap()[..Header::ENCODED_LEN])
+                .unwrap();
+        assert_eq!(header.collection_id, collection_id);
+        assert_eq!(header.collection_format, MAP_REGION_V2_FORMAT);
+        assert_eq!(storage.wear_report().retired_regions, 1);
+
+        // A cell with no inline room writes every value to a fresh region;
+        // after a warm-up write, the next allocation is that value region.
+        let mut cell = Cell::new_with_inline_limit(&mut storage, 0u32, 0).unwrap();
+        cell.set(&mut storage, 6).unwrap();
+        let (allocation_head, ..) = storage.free_space_cursors();
+        let next = storage.free_space_entries()[allocation_head as usize];
+        storage
+            .with_io_workspace(|flash, _| flash.fail_region(next))
+            .unwrap();
+        cell.set(&mut storage, 7).unwrap();
+        assert!(cell.region_index().is_some());
+        assert_ne!(cell.region_index(), Some(next));
+        assert_eq!(storage.wear_report().retired_regions, 2);
+        (cell.collection_id(), next)
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert!(storage.is_region_retired(next));
+    assert_eq!(*Cell::<u32>::open(cell_id, &mut storage).unwrap().get(), 7);
+    assert_eq!(storage.wear_report().retired_regions, 2);
+}
+
+//= spec/wear.md#bad-regions
+//= type=test
+//# `RING-WEAR-009` Retirement MUST survive reopen: replay MUST retire the
+//# region named by each retained `retire_region` record, and the wear table
+//# MUST carry the retired flag once the record is reclaimed.
+#[test]
+fn requirement_bad_region_retirement_survives_reopen() {
+    let mut flash = TestFlash::new(0xff);
+    let (cell_id, bad) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut cell = Cell::new(&mut storage, 0u32).unwrap();
+        let bad = churn_with_bad_dirty_region(&mut storage, &mut cell, 48, true).unwrap();
+        assert_eq!(storage.wear_report().retired_regions, 1);
+        (cell.collection_id(), bad)
+    };
+
+    let report = {
+        let mut storage = crate::test_reopen_storage(&mut flash);
+        assert_eq!(storage.wear_report().retired_regions, 1);
+        let mut cell = Cell::<u32>::open(cell_id, &mut storage).unwrap();
+        churn_with_bad_dirty_region(&mut storage, &mut cell, 32, false);
+        let persisted = persisted_wear_entries(&mut storage);
+        assert_ne!(persisted[bad as usize] & FREE_SPACE_WEAR_RETIRED_FLAG, 0);
+        storage.wear_report()
+    };
+    assert_eq!(report.retired_regions, 1);
+    assert_eq!(crate::test_reopen_storage(&mut flash).wear_report(), report);
+}
+
+/// Returns the regions whose header names `collection_id` and that are not
+/// queued free.
+fn live_regions_of(
+    storage: &mut TestStorage<'_>,
+    collection_id: CollectionId,
+) -> std::vec::Vec<u32> {
+    regions_owned_by(storage, collection_id)
+        .into_iter()
+        .filter(|region| !storage.runtime().free_space().contains_free_region(*region))
+        .collect()
+}
+
+fn read_object(
+    storage: &mut TestStorage<'_>,
+    log: &mut ObjectLog<'_, REGION_SIZE, 4, 16>,
+    handle: ObjectLogHandle,
+) -> std::vec::Vec<u8> {
+    let mut scratch = [0u8; 2048];
+    log.get(storage, handle, &mut scratch, |bytes| bytes.to_vec())
+        .unwrap()
+}
+
+//= spec/wear.md#static-wear-leveling
+//= type=test
+//# `RING-WEAR-010` For a channel, object-log, queue, ring-log, or
+//# time-series collection whose basis is a WAL snapshot with no retained
+//# updates after it, `Storage::wear_level` MUST copy a selected region and the
+//# regions that move with it into freshly allocated regions, append a snapshot
+//# naming the copies, and free every replaced region into the free-space FIFO,
+//# all within one collection transaction.
+#[test]
+fn requirement_wear_level_relocates_object_log_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let large = [9u8; 1200];
+    let (log_id, small_handle, large_handle, relocated) = {
+        let mut storage = crate::test_format_storage(&mut flash);
+        let mut memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
+        let mut log = ObjectLog::new(&mut storage, &mut memory, b"log").unwrap();
+        let mut scratch = [0u8; REGION_SIZE];
+        let small_handle = log.append(&mut storage, &[7u8; 100], &mut scratch).unwrap();
+        let large_handle = log.append(&mut storage, &large, &mut scratch).unwrap();
+        log.flush(&mut storage).unwrap();
+        let log_id = log.collection_id();
+        let before = live_regions_of(&mut storage, log_id);
+        // The large object spills into auxiliary regions.
+        assert!(before.len() >= 2);
+
+        churn_until_erased(&mut storage);
+        let report = storage
+            .wear_level(WearLevelBudget::new(usize::MAX, 1))
+            .unwrap();
+        assert!(report.complete);
+        assert!(report.retargeted_heads >= 1);
+        assert!(report.relocated_regions >= before.len());
+        assert!(!storage.runtime().transaction_open_for(log_id));
+
+        let relocated = live_regions_of(&mut storage, log_id);
+        assert_eq!(relocated.len(), before.len());
+        for region in before.iter() {
+            assert!(!relocated.contains(region));
+            assert!(storage.runtime().free_space().contains_free_region(*region));
+        }
+        (log_id, small_handle, large_handle, relocated)
+    };
+
+    let mut storage = crate::test_reopen_storage(&mut flash);
+    assert_eq!(live_regions_of(&mut storage, log_id), relocated);
+    let mut memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
+    let mut log = ObjectLog::open(log_id, &mut storage, &mut memory).unwrap();
+    assert_eq!(
+        read_object(&mut storage, &mut log, small_handle),
+        [7u8; 100]
+    );
+    assert_eq!(read_object(&mut storage, &mut log, large_handle), large);
+}
+
+//= spec/wear.md#static-wear-leveling
+//= type=test
+//# `RING-WEAR-011` After a pass moves a region of a collection, an open
+//# handle of that collection MUST replay the collection before its next
+//# operation, and an `ObjectLogHandle` returned before the move MUST still
+//# read the same object.
+#[test]
+fn requirement_wear_level_open_handles_follow_moved_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut log_memory = ObjectLogMemory::<REGION_SIZE, 4, 16>::new();
+    let mut log = ObjectLog::new(&mut storage, &mut log_memory, b"log").unwrap();
+    let mut scratch = [0u8; REGION_SIZE];
+    let handle = log.append(&mut storage, &[7u8; 300], &mut scratch).unwrap();
+    log.flush(&mut storage).unwrap();
+    let mut queue_memory = DurableQueueMemory::<REGION_SIZE>::new();
+    let mut queue = DurableQueue::<u32, REGION_SIZE>::new(&mut storage, &mut queue_memory).unwrap();
+    for value in 0..60u32 {
+        queue.push(&mut storage, &value).unwrap();
+    }
+    queue.flush(&mut storage).unwrap();
+    let log_regions = live_regions_of(&mut storage, log.collection_id());
+    let queue_regions = live_regions_of(&mut storage, queue.collection_id());
+
+    churn_until_erased(&mut storage);
+    let report = storage
+        .wear_level(WearLevelBudget::new(usize::MAX, 1))
+        .unwrap();
+    assert!(report.relocated_regions >= log_regions.len() + queue_regions.len());
+    for region in live_regions_of(&mut storage, queue.collection_id()) {
+        assert!(!queue_regions.contains(&region));
+    }
+    for region in live_regions_of(&mut storage, log.collection_id()) {
+        assert!(!log_regions.contains(&region));
+    }
+
+    assert_eq!(read_object(&mut storage, &mut log, handle), [7u8; 300]);
+    assert_eq!(log.next_handle(&mut storage, handle).unwrap(), None);
+    for value in 0..60u32 {
+        assert_eq!(queue.pop(&mut storage).unwrap(), Some(value));
+    }
+    assert_eq!(queue.pop(&mut storage).unwrap(), None);
+}
+
+//= spec/wear.md#static-wear-leveling
+//= type=test
+//# `RING-WEAR-013` When a pass moves a channel segment, it MUST move every
+//# flushed segment of that channel, and each copied segment link, command
+//# `prior` address, and the snapshot's first segment and checkpoint MUST name
+//# the copies.
+#[test]
+fn requirement_wear_level_rewrites_channel_links() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let author = MemberId::new(1);
+    let mut members_data = [MemberSequence::default(); 1];
+    let mut members = VecLikeSlice::new(&mut members_data);
+    let mut updates_data = [MemberId::default(); 1];
+    let mut updates = VecLikeSlice::new(&mut updates_data);
+    let mut pending_data: [AddCommand<u32, 64>; 64] =
+        core::array::from_fn(|_| AddCommand::default());
+    let mut pending = VecLikeSlice::new(&mut pending_data);
+    let mut channel = Channel::<_, _, _, _, 64, 1>::new(
+        &mut storage,
+        author,
+        &mut pending,
+        &mut members,
+        &mut updates,
+    )
+    .unwrap();
+    let mut prior = CommandAddress::zero();
+    for id in 0..12u8 {
+        prior = channel
+            .add_command(
+                &mut storage,
+                prior,
+                author,
+                MessageId::new(id.into()),
+                &[id; 16],
+            )
+            .unwrap();
+        if id % 4 == 3 {
+            channel.flush(&mut storage).unwrap();
+        }
+        if storage.wal_head() != storage.wal_tail() {
+            storage.reclaim_wal_head().unwrap();
+        }
+    }
+    let before = live_regions_of(&mut storage, channel.id());
+    assert!(before.len() >= 3);
+
+    churn_until_erased(&mut storage);
+    let report = storage
+        .wear_level(WearLevelBudget::new(usize::MAX, 1))
+        .unwrap();
+    assert!(report.relocated_regions >= before.len());
+    for region in live_regions_of(&mut storage, channel.id()) {
+        assert!(!before.contains(&region));
+    }
+
+    let mut previous = CommandAddress::zero();
+    let mut count = 0u8;
+    channel
+        .visit_commands(&mut storage, |address, command| {
+            assert_eq!(command.payload(), &[count; 16]);
+            assert_eq!(*command.prior(), previous);
+            previous = address.clone();
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(count, 12);
+    let last = channel.read_command(&mut storage, &previous).unwrap();
+    assert_eq!(last.payload(), &[11u8; 16]);
+}
+
+//= spec/wear.md#static-wear-leveling
+//= type=test
+//# `RING-WEAR-012` The report MUST count in `pinned_regions` every region
+//# of a live cell or plugin collection that is committed, not queued free, not
+//# retired, and at least `min_erase_gap` below the highest erase count when
+//# the pass begins.
+#[test]
+fn requirement_wear_level_reports_pinned_regions() {
+    let mut flash = TestFlash::new(0xff);
+    let mut storage = crate::test_format_storage(&mut flash);
+    let mut cell = Cell::new_with_inline_limit(&mut storage, 0u32, 0).unwrap();
+    cell.set(&mut storage, 7).unwrap();
+    let value_region = cell.region_index().unwrap();
+
+    // Every count is still zero, so nothing is cold yet.
+    let report = storage
+        .wear_level(WearLevelBudget::new(usize::MAX, 1))
+        .unwrap();
+    assert_eq!(report.pinned_regions, 0);
+
+    churn_until_erased(&mut storage);
+    let report = storage
+        .wear_level(WearLevelBudget::new(usize::MAX, 1))
+        .unwrap();
+    assert!(report.complete);
+    assert_eq!(report.pinned_regions, 1);
+    assert_eq!(cell.region_index(), Some(value_region));
+    assert_eq!(*cell.get(), 7);
+}